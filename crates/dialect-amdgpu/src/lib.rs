@@ -19,3 +19,12 @@
 //!
 //! Implements [AMD ROCm](https://www.amd.com/en/products/software/rocm.html) related operations.
 //!
+//! The `amdgpu` module is generated out of `AMDGPU.td`: the `amdgpu.lds_barrier` workgroup barrier,
+//! the `amdgpu.mfma` matrix fused multiply-add with its `MFMAPermB` broadcast enum,
+//! and `amdgpu.ext_packed_fp8`, which extends one fp8 value of a packed vector to `f32`.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/AMDGPU/IR/AMDGPU.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,80 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `amdgpu` operations and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_amdgpu::amdgpu;
+use fljuga_handahofi_mlir_ir::Context;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let mfma = print(
+        amdgpu::Dialect,
+        &["f32", "f32", "vector<32xf32>"],
+        |ctx, arguments| {
+            let i32 = ctx.integer_type(32);
+            let (m, k, blocks) = (
+                ctx.integer_attr(32, i32),
+                ctx.integer_attr(1, i32),
+                ctx.integer_attr(2, i32),
+            );
+            let blgp = amdgpu::MFMAPermB::BcastSecond32.attribute(ctx);
+            amdgpu::MFMAOp::build(
+                ctx,
+                m,
+                m,
+                k,
+                blocks,
+                arguments[0],
+                arguments[1],
+                arguments[2],
+                None,
+                None,
+                Some(blgp),
+                None,
+                None,
+                None,
+                None,
+            )
+        },
+    );
+    assert_eq!(mfma, "%0 = amdgpu.mfma %arg0 * %arg1 + %arg2 {blocks = 2 : i32, k = 1 : i32, m = 32 : i32, n = 32 : i32} blgp = bcast_second_32 : f32, f32, vector<32xf32>");
+    assert_eq!(
+        print(amdgpu::Dialect, &[], |ctx, _| amdgpu::LDSBarrierOp::build(
+            ctx
+        )),
+        "amdgpu.lds_barrier"
+    );
+}
+
+#[test]
+fn should_convert_enums() {
+    assert_eq!(
+        amdgpu::MFMAPermB::from_keyword("bcast_second_32"),
+        Some(amdgpu::MFMAPermB::BcastSecond32)
+    );
+    let mut ctx = Context::new();
+    let i32 = ctx.integer_type(32);
+    let attribute = amdgpu::MFMAPermB::Rotate16Right.attribute(&mut ctx);
+    assert_eq!(attribute, ctx.integer_attr(3, i32));
+    assert_eq!(
+        amdgpu::MFMAPermB::from_attribute(&ctx, attribute),
+        Some(amdgpu::MFMAPermB::Rotate16Right)
+    );
+}
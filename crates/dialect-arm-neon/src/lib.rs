@@ -16,3 +16,11 @@
 //!
 //! [ARM Neon](https://developer.arm.com/Architectures/Neon) SIMD dialect implements the most basic simd operations for ARMv7+ instruction sets.
 //!
+//! The `arm_neon` module is generated out of `ArmNeon.td`: the `arm_neon.intr.smull` widening multiply
+//! and the `arm_neon.intr.sdot` and `arm_neon.2d.sdot` 8-bit dot products.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/ArmNeon/ArmNeon.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,48 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `arm_neon` operations and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_arm_neon::arm_neon;
+use fljuga_handahofi_mlir_ir::parse_type;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let smull = print(
+        arm_neon::Dialect,
+        &["vector<8xi8>", "vector<8xi8>"],
+        |ctx, arguments| {
+            let result = parse_type(ctx, "vector<8xi16>").unwrap();
+            arm_neon::SMullOp::build(ctx, result, arguments[0], arguments[1])
+        },
+    );
+    assert_eq!(
+        smull,
+        "%0 = arm_neon.intr.smull %arg0, %arg1 : vector<8xi8> to vector<8xi16>"
+    );
+    let sdot = print(
+        arm_neon::Dialect,
+        &["vector<4xi32>", "vector<16xi8>", "vector<16xi8>"],
+        |ctx, arguments| arm_neon::Sdot2dOp::build(ctx, arguments[0], arguments[1], arguments[2]),
+    );
+    assert_eq!(
+        sdot,
+        "%0 = arm_neon.2d.sdot %arg0, %arg1, %arg2 : vector<16xi8>, vector<16xi8> to vector<4xi32>"
+    );
+}
@@ -17,3 +17,12 @@
 //! [ARM SME](https://community.arm.com/arm-community-blogs/b/architectures-and-processors-blog/posts/arm-scalable-matrix-extension-introduction) dialect
 //! implements floating point outer-product operations for ARMv9+ instruction sets.
 //!
+//! The `arm_sme` module is generated out of `ArmSMEOps.td`. It holds the `arm_sme.get_tile` and `arm_sme.zero`
+//! tile operations and `arm_sme.streaming_vl`, with the `TypeSize` and `CombiningKind` enums they are
+//! parameterized by.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/ArmSME/IR/ArmSMEOps.td",
+    includes = ["../mlir-codegen/include"]
+}
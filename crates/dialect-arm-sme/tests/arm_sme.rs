@@ -0,0 +1,51 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `arm_sme` operations and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_arm_sme::arm_sme;
+use fljuga_handahofi_mlir_ir::{parse_type, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let zero = print(arm_sme::Dialect, &[], |ctx, _| {
+        let result = parse_type(ctx, "vector<[4]x[4]xi32>").unwrap();
+        arm_sme::ZeroOp::build(ctx, result)
+    });
+    assert_eq!(zero, "%0 = arm_sme.zero : vector<[4]x[4]xi32>");
+    let streaming_vl = print(arm_sme::Dialect, &[], |ctx, _| {
+        let type_size = arm_sme::TypeSize::Word.attribute(ctx);
+        arm_sme::StreamingVLOp::build(ctx, type_size)
+    });
+    assert_eq!(streaming_vl, "%0 = arm_sme.streaming_vl word");
+}
+
+#[test]
+fn should_convert_enums() {
+    assert_eq!(
+        arm_sme::CombiningKind::CASES,
+        &[arm_sme::CombiningKind::Add, arm_sme::CombiningKind::Sub]
+    );
+    let mut ctx = Context::new();
+    let attribute = arm_sme::TypeSize::Double.attribute(&mut ctx);
+    assert_eq!(
+        arm_sme::TypeSize::from_attribute(&ctx, attribute),
+        Some(arm_sme::TypeSize::Double)
+    );
+}
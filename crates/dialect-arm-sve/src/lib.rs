@@ -16,3 +16,11 @@
 //!
 //! [ARM SVE](https://developer.arm.com/Architectures/Scalable%20Vector%20Extensions) SIMD dialect implements modern simd operations for ARMv9+ instruction sets.
 //!
+//! The `arm_sve` module is generated out of `ArmSVE.td`: the `sdot`/`udot` dot products
+//! and the `smmla`/`ummla` 8-bit matrix multiply-accumulates over the scalable vectors.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/ArmSVE/IR/ArmSVE.td",
+    includes = ["../mlir-codegen/include"]
+}
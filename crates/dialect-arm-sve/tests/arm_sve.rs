@@ -0,0 +1,39 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `arm_sve` operations and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_arm_sve::arm_sve;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let types = ["vector<[4]xi32>", "vector<[16]xi8>", "vector<[16]xi8>"];
+    assert_eq!(
+        print(arm_sve::Dialect, &types, |ctx, arguments| {
+            arm_sve::SdotOp::build(ctx, arguments[0], arguments[1], arguments[2])
+        }),
+        "%0 = arm_sve.sdot %arg0, %arg1, %arg2 : vector<[16]xi8> to vector<[4]xi32>"
+    );
+    assert_eq!(
+        print(arm_sve::Dialect, &types, |ctx, arguments| {
+            arm_sve::UmmlaOp::build(ctx, arguments[0], arguments[1], arguments[2])
+        }),
+        "%0 = arm_sve.ummla %arg0, %arg1, %arg2 : vector<[16]xi8> to vector<[4]xi32>"
+    );
+}
@@ -16,3 +16,12 @@
 //!
 //! Implements emit C mlir dialect support, allows direct C/C++ translation.
 //!
+//! The `emitc` module is generated out of `EmitC.td`. It holds the arithmetic and `emitc.cmp` operations,
+//! `emitc.verbatim` for the raw C/C++ lines, the `!emitc.opaque` and `!emitc.ptr` types
+//! and the `#emitc.opaque` attribute, all spelled as C/C++ by the translation.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/EmitC/IR/EmitC.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,61 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `emitc` operations, attributes, types and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_emit_c::emitc;
+use fljuga_handahofi_mlir_ir::{AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let add = print(emitc::Dialect, &["i32", "i32"], |ctx, arguments| {
+        let i32 = ctx.integer_type(32);
+        emitc::AddOp::build(ctx, i32, arguments[0], arguments[1])
+    });
+    assert_eq!(add, "%0 = emitc.add %arg0, %arg1 : i32, i32 -> i32");
+    let cmp = print(emitc::Dialect, &["i32", "i32"], |ctx, arguments| {
+        let i1 = ctx.integer_type(1);
+        let predicate = emitc::CmpPredicate::ThreeWay.attribute(ctx);
+        emitc::CmpOp::build(ctx, i1, predicate, arguments[0], arguments[1])
+    });
+    assert_eq!(
+        cmp,
+        "%0 = emitc.cmp three_way, %arg0, %arg1 : i32, i32 -> i1"
+    );
+    let verbatim = print(emitc::Dialect, &[], |ctx, _| {
+        let value = ctx.string_attr("#pragma once");
+        emitc::VerbatimOp::build(ctx, value)
+    });
+    assert_eq!(verbatim, "emitc.verbatim \"#pragma once\"");
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    let pointer = emitc::PointerType::from_text(&mut ctx, "!emitc.ptr<i32>").unwrap();
+    assert_eq!(pointer.to_text(&ctx), "!emitc.ptr<i32>");
+    assert_eq!(
+        emitc::OpaqueType::new("int32_t").to_text(&ctx),
+        "!emitc.opaque<\"int32_t\">"
+    );
+    assert_eq!(
+        emitc::OpaqueAttr::new("NULL").to_text(&ctx),
+        "#emitc.opaque<\"NULL\">"
+    );
+}
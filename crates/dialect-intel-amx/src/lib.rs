@@ -17,3 +17,11 @@
 //! Intel Advanced Matrix Extensions.
 //! TLDR; fancy XEON gold acceleration, a bit of a "special snowflake", but meh.
 //!
+//! The `amx` module is generated out of `AMX.td`: the `amx.tile_zero` and `amx.tile_mulf` operations
+//! over the 2-D tile vectors.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/AMX/AMX.td",
+    includes = ["../mlir-codegen/include"]
+}
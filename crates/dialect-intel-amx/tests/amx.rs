@@ -0,0 +1,46 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `amx` operations and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_intel_amx::amx;
+use fljuga_handahofi_mlir_ir::parse_type;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let tile_zero = print(amx::Dialect, &[], |ctx, _| {
+        let result = parse_type(ctx, "vector<16x16xbf16>").unwrap();
+        amx::TileZeroOp::build(ctx, result)
+    });
+    assert_eq!(tile_zero, "%0 = amx.tile_zero : vector<16x16xbf16>");
+    let types = [
+        "vector<16x32xbf16>",
+        "vector<16x32xbf16>",
+        "vector<16x16xf32>",
+    ];
+    assert_eq!(
+        print(amx::Dialect, &types, |ctx, arguments| amx::TileMulFOp::build(
+            ctx,
+            arguments[0],
+            arguments[1],
+            arguments[2]
+        )),
+        "%0 = amx.tile_mulf %arg0, %arg1, %arg2 : vector<16x32xbf16>, vector<16x32xbf16>, vector<16x16xf32>"
+    );
+}
@@ -18,3 +18,12 @@
 //!
 //! NOTE: *not sure* if even worth supporting, but meh.
 //!
+//! The `xegpu` module is generated out of `XeGPUOps.td`. It holds the named barrier operations and their
+//! `!xegpu.nbarrier` type, the `#xegpu.block_tdesc_attr` tensor descriptor attribute
+//! and the `CachePolicy` enum.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/XeGPU/IR/XeGPUOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,61 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `xegpu` operations, attributes, types and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_intel_xe::xegpu;
+use fljuga_handahofi_mlir_ir::{parse_type, AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    assert_eq!(
+        print(xegpu::Dialect, &["i8", "i8"], |ctx, arguments| {
+            xegpu::InitNbarrierOp::build(ctx, arguments[0], arguments[1])
+        }),
+        "%0 = xegpu.init_nbarrier %arg0, %arg1 : i8, i8 -> !xegpu.nbarrier"
+    );
+    assert_eq!(
+        print(xegpu::Dialect, &["!xegpu.nbarrier"], |ctx, arguments| {
+            xegpu::NbarrierWaitOp::build(ctx, arguments[0])
+        }),
+        "xegpu.nbarrier_wait %arg0 : !xegpu.nbarrier"
+    );
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    let desc = xegpu::BlockTensorDescAttr::from_text(
+        &mut ctx,
+        "#xegpu.block_tdesc_attr<array_length = 8>",
+    )
+    .unwrap();
+    assert_eq!(
+        desc.to_text(&ctx),
+        "#xegpu.block_tdesc_attr<array_length = 8>"
+    );
+    assert_eq!(
+        xegpu::NbarrierType.get(&mut ctx),
+        parse_type(&mut ctx, "!xegpu.nbarrier").unwrap()
+    );
+    assert_eq!(
+        xegpu::CachePolicy::from_keyword("write_back"),
+        Some(xegpu::CachePolicy::WriteBack)
+    );
+}
@@ -16,3 +16,12 @@
 //!
 //! Implements MPI related operations.
 //!
+//! The `mpi` module is generated out of `MPIOps.td`: `mpi.init`, `mpi.comm_rank` and `mpi.finalize`,
+//! which may return a `!mpi.retval`, and `mpi.retval_check`,
+//! which compares that value with an `MPI_ErrorClassEnum` class.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/MPI/IR/MPIOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,61 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `mpi` operations, types and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_mpi::mpi;
+use fljuga_handahofi_mlir_ir::{parse_type, AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let init = print(mpi::Dialect, &[], |ctx, _| {
+        let retval = mpi::RetvalType.get(ctx);
+        mpi::InitOp::build(ctx, Some(retval))
+    });
+    assert_eq!(init, "%0 = mpi.init : !mpi.retval");
+    assert_eq!(
+        print(mpi::Dialect, &[], |ctx, _| mpi::FinalizeOp::build(
+            ctx, None
+        )),
+        "mpi.finalize"
+    );
+    let check = print(mpi::Dialect, &["!mpi.retval"], |ctx, arguments| {
+        let error_class = mpi::MPI_ErrorClassEnum::MpiSuccess.attribute(ctx);
+        mpi::RetvalCheckOp::build(ctx, arguments[0], error_class)
+    });
+    assert_eq!(check, "%0 = mpi.retval_check %arg0 = MPI_SUCCESS : i1");
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    assert_eq!(mpi::RetvalType.to_text(&ctx), "!mpi.retval");
+    assert_eq!(
+        mpi::RetvalType.get(&mut ctx),
+        parse_type(&mut ctx, "!mpi.retval").unwrap()
+    );
+    assert_eq!(
+        mpi::MPI_ErrorClassEnum::MpiSuccess.to_string(),
+        "MPI_SUCCESS"
+    );
+    assert_eq!(
+        mpi::MPI_ErrorClassEnum::from_keyword("MPI_SUCCESS"),
+        Some(mpi::MPI_ErrorClassEnum::MpiSuccess)
+    );
+}
@@ -20,3 +20,13 @@
 //! This crate left ofr future optimization implementations.
 //!
 //! **NOTE:** translates from nvgpu dialect.
+//!
+//! The `nvgpu` module is generated out of `NVGPU.td`: the `nvgpu.device_async_create_group` and
+//! `nvgpu.device_async_wait` copies synchronization over the `!nvgpu.device.async.token` type,
+//! and the `TensorMapSwizzleKind` enum of the TMA descriptors.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/NVGPU/IR/NVGPU.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,57 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `nvgpu` operations, types and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_nvgpu::nvgpu;
+use fljuga_handahofi_mlir_ir::{AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let token = "!nvgpu.device.async.token";
+    assert_eq!(
+        print(nvgpu::Dialect, &[token], |ctx, arguments| {
+            nvgpu::DeviceAsyncCreateGroupOp::build(ctx, arguments.to_vec())
+        }),
+        "%0 = nvgpu.device_async_create_group %arg0"
+    );
+    let wait = print(nvgpu::Dialect, &[token], |ctx, arguments| {
+        let i32 = ctx.integer_type(32);
+        let num_groups = ctx.integer_attr(1, i32);
+        nvgpu::DeviceAsyncWaitOp::build(ctx, arguments[0], Some(num_groups))
+    });
+    assert_eq!(wait, "nvgpu.device_async_wait %arg0 {numGroups = 1 : i32}");
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let ctx = Context::new();
+    assert_eq!(
+        nvgpu::DeviceAsyncTokenType.to_text(&ctx),
+        "!nvgpu.device.async.token"
+    );
+    assert_eq!(
+        nvgpu::TensorMapSwizzleKind::Swizzle128b.to_string(),
+        "swizzle_128b"
+    );
+    assert_eq!(
+        nvgpu::TensorMapSwizzleKind::from_value(1),
+        Some(nvgpu::TensorMapSwizzleKind::Swizzle32b)
+    );
+}
@@ -17,3 +17,14 @@
 //! Implements nvgpu mlir high level dialect support.
 //! nvgpu abstracts nvvm dialect with Device-specific PTX Asm instructions.
 //! **NOTE:** translation is not perfect, **fljúga handahófi** may provide some hacks and low-level optimizations in the future, or directly contribute to mlir.
+//!
+//! The `nvvm` module is generated out of `NVVMOps.td`.
+//! It holds the `nvvm.read.ptx.sreg.{tid,ctaid,ntid}.x` special registers, `nvvm.barrier0`,
+//! and `nvvm.shfl.sync` with its `ShflKind` enum.
+//! The `#nvvm.target` attribute selects the PTX chip and the optimization level.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/LLVMIR/NVVMOps.td",
+    includes = ["../mlir-codegen/include"]
+}
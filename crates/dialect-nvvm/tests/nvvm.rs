@@ -0,0 +1,69 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `nvvm` operations, attributes and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_nvvm::nvvm;
+use fljuga_handahofi_mlir_ir::{parse_type, AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let thread_id = print(nvvm::Dialect, &[], |ctx, _| {
+        let i32 = ctx.integer_type(32);
+        nvvm::ThreadIdXOp::build(ctx, i32)
+    });
+    assert_eq!(thread_id, "%0 = nvvm.read.ptx.sreg.tid.x : i32");
+    assert_eq!(
+        print(nvvm::Dialect, &[], |ctx, _| nvvm::Barrier0Op::build(ctx)),
+        "nvvm.barrier0"
+    );
+    let shfl = print(
+        nvvm::Dialect,
+        &["i32", "f32", "i32", "i32"],
+        |ctx, arguments| {
+            let f32 = parse_type(ctx, "f32").unwrap();
+            let kind = nvvm::ShflKind::Bfly.attribute(ctx);
+            nvvm::ShflOp::build(
+                ctx,
+                f32,
+                arguments[0],
+                arguments[1],
+                arguments[2],
+                arguments[3],
+                kind,
+                None,
+            )
+        },
+    );
+    assert_eq!(
+        shfl,
+        "%0 = nvvm.shfl.sync bfly %arg0, %arg1, %arg2, %arg3 : f32 -> f32"
+    );
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    let target =
+        nvvm::NVVMTargetAttr::from_text(&mut ctx, "#nvvm.target<O = 3, chip = \"sm_90\">").unwrap();
+    assert_eq!(
+        target.to_text(&ctx),
+        "#nvvm.target<O = 3, chip = \"sm_90\">"
+    );
+}
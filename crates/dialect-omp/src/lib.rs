@@ -16,3 +16,11 @@
 //!
 //! Implements OpenMP related operations.
 //!
+//! The `omp` module is generated out of `OpenMPOps.td`: the `omp.barrier`, `omp.taskwait`, `omp.flush`
+//! and `omp.terminator` operations, the `ClauseProcBindKind` enum and the `#omp.version` module attribute.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/OpenMP/OpenMPOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,54 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `omp` operations, attributes and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_omp::omp;
+use fljuga_handahofi_mlir_ir::{AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    assert_eq!(
+        print(omp::Dialect, &[], |ctx, _| omp::BarrierOp::build(ctx)),
+        "omp.barrier"
+    );
+    assert_eq!(
+        print(omp::Dialect, &["memref<i32>"], |ctx, arguments| {
+            omp::FlushOp::build(ctx, arguments.to_vec())
+        }),
+        "omp.flush(%arg0 : memref<i32>)"
+    );
+    assert_eq!(
+        print(omp::Dialect, &[], |ctx, _| omp::FlushOp::build(ctx, vec![])),
+        "omp.flush"
+    );
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let ctx = Context::new();
+    assert_eq!(
+        omp::VersionAttr::new(51).to_text(&ctx),
+        "#omp.version<version = 51>"
+    );
+    assert_eq!(
+        omp::ClauseProcBindKind::from_keyword("spread"),
+        Some(omp::ClauseProcBindKind::Spread)
+    );
+}
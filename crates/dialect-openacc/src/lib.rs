@@ -18,3 +18,11 @@
 //!
 //! [OpenACC](https://www.openacc.org/about) is a set of C/Fortran pragmas for CPU/GPU accelerated HPC.
 //!
+//! The `acc` module is generated out of `OpenACCOps.td`. It holds the `acc.init` and `acc.shutdown` runtime
+//! operations, `acc.get_lowerbound` over the `!acc.data_bounds_ty` type, and the `DataClause` enum.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/OpenACC/OpenACCOps.td",
+    includes = ["../mlir-codegen/include"]
+}
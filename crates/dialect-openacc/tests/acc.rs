@@ -0,0 +1,64 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `acc` operations, types and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_openacc::acc;
+use fljuga_handahofi_mlir_ir::{parse_type, AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    assert_eq!(
+        print(acc::Dialect, &["!acc.data_bounds_ty"], |ctx, arguments| {
+            acc::GetLowerboundOp::build(ctx, arguments[0])
+        }),
+        "%0 = acc.get_lowerbound %arg0 : (!acc.data_bounds_ty) -> index"
+    );
+    assert_eq!(
+        print(acc::Dialect, &["i1"], |ctx, arguments| acc::InitOp::build(
+            ctx,
+            Some(arguments[0])
+        )),
+        "acc.init if(%arg0)"
+    );
+    assert_eq!(
+        print(acc::Dialect, &[], |ctx, _| acc::ShutdownOp::build(
+            ctx, None
+        )),
+        "acc.shutdown"
+    );
+}
+
+#[test]
+fn should_convert_enums() {
+    let mut ctx = Context::new();
+    let i64 = ctx.integer_type(64);
+    let attribute = acc::DataClause::AccCopyout.attribute(&mut ctx);
+    assert_eq!(attribute, ctx.integer_attr(4, i64));
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    assert_eq!(acc::DataBoundsType.to_text(&ctx), "!acc.data_bounds_ty");
+    assert_eq!(
+        acc::DataBoundsType.get(&mut ctx),
+        parse_type(&mut ctx, "!acc.data_bounds_ty").unwrap()
+    );
+}
@@ -17,3 +17,11 @@
 //! [SiFive VCIX](https://www.sifive.com/document-file/sifive-vector-coprocessor-interface-vcix-software)
 //! SIMD dialect implements the most basic simd operations for RISC-V processors.
 //!
+//! The `vcix` module is generated out of `VCIXOps.td`: the `vcix.binary` and `vcix.binary.ro`
+//! coprocessor instructions, with and without a vector result.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/LLVMIR/VCIXOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,61 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `vcix` operations and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_riscv_vcix::vcix;
+use fljuga_handahofi_mlir_ir::parse_type;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let binary = print(
+        vcix::Dialect,
+        &["vector<[4]xf32>", "f32", "i64"],
+        |ctx, arguments| {
+            let result = parse_type(ctx, "vector<[4]xf32>").unwrap();
+            let i64 = ctx.integer_type(64);
+            let opcode = ctx.integer_attr(3, i64);
+            vcix::BinaryOp::build(
+                ctx,
+                result,
+                opcode,
+                arguments[0],
+                arguments[1],
+                Some(arguments[2]),
+            )
+        },
+    );
+    assert_eq!(
+        binary,
+        "%0 = vcix.binary 3, %arg0, %arg1, %arg2 : (vector<[4]xf32>, f32, i64) -> vector<[4]xf32>"
+    );
+    let binary_ro = print(
+        vcix::Dialect,
+        &["vector<[4]xf32>", "f32"],
+        |ctx, arguments| {
+            let (i32, i64) = (ctx.integer_type(32), ctx.integer_type(64));
+            let (opcode, rd) = (ctx.integer_attr(3, i64), ctx.integer_attr(30, i32));
+            vcix::BinaryROOp::build(ctx, opcode, arguments[0], arguments[1], rd, None)
+        },
+    );
+    assert_eq!(
+        binary_ro,
+        "vcix.binary.ro 3, %arg0, %arg1, 30 : (vector<[4]xf32>, f32)"
+    );
+}
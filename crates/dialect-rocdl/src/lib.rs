@@ -16,3 +16,12 @@
 //!
 //! Implements low-level rocdl dialect.
 //!
+//! The `rocdl` module is generated out of `ROCDLOps.td`. It holds the `rocdl.workitem.id.x` and
+//! `rocdl.workgroup.id.x` registers and the `rocdl.barrier` and `rocdl.s.barrier` operations.
+//! The `#rocdl.target` attribute selects the GFX chip.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/LLVMIR/ROCDLOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,44 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `rocdl` operations and attributes, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_rocdl::rocdl;
+use fljuga_handahofi_mlir_ir::{AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let thread_id = print(rocdl::Dialect, &[], |ctx, _| {
+        let i32 = ctx.integer_type(32);
+        rocdl::ThreadIdXOp::build(ctx, i32)
+    });
+    assert_eq!(thread_id, "%0 = rocdl.workitem.id.x : i32");
+    assert_eq!(
+        print(rocdl::Dialect, &[], |ctx, _| rocdl::SBarrierOp::build(ctx)),
+        "rocdl.s.barrier"
+    );
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    let target =
+        rocdl::ROCDLTargetAttr::from_text(&mut ctx, "#rocdl.target<chip = \"gfx90a\">").unwrap();
+    assert_eq!(target.to_text(&ctx), "#rocdl.target<chip = \"gfx90a\">");
+}
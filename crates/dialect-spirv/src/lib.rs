@@ -18,3 +18,11 @@
 //!
 //! Implements low-level spir-v dialect.
 //!
+//! The `spirv` module is generated out of `SPIRVOps.td`: the integer arithmetic and the return operations,
+//! the `StorageClass` enum and the `#spirv.entry_point_abi` workgroup size attribute.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/SPIRV/IR/SPIRVOps.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,60 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `spirv` operations, attributes and enums, and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_spirv::spirv;
+use fljuga_handahofi_mlir_ir::{AttrOrTypeDef, Context};
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    assert_eq!(
+        print(spirv::Dialect, &["i32", "i32"], |ctx, arguments| {
+            spirv::IAddOp::build(ctx, arguments[0], arguments[1])
+        }),
+        "%0 = spirv.IAdd %arg0, %arg1 : i32"
+    );
+    assert_eq!(
+        print(spirv::Dialect, &["i32"], |ctx, arguments| {
+            spirv::ReturnValueOp::build(ctx, arguments[0])
+        }),
+        "spirv.ReturnValue %arg0 : i32"
+    );
+}
+
+#[test]
+fn should_round_trip_definitions() {
+    let mut ctx = Context::new();
+    let abi = spirv::EntryPointABIAttr::from_text(
+        &mut ctx,
+        "#spirv.entry_point_abi<workgroup_size = array<i32: 64, 1, 1>>",
+    )
+    .unwrap();
+    assert_eq!(
+        abi.to_text(&ctx),
+        "#spirv.entry_point_abi<workgroup_size = array<i32: 64, 1, 1>>"
+    );
+    assert_eq!(
+        spirv::StorageClass::StorageBuffer.to_string(),
+        "StorageBuffer"
+    );
+    let i32 = ctx.integer_type(32);
+    let attribute = spirv::StorageClass::Workgroup.attribute(&mut ctx);
+    assert_eq!(attribute, ctx.integer_attr(4, i32));
+}
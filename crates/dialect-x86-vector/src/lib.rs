@@ -18,3 +18,11 @@
 //!
 //! Implements low-level x86 vector emission.
 //!
+//! The `x86vector` module is generated out of `X86Vector.td`: the AVX512 `x86vector.avx512.mask.rndscale`,
+//! and the AVX `x86vector.avx.rsqrt` and `x86vector.avx.dot` operations.
+//!
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../mlir-codegen/include/mlir/Dialect/X86Vector/X86Vector.td",
+    includes = ["../mlir-codegen/include"]
+}
@@ -0,0 +1,39 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builds the generated `x86vector` operations and prints their custom forms.
+//!
+
+use fljuga_handahofi_dialect_x86_vector::x86vector;
+use fljuga_handahofi_test_support::print;
+
+#[test]
+fn should_print_built_operations() {
+    let types = ["vector<8xf32>", "vector<8xf32>"];
+    assert_eq!(
+        print(x86vector::Dialect, &types, |ctx, arguments| {
+            x86vector::RsqrtOp::build(ctx, arguments[0])
+        }),
+        "%0 = x86vector.avx.rsqrt %arg0 : vector<8xf32>"
+    );
+    assert_eq!(
+        print(x86vector::Dialect, &types, |ctx, arguments| {
+            x86vector::DotOp::build(ctx, arguments[0], arguments[1])
+        }),
+        "%0 = x86vector.avx.dot %arg0, %arg1 : vector<8xf32>"
+    );
+}
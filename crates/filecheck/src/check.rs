@@ -0,0 +1,523 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Check directives of the check files, matched against the input like the LLVM FileCheck does:
+//! the `LABEL`s split the input into the blocks first, then the directives of every block are matched
+//! in order, the `NOT`s in between the matches of the surrounding ones.
+//!
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::pattern::{Pattern, PatternMatch};
+use crate::report::{Annotation, Mismatch};
+use crate::{CheckFailure, FileCheckError};
+
+/// Kind of the directive, by its suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckKind {
+    /// `CHECK:` matches after the previous match.
+    Plain,
+    /// `CHECK-NEXT:` matches on the line after the previous match.
+    Next,
+    /// `CHECK-SAME:` matches on the line of the previous match.
+    Same,
+    /// `CHECK-EMPTY:` matches the empty line after the previous match.
+    Empty,
+    /// `CHECK-DAG:` matches in any order within the consecutive ones.
+    Dag,
+    /// `CHECK-NOT:` doesn't match between the surrounding matches.
+    Not,
+    /// `CHECK-LABEL:` matches uniquely, splitting the input into the blocks checked independently.
+    Label,
+}
+
+impl CheckKind {
+    const ALL: [CheckKind; 7] = [
+        CheckKind::Plain,
+        CheckKind::Next,
+        CheckKind::Same,
+        CheckKind::Empty,
+        CheckKind::Dag,
+        CheckKind::Not,
+        CheckKind::Label,
+    ];
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            CheckKind::Plain => "",
+            CheckKind::Next => "-NEXT",
+            CheckKind::Same => "-SAME",
+            CheckKind::Empty => "-EMPTY",
+            CheckKind::Dag => "-DAG",
+            CheckKind::Not => "-NOT",
+            CheckKind::Label => "-LABEL",
+        }
+    }
+}
+
+/// Directive of the check file.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub kind: CheckKind,
+    pub prefix: String,
+    /// Pattern text after the colon, trimmed.
+    pub text: String,
+    /// 1-based line and column of the prefix.
+    pub line: usize,
+    pub column: usize,
+    pattern: Pattern,
+}
+
+impl Check {
+    /// `CHECK-NEXT` like directive name.
+    pub fn directive(&self) -> String {
+        format!("{}{}", self.prefix, self.kind.suffix())
+    }
+}
+
+fn is_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Directive of the line, the first prefix occurrence at the word start followed by a known suffix.
+fn directive<'l>(line: &'l str, prefixes: &[&str]) -> Option<(usize, usize, CheckKind, &'l str)> {
+    let mut directives = vec![];
+    for (prefix_index, prefix) in prefixes.iter().enumerate() {
+        for (start, _) in line.match_indices(prefix) {
+            let at_word_start = line[..start]
+                .chars()
+                .next_back()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-')));
+            if !at_word_start {
+                continue;
+            }
+            let rest = &line[start + prefix.len()..];
+            let found = CheckKind::ALL.into_iter().find_map(|kind| {
+                rest.strip_prefix(kind.suffix())?
+                    .strip_prefix(':')
+                    .map(|text| (kind, text))
+            });
+            if let Some((kind, text)) = found {
+                directives.push((start, prefix_index, kind, text));
+                break;
+            }
+        }
+    }
+    directives.into_iter().min_by_key(|(start, ..)| *start)
+}
+
+/// Directives of the check file, in their order.
+#[derive(Debug, Clone)]
+pub struct CheckFile {
+    name: String,
+    source: String,
+    checks: Vec<Check>,
+}
+
+impl CheckFile {
+    /// Parses the directives of the prefixes, like `CHECK`, out of the check file of the name.
+    pub fn parse(name: impl Into<String>, source: &str, prefixes: &[&str]) -> Result<Self, FileCheckError> {
+        if let Some(prefix) = prefixes.iter().find(|prefix| !is_prefix(prefix)) {
+            return Err(FileCheckError::InvalidPrefix(prefix.to_string()));
+        }
+        let mut checks: Vec<Check> = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let Some((start, prefix, kind, text)) = directive(line, prefixes) else {
+                continue;
+            };
+            let error = |message: String| FileCheckError::Directive {
+                line: index + 1,
+                column: start + 1,
+                message,
+            };
+            let name = format!("{}{}", prefixes[prefix], kind.suffix());
+            let text = text.trim();
+            let pattern = Pattern::parse(text).map_err(|(_, message)| error(message))?;
+            match kind {
+                CheckKind::Next | CheckKind::Same | CheckKind::Empty if checks.is_empty() => {
+                    return Err(error(format!(
+                        "found '{name}' without previous '{}: line",
+                        prefixes[prefix]
+                    )));
+                }
+                CheckKind::Empty if !pattern.is_empty() => {
+                    return Err(error(format!(
+                        "found non-empty check string for empty check with prefix '{name}:'"
+                    )));
+                }
+                CheckKind::Empty => {}
+                _ if pattern.is_empty() => {
+                    return Err(error(format!("found empty check string with prefix '{name}:'")));
+                }
+                CheckKind::Label if pattern.has_variables() => {
+                    return Err(error(format!("found '{name}:' with variable definition or use")));
+                }
+                _ => {}
+            }
+            checks.push(Check {
+                kind,
+                prefix: prefixes[prefix].to_string(),
+                text: text.to_string(),
+                line: index + 1,
+                column: start + 1,
+                pattern,
+            });
+        }
+        if checks.is_empty() {
+            return Err(FileCheckError::NoChecks(
+                prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+            ));
+        }
+        Ok(Self {
+            name: name.into(),
+            source: source.to_string(),
+            checks,
+        })
+    }
+
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    /// Matches the directives against the input of the name, the failure reports the mismatches.
+    pub fn check(&self, input_name: &str, input: &str) -> Result<(), Box<CheckFailure>> {
+        let mut matcher = Matcher {
+            checks: &self.checks,
+            input,
+            variables: HashMap::new(),
+            annotations: vec![],
+            mismatches: vec![],
+        };
+        matcher.run();
+        match matcher.mismatches.is_empty() {
+            true => Ok(()),
+            false => Err(Box::new(CheckFailure {
+                check_name: self.name.clone(),
+                check_source: self.source.clone(),
+                checks: self.checks.clone(),
+                input_name: input_name.to_string(),
+                input: input.to_string(),
+                annotations: matcher.annotations,
+                mismatches: matcher.mismatches,
+            })),
+        }
+    }
+}
+
+struct Matcher<'a> {
+    checks: &'a [Check],
+    input: &'a str,
+    variables: HashMap<String, String>,
+    annotations: Vec<Annotation>,
+    mismatches: Vec<Mismatch>,
+}
+
+impl Matcher<'_> {
+    fn mismatch(&mut self, check: usize, message: impl Into<String>, scanned: usize, found: Option<Range<usize>>) {
+        self.mismatches.push(Mismatch {
+            check,
+            message: message.into(),
+            scanned,
+            found,
+        });
+    }
+
+    /// Match of the directive within the range, the undefined variables are reported as the mismatches.
+    fn find(&mut self, check: usize, range: Range<usize>) -> Result<Option<PatternMatch>, ()> {
+        let start = range.start;
+        match self.checks[check].pattern.find(self.input, range, &self.variables) {
+            Ok(found) => Ok(found),
+            Err(name) => {
+                self.mismatch(check, format!("uses undefined variable '{name}'"), start, None);
+                Err(())
+            }
+        }
+    }
+
+    /// Records the match, defining the variables of the directive.
+    fn matched(&mut self, check: usize, found: PatternMatch) {
+        self.variables.extend(found.definitions);
+        self.annotations.push(Annotation {
+            check,
+            range: found.range,
+        });
+    }
+
+    fn run(&mut self) {
+        let labels: Vec<usize> = (0..self.checks.len())
+            .filter(|index| self.checks[*index].kind == CheckKind::Label)
+            .collect();
+        let mut blocks = vec![];
+        let (mut checks_start, mut input_start) = (0, 0);
+        for label in labels {
+            let Ok(found) = self.find(label, input_start..self.input.len()) else {
+                return;
+            };
+            let Some(found) = found else {
+                self.mismatch(label, "expected string not found in input", input_start, None);
+                return;
+            };
+            blocks.push((checks_start..label, input_start..found.range.start));
+            (checks_start, input_start) = (label + 1, found.range.end);
+            self.matched(label, found);
+        }
+        blocks.push((checks_start..self.checks.len(), input_start..self.input.len()));
+        for (checks, input) in blocks {
+            self.run_block(checks, input);
+        }
+    }
+
+    /// Matches the directives of a label block, stopping at the first mismatch.
+    fn run_block(&mut self, checks: Range<usize>, input: Range<usize>) {
+        let mut position = input.start;
+        let mut nots = vec![];
+        let mut index = checks.start;
+        while index < checks.end {
+            let check = &self.checks[index];
+            match check.kind {
+                CheckKind::Not => {
+                    nots.push(index);
+                    index += 1;
+                }
+                CheckKind::Dag => {
+                    let end = (index..checks.end)
+                        .find(|index| self.checks[*index].kind != CheckKind::Dag)
+                        .unwrap_or(checks.end);
+                    let Some(found) = self.match_dags(index..end, position..input.end) else {
+                        return;
+                    };
+                    let first = found.iter().map(|found| found.start).min().unwrap_or(position);
+                    if !self.check_nots(&nots, position..first) {
+                        return;
+                    }
+                    position = found.iter().map(|found| found.end).max().unwrap_or(position);
+                    nots.clear();
+                    index = end;
+                }
+                kind => {
+                    let found = match kind {
+                        CheckKind::Empty => self.find_empty_line(position, input.end).map(|range| PatternMatch {
+                            range,
+                            definitions: vec![],
+                        }),
+                        _ => match self.find(index, position..input.end) {
+                            Ok(found) => found,
+                            Err(()) => return,
+                        },
+                    };
+                    let Some(found) = found else {
+                        self.mismatch(index, "expected string not found in input", position, None);
+                        return;
+                    };
+                    let lines = self.input[position..found.range.start].matches('\n').count();
+                    let misplaced = match kind {
+                        CheckKind::Next | CheckKind::Empty if lines == 0 => {
+                            Some("is on the same line as previous match")
+                        }
+                        CheckKind::Next | CheckKind::Empty if lines > 1 => {
+                            Some("is not on the line after the previous match")
+                        }
+                        CheckKind::Same if lines > 0 => Some("is not on the same line as the previous match"),
+                        _ => None,
+                    };
+                    if let Some(message) = misplaced {
+                        self.mismatch(index, message, position, Some(found.range));
+                        return;
+                    }
+                    if !self.check_nots(&nots, position..found.range.start) {
+                        return;
+                    }
+                    nots.clear();
+                    position = found.range.end;
+                    self.matched(index, found);
+                    index += 1;
+                }
+            }
+        }
+        self.check_nots(&nots, position..input.end);
+    }
+
+    /// Matches the consecutive `DAG`s without the overlaps, in any order.
+    fn match_dags(&mut self, dags: Range<usize>, input: Range<usize>) -> Option<Vec<Range<usize>>> {
+        let mut matches: Vec<Range<usize>> = vec![];
+        for dag in dags {
+            let mut start = input.start;
+            loop {
+                let found = match start <= input.end {
+                    true => self.find(dag, start..input.end).ok()?,
+                    false => None,
+                };
+                let Some(found) = found else {
+                    self.mismatch(dag, "expected string not found in input", input.start, None);
+                    return None;
+                };
+                let overlapping = matches
+                    .iter()
+                    .find(|other| found.range.start < other.end && other.start < found.range.end);
+                match overlapping {
+                    Some(other) => start = other.end.max(found.range.start + 1),
+                    None => {
+                        matches.push(found.range.clone());
+                        self.matched(dag, found);
+                        break;
+                    }
+                }
+            }
+        }
+        Some(matches)
+    }
+
+    /// Whether none of the `NOT`s matches within the range.
+    fn check_nots(&mut self, nots: &[usize], range: Range<usize>) -> bool {
+        for not in nots {
+            match self.find(*not, range.clone()) {
+                Ok(None) => {}
+                Ok(Some(found)) => {
+                    self.mismatch(*not, "excluded string found in input", range.start, Some(found.range));
+                    return false;
+                }
+                Err(()) => return false,
+            }
+        }
+        true
+    }
+
+    /// Empty line after the one of the position.
+    fn find_empty_line(&self, position: usize, end: usize) -> Option<Range<usize>> {
+        let start = position + self.input[position..end].find('\n')? + 1;
+        match self.input[start..end].starts_with('\n') {
+            true => Some(start..start),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+func.func @add(%arg0: i32, %arg1: i32) -> i32 {
+  %0 = arith.addi %arg0, %arg1 : i32
+  return %0 : i32
+}
+
+func.func @consts() -> (i32, i64) {
+  %c1_i64 = arith.constant 1 : i64
+  %c0_i32 = arith.constant 0 : i32
+  return %c0_i32, %c1_i64 : i32, i64
+}
+";
+
+    fn check(checks: &str) -> Result<(), Box<CheckFailure>> {
+        CheckFile::parse("test.mlir", checks, &["CHECK"])
+            .unwrap()
+            .check("<stdin>", INPUT)
+    }
+
+    fn errors(checks: &str) -> Vec<String> {
+        check(checks).unwrap_err().errors()
+    }
+
+    #[test]
+    fn should_match_the_directives() {
+        let checks = "\
+// CHECK-LABEL: func.func @add
+// CHECK-SAME:    (%[[A:.*]]: i32, %[[B:.*]]: i32)
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %[[A]], %[[B]] : i32
+// CHECK-NOT:     arith.constant
+// CHECK:         return %[[SUM]]
+// CHECK-NEXT:  }
+// CHECK-EMPTY:
+// CHECK-LABEL: func.func @consts
+// CHECK-DAG:     %[[ZERO:.*]] = arith.constant 0 : i32
+// CHECK-DAG:     %[[ONE:.*]] = arith.constant 1 : i64
+// CHECK:         return %[[ZERO]], %[[ONE]] : {{i[0-9]+}},   i64
+// CHECK-NOT:     arith
+";
+        check(checks).unwrap();
+    }
+
+    #[test]
+    fn should_report_the_mismatches() {
+        assert_eq!(
+            errors("// CHECK: func.func @add\n// CHECK-NEXT: return"),
+            ["test.mlir:2:4: error: CHECK-NEXT: is not on the line after the previous match"]
+        );
+        assert_eq!(
+            errors("// CHECK: arith.addi\n// CHECK-SAME: arith.constant"),
+            ["test.mlir:2:4: error: CHECK-SAME: is not on the same line as the previous match"]
+        );
+        assert_eq!(
+            errors("// CHECK: @add\n// CHECK-NOT: arith.constant\n// CHECK: @consts\n// CHECK: arith.muli"),
+            ["test.mlir:4:4: error: CHECK: expected string not found in input"]
+        );
+        assert_eq!(
+            errors("// CHECK: @add\n// CHECK-NOT: return\n// CHECK: @consts"),
+            ["test.mlir:2:4: error: CHECK-NOT: excluded string found in input"]
+        );
+        assert_eq!(
+            errors("// CHECK: return %[[SUM]]"),
+            ["test.mlir:1:4: error: CHECK: uses undefined variable 'SUM'"]
+        );
+        assert_eq!(
+            errors("// CHECK-LABEL: @consts\n// CHECK: arith.addi\n// CHECK-LABEL: @add"),
+            ["test.mlir:3:4: error: CHECK-LABEL: expected string not found in input"]
+        );
+    }
+
+    #[test]
+    fn should_not_overlap_the_dag_matches() {
+        check("// CHECK-DAG: arith.constant\n// CHECK-DAG: arith.constant\n// CHECK: return").unwrap();
+        assert_eq!(
+            errors("// CHECK-DAG: arith.constant\n// CHECK-DAG: arith.constant\n// CHECK-DAG: arith.constant"),
+            ["test.mlir:3:4: error: CHECK-DAG: expected string not found in input"]
+        );
+    }
+
+    #[test]
+    fn should_reject_the_malformed_check_files() {
+        let parse = |source| {
+            CheckFile::parse("test.mlir", source, &["CHECK"])
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(parse("// nothing"), "no check strings found with the prefixes CHECK");
+        assert_eq!(parse("// CHECK:"), "1:4: found empty check string with prefix 'CHECK:'");
+        assert_eq!(
+            parse("// CHECK-NEXT: a"),
+            "1:4: found 'CHECK-NEXT' without previous 'CHECK: line"
+        );
+        assert_eq!(
+            parse("// CHECK-LABEL: %[[A:.*]]"),
+            "1:4: found 'CHECK-LABEL:' with variable definition or use"
+        );
+        assert_eq!(
+            parse("// CHECK: {{a"),
+            "1:4: found start of regex string with no end '}}'"
+        );
+        assert_eq!(
+            CheckFile::parse("test.mlir", "", &["1CHECK"]).unwrap_err(),
+            FileCheckError::InvalidPrefix("1CHECK".to_string())
+        );
+        let checks = CheckFile::parse("test.mlir", "// XCHECK: a\n// CHECKER: b\n// CHECK-FOO: c", &["CHECK"]);
+        assert!(matches!(checks, Err(FileCheckError::NoChecks(_))));
+    }
+}
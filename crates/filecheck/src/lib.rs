@@ -0,0 +1,88 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi FileCheck
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! *fljuga-handahofi-filecheck* implements the LLVM FileCheck directives matching, the [CheckFile],
+//! and the lit-style `RUN:` lines of the `.mlir` regression tests, the [LitSuite], run by `cargo test`.
+//!
+
+mod check;
+mod lit;
+mod pattern;
+mod report;
+
+use std::path::PathBuf;
+
+pub use check::{Check, CheckFile, CheckKind};
+pub use lit::{LitReport, LitSuite};
+pub use report::CheckFailure;
+
+/// Derived [thiserror::Error] for the malformed check files
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FileCheckError {
+    #[error("no check strings found with the prefixes {}", .0.join(", "))]
+    NoChecks(Vec<String>),
+
+    #[error("{line}:{column}: {message}")]
+    Directive {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("invalid check prefix `{0}`")]
+    InvalidPrefix(String),
+}
+
+/// Derived [thiserror::Error] for the failed `RUN:` lines of the lit tests
+#[derive(thiserror::Error, Debug)]
+pub enum LitError {
+    #[error("no `RUN:` lines found")]
+    NoRunLines,
+
+    #[error("malformed `RUN:` line, {0}")]
+    Syntax(String),
+
+    #[error("`{0}` is neither `FileCheck` nor a registered tool")]
+    UnknownCommand(String),
+
+    #[error("`{command}` {status}\n{stderr}")]
+    Command {
+        command: String,
+        status: String,
+        stderr: String,
+    },
+
+    #[error("`{0}` was expected to fail")]
+    UnexpectedSuccess(String),
+
+    #[error(transparent)]
+    CheckFile(#[from] FileCheckError),
+
+    #[error("{0}")]
+    Check(Box<CheckFailure>),
+
+    #[error("{}: {error}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+}
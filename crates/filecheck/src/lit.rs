@@ -0,0 +1,457 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! lit-style tests, the files under the suite root run by their `// RUN:` lines, like
+//! `// RUN: fljuga-opt %s --canonicalize | FileCheck %s`.
+//! The commands are either the registered tools or the in-process `FileCheck`, optionally negated with `not`,
+//! and piped into each other, a failure of any of them fails the test, like with the lit `pipefail`.
+//! The `%s` and `%S` substitutions are the test file and its directory, the `%t` one is the temporary file of
+//! the test, like the `-o %t` outputs read back by the next `RUN:` lines.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::{CheckFile, LitError};
+
+/// Result of a pipeline stage, the `FileCheck` ones have no output.
+struct Output {
+    stdout: Vec<u8>,
+    error: Option<LitError>,
+}
+
+/// Test files of the extensions under the root directory, with the tools their `RUN:` lines refer to.
+pub struct LitSuite {
+    root: PathBuf,
+    extensions: Vec<String>,
+    tools: BTreeMap<String, PathBuf>,
+    temporary: PathBuf,
+}
+
+/// Failures of the suite tests.
+#[derive(Debug)]
+pub struct LitReport {
+    pub passed: usize,
+    pub failures: Vec<(PathBuf, LitError)>,
+}
+
+impl fmt::Display for LitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, error) in &self.failures {
+            writeln!(f, "FAIL: {}\n{error}", path.display())?;
+        }
+        write!(
+            f,
+            "{} of {} tests failed",
+            self.failures.len(),
+            self.passed + self.failures.len()
+        )
+    }
+}
+
+impl std::error::Error for LitReport {}
+
+impl LitSuite {
+    /// Suite of the `.mlir` files under the root, with the temporary files under the system temporary directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            extensions: vec!["mlir".to_string()],
+            tools: BTreeMap::new(),
+            temporary: std::env::temp_dir().join("lit"),
+        }
+    }
+
+    pub fn extension(mut self, extension: impl Into<String>) -> Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Registers the executable of the tool name the `RUN:` lines refer to.
+    pub fn tool(mut self, name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.tools.insert(name.into(), path.into());
+        self
+    }
+
+    /// Directory of the `%t` temporary files, mirroring the layout of the test files under the root.
+    pub fn temporary_dir(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.temporary = directory.into();
+        self
+    }
+
+    /// Test files under the root, ordered by their paths.
+    pub fn files(&self) -> Result<Vec<PathBuf>, LitError> {
+        let mut files = vec![];
+        let mut directories = vec![self.root.clone()];
+        while let Some(directory) = directories.pop() {
+            let io = |error| LitError::Io {
+                path: directory.clone(),
+                error,
+            };
+            for entry in fs::read_dir(&directory).map_err(io)? {
+                let path = entry.map_err(io)?.path();
+                if path.is_dir() {
+                    directories.push(path);
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| self.extensions.iter().any(|known| extension == known.as_str()))
+                {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Runs the tests, the number of the passed ones on success.
+    pub fn run(&self) -> Result<usize, LitReport> {
+        let files = self.files().map_err(|error| LitReport {
+            passed: 0,
+            failures: vec![(self.root.clone(), error)],
+        })?;
+        let mut report = LitReport {
+            passed: 0,
+            failures: vec![],
+        };
+        for path in files {
+            match self.run_file(&path) {
+                Ok(()) => report.passed += 1,
+                Err(error) => report.failures.push((path, error)),
+            }
+        }
+        match report.failures.is_empty() {
+            true => Ok(report.passed),
+            false => Err(report),
+        }
+    }
+
+    /// Runs the `RUN:` lines of the test file in order, stopping at the first failure.
+    pub fn run_file(&self, path: &Path) -> Result<(), LitError> {
+        let source = fs::read_to_string(path).map_err(|error| LitError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let lines = run_lines(&source);
+        if lines.is_empty() {
+            return Err(LitError::NoRunLines);
+        }
+        let temporary = self.temporary_file(path);
+        if let Some(directory) = temporary.parent() {
+            fs::create_dir_all(directory).map_err(|error| LitError::Io {
+                path: directory.to_path_buf(),
+                error,
+            })?;
+        }
+        for line in lines {
+            let mut stdout = vec![];
+            for stage in tokenize(&substitute(&line, path, &temporary))? {
+                let output = self.run_stage(&stage, &stdout)?;
+                if let Some(error) = output.error {
+                    return Err(error);
+                }
+                stdout = output.stdout;
+            }
+        }
+        Ok(())
+    }
+
+    /// `%t` of the test file, its path under the root with the `.tmp` suffix.
+    fn temporary_file(&self, path: &Path) -> PathBuf {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let mut file = self.temporary.join(relative).into_os_string();
+        file.push(".tmp");
+        PathBuf::from(file)
+    }
+
+    /// Runs the pipeline stage on the output of the previous one.
+    fn run_stage(&self, args: &[String], stdin: &[u8]) -> Result<Output, LitError> {
+        let mut args = args;
+        let mut negated = false;
+        while let [not, rest @ ..] = args {
+            if not != "not" {
+                break;
+            }
+            negated = !negated;
+            args = rest;
+        }
+        let merge_stderr = args.iter().any(|arg| arg == "2>&1");
+        let args: Vec<&String> = args.iter().filter(|arg| *arg != "2>&1").collect();
+        let Some((command, args)) = args.split_first() else {
+            return Err(LitError::Syntax("empty command".to_string()));
+        };
+        let mut output = match command.as_str() {
+            "FileCheck" => file_check(args, stdin)?,
+            tool => {
+                let path = self
+                    .tools
+                    .get(tool)
+                    .ok_or_else(|| LitError::UnknownCommand(tool.to_string()))?;
+                run_tool(path, tool, args, stdin, merge_stderr)?
+            }
+        };
+        let line = std::iter::once(*command)
+            .chain(args.iter().copied())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        output.error = match (negated, output.error) {
+            (false, error) => error,
+            (true, Some(_)) => None,
+            (true, None) => Some(LitError::UnexpectedSuccess(line)),
+        };
+        Ok(output)
+    }
+}
+
+/// `RUN:` commands of the source, the lines ending with `\` continue on the next `RUN:` line.
+fn run_lines(source: &str) -> Vec<String> {
+    let mut lines = vec![];
+    let mut continued: Option<String> = None;
+    for line in source.lines() {
+        let Some(start) = line.find("RUN:") else {
+            continue;
+        };
+        let command = line[start + "RUN:".len()..].trim();
+        let mut current = continued.take().unwrap_or_default();
+        match command.strip_suffix('\\') {
+            Some(command) => {
+                current.push_str(command);
+                continued = Some(current);
+            }
+            None => {
+                current.push_str(command);
+                lines.push(current);
+            }
+        }
+    }
+    lines.extend(continued);
+    lines
+}
+
+/// Replaces the `%s`, `%S`, `%t` and `%%` substitutions.
+fn substitute(line: &str, path: &Path, temporary: &Path) -> String {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut substituted = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('%', Some('s')) => substituted.push_str(&path.display().to_string()),
+            ('%', Some('S')) => substituted.push_str(&directory.display().to_string()),
+            ('%', Some('t')) => substituted.push_str(&temporary.display().to_string()),
+            ('%', Some('%')) => substituted.push('%'),
+            (c, _) => {
+                substituted.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    substituted
+}
+
+/// Arguments of the pipeline stages, split by the unquoted `|`, with the shell-like quoting.
+fn tokenize(line: &str) -> Result<Vec<Vec<String>>, LitError> {
+    let mut stages = vec![vec![]];
+    let mut token: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                stages.last_mut().unwrap().extend(token.take());
+            }
+            '|' => {
+                stages.last_mut().unwrap().extend(token.take());
+                stages.push(vec![]);
+            }
+            '\'' => {
+                let quoted = token.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err(LitError::Syntax(format!("unterminated quote in `{line}`"))),
+                    }
+                }
+            }
+            '"' => {
+                let quoted = token.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => quoted.extend(chars.next()),
+                        Some(c) => quoted.push(c),
+                        None => return Err(LitError::Syntax(format!("unterminated quote in `{line}`"))),
+                    }
+                }
+            }
+            '\\' => token.get_or_insert_with(String::new).extend(chars.next()),
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    stages.last_mut().unwrap().extend(token);
+    if stages.iter().any(Vec::is_empty) {
+        return Err(LitError::Syntax(format!("empty pipeline stage in `{line}`")));
+    }
+    Ok(stages)
+}
+
+/// Runs the tool, the error output goes before the standard one when merged by `2>&1`.
+fn run_tool(path: &Path, name: &str, args: &[&String], stdin: &[u8], merge_stderr: bool) -> Result<Output, LitError> {
+    let io = |error| LitError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let mut child = Command::new(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(io)?;
+    let mut input = child.stdin.take().expect("the standard input is piped");
+    let stdin = stdin.to_vec();
+    // Written on its own thread, so the full output pipes don't block the tool reading the input.
+    let writer = thread::spawn(move || input.write_all(&stdin));
+    let output = child.wait_with_output().map_err(io)?;
+    // The tools may exit without reading their input.
+    let _ = writer.join();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let error = (!output.status.success()).then(|| LitError::Command {
+        command: std::iter::once(name)
+            .chain(args.iter().map(|arg| arg.as_str()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        status: output.status.to_string(),
+        stderr: stderr.clone(),
+    });
+    let stdout = match merge_stderr {
+        true => [output.stderr, output.stdout].concat(),
+        false => output.stdout,
+    };
+    Ok(Output { stdout, error })
+}
+
+/// `FileCheck <check file> [--check-prefix=P] [--check-prefixes=P,Q] [--input-file=F]`
+fn file_check(args: &[&String], stdin: &[u8]) -> Result<Output, LitError> {
+    let mut check_file = None;
+    let mut prefixes: Vec<String> = vec![];
+    let mut input_file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg.strip_prefix("--").or_else(|| arg.strip_prefix('-')) else {
+            check_file = Some(arg.to_string());
+            continue;
+        };
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (option, None),
+        };
+        let mut value = || {
+            value
+                .clone()
+                .or_else(|| args.next().map(|arg| arg.to_string()))
+                .ok_or_else(|| LitError::Syntax(format!("`FileCheck {arg}` expects a value")))
+        };
+        match name {
+            "check-prefix" => prefixes.push(value()?),
+            "check-prefixes" => prefixes.extend(value()?.split(',').map(str::to_string)),
+            "input-file" => input_file = Some(value()?),
+            "dump-input" => {
+                value()?;
+            }
+            _ => return Err(LitError::Syntax(format!("unsupported FileCheck option `{arg}`"))),
+        }
+    }
+    let check_file = check_file.ok_or_else(|| LitError::Syntax("FileCheck expects a check file".to_string()))?;
+    if prefixes.is_empty() {
+        prefixes.push("CHECK".to_string());
+    }
+    let read = |path: &str| {
+        fs::read_to_string(path).map_err(|error| LitError::Io {
+            path: PathBuf::from(path),
+            error,
+        })
+    };
+    let (input_name, input) = match &input_file {
+        Some(path) => (path.as_str(), read(path)?),
+        None => ("<stdin>", String::from_utf8_lossy(stdin).into_owned()),
+    };
+    let prefixes: Vec<&str> = prefixes.iter().map(String::as_str).collect();
+    let checks = CheckFile::parse(&check_file, &read(&check_file)?, &prefixes)?;
+    Ok(Output {
+        stdout: vec![],
+        error: checks.check(input_name, &input).err().map(LitError::Check),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_the_run_lines() {
+        let source = "// RUN: fljuga-opt %s --split-input-file \\\n// RUN:   --canonicalize | FileCheck %s\n\
+                      // CHECK: RUN\n// RUN: not fljuga-opt %s 2>&1 | FileCheck %s --check-prefix=ERROR\n";
+        let lines = run_lines(source);
+        assert_eq!(
+            lines,
+            [
+                "fljuga-opt %s --split-input-file --canonicalize | FileCheck %s",
+                "not fljuga-opt %s 2>&1 | FileCheck %s --check-prefix=ERROR"
+            ]
+        );
+        let line = substitute(&lines[1], Path::new("tests/opt/a.mlir"), Path::new("tmp/a.mlir.tmp"));
+        assert_eq!(
+            tokenize(&line).unwrap(),
+            [
+                vec!["not", "fljuga-opt", "tests/opt/a.mlir", "2>&1"],
+                vec!["FileCheck", "tests/opt/a.mlir", "--check-prefix=ERROR"]
+            ]
+        );
+    }
+
+    #[test]
+    fn should_tokenize_quoted_arguments() {
+        let stages = tokenize(r#"opt '--pass-pipeline=builtin.module(a, b)' "--x=\"y | z\"" a\ b"#).unwrap();
+        assert_eq!(
+            stages,
+            [vec![
+                "opt",
+                "--pass-pipeline=builtin.module(a, b)",
+                "--x=\"y | z\"",
+                "a b"
+            ]]
+        );
+        assert!(matches!(tokenize("opt | | FileCheck"), Err(LitError::Syntax(_))));
+        assert!(matches!(tokenize("opt 'a"), Err(LitError::Syntax(_))));
+        assert_eq!(
+            substitute("%S/%%s -o %t", Path::new("a/b.mlir"), Path::new("tmp/b.mlir.tmp")),
+            "a/%s -o tmp/b.mlir.tmp"
+        );
+        let suite = LitSuite::new("tests").temporary_dir("tmp");
+        assert_eq!(
+            suite.temporary_file(Path::new("tests/lit/b.mlir")),
+            Path::new("tmp/lit/b.mlir.tmp")
+        );
+    }
+}
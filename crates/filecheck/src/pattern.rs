@@ -0,0 +1,264 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Check patterns, the literal text with the `{{regex}}` parts and the `[[VAR:regex]]` captures,
+//! compiled to the [Regex]es once the used variables are known.
+//!
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use regex::Regex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Regex(String),
+    /// `[[VAR:regex]]`
+    Define {
+        name: String,
+        regex: String,
+    },
+    /// `[[VAR]]`
+    Use(String),
+}
+
+/// Match of a pattern, along with the values of the variables it defines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PatternMatch {
+    pub range: Range<usize>,
+    pub definitions: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Pattern {
+    pieces: Vec<Piece>,
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Escapes the literal text, the runs of the horizontal whitespace match any other ones,
+/// like the FileCheck whitespace canonicalization.
+fn literal(text: &str, out: &mut String) {
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        match c {
+            ' ' | '\t' if in_whitespace => {}
+            ' ' | '\t' => {
+                in_whitespace = true;
+                out.push_str("[ \\t]+");
+            }
+            c => {
+                in_whitespace = false;
+                out.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])));
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Parses the pattern, trimmed of the surrounding whitespace, the error is a message on the pattern column.
+    pub fn parse(text: &str) -> Result<Self, (usize, String)> {
+        let mut pieces = vec![];
+        let mut defined = vec![];
+        let mut rest = text;
+        let offset = |rest: &str| text.len() - rest.len();
+        while !rest.is_empty() {
+            let next = [rest.find("{{"), rest.find("[[")].into_iter().flatten().min();
+            let Some(start) = next else {
+                pieces.push(Piece::Literal(rest.to_string()));
+                break;
+            };
+            if start > 0 {
+                pieces.push(Piece::Literal(rest[..start].to_string()));
+            }
+            let column = offset(rest) + start;
+            rest = &rest[start..];
+            if let Some(body) = rest.strip_prefix("{{") {
+                let Some(end) = body.find("}}") else {
+                    return Err((column, "found start of regex string with no end '}}'".to_string()));
+                };
+                // `{{[0-9]{2}}}` ends with the last one of the closing braces.
+                let end = end + body[end + 2..].chars().take_while(|c| *c == '}').count();
+                let regex = &body[..end];
+                Regex::new(regex).map_err(|error| (column, format!("invalid regex: {error}")))?;
+                pieces.push(Piece::Regex(regex.to_string()));
+                rest = &body[end + 2..];
+                continue;
+            }
+            let body = &rest[2..];
+            let Some(end) = body.find("]]") else {
+                return Err((
+                    column,
+                    "invalid variable definition or use, no closing ']]'".to_string(),
+                ));
+            };
+            let variable = &body[..end];
+            rest = &body[end + 2..];
+            match variable.split_once(':') {
+                Some((name, regex)) if is_variable_name(name) => {
+                    Regex::new(regex).map_err(|error| (column, format!("invalid regex: {error}")))?;
+                    if defined.iter().any(|defined| defined == name) {
+                        return Err((column, format!("variable '{name}' is defined twice in the pattern")));
+                    }
+                    defined.push(name.to_string());
+                    pieces.push(Piece::Define {
+                        name: name.to_string(),
+                        regex: regex.to_string(),
+                    });
+                }
+                None if is_variable_name(variable) => {
+                    if defined.iter().any(|defined| defined == variable) {
+                        return Err((
+                            column,
+                            format!("variable '{variable}' is used in the pattern defining it"),
+                        ));
+                    }
+                    pieces.push(Piece::Use(variable.to_string()));
+                }
+                _ if variable.starts_with('#') => {
+                    return Err((column, "numeric variables are not supported".to_string()));
+                }
+                _ => return Err((column, format!("invalid variable name in '[[{variable}]]'"))),
+            }
+        }
+        Ok(Self { pieces })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Whether the pattern defines or uses the variables.
+    pub fn has_variables(&self) -> bool {
+        self.pieces
+            .iter()
+            .any(|piece| matches!(piece, Piece::Define { .. } | Piece::Use(_)))
+    }
+
+    /// Regex of the pattern with the values of the used variables, the error names an undefined one.
+    fn regex(&self, variables: &HashMap<String, String>) -> Result<Regex, String> {
+        let mut regex = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(text) => literal(text, &mut regex),
+                Piece::Regex(text) => {
+                    regex.push_str("(?:");
+                    regex.push_str(text);
+                    regex.push(')');
+                }
+                Piece::Define { name, regex: text } => {
+                    regex.push_str(&format!("(?P<{name}>{text})"));
+                }
+                Piece::Use(name) => match variables.get(name) {
+                    Some(value) => regex.push_str(&regex::escape(value)),
+                    None => return Err(name.clone()),
+                },
+            }
+        }
+        Ok(Regex::new(&regex).expect("the pieces of the pattern are valid regexes"))
+    }
+
+    /// First match of the pattern in the input range, the error names an undefined variable.
+    pub fn find(
+        &self,
+        input: &str,
+        range: Range<usize>,
+        variables: &HashMap<String, String>,
+    ) -> Result<Option<PatternMatch>, String> {
+        let regex = self.regex(variables)?;
+        let Some(captures) = regex.captures_at(&input[..range.end], range.start) else {
+            return Ok(None);
+        };
+        let whole = captures.get(0).expect("the whole match is always captured");
+        let definitions = self
+            .pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Define { name, .. } => {
+                    let value = captures.name(name).map_or("", |value| value.as_str());
+                    Some((name.clone(), value.to_string()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(Some(PatternMatch {
+            range: whole.range(),
+            definitions,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, input: &str, variables: &[(&str, &str)]) -> Option<(String, Vec<(String, String)>)> {
+        let variables = variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let pattern = Pattern::parse(pattern).unwrap();
+        let found = pattern.find(input, 0..input.len(), &variables).unwrap()?;
+        Some((input[found.range].to_string(), found.definitions))
+    }
+
+    #[test]
+    fn should_match_literals_regexes_and_variables() {
+        assert_eq!(
+            find("%0 = arith.addi", "  %0   =\tarith.addi %a", &[]),
+            Some(("%0   =\tarith.addi".to_string(), vec![]))
+        );
+        assert_eq!(find("a.b(c)", "axb(c) a.b(c)", &[]).unwrap().0, "a.b(c)");
+        assert_eq!(find("i{{[0-9]{2}}}", "i1 i32", &[]).unwrap().0, "i32");
+        assert_eq!(
+            find(
+                "%[[SUM:.*]] = arith.addi %[[LHS]]",
+                "%7 = arith.addi %3",
+                &[("LHS", "3")]
+            ),
+            Some((
+                "%7 = arith.addi %3".to_string(),
+                vec![("SUM".to_string(), "7".to_string())]
+            ))
+        );
+        assert_eq!(find("[[V]]", "ab", &[("V", ".")]), None);
+    }
+
+    #[test]
+    fn should_reject_malformed_patterns() {
+        let error = |pattern: &str| Pattern::parse(pattern).unwrap_err();
+        assert_eq!(
+            error("a {{[0-9]"),
+            (2, "found start of regex string with no end '}}'".to_string())
+        );
+        assert_eq!(
+            error("[[1X:.*]]"),
+            (0, "invalid variable name in '[[1X:.*]]'".to_string())
+        );
+        assert_eq!(error("[[#N]]"), (0, "numeric variables are not supported".to_string()));
+        assert_eq!(
+            error("[[X:.*]] [[X]]"),
+            (9, "variable 'X' is used in the pattern defining it".to_string())
+        );
+        assert!(error("{{(}}").1.starts_with("invalid regex"));
+    }
+}
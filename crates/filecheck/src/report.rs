@@ -0,0 +1,210 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Reports of the failed checks, the mismatched directive lines against the input ones they were expected at,
+//! and the input annotated with the directive matches, like the FileCheck `--dump-input` one.
+//!
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::{Check, CheckKind};
+
+/// Match of a directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Annotation {
+    pub check: usize,
+    pub range: Range<usize>,
+}
+
+/// Directive failed at the input position, the `found` range is the misplaced or the excluded match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Mismatch {
+    pub check: usize,
+    pub message: String,
+    pub scanned: usize,
+    pub found: Option<Range<usize>>,
+}
+
+/// Failure of the check file directives on the input.
+#[derive(Debug, Clone)]
+pub struct CheckFailure {
+    pub(crate) check_name: String,
+    pub(crate) check_source: String,
+    pub(crate) checks: Vec<Check>,
+    pub(crate) input_name: String,
+    pub(crate) input: String,
+    pub(crate) annotations: Vec<Annotation>,
+    pub(crate) mismatches: Vec<Mismatch>,
+}
+
+/// Starts of the input lines, the empty input has a single empty line.
+fn line_starts(input: &str) -> Vec<usize> {
+    let newlines = input.match_indices('\n').map(|(index, _)| index + 1);
+    std::iter::once(0)
+        .chain(newlines.filter(|start| *start < input.len()))
+        .collect()
+}
+
+impl CheckFailure {
+    /// `file:line:column: error: CHECK-NEXT: message` lines of the mismatches.
+    pub fn errors(&self) -> Vec<String> {
+        self.mismatches
+            .iter()
+            .map(|mismatch| {
+                let check = &self.checks[mismatch.check];
+                format!(
+                    "{}:{}:{}: error: {}: {}",
+                    self.check_name,
+                    check.line,
+                    check.column,
+                    check.directive(),
+                    mismatch.message
+                )
+            })
+            .collect()
+    }
+
+    /// Index, start and end of the line of the position, the end excludes the newline.
+    fn line(&self, starts: &[usize], position: usize) -> (usize, usize, usize) {
+        let index = starts.partition_point(|start| *start <= position).saturating_sub(1);
+        let start = starts[index];
+        let end = self.input[start..]
+            .find('\n')
+            .map_or(self.input.len(), |end| start + end);
+        (index, start, end)
+    }
+
+    fn label(&self, check: usize) -> String {
+        let check = &self.checks[check];
+        let kind = match check.kind {
+            CheckKind::Plain => "check",
+            CheckKind::Next => "next",
+            CheckKind::Same => "same",
+            CheckKind::Empty => "empty",
+            CheckKind::Dag => "dag",
+            CheckKind::Not => "not",
+            CheckKind::Label => "label",
+        };
+        format!("{kind}:{}", check.line)
+    }
+}
+
+/// The mismatched directive lines, the `-` expected ones, against the `+` input lines, followed by the input
+/// annotated with the `^~~` matches, the `!~~` misplaced or excluded ones and the `X~~` scanned ranges.
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let starts = line_starts(&self.input);
+        for (mismatch, error) in self.mismatches.iter().zip(self.errors()) {
+            let check = &self.checks[mismatch.check];
+            let position = mismatch.found.as_ref().map_or(mismatch.scanned, |found| found.start);
+            let (line, start, end) = self.line(&starts, position);
+            writeln!(f, "{error}")?;
+            writeln!(
+                f,
+                "-{}",
+                self.check_source.lines().nth(check.line - 1).unwrap_or_default()
+            )?;
+            writeln!(f, "+{}", &self.input[start..end])?;
+            let note = match mismatch.found {
+                Some(_) => "found here",
+                None => "scanning from here",
+            };
+            writeln!(
+                f,
+                "{}:{}:{}: note: {note}",
+                self.input_name,
+                line + 1,
+                position - start + 1
+            )?;
+        }
+
+        let mut rows: Vec<Vec<(String, usize, String)>> = vec![vec![]; starts.len()];
+        for annotation in &self.annotations {
+            let (line, start, end) = self.line(&starts, annotation.range.start);
+            let width = annotation.range.end.min(end).saturating_sub(annotation.range.start);
+            let marker = format!("^{}", "~".repeat(width.saturating_sub(1)));
+            rows[line].push((self.label(annotation.check), annotation.range.start - start, marker));
+        }
+        for mismatch in &self.mismatches {
+            let (marker, range) = match &mismatch.found {
+                Some(found) => ('!', found.clone()),
+                None => ('X', mismatch.scanned..self.input.len()),
+            };
+            let (line, start, end) = self.line(&starts, range.start);
+            let width = range.end.min(end).saturating_sub(range.start);
+            let marker = format!(
+                "{marker}{} error: {}",
+                "~".repeat(width.saturating_sub(1)),
+                mismatch.message
+            );
+            rows[line].push((self.label(mismatch.check), range.start.min(end) - start, marker));
+        }
+        let width = rows
+            .iter()
+            .flatten()
+            .map(|(label, ..)| label.len())
+            .chain([starts.len().to_string().len()])
+            .max()
+            .unwrap_or_default();
+        writeln!(f, "\nInput was:\n<<<<<<")?;
+        for (index, start) in starts.iter().enumerate() {
+            let (_, _, end) = self.line(&starts, *start);
+            let text = format!("{:>width$}: {}", index + 1, &self.input[*start..end]);
+            writeln!(f, "{}", text.trim_end())?;
+            for (label, column, marker) in &rows[index] {
+                writeln!(f, "{label:<width$}  {}{marker}", " ".repeat(*column))?;
+            }
+        }
+        writeln!(f, ">>>>>>")
+    }
+}
+
+impl std::error::Error for CheckFailure {}
+
+#[cfg(test)]
+mod tests {
+    use crate::CheckFile;
+
+    #[test]
+    fn should_annotate_the_input() {
+        let checks = "// CHECK: %[[X:.*]] = arith.constant\n// CHECK-NEXT: return %[[X]]\n";
+        let input = "%c1 = arith.constant 1 : i32\n\n  return %c1 : i32\n";
+        let failure = CheckFile::parse("test.mlir", checks, &["CHECK"])
+            .unwrap()
+            .check("<stdin>", input)
+            .unwrap_err();
+        assert_eq!(
+            failure.to_string(),
+            "\
+test.mlir:2:4: error: CHECK-NEXT: is not on the line after the previous match
+-// CHECK-NEXT: return %[[X]]
++  return %c1 : i32
+<stdin>:3:3: note: found here
+
+Input was:
+<<<<<<
+      1: %c1 = arith.constant 1 : i32
+check:1  ^~~~~~~~~~~~~~~~~~~~
+      2:
+      3:   return %c1 : i32
+next:2     !~~~~~~~~~ error: is not on the line after the previous match
+>>>>>>
+"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir codegen assembly format
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! [Declarative assembly format](https://mlir.llvm.org/docs/DefiningDialects/Operations/#declarative-assembly-format)
+//! compiler, mirroring the upstream `OpFormatGen`.
+//! The `assemblyFormat` is parsed by [grammar], verified and bound to the operation by [analysis],
+//! then the Rust custom form printer and parser are generated by [printer] and [parser].
+//!
+//! `custom<Name>(...)` directives call the hand-written `print_name` and `parse_name` hooks,
+//! the operations using a directive without a hook are skipped.
+//! The attribute and type formats, with their `params` and `struct` directives, are compiled by [attr_or_type].
+//!
+
+pub(crate) mod analysis;
+pub(crate) mod attr_or_type;
+pub(crate) mod grammar;
+pub(crate) mod parser;
+pub(crate) mod printer;
+
+use crate::ods::Operation;
+
+pub use analysis::Format;
+
+/// Derived [thiserror::Error] for the assembly format errors, the messages follow `OpFormatGen`
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum FormatError {
+    #[error("operation has no 'assemblyFormat'")]
+    NoAssemblyFormat,
+
+    #[error("operation has a custom C++ assembly format")]
+    CustomAssemblyFormat,
+
+    #[error("invalid assembly format near {0:?}")]
+    Syntax(String),
+
+    #[error("{0} is not supported")]
+    Unsupported(String),
+
+    #[error("expected variable {0:?} to refer to an argument, region, result, or successor")]
+    UnknownVariable(String),
+
+    #[error("{0:?} is already bound")]
+    AlreadyBound(String),
+
+    #[error("{kind} #{index}, named {name:?}, not found")]
+    NotFound {
+        kind: &'static str,
+        index: usize,
+        name: String,
+    },
+
+    #[error("type of {kind} #{index}, named {name:?}, is not buildable and a buildable type cannot be inferred")]
+    TypeNotInferred {
+        kind: &'static str,
+        index: usize,
+        name: String,
+    },
+
+    #[error("'attr-dict' directive not found")]
+    MissingAttrDict,
+
+    #[error("'attr-dict' directive has already been seen")]
+    DuplicateAttrDict,
+
+    #[error("custom directive {0:?} has no hook")]
+    MissingHook(String),
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Generated custom form of the operation.
+#[derive(Debug, Clone)]
+pub struct CustomForm {
+    /// `fn print(op: Operation, p: &mut Printer<'_>)` body.
+    pub printer: String,
+    /// `fn parse(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError>` body.
+    pub parser: String,
+}
+
+/// Compiles the operation `assemblyFormat`, the `hooks` are the names of the available custom directives.
+pub fn compile(operation: &Operation, hooks: &[String], hooks_path: &str) -> Result<CustomForm, FormatError> {
+    let source = match &operation.assembly_format {
+        Some(source) => source,
+        None if operation.has_custom_assembly_format => return Err(FormatError::CustomAssemblyFormat),
+        None => return Err(FormatError::NoAssemblyFormat),
+    };
+    let elements = grammar::parse(source)?;
+    let format = Format::new(operation, &elements, hooks)?;
+    Ok(CustomForm {
+        printer: printer::generate(operation, &format, hooks_path),
+        parser: parser::generate(operation, &format, hooks_path),
+    })
+}
+
+/// `camelCase` and `PascalCase` names in `snake_case`, like `trueDestOperands` or `CustomDirectiveOperands`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous.is_some_and(|previous| previous.is_ascii_lowercase() || previous.is_ascii_digit()) {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+        previous = Some(c);
+    }
+    snake
+}
+
+/// Indented generated code lines.
+#[derive(Debug, Default)]
+pub(crate) struct CodeWriter {
+    code: String,
+    indent: usize,
+}
+
+impl CodeWriter {
+    pub fn line(&mut self, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        if !line.is_empty() {
+            self.code.extend(std::iter::repeat("    ").take(self.indent));
+        }
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    pub fn indent(&mut self) {
+        self.indent += 1;
+    }
+
+    pub fn dedent(&mut self) {
+        self.indent -= 1;
+    }
+
+    /// Appends the generated code lines at the current indentation.
+    pub fn append(&mut self, code: &str) {
+        for line in code.lines() {
+            self.line(line);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_names() {
+        assert_eq!(snake_case("trueDestOperands"), "true_dest_operands");
+        assert_eq!(snake_case("CustomDirectiveOperands"), "custom_directive_operands");
+        assert_eq!(snake_case("sym_name"), "sym_name");
+        assert_eq!(snake_case("odsResult0"), "ods_result0");
+    }
+}
@@ -0,0 +1,885 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Binds the format variables to the operation and infers the types the format doesn't print,
+//! mirroring the upstream `OpFormatParser` verification.
+//!
+
+use crate::assembly_format::grammar::Element;
+use crate::assembly_format::FormatError;
+use crate::ods::{Operation, Trait, Variadicity};
+
+/// Operation variable of the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Var {
+    Operand(usize),
+    Result(usize),
+    Attribute(usize),
+}
+
+/// Argument of the `type` and `functional-type` directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeArg {
+    Operand(usize),
+    Result(usize),
+    Operands,
+    Results,
+}
+
+/// Bound format element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatElement {
+    Literal(String),
+    Whitespace(String),
+    Newline,
+    Operand(usize),
+    Attribute { index: usize, qualified: bool },
+    Region(usize),
+    Successor(usize),
+    AttrDict { with_keyword: bool },
+    Operands,
+    Regions,
+    Successors,
+    Type { arg: TypeArg, qualified: bool },
+    FunctionalType { inputs: TypeArg, results: TypeArg },
+    Custom { name: String, params: Vec<CustomParam> },
+    Optional {
+        then_elements: Vec<FormatElement>,
+        else_elements: Vec<FormatElement>,
+        anchor: usize,
+    },
+}
+
+/// Custom directive parameter, the `ref` ones are already parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomParam {
+    pub element: FormatElement,
+    pub is_ref: bool,
+}
+
+/// `TypesMatchWith` transformer of the inferred types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// `$_self`
+    Identity,
+    /// `getElementType()` of the shaped types.
+    ElementType,
+    /// `getI1SameShape($_self)`
+    I1SameShape,
+}
+
+impl Transform {
+    pub(crate) fn from_transformer(transformer: &str) -> Option<Self> {
+        let transformer = transformer.trim();
+        if transformer == "$_self" {
+            Some(Transform::Identity)
+        } else if transformer.contains("getI1SameShape") {
+            Some(Transform::I1SameShape)
+        } else if transformer.contains(".getElementType()") {
+            Some(Transform::ElementType)
+        } else {
+            None
+        }
+    }
+
+    /// `Type` expression of the transformed `Type` place, uniqued in the `ctx` expression.
+    pub(crate) fn apply(self, ctx: &str, place: &str) -> String {
+        match self {
+            Transform::Identity => place.to_string(),
+            Transform::ElementType => format!("{place}.element_type({ctx}).unwrap_or({place})"),
+            Transform::I1SameShape => format!("{ctx}.i1_same_shape({place})"),
+        }
+    }
+}
+
+/// How the operand or result type is known to the parser.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeSource {
+    /// Parsed by the `type` or `functional-type` directive, or by a custom directive.
+    Parsed,
+    /// Built by the `BuildableType` builder call.
+    Buildable(String),
+    /// Inferred from the type of another variable.
+    Infer { from: Var, transform: Transform },
+}
+
+/// Source of the result type the ODS builders infer, `None` if the result type has to be given.
+pub(crate) fn result_type_source(operation: &Operation, index: usize) -> Option<TypeSource> {
+    let result = &operation.results[index];
+    if let Some(r#type) = &result.constraint.buildable {
+        return Some(TypeSource::Buildable(r#type.clone()));
+    }
+    let source = |name: &str| {
+        operation
+            .operand(name)
+            .map(Var::Operand)
+            .or_else(|| operation.attribute(name).map(Var::Attribute))
+    };
+    operation.traits.iter().find_map(|r#trait| match r#trait {
+        Trait::Native(name) if name == "SameOperandsAndResultType" && !operation.operands.is_empty() => {
+            Some(TypeSource::Infer {
+                from: Var::Operand(0),
+                transform: Transform::Identity,
+            })
+        }
+        Trait::AllTypesMatch(names) if names.contains(&result.name) => names
+            .iter()
+            .filter(|name| **name != result.name)
+            .find_map(|name| source(name))
+            .map(|from| TypeSource::Infer {
+                from,
+                transform: Transform::Identity,
+            }),
+        Trait::TypesMatchWith { lhs, rhs, transformer } if *rhs == result.name => {
+            Some(TypeSource::Infer {
+                from: source(lhs)?,
+                transform: Transform::from_transformer(transformer)?,
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Verified format of the operation.
+#[derive(Debug, Clone)]
+pub struct Format {
+    pub elements: Vec<FormatElement>,
+    pub operand_types: Vec<TypeSource>,
+    pub result_types: Vec<TypeSource>,
+    /// The operands are parsed by the `operands` directive.
+    pub all_operands: bool,
+    /// The operand types are parsed by `type(operands)` or `functional-type(operands, ...)`.
+    pub all_operand_types: bool,
+    /// The result types are parsed by `type(results)` or `functional-type(..., results)`.
+    pub all_result_types: bool,
+    /// The inferred operands and results, in the order they are resolved.
+    pub inferred: Vec<Var>,
+    /// Attributes printed by the format, elided from the `attr-dict`.
+    pub elided_attributes: Vec<String>,
+}
+
+/// Binding state of the format verification.
+struct Binder<'a> {
+    operation: &'a Operation,
+    hooks: &'a [String],
+    operands: Vec<bool>,
+    operand_types: Vec<bool>,
+    result_types: Vec<bool>,
+    attributes: Vec<bool>,
+    regions: Vec<bool>,
+    successors: Vec<bool>,
+    all_operands: bool,
+    all_operand_types: bool,
+    all_result_types: bool,
+    all_regions: bool,
+    all_successors: bool,
+    attr_dict: bool,
+}
+
+/// Context of the element being bound.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Context {
+    TopLevel,
+    Custom,
+    Ref,
+}
+
+impl Format {
+    pub fn new(operation: &Operation, elements: &[Element], hooks: &[String]) -> Result<Self, FormatError> {
+        if let Some(property) = operation.properties.first() {
+            return Err(FormatError::Unsupported(format!("property {property:?}")));
+        }
+        let mut binder = Binder {
+            operation,
+            hooks,
+            operands: vec![false; operation.operands.len()],
+            operand_types: vec![false; operation.operands.len()],
+            result_types: vec![false; operation.results.len()],
+            attributes: vec![false; operation.attributes.len()],
+            regions: vec![false; operation.regions.len()],
+            successors: vec![false; operation.successors.len()],
+            all_operands: false,
+            all_operand_types: false,
+            all_result_types: false,
+            all_regions: false,
+            all_successors: false,
+            attr_dict: false,
+        };
+        let elements = elements
+            .iter()
+            .map(|element| binder.bind(element, Context::TopLevel))
+            .collect::<Result<Vec<_>, _>>()?;
+        binder.verify()?;
+
+        let mut elided_attributes: Vec<String> = binder
+            .attributes
+            .iter()
+            .zip(&operation.attributes)
+            .filter(|(bound, _)| **bound)
+            .map(|(_, attribute)| attribute.name.clone())
+            .collect();
+        if operation.has_trait("AttrSizedOperandSegments") {
+            elided_attributes.push("operandSegmentSizes".to_string());
+        }
+        if operation.has_trait("AttrSizedResultSegments") {
+            elided_attributes.push("resultSegmentSizes".to_string());
+        }
+
+        let mut format = Self {
+            elements,
+            operand_types: vec![],
+            result_types: vec![],
+            all_operands: binder.all_operands,
+            all_operand_types: binder.all_operand_types,
+            all_result_types: binder.all_result_types,
+            inferred: vec![],
+            elided_attributes,
+        };
+        format.infer_types(operation, &binder)?;
+        format.verify_collective(operation)?;
+        Ok(format)
+    }
+
+    /// Resolves the types that are not parsed, like `genParserTypeResolution`.
+    fn infer_types(&mut self, operation: &Operation, binder: &Binder) -> Result<(), FormatError> {
+        let initial = |parsed: bool, variadicity: Variadicity, buildable: &Option<String>, result: bool| {
+            if parsed {
+                Some(TypeSource::Parsed)
+            } else {
+                match buildable {
+                    Some(r#type) if !result || variadicity == Variadicity::Single => {
+                        Some(TypeSource::Buildable(r#type.clone()))
+                    }
+                    _ => None,
+                }
+            }
+        };
+        let mut operand_types: Vec<Option<TypeSource>> = operation
+            .operands
+            .iter()
+            .enumerate()
+            .map(|(index, operand)| {
+                let parsed = binder.all_operand_types || binder.operand_types[index];
+                initial(parsed, operand.variadicity, &operand.constraint.buildable, false)
+            })
+            .collect();
+        let mut result_types: Vec<Option<TypeSource>> = operation
+            .results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let parsed = binder.all_result_types || binder.result_types[index];
+                initial(parsed, result.variadicity, &result.constraint.buildable, true)
+            })
+            .collect();
+
+        let mut relations: Vec<(Var, Var, Option<Transform>, String)> = vec![];
+        let equal = |vars: &[Var], relations: &mut Vec<_>| {
+            for target in vars {
+                for source in vars.iter().filter(|source| *source != target) {
+                    relations.push((*target, *source, Some(Transform::Identity), "$_self".to_string()));
+                }
+            }
+        };
+        let operands: Vec<Var> = (0..operation.operands.len()).map(Var::Operand).collect();
+        let results: Vec<Var> = (0..operation.results.len()).map(Var::Result).collect();
+        for r#trait in &operation.traits {
+            match r#trait {
+                Trait::Native(name) if name == "SameOperandsAndResultType" => {
+                    equal(&[operands.clone(), results.clone()].concat(), &mut relations)
+                }
+                Trait::Native(name) if name == "SameTypeOperands" => equal(&operands, &mut relations),
+                Trait::AllTypesMatch(names) => {
+                    let vars = names
+                        .iter()
+                        .map(|name| type_var(operation, name))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    equal(&vars, &mut relations);
+                }
+                Trait::TypesMatchWith { lhs, rhs, transformer } => relations.push((
+                    type_var(operation, rhs)?,
+                    type_var(operation, lhs)?,
+                    Transform::from_transformer(transformer),
+                    transformer.clone(),
+                )),
+                _ => {}
+            }
+        }
+
+        let is_known = |var: Var, operand_types: &[Option<TypeSource>], result_types: &[Option<TypeSource>]| match var {
+            Var::Operand(index) => operand_types[index].is_some(),
+            Var::Result(index) => result_types[index].is_some(),
+            Var::Attribute(_) => true,
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (target, source, transform, _) in &relations {
+                let Some(transform) = transform else { continue };
+                if is_known(*target, &operand_types, &result_types)
+                    || !is_known(*source, &operand_types, &result_types)
+                    || !self.can_infer(operation, *target, *source)
+                {
+                    continue;
+                }
+                let inferred = Some(TypeSource::Infer {
+                    from: *source,
+                    transform: *transform,
+                });
+                match target {
+                    Var::Operand(index) => operand_types[*index] = inferred,
+                    Var::Result(index) => result_types[*index] = inferred,
+                    _ => continue,
+                }
+                self.inferred.push(*target);
+                changed = true;
+            }
+        }
+
+        let unresolved = |var: Var, kind: &'static str, index: usize, name: &str| {
+            if let Some((_, _, _, transformer)) = relations
+                .iter()
+                .find(|(target, _, transform, _)| *target == var && transform.is_none())
+            {
+                return FormatError::Unsupported(format!("type transformer {transformer:?}"));
+            }
+            if kind == "result" && operation.has_interface("InferTypeOpInterface") {
+                return FormatError::Unsupported("'InferTypeOpInterface' result type inference".to_string());
+            }
+            FormatError::TypeNotInferred {
+                kind,
+                index,
+                name: name.to_string(),
+            }
+        };
+        for (index, source) in operand_types.into_iter().enumerate() {
+            let name = &operation.operands[index].name;
+            self.operand_types
+                .push(source.ok_or_else(|| unresolved(Var::Operand(index), "operand", index, name))?);
+        }
+        for (index, source) in result_types.into_iter().enumerate() {
+            let name = &operation.results[index].name;
+            self.result_types
+                .push(source.ok_or_else(|| unresolved(Var::Result(index), "result", index, name))?);
+        }
+        Ok(())
+    }
+
+    /// The `operands` directive and the collectively parsed types don't know the group sizes.
+    fn verify_collective(&self, operation: &Operation) -> Result<(), FormatError> {
+        if self.all_operands && operation.has_trait("AttrSizedOperandSegments") {
+            return Err(FormatError::Unsupported("'AttrSizedOperandSegments' with the 'operands' directive".to_string()));
+        }
+        if self.all_result_types && operation.has_trait("AttrSizedResultSegments") {
+            return Err(FormatError::Unsupported("'AttrSizedResultSegments' with the 'type(results)' directive".to_string()));
+        }
+        let variable = self.operand_types.iter().zip(&operation.operands).find(|(source, operand)| {
+            self.all_operands && **source != TypeSource::Parsed && operand.variadicity != Variadicity::Single
+        });
+        if let Some((_, operand)) = variable {
+            return Err(FormatError::Unsupported(format!(
+                "unparsed type of the variable length operand {:?} with the 'operands' directive",
+                operand.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// The collectively parsed types are sliced by the preceding single operands or results only,
+    /// the variadic results need a variadic source to know their count.
+    fn can_infer(&self, operation: &Operation, target: Var, source: Var) -> bool {
+        let single = |groups: &[crate::ods::NamedTypeConstraint], index: usize| {
+            groups[..index]
+                .iter()
+                .all(|group| group.variadicity == Variadicity::Single)
+        };
+        let sliceable = match source {
+            Var::Operand(index) => !self.all_operand_types || single(&operation.operands, index),
+            Var::Result(index) => !self.all_result_types || single(&operation.results, index),
+            Var::Attribute(_) => true,
+        };
+        let countable = match target {
+            Var::Operand(index) => !self.all_operands || operation.operands[index].variadicity == Variadicity::Single,
+            Var::Result(index) => match operation.results[index].variadicity {
+                Variadicity::Single => true,
+                _ => matches!(source, Var::Operand(source) if operation.operands[source].variadicity.is_variable_length())
+                    || matches!(source, Var::Result(source) if operation.results[source].variadicity.is_variable_length()),
+            },
+            Var::Attribute(_) => false,
+        };
+        sliceable && countable
+    }
+}
+
+/// Operand, result or attribute of the type constraint traits.
+fn type_var(operation: &Operation, name: &str) -> Result<Var, FormatError> {
+    operation
+        .operand(name)
+        .map(Var::Operand)
+        .or_else(|| operation.result(name).map(Var::Result))
+        .or_else(|| operation.attribute(name).map(Var::Attribute))
+        .ok_or_else(|| FormatError::UnknownVariable(name.to_string()))
+}
+
+impl Binder<'_> {
+    fn bind(&mut self, element: &Element, context: Context) -> Result<FormatElement, FormatError> {
+        let bound = match element {
+            Element::Literal(literal) => {
+                if context != Context::TopLevel {
+                    return Err(FormatError::Invalid(format!("literal {literal:?} is not valid here")));
+                }
+                FormatElement::Literal(literal.clone())
+            }
+            Element::Whitespace(space) => FormatElement::Whitespace(space.clone()),
+            Element::Newline => FormatElement::Newline,
+            Element::Variable(name) => self.bind_variable(name, context)?,
+            Element::AttrDict { with_keyword } => {
+                if context == Context::Ref {
+                    if !self.attr_dict {
+                        return Err(FormatError::MissingAttrDict);
+                    }
+                } else if std::mem::replace(&mut self.attr_dict, true) {
+                    return Err(FormatError::DuplicateAttrDict);
+                }
+                FormatElement::AttrDict {
+                    with_keyword: *with_keyword,
+                }
+            }
+            Element::Operands => {
+                self.bind_all(context, |binder| &mut binder.all_operands, "operands")?;
+                FormatElement::Operands
+            }
+            Element::Regions => {
+                self.bind_all(context, |binder| &mut binder.all_regions, "regions")?;
+                FormatElement::Regions
+            }
+            Element::Successors => {
+                self.bind_all(context, |binder| &mut binder.all_successors, "successors")?;
+                FormatElement::Successors
+            }
+            Element::Results => {
+                return Err(FormatError::Invalid(
+                    "'results' directive can only be used as a child to a 'type' directive".to_string(),
+                ))
+            }
+            Element::Type(arg) => FormatElement::Type {
+                arg: self.bind_type(arg, context)?,
+                qualified: false,
+            },
+            Element::Qualified(arg) => match self.bind(arg, context)? {
+                FormatElement::Attribute { index, .. } => FormatElement::Attribute { index, qualified: true },
+                FormatElement::Type { arg, .. } => FormatElement::Type { arg, qualified: true },
+                _ => {
+                    return Err(FormatError::Invalid(
+                        "'qualified' directive expects an attribute or a 'type' directive".to_string(),
+                    ))
+                }
+            },
+            Element::FunctionalType(inputs, results) => {
+                if context != Context::TopLevel {
+                    return Err(FormatError::Invalid("'functional-type' is only valid as a top-level directive".to_string()));
+                }
+                FormatElement::FunctionalType {
+                    inputs: self.bind_type(inputs, Context::TopLevel)?,
+                    results: self.bind_type(results, Context::TopLevel)?,
+                }
+            }
+            Element::Custom { name, params } => {
+                if context != Context::TopLevel {
+                    return Err(FormatError::Invalid("'custom' is only valid as a top-level directive".to_string()));
+                }
+                if !self.hooks.contains(name) {
+                    return Err(FormatError::MissingHook(name.clone()));
+                }
+                let params = params
+                    .iter()
+                    .map(|param| match param {
+                        Element::Ref(arg) => Ok(CustomParam {
+                            element: self.bind(arg, Context::Ref)?,
+                            is_ref: true,
+                        }),
+                        param => Ok(CustomParam {
+                            element: self.bind(param, Context::Custom)?,
+                            is_ref: false,
+                        }),
+                    })
+                    .collect::<Result<Vec<_>, FormatError>>()?;
+                FormatElement::Custom {
+                    name: name.clone(),
+                    params,
+                }
+            }
+            Element::Ref(_) => {
+                return Err(FormatError::Invalid(
+                    "'ref' is only valid within a `custom` directive".to_string(),
+                ))
+            }
+            Element::Optional {
+                then_elements,
+                else_elements,
+                anchor,
+            } => self.bind_optional(then_elements, else_elements, *anchor, context)?,
+            Element::Params | Element::Struct(_) => {
+                return Err(FormatError::Invalid(
+                    "'params' and 'struct' directives are only valid in the attribute and type formats".to_string(),
+                ))
+            }
+            Element::Unsupported(directive) => return Err(FormatError::Unsupported(directive.clone())),
+        };
+        Ok(bound)
+    }
+
+    fn bind_variable(&mut self, name: &str, context: Context) -> Result<FormatElement, FormatError> {
+        let operation = self.operation;
+        let mark = |bound: &mut bool| -> Result<(), FormatError> {
+            match context {
+                Context::Ref if !*bound => Err(FormatError::Invalid(format!(
+                    "'ref' of {name:?} is not bound by a prior directive"
+                ))),
+                Context::Ref => Ok(()),
+                _ if std::mem::replace(bound, true) => Err(FormatError::AlreadyBound(name.to_string())),
+                _ => Ok(()),
+            }
+        };
+        if let Some(index) = operation.operand(name) {
+            if operation.operands[index].variadicity == Variadicity::VariadicOfVariadic {
+                return Err(FormatError::Unsupported(format!("'VariadicOfVariadic' operand {name:?}")));
+            }
+            if self.all_operands && context != Context::Ref {
+                return Err(FormatError::AlreadyBound(name.to_string()));
+            }
+            mark(&mut self.operands[index])?;
+            return Ok(FormatElement::Operand(index));
+        }
+        if let Some(index) = operation.attribute(name) {
+            mark(&mut self.attributes[index])?;
+            return Ok(FormatElement::Attribute { index, qualified: false });
+        }
+        if let Some(index) = operation.region(name) {
+            if self.all_regions && context != Context::Ref {
+                return Err(FormatError::AlreadyBound(name.to_string()));
+            }
+            mark(&mut self.regions[index])?;
+            return Ok(FormatElement::Region(index));
+        }
+        if let Some(index) = operation.successor(name) {
+            if self.all_successors && context != Context::Ref {
+                return Err(FormatError::AlreadyBound(name.to_string()));
+            }
+            mark(&mut self.successors[index])?;
+            return Ok(FormatElement::Successor(index));
+        }
+        if operation.result(name).is_some() {
+            return Err(FormatError::Invalid(format!(
+                "result variable {name:?} can only be used as a child to a 'type' directive"
+            )));
+        }
+        Err(FormatError::UnknownVariable(name.to_string()))
+    }
+
+    fn bind_all(
+        &mut self,
+        context: Context,
+        all: impl Fn(&mut Self) -> &mut bool,
+        directive: &str,
+    ) -> Result<(), FormatError> {
+        let bound = match directive {
+            "operands" => self.operands.iter().any(|bound| *bound),
+            "regions" => self.regions.iter().any(|bound| *bound),
+            _ => self.successors.iter().any(|bound| *bound),
+        };
+        let all = all(self);
+        match context {
+            Context::Ref if !*all => Err(FormatError::Invalid(format!(
+                "'ref' of '{directive}' is not bound by a prior directive"
+            ))),
+            Context::Ref => Ok(()),
+            _ if *all || bound => Err(FormatError::AlreadyBound(directive.to_string())),
+            _ => {
+                *all = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn bind_type(&mut self, arg: &Element, context: Context) -> Result<TypeArg, FormatError> {
+        let is_ref = context == Context::Ref;
+        let mark = |bound: &mut bool, name: &str| -> Result<(), FormatError> {
+            match (is_ref, std::mem::replace(bound, true)) {
+                (true, false) => Err(FormatError::Invalid(format!(
+                    "'ref' of 'type({name})' is not bound by a prior directive"
+                ))),
+                (false, true) => Err(FormatError::AlreadyBound(format!("type({name})"))),
+                _ => Ok(()),
+            }
+        };
+        match arg {
+            Element::Operands => {
+                if !is_ref && self.operand_types.iter().any(|bound| *bound) {
+                    return Err(FormatError::AlreadyBound("type(operands)".to_string()));
+                }
+                mark(&mut self.all_operand_types, "operands")?;
+                Ok(TypeArg::Operands)
+            }
+            Element::Results => {
+                if !is_ref && self.result_types.iter().any(|bound| *bound) {
+                    return Err(FormatError::AlreadyBound("type(results)".to_string()));
+                }
+                mark(&mut self.all_result_types, "results")?;
+                Ok(TypeArg::Results)
+            }
+            Element::Variable(name) => {
+                if let Some(index) = self.operation.operand(name) {
+                    if self.all_operand_types && !is_ref {
+                        return Err(FormatError::AlreadyBound(format!("type({name})")));
+                    }
+                    mark(&mut self.operand_types[index], name)?;
+                    Ok(TypeArg::Operand(index))
+                } else if let Some(index) = self.operation.result(name) {
+                    if self.all_result_types && !is_ref {
+                        return Err(FormatError::AlreadyBound(format!("type({name})")));
+                    }
+                    mark(&mut self.result_types[index], name)?;
+                    Ok(TypeArg::Result(index))
+                } else {
+                    Err(FormatError::Invalid(format!(
+                        "type directive can only refer to variables, got {name:?}"
+                    )))
+                }
+            }
+            _ => Err(FormatError::Invalid(
+                "'type' directive expects an operand, a result, 'operands' or 'results'".to_string(),
+            )),
+        }
+    }
+
+    fn bind_optional(
+        &mut self,
+        then_elements: &[Element],
+        else_elements: &[Element],
+        anchor: Option<usize>,
+        context: Context,
+    ) -> Result<FormatElement, FormatError> {
+        if context != Context::TopLevel {
+            return Err(FormatError::Invalid("optional groups can only be used as top-level elements".to_string()));
+        }
+        let anchor = anchor.ok_or_else(|| FormatError::Invalid("optional group has no anchor element".to_string()))?;
+        let then_bound = then_elements
+            .iter()
+            .map(|element| self.bind(element, Context::TopLevel))
+            .collect::<Result<Vec<_>, _>>()?;
+        let else_bound = else_elements
+            .iter()
+            .map(|element| self.bind(element, Context::TopLevel))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.verify_anchor(&then_bound[anchor])?;
+        let first = then_bound
+            .iter()
+            .find(|element| !matches!(element, FormatElement::Whitespace(_) | FormatElement::Newline))
+            .ok_or_else(|| FormatError::Invalid("optional group has no parsable element".to_string()))?;
+        match first {
+            FormatElement::Literal(_)
+            | FormatElement::Operand(_)
+            | FormatElement::Attribute { .. }
+            | FormatElement::Region(_)
+            | FormatElement::Successor(_)
+            | FormatElement::Operands
+            | FormatElement::Regions
+            | FormatElement::Successors
+            | FormatElement::Type { .. } => {}
+            FormatElement::Custom { name, .. } => {
+                return Err(FormatError::Unsupported(format!(
+                    "custom directive {name:?} as the first element of an optional group"
+                )))
+            }
+            _ => {
+                return Err(FormatError::Invalid(
+                    "first parsable element of an optional group must be a literal, variable, or type directive"
+                        .to_string(),
+                ))
+            }
+        }
+        Ok(FormatElement::Optional {
+            then_elements: then_bound,
+            else_elements: else_bound,
+            anchor,
+        })
+    }
+
+    /// The anchor must be optionally present, like the optional or variadic variables.
+    fn verify_anchor(&self, anchor: &FormatElement) -> Result<(), FormatError> {
+        let operation = self.operation;
+        let optional = match anchor {
+            FormatElement::Operand(index) => operation.operands[*index].variadicity.is_variable_length(),
+            FormatElement::Type {
+                arg: TypeArg::Operand(index),
+                ..
+            } => operation.operands[*index].variadicity.is_variable_length(),
+            FormatElement::Type {
+                arg: TypeArg::Result(index),
+                ..
+            } => operation.results[*index].variadicity.is_variable_length(),
+            FormatElement::Attribute { index, .. } => {
+                let constraint = &operation.attributes[*index].constraint;
+                constraint.optional || constraint.is_unit() || constraint.default_value.is_some()
+            }
+            FormatElement::Region(_) => true,
+            FormatElement::Successor(index) => operation.successors[*index].variadic,
+            FormatElement::Operands | FormatElement::Regions | FormatElement::Successors => true,
+            FormatElement::Custom { name, .. } => {
+                return Err(FormatError::Unsupported(format!("custom directive {name:?} as an optional group anchor")))
+            }
+            _ => false,
+        };
+        if optional {
+            Ok(())
+        } else {
+            Err(FormatError::Invalid(
+                "only variables and types can be used to anchor an optional group".to_string(),
+            ))
+        }
+    }
+
+    /// Every operand, region and successor must be printed, like `verifyOperands`.
+    fn verify(&self) -> Result<(), FormatError> {
+        if !self.attr_dict {
+            return Err(FormatError::MissingAttrDict);
+        }
+        let operation = self.operation;
+        let missing = |kind: &'static str, bound: &[bool], all: bool, names: &mut dyn Iterator<Item = &str>| {
+            match (all, bound.iter().position(|bound| !bound)) {
+                (false, Some(index)) => Err(FormatError::NotFound {
+                    kind,
+                    index,
+                    name: names.nth(index).unwrap_or_default().to_string(),
+                }),
+                _ => Ok(()),
+            }
+        };
+        missing(
+            "operand",
+            &self.operands,
+            self.all_operands,
+            &mut operation.operands.iter().map(|operand| operand.name.as_str()),
+        )?;
+        missing(
+            "region",
+            &self.regions,
+            self.all_regions,
+            &mut operation.regions.iter().map(|region| region.name.as_str()),
+        )?;
+        missing(
+            "successor",
+            &self.successors,
+            self.all_successors,
+            &mut operation.successors.iter().map(|successor| successor.name.as_str()),
+        )?;
+        for (index, bound) in self.attributes.iter().enumerate() {
+            let attribute = &operation.attributes[index];
+            if *bound && attribute.constraint.bit_enum {
+                return Err(FormatError::Unsupported(format!("bit enum attribute {:?}", attribute.name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_format::grammar;
+    use crate::ods::tests::operations;
+
+    fn format(name: &str) -> Result<Format, FormatError> {
+        let operations = operations("TestOps.td");
+        let operation = operations
+            .iter()
+            .find(|operation| operation.name == name)
+            .unwrap();
+        let elements = grammar::parse(operation.assembly_format.as_deref().unwrap())?;
+        Format::new(operation, &elements, &["CustomDirectiveOperands".to_string()])
+    }
+
+    #[test]
+    fn should_transform_types() {
+        assert_eq!(Transform::Identity.apply("ctx", "x.r#type(ctx)"), "x.r#type(ctx)");
+        assert_eq!(
+            Transform::ElementType.apply("ctx", "x.r#type(ctx)"),
+            "x.r#type(ctx).element_type(ctx).unwrap_or(x.r#type(ctx))"
+        );
+        assert_eq!(Transform::I1SameShape.apply("rewriter", "type3"), "rewriter.i1_same_shape(type3)");
+    }
+
+    #[test]
+    fn should_infer_types() {
+        let cmpi = format("arith.cmpi").unwrap();
+        assert_eq!(
+            cmpi.operand_types,
+            vec![
+                TypeSource::Parsed,
+                TypeSource::Infer {
+                    from: Var::Operand(0),
+                    transform: Transform::Identity
+                }
+            ]
+        );
+        assert_eq!(
+            cmpi.result_types,
+            vec![TypeSource::Infer {
+                from: Var::Operand(0),
+                transform: Transform::I1SameShape
+            }]
+        );
+        assert_eq!(cmpi.elided_attributes, vec!["predicate"]);
+
+        let constant = format("arith.constant").unwrap();
+        assert_eq!(
+            constant.result_types,
+            vec![TypeSource::Infer {
+                from: Var::Attribute(0),
+                transform: Transform::Identity
+            }]
+        );
+
+        let load = format("memref.load").unwrap();
+        assert_eq!(load.operand_types[1], TypeSource::Buildable("index".to_string()));
+        assert_eq!(load.inferred, vec![Var::Result(0)]);
+
+        let call = format("func.call").unwrap();
+        assert!(call.all_result_types && !call.all_operands && !call.all_operand_types);
+    }
+
+    #[test]
+    fn should_verify_formats() {
+        let error = format("test.format_optional_operand_ref").unwrap_err();
+        assert_eq!(error, FormatError::MissingHook("OptionalOperandRef".to_string()));
+        let elements = grammar::parse("$lhs attr-dict").unwrap();
+        let operations = operations("TestOps.td");
+        let addi = operations.iter().find(|operation| operation.name == "arith.addi").unwrap();
+        assert_eq!(
+            Format::new(addi, &elements, &[]).unwrap_err().to_string(),
+            "operand #1, named \"rhs\", not found"
+        );
+        let elements = grammar::parse("$lhs `,` $rhs attr-dict attr-dict").unwrap();
+        assert_eq!(Format::new(addi, &elements, &[]).unwrap_err(), FormatError::DuplicateAttrDict);
+        let elements = grammar::parse("$lhs `,` $rhs attr-dict").unwrap();
+        assert_eq!(
+            Format::new(addi, &elements, &[]).unwrap_err().to_string(),
+            "type of operand #0, named \"lhs\", is not buildable and a buildable type cannot be inferred"
+        );
+    }
+}
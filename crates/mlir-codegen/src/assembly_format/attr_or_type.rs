@@ -0,0 +1,642 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Attribute and type assembly format compiler, mirroring the upstream `AttrOrTypeFormatGen`.
+//!
+
+use crate::assembly_format::grammar::{self, Element};
+use crate::assembly_format::printer::should_emit_space_before;
+use crate::assembly_format::{snake_case, CodeWriter, FormatError};
+
+/// Parameter of the generated attribute or type struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// TableGen parameter name, like `addressSpace`.
+    pub name: String,
+    /// Rust field name, like `address_space`.
+    pub ident: String,
+    /// Rust type, without the `Option` of the optional parameters.
+    pub rust_type: String,
+    pub optional: bool,
+    /// Rust default value expression.
+    pub default: Option<String>,
+}
+
+impl Field {
+    fn may_be_absent(&self) -> bool {
+        self.optional || self.default.is_some()
+    }
+
+    /// Condition of the parameter being printed, like the upstream `shouldPrintParameter`.
+    fn present(&self) -> String {
+        match (&self.default, self.rust_type.as_str()) {
+            (_, _) if self.optional => format!("self.{}.is_some()", self.ident),
+            (Some(default), "bool") if default == "false" => format!("self.{}", self.ident),
+            (Some(default), "bool") if default == "true" => format!("!self.{}", self.ident),
+            (Some(default), "String") => {
+                let literal = default.strip_prefix("String::from(").and_then(|default| default.strip_suffix(')'));
+                format!("self.{} != {}", self.ident, literal.unwrap_or(default))
+            }
+            (Some(default), _) => format!("self.{} != {default}", self.ident),
+            (None, _) => "true".to_string(),
+        }
+    }
+
+    /// The parsed parameter, the absent ones get their default value.
+    fn value(&self) -> String {
+        let ident = &self.ident;
+        match &self.default {
+            _ if self.optional => ident.clone(),
+            Some(default) if default.contains('(') => format!("{ident}.unwrap_or_else(|| {default})"),
+            Some(default) => format!("{ident}.unwrap_or({default})"),
+            None => format!(
+                "{ident}.ok_or_else(|| parser.error({:?}))?",
+                format!("missing required parameter: {}", self.name)
+            ),
+        }
+    }
+}
+
+/// Format element bound to the parameters.
+#[derive(Debug, Clone, PartialEq)]
+enum DefElement {
+    Literal(String),
+    Whitespace(String),
+    Parameter(usize),
+    /// `params`
+    Params(Vec<usize>),
+    /// `struct(params)`
+    Struct(Vec<usize>),
+    Custom { name: String, params: Vec<usize> },
+    Optional {
+        then_elements: Vec<DefElement>,
+        else_elements: Vec<DefElement>,
+        anchor: usize,
+    },
+}
+
+/// Generated `AttrOrTypeDef::print` and `AttrOrTypeDef::parse` bodies.
+#[derive(Debug, Clone)]
+pub struct DefForm {
+    /// `fn print(&self, p: &mut Printer<'_>)` body.
+    pub printer: String,
+    /// `fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, ParseError>` body.
+    pub parser: String,
+}
+
+/// Compiles the attribute or type `assemblyFormat`, the `hooks` are the names of the available custom directives.
+pub fn compile(source: &str, fields: &[Field], hooks: &[String], hooks_path: &str) -> Result<DefForm, FormatError> {
+    let elements = grammar::parse(source)?;
+    let mut binder = Binder {
+        fields,
+        hooks,
+        bound: vec![false; fields.len()],
+    };
+    let elements = elements
+        .iter()
+        .map(|element| binder.bind(element, false))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(index) = binder.bound.iter().position(|bound| !bound) {
+        return Err(FormatError::Invalid(format!(
+            "format is missing reference to parameter: {}",
+            fields[index].name
+        )));
+    }
+
+    let generator = Generator { fields, hooks_path };
+    let mut printer = CodeWriter::default();
+    let mut spacing = Spacing {
+        should_emit_space: true,
+        last_was_punctuation: false,
+    };
+    for element in &elements {
+        generator.print(element, &mut printer, &mut spacing);
+    }
+    if elements.is_empty() {
+        printer.line("let _ = p;");
+    }
+
+    let mut parser = CodeWriter::default();
+    for field in fields {
+        parser.line(format!("let mut {}: Option<{}> = None;", field.ident, field.rust_type));
+    }
+    for element in &elements {
+        generator.parse(element, &mut parser);
+    }
+    parser.append(&generator.construct());
+    Ok(DefForm {
+        printer: printer.finish(),
+        parser: parser.finish(),
+    })
+}
+
+/// Name of the directive the attribute and type formats don't have.
+fn operation_directive(element: &Element) -> &str {
+    match element {
+        Element::AttrDict { with_keyword: false } => "'attr-dict' directive",
+        Element::AttrDict { with_keyword: true } => "'attr-dict-with-keyword' directive",
+        Element::Operands => "'operands' directive",
+        Element::Results => "'results' directive",
+        Element::Regions => "'regions' directive",
+        Element::Successors => "'successors' directive",
+        Element::Type(_) => "'type' directive",
+        Element::FunctionalType(..) => "'functional-type' directive",
+        Element::Ref(_) => "'ref' directive",
+        Element::Unsupported(directive) => directive,
+        _ => "element",
+    }
+}
+
+struct Binder<'a> {
+    fields: &'a [Field],
+    hooks: &'a [String],
+    bound: Vec<bool>,
+}
+
+impl Binder<'_> {
+    fn bind(&mut self, element: &Element, optional: bool) -> Result<DefElement, FormatError> {
+        let bound = match element {
+            Element::Literal(literal) => DefElement::Literal(literal.clone()),
+            Element::Whitespace(space) => DefElement::Whitespace(space.clone()),
+            Element::Variable(name) => DefElement::Parameter(self.bind_parameter(name)?),
+            Element::Qualified(arg) => match arg.as_ref() {
+                Element::Variable(name) => DefElement::Parameter(self.bind_parameter(name)?),
+                _ => {
+                    return Err(FormatError::Invalid(
+                        "'qualified' directive expects a parameter".to_string(),
+                    ))
+                }
+            },
+            Element::Params => {
+                let params = self.bind_all()?;
+                if let Some(index) = params.iter().find(|index| self.fields[**index].may_be_absent()) {
+                    return Err(FormatError::Unsupported(format!(
+                        "optional parameter {:?} of the 'params' directive",
+                        self.fields[*index].name
+                    )));
+                }
+                DefElement::Params(params)
+            }
+            Element::Struct(_) if optional => {
+                return Err(FormatError::Unsupported("'struct' directive in an optional group".to_string()));
+            }
+            Element::Struct(params) => match params.as_slice() {
+                [Element::Params] => DefElement::Struct(self.bind_all()?),
+                params => DefElement::Struct(
+                    params
+                        .iter()
+                        .map(|param| match param {
+                            Element::Variable(name) => self.bind_parameter(name),
+                            _ => Err(FormatError::Invalid(
+                                "'struct' directive expects parameters or 'params'".to_string(),
+                            )),
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+            },
+            Element::Custom { name, params } => {
+                if !self.hooks.contains(name) {
+                    return Err(FormatError::MissingHook(name.clone()));
+                }
+                let params = params
+                    .iter()
+                    .map(|param| match param {
+                        Element::Variable(name) => self.bind_parameter(name),
+                        _ => Err(FormatError::Unsupported(format!(
+                            "custom directive {name:?} argument other than a parameter"
+                        ))),
+                    })
+                    .collect::<Result<_, _>>()?;
+                DefElement::Custom {
+                    name: name.clone(),
+                    params,
+                }
+            }
+            Element::Optional {
+                then_elements,
+                else_elements,
+                anchor,
+            } => self.bind_optional(then_elements, else_elements, *anchor)?,
+            Element::Newline => return Err(FormatError::Unsupported("'\\n' literal".to_string())),
+            element => {
+                return Err(FormatError::Invalid(format!(
+                    "{} is only valid in the operation formats",
+                    operation_directive(element)
+                )))
+            }
+        };
+        Ok(bound)
+    }
+
+    fn bind_parameter(&mut self, name: &str) -> Result<usize, FormatError> {
+        let index = self
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .ok_or_else(|| FormatError::Invalid(format!("{name:?} is not a parameter")))?;
+        if std::mem::replace(&mut self.bound[index], true) {
+            return Err(FormatError::AlreadyBound(name.to_string()));
+        }
+        Ok(index)
+    }
+
+    fn bind_all(&mut self) -> Result<Vec<usize>, FormatError> {
+        let names: Vec<String> = self.fields.iter().map(|field| field.name.clone()).collect();
+        names.iter().map(|name| self.bind_parameter(name)).collect()
+    }
+
+    fn bind_optional(
+        &mut self,
+        then_elements: &[Element],
+        else_elements: &[Element],
+        anchor: Option<usize>,
+    ) -> Result<DefElement, FormatError> {
+        let anchor = anchor.ok_or_else(|| FormatError::Invalid("optional group has no anchor element".to_string()))?;
+        let then_elements = then_elements
+            .iter()
+            .map(|element| self.bind(element, true))
+            .collect::<Result<Vec<_>, _>>()?;
+        let else_elements = else_elements
+            .iter()
+            .map(|element| self.bind(element, true))
+            .collect::<Result<Vec<_>, _>>()?;
+        match &then_elements[anchor] {
+            DefElement::Parameter(index) if self.fields[*index].may_be_absent() => {}
+            DefElement::Parameter(index) => {
+                return Err(FormatError::Invalid(format!(
+                    "optional group anchor must be an optional parameter, got {:?}",
+                    self.fields[*index].name
+                )))
+            }
+            _ => {
+                return Err(FormatError::Invalid(
+                    "optional group anchor must be a parameter".to_string(),
+                ))
+            }
+        }
+        match then_elements.iter().find(|element| !matches!(element, DefElement::Whitespace(_))) {
+            Some(DefElement::Literal(_)) => {}
+            Some(DefElement::Parameter(index)) if self.fields[*index].may_be_absent() => {}
+            _ => {
+                return Err(FormatError::Invalid(
+                    "first parsable element of an optional group must be a literal or an optional parameter"
+                        .to_string(),
+                ))
+            }
+        }
+        Ok(DefElement::Optional {
+            then_elements,
+            else_elements,
+            anchor,
+        })
+    }
+}
+
+/// Whitespace state of the printed elements, tracked at the generation time.
+struct Spacing {
+    should_emit_space: bool,
+    last_was_punctuation: bool,
+}
+
+impl Spacing {
+    fn before_value(&mut self, code: &mut CodeWriter) {
+        if self.should_emit_space || !self.last_was_punctuation {
+            code.line("p.write(\" \");");
+        }
+        self.should_emit_space = true;
+        self.last_was_punctuation = false;
+    }
+}
+
+struct Generator<'a> {
+    fields: &'a [Field],
+    hooks_path: &'a str,
+}
+
+fn is_keyword(literal: &str) -> bool {
+    literal.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic())
+}
+
+impl Generator<'_> {
+    fn print(&self, element: &DefElement, code: &mut CodeWriter, spacing: &mut Spacing) {
+        match element {
+            DefElement::Literal(literal) => {
+                let space = spacing.should_emit_space && should_emit_space_before(literal, spacing.last_was_punctuation);
+                let text = if space { format!(" {literal}") } else { literal.clone() };
+                code.line(format!("p.write({text:?});"));
+                let first = literal.chars().next().unwrap_or_default();
+                spacing.should_emit_space = literal.len() != 1 || !"<({[".contains(first);
+                spacing.last_was_punctuation = first != '_' && !first.is_ascii_alphabetic();
+            }
+            DefElement::Whitespace(space) => {
+                if space.is_empty() {
+                    spacing.last_was_punctuation = true;
+                } else {
+                    code.line("p.write(\" \");");
+                    spacing.last_was_punctuation = false;
+                }
+                spacing.should_emit_space = false;
+            }
+            DefElement::Parameter(index) => {
+                spacing.before_value(code);
+                self.print_value(&self.fields[*index], code);
+            }
+            DefElement::Params(params) => {
+                spacing.before_value(code);
+                for (position, index) in params.iter().enumerate() {
+                    if position > 0 {
+                        code.line("p.write(\", \");");
+                    }
+                    self.print_value(&self.fields[*index], code);
+                }
+            }
+            DefElement::Struct(params) => {
+                spacing.before_value(code);
+                self.print_struct(params, code);
+            }
+            DefElement::Custom { name, params } => {
+                spacing.before_value(code);
+                let mut args = vec!["p".to_string()];
+                args.extend(params.iter().map(|index| format!("&self.{}", self.fields[*index].ident)));
+                code.line(format!("{}::print_{}({});", self.hooks_path, snake_case(name), args.join(", ")));
+            }
+            DefElement::Optional {
+                then_elements,
+                else_elements,
+                anchor,
+            } => {
+                let DefElement::Parameter(index) = &then_elements[*anchor] else {
+                    return;
+                };
+                code.line(format!("if {} {{", self.fields[*index].present()));
+                code.indent();
+                for element in then_elements {
+                    self.print(element, code, spacing);
+                }
+                code.dedent();
+                if else_elements.is_empty() {
+                    code.line("}");
+                } else {
+                    code.line("} else {");
+                    code.indent();
+                    for element in else_elements {
+                        self.print(element, code, spacing);
+                    }
+                    code.dedent();
+                    code.line("}");
+                }
+            }
+        }
+    }
+
+    fn print_value(&self, field: &Field, code: &mut CodeWriter) {
+        if field.optional {
+            code.line(format!("if let Some(value) = &self.{} {{", field.ident));
+            code.line("    value.print(p);");
+            code.line("}");
+        } else {
+            code.line(format!("self.{}.print(p);", field.ident));
+        }
+    }
+
+    /// `name = value, ...` leaving out the absent optional parameters.
+    fn print_struct(&self, params: &[usize], code: &mut CodeWriter) {
+        let fields: Vec<&Field> = params.iter().map(|index| &self.fields[*index]).collect();
+        if !fields.iter().any(|field| field.may_be_absent()) {
+            for (position, field) in fields.iter().enumerate() {
+                let separator = if position > 0 { ", " } else { "" };
+                code.line(format!("p.write({:?});", format!("{separator}{} = ", field.name)));
+                code.line(format!("self.{}.print(p);", field.ident));
+            }
+            return;
+        }
+        code.line("#[allow(unused_assignments)]");
+        code.line("let mut separator = \"\";");
+        for field in fields {
+            let value = match field.optional {
+                true => "value".to_string(),
+                false => format!("self.{}", field.ident),
+            };
+            let conditional = field.may_be_absent();
+            if field.optional {
+                code.line(format!("if let Some(value) = &self.{} {{", field.ident));
+            } else if conditional {
+                code.line(format!("if {} {{", field.present()));
+            }
+            if conditional {
+                code.indent();
+            }
+            code.line("p.write(separator);");
+            code.line(format!("p.write({:?});", format!("{} = ", field.name)));
+            code.line(format!("{value}.print(p);"));
+            code.line("separator = \", \";");
+            if conditional {
+                code.dedent();
+                code.line("}");
+            }
+        }
+    }
+
+    fn parse(&self, element: &DefElement, code: &mut CodeWriter) {
+        match element {
+            DefElement::Literal(literal) if is_keyword(literal) => {
+                code.line(format!("parser.parse_keyword({literal:?})?;"))
+            }
+            DefElement::Literal(literal) => code.line(format!("parser.parse_punct({literal:?})?;")),
+            DefElement::Whitespace(_) => {}
+            DefElement::Parameter(index) => self.parse_value(&self.fields[*index], code),
+            DefElement::Params(params) => {
+                for (position, index) in params.iter().enumerate() {
+                    if position > 0 {
+                        code.line("parser.parse_punct(\",\")?;");
+                    }
+                    self.parse_value(&self.fields[*index], code);
+                }
+            }
+            DefElement::Struct(params) => self.parse_struct(params, code),
+            DefElement::Custom { name, params } => {
+                let mut args = vec!["parser".to_string()];
+                args.extend(params.iter().map(|index| format!("&mut {}", self.fields[*index].ident)));
+                code.line(format!("{}::parse_{}({})?;", self.hooks_path, snake_case(name), args.join(", ")));
+            }
+            DefElement::Optional {
+                then_elements,
+                else_elements,
+                ..
+            } => {
+                let mut elements = then_elements.iter().skip_while(|element| matches!(element, DefElement::Whitespace(_)));
+                match elements.next() {
+                    Some(DefElement::Literal(literal)) if is_keyword(literal) => {
+                        code.line(format!("if parser.parse_optional_keyword({literal:?}) {{"))
+                    }
+                    Some(DefElement::Literal(literal)) => code.line(format!("if parser.parse_optional_punct({literal:?}) {{")),
+                    Some(DefElement::Parameter(index)) => {
+                        let field = &self.fields[*index];
+                        code.line(format!(
+                            "if let Some(value) = <{} as AsmParameter>::parse_optional(parser)? {{",
+                            field.rust_type
+                        ));
+                        code.indent();
+                        code.line(format!("{} = Some(value);", field.ident));
+                        code.dedent();
+                    }
+                    _ => return,
+                }
+                code.indent();
+                for element in elements {
+                    self.parse(element, code);
+                }
+                code.dedent();
+                if else_elements.is_empty() {
+                    code.line("}");
+                } else {
+                    code.line("} else {");
+                    code.indent();
+                    for element in else_elements {
+                        self.parse(element, code);
+                    }
+                    code.dedent();
+                    code.line("}");
+                }
+            }
+        }
+    }
+
+    fn parse_value(&self, field: &Field, code: &mut CodeWriter) {
+        code.line(format!(
+            "{} = Some(<{} as AsmParameter>::parse(parser)?);",
+            field.ident, field.rust_type
+        ));
+    }
+
+    /// Upstream `genStructParser`, the parameters may come in any order.
+    fn parse_struct(&self, params: &[usize], code: &mut CodeWriter) {
+        code.line("let mut count = 0;");
+        code.line(format!(
+            "while count == 0 || (count < {} && parser.parse_optional_punct(\",\")) {{",
+            params.len()
+        ));
+        code.indent();
+        code.line("let Some(key) = parser.parse_optional_identifier() else {");
+        code.indent();
+        code.line("if count > 0 {");
+        code.line("    return Err(parser.error(\"expected struct parameter name\"));");
+        code.line("}");
+        code.line("break;");
+        code.dedent();
+        code.line("};");
+        code.line("parser.parse_punct(\"=\")?;");
+        code.line("match key {");
+        code.indent();
+        for index in params {
+            let field = &self.fields[*index];
+            code.line(format!(
+                "{:?} if {}.is_none() => {} = Some(<{} as AsmParameter>::parse(parser)?),",
+                field.name, field.ident, field.ident, field.rust_type
+            ));
+        }
+        code.line("_ => return Err(parser.error(format!(\"duplicate or unknown struct parameter name: {key}\"))),");
+        code.dedent();
+        code.line("}");
+        code.line("count += 1;");
+        code.dedent();
+        code.line("}");
+    }
+
+    /// `Ok(Self { ... })` out of the parsed parameters.
+    fn construct(&self) -> String {
+        let mut code = CodeWriter::default();
+        if self.fields.is_empty() {
+            code.line("let _ = parser;");
+            code.line("Ok(Self)");
+            return code.finish();
+        }
+        code.line("Ok(Self {");
+        code.indent();
+        for field in self.fields {
+            let value = field.value();
+            match value == field.ident {
+                true => code.line(format!("{value},")),
+                false => code.line(format!("{}: {value},", field.ident)),
+            }
+        }
+        code.dedent();
+        code.line("})");
+        code.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, rust_type: &str) -> Field {
+        Field {
+            name: name.to_string(),
+            ident: snake_case(name),
+            rust_type: rust_type.to_string(),
+            optional: false,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn should_compile_formats() {
+        let address_space = Field {
+            default: Some("0".to_string()),
+            ..field("addressSpace", "u32")
+        };
+        let form = compile("(`<` $addressSpace^ `>`)?", &[address_space.clone()], &[], "hooks").unwrap();
+        assert_eq!(
+            form.printer,
+            "if self.address_space != 0 {\n    p.write(\"<\");\n    self.address_space.print(p);\n    p.write(\">\");\n}\n"
+        );
+        assert!(form.parser.contains("if parser.parse_optional_punct(\"<\") {"));
+        assert!(form.parser.contains("address_space: address_space.unwrap_or(0),"));
+
+        let fields = [field("one", "i64"), field("two", "String")];
+        let form = compile("`<` $one `:` struct($two) `>`", &fields, &[], "hooks").unwrap();
+        assert!(form.printer.contains("p.write(\" :\");\np.write(\" \");\np.write(\"two = \");"));
+        assert!(form.parser.contains("\"two\" if two.is_none() => two = Some(<String as AsmParameter>::parse(parser)?),"));
+    }
+
+    #[test]
+    fn should_verify_formats() {
+        let fields = [field("one", "i64"), field("two", "String")];
+        assert_eq!(
+            compile("`<` $one `>`", &fields, &[], "hooks").unwrap_err(),
+            FormatError::Invalid("format is missing reference to parameter: two".to_string())
+        );
+        assert_eq!(
+            compile("`<` $one $one $two `>`", &fields, &[], "hooks").unwrap_err(),
+            FormatError::AlreadyBound("one".to_string())
+        );
+        assert!(matches!(
+            compile("`<` (`x` $one^)? $two `>`", &fields, &[], "hooks"),
+            Err(FormatError::Invalid(_))
+        ));
+        assert_eq!(
+            compile("`<` custom<Pair>($one, $two) `>`", &fields, &[], "hooks").unwrap_err(),
+            FormatError::MissingHook("Pair".to_string())
+        );
+        assert!(compile("`<` custom<Pair>($one, $two) `>`", &fields, &["Pair".to_string()], "hooks").is_ok());
+        assert!(matches!(
+            compile("$one attr-dict $two", &fields, &[], "hooks"),
+            Err(FormatError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,308 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Assembly format mini-language parsing.
+//!
+
+use winnow::ascii::multispace0;
+use winnow::combinator::*;
+use winnow::token::*;
+use winnow::PResult;
+use winnow::*;
+
+use crate::assembly_format::FormatError;
+
+/// Assembly format element, the variables are bound by [crate::assembly_format::analysis].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    /// `` `keyword` `` or `` `,` ``
+    Literal(String),
+    /// `` ` ` `` or ``` `` ```
+    Whitespace(String),
+    /// `` `\n` ``
+    Newline,
+    /// `$name`
+    Variable(String),
+    /// `attr-dict` or `attr-dict-with-keyword`
+    AttrDict { with_keyword: bool },
+    Operands,
+    Results,
+    Regions,
+    Successors,
+    /// `params` of the attribute and type formats
+    Params,
+    /// `struct(params)` of the attribute and type formats
+    Struct(Vec<Element>),
+    /// `type(arg)`
+    Type(Box<Element>),
+    /// `qualified(arg)`
+    Qualified(Box<Element>),
+    /// `functional-type(inputs, results)`
+    FunctionalType(Box<Element>, Box<Element>),
+    /// `custom<Name>(params)`
+    Custom { name: String, params: Vec<Element> },
+    /// `ref(arg)`
+    Ref(Box<Element>),
+    /// `(then elements)` with the `^` anchor, followed by `?` or `:(else elements)?`
+    Optional {
+        then_elements: Vec<Element>,
+        else_elements: Vec<Element>,
+        anchor: Option<usize>,
+    },
+    /// Directives the code generator doesn't support, like `oilist` or `prop-dict`.
+    Unsupported(String),
+}
+
+fn identifier<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    (
+        take_while(1, ('a'..='z', 'A'..='Z', '_')),
+        take_while(0.., ('a'..='z', 'A'..='Z', '0'..='9', '_')),
+    )
+        .take()
+        .parse_next(input)
+}
+
+fn spaced<'a, O>(parser: impl Parser<&'a str, O, error::ContextError>) -> impl Parser<&'a str, O, error::ContextError> {
+    delimited(multispace0, parser, multispace0)
+}
+
+/// `` `literal` ``
+fn literal(input: &mut &str) -> PResult<Element> {
+    delimited('`', take_till(0.., '`'), '`')
+        .map(|literal: &str| match literal {
+            "" | " " => Element::Whitespace(literal.to_string()),
+            "\\n" => Element::Newline,
+            literal => Element::Literal(literal.to_string()),
+        })
+        .parse_next(input)
+}
+
+/// `$name`
+fn variable(input: &mut &str) -> PResult<Element> {
+    preceded('$', identifier)
+        .map(|name| Element::Variable(name.to_string()))
+        .parse_next(input)
+}
+
+fn argument(input: &mut &str) -> PResult<Element> {
+    delimited(spaced('('), element, spaced(')')).parse_next(input)
+}
+
+/// `custom<Name>(params)`
+fn custom(input: &mut &str) -> PResult<Element> {
+    (
+        delimited(spaced('<'), identifier, spaced('>')),
+        delimited(
+            spaced('('),
+            separated(0.., element, spaced(',')),
+            spaced(')'),
+        ),
+    )
+        .map(|(name, params)| Element::Custom {
+            name: name.to_string(),
+            params,
+        })
+        .parse_next(input)
+}
+
+/// Parenthesized arguments of the unsupported directives.
+fn skipped_arguments(input: &mut &str) -> PResult<()> {
+    let mut depth = 0usize;
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    *input = &input[index + 1..];
+                    return Ok(());
+                }
+            }
+            _ if depth == 0 => return Ok(()),
+            _ => {}
+        }
+    }
+    Err(error::ErrMode::Cut(error::ContextError::new()))
+}
+
+fn directive(input: &mut &str) -> PResult<Element> {
+    let keyword = take_while(1.., ('a'..='z', '-')).parse_next(input)?;
+    match keyword {
+        "attr-dict" => Ok(Element::AttrDict { with_keyword: false }),
+        "attr-dict-with-keyword" => Ok(Element::AttrDict { with_keyword: true }),
+        "operands" => Ok(Element::Operands),
+        "results" => Ok(Element::Results),
+        "regions" => Ok(Element::Regions),
+        "successors" => Ok(Element::Successors),
+        "params" => Ok(Element::Params),
+        "struct" => delimited(
+            spaced('('),
+            separated(1.., element, spaced(',')),
+            spaced(')'),
+        )
+        .map(Element::Struct)
+        .parse_next(input),
+        "type" => argument.map(|arg| Element::Type(Box::new(arg))).parse_next(input),
+        "qualified" => argument.map(|arg| Element::Qualified(Box::new(arg))).parse_next(input),
+        "ref" => argument.map(|arg| Element::Ref(Box::new(arg))).parse_next(input),
+        "functional-type" => delimited(
+            spaced('('),
+            separated_pair(element, spaced(','), element),
+            spaced(')'),
+        )
+        .map(|(inputs, results)| Element::FunctionalType(Box::new(inputs), Box::new(results)))
+        .parse_next(input),
+        "custom" => custom.parse_next(input),
+        "oilist" | "prop-dict" => {
+            let _ = multispace0.parse_next(input)?;
+            skipped_arguments(input)?;
+            Ok(Element::Unsupported(format!("'{keyword}' directive")))
+        }
+        _ => Err(error::ErrMode::Backtrack(error::ContextError::new())),
+    }
+}
+
+/// `(elements)?` or `(elements):(elements)?`, the then elements have a `^` anchor.
+fn optional_group(input: &mut &str) -> PResult<Element> {
+    let then_items: Vec<(Element, Option<char>)> = delimited(
+        spaced('('),
+        repeat(1.., (element, opt(spaced('^')))),
+        spaced(')'),
+    )
+    .parse_next(input)?;
+    let else_elements: Option<Vec<Element>> = opt(preceded(
+        spaced(':'),
+        delimited(spaced('('), repeat(1.., element), spaced(')')),
+    ))
+    .parse_next(input)?;
+    spaced('?').parse_next(input)?;
+    let anchor = then_items.iter().position(|(_, anchor)| anchor.is_some());
+    Ok(Element::Optional {
+        then_elements: then_items.into_iter().map(|(element, _)| element).collect(),
+        else_elements: else_elements.unwrap_or_default(),
+        anchor,
+    })
+}
+
+fn element(input: &mut &str) -> PResult<Element> {
+    spaced(alt((literal, variable, optional_group, directive))).parse_next(input)
+}
+
+/// Parses the assembly format elements.
+pub fn parse(source: &str) -> Result<Vec<Element>, FormatError> {
+    let mut input = source;
+    let elements: Vec<Element> = repeat(0.., element)
+        .parse_next(&mut input)
+        .map_err(|_| FormatError::Syntax(input.to_string()))?;
+    if !input.trim().is_empty() {
+        return Err(FormatError::Syntax(input.trim().to_string()));
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Element {
+        Element::Variable(name.to_string())
+    }
+
+    fn lit(literal: &str) -> Element {
+        Element::Literal(literal.to_string())
+    }
+
+    #[test]
+    fn should_parse_formats() {
+        assert_eq!(
+            parse("$lhs `,` $rhs attr-dict `:` type($result)"),
+            Ok(vec![
+                var("lhs"),
+                lit(","),
+                var("rhs"),
+                Element::AttrDict { with_keyword: false },
+                lit(":"),
+                Element::Type(Box::new(var("result"))),
+            ])
+        );
+        assert_eq!(
+            parse("$callee `(` $operands `)` attr-dict `:` functional-type($operands, results)"),
+            Ok(vec![
+                var("callee"),
+                lit("("),
+                var("operands"),
+                lit(")"),
+                Element::AttrDict { with_keyword: false },
+                lit(":"),
+                Element::FunctionalType(Box::new(var("operands")), Box::new(Element::Results)),
+            ])
+        );
+        assert_eq!(
+            parse("attr-dict-with-keyword `\\n` ` ` `` qualified(type($x))"),
+            Ok(vec![
+                Element::AttrDict { with_keyword: true },
+                Element::Newline,
+                Element::Whitespace(" ".to_string()),
+                Element::Whitespace("".to_string()),
+                Element::Qualified(Box::new(Element::Type(Box::new(var("x"))))),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_parse_optional_groups() {
+        assert_eq!(
+            parse("(`then` $present^):(`else`)? custom<Operands>($a, ref(type($b)), attr-dict)"),
+            Ok(vec![
+                Element::Optional {
+                    then_elements: vec![lit("then"), var("present")],
+                    else_elements: vec![lit("else")],
+                    anchor: Some(1),
+                },
+                Element::Custom {
+                    name: "Operands".to_string(),
+                    params: vec![
+                        var("a"),
+                        Element::Ref(Box::new(Element::Type(Box::new(var("b"))))),
+                        Element::AttrDict { with_keyword: false },
+                    ],
+                },
+            ])
+        );
+        assert_eq!(
+            parse("oilist(`a` $a | `b` $b) attr-dict"),
+            Ok(vec![
+                Element::Unsupported("'oilist' directive".to_string()),
+                Element::AttrDict { with_keyword: false },
+            ])
+        );
+        assert_eq!(
+            parse("`<` $one `:` struct($two, $four) `:` params `>`"),
+            Ok(vec![
+                lit("<"),
+                var("one"),
+                lit(":"),
+                Element::Struct(vec![var("two"), var("four")]),
+                lit(":"),
+                Element::Params,
+                lit(">"),
+            ])
+        );
+        assert!(matches!(parse("$a `,"), Err(FormatError::Syntax(_))));
+        assert!(matches!(parse("unknown($a)"), Err(FormatError::Syntax(_))));
+    }
+}
@@ -0,0 +1,602 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Generates the custom form parser, mirroring the upstream `OperationFormat::genParser`.
+//!
+
+use crate::assembly_format::analysis::{CustomParam, Format, FormatElement, Transform, TypeArg, TypeSource, Var};
+use crate::assembly_format::printer::enum_cases;
+use crate::assembly_format::{snake_case, CodeWriter};
+use crate::ods::{AttrStyle, Operation, Variadicity};
+
+struct Parser<'a> {
+    operation: &'a Operation,
+    format: &'a Format,
+    hooks_path: &'a str,
+}
+
+/// Generates the `fn(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError>` body.
+pub fn generate(operation: &Operation, format: &Format, hooks_path: &str) -> String {
+    let parser = Parser {
+        operation,
+        format,
+        hooks_path,
+    };
+    let mut code = CodeWriter::default();
+    parser.declarations(&mut code);
+    for element in &format.elements {
+        parser.element(element, &mut code);
+    }
+    parser.resolution(&mut code);
+    code.line("state.move_into_properties(parser.ctx(), Self::ATTRIBUTE_NAMES);");
+    code.line("Ok(())");
+    code.finish()
+}
+
+impl Parser<'_> {
+    fn operand(&self, index: usize) -> String {
+        format!("operand_{}", snake_case(&self.operation.operands[index].name))
+    }
+
+    fn operand_types(&self, index: usize) -> String {
+        format!("operand_{}_types", snake_case(&self.operation.operands[index].name))
+    }
+
+    fn result_types(&self, index: usize) -> String {
+        format!("result_{}_types", snake_case(&self.operation.results[index].name))
+    }
+
+    fn attribute(&self, index: usize) -> String {
+        format!("attribute_{}", snake_case(&self.operation.attributes[index].name))
+    }
+
+    fn region(&self, index: usize) -> String {
+        format!("region_{}", snake_case(&self.operation.regions[index].name))
+    }
+
+    fn successor(&self, index: usize) -> String {
+        format!("successor_{}", snake_case(&self.operation.successors[index].name))
+    }
+
+    fn types(&self, arg: TypeArg) -> String {
+        match arg {
+            TypeArg::Operand(index) => self.operand_types(index),
+            TypeArg::Result(index) => self.result_types(index),
+            TypeArg::Operands => "all_operand_types".to_string(),
+            TypeArg::Results => "all_result_types".to_string(),
+        }
+    }
+
+    fn type_variadicity(&self, arg: TypeArg) -> Variadicity {
+        match arg {
+            TypeArg::Operand(index) => self.operation.operands[index].variadicity,
+            TypeArg::Result(index) => self.operation.results[index].variadicity,
+            _ => Variadicity::Variadic,
+        }
+    }
+
+    /// Dialect of the single `TypeDef` values, parsed without the `!dialect.` prefix.
+    fn type_dialect(&self, arg: TypeArg, qualified: bool) -> Option<&str> {
+        let value = match arg {
+            TypeArg::Operand(index) => &self.operation.operands[index],
+            TypeArg::Result(index) => &self.operation.results[index],
+            _ => return None,
+        };
+        if qualified || value.variadicity != Variadicity::Single {
+            return None;
+        }
+        value.constraint.dialect.as_deref()
+    }
+
+    fn declarations(&self, code: &mut CodeWriter) {
+        let operation = self.operation;
+        let format = self.format;
+        if format.all_operands {
+            code.line("let mut all_operands: Vec<UnresolvedOperand> = Vec::new();");
+        } else {
+            for index in 0..operation.operands.len() {
+                code.line(format!("let mut {}: Vec<UnresolvedOperand> = Vec::new();", self.operand(index)));
+            }
+        }
+        if format.all_operand_types {
+            code.line("let mut all_operand_types: Vec<Type> = Vec::new();");
+        } else {
+            for (index, source) in format.operand_types.iter().enumerate() {
+                if *source == TypeSource::Parsed {
+                    code.line(format!("let mut {}: Vec<Type> = Vec::new();", self.operand_types(index)));
+                }
+            }
+        }
+        if format.all_result_types {
+            code.line("let mut all_result_types: Vec<Type> = Vec::new();");
+        } else {
+            for (index, source) in format.result_types.iter().enumerate() {
+                if *source == TypeSource::Parsed {
+                    code.line(format!("let mut {}: Vec<Type> = Vec::new();", self.result_types(index)));
+                }
+            }
+        }
+        for index in 0..operation.attributes.len() {
+            if format.elided_attributes.contains(&operation.attributes[index].name) {
+                // The required attributes are assigned unconditionally, so the initial value is never read.
+                code.line("#[allow(unused_assignments)]");
+                code.line(format!("let mut {}: Option<Attribute> = None;", self.attribute(index)));
+            }
+        }
+        if !format.elements.iter().any(|element| contains(element, &FormatElement::Regions)) {
+            for index in 0..operation.regions.len() {
+                code.line(format!("let mut {}: Vec<Vec<Block>> = Vec::new();", self.region(index)));
+            }
+        }
+        if !format.elements.iter().any(|element| contains(element, &FormatElement::Successors)) {
+            for index in 0..operation.successors.len() {
+                code.line(format!("let mut {}: Vec<Block> = Vec::new();", self.successor(index)));
+            }
+        }
+    }
+
+    fn element(&self, element: &FormatElement, code: &mut CodeWriter) {
+        let operation = self.operation;
+        match element {
+            FormatElement::Literal(literal) => {
+                if literal.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+                    code.line(format!("parser.parse_keyword({literal:?})?;"));
+                } else {
+                    code.line(format!("parser.parse_punct({literal:?})?;"));
+                }
+            }
+            FormatElement::Whitespace(_) | FormatElement::Newline => {}
+            FormatElement::AttrDict { with_keyword } => {
+                let method = match with_keyword {
+                    true => "parse_optional_attr_dict_with_keyword",
+                    false => "parse_optional_attr_dict",
+                };
+                code.line(format!("state.attributes.extend(parser.{method}()?);"));
+            }
+            FormatElement::Operand(index) => {
+                let operand = self.operand(*index);
+                match operation.operands[*index].variadicity {
+                    Variadicity::Single => code.line(format!("{operand}.push(parser.parse_operand()?);")),
+                    Variadicity::Optional => code.line(format!("{operand}.extend(parser.parse_optional_operand()?);")),
+                    _ => code.line(format!("{operand}.extend(parser.parse_operand_list()?);")),
+                }
+            }
+            FormatElement::Attribute { index, qualified } => {
+                let constraint = &operation.attributes[*index].constraint;
+                let attribute = self.attribute(*index);
+                let (hoisted, call) = attribute_parser(constraint.style(*qualified), constraint.optional);
+                if let Some(hoisted) = hoisted {
+                    code.line(hoisted);
+                }
+                match constraint.optional {
+                    true => code.line(format!("{attribute} = parser.{call}?;")),
+                    false => code.line(format!("{attribute} = Some(parser.{call}?);")),
+                }
+            }
+            FormatElement::Region(index) => {
+                let region = self.region(*index);
+                match operation.regions[*index].variadic {
+                    true => code.line(format!("{region}.extend(parser.parse_region_list()?);")),
+                    false => code.line(format!("{region}.push(parser.parse_region()?);")),
+                }
+            }
+            FormatElement::Successor(index) => {
+                let successor = self.successor(*index);
+                match operation.successors[*index].variadic {
+                    true => code.line(format!("{successor}.extend(parser.parse_successor_list()?);")),
+                    false => code.line(format!("{successor}.push(parser.parse_successor()?);")),
+                }
+            }
+            FormatElement::Operands => code.line("all_operands.extend(parser.parse_operand_list()?);"),
+            FormatElement::Regions => code.line("state.regions.extend(parser.parse_region_list()?);"),
+            FormatElement::Successors => code.line("state.successors.extend(parser.parse_successor_list()?);"),
+            FormatElement::Type { arg, qualified } => {
+                let types = self.types(*arg);
+                match (self.type_variadicity(*arg), self.type_dialect(*arg, *qualified)) {
+                    (Variadicity::Single, Some(dialect)) => {
+                        code.line(format!("{types}.push(parser.parse_stripped_type({dialect:?})?);"))
+                    }
+                    (Variadicity::Single, None) => code.line(format!("{types}.push(parser.parse_type()?);")),
+                    (Variadicity::Optional, _) => code.line(format!("{types}.extend(parser.parse_optional_type()?);")),
+                    _ => code.line(format!("{types}.extend(parser.parse_type_list()?);")),
+                }
+            }
+            FormatElement::FunctionalType { inputs, results } => {
+                code.line("let (inputs, results) = parser.parse_functional_type()?;");
+                code.line(format!("{}.extend(inputs);", self.types(*inputs)));
+                code.line(format!("{}.extend(results);", self.types(*results)));
+            }
+            FormatElement::Custom { name, params } => {
+                let mut args = vec!["parser".to_string()];
+                for param in params {
+                    args.push(self.custom_arg(param));
+                }
+                code.line(format!(
+                    "{}::parse_{}({})?;",
+                    self.hooks_path,
+                    snake_case(name),
+                    args.join(", ")
+                ));
+            }
+            FormatElement::Optional {
+                then_elements,
+                else_elements,
+                anchor,
+            } => self.optional(then_elements, else_elements, *anchor, code),
+        }
+    }
+
+    fn custom_arg(&self, param: &CustomParam) -> String {
+        let (borrow, list) = match param.is_ref {
+            true => ("", ".as_slice()"),
+            false => ("&mut ", ""),
+        };
+        match &param.element {
+            FormatElement::Operand(index) => format!("{borrow}{}{list}", self.operand(*index)),
+            FormatElement::Attribute { index, .. } if param.is_ref => self.attribute(*index),
+            FormatElement::Attribute { index, .. } => format!("&mut {}", self.attribute(*index)),
+            FormatElement::Region(index) => format!("{borrow}{}{list}", self.region(*index)),
+            FormatElement::Successor(index) => format!("{borrow}{}{list}", self.successor(*index)),
+            FormatElement::Operands => format!("{borrow}all_operands{list}"),
+            FormatElement::Regions => format!("{borrow}state.regions{list}"),
+            FormatElement::Successors => format!("{borrow}state.successors{list}"),
+            FormatElement::Type { arg, .. } => format!("{borrow}{}{list}", self.types(*arg)),
+            FormatElement::AttrDict { .. } => format!("{borrow}state.attributes{list}"),
+            _ => "()".to_string(),
+        }
+    }
+
+    /// Upstream `genOptionalGroupParser`, the first parsable element decides the group presence.
+    fn optional(
+        &self,
+        then_elements: &[FormatElement],
+        else_elements: &[FormatElement],
+        anchor: usize,
+        code: &mut CodeWriter,
+    ) {
+        let operation = self.operation;
+        let first = then_elements
+            .iter()
+            .position(|element| !matches!(element, FormatElement::Whitespace(_) | FormatElement::Newline))
+            .unwrap_or_default();
+        let mut rest = vec![];
+        match &then_elements[first] {
+            FormatElement::Literal(literal) => {
+                if literal.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+                    code.line(format!("if parser.parse_optional_keyword({literal:?}) {{"));
+                } else {
+                    code.line(format!("if parser.parse_optional_punct({literal:?}) {{"));
+                }
+            }
+            FormatElement::Operand(index) => {
+                let operand = self.operand(*index);
+                code.line("if let Some(operand) = parser.parse_optional_operand()? {");
+                rest.push(format!("{operand}.push(operand);"));
+                if operation.operands[*index].variadicity == Variadicity::Variadic {
+                    rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                    rest.push(format!("    {operand}.push(parser.parse_operand()?);"));
+                    rest.push("}".to_string());
+                }
+            }
+            FormatElement::Operands => {
+                code.line("if let Some(operand) = parser.parse_optional_operand()? {");
+                rest.push("all_operands.push(operand);".to_string());
+                rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                rest.push("    all_operands.push(parser.parse_operand()?);".to_string());
+                rest.push("}".to_string());
+            }
+            FormatElement::Attribute { index, qualified } => {
+                let constraint = &operation.attributes[*index].constraint;
+                let (hoisted, call) = attribute_parser(constraint.style(*qualified), true);
+                if let Some(hoisted) = hoisted {
+                    code.line(hoisted);
+                }
+                code.line(format!("if let Some(attribute) = parser.{call}? {{"));
+                rest.push(format!("{} = Some(attribute);", self.attribute(*index)));
+            }
+            FormatElement::Region(index) => {
+                let region = self.region(*index);
+                code.line("if let Some(region) = parser.parse_optional_region()? {");
+                rest.push(format!("{region}.push(region);"));
+                if operation.regions[*index].variadic {
+                    rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                    rest.push(format!("    {region}.push(parser.parse_region()?);"));
+                    rest.push("}".to_string());
+                }
+            }
+            FormatElement::Regions => {
+                code.line("if let Some(region) = parser.parse_optional_region()? {");
+                rest.push("state.regions.push(region);".to_string());
+                rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                rest.push("    state.regions.push(parser.parse_region()?);".to_string());
+                rest.push("}".to_string());
+            }
+            FormatElement::Successor(index) => {
+                let successor = self.successor(*index);
+                code.line("if let Some(successor) = parser.parse_optional_successor()? {");
+                rest.push(format!("{successor}.push(successor);"));
+                if operation.successors[*index].variadic {
+                    rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                    rest.push(format!("    {successor}.push(parser.parse_successor()?);"));
+                    rest.push("}".to_string());
+                }
+            }
+            FormatElement::Successors => {
+                code.line("if let Some(successor) = parser.parse_optional_successor()? {");
+                rest.push("state.successors.push(successor);".to_string());
+                rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                rest.push("    state.successors.push(parser.parse_successor()?);".to_string());
+                rest.push("}".to_string());
+            }
+            FormatElement::Type { arg, qualified } => {
+                let types = self.types(*arg);
+                match self.type_dialect(*arg, *qualified) {
+                    Some(dialect) => code.line(format!(
+                        "if let Some(r#type) = parser.parse_optional_stripped_type({dialect:?})? {{"
+                    )),
+                    None => code.line("if let Some(r#type) = parser.parse_optional_type()? {"),
+                }
+                rest.push(format!("{types}.push(r#type);"));
+                if self.type_variadicity(*arg) == Variadicity::Variadic {
+                    rest.push("while parser.parse_optional_punct(\",\") {".to_string());
+                    rest.push(format!("    {types}.push(parser.parse_type()?);"));
+                    rest.push("}".to_string());
+                }
+            }
+            _ => code.line("if true {"),
+        }
+        code.indent();
+        for line in rest {
+            code.line(line);
+        }
+        for (index, element) in then_elements.iter().enumerate().skip(first + 1) {
+            match element {
+                FormatElement::Attribute { index: attribute, .. }
+                    if index == anchor && operation.attributes[*attribute].constraint.is_unit() =>
+                {
+                    code.line(format!("{} = Some(parser.ctx().unit_attr());", self.attribute(*attribute)));
+                }
+                element => self.element(element, code),
+            }
+        }
+        code.dedent();
+        if else_elements.is_empty() {
+            code.line("}");
+        } else {
+            code.line("} else {");
+            code.indent();
+            for element in else_elements {
+                self.element(element, code);
+            }
+            code.dedent();
+            code.line("}");
+        }
+    }
+
+    /// Sets the parsed attributes, regions and successors, then resolves the operand and result types,
+    /// like `genParserTypeResolution` and `genParserOperandTypeResolution`.
+    fn resolution(&self, code: &mut CodeWriter) {
+        let operation = self.operation;
+        let format = self.format;
+        for (index, attribute) in operation.attributes.iter().enumerate() {
+            if format.elided_attributes.contains(&attribute.name) {
+                code.line(format!("if let Some(attribute) = {} {{", self.attribute(index)));
+                code.indent();
+                code.line(format!("state.set_attribute({:?}, attribute);", attribute.name));
+                code.dedent();
+                code.line("}");
+            }
+        }
+        if operation.has_trait("AttrSizedOperandSegments") && !format.all_operands {
+            let sizes: Vec<String> = (0..operation.operands.len())
+                .map(|index| format!("{}.len() as i32", self.operand(index)))
+                .collect();
+            segment_sizes(code, "operandSegmentSizes", &sizes);
+        }
+        let regions_directive = format
+            .elements
+            .iter()
+            .any(|element| contains(element, &FormatElement::Regions));
+        if !regions_directive {
+            for index in 0..operation.regions.len() {
+                code.line(format!("state.regions.extend({});", self.region(index)));
+            }
+        }
+        if let Some(terminator) = &operation.implicit_terminator {
+            code.line("for region in &mut state.regions {");
+            code.indent();
+            code.line(format!("parser.ensure_terminator(region, {terminator:?})?;"));
+            code.dedent();
+            code.line("}");
+        }
+        let successors_directive = format
+            .elements
+            .iter()
+            .any(|element| contains(element, &FormatElement::Successors));
+        if !successors_directive {
+            for index in 0..operation.successors.len() {
+                code.line(format!("state.successors.extend({});", self.successor(index)));
+            }
+        }
+
+        for (index, source) in format.operand_types.iter().enumerate() {
+            if let (TypeSource::Buildable(r#type), false) = (source, format.all_operand_types) {
+                let count = match format.all_operands {
+                    true => "1".to_string(),
+                    false => format!("{}.len()", self.operand(index)),
+                };
+                code.line(format!(
+                    "let {}: Vec<Type> = vec![parser.ctx().buildable_type({type:?}); {count}];",
+                    self.operand_types(index)
+                ));
+            }
+        }
+        for (index, source) in format.result_types.iter().enumerate() {
+            if let (TypeSource::Buildable(r#type), false) = (source, format.all_result_types) {
+                code.line(format!(
+                    "let {}: Vec<Type> = vec![parser.ctx().buildable_type({type:?})];",
+                    self.result_types(index)
+                ));
+            }
+        }
+        for var in &format.inferred {
+            let (target, source, variadicity) = match var {
+                Var::Operand(index) => (
+                    self.operand_types(*index),
+                    &format.operand_types[*index],
+                    operation.operands[*index].variadicity,
+                ),
+                Var::Result(index) => (
+                    self.result_types(*index),
+                    &format.result_types[*index],
+                    operation.results[*index].variadicity,
+                ),
+                _ => continue,
+            };
+            let TypeSource::Infer { from, transform } = source else {
+                continue;
+            };
+            let borrows_ctx = *transform != Transform::Identity || matches!(from, Var::Attribute(_));
+            let from = match from {
+                Var::Operand(index) if format.all_operand_types => {
+                    format!("all_operand_types.iter().copied().skip({index})")
+                }
+                Var::Operand(index) => format!("{}.iter().copied()", self.operand_types(*index)),
+                Var::Result(index) if format.all_result_types => format!("all_result_types.iter().copied().skip({index})"),
+                Var::Result(index) => format!("{}.iter().copied()", self.result_types(*index)),
+                Var::Attribute(index) => format!(
+                    "state.attribute({:?}).and_then(|attribute| attribute.r#type(ctx)).into_iter()",
+                    operation.attributes[*index].name
+                ),
+            };
+            // The count goes first, the cycled iterator can't clone the closure borrowing the context.
+            let count = match (var, variadicity) {
+                (_, Variadicity::Single) => ".take(1)".to_string(),
+                (Var::Operand(index), _) => format!(".cycle().take({}.len())", self.operand(*index)),
+                _ => String::new(),
+            };
+            let transform = match transform {
+                Transform::Identity => String::new(),
+                transform => format!(".map(|r#type| {})", transform.apply("ctx", "r#type")),
+            };
+            let types = format!("{from}{count}{transform}.collect()");
+            match borrows_ctx {
+                true => {
+                    code.line(format!("let {target}: Vec<Type> = {{"));
+                    code.indent();
+                    code.line("let ctx = parser.ctx();");
+                    code.line(types);
+                    code.dedent();
+                    code.line("};");
+                }
+                false => code.line(format!("let {target}: Vec<Type> = {types};")),
+            }
+        }
+
+        if !operation.operands.is_empty() {
+            let names = match format.all_operands {
+                true => "&all_operands".to_string(),
+                false => concat((0..operation.operands.len()).map(|index| self.operand(index)).collect()),
+            };
+            let types = match format.all_operand_types {
+                true => "&all_operand_types".to_string(),
+                false => concat(
+                    (0..operation.operands.len())
+                        .map(|index| self.operand_types(index))
+                        .collect(),
+                ),
+            };
+            code.line(format!("parser.resolve_operands({names}, {types}, state)?;"));
+        }
+        if operation.has_trait("AttrSizedResultSegments") && !format.all_result_types {
+            let sizes: Vec<String> = (0..operation.results.len())
+                .map(|index| format!("{}.len() as i32", self.result_types(index)))
+                .collect();
+            segment_sizes(code, "resultSegmentSizes", &sizes);
+        }
+        if format.all_result_types {
+            code.line("state.add_types(all_result_types);");
+        } else {
+            for index in 0..operation.results.len() {
+                code.line(format!("state.add_types({});", self.result_types(index)));
+            }
+        }
+    }
+}
+
+/// `parse_*` or `parse_optional_*` call of the attribute style, along with the attribute type built before
+/// the call, as the parser can't lend the context while it's parsing.
+fn attribute_parser(style: AttrStyle, optional: bool) -> (Option<String>, String) {
+    let prefix = match optional {
+        true => "parse_optional_",
+        false => "parse_",
+    };
+    let hoisted = |r#type: &str| Some(format!("let attribute_type = parser.ctx().buildable_type({type:?});"));
+    match style {
+        AttrStyle::Enum { cases, r#type } => (
+            hoisted(&r#type),
+            format!("{prefix}enum(&[{}], attribute_type)", enum_cases(&cases)),
+        ),
+        AttrStyle::SymbolName => (None, format!("{prefix}symbol_name()")),
+        AttrStyle::WithoutType(r#type) if optional => {
+            (hoisted(&r#type), "parse_optional_attribute(Some(attribute_type))".to_string())
+        }
+        AttrStyle::WithoutType(r#type) => (hoisted(&r#type), "parse_attribute_of_type(attribute_type)".to_string()),
+        AttrStyle::Full | AttrStyle::Stripped if optional => (None, "parse_optional_attribute(None)".to_string()),
+        AttrStyle::Full | AttrStyle::Stripped => (None, "parse_attribute()".to_string()),
+        AttrStyle::Dialect(dialect) => (None, format!("{prefix}stripped_attribute({dialect:?})")),
+        AttrStyle::DenseArray(element) => (
+            hoisted(&element),
+            format!("{prefix}stripped_dense_array(attribute_type)"),
+        ),
+    }
+}
+
+fn segment_sizes(code: &mut CodeWriter, name: &str, sizes: &[String]) {
+    code.line(format!(
+        "let sizes = parser.ctx().dense_i32_array_attr(&[{}]);",
+        sizes.join(", ")
+    ));
+    code.line(format!("state.set_attribute({name:?}, sizes);"));
+}
+
+/// `&name` or `&[a.as_slice(), b.as_slice()].concat()`
+fn concat(names: Vec<String>) -> String {
+    match names.as_slice() {
+        [name] => format!("&{name}"),
+        names => {
+            let slices: Vec<String> = names.iter().map(|name| format!("{name}.as_slice()")).collect();
+            format!("&[{}].concat()", slices.join(", "))
+        }
+    }
+}
+
+/// Checks if the element or any of its optional group elements is the given one.
+fn contains(element: &FormatElement, expected: &FormatElement) -> bool {
+    match element {
+        FormatElement::Optional {
+            then_elements,
+            else_elements,
+            ..
+        } => then_elements
+            .iter()
+            .chain(else_elements)
+            .any(|element| contains(element, expected)),
+        FormatElement::Custom { params, .. } => params.iter().any(|param| &param.element == expected),
+        element => element == expected,
+    }
+}
@@ -0,0 +1,355 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Generates the custom form printer, mirroring the upstream `OperationFormat::genPrinter`.
+//!
+
+use crate::assembly_format::analysis::{CustomParam, Format, FormatElement, TypeArg};
+use crate::assembly_format::{snake_case, CodeWriter};
+use crate::ods::{AttrStyle, Operation, Variadicity};
+
+/// Whitespace state of the printed elements, tracked at the generation time.
+struct Spacing {
+    should_emit_space: bool,
+    last_was_punctuation: bool,
+}
+
+/// Groups of the operation values used by the printed elements.
+#[derive(Default)]
+struct Groups {
+    operands: bool,
+    results: bool,
+    regions: bool,
+    successors: bool,
+}
+
+struct Printer<'a> {
+    operation: &'a Operation,
+    format: &'a Format,
+    hooks_path: &'a str,
+    groups: Groups,
+}
+
+/// Generates the `fn(op: Operation, p: &mut Printer<'_>)` body.
+pub fn generate(operation: &Operation, format: &Format, hooks_path: &str) -> String {
+    let mut printer = Printer {
+        operation,
+        format,
+        hooks_path,
+        groups: Groups::default(),
+    };
+    let mut body = CodeWriter::default();
+    let mut spacing = Spacing {
+        should_emit_space: true,
+        last_was_punctuation: false,
+    };
+    for element in &format.elements {
+        printer.element(element, &mut body, &mut spacing);
+    }
+
+    let mut code = CodeWriter::default();
+    code.line("let ctx = p.ctx();");
+    let variadicity = |variadicity: Variadicity| match variadicity {
+        Variadicity::Single => "Variadicity::Single",
+        Variadicity::Optional => "Variadicity::Optional",
+        _ => "Variadicity::Variadic",
+    };
+    let group_spec = |variadic: &mut dyn Iterator<Item = &'static str>| variadic.collect::<Vec<_>>().join(", ");
+    if printer.groups.operands {
+        let spec = group_spec(&mut operation.operands.iter().map(|operand| variadicity(operand.variadicity)));
+        code.line(format!("let operands = op.operand_groups(ctx, &[{spec}]);"));
+    }
+    if printer.groups.results {
+        let spec = group_spec(&mut operation.results.iter().map(|result| variadicity(result.variadicity)));
+        code.line(format!("let results = op.result_groups(ctx, &[{spec}]);"));
+    }
+    if printer.groups.regions {
+        let spec = group_spec(&mut operation.regions.iter().map(|region| match region.variadic {
+            true => "Variadicity::Variadic",
+            false => "Variadicity::Single",
+        }));
+        code.line(format!("let regions = op.region_groups(ctx, &[{spec}]);"));
+    }
+    if printer.groups.successors {
+        let spec = group_spec(&mut operation.successors.iter().map(|successor| match successor.variadic {
+            true => "Variadicity::Variadic",
+            false => "Variadicity::Single",
+        }));
+        code.line(format!("let successors = op.successor_groups(ctx, &[{spec}]);"));
+    }
+    code.append(&body.finish());
+    code.finish()
+}
+
+/// Upstream `shouldEmitSpaceBefore`.
+pub(crate) fn should_emit_space_before(value: &str, last_was_punctuation: bool) -> bool {
+    if value.len() != 1 && value != "->" {
+        return true;
+    }
+    let first = value.chars().next().unwrap_or_default();
+    if last_was_punctuation {
+        !">)}],".contains(first)
+    } else {
+        !"<>(){}[],".contains(first)
+    }
+}
+
+impl Printer<'_> {
+    fn element(&mut self, element: &FormatElement, code: &mut CodeWriter, spacing: &mut Spacing) {
+        match element {
+            FormatElement::Literal(literal) => {
+                let space = spacing.should_emit_space && should_emit_space_before(literal, spacing.last_was_punctuation);
+                let text = if space { format!(" {literal}") } else { literal.clone() };
+                code.line(format!("p.write({text:?});"));
+                let first = literal.chars().next().unwrap_or_default();
+                spacing.should_emit_space = literal.len() != 1 || !"<({[".contains(first);
+                spacing.last_was_punctuation = first != '_' && !first.is_ascii_alphabetic();
+            }
+            FormatElement::Whitespace(space) => {
+                if space.is_empty() {
+                    spacing.last_was_punctuation = true;
+                } else {
+                    code.line("p.write(\" \");");
+                    spacing.last_was_punctuation = false;
+                }
+                spacing.should_emit_space = false;
+            }
+            FormatElement::Newline => code.line("p.print_newline();"),
+            FormatElement::AttrDict { with_keyword } => {
+                let elided: Vec<String> = self
+                    .format
+                    .elided_attributes
+                    .iter()
+                    .map(|name| format!("{name:?}"))
+                    .collect();
+                let method = match with_keyword {
+                    true => "print_optional_attr_dict_with_keyword",
+                    false => "print_optional_attr_dict",
+                };
+                code.line(format!("p.{method}(&op.all_attributes(ctx), &[{}]);", elided.join(", ")));
+                spacing.last_was_punctuation = false;
+            }
+            FormatElement::Optional {
+                then_elements,
+                else_elements,
+                anchor,
+            } => {
+                code.line(format!("if {} {{", self.anchor_condition(&then_elements[*anchor])));
+                let elided = match &then_elements[*anchor] {
+                    FormatElement::Attribute { index, .. }
+                        if *anchor != 0 && self.operation.attributes[*index].constraint.is_unit() =>
+                    {
+                        Some(*anchor)
+                    }
+                    _ => None,
+                };
+                code.indent();
+                for (index, element) in then_elements.iter().enumerate() {
+                    if Some(index) != elided {
+                        self.element(element, code, spacing);
+                    }
+                }
+                code.dedent();
+                if else_elements.is_empty() {
+                    code.line("}");
+                } else {
+                    code.line("} else {");
+                    code.indent();
+                    for element in else_elements {
+                        self.element(element, code, spacing);
+                    }
+                    code.dedent();
+                    code.line("}");
+                }
+            }
+            element => {
+                if spacing.should_emit_space || !spacing.last_was_punctuation {
+                    code.line("p.write(\" \");");
+                }
+                spacing.last_was_punctuation = false;
+                spacing.should_emit_space = true;
+                self.value(element, code);
+            }
+        }
+    }
+
+    fn value(&mut self, element: &FormatElement, code: &mut CodeWriter) {
+        let implicit_terminator = self.operation.implicit_terminator.is_some();
+        match element {
+            FormatElement::Operand(index) => {
+                self.groups.operands = true;
+                code.line(format!("p.print_operands(operands[{index}]);"));
+            }
+            FormatElement::Attribute { index, qualified } => {
+                let attribute = &self.operation.attributes[*index];
+                let print = match attribute.constraint.style(*qualified) {
+                    AttrStyle::Enum { cases, .. } => format!("p.print_enum(attribute, &[{}]);", enum_cases(&cases)),
+                    AttrStyle::SymbolName => "p.print_symbol_name(attribute);".to_string(),
+                    AttrStyle::WithoutType(_) => "p.print_attribute_without_type(attribute);".to_string(),
+                    AttrStyle::Full => "p.print_attribute(attribute);".to_string(),
+                    AttrStyle::Dialect(dialect) => format!("p.print_stripped_attribute(attribute, Some({dialect:?}));"),
+                    AttrStyle::DenseArray(_) | AttrStyle::Stripped => {
+                        "p.print_stripped_attribute(attribute, None);".to_string()
+                    }
+                };
+                code.line(format!("if let Some(attribute) = op.attribute(ctx, {:?}) {{", attribute.name));
+                code.indent();
+                code.line(print);
+                code.dedent();
+                code.line("}");
+            }
+            FormatElement::Region(index) => {
+                self.groups.regions = true;
+                code.line(format!("p.print_regions(regions[{index}], {implicit_terminator});"));
+            }
+            FormatElement::Successor(index) => {
+                self.groups.successors = true;
+                code.line(format!("p.print_successors(successors[{index}]);"));
+            }
+            FormatElement::Operands => code.line("p.print_operands(op.operands(ctx));"),
+            FormatElement::Regions => code.line(format!("p.print_regions(op.regions(ctx), {implicit_terminator});")),
+            FormatElement::Successors => code.line("p.print_successors(op.successors(ctx));"),
+            FormatElement::Type { arg, qualified } => {
+                let dialect = match arg {
+                    TypeArg::Operand(index) => Some(&self.operation.operands[*index]),
+                    TypeArg::Result(index) => Some(&self.operation.results[*index]),
+                    _ => None,
+                }
+                .filter(|value| value.variadicity == Variadicity::Single && !qualified)
+                .and_then(|value| value.constraint.dialect.clone());
+                let values = self.values(*arg);
+                match dialect {
+                    Some(dialect) => {
+                        code.line(format!("for value in {values} {{"));
+                        code.indent();
+                        code.line(format!("p.print_stripped_type(value.r#type(ctx), {dialect:?});"));
+                        code.dedent();
+                        code.line("}");
+                    }
+                    None => code.line(format!("p.print_value_types({values});")),
+                }
+            }
+            FormatElement::FunctionalType { inputs, results } => {
+                let inputs = self.values(*inputs);
+                let results = self.values(*results);
+                code.line(format!("p.print_function_type(&types(ctx, {inputs}), &types(ctx, {results}));"));
+            }
+            FormatElement::Custom { name, params } => {
+                let mut args = vec!["p".to_string(), "op".to_string()];
+                for CustomParam { element, .. } in params {
+                    args.push(self.custom_arg(element));
+                }
+                code.line(format!(
+                    "{}::print_{}({});",
+                    self.hooks_path,
+                    snake_case(name),
+                    args.join(", ")
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn values(&mut self, arg: TypeArg) -> String {
+        match arg {
+            TypeArg::Operand(index) => {
+                self.groups.operands = true;
+                format!("operands[{index}]")
+            }
+            TypeArg::Result(index) => {
+                self.groups.results = true;
+                format!("results[{index}]")
+            }
+            TypeArg::Operands => "op.operands(ctx)".to_string(),
+            TypeArg::Results => "op.results(ctx)".to_string(),
+        }
+    }
+
+    fn custom_arg(&mut self, element: &FormatElement) -> String {
+        match element {
+            FormatElement::Operand(index) => {
+                self.groups.operands = true;
+                format!("operands[{index}]")
+            }
+            FormatElement::Attribute { index, .. } => {
+                format!("op.attribute(ctx, {:?})", self.operation.attributes[*index].name)
+            }
+            FormatElement::Region(index) => {
+                self.groups.regions = true;
+                format!("regions[{index}]")
+            }
+            FormatElement::Successor(index) => {
+                self.groups.successors = true;
+                format!("successors[{index}]")
+            }
+            FormatElement::Operands => "op.operands(ctx)".to_string(),
+            FormatElement::Regions => "op.regions(ctx)".to_string(),
+            FormatElement::Successors => "op.successors(ctx)".to_string(),
+            FormatElement::Type { arg, .. } => format!("&types(ctx, {})", self.values(*arg)),
+            FormatElement::AttrDict { .. } => "&op.all_attributes(ctx)".to_string(),
+            _ => "()".to_string(),
+        }
+    }
+
+    /// Upstream `genOptionalGroupPrinterAnchor`.
+    fn anchor_condition(&mut self, anchor: &FormatElement) -> String {
+        match anchor {
+            FormatElement::Operand(index)
+            | FormatElement::Type {
+                arg: TypeArg::Operand(index),
+                ..
+            } => {
+                self.groups.operands = true;
+                format!("!operands[{index}].is_empty()")
+            }
+            FormatElement::Type {
+                arg: TypeArg::Result(index),
+                ..
+            } => {
+                self.groups.results = true;
+                format!("!results[{index}].is_empty()")
+            }
+            FormatElement::Attribute { index, .. } => {
+                format!("op.attribute(ctx, {:?}).is_some()", self.operation.attributes[*index].name)
+            }
+            FormatElement::Region(index) => {
+                self.groups.regions = true;
+                format!("regions[{index}].iter().any(|region| !region.is_empty(ctx))")
+            }
+            FormatElement::Successor(index) => {
+                self.groups.successors = true;
+                format!("!successors[{index}].is_empty()")
+            }
+            FormatElement::Operands | FormatElement::Type { arg: TypeArg::Operands, .. } => {
+                "!op.operands(ctx).is_empty()".to_string()
+            }
+            FormatElement::Type { arg: TypeArg::Results, .. } => "!op.results(ctx).is_empty()".to_string(),
+            FormatElement::Regions => "!op.regions(ctx).is_empty()".to_string(),
+            FormatElement::Successors => "!op.successors(ctx).is_empty()".to_string(),
+            _ => "true".to_string(),
+        }
+    }
+}
+
+/// `(value, "case")` pairs of the enum attributes.
+pub(crate) fn enum_cases(cases: &[(i128, String)]) -> String {
+    cases
+        .iter()
+        .map(|(value, name)| format!("({value}, {name:?})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
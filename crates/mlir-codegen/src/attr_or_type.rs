@@ -0,0 +1,302 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir codegen attr or type
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Rust structs generated out of the `AttrDef` and `TypeDef` records, with the parameters translated to the
+//! `fljuga-handahofi-mlir-ir` types. The structs derive the equality and hashing the upstream storage uniquing relies on,
+//! and implement `AttrOrTypeDef` with the printer and parser compiled out of the `assemblyFormat`.
+//! The definitions with a custom C++ assembly format call the hand-written `print_class` and `parse_class` hooks.
+//!
+
+use crate::assembly_format::attr_or_type::{self, DefForm, Field};
+use crate::assembly_format::{snake_case, CodeWriter};
+use crate::interface::{argument_name, template};
+use crate::ods::{AttrOrTypeDef, DefKind};
+use crate::rustdoc::{self, Links};
+
+/// Translates the C++ parameter type to the owned Rust one, `None` if there's no runtime counterpart.
+///
+/// The `mlir` attribute and type classes, like `::mlir::IntegerAttr`, are kept as the textual
+/// `Attribute` and `Type`, the classes of the other namespaces are dialect specific.
+pub fn storage_type(cpp: &str) -> Option<String> {
+    let cpp = cpp.trim();
+    let cpp = cpp.strip_prefix("const ").unwrap_or(cpp).trim();
+    let (name, arguments) = template(cpp)?;
+    let name = name.trim_start_matches("::");
+    let name = name
+        .strip_prefix("llvm::")
+        .or_else(|| name.strip_prefix("std::"))
+        .or_else(|| name.strip_prefix("mlir::"))
+        .unwrap_or(name);
+    let r#type = match (name, arguments.as_slice()) {
+        ("ArrayRef" | "SmallVector", [element, ..]) => format!("Vec<{}>", storage_type(element)?),
+        ("optional", [value]) => format!("Option<{}>", storage_type(value)?),
+        ("bool", []) => "bool".to_string(),
+        ("int8_t", []) => "i8".to_string(),
+        ("int16_t", []) => "i16".to_string(),
+        ("int" | "int32_t", []) => "i32".to_string(),
+        ("int64_t", []) => "i64".to_string(),
+        ("uint8_t", []) => "u8".to_string(),
+        ("uint16_t", []) => "u16".to_string(),
+        ("unsigned" | "unsigned int" | "uint32_t", []) => "u32".to_string(),
+        ("uint64_t", []) => "u64".to_string(),
+        ("size_t", []) => "usize".to_string(),
+        ("StringRef" | "string", []) => "String".to_string(),
+        ("Attribute", []) => "Attribute".to_string(),
+        ("Type", []) => "Type".to_string(),
+        (name, []) if !name.contains("::") && name.ends_with("Attr") => "Attribute".to_string(),
+        (name, []) if !name.contains("::") && name.ends_with("Type") => "Type".to_string(),
+        _ => return None,
+    };
+    Some(r#type)
+}
+
+/// Translates the C++ default value of the integer, floating point, boolean and string parameters.
+pub fn default_value(rust_type: &str, cpp: &str) -> Option<String> {
+    let cpp = cpp.trim();
+    match rust_type {
+        "bool" if matches!(cpp, "true" | "false") => Some(cpp.to_string()),
+        "String" if cpp.len() >= 2 && cpp.starts_with('"') && cpp.ends_with('"') => Some(format!("String::from({cpp})")),
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            let literal = cpp.trim_end_matches(['u', 'U', 'l', 'L']);
+            let value = literal.parse::<i128>().ok()?;
+            let (min, max): (i128, i128) = match rust_type {
+                "i8" => (i8::MIN.into(), i8::MAX.into()),
+                "i16" => (i16::MIN.into(), i16::MAX.into()),
+                "i32" => (i32::MIN.into(), i32::MAX.into()),
+                "i64" => (i64::MIN.into(), i64::MAX.into()),
+                "u8" => (0, u8::MAX.into()),
+                "u16" => (0, u16::MAX.into()),
+                "u32" => (0, u32::MAX.into()),
+                _ => (0, u64::MAX.into()),
+            };
+            match value {
+                // The C++ unsigned `-1` wraps around.
+                -1 if min == 0 => Some(format!("{rust_type}::MAX")),
+                value if (min..=max).contains(&value) => Some(literal.to_string()),
+                _ => None,
+            }
+        }
+        "f32" | "f64" => {
+            let literal = cpp.trim_end_matches(['f', 'F']);
+            let value = literal.parse::<f64>().ok().filter(|value| value.is_finite())?;
+            Some(format!("{value:?}"))
+        }
+        _ => None,
+    }
+}
+
+/// Struct fields of the definition parameters.
+pub fn fields(def: &AttrOrTypeDef) -> Result<Vec<Field>, String> {
+    def.parameters
+        .iter()
+        .map(|parameter| {
+            let rust_type = storage_type(&parameter.cpp_type).ok_or_else(|| {
+                format!(
+                    "parameter {:?} of the C++ type {:?} has no Rust counterpart",
+                    parameter.name, parameter.cpp_type
+                )
+            })?;
+            let (rust_type, optional) = match rust_type.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')) {
+                Some(inner) => (inner.to_string(), true),
+                None => (rust_type, parameter.optional),
+            };
+            let default = match &parameter.default_value {
+                Some(cpp) if !optional => Some(default_value(&rust_type, cpp).ok_or_else(|| {
+                    format!("parameter {:?} has the unsupported default value {cpp:?}", parameter.name)
+                })?),
+                _ => None,
+            };
+            Ok(Field {
+                name: parameter.name.clone(),
+                ident: argument_name(&parameter.name),
+                rust_type,
+                optional,
+                default,
+            })
+        })
+        .collect()
+}
+
+/// Compiles the printer and parser of the definition.
+fn form(def: &AttrOrTypeDef, fields: &[Field], custom: &[String], hooks: &str) -> Result<DefForm, String> {
+    if def.mnemonic.is_none() {
+        return Err("definition has no mnemonic".to_string());
+    }
+    match (&def.assembly_format, def.has_custom_assembly_format) {
+        (Some(source), _) => attr_or_type::compile(source, fields, custom, hooks).map_err(|error| error.to_string()),
+        (None, true) if custom.contains(&def.class_name) => {
+            let name = snake_case(&def.class_name);
+            Ok(DefForm {
+                printer: format!("{hooks}::print_{name}(self, p);\n"),
+                parser: format!("{hooks}::parse_{name}(parser)\n"),
+            })
+        }
+        (None, true) => Err("definition has a custom C++ assembly format".to_string()),
+        (None, false) if fields.is_empty() => attr_or_type::compile("", fields, custom, hooks).map_err(|error| error.to_string()),
+        (None, false) => Err("definition has no 'assemblyFormat'".to_string()),
+    }
+}
+
+/// Generates the struct of the definition with its builder and `AttrOrTypeDef` implementation,
+/// returns the reason the definition or its textual form is skipped.
+pub fn generate(
+    def: &AttrOrTypeDef,
+    custom: &[String],
+    hooks: &str,
+    links: &Links,
+    code: &mut CodeWriter,
+) -> Result<(), String> {
+    let fields = fields(def)?;
+    let class = &def.class_name;
+    let name = def
+        .name()
+        .map(|name| format!("{}{name}", def.prefix()))
+        .unwrap_or_else(|| class.clone());
+    code.line("");
+    match def.summary.trim() {
+        "" => code.line(format!("/// `{name}`")),
+        summary => code.line(format!("/// `{name}`: {summary}")),
+    }
+    rustdoc::generate(&def.description, links, code);
+    if fields.is_empty() {
+        code.line("#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]");
+        code.line(format!("pub struct {class};"));
+    } else {
+        code.line("#[derive(Debug, Clone, PartialEq, Eq, Hash)]");
+        code.line(format!("pub struct {class} {{"));
+        code.indent();
+        for (field, parameter) in fields.iter().zip(&def.parameters) {
+            if !parameter.summary.trim().is_empty() {
+                code.line(format!("/// {}", parameter.summary.trim()));
+            }
+            match field.optional {
+                true => code.line(format!("pub {}: Option<{}>,", field.ident, field.rust_type)),
+                false => code.line(format!("pub {}: {},", field.ident, field.rust_type)),
+            }
+        }
+        code.dedent();
+        code.line("}");
+        if !def.skip_default_builders {
+            builder(def, &name, &fields, code);
+        }
+    }
+
+    let form = form(def, &fields, custom, hooks)?;
+    let handle = match def.kind {
+        DefKind::Attr => "Attribute",
+        DefKind::Type => "Type",
+    };
+    code.line("");
+    code.line(format!("impl AttrOrTypeDef for {class} {{"));
+    code.indent();
+    code.line(format!("const PREFIX: char = '{}';", def.prefix()));
+    code.line(format!("const NAME: &'static str = {:?};", def.name().unwrap_or_default()));
+    code.line(format!("type Handle = {handle};"));
+    code.line("");
+    code.line("fn print(&self, p: &mut Printer<'_>) {");
+    code.indent();
+    code.append(&form.printer);
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line("fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, ParseError> {");
+    code.indent();
+    code.append(&form.parser);
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    Ok(())
+}
+
+/// `new` builder taking all the parameters, like the upstream default `get` builder.
+fn builder(def: &AttrOrTypeDef, name: &str, fields: &[Field], code: &mut CodeWriter) {
+    let rust_type = |field: &Field| match (field.optional, field.rust_type.as_str()) {
+        (true, rust_type) => format!("Option<{rust_type}>"),
+        (false, "String") => "impl Into<String>".to_string(),
+        (false, rust_type) => rust_type.to_string(),
+    };
+    let arguments: Vec<String> = fields
+        .iter()
+        .map(|field| format!("{}: {}", field.ident, rust_type(field)))
+        .collect();
+    let values: Vec<String> = fields
+        .iter()
+        .map(|field| match (field.optional, field.rust_type.as_str()) {
+            (false, "String") => format!("{}: {}.into()", field.ident, field.ident),
+            _ => field.ident.clone(),
+        })
+        .collect();
+    let kind = match def.kind {
+        DefKind::Attr => "attribute",
+        DefKind::Type => "type",
+    };
+    code.line("");
+    code.line(format!("impl {} {{", def.class_name));
+    code.indent();
+    code.line(format!("/// Builds the `{name}` {kind} out of its parameters."));
+    let summaries: Vec<_> = fields
+        .iter()
+        .zip(&def.parameters)
+        .filter(|(_, parameter)| !parameter.summary.trim().is_empty())
+        .collect();
+    if !summaries.is_empty() {
+        code.line("///");
+        for (field, parameter) in summaries {
+            code.line(format!("/// * `{}`: {}", field.ident, parameter.summary.trim()));
+        }
+    }
+    if fields.len() > 7 {
+        code.line("#[allow(clippy::too_many_arguments)]");
+    }
+    code.line(format!("pub fn new({}) -> Self {{", arguments.join(", ")));
+    code.line(format!("    Self {{ {} }}", values.join(", ")));
+    code.line("}");
+    code.dedent();
+    code.line("}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_translate_parameter_types() {
+        assert_eq!(storage_type("unsigned").as_deref(), Some("u32"));
+        assert_eq!(storage_type("::llvm::StringRef").as_deref(), Some("String"));
+        assert_eq!(storage_type("std::string").as_deref(), Some("String"));
+        assert_eq!(storage_type("::llvm::ArrayRef<int>").as_deref(), Some("Vec<i32>"));
+        assert_eq!(storage_type("::mlir::IntegerAttr").as_deref(), Some("Attribute"));
+        assert_eq!(storage_type("mlir::IntegerType").as_deref(), Some("Type"));
+        assert_eq!(storage_type("std::optional<::mlir::Type>").as_deref(), Some("Option<Type>"));
+        assert_eq!(storage_type("::mlir::sparse_tensor::LevelType"), None);
+        assert_eq!(storage_type("AffineMap"), None);
+
+        assert_eq!(default_value("u32", "0").as_deref(), Some("0"));
+        assert_eq!(default_value("u64", "64u").as_deref(), Some("64"));
+        assert_eq!(default_value("u32", "-1U").as_deref(), Some("u32::MAX"));
+        assert_eq!(default_value("u8", "256"), None);
+        assert_eq!(default_value("f64", "0.30f").as_deref(), Some("0.3"));
+        assert_eq!(default_value("f32", "1").as_deref(), Some("1.0"));
+        assert_eq!(default_value("String", "\"none\"").as_deref(), Some("String::from(\"none\")"));
+        assert_eq!(default_value("Type", "mlir::IntegerType::get($_ctxt, 32)"), None);
+    }
+}
@@ -0,0 +1,289 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+
+//! ## fljúga handahófi mlir codegen builder
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! The `build` functions of the operations, mirroring the `__init__` of the `OpView` classes the upstream
+//! `mlir-tblgen -gen-python-op-bindings` generates. The `Context` uniquing the built types and attributes
+//! comes first, like the implicit one of the Python bindings, then the result types the ODS builders
+//! can't infer, the operands and the attributes in the order of the arguments, the optional ones last,
+//! the way the Python builders take them as the keyword arguments.
+//! The attributes are moved into the properties, the successors and the variadic regions are left to the caller.
+//!
+
+use crate::assembly_format::analysis::{result_type_source, Transform, TypeSource, Var};
+use crate::assembly_format::CodeWriter;
+use crate::interface::argument_name;
+use crate::ods::{Argument, Operation, Variadicity};
+
+/// What the builder parameter sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Result(usize),
+    Operand(usize),
+    Attribute(usize),
+}
+
+/// `build` function parameter, a Python builder argument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    /// ODS name, like `overflowFlags`.
+    pub name: String,
+    pub kind: ParamKind,
+    /// The optional operands and attributes are the keyword-only arguments of the Python builders.
+    pub keyword: bool,
+}
+
+impl Param {
+    /// Rust parameter name, like `overflow_flags`.
+    pub fn ident(&self) -> String {
+        argument_name(&self.name)
+    }
+
+    fn rust_type(&self, operation: &Operation) -> &'static str {
+        let variadicity = match self.kind {
+            ParamKind::Result(index) => operation.results[index].variadicity,
+            ParamKind::Operand(index) => operation.operands[index].variadicity,
+            ParamKind::Attribute(_) if self.keyword => return "Option<Attribute>",
+            ParamKind::Attribute(_) => return "Attribute",
+        };
+        match (self.kind, variadicity) {
+            (ParamKind::Result(_), Variadicity::Single) => "Type",
+            (ParamKind::Result(_), Variadicity::Optional) => "Option<Type>",
+            (ParamKind::Result(_), _) => "Vec<Type>",
+            (_, Variadicity::Single) => "Value",
+            (_, Variadicity::Optional) => "Option<Value>",
+            _ => "Vec<Value>",
+        }
+    }
+}
+
+/// Unnamed operands and results are named by their position.
+fn name(name: &str, prefix: &str, index: usize) -> String {
+    match name.is_empty() {
+        true => format!("{prefix}{index}"),
+        false => name.to_string(),
+    }
+}
+
+/// Whether the ODS builders infer all the result types out of the single operands and the attributes.
+pub fn infers_results(operation: &Operation) -> bool {
+    (0..operation.results.len()).all(|index| {
+        operation.results[index].variadicity == Variadicity::Single
+            && match result_type_source(operation, index) {
+                Some(TypeSource::Infer {
+                    from: Var::Operand(operand),
+                    ..
+                }) => operation.operands[operand].variadicity == Variadicity::Single,
+                Some(TypeSource::Buildable(_)) | Some(TypeSource::Infer { .. }) => true,
+                _ => false,
+            }
+    })
+}
+
+/// Parameters of the `build` function, in the order of the Python builder arguments.
+pub fn params(operation: &Operation) -> Vec<Param> {
+    let mut params = vec![];
+    if !infers_results(operation) {
+        params.extend(operation.results.iter().enumerate().map(|(index, result)| Param {
+            name: name(&result.name, "result", index),
+            kind: ParamKind::Result(index),
+            keyword: false,
+        }));
+    }
+    for argument in &operation.arguments {
+        match *argument {
+            Argument::Operand(index) => {
+                let operand = &operation.operands[index];
+                params.push(Param {
+                    name: name(&operand.name, "operand", index),
+                    kind: ParamKind::Operand(index),
+                    keyword: operand.variadicity == Variadicity::Optional,
+                });
+            }
+            Argument::Attribute(index) => {
+                let attribute = &operation.attributes[index];
+                if attribute.constraint.derived {
+                    continue;
+                }
+                params.push(Param {
+                    name: attribute.name.clone(),
+                    kind: ParamKind::Attribute(index),
+                    keyword: attribute.constraint.optional
+                        || attribute.constraint.default_value.is_some()
+                        || attribute.constraint.base_name == "UnitAttr",
+                });
+            }
+            Argument::Property(_) => {}
+        }
+    }
+    params.sort_by_key(|param| param.keyword);
+    params
+}
+
+/// `1` like segment size of the parameter.
+fn segment_size(param: &Param, variadicity: Variadicity) -> String {
+    let ident = param.ident();
+    match variadicity {
+        Variadicity::Single => "1".to_string(),
+        Variadicity::Optional => format!("i32::from({ident}.is_some())"),
+        _ => format!("{ident}.len() as i32"),
+    }
+}
+
+/// `segments` attribute of the `AttrSizedOperandSegments` and `AttrSizedResultSegments` operations.
+fn segment_sizes(code: &mut CodeWriter, name: &str, sizes: &[String]) {
+    code.line(format!("let {} = ctx.dense_i32_array_attr(&[{}]);", name, sizes.join(", ")))
+}
+
+/// Names of the inherent attributes, stored as the properties like the upstream ODS operations,
+/// the `operandSegmentSizes` and `resultSegmentSizes` included.
+pub fn attribute_names(operation: &Operation) -> Vec<String> {
+    let mut names: Vec<String> = operation
+        .attributes
+        .iter()
+        .filter(|attribute| !attribute.constraint.derived)
+        .map(|attribute| attribute.name.clone())
+        .collect();
+    if operation.has_trait("AttrSizedOperandSegments") {
+        names.push("operandSegmentSizes".to_string());
+    }
+    if operation.has_trait("AttrSizedResultSegments") {
+        names.push("resultSegmentSizes".to_string());
+    }
+    names
+}
+
+/// Generates the `build` function of the operation, returning its `OperationState`.
+pub fn generate(operation: &Operation, code: &mut CodeWriter) {
+    let params = params(operation);
+    let param = |kind: ParamKind| params.iter().find(|param| param.kind == kind);
+    let arguments: Vec<String> = params
+        .iter()
+        .map(|param| format!("{}: {}", param.ident(), param.rust_type(operation)))
+        .collect();
+
+    code.line(format!("/// Builds the `{}` operation state, like the Python `{}`.", operation.name, operation.class_name));
+    if arguments.len() > 6 {
+        code.line("#[allow(clippy::too_many_arguments)]");
+    }
+    let arguments: Vec<String> = std::iter::once("ctx: &mut Context".to_string()).chain(arguments).collect();
+    code.line(format!("pub fn build({}) -> OperationState {{", arguments.join(", ")));
+    code.indent();
+    code.line("let mut ods_state = OperationState::new(Self::NAME);");
+
+    let segments = |has_trait: bool, groups: &[crate::ods::NamedTypeConstraint], kind: fn(usize) -> ParamKind| {
+        match has_trait {
+            true => groups
+                .iter()
+                .enumerate()
+                .map(|(index, group)| param(kind(index)).map(|param| segment_size(param, group.variadicity)))
+                .collect::<Option<Vec<_>>>(),
+            false => None,
+        }
+    };
+    let operand_segments = segments(
+        operation.has_trait("AttrSizedOperandSegments"),
+        &operation.operands,
+        ParamKind::Operand,
+    );
+    let result_segments = segments(
+        operation.has_trait("AttrSizedResultSegments"),
+        &operation.results,
+        ParamKind::Result,
+    );
+    if let Some(sizes) = &operand_segments {
+        segment_sizes(code, "operand_segments", sizes);
+    }
+    if let Some(sizes) = &result_segments {
+        segment_sizes(code, "result_segments", sizes);
+    }
+
+    for (index, result) in operation.results.iter().enumerate() {
+        let types = match param(ParamKind::Result(index)) {
+            Some(param) if result.variadicity == Variadicity::Single => format!("[{}]", param.ident()),
+            Some(param) => param.ident(),
+            None => match result_type_source(operation, index) {
+                Some(TypeSource::Buildable(r#type)) => format!("[ctx.buildable_type({type:?})]"),
+                Some(TypeSource::Infer {
+                    from: Var::Operand(operand),
+                    transform,
+                }) => {
+                    let place = format!(
+                        "{}.r#type(ctx)",
+                        param(ParamKind::Operand(operand)).map(Param::ident).unwrap_or_default()
+                    );
+                    format!("[{}]", transform.apply("ctx", &place))
+                }
+                Some(TypeSource::Infer {
+                    from: Var::Attribute(attribute),
+                    transform,
+                }) => {
+                    let attribute = param(ParamKind::Attribute(attribute));
+                    let ident = attribute.map(Param::ident).unwrap_or_default();
+                    let r#type = match attribute.is_some_and(|attribute| attribute.keyword) {
+                        true => format!("{ident}.and_then(|attribute| attribute.r#type(ctx))"),
+                        false => format!("{ident}.r#type(ctx)"),
+                    };
+                    match transform {
+                        Transform::Identity => r#type,
+                        transform => format!("{type}.map(|r#type| {})", transform.apply("ctx", "r#type")),
+                    }
+                }
+                _ => continue,
+            },
+        };
+        code.line(format!("ods_state.add_types({types});"));
+    }
+    for (index, operand) in operation.operands.iter().enumerate() {
+        if let Some(param) = param(ParamKind::Operand(index)) {
+            match operand.variadicity {
+                Variadicity::Single => code.line(format!("ods_state.add_operands([{}]);", param.ident())),
+                _ => code.line(format!("ods_state.add_operands({});", param.ident())),
+            }
+        }
+    }
+    for (index, attribute) in operation.attributes.iter().enumerate() {
+        match param(ParamKind::Attribute(index)) {
+            Some(param) if param.keyword => code.line(format!(
+                "if let Some(attribute) = {} {{ ods_state.set_attribute({:?}, attribute); }}",
+                param.ident(),
+                attribute.name
+            )),
+            Some(param) => code.line(format!("ods_state.set_attribute({:?}, {});", attribute.name, param.ident())),
+            None => {}
+        }
+    }
+    if operand_segments.is_some() {
+        code.line("ods_state.set_attribute(\"operandSegmentSizes\", operand_segments);");
+    }
+    if result_segments.is_some() {
+        code.line("ods_state.set_attribute(\"resultSegmentSizes\", result_segments);");
+    }
+    let regions = operation.regions.iter().filter(|region| !region.variadic).count();
+    if regions > 0 {
+        code.line(format!("ods_state.add_regions({regions});"));
+    }
+    code.line("ods_state.move_into_properties(ctx, Self::ATTRIBUTE_NAMES);");
+    code.line("ods_state");
+    code.dedent();
+    code.line("}");
+}
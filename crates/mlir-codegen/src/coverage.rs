@@ -0,0 +1,334 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir codegen coverage
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Coverage report of the `dialect!` macro, listing the generated operations, attributes, types and rewrite patterns
+//! of the TableGen file, and the skipped ones with the reason, like an unknown constraint or a `custom<>` directive
+//! without a hook. The interfaces without a generated implementation and the ignored C++ only `extraClassDeclaration`
+//! are listed as well. The coverage tests write the report as JSON and as a Markdown table, the checked-in JSON
+//! reports are the baselines the coverage must not regress from.
+//!
+
+#[cfg(test)]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Derived [thiserror::Error] for the coverage report errors
+#[cfg(test)]
+#[derive(thiserror::Error, Debug)]
+pub enum CoverageError {
+    #[error("{path}: {source}")]
+    Io { path: String, source: std::io::Error },
+
+    #[error("invalid coverage report: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Skipped operation, definition, interface or pattern, with the reason.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Skipped {
+    pub name: String,
+    pub reason: String,
+}
+
+impl Skipped {
+    pub fn new(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Coverage of a dialect defined in the file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialectCoverage {
+    pub name: String,
+    /// Operations with the generated custom form.
+    pub operations: Vec<String>,
+    pub skipped_operations: Vec<Skipped>,
+    /// Attributes and types with the generated struct, and the generated enums.
+    pub definitions: Vec<String>,
+    pub skipped_definitions: Vec<Skipped>,
+    /// Interfaces and interface methods of the operations without the generated implementation.
+    pub skipped_interfaces: Vec<Skipped>,
+    /// Operations, attributes and types with the ignored `extraClassDeclaration`.
+    pub ignored_declarations: Vec<String>,
+}
+
+/// Coverage report of a TableGen file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    /// TableGen file, relative to the crate root.
+    pub file: String,
+    pub dialects: Vec<DialectCoverage>,
+    /// Rewrite patterns with the generated `RewritePattern`.
+    pub patterns: Vec<String>,
+    pub skipped_patterns: Vec<Skipped>,
+}
+
+/// Escapes the Markdown table cell.
+#[cfg(test)]
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// `generated / total (percent)` Markdown table cell.
+#[cfg(test)]
+fn ratio(generated: usize, skipped: usize) -> String {
+    match generated + skipped {
+        0 => "-".to_string(),
+        total => format!("{generated} / {total} ({}%)", generated * 100 / total),
+    }
+}
+
+impl Coverage {
+    pub fn new(file: impl Into<String>) -> Self {
+        Self {
+            file: file.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Reports and baseline checks of the coverage tests.
+#[cfg(test)]
+impl Coverage {
+    pub fn to_json(&self) -> Result<String, CoverageError> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Summary table of the dialects, followed by the table of the skipped items of each dialect.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# `{}` coverage\n\n", self.file);
+        markdown
+            .push_str("| Dialect | Operations | Attributes, types and enums | Skipped interfaces | Ignored declarations |\n");
+        markdown.push_str("|---|---|---|---|---|\n");
+        for dialect in &self.dialects {
+            markdown.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                dialect.name,
+                ratio(dialect.operations.len(), dialect.skipped_operations.len()),
+                ratio(dialect.definitions.len(), dialect.skipped_definitions.len()),
+                dialect.skipped_interfaces.len(),
+                dialect.ignored_declarations.len(),
+            ));
+        }
+        if !self.patterns.is_empty() || !self.skipped_patterns.is_empty() {
+            markdown.push_str(&format!(
+                "\nRewrite patterns: {}\n",
+                ratio(self.patterns.len(), self.skipped_patterns.len())
+            ));
+        }
+
+        for dialect in &self.dialects {
+            let skipped = [
+                ("operation", &dialect.skipped_operations),
+                ("attribute or type", &dialect.skipped_definitions),
+                ("interface", &dialect.skipped_interfaces),
+            ];
+            if skipped.iter().all(|(_, skipped)| skipped.is_empty()) && dialect.ignored_declarations.is_empty() {
+                continue;
+            }
+            markdown.push_str(&format!("\n## `{}`\n\n", dialect.name));
+            markdown.push_str("| Skipped | Kind | Reason |\n");
+            markdown.push_str("|---|---|---|\n");
+            for (kind, skipped) in skipped {
+                for Skipped { name, reason } in skipped {
+                    markdown.push_str(&format!("| `{}` | {kind} | {} |\n", cell(name), cell(reason)));
+                }
+            }
+            for name in &dialect.ignored_declarations {
+                markdown.push_str(&format!(
+                    "| `{}` | extraClassDeclaration | C++ only declarations |\n",
+                    cell(name)
+                ));
+            }
+        }
+        if !self.skipped_patterns.is_empty() {
+            markdown.push_str("\n## Rewrite patterns\n\n");
+            markdown.push_str("| Skipped | Reason |\n");
+            markdown.push_str("|---|---|\n");
+            for Skipped { name, reason } in &self.skipped_patterns {
+                markdown.push_str(&format!("| `{}` | {} |\n", cell(name), cell(reason)));
+            }
+        }
+        markdown
+    }
+
+    /// Writes the `<file stem>.json` and `<file stem>.md` reports into the directory.
+    pub fn write(&self, dir: &Path) -> Result<(), CoverageError> {
+        let stem = Path::new(&self.file)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let write = |extension: &str, contents: String| {
+            let path = dir.join(format!("{stem}.{extension}"));
+            std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&path, contents))
+                .map_err(|source| CoverageError::Io {
+                    path: path.to_string_lossy().to_string(),
+                    source,
+                })
+        };
+        write("json", self.to_json()?)?;
+        write("md", self.to_markdown())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, CoverageError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Items generated according to the baseline, but skipped or missing now.
+    pub fn regressions(&self, baseline: &Coverage) -> Vec<String> {
+        let mut regressions = vec![];
+        for expected in &baseline.dialects {
+            let dialect = self.dialects.iter().find(|dialect| dialect.name == expected.name);
+            let generated = |items: fn(&DialectCoverage) -> &Vec<String>, name: &String| {
+                dialect.is_some_and(|dialect| items(dialect).contains(name))
+            };
+            for name in &expected.operations {
+                if !generated(|dialect| &dialect.operations, name) {
+                    regressions.push(format!("operation `{name}` is no longer generated"));
+                }
+            }
+            for name in &expected.definitions {
+                if !generated(|dialect| &dialect.definitions, name) {
+                    regressions.push(format!("`{name}` is no longer generated"));
+                }
+            }
+            // The interfaces of the operations added since the baseline aren't regressions.
+            let known = |name: &String| {
+                expected.operations.contains(name)
+                    || expected.skipped_operations.iter().any(|skipped| skipped.name == *name)
+            };
+            let skipped_interfaces = dialect
+                .map(|dialect| dialect.skipped_interfaces.as_slice())
+                .unwrap_or_default();
+            for skipped in skipped_interfaces.iter().filter(|skipped| known(&skipped.name)) {
+                if !expected.skipped_interfaces.contains(skipped) {
+                    regressions.push(format!("`{}` interface is skipped: {}", skipped.name, skipped.reason));
+                }
+            }
+        }
+        for name in &baseline.patterns {
+            if !self.patterns.contains(name) {
+                regressions.push(format!("pattern `{name}` is no longer generated"));
+            }
+        }
+        regressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{dialect_crates, generate, DialectArgs};
+
+    /// `dialect!` arguments of the generated goldens.
+    const FIXTURES: [&str; 4] = [
+        r#"file = "src/testdata/ods/TestOps.td", includes = ["include", "src/testdata/ods"], hooks = hooks, custom = ["CustomDirectiveOperands", "OptionalOperandRef"], interfaces = crate::interfaces, methods = ["ForOp::getLoopRegions"]"#,
+        r#"file = "src/testdata/ods/TestAttrDefs.td", includes = ["include", "src/testdata/ods"], hooks = hooks, custom = ["PrettyLLVMType", "CompoundAAttr"]"#,
+        r#"file = "include/mlir/Dialect/Arith/IR/ArithOps.td", includes = ["include"]"#,
+        r#"file = "../targets/src/arith/ArithCanonicalization.td", includes = ["include"], hooks = hooks, custom = ["AddIntegerAttrs", "SubIntegerAttrs", "MulIntegerAttrs", "GetZeroAttr", "IsScalarOrSplatNegativeOne", "IsEqOrNe"]"#,
+    ];
+
+    /// Checks the coverage against the checked-in baseline, updated with `GOLDIE_UPDATE=1` like the goldens.
+    fn assert_no_regressions(root: &Path, args: &DialectArgs, baselines: &Path) {
+        let (_, coverage) = generate(&args.load(root).unwrap().0, args);
+        if std::env::var_os("GOLDIE_UPDATE").is_some() {
+            coverage.write(baselines).unwrap();
+            return;
+        }
+        let stem = Path::new(&args.file).file_stem().unwrap().to_string_lossy().to_string();
+        let baseline = std::fs::read_to_string(baselines.join(format!("{stem}.json"))).unwrap();
+        let regressions = coverage.regressions(&Coverage::from_json(&baseline).unwrap());
+        assert!(
+            regressions.is_empty(),
+            "{} coverage regressed: {regressions:#?}",
+            args.file
+        );
+    }
+
+    #[test]
+    fn should_not_regress_coverage() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for fixture in FIXTURES {
+            let args = DialectArgs::parse(fixture).unwrap();
+            assert_no_regressions(root, &args, &root.join("src/testdata/coverage"));
+        }
+    }
+
+    #[test]
+    fn should_not_regress_dialect_crates_coverage() {
+        let baselines = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/testdata/coverage/dialects");
+        let crates = dialect_crates();
+        assert!(!crates.is_empty());
+        for (root, args) in crates {
+            assert_no_regressions(&root, &args, &baselines);
+        }
+    }
+
+    #[test]
+    fn should_report_regressions() {
+        let baseline = Coverage {
+            file: "Test.td".to_string(),
+            dialects: vec![DialectCoverage {
+                name: "test".to_string(),
+                operations: vec!["test.a".to_string(), "test.b".to_string()],
+                skipped_operations: vec![Skipped::new("test.c", "`custom<C>` has no hook")],
+                ..DialectCoverage::default()
+            }],
+            patterns: vec!["FoldA".to_string()],
+            skipped_patterns: vec![],
+        };
+        assert_eq!(Coverage::from_json(&baseline.to_json().unwrap()).unwrap(), baseline);
+        assert!(baseline.regressions(&baseline).is_empty());
+
+        let mut coverage = baseline.clone();
+        coverage.dialects[0].operations = vec!["test.b".to_string(), "test.c".to_string()];
+        coverage.dialects[0].skipped_operations = vec![Skipped::new("test.a", "unknown constraint `Foo`")];
+        coverage.dialects[0].skipped_interfaces = vec![
+            Skipped::new("test.b", "`FooOpInterface` has no generated implementation"),
+            Skipped::new("test.d", "`FooOpInterface` has no generated implementation"),
+        ];
+        coverage.patterns.clear();
+        coverage.skipped_patterns = vec![Skipped::new("FoldA", "constraint IsA has no hook")];
+        assert_eq!(
+            coverage.regressions(&baseline),
+            vec![
+                "operation `test.a` is no longer generated",
+                "`test.b` interface is skipped: `FooOpInterface` has no generated implementation",
+                "pattern `FoldA` is no longer generated",
+            ]
+        );
+        assert!(coverage
+            .to_markdown()
+            .contains("| `test` | 2 / 3 (66%) | - | 2 | 0 |\n"));
+        assert!(coverage
+            .to_markdown()
+            .contains("| `test.a` | operation | unknown constraint `Foo` |\n"));
+        assert!(coverage
+            .to_markdown()
+            .contains("| `FoldA` | constraint IsA has no hook |\n"));
+    }
+}
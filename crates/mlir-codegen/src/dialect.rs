@@ -0,0 +1,779 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir codegen dialect
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Dialect module generator of the `dialect!` macro, every operation gets its custom form printer and parser
+//! compiled out of the `assemblyFormat`, the ones that can't be compiled are listed with the reason.
+//! The attributes and types defined in the file become structs with their textual form, see [crate::attr_or_type].
+//! The enums defined in the file become Rust enums converting to their integer attributes, see [crate::enum_attr].
+//! The interfaces defined in the file become traits, implemented by the operations declaring their methods.
+//! The passes defined in the file get their options structs and registrations in the `passes` module, see [crate::pass].
+//! The Declarative Rewrite Rules defined in the file become the rewrite patterns of the `patterns` module, see [crate::pattern].
+//! The generated and skipped items are collected into the coverage report, see [crate::coverage].
+//! The ODS summaries and descriptions become the doc comments of the generated items, see [crate::rustdoc].
+//!
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use fljuga_handahofi_tablegen::fetch::{self, TableGenError, TdLock, TdSource, Vendored};
+use fljuga_handahofi_tablegen::records::{RecordError, RecordKeeper};
+use winnow::ascii::multispace0;
+use winnow::combinator::*;
+use winnow::token::*;
+use winnow::*;
+
+use crate::assembly_format::{self, snake_case, CodeWriter};
+use crate::attr_or_type;
+use crate::builder;
+use crate::enum_attr;
+use crate::coverage::{Coverage, DialectCoverage, Skipped};
+use crate::interface::{self, Signature};
+use crate::ods::{OdsError, Ods, Operation, Trait, Variadicity};
+use crate::pass;
+use crate::pattern;
+use crate::rustdoc::{self, Links};
+
+/// Derived [thiserror::Error] for the dialect generation errors
+#[derive(thiserror::Error, Debug)]
+pub enum DialectError {
+    #[error("invalid dialect! arguments near {0:?}, expected `file = \"...\", includes = [...], hooks = path, custom = [...], interfaces = path, methods = [...]`")]
+    InvalidArguments(String),
+
+    #[error("missing `file` argument")]
+    MissingFile,
+
+    #[error("{0}")]
+    TableGenError(#[from] TableGenError),
+
+    #[error("{0}")]
+    RecordError(#[from] RecordError),
+
+    #[error("{0}")]
+    OdsError(#[from] OdsError),
+}
+
+/// `dialect!` macro arguments.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DialectArgs {
+    /// TableGen file with the operations, relative to the crate root.
+    pub file: String,
+    /// Include directories, relative to the crate root.
+    pub includes: Vec<String>,
+    /// Path of the module with the custom directive hooks.
+    pub hooks: Option<String>,
+    /// Custom directives, the attributes and types with a custom assembly format,
+    /// and the native code calls and constraints of the rewrite patterns, implemented by the hooks.
+    pub custom: Vec<String>,
+    /// Path of the module with the interface traits, the generated `interfaces` module by default.
+    pub interfaces: Option<String>,
+    /// Declared interface methods implemented by the hooks, like `ForOp::getLoopRegions`.
+    pub methods: Vec<String>,
+}
+
+enum Argument {
+    File(String),
+    Includes(Vec<String>),
+    Hooks(String),
+    Custom(Vec<String>),
+    Interfaces(String),
+    Methods(Vec<String>),
+}
+
+fn string(input: &mut &str) -> PResult<String> {
+    delimited('"', take_till(0.., '"'), '"')
+        .map(str::to_string)
+        .parse_next(input)
+}
+
+fn strings(input: &mut &str) -> PResult<Vec<String>> {
+    delimited(
+        ('[', multispace0),
+        separated(0.., string, (multispace0, ',', multispace0)),
+        (multispace0, opt((',', multispace0)), ']'),
+    )
+    .parse_next(input)
+}
+
+/// `crate::hooks` path, the token stream puts the spaces around `::`.
+fn path(input: &mut &str) -> PResult<String> {
+    separated(
+        1..,
+        take_while(1.., ('a'..='z', 'A'..='Z', '0'..='9', '_')),
+        (multispace0, "::", multispace0),
+    )
+    .map(|segments: Vec<&str>| segments.join("::"))
+    .parse_next(input)
+}
+
+fn argument(input: &mut &str) -> PResult<Argument> {
+    let name = terminated(
+        take_while(1.., ('a'..='z', '_')),
+        (multispace0, '=', multispace0),
+    )
+    .parse_next(input)?;
+    match name {
+        "file" => string.map(Argument::File).parse_next(input),
+        "includes" => strings.map(Argument::Includes).parse_next(input),
+        "hooks" => path.map(Argument::Hooks).parse_next(input),
+        "custom" => strings.map(Argument::Custom).parse_next(input),
+        "interfaces" => path.map(Argument::Interfaces).parse_next(input),
+        "methods" => strings.map(Argument::Methods).parse_next(input),
+        _ => Err(error::ErrMode::Cut(error::ContextError::new())),
+    }
+}
+
+impl DialectArgs {
+    /// Parses the macro arguments out of the token stream text.
+    pub fn parse(source: &str) -> Result<Self, DialectError> {
+        let mut input = source.trim();
+        let arguments: Vec<Argument> = separated(0.., argument, (multispace0, ',', multispace0))
+            .parse_next(&mut input)
+            .map_err(|_| DialectError::InvalidArguments(input.to_string()))?;
+        let _: PResult<_> = (multispace0, opt(','), multispace0).parse_next(&mut input);
+        if !input.is_empty() {
+            return Err(DialectError::InvalidArguments(input.to_string()));
+        }
+        let mut args = Self::default();
+        for argument in arguments {
+            match argument {
+                Argument::File(file) => args.file = file,
+                Argument::Includes(includes) => args.includes = includes,
+                Argument::Hooks(hooks) => args.hooks = Some(hooks),
+                Argument::Custom(custom) => args.custom = custom,
+                Argument::Interfaces(interfaces) => args.interfaces = Some(interfaces),
+                Argument::Methods(methods) => args.methods = methods,
+            }
+        }
+        if args.file.is_empty() {
+            return Err(DialectError::MissingFile);
+        }
+        Ok(args)
+    }
+
+    /// Evaluates the TableGen file relative to the crate root.
+    /// The include closure is read through the [IncludeSource] and checked against the `tablegen.lock`
+    /// files of the include directories, so the vendored files can't drift from the fetched ones.
+    /// Returns the files read along, the generated code tracks them with `include_str!`.
+    pub fn load(&self, root: &Path) -> Result<(Ods, Vec<PathBuf>), DialectError> {
+        let file = root.join(&self.file);
+        let source = IncludeSource::new(root, &self.includes, &file);
+        let entry_point = source.relative(&file);
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(TableGenError::from)?;
+        let (files, resolved) = runtime.block_on(fetch::resolve_includes(&source, &[&entry_point]))?;
+        let mut tracked = source.read.into_inner();
+        for dir in &source.dirs {
+            let path = dir.root.join(TdLock::FILE_NAME);
+            if let Some(lock) = TdLock::read(&path)? {
+                lock.verify(&resolved)?;
+                tracked.push(path);
+            }
+        }
+
+        let filename = file.to_string_lossy().to_string();
+        let loader: HashMap<String, String> = files.into_iter().collect();
+        let keeper = RecordKeeper::from_source(&filename, &loader[&entry_point], &loader)?;
+        Ok((Ods::from_records(&keeper, &filename)?, tracked))
+    }
+}
+
+/// Crate roots and `dialect!` arguments of the `crates/dialect-*` crates, taken from their `lib.rs`.
+#[cfg(test)]
+pub(crate) fn dialect_crates() -> Vec<(PathBuf, DialectArgs)> {
+    let mut crates: Vec<_> = std::fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("dialect-"))
+        .collect();
+    crates.sort();
+    crates
+        .into_iter()
+        .map(|root| {
+            let lib = std::fs::read_to_string(root.join("src/lib.rs")).unwrap();
+            let (_, invocation) = lib.split_once("dialect! {").unwrap();
+            let (invocation, _) = invocation.split_once("\n}").unwrap();
+            let args = DialectArgs::parse(invocation).unwrap();
+            (root, args)
+        })
+        .collect()
+}
+
+/// Include directories of the `dialect!` macro, searched in order like `tblgen -I`,
+/// followed by the directory of the TableGen file.
+struct IncludeSource {
+    dirs: Vec<Vendored>,
+    /// Files read so far.
+    read: RefCell<Vec<PathBuf>>,
+}
+
+impl IncludeSource {
+    fn new(root: &Path, includes: &[String], file: &Path) -> Self {
+        let mut dirs: Vec<_> = includes.iter().map(|include| root.join(include)).collect();
+        let parent = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        if !dirs.contains(&parent) {
+            dirs.push(parent);
+        }
+        IncludeSource {
+            dirs: dirs.into_iter().map(|root| Vendored { root }).collect(),
+            read: RefCell::default(),
+        }
+    }
+
+    /// Path of the file relative to the first include directory containing it.
+    fn relative(&self, file: &Path) -> String {
+        let relative = self.dirs.iter().find_map(|dir| file.strip_prefix(&dir.root).ok());
+        relative.map(PathBuf::from).unwrap_or_default().to_string_lossy().to_string()
+    }
+}
+
+impl TdSource for IncludeSource {
+    fn name(&self) -> String {
+        let dirs: Vec<_> = self.dirs.iter().map(|dir| dir.root.display().to_string()).collect();
+        format!("include directories {}", dirs.join(", "))
+    }
+
+    async fn read(&self, path: &str) -> Result<String, TableGenError> {
+        for dir in &self.dirs {
+            match dir.read(path).await {
+                Err(TableGenError::NotFound { .. }) => continue,
+                Ok(contents) => {
+                    self.read.borrow_mut().push(dir.root.join(path));
+                    return Ok(contents);
+                }
+                error => return error,
+            }
+        }
+        Err(TableGenError::NotFound {
+            path: path.to_string(),
+            provider: self.name(),
+        })
+    }
+}
+
+/// Native ODS traits of the [OpTrait] counterparts.
+const OP_TRAITS: [(&str, &str); 5] = [
+    ("IsTerminator", "Terminator"),
+    ("NoTerminator", "NoTerminator"),
+    ("IsIsolatedFromAbove", "IsolatedFromAbove"),
+    ("SymbolTable", "SymbolTable"),
+    ("ReturnLike", "ReturnLike"),
+];
+
+/// `OpDefinition` of the operation, `Pure` ones are the `AlwaysSpeculatableImplTrait` ones.
+fn generate_definition(operation: &Operation, code: &mut CodeWriter) {
+    let variadicity = |variadicity: Variadicity| match variadicity {
+        Variadicity::Single => "Variadicity::Single",
+        Variadicity::Optional => "Variadicity::Optional",
+        _ => "Variadicity::Variadic",
+    };
+    let variadic = |variadic: bool| match variadic {
+        true => "Variadicity::Variadic",
+        false => "Variadicity::Single",
+    };
+    let spec = |spec: Vec<&str>| format!("&[{}]", spec.join(", "));
+    let traits: Vec<_> = OP_TRAITS
+        .iter()
+        .filter(|(native, _)| operation.has_trait(native))
+        .map(|(_, r#trait)| format!("OpTrait::{trait}"))
+        .collect();
+    code.line("OpDefinition {");
+    code.indent();
+    code.line(format!("name: {}::NAME,", operation.class_name));
+    code.line(format!("traits: &[{}],", traits.join(", ")));
+    code.line(format!("pure: {},", operation.has_trait("AlwaysSpeculatableImplTrait")));
+    code.line(format!(
+        "operands: {},",
+        spec(operation.operands.iter().map(|operand| variadicity(operand.variadicity)).collect())
+    ));
+    code.line(format!(
+        "results: {},",
+        spec(operation.results.iter().map(|result| variadicity(result.variadicity)).collect())
+    ));
+    code.line(format!(
+        "regions: {},",
+        spec(operation.regions.iter().map(|region| variadic(region.variadic)).collect())
+    ));
+    code.line(format!(
+        "successors: {},",
+        spec(operation.successors.iter().map(|successor| variadic(successor.variadic)).collect())
+    ));
+    code.dedent();
+    code.line("},");
+}
+
+/// Path relative to the generated dialect modules.
+fn module_path(path: &str) -> String {
+    match path.starts_with("crate::") || path.starts_with("::") {
+        true => path.to_string(),
+        false => format!("super::{path}"),
+    }
+}
+
+/// Interfaces of the operation without the generated implementation,
+/// and the declared methods without the Rust counterpart of their C++ types.
+fn skipped_interfaces(operation: &Operation) -> Vec<Skipped> {
+    let mut skipped = vec![];
+    for r#trait in &operation.traits {
+        let Trait::Interface(name) = r#trait else {
+            continue;
+        };
+        match operation.declared_interfaces.iter().find(|declared| declared.interface.name == *name) {
+            None => skipped.push(Skipped::new(&operation.name, format!("`{name}` has no generated implementation"))),
+            Some(declared) => {
+                for method in declared.declared_methods() {
+                    if let Err(cpp) = Signature::new(declared.interface.kind, method) {
+                        skipped.push(Skipped::new(
+                            &operation.name,
+                            format!("`{name}::{}` uses `{cpp}` without a Rust counterpart", method.name),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    skipped
+}
+
+/// Generates the dialect modules with the operation printers and parsers, the attribute and type structs,
+/// the interface traits, the `Dialect` of every dialect module, and the `register` and `register_interfaces` functions.
+/// Returns the code and the coverage report of the generated and skipped items.
+pub fn generate(ods: &Ods, args: &DialectArgs) -> (String, Coverage) {
+    let mut coverage = Coverage::new(&args.file);
+    let hooks = args.hooks.as_deref().map(module_path).unwrap_or_default();
+    let interfaces = module_path(args.interfaces.as_deref().unwrap_or("interfaces"));
+    let custom: &[String] = match args.hooks {
+        Some(_) => &args.custom,
+        None => &[],
+    };
+    let mut dialects: Vec<&str> = vec![];
+    let names = ods.operations.iter().map(|operation| &operation.dialect);
+    for dialect in names.chain(ods.defs.iter().map(|def| &def.dialect)) {
+        if !dialects.contains(&dialect.as_str()) {
+            dialects.push(dialect);
+        }
+    }
+
+    // The enums go with the dialect of their C++ namespace, the first one of the file otherwise.
+    let enum_dialects: Vec<Option<String>> = ods
+        .enums
+        .iter()
+        .map(|enum_attr| {
+            let namespace = enum_attr.cpp_namespace.trim_start_matches("::");
+            ods.dialects
+                .iter()
+                .find(|ods_dialect| ods_dialect.cpp_namespace.trim_start_matches("::") == namespace)
+                .map(|ods_dialect| ods_dialect.name.as_str())
+                .or(dialects.first().copied())
+                .map(str::to_string)
+        })
+        .collect();
+    for dialect in enum_dialects.iter().flatten() {
+        if !dialects.contains(&dialect.as_str()) {
+            dialects.push(dialect);
+        }
+    }
+
+    let module = |dialect: &str| snake_case(dialect).replace('.', "_");
+    let mut links = Links::default();
+    for operation in &ods.operations {
+        links.insert(&operation.name, &module(&operation.dialect), &operation.class_name);
+    }
+    for def in ods.defs.iter().filter(|def| attr_or_type::fields(def).is_ok()) {
+        if let Some(name) = def.name() {
+            links.insert(format!("{}{name}", def.prefix()), &module(&def.dialect), &def.class_name);
+        }
+    }
+
+    let mut code = CodeWriter::default();
+    for dialect in &dialects {
+        let module = module(dialect);
+        let ods_dialect = ods.dialects.iter().find(|ods_dialect| ods_dialect.name == *dialect);
+        let summary = ods_dialect
+            .map(|ods_dialect| ods_dialect.summary.trim())
+            .filter(|summary| !summary.is_empty());
+        code.line(format!("/// `{dialect}` dialect{}", summary.map(|summary| format!(": {summary}")).unwrap_or_default()));
+        if let Some(ods_dialect) = ods_dialect {
+            rustdoc::generate(&ods_dialect.description, &links, &mut code);
+        }
+        code.line(format!("pub mod {module} {{"));
+        code.indent();
+        code.line("#[allow(unused_imports)]");
+        code.line("use ::fljuga_handahofi_mlir_ir::{");
+        code.line("    types, AsmParameter, AttrOrTypeDef, Attribute, Block, Context, FailureOr, InterfaceRegistry, LogicalResult,");
+        code.line("    NamedAttribute, OpAsmRegistry, Operation, OperationState, ParseError, Parser, Printer, Region, Type,");
+        code.line("    OpDefinition, OpTrait, UnresolvedOperand, Value, Variadicity,");
+        code.line("};");
+
+        let mut registered = vec![];
+        let mut skipped = vec![];
+        let mut implemented = vec![];
+        let mut dialect_coverage = DialectCoverage {
+            name: dialect.to_string(),
+            ..DialectCoverage::default()
+        };
+        for operation in ods.operations.iter().filter(|operation| operation.dialect == *dialect) {
+            dialect_coverage.skipped_interfaces.extend(skipped_interfaces(operation));
+            if operation.extra_class_declaration.is_some() {
+                dialect_coverage.ignored_declarations.push(operation.name.clone());
+            }
+            code.line("");
+            let summary = operation.summary.trim();
+            match summary.is_empty() {
+                true => code.line(format!("/// `{}`", operation.name)),
+                false => code.line(format!("/// `{}`: {summary}", operation.name)),
+            }
+            rustdoc::generate(&operation.description, &links, &mut code);
+            code.line(format!("pub struct {};", operation.class_name));
+            code.line("");
+            code.line(format!("impl {} {{", operation.class_name));
+            code.indent();
+            code.line(format!("pub const NAME: &'static str = {:?};", operation.name));
+            code.line(format!(
+                "pub const ATTRIBUTE_NAMES: &'static [&'static str] = &[{}];",
+                builder::attribute_names(operation)
+                    .iter()
+                    .map(|name| format!("{name:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            code.line("");
+            builder::generate(operation, &mut code);
+            match assembly_format::compile(operation, custom, &hooks) {
+                Ok(form) => {
+                    code.line("");
+                    code.line("pub fn print(op: Operation, p: &mut Printer<'_>) {");
+                    code.indent();
+                    code.append(&form.printer);
+                    code.dedent();
+                    code.line("}");
+                    code.line("");
+                    code.line("pub fn parse(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {");
+                    code.indent();
+                    code.append(&form.parser);
+                    code.dedent();
+                    code.line("}");
+                    registered.push(operation.class_name.clone());
+                    dialect_coverage.operations.push(operation.name.clone());
+                }
+                Err(error) => skipped.push((operation.name.clone(), error.to_string())),
+            }
+            code.dedent();
+            code.line("}");
+            for declared in &operation.declared_interfaces {
+                code.line("");
+                interface::generate_impl(&operation.class_name, declared, &interfaces, &hooks, &args.methods, &mut code);
+                implemented.push((operation.class_name.clone(), declared.interface.name.clone()));
+            }
+        }
+
+        let mut skipped_defs = vec![];
+        let defs: Vec<_> = ods.defs.iter().filter(|def| def.dialect == *dialect).collect();
+        for def in &defs {
+            let name = def.name().unwrap_or_else(|| def.class_name.clone());
+            if def.extra_class_declaration.is_some() {
+                dialect_coverage.ignored_declarations.push(name.clone());
+            }
+            match attr_or_type::generate(def, custom, &hooks, &links, &mut code) {
+                Ok(()) => dialect_coverage.definitions.push(name),
+                Err(reason) => skipped_defs.push((name, reason)),
+            }
+        }
+        let enums: Vec<_> = ods
+            .enums
+            .iter()
+            .zip(&enum_dialects)
+            .filter(|(_, enum_dialect)| enum_dialect.as_deref() == Some(*dialect))
+            .map(|(enum_attr, _)| enum_attr)
+            .collect();
+        for enum_attr in &enums {
+            match enum_attr::generate(enum_attr, &links, &mut code) {
+                Ok(()) => dialect_coverage.definitions.push(enum_attr.class_name.clone()),
+                Err(reason) => skipped_defs.push((enum_attr.class_name.clone(), reason)),
+            }
+        }
+        dialect_coverage.skipped_operations = skipped.iter().map(|(name, reason)| Skipped::new(name, reason)).collect();
+        dialect_coverage.skipped_definitions = skipped_defs.iter().map(|(name, reason)| Skipped::new(name, reason)).collect();
+        coverage.dialects.push(dialect_coverage);
+
+        code.line("");
+        code.line("/// Operations printed in the generic form, with the reason they have no generated custom form.");
+        if skipped.is_empty() {
+            code.line("pub const SKIPPED: &[(&str, &str)] = &[];");
+        } else {
+            code.line("pub const SKIPPED: &[(&str, &str)] = &[");
+            code.indent();
+            for (name, reason) in &skipped {
+                code.line(format!("({name:?}, {reason:?}),"));
+            }
+            code.dedent();
+            code.line("];");
+        }
+        if !defs.is_empty() || !enums.is_empty() {
+            code.line("");
+            code.line("/// Attributes, types and enums without the generated counterpart, with the reason.");
+            if skipped_defs.is_empty() {
+                code.line("pub const SKIPPED_DEFS: &[(&str, &str)] = &[];");
+            } else {
+                code.line("pub const SKIPPED_DEFS: &[(&str, &str)] = &[");
+                code.indent();
+                for (name, reason) in &skipped_defs {
+                    code.line(format!("({name:?}, {reason:?}),"));
+                }
+                code.dedent();
+                code.line("];");
+            }
+        }
+        code.line("");
+        code.line(format!("pub const NAMESPACE: &str = {dialect:?};"));
+        code.line("");
+        code.line("/// ODS declarations of the operations, verified and queried by the [Dialect].");
+        let operations: Vec<_> = ods.operations.iter().filter(|operation| operation.dialect == *dialect).collect();
+        if operations.is_empty() {
+            code.line("pub const OPERATIONS: &[OpDefinition] = &[];");
+        } else {
+            code.line("pub const OPERATIONS: &[OpDefinition] = &[");
+            code.indent();
+            for operation in operations {
+                generate_definition(operation, &mut code);
+            }
+            code.dedent();
+            code.line("];");
+        }
+        code.line("");
+        code.line("pub fn register(registry: &mut OpAsmRegistry) {");
+        code.indent();
+        for class_name in &registered {
+            code.line(format!("registry.register({class_name}::NAME, {class_name}::print, {class_name}::parse);"));
+        }
+        if registered.is_empty() {
+            code.line("let _ = registry;");
+        }
+        code.dedent();
+        code.line("}");
+        code.line("");
+        code.line("pub fn register_interfaces(registry: &mut InterfaceRegistry) {");
+        code.indent();
+        for (class_name, interface) in &implemented {
+            code.line(format!("registry.register::<dyn {interfaces}::{interface}>({class_name}::NAME, &{class_name});"));
+        }
+        if implemented.is_empty() {
+            code.line("let _ = registry;");
+        }
+        code.dedent();
+        code.line("}");
+        code.line("");
+        code.line(format!(
+            "/// Registers the `{dialect}` custom forms and interface implementations with the [Context],"
+        ));
+        code.line("/// verifying the operations against their ODS declarations.");
+        code.line("pub struct Dialect;");
+        code.line("");
+        code.line("impl ::fljuga_handahofi_mlir_ir::Dialect for Dialect {");
+        code.indent();
+        code.line("fn namespace(&self) -> &str {");
+        code.line("    NAMESPACE");
+        code.line("}");
+        code.line("");
+        code.line("fn register_interfaces(&self, registry: &mut InterfaceRegistry) {");
+        code.line("    register_interfaces(registry);");
+        code.line("}");
+        code.line("");
+        code.line("fn register_assembly(&self, registry: &mut OpAsmRegistry) {");
+        code.line("    register(registry);");
+        code.line("}");
+        code.line("");
+        code.line("/// `Pure` operations other than the terminators, kept like the upstream `isOpTriviallyDead` does.");
+        code.line("fn is_pure(&self, ctx: &Context, op: Operation) -> bool {");
+        code.line("    OpDefinition::lookup(OPERATIONS, op.name(ctx))");
+        code.line("        .is_some_and(|definition| definition.pure && !definition.traits.contains(&OpTrait::Terminator))");
+        code.line("}");
+        code.line("");
+        code.line("fn has_trait(&self, ctx: &Context, op: Operation, r#trait: OpTrait) -> bool {");
+        code.line("    OpDefinition::lookup(OPERATIONS, op.name(ctx))");
+        code.line("        .is_some_and(|definition| definition.traits.contains(&r#trait))");
+        code.line("}");
+        code.line("");
+        code.line("fn verify(&self, ctx: &Context, op: Operation) -> Result<(), String> {");
+        code.line("    match OpDefinition::lookup(OPERATIONS, op.name(ctx)) {");
+        code.line("        Some(definition) => definition.verify(ctx, op),");
+        code.line("        None => Ok(()),");
+        code.line("    }");
+        code.line("}");
+        code.dedent();
+        code.line("}");
+        code.dedent();
+        code.line("}");
+        code.line("");
+    }
+    if !ods.interfaces.is_empty() {
+        interface::generate_traits(&ods.interfaces, &mut code);
+        code.line("");
+    }
+    if !ods.passes.is_empty() {
+        pass::generate(&ods.passes, &links, &mut code);
+        code.line("");
+    }
+    if !ods.patterns.is_empty() {
+        coverage.skipped_patterns = pattern::generate(&ods.patterns, custom, &hooks, &mut code);
+        coverage.patterns = ods
+            .patterns
+            .iter()
+            .map(|pattern| pattern.name.clone())
+            .filter(|name| !coverage.skipped_patterns.iter().any(|skipped| skipped.name == *name))
+            .collect();
+        code.line("");
+    }
+    code.line("/// Registers the custom forms of all the generated dialects.");
+    code.line("pub fn register(registry: &mut ::fljuga_handahofi_mlir_ir::OpAsmRegistry) {");
+    code.indent();
+    for dialect in &dialects {
+        code.line(format!("{}::register(registry);", snake_case(dialect).replace('.', "_")));
+    }
+    if dialects.is_empty() {
+        code.line("let _ = registry;");
+    }
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line("/// Registers the interface implementations of all the generated dialects.");
+    code.line("pub fn register_interfaces(registry: &mut ::fljuga_handahofi_mlir_ir::InterfaceRegistry) {");
+    code.indent();
+    for dialect in &dialects {
+        code.line(format!("{}::register_interfaces(registry);", snake_case(dialect).replace('.', "_")));
+    }
+    if dialects.is_empty() {
+        code.line("let _ = registry;");
+    }
+    code.dedent();
+    code.line("}");
+    (code.finish(), coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_arguments() {
+        let args = DialectArgs::parse(
+            r#"file = "src/testdata/ods/TestOps.td" , includes = ["include", "src/testdata/ods",] , hooks = crate :: hooks , custom = ["A" , "B"], interfaces = interfaces, methods = ["ForOp::getLoopRegions"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            DialectArgs {
+                file: "src/testdata/ods/TestOps.td".to_string(),
+                includes: vec!["include".to_string(), "src/testdata/ods".to_string()],
+                hooks: Some("crate::hooks".to_string()),
+                custom: vec!["A".to_string(), "B".to_string()],
+                interfaces: Some("interfaces".to_string()),
+                methods: vec!["ForOp::getLoopRegions".to_string()],
+            }
+        );
+        assert!(matches!(DialectArgs::parse("includes = []"), Err(DialectError::MissingFile)));
+        assert!(matches!(
+            DialectArgs::parse(r#"file = "a.td", unknown = 1"#),
+            Err(DialectError::InvalidArguments(_))
+        ));
+    }
+
+    #[test]
+    fn should_generate_dialects() {
+        let args = DialectArgs {
+            file: "src/testdata/ods/TestOps.td".to_string(),
+            includes: vec!["include".to_string(), "src/testdata/ods".to_string()],
+            hooks: Some("hooks".to_string()),
+            custom: vec!["CustomDirectiveOperands".to_string(), "OptionalOperandRef".to_string()],
+            interfaces: Some("crate::interfaces".to_string()),
+            methods: vec!["ForOp::getLoopRegions".to_string()],
+        };
+        let ods = args.load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap().0;
+        goldie::assert!(generate(&ods, &args).0);
+    }
+
+    /// It should read the includes through the include directories, refusing the ones changed since locked.
+    #[test]
+    fn should_verify_the_tablegen_lock() {
+        let root = tempfile::tempdir().unwrap();
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for (path, dir) in [
+            ("TestOps.td", manifest_dir.join("src/testdata/ods")),
+            ("TestInterfaces.td", manifest_dir.join("src/testdata/ods")),
+            ("mlir/IR/OpBase.td", manifest_dir.join("include")),
+        ] {
+            let file = root.path().join("include").join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::copy(dir.join(path), file).unwrap();
+        }
+        let args = DialectArgs::parse(r#"file = "include/TestOps.td", includes = ["include"]"#).unwrap();
+        let source = IncludeSource::new(root.path(), &args.includes, &root.path().join(&args.file));
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let (_, lock) = runtime.block_on(fetch::resolve_includes(&source, &["TestOps.td"])).unwrap();
+        lock.write(&root.path().join("include").join(TdLock::FILE_NAME)).unwrap();
+        let (ods, tracked) = args.load(root.path()).unwrap();
+        assert!(!ods.operations.is_empty());
+        assert_eq!(
+            tracked,
+            ["TestOps.td", "mlir/IR/OpBase.td", "TestInterfaces.td", TdLock::FILE_NAME]
+                .map(|path| root.path().join("include").join(path))
+        );
+
+        std::fs::write(root.path().join("include/mlir/IR/OpBase.td"), "class Op;").unwrap();
+        assert!(matches!(
+            args.load(root.path()),
+            Err(DialectError::TableGenError(TableGenError::LockMismatch { path })) if path == "mlir/IR/OpBase.td"
+        ));
+    }
+
+    #[test]
+    fn should_generate_attributes_and_types() {
+        let args = DialectArgs::parse(
+            r#"file = "src/testdata/ods/TestAttrDefs.td", includes = ["include", "src/testdata/ods"], hooks = hooks, custom = ["PrettyLLVMType", "CompoundAAttr"]"#,
+        )
+        .unwrap();
+        let ods = args.load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap().0;
+        goldie::assert!(generate(&ods, &args).0);
+    }
+
+    #[test]
+    fn should_generate_interfaces() {
+        let args = DialectArgs::parse(r#"file = "src/testdata/ods/TestInterfaces.td", includes = ["include", "src/testdata/ods"]"#).unwrap();
+        let ods = args.load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap().0;
+        goldie::assert!(generate(&ods, &args).0);
+    }
+
+    #[test]
+    fn should_generate_passes() {
+        let args = DialectArgs::parse(r#"file = "src/testdata/ods/TestPasses.td", includes = ["include", "src/testdata/ods"]"#).unwrap();
+        let ods = args.load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap().0;
+        goldie::assert!(generate(&ods, &args).0);
+    }
+
+    #[test]
+    fn should_generate_patterns() {
+        let args = DialectArgs::parse(
+            r#"file = "../targets/src/arith/ArithCanonicalization.td", includes = ["include"], hooks = hooks, custom = ["AddIntegerAttrs", "SubIntegerAttrs", "MulIntegerAttrs", "GetZeroAttr", "IsScalarOrSplatNegativeOne", "IsEqOrNe"]"#,
+        )
+        .unwrap();
+        let ods = args.load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap().0;
+        goldie::assert!(generate(&ods, &args).0);
+    }
+}
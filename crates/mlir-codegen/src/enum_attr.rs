@@ -0,0 +1,152 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+
+//! ## fljúga handahófi mlir codegen enum attr
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Rust enums generated out of the `EnumAttrInfo` records, like the `arith.cmpi` predicates.
+//! The cases keep their integer values and textual form keywords, and convert to and from the integer
+//! `Attribute` the operations store them as.
+//! The bit enums have no generated counterpart, like their custom forms.
+//!
+
+use crate::assembly_format::CodeWriter;
+use crate::ods::EnumAttr;
+use crate::rustdoc::{self, Links};
+
+/// `PascalCase` variant name of the C++ enumerant, like `Slt` of `slt` or `Minui` of `MINUI`.
+pub fn variant_name(symbol: &str) -> String {
+    let shouting = !symbol.chars().any(|c| c.is_ascii_lowercase());
+    let mut name = String::new();
+    for word in symbol.split('_').filter(|word| !word.is_empty()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            match shouting {
+                true => name.extend(chars.map(|c| c.to_ascii_lowercase())),
+                false => name.extend(chars),
+            }
+        }
+    }
+    match name.starts_with(|c: char| c.is_ascii_digit()) || name.is_empty() {
+        true => format!("V{name}"),
+        false => name,
+    }
+}
+
+/// Generates the enum with its conversions, `Err` with the reason if there's no Rust counterpart.
+pub fn generate(enum_attr: &EnumAttr, links: &Links, code: &mut CodeWriter) -> Result<(), String> {
+    if enum_attr.bit_enum {
+        return Err("bit enums are not supported".to_string());
+    }
+    let Some(r#type) = &enum_attr.r#type else {
+        return Err("enum without an integer storage type".to_string());
+    };
+    let name = &enum_attr.class_name;
+    let variants: Vec<String> = enum_attr.cases.iter().map(|case| variant_name(&case.symbol)).collect();
+
+    code.line("");
+    match enum_attr.summary.is_empty() {
+        true => code.line(format!("/// `{name}`")),
+        false => code.line(format!("/// `{name}`: {}", enum_attr.summary)),
+    }
+    rustdoc::generate(&enum_attr.description, links, code);
+    code.line("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]");
+    code.line(format!("pub enum {name} {{"));
+    code.indent();
+    for (case, variant) in enum_attr.cases.iter().zip(&variants) {
+        code.line(format!("/// `{}`", case.keyword));
+        code.line(format!("{variant},"));
+    }
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line(format!("impl {name} {{"));
+    code.indent();
+    let cases: Vec<String> = variants.iter().map(|variant| format!("Self::{variant}")).collect();
+    code.line(format!("pub const CASES: &'static [Self] = &[{}];", cases.join(", ")));
+    code.line("");
+    code.line("/// Integer value the attribute stores.");
+    code.line("pub fn value(self) -> i128 {");
+    code.indent();
+    code.line("match self {");
+    code.indent();
+    for (case, variant) in enum_attr.cases.iter().zip(&variants) {
+        code.line(format!("Self::{variant} => {},", case.value));
+    }
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line("pub fn from_value(value: i128) -> Option<Self> {");
+    code.line("    Self::CASES.iter().copied().find(|case| case.value() == value)");
+    code.line("}");
+    code.line("");
+    code.line("/// Keyword of the textual form.");
+    code.line("pub fn keyword(self) -> &'static str {");
+    code.indent();
+    code.line("match self {");
+    code.indent();
+    for (case, variant) in enum_attr.cases.iter().zip(&variants) {
+        code.line(format!("Self::{variant} => {:?},", case.keyword));
+    }
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line("pub fn from_keyword(keyword: &str) -> Option<Self> {");
+    code.line("    Self::CASES.iter().copied().find(|case| case.keyword() == keyword)");
+    code.line("}");
+    code.line("");
+    code.line("pub fn from_attribute(ctx: &Context, attribute: Attribute) -> Option<Self> {");
+    code.line("    attribute.as_integer(ctx).and_then(Self::from_value)");
+    code.line("}");
+    code.line("");
+    code.line("/// Integer attribute the operations store the case as.");
+    code.line("pub fn attribute(self, ctx: &mut Context) -> Attribute {");
+    code.line(format!("    let r#type = ctx.buildable_type({type:?});"));
+    code.line("    ctx.integer_attr(self.value(), r#type)");
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line(format!("impl ::std::fmt::Display for {name} {{"));
+    code.line("    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {");
+    code.line("        f.write_str(self.keyword())");
+    code.line("    }");
+    code.line("}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_variants() {
+        assert_eq!(variant_name("slt"), "Slt");
+        assert_eq!(variant_name("MINUI"), "Minui");
+        assert_eq!(variant_name("dev_global"), "DevGlobal");
+        assert_eq!(variant_name("StorageBuffer"), "StorageBuffer");
+        assert_eq!(variant_name("2D"), "V2d");
+    }
+}
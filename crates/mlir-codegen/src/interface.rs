@@ -0,0 +1,421 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir codegen interface
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Rust traits generated out of the `OpInterface`, `AttrInterface` and `TypeInterface` records,
+//! with the `InterfaceMethod` signatures translated to the `fljuga-handahofi-mlir-ir` types.
+//! The operations declaring `DeclareOpInterfaceMethods` get the `impl` stubs, calling either the hand-written hooks
+//! or `unimplemented!`, the methods with C++ default implementations are `unimplemented!` by default.
+//!
+
+use crate::assembly_format::{snake_case, CodeWriter};
+use crate::ods::{DeclaredInterface, Interface, InterfaceKind, InterfaceMethod};
+
+/// Types without the runtime counterpart, the arguments of these types are left out of the signatures.
+const DROPPED_ARGUMENTS: &[&str] = &["MLIRContext *", "optional<Location>", "Location", "OpaqueProperties"];
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+    "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "super", "trait",
+    "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box", "do",
+    "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Strips the `::mlir::`, `::llvm::` and `::std::` namespaces, including the template arguments.
+fn unqualified(cpp: &str) -> String {
+    let mut unqualified = cpp.replace("::mlir::", "").replace("::llvm::", "").replace("::std::", "");
+    for namespace in ["mlir::", "llvm::", "std::"] {
+        unqualified = unqualified.replace(namespace, "");
+    }
+    unqualified.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `Name<a, b>` template name and its top level arguments.
+pub(crate) fn template(cpp: &str) -> Option<(&str, Vec<&str>)> {
+    let Some((name, rest)) = cpp.split_once('<') else {
+        return Some((cpp, vec![]));
+    };
+    let rest = rest.strip_suffix('>')?;
+    let mut arguments = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (index, c) in rest.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(rest[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(rest[start..].trim());
+    Some((name.trim(), arguments))
+}
+
+/// Translates the C++ type to the Rust one, `None` if there's no runtime counterpart.
+pub fn rust_type(cpp: &str) -> Option<String> {
+    translate(&unqualified(cpp))
+}
+
+fn translate(cpp: &str) -> Option<String> {
+    let cpp = cpp.trim();
+    let cpp = cpp.strip_prefix("const ").unwrap_or(cpp).trim();
+    if let Some(inner) = cpp.strip_suffix('&') {
+        return reference(inner.trim(), true);
+    }
+    if let Some(inner) = cpp.strip_suffix('*') {
+        return reference(inner.trim(), false);
+    }
+    let (name, arguments) = template(cpp)?;
+    let r#type = match (name, arguments.as_slice()) {
+        ("void", []) => "()".to_string(),
+        ("bool", []) => "bool".to_string(),
+        ("int" | "int32_t", []) => "i32".to_string(),
+        ("unsigned" | "unsigned int" | "uint32_t", []) => "u32".to_string(),
+        ("int64_t", []) => "i64".to_string(),
+        ("uint64_t", []) => "u64".to_string(),
+        ("size_t", []) => "usize".to_string(),
+        ("StringRef", []) => "&str".to_string(),
+        ("string", []) => "String".to_string(),
+        ("Value" | "BlockArgument" | "OpResult", []) => "Value".to_string(),
+        ("Type", []) => "Type".to_string(),
+        ("Attribute" | "TypedAttr", []) => "Attribute".to_string(),
+        ("LogicalResult", []) => "LogicalResult".to_string(),
+        ("ValueRange" | "OperandRange" | "ResultRange", []) => "&[Value]".to_string(),
+        ("TypeRange", []) => "&[Type]".to_string(),
+        ("RegionRange", []) => "&[Region]".to_string(),
+        ("DictionaryAttr", []) => "&[NamedAttribute]".to_string(),
+        // The produced operands are not modelled, these are the forwarded ones.
+        ("SuccessorOperands", []) => "Vec<Value>".to_string(),
+        ("ArrayRef", [element]) => format!("&[{}]", translate(element)?),
+        ("SmallVector", [element, ..]) => format!("Vec<{}>", translate(element)?),
+        ("optional" | "Optional", [value]) => format!("Option<{}>", translate(value)?),
+        ("FailureOr", [value]) => format!("FailureOr<{}>", translate(value)?),
+        _ => return None,
+    };
+    Some(r#type)
+}
+
+fn reference(inner: &str, is_reference: bool) -> Option<String> {
+    let inner = inner.strip_prefix("const ").unwrap_or(inner).trim();
+    let (name, arguments) = template(inner)?;
+    let r#type = match (name, arguments.as_slice()) {
+        ("SmallVectorImpl", [element]) if is_reference => format!("&mut Vec<{}>", translate(element)?),
+        ("Operation", []) => "Operation".to_string(),
+        ("Region", []) => "Region".to_string(),
+        ("Block", []) => "Block".to_string(),
+        _ if is_reference => match translate(inner)? {
+            r#type if r#type.starts_with('&') => r#type,
+            r#type => format!("&{type}"),
+        },
+        _ => return None,
+    };
+    Some(r#type)
+}
+
+/// Snake case argument name, escaping the keywords.
+pub(crate) fn argument_name(name: &str) -> String {
+    let name = snake_case(name);
+    match KEYWORDS.contains(&name.as_str()) {
+        true => format!("r#{name}"),
+        false => name,
+    }
+}
+
+/// Translated Rust method signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    /// Receiver of the non-static methods, like `op: Operation`.
+    pub receiver: Option<(&'static str, &'static str)>,
+    pub arguments: Vec<(String, String)>,
+    pub return_type: Option<String>,
+}
+
+impl Signature {
+    /// Translates the method, returns the C++ type without the Rust counterpart on failure.
+    pub fn new(kind: InterfaceKind, method: &InterfaceMethod) -> Result<Self, String> {
+        let receiver = match (method.is_static, kind) {
+            (true, _) => None,
+            (false, InterfaceKind::Op) => Some(("op", "Operation")),
+            (false, InterfaceKind::Attr) => Some(("attr", "Attribute")),
+            (false, InterfaceKind::Type) => Some(("r#type", "Type")),
+        };
+        let mut arguments = vec![];
+        for (cpp, name) in &method.arguments {
+            if DROPPED_ARGUMENTS.contains(&unqualified(cpp).as_str()) {
+                continue;
+            }
+            let mut name = argument_name(name);
+            if name == "ctx" || receiver.is_some_and(|(receiver, _)| receiver == name) {
+                name.push_str("_arg");
+            }
+            arguments.push((name, rust_type(cpp).ok_or_else(|| cpp.clone())?));
+        }
+        // The returned pointers may be null, like the `getSuccessorForOperands` block.
+        let nullable = method.return_type.ends_with('*');
+        let return_type = match rust_type(&method.return_type).ok_or_else(|| method.return_type.clone())? {
+            unit if unit == "()" => None,
+            return_type if nullable => Some(format!("Option<{return_type}>")),
+            return_type => Some(return_type),
+        };
+        Ok(Self {
+            name: argument_name(&method.name),
+            receiver,
+            arguments,
+            return_type,
+        })
+    }
+
+    /// `fn name<'a>(&self, ctx: &'a Context, op: Operation, ...) -> &'a [Value]`, the returned references borrow
+    /// the context.
+    pub fn declaration(&self) -> String {
+        let borrows = self.return_type.as_ref().is_some_and(|r#type| r#type.contains('&'));
+        let lifetime = match borrows {
+            true => "&'a ",
+            false => "&",
+        };
+        let mut arguments = vec!["&self".to_string(), format!("ctx: {lifetime}Context")];
+        if let Some((name, r#type)) = self.receiver {
+            arguments.push(format!("{name}: {type}"));
+        }
+        arguments.extend(self.arguments.iter().map(|(name, r#type)| format!("{name}: {type}")));
+        let return_type = match &self.return_type {
+            Some(r#type) if borrows => format!(" -> {}", r#type.replace('&', "&'a ")),
+            Some(r#type) => format!(" -> {type}"),
+            None => String::new(),
+        };
+        let generics = if borrows { "<'a>" } else { "" };
+        format!("fn {}{generics}({}){return_type}", self.name, arguments.join(", "))
+    }
+
+    /// Arguments forwarded to the hook, starting with the context and the receiver.
+    fn forwarded(&self) -> Vec<&str> {
+        std::iter::once("ctx")
+            .chain(self.receiver.map(|(name, _)| name))
+            .chain(self.arguments.iter().map(|(name, _)| name.as_str()))
+            .collect()
+    }
+}
+
+/// First paragraph of the description as the doc comment lines.
+fn doc(description: &str, code: &mut CodeWriter) {
+    for line in description.trim().lines().map(str::trim).take_while(|line| !line.is_empty()) {
+        code.line(format!("/// {line}"));
+    }
+}
+
+/// Generates the `interfaces` module with a trait per interface.
+pub fn generate_traits(interfaces: &[Interface], code: &mut CodeWriter) {
+    code.line("/// Traits of the interfaces defined in the file.");
+    code.line("pub mod interfaces {");
+    code.indent();
+    code.line("#[allow(unused_imports)]");
+    code.line("use ::fljuga_handahofi_mlir_ir::{");
+    code.line("    Attribute, Block, Context, FailureOr, LogicalResult, NamedAttribute, Operation, Region, Type, Value,");
+    code.line("};");
+    for interface in interfaces {
+        code.line("");
+        generate_trait(interface, interfaces, code);
+    }
+    code.dedent();
+    code.line("}");
+}
+
+fn generate_trait(interface: &Interface, interfaces: &[Interface], code: &mut CodeWriter) {
+    let kind = match interface.kind {
+        InterfaceKind::Op => "op",
+        InterfaceKind::Attr => "attribute",
+        InterfaceKind::Type => "type",
+    };
+    code.line(format!("/// `{}` {kind} interface.", interface.name));
+    if !interface.description.trim().is_empty() {
+        code.line("///");
+        doc(&interface.description, code);
+    }
+    let mut methods = vec![];
+    let mut untranslated = vec![];
+    for method in &interface.methods {
+        match Signature::new(interface.kind, method) {
+            Ok(signature) => methods.push((method, signature)),
+            Err(cpp) => untranslated.push((method, cpp)),
+        }
+    }
+    if !untranslated.is_empty() {
+        code.line("///");
+        code.line("/// Methods left out, their C++ types have no Rust counterpart:");
+        for (method, cpp) in &untranslated {
+            code.line(format!("/// - `{}`: `{cpp}`", method.name));
+        }
+    }
+    let bases: Vec<&str> = interface
+        .base_interfaces
+        .iter()
+        .filter(|base| interfaces.iter().any(|interface| &interface.name == *base))
+        .map(String::as_str)
+        .collect();
+    // The implementations are shared through the registry, the bases are `Sync` already.
+    let bases = match bases.is_empty() {
+        true => ": Sync".to_string(),
+        false => format!(": {}", bases.join(" + ")),
+    };
+    if methods.is_empty() {
+        code.line(format!("pub trait {}{bases} {{}}", interface.name));
+        return;
+    }
+    code.line("#[allow(unused_variables)]");
+    code.line(format!("pub trait {}{bases} {{", interface.name));
+    code.indent();
+    for (index, (method, signature)) in methods.iter().enumerate() {
+        if index > 0 {
+            code.line("");
+        }
+        doc(&method.description, code);
+        if method.is_required() {
+            code.line(format!("{};", signature.declaration()));
+        } else {
+            code.line(format!("{} {{", signature.declaration()));
+            code.line(format!(
+                "    unimplemented!(\"`{}::{}` is implemented in C++\")",
+                interface.name, method.name
+            ));
+            code.line("}");
+        }
+    }
+    code.dedent();
+    code.line("}");
+}
+
+/// Generates the `impl` of the declared interface, the `methods` listed as `Class::method` call the hooks.
+pub fn generate_impl(
+    class_name: &str,
+    declared: &DeclaredInterface,
+    interfaces: &str,
+    hooks: &str,
+    methods: &[String],
+    code: &mut CodeWriter,
+) {
+    let interface = &declared.interface;
+    let signatures: Vec<(&InterfaceMethod, Signature)> = declared
+        .declared_methods()
+        .filter_map(|method| Signature::new(interface.kind, method).ok().map(|signature| (method, signature)))
+        .collect();
+    if signatures.is_empty() {
+        code.line(format!("impl {interfaces}::{} for {class_name} {{}}", interface.name));
+        return;
+    }
+    code.line("#[allow(unused_variables)]");
+    code.line(format!("impl {interfaces}::{} for {class_name} {{", interface.name));
+    code.indent();
+    for (index, (method, signature)) in signatures.into_iter().enumerate() {
+        if index > 0 {
+            code.line("");
+        }
+        code.line(format!("{} {{", signature.declaration()));
+        if !hooks.is_empty() && methods.contains(&format!("{class_name}::{}", method.name)) {
+            code.line(format!(
+                "    {hooks}::{}_{}({})",
+                snake_case(class_name),
+                snake_case(&method.name),
+                signature.forwarded().join(", ")
+            ));
+        } else {
+            code.line(format!("    unimplemented!(\"`{class_name}::{}`\")", method.name));
+        }
+        code.line("}");
+    }
+    code.dedent();
+    code.line("}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_translate_types() {
+        assert_eq!(rust_type("::llvm::LogicalResult").as_deref(), Some("LogicalResult"));
+        assert_eq!(rust_type("::mlir::Value ").as_deref(), Some("Value"));
+        assert_eq!(rust_type("::mlir::Operation *").as_deref(), Some("Operation"));
+        assert_eq!(rust_type("::llvm::SmallVector<::mlir::Region *>").as_deref(), Some("Vec<Region>"));
+        assert_eq!(rust_type("::llvm::SmallVectorImpl<::mlir::Type>&").as_deref(), Some("&mut Vec<Type>"));
+        assert_eq!(
+            rust_type("::std::optional<::llvm::ArrayRef<int64_t>>").as_deref(),
+            Some("Option<&[i64]>")
+        );
+        assert_eq!(rust_type("const ::mlir::Type &").as_deref(), Some("&Type"));
+        assert_eq!(rust_type("::llvm::ArrayRef<::mlir::Attribute>").as_deref(), Some("&[Attribute]"));
+        assert_eq!(rust_type("::mlir::AffineMap"), None);
+        assert_eq!(rust_type("::mlir::MLIRContext *"), None);
+    }
+
+    #[test]
+    fn should_translate_signatures() {
+        let method = InterfaceMethod {
+            name: "moveOutOfLoop".to_string(),
+            description: String::new(),
+            return_type: "void".to_string(),
+            arguments: vec![("::mlir::Operation *".to_string(), "op".to_string())],
+            is_static: false,
+            has_body: false,
+            has_default: true,
+        };
+        let signature = Signature::new(InterfaceKind::Op, &method).unwrap();
+        assert_eq!(
+            signature.declaration(),
+            "fn move_out_of_loop(&self, ctx: &Context, op: Operation, op_arg: Operation)"
+        );
+
+        let method = InterfaceMethod {
+            name: "getLoopRegions".to_string(),
+            return_type: "::llvm::SmallVector<::mlir::Region *>".to_string(),
+            arguments: vec![],
+            ..method
+        };
+        assert_eq!(
+            Signature::new(InterfaceKind::Op, &method).unwrap().declaration(),
+            "fn get_loop_regions(&self, ctx: &Context, op: Operation) -> Vec<Region>"
+        );
+
+        let method = InterfaceMethod {
+            name: "inferReturnTypes".to_string(),
+            return_type: "::llvm::LogicalResult".to_string(),
+            arguments: vec![
+                ("::mlir::MLIRContext *".to_string(), "context".to_string()),
+                ("::std::optional<::mlir::Location>".to_string(), "location".to_string()),
+                ("::mlir::ValueRange".to_string(), "operands".to_string()),
+                ("::llvm::SmallVectorImpl<::mlir::Type>&".to_string(), "inferredReturnTypes".to_string()),
+            ],
+            is_static: true,
+            ..method
+        };
+        assert_eq!(
+            Signature::new(InterfaceKind::Op, &method).unwrap().declaration(),
+            "fn infer_return_types(&self, ctx: &Context, operands: &[Value], inferred_return_types: &mut Vec<Type>) -> LogicalResult"
+        );
+
+        let method = InterfaceMethod {
+            return_type: "::mlir::AffineMap".to_string(),
+            ..method
+        };
+        assert_eq!(Signature::new(InterfaceKind::Op, &method), Err("::mlir::AffineMap".to_string()));
+    }
+}
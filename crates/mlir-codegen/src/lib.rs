@@ -29,5 +29,68 @@
 #![feature(async_closure)]
 extern crate fljuga_handahofi_tablegen;
 
-mod client;
-mod tablegen;
+mod assembly_format;
+mod attr_or_type;
+mod builder;
+mod coverage;
+mod dialect;
+mod enum_attr;
+mod interface;
+mod ods;
+mod pass;
+#[cfg(test)]
+mod parity;
+mod pattern;
+mod rustdoc;
+
+use proc_macro::TokenStream;
+
+/// Generates the builders and the custom form printers and parsers of the TableGen operations out of their `assemblyFormat`,
+/// the traits of the TableGen interfaces, the options structs of the TableGen passes,
+/// and the rewrite patterns of the Declarative Rewrite Rules.
+///
+/// ```ignore
+/// fljuga_handahofi_mlir_codegen::dialect! {
+///     file = "td/TestOps.td",
+///     includes = ["td"],
+///     hooks = crate::hooks,
+///     custom = ["CustomDirectiveOperands"],
+///     interfaces = crate::interfaces,
+///     methods = ["ForOp::getLoopRegions"],
+/// }
+/// ```
+///
+/// The operations get the `build` functions taking the arguments of the upstream Python builders,
+/// the inferred result types left out and the optional operands and attributes as `Option`s.
+/// The paths are relative to the crate root, the `custom<Name>` directives call the
+/// `print_name` and `parse_name` functions of the `hooks` module.
+/// The operations declaring the interface methods implement the traits of the `interfaces` module,
+/// the `methods` call the `op_class_method` functions of the `hooks` module, the rest are `unimplemented!`.
+/// The passes of the `Passes.td` files are parsed from the `pass-name{opt=val}` pipeline elements
+/// and registered by the `passes::register` function.
+/// The `Pat` and `Pattern` records become the `RewritePattern` structs of the `patterns` module,
+/// their native code calls and constraints call the `custom` functions of the `hooks` module, like `add_integer_attrs`.
+/// The `.td` files read, the resolved includes and the `tablegen.lock` files included, are tracked
+/// with `include_str!`, so editing them rebuilds the invoking crate.
+/// The ODS summaries and descriptions become the rustdoc of the generated items, with the `mlir` examples
+/// as the `# Examples` sections.
+#[proc_macro]
+pub fn dialect(input: TokenStream) -> TokenStream {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let code = dialect::DialectArgs::parse(&input.to_string())
+        .and_then(|args| {
+            let root = std::path::Path::new(&root);
+            let (ods, tracked) = args.load(root)?;
+            let (code, _) = dialect::generate(&ods, &args);
+            let tracked = tracked
+                .iter()
+                .map(|path| format!("const _: &str = include_str!({:?});\n", path.display().to_string()));
+            Ok(tracked.collect::<String>() + &code)
+        })
+        .unwrap_or_else(|error| format!("compile_error!({:?});", error.to_string()));
+    code.parse().unwrap_or_else(|error| {
+        format!("compile_error!({:?});", format!("{error:?}"))
+            .parse()
+            .unwrap_or_default()
+    })
+}
@@ -0,0 +1,369 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir codegen ods
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! [Operation Definition Specification](https://mlir.llvm.org/docs/DefiningDialects/Operations/) model,
+//! built out of the evaluated Tablegen records, mirroring the upstream `mlir::tblgen::Operator`.
+//!
+
+pub(crate) mod attr_or_type;
+pub(crate) mod constraint;
+pub(crate) mod enum_attr;
+pub(crate) mod interface;
+pub(crate) mod operation;
+pub(crate) mod pass;
+pub(crate) mod pattern;
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{Record, RecordError, RecordKeeper};
+
+pub use attr_or_type::{AttrOrTypeDef, DefKind};
+pub use constraint::{AttrConstraint, AttrStyle, TypeConstraint};
+pub use enum_attr::EnumAttr;
+pub use interface::{DeclaredInterface, Interface, InterfaceKind, InterfaceMethod};
+pub use operation::{Argument, NamedTypeConstraint, Operation, Trait, Variadicity};
+pub use pass::{Pass, PassOption};
+pub use pattern::{DagNode, LeafConstraint, Pattern};
+
+/// Derived [thiserror::Error] for ODS records errors
+#[derive(thiserror::Error, Debug)]
+pub enum OdsError {
+    #[error("{0}")]
+    RecordError(#[from] RecordError),
+
+    #[error("Record {record:?}: {message}")]
+    InvalidRecord { record: String, message: String },
+}
+
+impl OdsError {
+    pub(crate) fn invalid(record: &Record, message: impl Into<String>) -> Self {
+        OdsError::InvalidRecord {
+            record: record.name.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+/// `Dialect` record.
+#[derive(Debug, Clone)]
+pub struct Dialect {
+    pub name: String,
+    /// C++ namespace of the dialect classes, like `::mlir::arith`.
+    pub cpp_namespace: String,
+    pub summary: String,
+    pub description: String,
+}
+
+impl Dialect {
+    pub fn from_record(record: &Rc<Record>) -> Self {
+        let string = |name| record.get_string(name).unwrap_or_default().to_string();
+        Self {
+            name: string("name"),
+            cpp_namespace: string("cppNamespace"),
+            summary: string("summary"),
+            description: string("description"),
+        }
+    }
+}
+
+/// Dialects, operations, attributes, types, enums, interfaces, passes and rewrite patterns defined in the file,
+/// the included ones are left out like `mlir-tblgen` does.
+#[derive(Debug, Default)]
+pub struct Ods {
+    pub dialects: Vec<Dialect>,
+    pub operations: Vec<Operation>,
+    /// `AttrDef` and `TypeDef` records.
+    pub defs: Vec<AttrOrTypeDef>,
+    /// `EnumAttrInfo` records.
+    pub enums: Vec<EnumAttr>,
+    pub interfaces: Vec<Interface>,
+    /// `Pass` and `InterfacePass` records of the `Passes.td` files.
+    pub passes: Vec<Pass>,
+    /// `Pattern` and `Pat` records of the Declarative Rewrite Rules.
+    pub patterns: Vec<Pattern>,
+}
+
+impl Ods {
+    pub fn from_records(keeper: &RecordKeeper, filename: &str) -> Result<Self, OdsError> {
+        let dialects = keeper
+            .derived_definitions("Dialect")
+            .filter(|record| record.filename == filename)
+            .map(Dialect::from_record)
+            .collect();
+        let operations = keeper
+            .derived_definitions("Op")
+            .filter(|record| record.filename == filename)
+            .map(|record| Operation::from_record(keeper, record))
+            .collect::<Result<_, _>>()?;
+        let defs = keeper
+            .derived_definitions("AttrOrTypeDef")
+            .filter(|record| record.filename == filename)
+            .map(AttrOrTypeDef::from_record)
+            .collect::<Result<_, _>>()?;
+        let enums = keeper
+            .derived_definitions("EnumAttrInfo")
+            .filter(|record| record.filename == filename)
+            .map(EnumAttr::from_record)
+            .collect();
+        let interfaces = keeper
+            .derived_definitions("Interface")
+            .filter(|record| record.filename == filename && !record.is_subclass_of("DeclareOpInterfaceMethods"))
+            .filter_map(Interface::from_record)
+            .collect();
+        let passes = keeper
+            .derived_definitions("PassBase")
+            .filter(|record| record.filename == filename)
+            .map(Pass::from_record)
+            .collect();
+        let patterns = keeper
+            .derived_definitions("Pattern")
+            .filter(|record| record.filename == filename)
+            .enumerate()
+            .map(|(index, record)| Pattern::from_record(keeper, record, index))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            dialects,
+            operations,
+            defs,
+            enums,
+            interfaces,
+            passes,
+            patterns,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use attr_or_type::AttrOrTypeParameter;
+    use enum_attr::EnumCase;
+    use fljuga_handahofi_tablegen::records::IncludeDirs;
+
+    /// Evaluates the file relative to the crate, returns the records and the file name.
+    pub(crate) fn records(file: &str) -> (RecordKeeper, String) {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let filename = format!("{dir}/{file}");
+        let source = std::fs::read_to_string(&filename).unwrap();
+        let includes = IncludeDirs::new([format!("{dir}/include"), format!("{dir}/src/testdata/ods")]);
+        let keeper = RecordKeeper::from_source(&filename, &source, &includes).unwrap();
+        (keeper, filename)
+    }
+
+    /// Operations of the `src/testdata/ods` file.
+    pub(crate) fn operations(file: &str) -> Vec<Operation> {
+        let (keeper, filename) = records(&format!("src/testdata/ods/{file}"));
+        Ods::from_records(&keeper, &filename).unwrap().operations
+    }
+
+    #[test]
+    fn should_load_operations() {
+        let (keeper, filename) = records("src/testdata/ods/TestOps.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+
+        assert_eq!(ods.dialects.len(), 7);
+        let addi = ods
+            .operations
+            .iter()
+            .find(|op| op.name == "arith.addi")
+            .unwrap();
+        assert_eq!(addi.class_name, "AddIOp");
+        assert_eq!(addi.operands.len(), 2);
+        assert_eq!(addi.results[0].name, "result");
+        assert!(addi.has_trait("SameOperandsAndResultType"));
+        assert!(addi.has_interface("InferTypeOpInterface"));
+
+        let br = ods.operations.iter().find(|op| op.name == "cf.br").unwrap();
+        assert_eq!(br.successors[0].name, "dest");
+        assert_eq!(br.operands[0].variadicity, Variadicity::Variadic);
+
+        let load = ods.operations.iter().find(|op| op.name == "memref.load").unwrap();
+        assert_eq!(
+            load.traits,
+            vec![
+                Trait::TypesMatchWith {
+                    lhs: "memref".to_string(),
+                    rhs: "result".to_string(),
+                    transformer: "::llvm::cast<::mlir::MemRefType>($_self).getElementType()".to_string(),
+                },
+                Trait::Interface("MemoryEffectOpInterface".to_string()),
+            ]
+        );
+        assert_eq!(load.extra_class_declaration, None);
+
+        let for_op = ods.operations.iter().find(|op| op.name == "scf.for").unwrap();
+        assert!(for_op
+            .extra_class_declaration
+            .as_deref()
+            .is_some_and(|declaration| declaration.starts_with("using BodyBuilderFn")));
+    }
+
+    #[test]
+    fn should_load_attribute_and_type_definitions() {
+        let (keeper, filename) = records("src/testdata/ods/TestAttrDefs.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        let ptr = ods.defs.iter().find(|def| def.class_name == "LLVMPointerType").unwrap();
+        assert_eq!(ptr.kind, DefKind::Type);
+        assert_eq!(ptr.name().as_deref(), Some("llvm.ptr"));
+        assert_eq!(
+            ptr.extra_class_declaration.as_deref(),
+            Some("bool isOpaque() const { return true; }")
+        );
+        assert_eq!(
+            ptr.parameters,
+            vec![AttrOrTypeParameter {
+                name: "addressSpace".to_string(),
+                cpp_type: "unsigned".to_string(),
+                summary: String::new(),
+                optional: false,
+                default_value: Some("0".to_string()),
+            }]
+        );
+        let attr = ods.defs.iter().find(|def| def.class_name == "TestAttrWithFormatAttr").unwrap();
+        assert_eq!(attr.kind, DefKind::Attr);
+        assert_eq!(attr.prefix(), '#');
+        assert_eq!(attr.parameters[1].cpp_type, "::llvm::StringRef");
+        assert_eq!(attr.parameters[1].default_value, None);
+        assert_eq!(attr.parameters[3].cpp_type, "::llvm::ArrayRef<int>");
+        let optional = ods.defs.iter().find(|def| def.class_name == "TestTypeOptionalStructType").unwrap();
+        assert!(optional.parameters[0].optional);
+    }
+
+    #[test]
+    fn should_load_enums() {
+        let (keeper, filename) = records("include/mlir/Dialect/Arith/IR/ArithOps.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        assert_eq!(ods.dialects[0].cpp_namespace, "::mlir::arith");
+        let predicate = &ods.enums[0];
+        assert_eq!(predicate.class_name, "CmpIPredicate");
+        assert_eq!(predicate.cpp_namespace, "::mlir::arith");
+        assert_eq!(predicate.r#type.as_deref(), Some("i64"));
+        assert_eq!(
+            predicate.cases[2],
+            EnumCase {
+                symbol: "slt".to_string(),
+                keyword: "slt".to_string(),
+                value: 2,
+            }
+        );
+        assert_eq!(predicate.cases.len(), 10);
+    }
+
+    #[test]
+    fn should_load_passes() {
+        let (keeper, filename) = records("src/testdata/ods/TestPasses.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        assert!(ods.operations.is_empty());
+        let inliner = ods.passes.iter().find(|pass| pass.name == "Inliner").unwrap();
+        assert_eq!(inliner.argument, "inline");
+        assert_eq!(inliner.anchor(), None);
+        assert_eq!(
+            inliner.options[0],
+            PassOption {
+                cpp_name: "defaultPipelineStr".to_string(),
+                argument: "default-pipeline".to_string(),
+                cpp_type: "std::string".to_string(),
+                default_value: "\"canonicalize\"".to_string(),
+                description: "The optimizer pipeline used for callables that do not have \
+                              a dedicated optimizer pipeline in opPipelineList"
+                    .to_string(),
+                is_list: false,
+            }
+        );
+        assert!(inliner.options[1].is_list);
+        let convert = ods.passes.iter().find(|pass| pass.argument == "convert-func-to-llvm").unwrap();
+        assert_eq!(convert.anchor(), Some("ModuleOp"));
+        assert_eq!(convert.dependent_dialects, vec!["LLVM::LLVMDialect", "mlir::DLTIDialect"]);
+        let licm = ods.passes.iter().find(|pass| pass.name == "LoopInvariantCodeMotion").unwrap();
+        assert_eq!(licm.anchor(), Some("LoopLikeOpInterface"));
+    }
+
+    #[test]
+    fn should_load_patterns() {
+        let (keeper, filename) = records("../targets/src/arith/ArithCanonicalization.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        assert!(ods.operations.is_empty());
+        assert_eq!(ods.patterns.len(), 21);
+        let add = &ods.patterns[0];
+        assert_eq!(add.name, "AddIAddConstant");
+        assert_eq!(add.benefit(), 2);
+        assert_eq!(
+            add.source.to_string(),
+            "(Arith_AddIOp:$res (Arith_AddIOp $x, (ConstantLikeMatcher APIntAttr:$c0)), (ConstantLikeMatcher APIntAttr:$c1))"
+        );
+        assert_eq!(
+            add.results[0].to_string(),
+            "(Arith_AddIOp $x, (Arith_ConstantOp (AddIntegerAttrs $res, $c0, $c1)))"
+        );
+        let cmpi = ods.patterns.iter().find(|pattern| pattern.name == "CmpIExtSI").unwrap();
+        assert_eq!(
+            cmpi.constraints.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["(Constraint<CPred<\"$0.getType() == $1.getType()\">> $a, $b)", "(IsEqOrNe $pred)"]
+        );
+        let DagNode::Operation { op, .. } = &cmpi.source else { panic!("expected an operation") };
+        assert_eq!(op.arguments, vec![Argument::Attribute(0), Argument::Operand(0), Argument::Operand(1)]);
+        let not = ods.patterns.iter().find(|pattern| pattern.name == "SelectI1ToNot").unwrap();
+        assert_eq!(
+            not.results[0].to_string(),
+            "(Arith_XOrIOp $pred, (Arith_ConstantOp ConstantAttr<I1Attr, \"1\">))"
+        );
+        let index_cast = ods.patterns.iter().find(|pattern| pattern.name == "IndexCastOfIndexCast").unwrap();
+        assert_eq!(index_cast.results[0].to_string(), "(replaceWithValue $x)");
+    }
+
+    #[test]
+    fn should_load_interfaces() {
+        let (keeper, filename) = records("src/testdata/ods/TestInterfaces.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        let names: Vec<&str> = ods.interfaces.iter().map(|interface| interface.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "InferTypeOpInterface",
+                "MemoryEffectOpInterface",
+                "BranchOpInterface",
+                "LoopLikeOpInterface",
+                "ShapedType",
+                "MemRefLayoutAttrInterface"
+            ]
+        );
+        assert_eq!(ods.interfaces[4].kind, InterfaceKind::Type);
+        let infer = &ods.interfaces[0].methods[0];
+        assert_eq!(infer.name, "inferReturnTypes");
+        assert!(infer.is_static && infer.is_required());
+        assert_eq!(
+            infer.arguments[6],
+            (
+                "::llvm::SmallVectorImpl<::mlir::Type>&".to_string(),
+                "inferredReturnTypes".to_string()
+            )
+        );
+
+        let (keeper, filename) = records("src/testdata/ods/TestOps.td");
+        let ods = Ods::from_records(&keeper, &filename).unwrap();
+        assert!(ods.interfaces.is_empty());
+        let for_op = ods.operations.iter().find(|op| op.name == "scf.for").unwrap();
+        let declared = &for_op.declared_interfaces[0];
+        assert_eq!(declared.interface.name, "LoopLikeOpInterface");
+        let methods: Vec<&str> = declared.declared_methods().map(|method| method.name.as_str()).collect();
+        assert_eq!(methods, vec!["getLoopRegions", "getLoopInductionVars"]);
+    }
+}
@@ -0,0 +1,142 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `AttrDef` and `TypeDef` records with their parameters.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::Record;
+
+use crate::ods::OdsError;
+
+/// Entity the definition describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Attr,
+    Type,
+}
+
+/// `AttrOrTypeParameter` record or the C++ type string, mirroring the upstream `mlir::tblgen::AttrOrTypeParameter`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrOrTypeParameter {
+    pub name: String,
+    /// C++ type, like `::llvm::ArrayRef<int>`.
+    pub cpp_type: String,
+    pub summary: String,
+    pub optional: bool,
+    /// C++ default value expression, the empty ones are left out like the upstream `getDefaultValue`.
+    pub default_value: Option<String>,
+}
+
+/// `AttrDef` or `TypeDef` record, mirroring the upstream `mlir::tblgen::AttrOrTypeDef`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrOrTypeDef {
+    pub kind: DefKind,
+    /// C++ class name, like `LLVMPointerType`.
+    pub class_name: String,
+    pub dialect: String,
+    pub mnemonic: Option<String>,
+    pub summary: String,
+    pub description: String,
+    pub parameters: Vec<AttrOrTypeParameter>,
+    pub assembly_format: Option<String>,
+    pub has_custom_assembly_format: bool,
+    pub skip_default_builders: bool,
+    /// C++ only `extraClassDeclaration`, it has no Rust counterpart.
+    pub extra_class_declaration: Option<String>,
+}
+
+impl AttrOrTypeDef {
+    pub fn from_record(record: &Rc<Record>) -> Result<Self, OdsError> {
+        let kind = match record.is_subclass_of("AttrDef") {
+            true => DefKind::Attr,
+            false => DefKind::Type,
+        };
+        let string = |name| record.get_string(name).map(str::to_string);
+        let dialect = record
+            .get_def("dialect")
+            .and_then(|dialect| dialect.get_string("name"))
+            .ok_or_else(|| OdsError::invalid(record, "definition without a dialect"))?;
+        let mut parameters = vec![];
+        if let Some(dag) = record.get_dag("parameters") {
+            for (init, name) in &dag.args {
+                let name = name
+                    .clone()
+                    .ok_or_else(|| OdsError::invalid(record, "unnamed parameter"))?;
+                let parameter = match (init.as_str(), init.as_def()) {
+                    (Some(cpp_type), _) => AttrOrTypeParameter {
+                        name,
+                        cpp_type: cpp_type.trim().to_string(),
+                        summary: String::new(),
+                        optional: false,
+                        default_value: None,
+                    },
+                    (None, Some(def)) => AttrOrTypeParameter {
+                        name,
+                        cpp_type: def.get_string("cppType").unwrap_or_default().trim().to_string(),
+                        summary: def.get_string("summary").unwrap_or_default().to_string(),
+                        optional: def.get_bit("isOptional").unwrap_or_default(),
+                        default_value: def
+                            .get_string("defaultValue")
+                            .map(str::trim)
+                            .filter(|value| !value.is_empty())
+                            .map(str::to_string),
+                    },
+                    _ => return Err(OdsError::invalid(record, format!("invalid parameter {name:?}"))),
+                };
+                parameters.push(parameter);
+            }
+        }
+        Ok(Self {
+            kind,
+            class_name: string("cppClassName").unwrap_or_else(|| record.name.clone()),
+            dialect: dialect.to_string(),
+            mnemonic: string("mnemonic"),
+            summary: string("summary").unwrap_or_default(),
+            description: string("description").unwrap_or_default(),
+            parameters,
+            assembly_format: record
+                .get_string("assemblyFormat")
+                .map(str::trim)
+                .filter(|format| !format.is_empty())
+                .map(str::to_string),
+            has_custom_assembly_format: record.get_bit("hasCustomAssemblyFormat").unwrap_or_default(),
+            skip_default_builders: record.get_bit("skipDefaultBuilders").unwrap_or_default(),
+            extra_class_declaration: record
+                .get_string("extraClassDeclaration")
+                .map(str::trim)
+                .filter(|declaration| !declaration.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// `dialect.mnemonic`, the definitions without the mnemonic can't be printed by the dialect.
+    pub fn name(&self) -> Option<String> {
+        self.mnemonic
+            .as_ref()
+            .map(|mnemonic| format!("{}.{mnemonic}", self.dialect))
+    }
+
+    /// `#` for the attributes, `!` for the types.
+    pub fn prefix(&self) -> char {
+        match self.kind {
+            DefKind::Attr => '#',
+            DefKind::Type => '!',
+        }
+    }
+}
@@ -0,0 +1,241 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Type and attribute constraints of the operation arguments and results.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::Record;
+
+use crate::ods::EnumAttr;
+
+/// `TypeConstraint` record, the variadic wrappers are unwrapped by [crate::ods::NamedTypeConstraint].
+#[derive(Debug, Clone)]
+pub struct TypeConstraint {
+    /// Type built by the `builderCall` of the buildable types, like `i1`.
+    pub buildable: Option<String>,
+    /// Dialect name of the `TypeDef` types, printed without their `!dialect.` prefix.
+    /// The parameterless ones are printed in full, like the upstream `printStrippedAttrOrType` fallback.
+    pub dialect: Option<String>,
+}
+
+impl TypeConstraint {
+    pub fn from_record(record: &Rc<Record>) -> Self {
+        let parameterless = parameterless_type(record);
+        Self {
+            dialect: match parameterless {
+                Some(_) => None,
+                None => def_dialect(record, "TypeDef"),
+            },
+            buildable: record.get_string("builderCall").and_then(buildable_type).or(parameterless),
+        }
+    }
+}
+
+/// How the attribute is printed and parsed by the generated custom form.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrStyle {
+    /// Enum case keyword, stored as the integer of the given type.
+    Enum { cases: Vec<(i128, String)>, r#type: String },
+    /// `@symbol` of the `SymbolNameAttr`.
+    SymbolName,
+    /// Integer or float without its buildable type.
+    WithoutType(String),
+    /// The attribute is printed in full.
+    Full,
+    /// Dense array without its `array<...>` wrapper.
+    DenseArray(String),
+    /// Dialect attribute without its `#dialect.` prefix.
+    Dialect(String),
+    /// Builtin attribute printed as is.
+    Stripped,
+}
+
+/// `Attr` record of the operation arguments.
+#[derive(Debug, Clone)]
+pub struct AttrConstraint {
+    /// The innermost attribute of the `OptionalAttr` and `DefaultValuedAttr` wrappers.
+    pub base_name: String,
+    pub storage_type: String,
+    pub optional: bool,
+    pub default_value: Option<String>,
+    /// Buildable `valueType`, like `i64` of the `I64Attr`.
+    pub value_type: Option<String>,
+    /// Enum cases, the unsupported bit enums have none.
+    pub enum_cases: Option<Vec<(i128, String)>>,
+    pub bit_enum: bool,
+    /// Integer type of the enum storage.
+    pub enum_type: Option<String>,
+    pub dialect: Option<String>,
+    pub derived: bool,
+}
+
+impl AttrConstraint {
+    pub fn from_record(record: &Rc<Record>) -> Self {
+        let mut base = record.clone();
+        while let Some(inner) = base.get_def("baseAttr") {
+            if Rc::ptr_eq(inner, &base) {
+                break;
+            }
+            base = inner.clone();
+        }
+        let enum_attr = base.is_subclass_of("EnumAttrInfo").then(|| EnumAttr::from_record(&base));
+        Self {
+            base_name: base.name.clone(),
+            storage_type: record
+                .get_string("storageType")
+                .map(str::trim)
+                .unwrap_or("::mlir::Attribute")
+                .to_string(),
+            optional: record.get_bit("isOptional").unwrap_or_default(),
+            default_value: record
+                .get_string("defaultValue")
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            value_type: record
+                .get_def("valueType")
+                .and_then(|value_type| value_type.get_string("builderCall"))
+                .and_then(buildable_type),
+            bit_enum: base.is_subclass_of("BitEnumAttr"),
+            enum_cases: enum_attr.as_ref().map(|enum_attr| {
+                enum_attr
+                    .cases
+                    .iter()
+                    .map(|case| (case.value, case.keyword.clone()))
+                    .collect()
+            }),
+            enum_type: enum_attr.and_then(|enum_attr| enum_attr.r#type),
+            dialect: def_dialect(&base, "AttrDef"),
+            derived: record.is_subclass_of("DerivedAttr"),
+        }
+    }
+
+    pub fn is_unit(&self) -> bool {
+        self.storage_type == "::mlir::UnitAttr"
+    }
+
+    /// Mirrors the upstream `OpFormatGen` attribute printer selection.
+    pub fn style(&self, qualified: bool) -> AttrStyle {
+        if let (Some(cases), Some(r#type), false) = (&self.enum_cases, &self.enum_type, self.bit_enum) {
+            return AttrStyle::Enum {
+                cases: cases.clone(),
+                r#type: r#type.clone(),
+            };
+        }
+        if self.base_name == "SymbolNameAttr" {
+            return AttrStyle::SymbolName;
+        }
+        if let Some(value_type) = &self.value_type {
+            return AttrStyle::WithoutType(value_type.clone());
+        }
+        if qualified || self.optional || self.storage_type == "::mlir::Attribute" {
+            return AttrStyle::Full;
+        }
+        if let Some(dialect) = &self.dialect {
+            return AttrStyle::Dialect(dialect.clone());
+        }
+        match dense_array_element(&self.storage_type) {
+            Some(element) => AttrStyle::DenseArray(element.to_string()),
+            None => AttrStyle::Stripped,
+        }
+    }
+}
+
+/// Dialect name of the `TypeDef` and `AttrDef` records.
+fn def_dialect(record: &Record, class: &str) -> Option<String> {
+    if !record.is_subclass_of(class) {
+        return None;
+    }
+    record
+        .get_def("dialect")
+        .and_then(|dialect| dialect.get_string("name"))
+        .map(str::to_string)
+}
+
+/// `TypeDef` without parameters, built by its `$_builder.getType<...>()` upstream, like `!gpu.async.token`.
+fn parameterless_type(record: &Record) -> Option<String> {
+    let dialect = def_dialect(record, "TypeDef")?;
+    if record.get_dag("parameters").is_some_and(|parameters| !parameters.args.is_empty()) {
+        return None;
+    }
+    let mnemonic = record.get_string("mnemonic")?;
+    Some(format!("!{dialect}.{mnemonic}"))
+}
+
+/// Element type of the `DenseArrayAttr` storage types.
+fn dense_array_element(storage_type: &str) -> Option<&'static str> {
+    let name = storage_type.trim_start_matches("::mlir::");
+    let element = match name {
+        "DenseBoolArrayAttr" => "i1",
+        "DenseI8ArrayAttr" => "i8",
+        "DenseI16ArrayAttr" => "i16",
+        "DenseI32ArrayAttr" => "i32",
+        "DenseI64ArrayAttr" => "i64",
+        "DenseF32ArrayAttr" => "f32",
+        "DenseF64ArrayAttr" => "f64",
+        _ => return None,
+    };
+    Some(element)
+}
+
+/// Type built by the `BuildableType` builder call, like `$_builder.getIntegerType(32)`.
+pub fn buildable_type(builder_call: &str) -> Option<String> {
+    let call = builder_call.trim().strip_prefix("$_builder.")?;
+    let (method, arguments) = call.strip_suffix(')')?.split_once('(')?;
+    let arguments: Vec<&str> = arguments
+        .split(',')
+        .map(|argument| argument.split("*/").last().unwrap_or_default().trim())
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    let r#type = match (method, arguments.as_slice()) {
+        ("getIntegerType", [width]) => format!("i{}", width.parse::<u32>().ok()?),
+        ("getIntegerType", [width, "true"]) => format!("si{}", width.parse::<u32>().ok()?),
+        ("getIntegerType", [width, "false"]) => format!("ui{}", width.parse::<u32>().ok()?),
+        ("getI1Type", []) => "i1".to_string(),
+        ("getIndexType", []) => "index".to_string(),
+        ("getNoneType", []) => "none".to_string(),
+        (method, []) => {
+            let name = method.strip_prefix("get")?.strip_suffix("Type")?;
+            match name {
+                "I8" | "I16" | "I32" | "I64" => name.to_lowercase(),
+                "F16" | "BF16" | "TF32" | "F32" | "F64" | "F80" | "F128" => name.to_lowercase(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(r#type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_types() {
+        assert_eq!(buildable_type("$_builder.getI1Type()").as_deref(), Some("i1"));
+        assert_eq!(buildable_type("$_builder.getIntegerType(64)").as_deref(), Some("i64"));
+        assert_eq!(
+            buildable_type("$_builder.getIntegerType(8, /*isSigned=*/true)").as_deref(),
+            Some("si8")
+        );
+        assert_eq!(buildable_type("$_builder.getF32Type()").as_deref(), Some("f32"));
+        assert_eq!(buildable_type("$_builder.getBF16Type()").as_deref(), Some("bf16"));
+        assert_eq!(buildable_type("$_builder.getType<::mlir::NoneType>()"), None);
+    }
+}
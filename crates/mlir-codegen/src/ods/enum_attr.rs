@@ -0,0 +1,90 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `EnumAttrInfo` records with their cases, mirroring the upstream `mlir::tblgen::EnumAttr`.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{Init, Record};
+
+/// `EnumAttrCaseInfo` record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumCase {
+    /// C++ enumerant, like `slt`.
+    pub symbol: String,
+    /// Keyword of the textual form, the symbol unless given.
+    pub keyword: String,
+    pub value: i128,
+}
+
+impl EnumCase {
+    fn from_record(record: &Rc<Record>) -> Self {
+        let symbol = record.get_string("symbol").unwrap_or_default();
+        Self {
+            symbol: symbol.to_string(),
+            keyword: record.get_string("str").unwrap_or(symbol).to_string(),
+            value: record.get_int("value").unwrap_or_default() as i128,
+        }
+    }
+}
+
+/// `EnumAttrInfo` record, like the `I64EnumAttr` of the `arith.cmpi` predicates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumAttr {
+    /// C++ enum class name, like `CmpIPredicate`.
+    pub class_name: String,
+    /// C++ namespace of the enum class, like `::mlir::arith`.
+    pub cpp_namespace: String,
+    pub summary: String,
+    pub description: String,
+    pub cases: Vec<EnumCase>,
+    /// Integer type of the attribute storage, like `i64`.
+    pub r#type: Option<String>,
+    pub bit_enum: bool,
+}
+
+impl EnumAttr {
+    pub fn from_record(record: &Rc<Record>) -> Self {
+        let string = |name| record.get_string(name).unwrap_or_default().trim().to_string();
+        Self {
+            class_name: string("className"),
+            cpp_namespace: string("cppNamespace"),
+            summary: string("summary"),
+            description: string("description"),
+            cases: record
+                .get_list("enumerants")
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Init::as_def)
+                .map(EnumCase::from_record)
+                .collect(),
+            r#type: record.get_string("underlyingType").and_then(integer_type),
+            bit_enum: record.is_subclass_of("BitEnumAttr"),
+        }
+    }
+}
+
+/// Integer type of the C++ underlying type, like `i32` of `uint32_t`.
+fn integer_type(underlying: &str) -> Option<String> {
+    let width = underlying
+        .trim_start_matches("::")
+        .trim_start_matches("std::")
+        .trim_start_matches(['u', 'i', 'n', 't'])
+        .trim_end_matches("_t");
+    width.parse::<u32>().ok().map(|width| format!("i{width}"))
+}
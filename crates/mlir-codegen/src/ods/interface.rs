@@ -0,0 +1,149 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `OpInterface`, `AttrInterface` and `TypeInterface` records with their methods.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{Init, Record};
+
+/// Entity the interface is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceKind {
+    Op,
+    Attr,
+    Type,
+}
+
+/// `InterfaceMethod` or `StaticInterfaceMethod` record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceMethod {
+    pub name: String,
+    pub description: String,
+    /// C++ return type, like `::llvm::LogicalResult`.
+    pub return_type: String,
+    /// C++ argument types with their names.
+    pub arguments: Vec<(String, String)>,
+    pub is_static: bool,
+    /// The method is implemented by the interface itself, the entities can't override it.
+    pub has_body: bool,
+    pub has_default: bool,
+}
+
+impl InterfaceMethod {
+    fn from_record(record: &Rc<Record>) -> Self {
+        let string = |name| record.get_string(name).unwrap_or_default().to_string();
+        let arguments = record
+            .get_dag("arguments")
+            .map(|dag| {
+                dag.args
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (init, name))| {
+                        let name = name.clone().unwrap_or_else(|| format!("odsArg{index}"));
+                        (init.as_str().unwrap_or_default().trim().to_string(), name)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            name: string("name"),
+            description: string("description"),
+            return_type: string("returnType").trim().to_string(),
+            arguments,
+            is_static: record.is_subclass_of("StaticInterfaceMethod"),
+            has_body: !string("body").trim().is_empty(),
+            has_default: !string("defaultBody").trim().is_empty(),
+        }
+    }
+
+    /// The entities have to implement the method, like the upstream `InterfaceMethod::getDefaultImplementation`.
+    pub fn is_required(&self) -> bool {
+        !self.has_body && !self.has_default
+    }
+}
+
+/// `Interface` record, mirroring the upstream `mlir::tblgen::Interface`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    /// C++ interface name, like `LoopLikeOpInterface`.
+    pub name: String,
+    pub kind: InterfaceKind,
+    pub description: String,
+    pub methods: Vec<InterfaceMethod>,
+    pub base_interfaces: Vec<String>,
+}
+
+impl Interface {
+    pub fn from_record(record: &Rc<Record>) -> Option<Self> {
+        let kind = if record.is_subclass_of("OpInterface") {
+            InterfaceKind::Op
+        } else if record.is_subclass_of("AttrInterface") {
+            InterfaceKind::Attr
+        } else if record.is_subclass_of("TypeInterface") {
+            InterfaceKind::Type
+        } else {
+            return None;
+        };
+        let defs = |name| record.get_list(name).unwrap_or_default().iter().filter_map(Init::as_def);
+        Some(Self {
+            name: record.get_string("cppInterfaceName")?.to_string(),
+            kind,
+            description: record.get_string("description").unwrap_or_default().to_string(),
+            methods: defs("methods").map(InterfaceMethod::from_record).collect(),
+            base_interfaces: defs("baseInterfaces")
+                .filter_map(|base| base.get_string("cppInterfaceName"))
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
+
+/// `DeclareOpInterfaceMethods<Interface, [methods]>` of the operation traits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredInterface {
+    pub interface: Interface,
+    /// Methods with the default implementation the operation overrides anyway.
+    pub overridden: Vec<String>,
+}
+
+impl DeclaredInterface {
+    pub fn from_record(record: &Rc<Record>) -> Option<Self> {
+        if !record.is_subclass_of("DeclareOpInterfaceMethods") {
+            return None;
+        }
+        Some(Self {
+            interface: Interface::from_record(record)?,
+            overridden: record
+                .get_list("alwaysOverriddenMethods")
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Init::as_str)
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    /// Methods the operation declares, like the upstream `OpInterfaceTrait::shouldDeclareMethod`.
+    pub fn declared_methods(&self) -> impl Iterator<Item = &InterfaceMethod> {
+        self.interface
+            .methods
+            .iter()
+            .filter(|method| !method.has_body && (!method.has_default || self.overridden.contains(&method.name)))
+    }
+}
@@ -0,0 +1,369 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `Op` records with their arguments, results, regions, successors and traits.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{Init, Record, RecordKeeper};
+
+use crate::ods::{AttrConstraint, DeclaredInterface, Dialect, OdsError, TypeConstraint};
+
+/// Operand, result, region or successor variadicity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variadicity {
+    Single,
+    Optional,
+    Variadic,
+    VariadicOfVariadic,
+}
+
+impl Variadicity {
+    pub fn is_variable_length(self) -> bool {
+        self != Variadicity::Single
+    }
+}
+
+/// Named operand or result.
+#[derive(Debug, Clone)]
+pub struct NamedTypeConstraint {
+    pub name: String,
+    pub constraint: TypeConstraint,
+    pub variadicity: Variadicity,
+}
+
+impl NamedTypeConstraint {
+    fn from_record(name: String, record: &Rc<Record>) -> Self {
+        let variadicity = if record.is_subclass_of("VariadicOfVariadic") {
+            Variadicity::VariadicOfVariadic
+        } else if record.is_subclass_of("Variadic") {
+            Variadicity::Variadic
+        } else if record.is_subclass_of("Optional") {
+            Variadicity::Optional
+        } else {
+            Variadicity::Single
+        };
+        let base = match variadicity {
+            Variadicity::Single => record,
+            _ => record.get_def("baseType").unwrap_or(record),
+        };
+        Self {
+            name,
+            constraint: TypeConstraint::from_record(base),
+            variadicity,
+        }
+    }
+}
+
+/// Named attribute argument.
+#[derive(Debug, Clone)]
+pub struct NamedAttribute {
+    pub name: String,
+    pub constraint: AttrConstraint,
+}
+
+/// Named region.
+#[derive(Debug, Clone)]
+pub struct NamedRegion {
+    pub name: String,
+    pub variadic: bool,
+}
+
+/// Named successor.
+#[derive(Debug, Clone)]
+pub struct NamedSuccessor {
+    pub name: String,
+    pub variadic: bool,
+}
+
+/// Operand, attribute or property of the operation `arguments`, indexing their own lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    Operand(usize),
+    Attribute(usize),
+    Property(usize),
+}
+
+/// Operation trait, the ones used for the type inference are kept apart.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trait {
+    /// `NativeOpTrait` by its C++ trait name, like `SameOperandsAndResultType`.
+    Native(String),
+    /// `OpInterface` by its C++ interface name, like `InferTypeOpInterface`.
+    Interface(String),
+    /// `AllTypesMatch<["lhs", "rhs"]>`
+    AllTypesMatch(Vec<String>),
+    /// `TypesMatchWith<summary, lhs, rhs, transformer>`
+    TypesMatchWith {
+        lhs: String,
+        rhs: String,
+        transformer: String,
+    },
+    /// Any other predicate trait by its summary.
+    Pred(String),
+}
+
+/// `Op` record, mirroring the upstream `mlir::tblgen::Operator`.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    /// Operation name with its dialect prefix, like `arith.addi`.
+    pub name: String,
+    pub dialect: String,
+    /// C++ class name, like `AddIOp` of the `Arith_AddIOp` record.
+    pub class_name: String,
+    pub summary: String,
+    /// Markdown description, with the indentation of the TableGen code.
+    pub description: String,
+    pub operands: Vec<NamedTypeConstraint>,
+    pub attributes: Vec<NamedAttribute>,
+    /// Operands, attributes and properties in their `arguments` order, like the upstream `Operator::getArg`.
+    pub arguments: Vec<Argument>,
+    /// Properties are not supported by the generated custom forms.
+    pub properties: Vec<String>,
+    pub results: Vec<NamedTypeConstraint>,
+    pub regions: Vec<NamedRegion>,
+    pub successors: Vec<NamedSuccessor>,
+    pub traits: Vec<Trait>,
+    /// Interfaces of the `DeclareOpInterfaceMethods` traits.
+    pub declared_interfaces: Vec<DeclaredInterface>,
+    pub assembly_format: Option<String>,
+    pub has_custom_assembly_format: bool,
+    /// Operation name of the `SingleBlockImplicitTerminator` trait.
+    pub implicit_terminator: Option<String>,
+    /// C++ only `extraClassDeclaration`, it has no Rust counterpart.
+    pub extra_class_declaration: Option<String>,
+}
+
+impl Operation {
+    pub fn from_record(keeper: &RecordKeeper, record: &Rc<Record>) -> Result<Self, OdsError> {
+        let dialect = record
+            .get_def("opDialect")
+            .map(Dialect::from_record)
+            .ok_or_else(|| OdsError::invalid(record, "operation has no dialect"))?;
+        let op_name = record
+            .get_string("opName")
+            .ok_or_else(|| OdsError::invalid(record, "operation has no name"))?;
+        let name = if dialect.name.is_empty() {
+            op_name.to_string()
+        } else {
+            format!("{}.{op_name}", dialect.name)
+        };
+        let class_name = match record.name.split_once('_') {
+            Some((prefix, class)) if !prefix.is_empty() && !class.is_empty() => class.to_string(),
+            _ => record.name.clone(),
+        };
+        let string = |field| record.get_string(field).unwrap_or_default().to_string();
+
+        let mut operation = Self {
+            name,
+            dialect: dialect.name.clone(),
+            class_name,
+            summary: string("summary"),
+            description: string("description"),
+            operands: vec![],
+            attributes: vec![],
+            arguments: vec![],
+            properties: vec![],
+            results: vec![],
+            regions: vec![],
+            successors: vec![],
+            traits: vec![],
+            declared_interfaces: vec![],
+            assembly_format: record
+                .get_string("assemblyFormat")
+                .map(str::trim)
+                .filter(|format| !format.is_empty())
+                .map(str::to_string),
+            has_custom_assembly_format: record.get_bit("hasCustomAssemblyFormat").unwrap_or_default(),
+            implicit_terminator: None,
+            extra_class_declaration: record
+                .get_string("extraClassDeclaration")
+                .map(str::trim)
+                .filter(|declaration| !declaration.is_empty())
+                .map(str::to_string),
+        };
+
+        for (index, (arg, name)) in dag_args(record, "arguments").iter().enumerate() {
+            let name = name.clone().unwrap_or_else(|| format!("odsArg{index}"));
+            let mut arg = arg.clone();
+            while arg.is_subclass_of("OpVariable") {
+                arg = arg
+                    .get_def("constraint")
+                    .cloned()
+                    .ok_or_else(|| OdsError::invalid(record, format!("argument {name:?} has no constraint")))?;
+            }
+            if arg.is_subclass_of("TypeConstraint") {
+                operation.arguments.push(Argument::Operand(operation.operands.len()));
+                operation.operands.push(NamedTypeConstraint::from_record(name, &arg));
+            } else if arg.is_subclass_of("AttrConstraint") {
+                let constraint = AttrConstraint::from_record(&arg);
+                if !constraint.derived {
+                    operation.arguments.push(Argument::Attribute(operation.attributes.len()));
+                    operation.attributes.push(NamedAttribute { name, constraint });
+                }
+            } else if arg.is_subclass_of("Property") {
+                operation.arguments.push(Argument::Property(operation.properties.len()));
+                operation.properties.push(name);
+            } else {
+                return Err(OdsError::invalid(record, format!("unknown argument {name:?} kind")));
+            }
+        }
+        for (index, (result, name)) in dag_args(record, "results").iter().enumerate() {
+            let mut result = result.clone();
+            while result.is_subclass_of("OpVariable") {
+                result = result.get_def("constraint").cloned().unwrap_or(result);
+            }
+            let name = name.clone().unwrap_or_else(|| format!("odsResult{index}"));
+            operation.results.push(NamedTypeConstraint::from_record(name, &result));
+        }
+        for (index, (region, name)) in dag_args(record, "regions").iter().enumerate() {
+            operation.regions.push(NamedRegion {
+                name: name.clone().unwrap_or_else(|| format!("odsRegion{index}")),
+                variadic: region.is_subclass_of("VariadicRegion"),
+            });
+        }
+        for (index, (successor, name)) in dag_args(record, "successors").iter().enumerate() {
+            operation.successors.push(NamedSuccessor {
+                name: name.clone().unwrap_or_else(|| format!("odsSuccessor{index}")),
+                variadic: successor.is_subclass_of("VariadicSuccessor"),
+            });
+        }
+
+        let mut traits = vec![];
+        flatten_traits(record.get_list("traits").unwrap_or_default(), &mut traits);
+        for r#trait in traits {
+            if let Some(name) = r#trait.get_string("cppInterfaceName") {
+                operation.traits.push(Trait::Interface(name.to_string()));
+                operation.declared_interfaces.extend(DeclaredInterface::from_record(&r#trait));
+            } else if let Some(name) = r#trait.get_string("trait") {
+                if let Some(terminator) = name
+                    .strip_prefix("SingleBlockImplicitTerminator<")
+                    .and_then(|name| name.split_once('>'))
+                    .map(|(terminator, _)| terminator)
+                {
+                    operation.implicit_terminator = Some(terminator_name(keeper, &dialect, terminator));
+                }
+                operation.traits.push(Trait::Native(name.to_string()));
+            } else if let Some(values) = r#trait.get_list("values") {
+                let values = values.iter().filter_map(Init::as_str).map(str::to_string).collect();
+                operation.traits.push(Trait::AllTypesMatch(values));
+            } else if let (Some(lhs), Some(rhs), Some(transformer)) = (
+                r#trait.get_string("lhs"),
+                r#trait.get_string("rhs"),
+                r#trait.get_string("transformer"),
+            ) {
+                operation.traits.push(Trait::TypesMatchWith {
+                    lhs: lhs.to_string(),
+                    rhs: rhs.to_string(),
+                    transformer: transformer.to_string(),
+                });
+            } else if r#trait.is_subclass_of("PredOpTrait") {
+                let summary = r#trait.get_string("summary").unwrap_or_default();
+                operation.traits.push(Trait::Pred(summary.to_string()));
+            }
+        }
+        Ok(operation)
+    }
+
+    /// Checks the native trait by its name, the template arguments are ignored.
+    pub fn has_trait(&self, name: &str) -> bool {
+        self.traits.iter().any(|r#trait| match r#trait {
+            Trait::Native(native) => {
+                native == name || native.strip_prefix(name).is_some_and(|rest| rest.starts_with(['<', ':']))
+            }
+            _ => false,
+        })
+    }
+
+    pub fn has_interface(&self, name: &str) -> bool {
+        self.traits
+            .iter()
+            .any(|r#trait| matches!(r#trait, Trait::Interface(interface) if interface == name))
+    }
+
+    pub fn operand(&self, name: &str) -> Option<usize> {
+        self.operands.iter().position(|operand| operand.name == name)
+    }
+
+    pub fn result(&self, name: &str) -> Option<usize> {
+        self.results.iter().position(|result| result.name == name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<usize> {
+        self.attributes.iter().position(|attribute| attribute.name == name)
+    }
+
+    pub fn region(&self, name: &str) -> Option<usize> {
+        self.regions.iter().position(|region| region.name == name)
+    }
+
+    pub fn successor(&self, name: &str) -> Option<usize> {
+        self.successors.iter().position(|successor| successor.name == name)
+    }
+}
+
+/// `(ins Type:$name, ...)` dag arguments of the record field.
+fn dag_args(record: &Record, field: &str) -> Vec<(Rc<Record>, Option<String>)> {
+    record
+        .get_dag(field)
+        .map(|dag| {
+            dag.args
+                .iter()
+                .filter_map(|(init, name)| init.as_def().map(|def| (def.clone(), name.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Expands the nested `TraitList` records.
+fn flatten_traits(traits: &[Init], flattened: &mut Vec<Rc<Record>>) {
+    for r#trait in traits.iter().filter_map(Init::as_def) {
+        match r#trait.get_list("traits") {
+            Some(nested) if r#trait.is_subclass_of("TraitList") => flatten_traits(nested, flattened),
+            _ => flattened.push(r#trait.clone()),
+        }
+    }
+}
+
+/// Finds the terminator operation name by its C++ class, like `scf::YieldOp`.
+fn terminator_name(keeper: &RecordKeeper, dialect: &Dialect, class: &str) -> String {
+    let class = class.rsplit("::").next().unwrap_or(class);
+    let class_name = |record: &Rc<Record>| match record.name.split_once('_') {
+        Some((_, name)) if !name.is_empty() => name == class,
+        _ => record.name == class,
+    };
+    let candidates: Vec<&Rc<Record>> = keeper.derived_definitions("Op").filter(|op| class_name(op)).collect();
+    let record = candidates
+        .iter()
+        .find(|op| {
+            op.get_def("opDialect")
+                .is_some_and(|op_dialect| op_dialect.get_string("name") == Some(&dialect.name))
+        })
+        .or_else(|| candidates.first());
+    match record {
+        Some(record) => {
+            let dialect = record
+                .get_def("opDialect")
+                .and_then(|dialect| dialect.get_string("name"))
+                .unwrap_or_default();
+            let name = record.get_string("opName").unwrap_or_default();
+            format!("{dialect}.{name}")
+        }
+        None => class.to_string(),
+    }
+}
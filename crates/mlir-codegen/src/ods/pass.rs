@@ -0,0 +1,99 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//!
+//! `Pass` and `InterfacePass` records of the `Passes.td` files with their options.
+//!
+
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{Init, Record};
+
+/// `Option` or `ListOption` record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassOption {
+    /// C++ member name, like `includeBf16`.
+    pub cpp_name: String,
+    /// Option name of the pipeline, like `include-bf16`.
+    pub argument: String,
+    /// C++ value type, the element type of the list options.
+    pub cpp_type: String,
+    /// C++ default value expression, empty for the default constructed value.
+    pub default_value: String,
+    pub description: String,
+    pub is_list: bool,
+}
+
+impl PassOption {
+    fn from_record(record: &Rc<Record>) -> Self {
+        let string = |name| record.get_string(name).unwrap_or_default().trim().to_string();
+        Self {
+            cpp_name: string("cppName"),
+            argument: string("argument"),
+            cpp_type: string("type"),
+            default_value: string("defaultValue"),
+            description: string("description"),
+            is_list: record.is_subclass_of("ListOption"),
+        }
+    }
+}
+
+/// `PassBase` record, mirroring the upstream `mlir::tblgen::Pass`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pass {
+    /// C++ class name, the record name like `ArithExpandOpsPass`.
+    pub name: String,
+    /// Pass argument of the pipeline, like `arith-expand`.
+    pub argument: String,
+    /// C++ base class, like `::mlir::OperationPass<ModuleOp>`.
+    pub base_class: String,
+    pub summary: String,
+    pub description: String,
+    /// C++ dialect classes, like `vector::VectorDialect`.
+    pub dependent_dialects: Vec<String>,
+    pub options: Vec<PassOption>,
+}
+
+impl Pass {
+    pub fn from_record(record: &Rc<Record>) -> Self {
+        let string = |name| record.get_string(name).unwrap_or_default().to_string();
+        let list = |name| record.get_list(name).unwrap_or_default();
+        Self {
+            name: record.name.clone(),
+            argument: string("argument"),
+            base_class: string("baseClass"),
+            summary: string("summary"),
+            description: string("description"),
+            dependent_dialects: list("dependentDialects")
+                .iter()
+                .filter_map(Init::as_str)
+                .map(str::to_string)
+                .collect(),
+            options: list("options")
+                .iter()
+                .filter_map(Init::as_def)
+                .map(PassOption::from_record)
+                .collect(),
+        }
+    }
+
+    /// Operation or interface the pass runs on, like `ModuleOp`, `None` for the passes running on any operation.
+    pub fn anchor(&self) -> Option<&str> {
+        let (_, anchor) = self.base_class.split_once('<')?;
+        Some(anchor.strip_suffix('>')?.trim()).filter(|anchor| !anchor.is_empty())
+    }
+}
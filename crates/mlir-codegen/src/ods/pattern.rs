@@ -0,0 +1,313 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//!
+//! `Pattern` and `Pat` records of the Declarative Rewrite Rules with their source and result dags.
+//!
+
+use std::fmt;
+use std::rc::Rc;
+
+use fljuga_handahofi_tablegen::records::{DagInit, Init, Record, RecordKeeper};
+
+use crate::ods::{AttrConstraint, OdsError, Operation, TypeConstraint};
+
+/// Constraint of the dag leaf, like `APIntAttr` of the `APIntAttr:$c0` leaf.
+#[derive(Debug, Clone)]
+pub enum LeafConstraint {
+    /// `ConstantAttr<I1Attr, "1">`
+    ConstantAttr {
+        attr: AttrConstraint,
+        value: String,
+    },
+    Attr {
+        name: String,
+        constraint: AttrConstraint,
+    },
+    Type {
+        name: String,
+        constraint: TypeConstraint,
+    },
+    /// Enum cases and the other records matched by their C++ predicates.
+    Other(String),
+}
+
+/// Node of the source, result or constraint dags, mirroring the upstream `DagNode` and `DagLeaf`.
+#[derive(Debug, Clone)]
+pub enum DagNode {
+    /// `(Arith_AddIOp:$res $x, $y)`
+    Operation {
+        record: String,
+        op: Box<Operation>,
+        name: Option<String>,
+        args: Vec<DagNode>,
+    },
+    /// `(AddIntegerAttrs $res, $c0, $c1)`, the C++ expression is kept as is.
+    NativeCodeCall {
+        record: String,
+        expression: String,
+        name: Option<String>,
+        args: Vec<DagNode>,
+    },
+    /// `(IsEqOrNe $pred)` of the pattern constraints, anonymous ones are named after their C++ predicate.
+    Constraint {
+        record: Option<String>,
+        predicate: String,
+        args: Vec<DagNode>,
+    },
+    /// `replaceWithValue`, `location`, `either` and the other directives.
+    Directive { directive: String, args: Vec<DagNode> },
+    /// `$x`, `APIntAttr:$c0` or `ConstantAttr<I1Attr, "1">`
+    Leaf {
+        constraint: Option<LeafConstraint>,
+        name: Option<String>,
+    },
+}
+
+impl DagNode {
+    fn from_dag(keeper: &RecordKeeper, dag: &DagInit) -> Result<Self, OdsError> {
+        let args = dag
+            .args
+            .iter()
+            .map(|(init, name)| Self::from_arg(keeper, init, name.as_deref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = dag.operator_name.clone();
+        let Some(operator) = dag.operator.as_def() else {
+            return Ok(DagNode::Directive {
+                directive: dag.operator.to_string(),
+                args,
+            });
+        };
+        let node = if operator.is_subclass_of("Op") {
+            DagNode::Operation {
+                record: operator.name.clone(),
+                op: Box::new(Operation::from_record(keeper, operator)?),
+                name,
+                args,
+            }
+        } else if operator.is_subclass_of("NativeCodeCall") {
+            DagNode::NativeCodeCall {
+                record: operator.name.clone(),
+                expression: operator.get_string("expression").unwrap_or_default().to_string(),
+                name,
+                args,
+            }
+        } else if operator.is_subclass_of("Constraint") {
+            DagNode::Constraint {
+                record: (!operator.anonymous).then(|| operator.name.clone()),
+                predicate: predicate(operator),
+                args,
+            }
+        } else {
+            DagNode::Directive {
+                directive: operator.name.clone(),
+                args,
+            }
+        };
+        Ok(node)
+    }
+
+    fn from_arg(keeper: &RecordKeeper, init: &Init, name: Option<&str>) -> Result<Self, OdsError> {
+        let name = name.map(str::to_string);
+        match init {
+            Init::Dag(dag) => Self::from_dag(keeper, dag),
+            Init::Def(record) => Ok(DagNode::Leaf {
+                constraint: Some(LeafConstraint::from_record(record)),
+                name,
+            }),
+            _ => Ok(DagNode::Leaf { constraint: None, name }),
+        }
+    }
+
+    /// Operations of the dag, the nested ones included.
+    pub fn operations(&self) -> usize {
+        match self {
+            DagNode::Operation { args, .. } => 1 + args.iter().map(DagNode::operations).sum::<usize>(),
+            DagNode::NativeCodeCall { args, .. }
+            | DagNode::Constraint { args, .. }
+            | DagNode::Directive { args, .. } => args.iter().map(DagNode::operations).sum(),
+            DagNode::Leaf { .. } => 0,
+        }
+    }
+}
+
+impl LeafConstraint {
+    fn from_record(record: &Rc<Record>) -> Self {
+        if record.is_subclass_of("ConstantAttr") {
+            if let Some(attr) = record.get_def("attr") {
+                return LeafConstraint::ConstantAttr {
+                    attr: AttrConstraint::from_record(attr),
+                    value: record.get_string("value").unwrap_or_default().to_string(),
+                };
+            }
+        }
+        if record.is_subclass_of("Attr") {
+            return LeafConstraint::Attr {
+                name: record.name.clone(),
+                constraint: AttrConstraint::from_record(record),
+            };
+        }
+        if record.is_subclass_of("TypeConstraint") {
+            return LeafConstraint::Type {
+                name: record.name.clone(),
+                constraint: TypeConstraint::from_record(record),
+            };
+        }
+        LeafConstraint::Other(record.name.clone())
+    }
+}
+
+/// C++ expression of the `CPred` predicates without their `predExpr` parentheses,
+/// the combined ones are joined with `&&` and `||`.
+fn predicate(record: &Record) -> String {
+    fn visit(pred: &Record) -> String {
+        if let Some(expression) = pred.get_string("predExpr") {
+            let expression = expression
+                .strip_prefix('(')
+                .and_then(|expression| expression.strip_suffix(')'));
+            return expression.unwrap_or_default().to_string();
+        }
+        let children: Vec<String> = pred
+            .get_list("children")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Init::as_def)
+            .map(|child| visit(child))
+            .collect();
+        let operator = match pred.is_subclass_of("Or") {
+            true => " || ",
+            false => " && ",
+        };
+        children
+            .iter()
+            .map(|child| format!("({child})"))
+            .collect::<Vec<_>>()
+            .join(operator)
+    }
+    record.get_def("predicate").map(|pred| visit(pred)).unwrap_or_default()
+}
+
+/// `Pattern` record, mirroring the upstream `mlir::tblgen::Pattern`.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// Record name, like `AddIAddConstant`, or `GeneratedConvert0` of the anonymous patterns.
+    pub name: String,
+    pub source: DagNode,
+    pub results: Vec<DagNode>,
+    pub constraints: Vec<DagNode>,
+    pub supplemental: Vec<DagNode>,
+    /// `(addBenefit 1)` value.
+    pub benefit_delta: i64,
+}
+
+impl Pattern {
+    /// Loads the pattern, `index` numbers the anonymous ones.
+    pub fn from_record(keeper: &RecordKeeper, record: &Rc<Record>, index: usize) -> Result<Self, OdsError> {
+        let dags = |field| -> Result<Vec<DagNode>, OdsError> {
+            record
+                .get_list(field)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Init::as_dag)
+                .map(|dag| DagNode::from_dag(keeper, dag))
+                .collect()
+        };
+        let source = record
+            .get_dag("sourcePattern")
+            .ok_or_else(|| OdsError::invalid(record, "pattern has no source dag"))?;
+        Ok(Self {
+            name: match record.anonymous {
+                true => format!("GeneratedConvert{index}"),
+                false => record.name.clone(),
+            },
+            source: DagNode::from_dag(keeper, source)?,
+            results: dags("resultPatterns")?,
+            constraints: dags("constraints")?,
+            supplemental: dags("supplementalPatterns")?,
+            benefit_delta: record
+                .get_dag("benefitDelta")
+                .and_then(|dag| dag.args.first())
+                .and_then(|(init, _)| init.as_int())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Operations of the source dag plus the added benefit, like the upstream `Pattern::getBenefit`.
+    pub fn benefit(&self) -> usize {
+        (self.source.operations() as i64 + self.benefit_delta).max(0) as usize
+    }
+}
+
+fn separated(f: &mut fmt::Formatter<'_>, args: &[DagNode]) -> fmt::Result {
+    for (index, arg) in args.iter().enumerate() {
+        match index {
+            0 => write!(f, " {arg}")?,
+            _ => write!(f, ", {arg}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Prints the dag like it's written in the TableGen file.
+impl fmt::Display for DagNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |name: &Option<String>| name.as_ref().map(|name| format!(":${name}")).unwrap_or_default();
+        match self {
+            DagNode::Operation { record, name, args, .. } | DagNode::NativeCodeCall { record, name, args, .. } => {
+                write!(f, "({record}{}", bound(name))?;
+                separated(f, args)?;
+                write!(f, ")")
+            }
+            DagNode::Constraint {
+                record,
+                predicate,
+                args,
+            } => {
+                match record {
+                    Some(record) => write!(f, "({record}")?,
+                    None => write!(f, "(Constraint<CPred<{predicate:?}>>")?,
+                }
+                separated(f, args)?;
+                write!(f, ")")
+            }
+            DagNode::Directive { directive, args } => {
+                write!(f, "({directive}")?;
+                separated(f, args)?;
+                write!(f, ")")
+            }
+            DagNode::Leaf { constraint, name } => {
+                let constraint = match constraint {
+                    Some(LeafConstraint::ConstantAttr { attr, value }) => {
+                        format!("ConstantAttr<{}, {value:?}>", attr.base_name)
+                    }
+                    Some(
+                        LeafConstraint::Attr { name, .. }
+                        | LeafConstraint::Type { name, .. }
+                        | LeafConstraint::Other(name),
+                    ) => name.clone(),
+                    None => String::new(),
+                };
+                match name {
+                    Some(name) if constraint.is_empty() => write!(f, "${name}"),
+                    Some(name) => write!(f, "{constraint}:${name}"),
+                    None if constraint.is_empty() => write!(f, "?"),
+                    None => write!(f, "{constraint}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,433 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir codegen parity
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Parity check of the generated dialect modules with the upstream MLIR Python bindings of `mlir/python/mlir/dialects`.
+//! The `_<dialect>_ops_gen.py` stubs of `mlir-tblgen -gen-python-op-bindings` are vendored in `src/testdata/python`,
+//! so the check runs offline. The operations missing on either side, the differing operand names
+//! and the differing builder signatures are reported, the keyword argument names of the Python builders
+//! are the reference.
+//!
+//! The `optional-tests-with-upstream` feature checks every dialect of the dialect crates, failing on any mismatch.
+//! It needs the upstream `.td` files vendored by `cargo xtask vendor --tarball` and their stubs, generated with
+//! `mlir-tblgen -gen-python-op-bindings -bind-dialect=<dialect> -I include include/<entry point>`.
+//!
+
+use std::path::Path;
+
+use crate::assembly_format::snake_case;
+use crate::builder;
+use crate::ods::Ods;
+
+/// Derived [thiserror::Error] for the Python stub errors
+#[derive(thiserror::Error, Debug)]
+pub enum ParityError {
+    #[error("{path}: {source}")]
+    Io { path: String, source: std::io::Error },
+
+    #[error("line {line}: {message}")]
+    InvalidStub { line: usize, message: String },
+}
+
+/// `OpView` class of the generated Python bindings.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PythonOp {
+    /// Python class name, like `AddIOp`.
+    pub class_name: String,
+    /// `OPERATION_NAME`, like `arith.addi`.
+    pub name: String,
+    /// Positional `__init__` arguments, like `lhs`.
+    pub positional: Vec<String>,
+    /// Keyword-only `__init__` arguments, like `overflowFlags`.
+    pub keywords: Vec<String>,
+    /// Properties accessing `self.operation.operands`, in their declaration order.
+    pub operands: Vec<String>,
+}
+
+/// Python and Rust sides of a mismatch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub operation: String,
+    pub python: String,
+    pub rust: String,
+}
+
+/// Parity report of a dialect.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParityReport {
+    pub dialect: String,
+    /// Operations of the Python bindings without a generated Rust counterpart.
+    pub missing: Vec<String>,
+    /// Generated operations the Python bindings don't have.
+    pub extra: Vec<String>,
+    pub operands: Vec<Mismatch>,
+    pub builders: Vec<Mismatch>,
+}
+
+/// Python names compared with the ODS ones, like `in_` with `in` and `overflowFlags` with `overflow_flags`.
+fn normalize(name: &str) -> String {
+    snake_case(name.trim_end_matches('_'))
+}
+
+/// `lhs, rhs, *, overflow_flags` signature of the builder arguments.
+fn signature(positional: &[String], keywords: &[String]) -> String {
+    let mut arguments: Vec<String> = positional.iter().map(|name| normalize(name)).collect();
+    if !keywords.is_empty() {
+        arguments.push("*".to_string());
+        arguments.extend(keywords.iter().map(|name| normalize(name)));
+    }
+    arguments.join(", ")
+}
+
+/// `__init__(self, lhs, rhs, *, overflowFlags=None, loc=None, ip=None)` arguments without `self`, `loc` and `ip`.
+fn init_arguments(arguments: &str, op: &mut PythonOp) {
+    let mut keyword = false;
+    for argument in arguments.split(',').map(str::trim) {
+        let name = argument.split('=').next().unwrap_or_default().trim();
+        match name {
+            "*" => keyword = true,
+            "" | "self" | "loc" | "ip" => {}
+            name if keyword => op.keywords.push(name.to_string()),
+            name => op.positional.push(name.to_string()),
+        }
+    }
+}
+
+impl PythonOp {
+    /// Parses the `OpView` classes of a `_<dialect>_ops_gen.py` stub, line by line like the generator emits them.
+    pub fn parse(stub: &str) -> Result<Vec<Self>, ParityError> {
+        let mut ops: Vec<Self> = vec![];
+        let mut current: Option<Self> = None;
+        // Property name waiting for the first `self.operation` access of its body.
+        let mut property: Option<String> = None;
+        let mut lines = stub.lines().enumerate().peekable();
+        while let Some((index, line)) = lines.next() {
+            let trimmed = line.trim();
+            if !line.starts_with(' ') && !trimmed.is_empty() && !trimmed.starts_with('@') {
+                ops.extend(current.take().filter(|op| !op.name.is_empty()));
+                property = None;
+                if let Some(class) = trimmed.strip_prefix("class ") {
+                    if class.contains("(_ods_ir.OpView)") {
+                        current = Some(Self {
+                            class_name: class.split('(').next().unwrap_or_default().to_string(),
+                            ..Self::default()
+                        });
+                    }
+                }
+                continue;
+            }
+            let Some(op) = current.as_mut() else {
+                continue;
+            };
+            let invalid = |message: &str| ParityError::InvalidStub {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            if let Some(name) = trimmed.strip_prefix("OPERATION_NAME = ") {
+                op.name = name.trim_matches('"').to_string();
+            } else if let Some(arguments) = trimmed.strip_prefix("def __init__(") {
+                let arguments = arguments
+                    .rsplit_once("):")
+                    .ok_or_else(|| invalid("multiline __init__ arguments"))?
+                    .0;
+                init_arguments(arguments, op);
+            } else if trimmed == "@builtins.property" {
+                let (_, def) = lines.next().ok_or_else(|| invalid("property without a def"))?;
+                let name = def
+                    .trim()
+                    .strip_prefix("def ")
+                    .and_then(|def| def.split('(').next())
+                    .ok_or_else(|| invalid("property without a def"))?;
+                property = Some(name.to_string());
+            } else if trimmed.starts_with('@') || trimmed.starts_with("def ") {
+                property = None;
+            } else if let Some(accessed) = trimmed.split("self.operation.").nth(1) {
+                if let Some(name) = property.take() {
+                    if accessed.starts_with("operands") {
+                        op.operands.push(name);
+                    }
+                }
+            }
+        }
+        ops.extend(current.filter(|op| !op.name.is_empty()));
+        Ok(ops)
+    }
+
+    /// Reads the `OpView` classes of a vendored stub.
+    pub fn read(path: &Path) -> Result<Vec<Self>, ParityError> {
+        let stub = std::fs::read_to_string(path).map_err(|source| ParityError::Io {
+            path: path.to_string_lossy().to_string(),
+            source,
+        })?;
+        Self::parse(&stub)
+    }
+}
+
+impl ParityReport {
+    /// Compares the operations of the dialect with the Python bindings.
+    pub fn new(dialect: &str, ods: &Ods, python: &[PythonOp]) -> Self {
+        let mut report = Self {
+            dialect: dialect.to_string(),
+            ..Self::default()
+        };
+        let operations: Vec<_> = ods
+            .operations
+            .iter()
+            .filter(|operation| operation.dialect == dialect)
+            .collect();
+        for op in python {
+            let Some(operation) = operations.iter().find(|operation| operation.name == op.name) else {
+                report.missing.push(op.name.clone());
+                continue;
+            };
+            let python_operands = signature(&op.operands, &[]);
+            let rust_operands = signature(
+                &operation
+                    .operands
+                    .iter()
+                    .map(|operand| operand.name.clone())
+                    .collect::<Vec<_>>(),
+                &[],
+            );
+            if python_operands != rust_operands {
+                report.operands.push(Mismatch {
+                    operation: op.name.clone(),
+                    python: python_operands,
+                    rust: rust_operands,
+                });
+            }
+
+            let params = builder::params(operation);
+            let names = |keyword: bool| {
+                params
+                    .iter()
+                    .filter(|param| param.keyword == keyword)
+                    .map(|param| param.name.clone())
+                    .collect::<Vec<_>>()
+            };
+            let python_builder = signature(&op.positional, &op.keywords);
+            let rust_builder = signature(&names(false), &names(true));
+            if python_builder != rust_builder {
+                report.builders.push(Mismatch {
+                    operation: op.name.clone(),
+                    python: python_builder,
+                    rust: rust_builder,
+                });
+            }
+        }
+        report.extra = operations
+            .iter()
+            .filter(|operation| python.iter().all(|op| op.name != operation.name))
+            .map(|operation| operation.name.clone())
+            .collect();
+        report
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.operands.is_empty() && self.builders.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# `{}` Python bindings parity\n", self.dialect);
+        if self.is_empty() {
+            markdown.push_str("\nThe generated operations match the Python bindings.\n");
+            return markdown;
+        }
+        for (title, operations) in [
+            ("Missing operations", &self.missing),
+            ("Operations without Python bindings", &self.extra),
+        ] {
+            if !operations.is_empty() {
+                markdown.push_str(&format!("\n## {title}\n\n"));
+                for operation in operations {
+                    markdown.push_str(&format!("- `{operation}`\n"));
+                }
+            }
+        }
+        for (title, mismatches) in [
+            ("Operand names", &self.operands),
+            ("Builder signatures", &self.builders),
+        ] {
+            if !mismatches.is_empty() {
+                markdown.push_str(&format!(
+                    "\n## {title}\n\n| Operation | Python | Rust |\n|---|---|---|\n"
+                ));
+                for mismatch in mismatches {
+                    markdown.push_str(&format!(
+                        "| `{}` | `({})` | `({})` |\n",
+                        mismatch.operation, mismatch.python, mismatch.rust
+                    ));
+                }
+            }
+        }
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::DialectArgs;
+
+    const STUB: &str = r#"
+@_ods_cext.register_dialect
+class _Dialect(_ods_ir.Dialect):
+  DIALECT_NAMESPACE = "test"
+
+@_ods_cext.register_operation(_Dialect)
+class SegmentedOp(_ods_ir.OpView):
+  OPERATION_NAME = "test.segmented"
+
+  _ODS_OPERAND_SEGMENTS = [0,-1,1,]
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, result, lhs, rhs, *, bias=None, flags=None, loc=None, ip=None):
+    operands = []
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    operand_range = _ods_segmented_accessor(
+         self.operation.operands,
+         self.operation.attributes["operandSegmentSizes"], 0)
+    return operand_range
+
+  @builtins.property
+  def rhs(self):
+    operand_range = _ods_segmented_accessor(
+         self.operation.operands,
+         self.operation.attributes["operandSegmentSizes"], 1)
+    return operand_range
+
+  @builtins.property
+  def bias(self):
+    operand_range = _ods_segmented_accessor(
+         self.operation.operands,
+         self.operation.attributes["operandSegmentSizes"], 2)
+    return operand_range[0] if len(operand_range) > 0 else None
+
+  @builtins.property
+  def flags(self):
+    if "flags" not in self.operation.attributes:
+      return None
+    return self.operation.attributes["flags"]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def segmented(result, lhs, rhs, *, bias=None, flags=None, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(SegmentedOp(result=result, lhs=lhs, rhs=rhs, bias=bias, flags=flags, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class VariadicOp(_ods_ir.OpView):
+  OPERATION_NAME = "test.variadic"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, in_, *, loc=None, ip=None):
+    operands = []
+    operands.extend(_get_op_results_or_values(in_))
+
+  @builtins.property
+  def in_(self):
+    _ods_variadic_group_length = len(self.operation.operands) - 1 + 1
+    return self.operation.operands[0:0 + _ods_variadic_group_length]
+"#;
+
+    #[test]
+    fn should_parse_python_bindings() {
+        assert_eq!(
+            PythonOp::parse(STUB).unwrap(),
+            vec![
+                PythonOp {
+                    class_name: "SegmentedOp".to_string(),
+                    name: "test.segmented".to_string(),
+                    positional: vec!["result".to_string(), "lhs".to_string(), "rhs".to_string()],
+                    keywords: vec!["bias".to_string(), "flags".to_string()],
+                    operands: vec!["lhs".to_string(), "rhs".to_string(), "bias".to_string()],
+                },
+                PythonOp {
+                    class_name: "VariadicOp".to_string(),
+                    name: "test.variadic".to_string(),
+                    positional: vec!["in_".to_string()],
+                    keywords: vec![],
+                    operands: vec!["in_".to_string()],
+                },
+            ]
+        );
+        assert!(matches!(
+            PythonOp::parse("class AOp(_ods_ir.OpView):\n  def __init__(self,\n"),
+            Err(ParityError::InvalidStub { line: 2, .. })
+        ));
+    }
+
+    /// The reduced `ArithOps.td` leaves out the overflow flags and a few operations of the upstream one.
+    #[test]
+    fn should_report_mismatches() {
+        let args = DialectArgs {
+            file: "include/mlir/Dialect/Arith/IR/ArithOps.td".to_string(),
+            includes: vec!["include".to_string()],
+            ..DialectArgs::default()
+        };
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let (ods, _) = args.load(root).unwrap();
+        let python = PythonOp::read(&root.join("src/testdata/python/_arith_ops_gen.py")).unwrap();
+        let report = ParityReport::new("arith", &ods, &python);
+        assert_eq!(report.missing, ["arith.addf", "arith.maxsi"]);
+        assert!(report.extra.is_empty() && report.operands.is_empty());
+        assert_eq!(
+            report.builders,
+            ["arith.addi", "arith.muli", "arith.subi"].map(|operation| Mismatch {
+                operation: operation.to_string(),
+                python: "lhs, rhs, *, overflow_flags".to_string(),
+                rust: "lhs, rhs".to_string(),
+            })
+        );
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| `arith.addi` | `(lhs, rhs, *, overflow_flags)` | `(lhs, rhs)` |"), "{markdown}");
+    }
+
+    /// Every dialect of the dialect crates against the `_<dialect>_ops_gen.py` stub of its vendored `.td` file,
+    /// needs the upstream files vendored by `cargo xtask vendor --tarball` and the stubs generated out of them.
+    #[cfg(feature = "optional-tests-with-upstream")]
+    #[test]
+    fn should_match_python_bindings() {
+        let stubs = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/testdata/python");
+        let mut mismatches = vec![];
+        for (root, args) in crate::dialect::dialect_crates() {
+            let (ods, _) = args.load(&root).unwrap();
+            let mut dialects: Vec<_> = ods.operations.iter().map(|operation| operation.dialect.as_str()).collect();
+            dialects.sort();
+            dialects.dedup();
+            for dialect in dialects {
+                let stub = stubs.join(format!("_{dialect}_ops_gen.py"));
+                let python = PythonOp::read(&stub).unwrap_or_else(|error| panic!("{}: {error}", args.file));
+                let report = ParityReport::new(dialect, &ods, &python);
+                if !report.is_empty() {
+                    mismatches.push(report.to_markdown());
+                }
+            }
+        }
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    }
+}
@@ -0,0 +1,355 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir codegen pass
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! The `passes` module generated out of the `Pass` and `InterfacePass` records of the `Passes.td` files.
+//! Every pass gets an options struct implementing `PassOptions`, parsed from and printed to the
+//! `pass-name{opt=val ...}` pipeline element, and a `PassInfo` with the summary and the dependent dialects.
+//! The options of the C++ types without a Rust counterpart, like the enums, are kept as their text.
+//!
+
+use crate::assembly_format::CodeWriter;
+use crate::attr_or_type::{default_value, storage_type};
+use crate::interface::argument_name;
+use crate::ods::{Pass, PassOption};
+use crate::rustdoc::{self, Links};
+
+/// Translates the C++ option value type, `None` if it has no Rust counterpart.
+pub fn option_type(cpp: &str) -> Option<String> {
+    match cpp.trim() {
+        "float" => Some("f32".to_string()),
+        "double" => Some("f64".to_string()),
+        cpp => storage_type(cpp).filter(|rust_type| {
+            matches!(
+                rust_type.as_str(),
+                "bool" | "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" | "String"
+            )
+        }),
+    }
+}
+
+/// Dialect name of the C++ dialect class, the lowercase namespace like `arith` of `arith::ArithDialect`,
+/// or the lowercase class name without the `Dialect` suffix like `dlti` of `mlir::DLTIDialect`.
+pub fn dialect_name(cpp: &str) -> String {
+    let cpp = cpp.trim().trim_start_matches("::");
+    let cpp = cpp.strip_prefix("mlir::").unwrap_or(cpp);
+    match cpp.rsplit_once("::") {
+        Some((namespace, _)) => namespace.rsplit("::").next().unwrap_or(namespace).to_ascii_lowercase(),
+        None => cpp.strip_suffix("Dialect").unwrap_or(cpp).to_ascii_lowercase(),
+    }
+}
+
+/// Options struct field.
+#[derive(Debug, Clone, PartialEq)]
+struct Field<'a> {
+    option: &'a PassOption,
+    ident: String,
+    /// Rust type, `String` for the text of the untranslated C++ types.
+    rust_type: String,
+    /// Rust default value expression, `None` if the field is an `Option` keeping the C++ default.
+    default: Option<String>,
+}
+
+impl<'a> Field<'a> {
+    fn new(option: &'a PassOption) -> Self {
+        let translated = option_type(&option.cpp_type);
+        let rust_type = match (&translated, option.is_list) {
+            (Some(rust_type), true) => format!("Vec<{rust_type}>"),
+            (Some(rust_type), false) => rust_type.clone(),
+            (None, _) => "String".to_string(),
+        };
+        let default = match (&translated, option.default_value.as_str()) {
+            (None, _) => None,
+            (Some(_), _) if option.is_list => Some("Vec::new()".to_string()),
+            (Some(_), "") => Some("Default::default()".to_string()),
+            (Some(rust_type), cpp) => default_value(rust_type, cpp),
+        };
+        Self {
+            option,
+            ident: argument_name(&option.cpp_name),
+            rust_type,
+            default,
+        }
+    }
+
+    /// The default is the `Default` of the field type, so the struct derives it.
+    fn is_type_default(&self) -> bool {
+        match (self.default.as_deref(), self.rust_type.as_str()) {
+            (None | Some("Default::default()" | "Vec::new()"), _) => true,
+            (Some("false"), "bool") => true,
+            (Some("0"), rust_type) => rust_type != "bool",
+            (Some("0.0"), _) => true,
+            _ => false,
+        }
+    }
+
+    fn field_type(&self) -> String {
+        match self.default {
+            Some(_) => self.rust_type.clone(),
+            None => format!("Option<{}>", self.rust_type),
+        }
+    }
+}
+
+/// Generates the `passes` module with the options struct and the `PassInfo` of every pass,
+/// and the `register` function.
+pub fn generate(passes: &[Pass], links: &Links, code: &mut CodeWriter) {
+    code.line("/// Options and registrations of the passes defined in the file.");
+    code.line("pub mod passes {");
+    code.indent();
+    code.line("#[allow(unused_imports)]");
+    code.line("use ::fljuga_handahofi_mlir_ir::passes::{");
+    code.line("    parse_option, PassInfo, PassOptionError, PassOptionInfo, PassOptionValue, PassOptions, PassRegistry,");
+    code.line("};");
+    for pass in passes {
+        code.line("");
+        generate_options(pass, links, code);
+    }
+    code.line("");
+    code.line("/// Passes defined in the file, in the definition order.");
+    code.line("pub static PASSES: &[PassInfo] = &[");
+    code.indent();
+    for pass in passes {
+        generate_info(pass, code);
+    }
+    code.dedent();
+    code.line("];");
+    code.line("");
+    code.line("pub fn register(registry: &mut PassRegistry) {");
+    code.indent();
+    code.line("for info in PASSES {");
+    code.line("    registry.register(info);");
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+}
+
+fn generate_options(pass: &Pass, links: &Links, code: &mut CodeWriter) {
+    let class = format!("{}Options", pass.name);
+    let fields: Vec<Field> = pass.options.iter().map(Field::new).collect();
+    match pass.summary.trim() {
+        "" => code.line(format!("/// `{}` pass options.", pass.argument)),
+        summary => code.line(format!("/// `{}`: {summary}", pass.argument)),
+    }
+    rustdoc::generate(&pass.description, links, code);
+    if let Some(anchor) = pass.anchor() {
+        code.line("///");
+        code.line(format!("/// Runs on `{anchor}`."));
+    }
+    if fields.is_empty() {
+        code.line("#[derive(Debug, Clone, Default, PartialEq)]");
+        code.line(format!("pub struct {class};"));
+    } else {
+        let derived = fields.iter().all(Field::is_type_default);
+        match derived {
+            true => code.line("#[derive(Debug, Clone, Default, PartialEq)]"),
+            false => code.line("#[derive(Debug, Clone, PartialEq)]"),
+        }
+        code.line(format!("pub struct {class} {{"));
+        code.indent();
+        for field in &fields {
+            rustdoc::write(&field.option.description, links, code);
+            match (&field.default, option_type(&field.option.cpp_type)) {
+                (Some(_), _) => {}
+                (None, Some(_)) => {
+                    code.line("///");
+                    code.line(format!("/// `None` keeps the C++ default `{}`.", field.option.default_value));
+                }
+                (None, None) => {
+                    code.line("///");
+                    code.line(format!(
+                        "/// The C++ `{}` value is kept as its text, `None` keeps the C++ default.",
+                        field.option.cpp_type
+                    ));
+                }
+            }
+            code.line(format!("pub {}: {},", field.ident, field.field_type()));
+        }
+        code.dedent();
+        code.line("}");
+        if !derived {
+            generate_default(&class, &fields, code);
+        }
+    }
+
+    code.line("");
+    code.line(format!("impl PassOptions for {class} {{"));
+    code.indent();
+    code.line(format!("const ARGUMENT: &'static str = {:?};", pass.argument));
+    code.line("");
+    code.line("fn set_option(&mut self, option: &str, value: &str) -> Result<(), PassOptionError> {");
+    code.indent();
+    if fields.is_empty() {
+        code.line("let _ = value;");
+        code.line("Err(PassOptionError::UnknownOption {");
+        code.line("    pass: Self::ARGUMENT,");
+        code.line("    option: option.to_string(),");
+        code.line("})");
+    } else {
+        code.line("match option {");
+        code.indent();
+        for field in &fields {
+            let parsed = "parse_option(Self::ARGUMENT, option, value)?";
+            match field.default {
+                Some(_) => code.line(format!("{:?} => self.{} = {parsed},", field.option.argument, field.ident)),
+                None => code.line(format!("{:?} => self.{} = Some({parsed}),", field.option.argument, field.ident)),
+            }
+        }
+        code.line("_ => {");
+        code.line("    return Err(PassOptionError::UnknownOption {");
+        code.line("        pass: Self::ARGUMENT,");
+        code.line("        option: option.to_string(),");
+        code.line("    })");
+        code.line("}");
+        code.dedent();
+        code.line("}");
+        code.line("Ok(())");
+    }
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line("fn options(&self) -> Vec<(&'static str, String)> {");
+    code.indent();
+    if fields.is_empty() {
+        code.line("vec![]");
+    } else if fields.iter().all(|field| field.default.is_some()) {
+        code.line("vec![");
+        code.indent();
+        for field in &fields {
+            code.line(format!("({:?}, self.{}.print_value()),", field.option.argument, field.ident));
+        }
+        code.dedent();
+        code.line("]");
+    } else {
+        code.line("[");
+        code.indent();
+        for field in &fields {
+            match field.default {
+                Some(_) => code.line(format!("Some(({:?}, self.{}.print_value())),", field.option.argument, field.ident)),
+                None => code.line(format!(
+                    "self.{}.as_ref().map(|value| ({:?}, value.print_value())),",
+                    field.ident, field.option.argument
+                )),
+            }
+        }
+        code.dedent();
+        code.line("]");
+        code.line(".into_iter()");
+        code.line(".flatten()");
+        code.line(".collect()");
+    }
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line(format!("impl std::str::FromStr for {class} {{"));
+    code.indent();
+    code.line("type Err = PassOptionError;");
+    code.line("");
+    code.line("fn from_str(text: &str) -> Result<Self, PassOptionError> {");
+    code.line("    Self::parse_options(text)");
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.line("");
+    code.line(format!("impl std::fmt::Display for {class} {{"));
+    code.indent();
+    code.line("fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {");
+    code.line("    f.write_str(&self.print_options())");
+    code.line("}");
+    code.dedent();
+    code.line("}");
+}
+
+fn generate_default(class: &str, fields: &[Field], code: &mut CodeWriter) {
+    code.line("");
+    code.line(format!("impl Default for {class} {{"));
+    code.indent();
+    code.line("fn default() -> Self {");
+    code.indent();
+    code.line("Self {");
+    code.indent();
+    for field in fields {
+        code.line(format!("{}: {},", field.ident, field.default.as_deref().unwrap_or("None")));
+    }
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+}
+
+fn generate_info(pass: &Pass, code: &mut CodeWriter) {
+    let dialects: Vec<String> = pass
+        .dependent_dialects
+        .iter()
+        .map(|dialect| format!("{:?}", dialect_name(dialect)))
+        .collect();
+    code.line("PassInfo {");
+    code.indent();
+    code.line(format!("argument: {:?},", pass.argument));
+    code.line(format!("summary: {:?},", pass.summary.trim()));
+    code.line(format!("dependent_dialects: &[{}],", dialects.join(", ")));
+    if pass.options.is_empty() {
+        code.line("options: &[],");
+    } else {
+        code.line("options: &[");
+        code.indent();
+        for option in &pass.options {
+            code.line("PassOptionInfo {");
+            code.line(format!("    argument: {:?},", option.argument));
+            code.line(format!("    description: {:?},", option.description));
+            code.line("},");
+        }
+        code.dedent();
+        code.line("],");
+    }
+    code.dedent();
+    code.line("},");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_translate_option_types() {
+        assert_eq!(option_type("unsigned").as_deref(), Some("u32"));
+        assert_eq!(option_type("double").as_deref(), Some("f64"));
+        assert_eq!(option_type("std::string").as_deref(), Some("String"));
+        assert_eq!(option_type("mlir::GreedySimplifyRegionLevel"), None);
+        assert_eq!(option_type("OpPassManager"), None);
+        assert_eq!(option_type("::mlir::Type"), None);
+    }
+
+    #[test]
+    fn should_name_dependent_dialects() {
+        assert_eq!(dialect_name("vector::VectorDialect"), "vector");
+        assert_eq!(dialect_name("::mlir::arith::ArithDialect"), "arith");
+        assert_eq!(dialect_name("LLVM::LLVMDialect"), "llvm");
+        assert_eq!(dialect_name("sparse_tensor::SparseTensorDialect"), "sparse_tensor");
+        assert_eq!(dialect_name("mlir::DLTIDialect"), "dlti");
+    }
+}
@@ -0,0 +1,786 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir codegen pattern
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! The `patterns` module generated out of the `Pattern` and `Pat` records of the Declarative Rewrite Rules,
+//! mirroring the upstream `mlir-tblgen -gen-rewriters`. Every pattern implements the core `RewritePattern`,
+//! matching the source dag against the root operation and the operations defining its operands,
+//! and building the result dags in its place. The constant attributes and types are built before the match,
+//! the result attributes before the first operation is created, so a failed rewrite leaves the IR untouched.
+//!
+//! The native code calls and the constraints are C++ code, so they call the hand-written hooks
+//! named after their records, like `add_integer_attrs` of the `AddIntegerAttrs` record.
+//! The patterns using the records without a hook, or the unsupported directives, are skipped.
+//!
+
+use std::collections::HashMap;
+
+use crate::assembly_format::analysis::{result_type_source, TypeSource, Var};
+use crate::assembly_format::{snake_case, CodeWriter};
+use crate::coverage::Skipped;
+use crate::interface::argument_name;
+use crate::ods::{Argument, AttrConstraint, DagNode, LeafConstraint, Operation, Pattern, Variadicity};
+
+/// Bound value or attribute of the source dag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Value,
+    Attribute,
+}
+
+/// Generated `match_and_rewrite` body of a pattern.
+struct Rewrite<'a> {
+    custom: &'a [String],
+    hooks: &'a str,
+    /// Locals of the bound `$names`.
+    bindings: HashMap<String, (String, Kind)>,
+    /// Constant attributes and types the matcher compares with, built before the operations are borrowed.
+    prelude: Vec<String>,
+    matcher: Vec<String>,
+    /// Result attributes, evaluated before the first operation is created.
+    attributes: Vec<String>,
+    rewriter: Vec<String>,
+    locals: usize,
+}
+
+fn fail(reason: impl AsRef<str>) -> String {
+    format!("return Err(rewriter.notify_match_failure(op, {:?}));", reason.as_ref())
+}
+
+/// `Attribute` expression of the `ConstantAttr` value, built by the rewriter.
+fn constant_attribute(attr: &AttrConstraint, value: &str) -> Result<String, String> {
+    let unsupported = || format!("constant attribute {value:?} of {}", attr.base_name);
+    match attr.value_type.as_deref() {
+        Some("i1") => match value {
+            "1" | "true" => Ok("rewriter.bool_attr(true)".to_string()),
+            "0" | "false" => Ok("rewriter.bool_attr(false)".to_string()),
+            _ => Err(unsupported()),
+        },
+        Some("index") => {
+            let value: i128 = value.parse().map_err(|_| unsupported())?;
+            Ok(format!("rewriter.index_attr({value})"))
+        }
+        Some(r#type) if r#type.starts_with('i') => {
+            let value: i128 = value.parse().map_err(|_| unsupported())?;
+            Ok(format!(
+                "{{ let r#type = rewriter.buildable_type({type:?}); rewriter.integer_attr({value}, r#type) }}"
+            ))
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+impl<'a> Rewrite<'a> {
+    fn new(custom: &'a [String], hooks: &'a str) -> Self {
+        Self {
+            custom,
+            hooks,
+            bindings: HashMap::new(),
+            prelude: vec![],
+            matcher: vec![],
+            attributes: vec![],
+            rewriter: vec![],
+            locals: 0,
+        }
+    }
+
+    fn local(&mut self, prefix: &str) -> String {
+        let local = format!("{prefix}{}", self.locals);
+        self.locals += 1;
+        local
+    }
+
+    /// Local of the buildable type, built in the prelude.
+    fn buildable_type(&mut self, r#type: &str) -> String {
+        let local = self.local("type");
+        self.prelude
+            .push(format!("let {local} = rewriter.buildable_type({type:?});"));
+        local
+    }
+
+    /// Check of the attribute constraint, `Ok(None)` if any attribute satisfies it.
+    fn attribute_check(&mut self, attr: &AttrConstraint, local: &str) -> Result<Option<String>, String> {
+        let kind = |pattern: &str| format!("matches!({local}.kind(rewriter), AttributeKind::{pattern})");
+        let check = match attr.storage_type.trim_start_matches("::mlir::") {
+            "Attribute" => return Ok(None),
+            "TypedAttr" => format!("{local}.r#type(rewriter).is_some()"),
+            // The `BoolAttr` is the `IntegerAttr` of `i1` upstream.
+            "IntegerAttr" => match &attr.value_type {
+                Some(r#type) => {
+                    let r#type = self.buildable_type(r#type);
+                    format!(
+                        "{} && {local}.r#type(rewriter) == Some({type})",
+                        kind("Integer { .. } | AttributeKind::Bool(_)")
+                    )
+                }
+                None => kind("Integer { .. } | AttributeKind::Bool(_)"),
+            },
+            "FloatAttr" => kind("Float { .. }"),
+            "StringAttr" => kind("String(_)"),
+            "TypeAttr" => kind("Type(_)"),
+            "ArrayAttr" => kind("Array(_)"),
+            "UnitAttr" => kind("Unit"),
+            _ => return Err(format!("attribute constraint {}", attr.base_name)),
+        };
+        Ok(Some(check))
+    }
+
+    /// Binds the `Value` or `Attribute` expression, the repeated names have to be equal.
+    fn bind(&mut self, name: &str, kind: Kind, expression: &str) -> Result<(), String> {
+        match self.bindings.get(name) {
+            Some((local, bound)) if *bound == kind => {
+                self.matcher.push(format!(
+                    "if {expression} != {local} {{ {} }}",
+                    fail(format!("`{name}` values differ"))
+                ));
+                Ok(())
+            }
+            Some(_) => Err(format!("`{name}` is bound to both a value and an attribute")),
+            None => {
+                let local = argument_name(name);
+                self.matcher.push(format!("let {local} = {expression};"));
+                self.bindings.insert(name.to_string(), (local, kind));
+                Ok(())
+            }
+        }
+    }
+
+    /// Hook of the native code call or the constraint record, `None` if it's not implemented.
+    fn hook(&self, record: &str) -> Option<String> {
+        self.custom
+            .iter()
+            .any(|custom| custom == record)
+            .then(|| format!("{}::{}", self.hooks, snake_case(record)))
+    }
+
+    /// Locals passed to the hook, the bound leaves only.
+    fn hook_arguments(&self, args: &[DagNode]) -> Result<Vec<(String, Kind)>, String> {
+        args.iter()
+            .map(|arg| match arg {
+                DagNode::Leaf {
+                    constraint: None,
+                    name: Some(name),
+                } => self
+                    .bindings
+                    .get(name)
+                    .map(|(local, kind)| (local.clone(), *kind))
+                    .ok_or_else(|| format!("`{name}` is not bound by the source dag")),
+                arg => Err(format!("argument {arg} of a native code call or a constraint")),
+            })
+            .collect()
+    }
+
+    fn match_root(&mut self, source: &DagNode) -> Result<(), String> {
+        let DagNode::Operation { op, name, args, .. } = source else {
+            return Err(format!("source dag {source} is not an operation"));
+        };
+        self.matcher.push("let op0 = op;".to_string());
+        self.matcher.push(format!(
+            "if op0.name(rewriter) != {:?} {{ {} }}",
+            op.name,
+            fail(format!("expected {}", op.name))
+        ));
+        self.matcher
+            .push("let result_types = types(rewriter, op0.results(rewriter));".to_string());
+        self.locals = 1;
+        self.match_operation("op0", op, name.as_deref(), args)
+    }
+
+    fn match_operation(
+        &mut self,
+        var: &str,
+        op: &Operation,
+        name: Option<&str>,
+        args: &[DagNode],
+    ) -> Result<(), String> {
+        if let Some(name) = name {
+            let result = self.local("value");
+            self.matcher.push(format!(
+                "let Some(&{result}) = {var}.results(rewriter).first() else {{ {} }};",
+                fail(format!("{} has no results", op.name))
+            ));
+            self.bind(name, Kind::Value, &result)?;
+        }
+        if args.len() != op.arguments.len() {
+            return Err(format!(
+                "{} has {} arguments, the dag has {}",
+                op.name,
+                op.arguments.len(),
+                args.len()
+            ));
+        }
+        let operands = self.local("operands");
+        if !op.operands.is_empty() {
+            let spec: Vec<String> = op
+                .operands
+                .iter()
+                .map(|operand| format!("Variadicity::{:?}", operand.variadicity))
+                .collect();
+            self.matcher.push(format!(
+                "let {operands} = {var}.operand_groups(rewriter, &[{}]);",
+                spec.join(", ")
+            ));
+        }
+        for (arg, argument) in args.iter().zip(&op.arguments) {
+            if matches!(
+                arg,
+                DagNode::Leaf {
+                    constraint: None,
+                    name: None
+                }
+            ) {
+                continue;
+            }
+            match *argument {
+                Argument::Operand(index) => {
+                    let operand = &op.operands[index];
+                    if operand.variadicity != Variadicity::Single {
+                        return Err(format!("variadic operand `{}` of {}", operand.name, op.name));
+                    }
+                    let value = self.local("value");
+                    let slot = format!("`{}` of {}", operand.name, op.name);
+                    self.matcher.push(format!(
+                        "let &[{value}] = {operands}[{index}] else {{ {} }};",
+                        fail(format!("expected a single {slot}"))
+                    ));
+                    self.match_operand(&value, &slot, arg)?;
+                }
+                Argument::Attribute(index) => {
+                    let attribute = &op.attributes[index].name;
+                    let attr = self.local("attr");
+                    self.matcher.push(format!(
+                        "let Some({attr}) = {var}.attribute(rewriter, {attribute:?}) else {{ {} }};",
+                        fail(format!("{} has no `{attribute}` attribute", op.name))
+                    ));
+                    self.match_attribute(&attr, &format!("`{attribute}` of {}", op.name), arg)?;
+                }
+                Argument::Property(_) => return Err(format!("properties of {}", op.name)),
+            }
+        }
+        Ok(())
+    }
+
+    fn match_operand(&mut self, value: &str, slot: &str, arg: &DagNode) -> Result<(), String> {
+        match arg {
+            DagNode::Leaf { constraint, name } => {
+                match constraint {
+                    None => {}
+                    Some(LeafConstraint::Type { constraint, .. }) if constraint.buildable.is_some() => {
+                        let r#type = constraint.buildable.as_deref().unwrap_or_default();
+                        let local = self.buildable_type(r#type);
+                        self.matcher.push(format!(
+                            "if {value}.r#type(rewriter) != {local} {{ {} }}",
+                            fail(format!("{slot} is not {type}"))
+                        ));
+                    }
+                    Some(LeafConstraint::Type { name, .. }) if name == "AnyType" => {}
+                    Some(_) => return Err(format!("operand constraint {arg}")),
+                }
+                match name {
+                    Some(name) => self.bind(name, Kind::Value, value),
+                    None => Ok(()),
+                }
+            }
+            DagNode::Operation { op, name, args, .. } => {
+                let defining = self.local("op");
+                self.matcher.push(format!(
+                    "let Some({defining}) = {value}.defining_op(rewriter) else {{ {} }};",
+                    fail(format!("{slot} is not defined by an operation"))
+                ));
+                self.matcher.push(format!(
+                    "if {defining}.name(rewriter) != {:?} {{ {} }}",
+                    op.name,
+                    fail(format!("{slot} is not defined by {}", op.name))
+                ));
+                self.match_operation(&defining, op, name.as_deref(), args)
+            }
+            DagNode::NativeCodeCall {
+                record,
+                name: None,
+                args,
+                ..
+            } if record == "ConstantLikeMatcher" && args.len() == 1 => {
+                let attr = self.local("attr");
+                self.matcher.push(format!(
+                    "let Some({attr}) = {value}.constant_value(rewriter) else {{ {} }};",
+                    fail(format!("{slot} is not a constant"))
+                ));
+                self.match_attribute(&attr, &format!("constant {slot}"), &args[0])
+            }
+            DagNode::NativeCodeCall { record, .. } => Err(format!("native code call {record} in the source dag")),
+            arg => Err(format!("{arg} in the source dag")),
+        }
+    }
+
+    fn match_attribute(&mut self, attr: &str, slot: &str, arg: &DagNode) -> Result<(), String> {
+        let DagNode::Leaf { constraint, name } = arg else {
+            return Err(format!("attribute {arg} in the source dag"));
+        };
+        match constraint {
+            None => {}
+            Some(LeafConstraint::ConstantAttr { attr: constant, value }) => {
+                let expected = self.local("attr");
+                self.prelude
+                    .push(format!("let {expected} = {};", constant_attribute(constant, value)?));
+                self.matcher.push(format!(
+                    "if {attr} != {expected} {{ {} }}",
+                    fail(format!("{slot} is not {value}"))
+                ));
+            }
+            Some(LeafConstraint::Attr { name, constraint }) => {
+                if let Some(check) = self.attribute_check(constraint, attr)? {
+                    self.matcher
+                        .push(format!("if !({check}) {{ {} }}", fail(format!("{slot} is not {name}"))));
+                }
+            }
+            Some(_) => return Err(format!("attribute constraint {arg}")),
+        }
+        match name {
+            Some(name) => self.bind(name, Kind::Attribute, attr),
+            None => Ok(()),
+        }
+    }
+
+    fn match_constraint(&mut self, constraint: &DagNode) -> Result<(), String> {
+        let DagNode::Constraint {
+            record,
+            predicate,
+            args,
+        } = constraint
+        else {
+            return Err(format!("constraint {constraint}"));
+        };
+        let arguments = self.hook_arguments(args)?;
+        if let Some(hook) = record.as_deref().and_then(|record| self.hook(record)) {
+            let arguments: Vec<&str> = ["rewriter"]
+                .into_iter()
+                .chain(arguments.iter().map(|(argument, _)| argument.as_str()))
+                .collect();
+            self.matcher.push(format!(
+                "if !{hook}({}) {{ {} }}",
+                arguments.join(", "),
+                fail(format!("{} is not satisfied", record.as_deref().unwrap_or_default()))
+            ));
+            return Ok(());
+        }
+        match (predicate.as_str(), arguments.as_slice()) {
+            ("$0.getType() == $1.getType()", [(lhs, Kind::Value), (rhs, Kind::Value)]) => {
+                self.matcher.push(format!(
+                    "if {lhs}.r#type(rewriter) != {rhs}.r#type(rewriter) {{ {} }}",
+                    fail(format!("`{lhs}` and `{rhs}` types differ"))
+                ));
+                Ok(())
+            }
+            _ => match record {
+                Some(record) => Err(format!("constraint {record} has no hook")),
+                None => Err(format!("constraint {predicate:?}")),
+            },
+        }
+    }
+
+    /// Builds the replacement values of the root results, starting at the `offset` result.
+    fn build_result(&mut self, node: &DagNode, offset: usize) -> Result<(String, usize), String> {
+        match node {
+            DagNode::Operation {
+                op, name: None, args, ..
+            } => {
+                if op
+                    .results
+                    .iter()
+                    .any(|result| result.variadicity != Variadicity::Single)
+                {
+                    return Err(format!("variadic results of {}", op.name));
+                }
+                let count = op.results.len();
+                let types = format!("result_types[{offset}..{}].to_vec()", offset + count);
+                Ok((self.build_operation(op, args, Some(types))?, count))
+            }
+            DagNode::Directive { directive, args } if directive == "replaceWithValue" => match args.as_slice() {
+                [arg @ DagNode::Leaf {
+                    constraint: None,
+                    name: Some(_),
+                }] => Ok((format!("vec![{}]", self.build_operand(arg)?), 1)),
+                _ => Err(format!("{node} has to replace the result with a bound value")),
+            },
+            DagNode::NativeCodeCall { .. } => Ok((format!("vec![{}]", self.build_operand(node)?), 1)),
+            node => Err(format!("result {node}")),
+        }
+    }
+
+    /// Creates the operation, returns the local with its results.
+    fn build_operation(&mut self, op: &Operation, args: &[DagNode], types: Option<String>) -> Result<String, String> {
+        if args.len() > op.arguments.len() {
+            return Err(format!(
+                "{} has {} arguments, the dag has {}",
+                op.name,
+                op.arguments.len(),
+                args.len()
+            ));
+        }
+        for argument in &op.arguments[args.len()..] {
+            match *argument {
+                Argument::Attribute(index)
+                    if op.attributes[index].constraint.optional
+                        || op.attributes[index].constraint.default_value.is_some() => {}
+                _ => {
+                    return Err(format!(
+                        "{} has {} arguments, the dag has {}",
+                        op.name,
+                        op.arguments.len(),
+                        args.len()
+                    ))
+                }
+            }
+        }
+        let mut operands = vec![];
+        let mut attributes = vec![];
+        for (arg, argument) in args.iter().zip(&op.arguments) {
+            match *argument {
+                Argument::Operand(index) => {
+                    if op.operands[index].variadicity != Variadicity::Single {
+                        return Err(format!("variadic operand `{}` of {}", op.operands[index].name, op.name));
+                    }
+                    operands.push(self.build_operand(arg)?);
+                }
+                Argument::Attribute(index) => {
+                    attributes.push((op.attributes[index].name.clone(), self.build_attribute(arg)?));
+                }
+                Argument::Property(_) => return Err(format!("properties of {}", op.name)),
+            }
+        }
+        let types = match types {
+            Some(types) => types,
+            None => {
+                let types = (0..op.results.len())
+                    .map(|index| self.infer_type(op, index, &operands, &attributes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("[{}]", types.join(", "))
+            }
+        };
+
+        let state = self.local("state");
+        self.rewriter
+            .push(format!("let mut {state} = OperationState::new({:?});", op.name));
+        if !operands.is_empty() {
+            self.rewriter
+                .push(format!("{state}.add_operands([{}]);", operands.join(", ")));
+        }
+        for (name, local) in &attributes {
+            self.rewriter.push(format!("{state}.add_attribute({name:?}, {local});"));
+        }
+        self.rewriter.push(format!("{state}.add_types({types});"));
+        if !attributes.is_empty() {
+            let names: Vec<String> = attributes.iter().map(|(name, _)| format!("{name:?}")).collect();
+            self.rewriter.push(format!(
+                "{state}.move_into_properties(rewriter, &[{}]);",
+                names.join(", ")
+            ));
+        }
+        let created = self.local("op");
+        self.rewriter.push(format!("let {created} = rewriter.create({state});"));
+        let values = self.local("values");
+        self.rewriter
+            .push(format!("let {values} = {created}.results(rewriter).to_vec();"));
+        Ok(values)
+    }
+
+    /// `Value` expression of the result dag operand.
+    fn build_operand(&mut self, arg: &DagNode) -> Result<String, String> {
+        match arg {
+            DagNode::Leaf {
+                constraint: None,
+                name: Some(name),
+            } => match self.bindings.get(name) {
+                Some((local, Kind::Value)) => Ok(local.clone()),
+                Some(_) => Err(format!("attribute `{name}` used as an operand")),
+                None => Err(format!("`{name}` is not bound by the source dag")),
+            },
+            DagNode::Operation {
+                op, name: None, args, ..
+            } => {
+                if op.results.len() != 1 || op.results[0].variadicity != Variadicity::Single {
+                    return Err(format!("{} used as an operand has to have a single result", op.name));
+                }
+                Ok(format!("{}[0]", self.build_operation(op, args, None)?))
+            }
+            DagNode::NativeCodeCall {
+                record,
+                expression,
+                args,
+                ..
+            } => {
+                let hook = self
+                    .hook(record)
+                    .ok_or_else(|| format!("native code call {record} of {expression:?} has no hook"))?;
+                let arguments: Vec<String> = ["rewriter".to_string()]
+                    .into_iter()
+                    .chain(self.hook_arguments(args)?.into_iter().map(|(argument, _)| argument))
+                    .collect();
+                let value = self.local("value");
+                self.rewriter
+                    .push(format!("let {value} = {hook}({});", arguments.join(", ")));
+                Ok(value)
+            }
+            arg => Err(format!("operand {arg} of the result dag")),
+        }
+    }
+
+    /// Local with the `Attribute` of the result dag attribute.
+    fn build_attribute(&mut self, arg: &DagNode) -> Result<String, String> {
+        match arg {
+            DagNode::Leaf {
+                constraint: None,
+                name: Some(name),
+            } => match self.bindings.get(name) {
+                Some((local, Kind::Attribute)) => Ok(local.clone()),
+                Some(_) => Err(format!("value `{name}` used as an attribute")),
+                None => Err(format!("`{name}` is not bound by the source dag")),
+            },
+            DagNode::Leaf {
+                constraint: Some(LeafConstraint::ConstantAttr { attr, value }),
+                name: None,
+            } => {
+                let expression = constant_attribute(attr, value)?;
+                let local = self.local("attr");
+                self.attributes.push(format!("let {local} = {expression};"));
+                Ok(local)
+            }
+            DagNode::NativeCodeCall {
+                record,
+                expression,
+                args,
+                ..
+            } => {
+                let hook = self
+                    .hook(record)
+                    .ok_or_else(|| format!("native code call {record} of {expression:?} has no hook"))?;
+                let arguments: Vec<String> = ["rewriter".to_string()]
+                    .into_iter()
+                    .chain(self.hook_arguments(args)?.into_iter().map(|(argument, _)| argument))
+                    .collect();
+                let local = self.local("attr");
+                self.attributes
+                    .push(format!("let {local} = {hook}({});", arguments.join(", ")));
+                Ok(local)
+            }
+            arg => Err(format!("attribute {arg} of the result dag")),
+        }
+    }
+
+    /// `Type` expression of the created operation result, like the ODS builders infer it.
+    fn infer_type(
+        &mut self,
+        op: &Operation,
+        index: usize,
+        operands: &[String],
+        attributes: &[(String, String)],
+    ) -> Result<String, String> {
+        let place = match result_type_source(op, index) {
+            Some(TypeSource::Buildable(r#type)) => return Ok(self.buildable_type(&r#type)),
+            Some(TypeSource::Infer {
+                from: Var::Operand(operand),
+                transform: kind,
+            }) => match operands.get(operand) {
+                Some(operand) => {
+                    let r#type = self.local("type");
+                    self.rewriter
+                        .push(format!("let {type} = {operand}.r#type(rewriter);"));
+                    Some((r#type, kind))
+                }
+                None => None,
+            },
+            Some(TypeSource::Infer {
+                from: Var::Attribute(attribute),
+                transform: kind,
+            }) => {
+                let name = &op.attributes[attribute].name;
+                match attributes.iter().find(|(attribute, _)| attribute == name) {
+                    Some((_, local)) => {
+                        let r#type = self.local("type");
+                        self.attributes.push(format!(
+                            "let Some({type}) = {local}.r#type(rewriter) else {{ {} }};",
+                            fail(format!("`{name}` attribute of {} has no type", op.name))
+                        ));
+                        Some((r#type, kind))
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        match place {
+            Some((place, kind)) => Ok(kind.apply("rewriter", &place)),
+            None => Err(format!("result types of {} can't be inferred", op.name)),
+        }
+    }
+
+    /// Generates the `match_and_rewrite` body lines.
+    fn generate(mut self, pattern: &Pattern) -> Result<Vec<String>, String> {
+        if !pattern.supplemental.is_empty() {
+            return Err("supplemental patterns".to_string());
+        }
+        self.match_root(&pattern.source)?;
+        for constraint in &pattern.constraints {
+            self.match_constraint(constraint)?;
+        }
+        let DagNode::Operation { op: root, .. } = &pattern.source else {
+            unreachable!("matched root operation")
+        };
+        if root
+            .results
+            .iter()
+            .any(|result| result.variadicity != Variadicity::Single)
+        {
+            return Err(format!("variadic results of {}", root.name));
+        }
+        let mut replacements = vec![];
+        let mut offset = 0;
+        for result in &pattern.results {
+            let (values, count) = self.build_result(result, offset)?;
+            replacements.push(values);
+            offset += count;
+        }
+        if offset != root.results.len() {
+            return Err(format!("{} results replaced with {offset} values", root.results.len()));
+        }
+        let replacement = match replacements.as_slice() {
+            [values] => values.clone(),
+            values => format!("[{}].concat()", values.join(", ")),
+        };
+        self.rewriter.push(format!(
+            "rewriter.replace_op(op, &{replacement}).map_err(|error| rewriter.notify_match_failure(op, error))"
+        ));
+        let insertion = vec!["rewriter.set_insertion_point_before(op);".to_string()];
+        Ok([self.prelude, self.matcher, self.attributes, insertion, self.rewriter].concat())
+    }
+}
+
+/// Generates the `patterns` module with a `RewritePattern` struct per pattern,
+/// the `hooks` implement the `custom` native code calls and constraints. Returns the skipped patterns.
+pub fn generate(patterns: &[Pattern], custom: &[String], hooks: &str, code: &mut CodeWriter) -> Vec<Skipped> {
+    code.line("/// Rewrite patterns of the Declarative Rewrite Rules defined in the file.");
+    code.line("pub mod patterns {");
+    code.indent();
+    code.line("#[allow(unused_imports)]");
+    code.line("use ::fljuga_handahofi_mlir_ir::{");
+    code.line("    types, AttributeKind, MatchFailure, Operation, OperationState, PatternRewriter, RewritePattern,");
+    code.line("    RewritePatternSet, Variadicity,");
+    code.line("};");
+
+    let mut generated = vec![];
+    let mut skipped = vec![];
+    for pattern in patterns {
+        let body = match Rewrite::new(custom, hooks).generate(pattern) {
+            Ok(body) => body,
+            Err(reason) => {
+                skipped.push(Skipped::new(&pattern.name, reason));
+                continue;
+            }
+        };
+        let DagNode::Operation { op: root, .. } = &pattern.source else {
+            continue;
+        };
+        code.line("");
+        code.line(format!("/// `{}`", pattern.name));
+        code.line("///");
+        code.line("/// ```text");
+        code.line(format!("/// {}", pattern.source));
+        for result in &pattern.results {
+            code.line(format!("/// -> {result}"));
+        }
+        if !pattern.constraints.is_empty() {
+            let constraints: Vec<String> = pattern.constraints.iter().map(ToString::to_string).collect();
+            code.line(format!("/// [{}]", constraints.join(", ")));
+        }
+        code.line("/// ```");
+        code.line(format!("pub struct {};", pattern.name));
+        code.line("");
+        code.line(format!("impl RewritePattern for {} {{", pattern.name));
+        code.indent();
+        code.line("fn name(&self) -> &str {");
+        code.line(format!("    {:?}", pattern.name));
+        code.line("}");
+        code.line("");
+        code.line("fn root_name(&self) -> Option<&str> {");
+        code.line(format!("    Some({:?})", root.name));
+        code.line("}");
+        code.line("");
+        code.line("fn benefit(&self) -> usize {");
+        code.line(format!("    {}", pattern.benefit()));
+        code.line("}");
+        code.line("");
+        code.line("#[allow(unused_variables)]");
+        code.line(
+            "fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure> {",
+        );
+        code.indent();
+        for line in body {
+            code.line(line);
+        }
+        code.dedent();
+        code.line("}");
+        code.dedent();
+        code.line("}");
+        generated.push(pattern.name.clone());
+    }
+
+    code.line("");
+    code.line("/// Patterns without the generated rewrite, with the reason.");
+    if skipped.is_empty() {
+        code.line("pub const SKIPPED: &[(&str, &str)] = &[];");
+    } else {
+        code.line("pub const SKIPPED: &[(&str, &str)] = &[");
+        code.indent();
+        for Skipped { name, reason } in &skipped {
+            code.line(format!("({name:?}, {reason:?}),"));
+        }
+        code.dedent();
+        code.line("];");
+    }
+    code.line("");
+    code.line("/// Adds the generated patterns, like the upstream `populateWithGenerated`.");
+    code.line("pub fn populate_with_generated(patterns: &mut RewritePatternSet) {");
+    code.indent();
+    for name in &generated {
+        code.line(format!("patterns.add({name});"));
+    }
+    if generated.is_empty() {
+        code.line("let _ = patterns;");
+    }
+    code.dedent();
+    code.line("}");
+    code.dedent();
+    code.line("}");
+    skipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_escape_match_failures() {
+        assert_eq!(
+            fail("`lhs` of \"arith.addi\""),
+            r#"return Err(rewriter.notify_match_failure(op, "`lhs` of \"arith.addi\""));"#
+        );
+    }
+}
@@ -0,0 +1,320 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir codegen rustdoc
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Rewrites the Markdown `summary` and `description` of the ODS records into the doc comments of the generated items.
+//! The fenced `mlir` examples become the `# Examples` sections rustdoc doesn't compile, the indented and unlabeled
+//! code blocks are fenced as `text`, and the operation, attribute and type names in backticks become intra-doc links
+//! to the generated structs.
+//!
+
+use std::collections::HashMap;
+
+use crate::assembly_format::CodeWriter;
+
+/// The `mlir-www` site the absolute documentation links of the descriptions are relative to.
+const MLIR_SITE: &str = "https://mlir.llvm.org";
+
+/// Intra-doc link targets of the generated items, relative to the dialect modules.
+#[derive(Debug, Default)]
+pub struct Links {
+    paths: HashMap<String, String>,
+}
+
+impl Links {
+    /// Links the operation name, like `arith.addi`, or the prefixed attribute and type name, like `!llvm.ptr`,
+    /// to the struct of the `module` dialect module.
+    pub fn insert(&mut self, name: impl Into<String>, module: &str, class: &str) {
+        self.paths.insert(name.into(), format!("super::{module}::{class}"));
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.paths.get(name).map(String::as_str)
+    }
+}
+
+/// Writes the description as the doc comment lines, separated from the summary line by an empty one.
+pub fn generate(description: &str, links: &Links, code: &mut CodeWriter) {
+    if !description.trim().is_empty() {
+        code.line("///");
+        write(description, links, code);
+    }
+}
+
+/// Writes the text as the doc comment lines.
+pub fn write(text: &str, links: &Links, code: &mut CodeWriter) {
+    for line in markdown(text, links) {
+        match line.is_empty() {
+            true => code.line("///"),
+            false => code.line(format!("/// {line}")),
+        }
+    }
+}
+
+/// Rewrites the Markdown description into the rustdoc one, line by line.
+pub fn markdown(description: &str, links: &Links) -> Vec<String> {
+    let lines = dedent(description);
+    let mut doc: Vec<String> = vec![];
+    let mut fence: Option<String> = None;
+    let mut indented = false;
+    let mut in_list = false;
+    let mut previous_blank = true;
+    for line in lines {
+        let trimmed = line.trim_start();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+                doc.push("```".to_string());
+            } else {
+                doc.push(line.to_string());
+            }
+            continue;
+        }
+        if indented {
+            if line.is_empty() || line.starts_with("    ") {
+                doc.push(line.get(4..).unwrap_or_default().to_string());
+                continue;
+            }
+            let blanks = doc.iter().rev().take_while(|line| line.is_empty()).count();
+            doc.insert(doc.len() - blanks, "```".to_string());
+            indented = false;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            let language = trimmed.trim_start_matches(marker.chars().next().unwrap_or('`')).trim();
+            if language == "mlir" {
+                examples_heading(&mut doc);
+            }
+            let language = match language {
+                "" | "rust" => "text",
+                language => language,
+            };
+            fence = Some(marker.to_string());
+            doc.push(format!("```{language}"));
+            previous_blank = false;
+            continue;
+        }
+        if line.starts_with("    ") && previous_blank && !in_list {
+            indented = true;
+            doc.push("```text".to_string());
+            doc.push(line[4..].to_string());
+            continue;
+        }
+        if line.is_empty() {
+            previous_blank = true;
+            doc.push(String::new());
+            continue;
+        }
+        in_list = is_list_item(trimmed) || (in_list && (!previous_blank || line.starts_with(' ')));
+        previous_blank = false;
+        doc.push(inline(&line, links));
+    }
+    if fence.is_some() || indented {
+        while doc.last().is_some_and(String::is_empty) {
+            doc.pop();
+        }
+        doc.push("```".to_string());
+    }
+    while doc.last().is_some_and(String::is_empty) {
+        doc.pop();
+    }
+    doc
+}
+
+/// Strips the common indentation of the `[{ ... }]` code, and the surrounding empty lines.
+fn dedent(description: &str) -> Vec<String> {
+    let lines: Vec<&str> = description.lines().map(str::trim_end).collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    let mut lines: Vec<String> = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default().to_string())
+        .skip_while(String::is_empty)
+        .collect();
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Turns the `Example:` paragraph preceding the `mlir` block into the `# Examples` section heading.
+fn examples_heading(doc: &mut [String]) {
+    let Some(line) = doc.iter_mut().rev().find(|line| !line.is_empty()) else {
+        return;
+    };
+    let title = line.trim_start_matches('#').trim().trim_end_matches(':').trim().to_ascii_lowercase();
+    if matches!(title.as_str(), "example" | "examples") {
+        *line = "# Examples".to_string();
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    let ordered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.starts_with("+ ")
+        || (ordered.len() < line.len() && (ordered.starts_with(". ") || ordered.starts_with(") ")))
+}
+
+/// Links the code spans naming the generated items, escapes the brackets and angle brackets rustdoc would
+/// take for the intra-doc links and HTML tags, and resolves the site relative links.
+///
+/// The `[`arith.constant`](#arithconstant-arithconstantop)` anchors of the upstream dialect pages
+/// are rewritten to the intra-doc links as well.
+fn inline(line: &str, links: &Links) -> String {
+    let mut text = String::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('`') else {
+            text.push_str(&prose(rest, ""));
+            break;
+        };
+        text.push_str(&prose(&rest[..start], &rest[start..]));
+        let ticks = rest[start..].len() - rest[start..].trim_start_matches('`').len();
+        let delimiter = &rest[start..start + ticks];
+        let Some(end) = rest[start + ticks..].find(delimiter) else {
+            text.push_str(&rest[start..]);
+            break;
+        };
+        let span = &rest[start..start + ticks + end + ticks];
+        let content = &span[ticks..span.len() - ticks];
+        rest = &rest[start + span.len()..];
+        match links.get(content.trim()) {
+            Some(path) if text.ends_with('[') && rest.starts_with("](#") => {
+                text.push_str(&format!("{span}]({path})"));
+                rest = rest.find(')').map(|end| &rest[end + 1..]).unwrap_or_default();
+            }
+            Some(path) if !text.ends_with('[') => text.push_str(&format!("[{span}]({path})")),
+            _ => text.push_str(span),
+        }
+    }
+    text
+}
+
+/// Escapes the text outside the code spans, `after` is the rest of the line the links may close in.
+fn prose(text: &str, after: &str) -> String {
+    let text = text.replace("](/", &format!("]({MLIR_SITE}/"));
+    let mut escaped = String::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '[' => {
+                let tail = format!("{}{after}", &text[index..]);
+                let link = tail.find(']').is_some_and(|end| tail[end + 1..].starts_with('('));
+                match link {
+                    true => escaped.push(c),
+                    false => escaped.push_str("\\["),
+                }
+            }
+            ']' if !text[index + 1..].starts_with('(') => escaped.push_str("\\]"),
+            '<' if !text[index + 1..].starts_with("http") => escaped.push_str("\\<"),
+            '\\' if chars.peek().is_some_and(|(_, next)| next.is_ascii_punctuation()) => {
+                escaped.push(c);
+                if let Some((_, next)) = chars.next() {
+                    escaped.push(next);
+                }
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links() -> Links {
+        let mut links = Links::default();
+        links.insert("arith.addi", "arith", "AddIOp");
+        links.insert("!llvm.ptr", "llvm", "LLVMPointerType");
+        links
+    }
+
+    #[test]
+    fn should_rewrite_descriptions() {
+        let description = r#"
+    The `arith.addi` operation takes two operands of `!llvm.ptr` or `arith.subi` type,
+    see [Arith](/docs/Dialects/ArithOps/) for the vector<4xi32> operands,
+    produced by [`arith.addi`](#arithaddi-arithaddiop) or [`arith.subi`](#arithsubi-arithsubiop).
+
+    Example:
+
+    ```mlir
+    // Scalar addition.
+    %a = arith.addi %b, %c : i64
+    ```
+
+    The semantics of `a[i]`:
+
+        %x = arith.addi %a, %b : i64
+
+    - a list item
+        with the continuation
+  "#;
+        assert_eq!(
+            markdown(description, &links()),
+            vec![
+                "The [`arith.addi`](super::arith::AddIOp) operation takes two operands of [`!llvm.ptr`](super::llvm::LLVMPointerType) or `arith.subi` type,",
+                "see [Arith](https://mlir.llvm.org/docs/Dialects/ArithOps/) for the vector\\<4xi32> operands,",
+                "produced by [`arith.addi`](super::arith::AddIOp) or [`arith.subi`](#arithsubi-arithsubiop).",
+                "",
+                "# Examples",
+                "",
+                "```mlir",
+                "// Scalar addition.",
+                "%a = arith.addi %b, %c : i64",
+                "```",
+                "",
+                "The semantics of `a[i]`:",
+                "",
+                "```text",
+                "%x = arith.addi %a, %b : i64",
+                "```",
+                "",
+                "- a list item",
+                "    with the continuation",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fence_unlabeled_code_blocks() {
+        let description = "Text [x] and \\[y\\]\n```\n%0 = test.op\n```\n~~~c++\nint x;\n~~~\n    unterminated";
+        assert_eq!(
+            markdown(description, &Links::default()),
+            vec![
+                "Text \\[x\\] and \\[y\\]",
+                "```text",
+                "%0 = test.op",
+                "```",
+                "```c++",
+                "int x;",
+                "```",
+                "    unterminated",
+            ]
+        );
+        assert!(markdown("  \n  ", &Links::default()).is_empty());
+    }
+}
@@ -0,0 +1,574 @@
+
+# Autogenerated by mlir-tblgen; don't manually edit.
+
+from ._ods_common import _cext as _ods_cext
+from ._ods_common import (
+    equally_sized_accessor as _ods_equally_sized_accessor,
+    get_default_loc_context as _ods_get_default_loc_context,
+    get_op_result_or_op_results as _get_op_result_or_op_results,
+    get_op_result_or_value as _get_op_result_or_value,
+    get_op_results_or_values as _get_op_results_or_values,
+    segmented_accessor as _ods_segmented_accessor,
+)
+_ods_ir = _ods_cext.ir
+
+import builtins
+from typing import Sequence as _Sequence, Union as _Union
+
+
+@_ods_cext.register_dialect
+class _Dialect(_ods_ir.Dialect):
+  DIALECT_NAMESPACE = "arith"
+
+@_ods_cext.register_operation(_Dialect)
+class AddFOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.addf"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, fastmath=None, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    if fastmath is not None: attributes["fastmath"] = fastmath
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def fastmath(self):
+    return self.operation.attributes["fastmath"]
+
+  @fastmath.setter
+  def fastmath(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["fastmath"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def addf(lhs, rhs, *, fastmath=None, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(AddFOp(lhs=lhs, rhs=rhs, fastmath=fastmath, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class AddIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.addi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, overflowFlags=None, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    if overflowFlags is not None: attributes["overflowFlags"] = overflowFlags
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def overflowFlags(self):
+    return self.operation.attributes["overflowFlags"]
+
+  @overflowFlags.setter
+  def overflowFlags(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["overflowFlags"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def addi(lhs, rhs, *, overflow_flags=None, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(AddIOp(lhs=lhs, rhs=rhs, overflowFlags=overflow_flags, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class AndIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.andi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def andi(lhs, rhs, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(AndIOp(lhs=lhs, rhs=rhs, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class BitcastOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.bitcast"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, out, in_, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(in_))
+    _ods_context = _ods_get_default_loc_context(loc)
+    results.append(out)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def in_(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def out(self):
+    return self.operation.results[0]
+
+def bitcast(out, in_, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(BitcastOp(out=out, in_=in_, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class CmpIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.cmpi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, predicate, lhs, rhs, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    attributes["predicate"] = predicate
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def predicate(self):
+    return self.operation.attributes["predicate"]
+
+  @predicate.setter
+  def predicate(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["predicate"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def cmpi(predicate, lhs, rhs, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(CmpIOp(predicate=predicate, lhs=lhs, rhs=rhs, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class ConstantOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.constant"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, value, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    _ods_context = _ods_get_default_loc_context(loc)
+    attributes["value"] = value
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def value(self):
+    return self.operation.attributes["value"]
+
+  @value.setter
+  def value(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["value"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def constant(value, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(ConstantOp(value=value, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class ExtSIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.extsi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, out, in_, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(in_))
+    _ods_context = _ods_get_default_loc_context(loc)
+    results.append(out)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def in_(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def out(self):
+    return self.operation.results[0]
+
+def extsi(out, in_, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(ExtSIOp(out=out, in_=in_, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class ExtUIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.extui"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, out, in_, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(in_))
+    _ods_context = _ods_get_default_loc_context(loc)
+    results.append(out)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def in_(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def out(self):
+    return self.operation.results[0]
+
+def extui(out, in_, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(ExtUIOp(out=out, in_=in_, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class IndexCastOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.index_cast"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, out, in_, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(in_))
+    _ods_context = _ods_get_default_loc_context(loc)
+    results.append(out)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def in_(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def out(self):
+    return self.operation.results[0]
+
+def index_cast(out, in_, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(IndexCastOp(out=out, in_=in_, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class MaxSIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.maxsi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def maxsi(lhs, rhs, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(MaxSIOp(lhs=lhs, rhs=rhs, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class MulIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.muli"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, overflowFlags=None, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    if overflowFlags is not None: attributes["overflowFlags"] = overflowFlags
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def overflowFlags(self):
+    return self.operation.attributes["overflowFlags"]
+
+  @overflowFlags.setter
+  def overflowFlags(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["overflowFlags"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def muli(lhs, rhs, *, overflow_flags=None, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(MulIOp(lhs=lhs, rhs=rhs, overflowFlags=overflow_flags, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class OrIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.ori"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def ori(lhs, rhs, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(OrIOp(lhs=lhs, rhs=rhs, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class SelectOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.select"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, condition, true_value, false_value, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(condition))
+    operands.append(_get_op_result_or_value(true_value))
+    operands.append(_get_op_result_or_value(false_value))
+    _ods_context = _ods_get_default_loc_context(loc)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def condition(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def true_value(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def false_value(self):
+    return self.operation.operands[2]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def select(condition, true_value, false_value, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(SelectOp(condition=condition, true_value=true_value, false_value=false_value, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class SubIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.subi"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, overflowFlags=None, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    if overflowFlags is not None: attributes["overflowFlags"] = overflowFlags
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def overflowFlags(self):
+    return self.operation.attributes["overflowFlags"]
+
+  @overflowFlags.setter
+  def overflowFlags(self, value):
+    if value is None:
+      raise ValueError("'None' not allowed as value for mandatory attributes")
+    self.operation.attributes["overflowFlags"] = value
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def subi(lhs, rhs, *, overflow_flags=None, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(SubIOp(lhs=lhs, rhs=rhs, overflowFlags=overflow_flags, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class TruncIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.trunci"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, out, in_, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(in_))
+    _ods_context = _ods_get_default_loc_context(loc)
+    results.append(out)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, results=results, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def in_(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def out(self):
+    return self.operation.results[0]
+
+def trunci(out, in_, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(TruncIOp(out=out, in_=in_, loc=loc, ip=ip))
+
+@_ods_cext.register_operation(_Dialect)
+class XOrIOp(_ods_ir.OpView):
+  OPERATION_NAME = "arith.xori"
+
+  _ODS_REGIONS = (0, True)
+
+  def __init__(self, lhs, rhs, *, loc=None, ip=None):
+    operands = []
+    results = []
+    attributes = {}
+    regions = None
+    operands.append(_get_op_result_or_value(lhs))
+    operands.append(_get_op_result_or_value(rhs))
+    _ods_context = _ods_get_default_loc_context(loc)
+    _ods_successors = None
+    super().__init__(self.OPERATION_NAME, self._ODS_REGIONS, self._ODS_OPERAND_SEGMENTS, self._ODS_RESULT_SEGMENTS, attributes=attributes, operands=operands, successors=_ods_successors, regions=regions, loc=loc, ip=ip)
+
+  @builtins.property
+  def lhs(self):
+    return self.operation.operands[0]
+
+  @builtins.property
+  def rhs(self):
+    return self.operation.operands[1]
+
+  @builtins.property
+  def result(self):
+    return self.operation.results[0]
+
+def xori(lhs, rhs, *, loc=None, ip=None) -> _ods_ir.Value:
+  return _get_op_result_or_op_results(XOrIOp(lhs=lhs, rhs=rhs, loc=loc, ip=ip))
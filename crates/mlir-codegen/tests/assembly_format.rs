@@ -0,0 +1,302 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Round trips the generated custom forms through the snippets of the upstream MLIR tests.
+//!
+
+use fljuga_handahofi_mlir_ir::{
+    parse, parse_type, print, Context, Dialect, InterfaceRegistry, OpAsmRegistry, Operation, PrinterOptions,
+    Variadicity,
+};
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "src/testdata/ods/TestOps.td",
+    includes = ["include", "src/testdata/ods"],
+    hooks = hooks,
+    custom = ["CustomDirectiveOperands", "OptionalOperandRef"],
+    interfaces = td::interfaces
+}
+
+/// Traits of the interfaces the operations declare.
+mod td {
+    fljuga_handahofi_mlir_codegen::dialect! {
+        file = "src/testdata/ods/TestInterfaces.td",
+        includes = ["include", "src/testdata/ods"]
+    }
+}
+
+/// Hand-written `custom<...>` directives, mirroring the upstream `TestOpDefs.cpp`.
+mod hooks {
+    use fljuga_handahofi_mlir_ir::{Operation, ParseError, Parser, Printer, UnresolvedOperand, Value};
+
+    pub fn print_custom_directive_operands(
+        p: &mut Printer<'_>,
+        _op: Operation,
+        operand: &[Value],
+        opt_operand: &[Value],
+        var_operands: &[Value],
+    ) {
+        p.print_operands(operand);
+        if !opt_operand.is_empty() {
+            p.write(", ");
+            p.print_operands(opt_operand);
+        }
+        p.write(" -> (");
+        p.print_operands(var_operands);
+        p.write(")");
+    }
+
+    pub fn parse_custom_directive_operands(
+        parser: &mut Parser<'_, '_>,
+        operand: &mut Vec<UnresolvedOperand>,
+        opt_operand: &mut Vec<UnresolvedOperand>,
+        var_operands: &mut Vec<UnresolvedOperand>,
+    ) -> Result<(), ParseError> {
+        operand.push(parser.parse_operand()?);
+        if parser.parse_optional_punct(",") {
+            opt_operand.push(parser.parse_operand()?);
+        }
+        parser.parse_punct("->")?;
+        parser.parse_punct("(")?;
+        var_operands.extend(parser.parse_operand_list()?);
+        parser.parse_punct(")")?;
+        Ok(())
+    }
+
+    pub fn print_optional_operand_ref(p: &mut Printer<'_>, _op: Operation, opt_operand: &[Value]) {
+        p.write(if opt_operand.is_empty() { "0" } else { "1" });
+    }
+
+    pub fn parse_optional_operand_ref(
+        parser: &mut Parser<'_, '_>,
+        opt_operand: &[UnresolvedOperand],
+    ) -> Result<(), ParseError> {
+        let present = parser.parse_integer()?;
+        if present != i128::from(!opt_operand.is_empty()) {
+            return Err(parser.error("operand presence mismatch"));
+        }
+        Ok(())
+    }
+}
+
+/// Registers the generated custom forms and interfaces of all the `TestOps.td` dialects.
+struct TestDialect;
+
+impl Dialect for TestDialect {
+    fn namespace(&self) -> &str {
+        "test"
+    }
+
+    fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+        register_interfaces(registry);
+    }
+
+    fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+        register(registry);
+    }
+}
+
+fn context() -> Context {
+    let mut ctx = Context::new();
+    ctx.register_dialect(TestDialect);
+    ctx
+}
+
+/// Module of the `test.region` taking the block `arguments`, with the snippet indented like the printed one.
+fn wrap(arguments: &str, snippet: &str) -> String {
+    let mut source = "module {\n  \"test.region\"() ({\n".to_string();
+    if !arguments.is_empty() {
+        source.push_str(&format!("  ^bb0({arguments}):\n"));
+    }
+    for line in snippet.lines() {
+        let indent = if line.starts_with('^') { "  " } else { "    " };
+        source.push_str(&format!("{indent}{line}\n"));
+    }
+    source.push_str("  }) : () -> ()\n}\n");
+    source
+}
+
+fn assert_round_trip(ctx: &mut Context, source: &str) {
+    let op = parse(ctx, source).unwrap_or_else(|error| panic!("{source}: {error}"));
+    assert_eq!(print(ctx, op, PrinterOptions::default()), source);
+}
+
+/// The operation of the snippet in the `test.region`.
+fn parse_snippet(ctx: &mut Context, arguments: &str, snippet: &str) -> Operation {
+    let module = parse(ctx, &wrap(arguments, snippet)).unwrap();
+    module.walk(ctx)[2]
+}
+
+#[test]
+fn should_round_trip_upstream_snippets() {
+    let mut ctx = context();
+    let snippets = [
+        // mlir/test/Dialect/Arith/ops.mlir
+        ("%arg0: i32, %arg1: i32", "%0 = arith.addi %arg0, %arg1 : i32"),
+        (
+            "%arg0: vector<8xi64>, %arg1: vector<8xi64>",
+            "%0 = arith.addi %arg0, %arg1 {overflowFlags = 1 : i32} : vector<8xi64>",
+        ),
+        ("%arg0: i32, %arg1: i32", "%0 = arith.cmpi slt, %arg0, %arg1 : i32"),
+        (
+            "%arg0: tensor<8x8xindex>, %arg1: tensor<8x8xindex>",
+            "%0 = arith.cmpi uge, %arg0, %arg1 : tensor<8x8xindex>",
+        ),
+        ("", "%0 = arith.constant 42 : i32"),
+        ("", "%0 = arith.constant 1.000000e+00 : f32"),
+        ("", "%0 = arith.constant dense<0> : vector<4xi32>"),
+        // mlir/test/IR/core-ops.mlir
+        (
+            "%arg0: memref<4x4xf32>, %arg1: index, %arg2: index",
+            "%0 = memref.load %arg0[%arg1, %arg2] : memref<4x4xf32>",
+        ),
+        ("%arg0: i32, %arg1: f32", "%0:2 = func.call @f(%arg0, %arg1) : (i32, f32) -> (i32, f32)"),
+        ("", "%0 = func.call @g() : () -> i64"),
+        ("%arg0: index", "func.call @h(%arg0) {inline} : (index) -> ()"),
+        ("", "func.return"),
+        ("%arg0: i32, %arg1: f32", "func.return %arg0, %arg1 : i32, f32"),
+        // mlir/test/Dialect/ControlFlow/ops.mlir
+        ("", "cf.br ^bb1\n^bb1:  // pred: ^bb0\nfunc.return"),
+        (
+            "%arg0: i32, %arg1: f32",
+            "cf.br ^bb1(%arg0, %arg1 : i32, f32)\n^bb1(%0: i32, %1: f32):  // pred: ^bb0\nfunc.return",
+        ),
+        (
+            "%arg0: i1, %arg1: i32",
+            "cf.cond_br %arg0, ^bb1, ^bb2(%arg1 : i32)\n^bb1:  // pred: ^bb0\nfunc.return\n^bb2(%0: i32):  // pred: ^bb0\nfunc.return",
+        ),
+        // mlir/test/Dialect/Builtin/ops.mlir
+        ("", "%0 = builtin.unrealized_conversion_cast to i32"),
+        ("%arg0: i64", "%0 = builtin.unrealized_conversion_cast %arg0 : i64 to i32"),
+        (
+            "%arg0: i64, %arg1: i64",
+            "%0:2 = builtin.unrealized_conversion_cast %arg0, %arg1 : i64, i64 to i32, i32",
+        ),
+        // mlir/test/Dialect/SCF/ops.mlir
+        ("%arg0: i64", "%0 = scf.execute_region -> i64 {\n  scf.yield %arg0 : i64\n}"),
+        ("", "scf.execute_region {\n  scf.yield\n}"),
+        ("", "scf.forall.in_parallel {\n}"),
+        // mlir/test/mlir-tblgen/op-format.mlir
+        ("%arg0: i64", "test.format_custom_directive_operands %arg0, %arg0 -> (%arg0)"),
+        ("%arg0: i64", "test.format_custom_directive_operands %arg0 -> (%arg0)"),
+        ("%arg0: i64", "test.format_custom_directive_operands %arg0 -> ()"),
+        ("%arg0: i64", "test.format_optional_operand_ref %arg0 : 1"),
+        ("", "test.format_optional_operand_ref : 0"),
+        ("", "test.format_optional_unit_attribute is_optional"),
+        ("", "test.format_optional_unit_attribute"),
+        ("", "test.format_optional_else then"),
+        ("", "test.format_optional_else else"),
+        ("", "test.format_dense_array [1, 2, 3], 4"),
+        ("", "test.format_dense_array [], 0 {test.attr}"),
+        ("%arg0: i64, %arg1: i64", "test.format_newline %arg0,\n %arg1 :"),
+    ];
+    for (arguments, snippet) in snippets {
+        assert_round_trip(&mut ctx, &wrap(arguments, snippet));
+    }
+    assert_round_trip(&mut ctx, "module {\n}\n");
+    assert_round_trip(&mut ctx, "module @named attributes {test.attr} {\n}\n");
+}
+
+#[test]
+fn should_infer_types() {
+    let mut ctx = context();
+    let cmpi = parse_snippet(
+        &mut ctx,
+        "%arg0: vector<4xi32>, %arg1: vector<4xi32>",
+        "%0 = arith.cmpi eq, %arg0, %arg1 : vector<4xi32>",
+    );
+    let (i64, vector) = (ctx.integer_type(64), parse_type(&mut ctx, "vector<4xi1>").unwrap());
+    assert_eq!(cmpi.result_types(&ctx), [vector]);
+    let predicate = ctx.integer_attr(0, i64);
+    assert_eq!(cmpi.attribute(&ctx, "predicate"), Some(predicate));
+    assert!(cmpi.attributes(&ctx).is_empty());
+
+    let load = parse_snippet(
+        &mut ctx,
+        "%arg0: memref<4xf32>, %arg1: index",
+        "%0 = memref.load %arg0[%arg1] : memref<4xf32>",
+    );
+    let f32 = parse_type(&mut ctx, "f32").unwrap();
+    assert_eq!(load.result_types(&ctx), [f32]);
+}
+
+#[test]
+fn should_add_segment_sizes() {
+    let mut ctx = context();
+    let op = parse_snippet(
+        &mut ctx,
+        "%arg0: i64, %arg1: i64",
+        "test.format_custom_directive_operands %arg0 -> (%arg1, %arg1)",
+    );
+    let sizes = ctx.dense_i32_array_attr(&[1, 0, 2]);
+    assert_eq!(op.attribute(&ctx, "operandSegmentSizes"), Some(sizes));
+}
+
+#[test]
+fn should_build_operations() {
+    let mut ctx = context();
+    let vector = parse_type(&mut ctx, "vector<4xi32>").unwrap();
+    let i1 = ctx.integer_type(1);
+    let block = ctx.create_block(&[vector, vector, i1]);
+    let [a, b, cond] = block.arguments(&ctx).to_vec()[..] else {
+        unreachable!("three arguments")
+    };
+    let i64 = ctx.integer_type(64);
+    let predicate = ctx.integer_attr(2, i64);
+    let cmpi = arith::CmpIOp::build(&mut ctx, predicate, a, b);
+    assert_eq!(cmpi.name, "arith.cmpi");
+    assert_eq!(cmpi.operands, [a, b]);
+    assert_eq!(cmpi.types, [parse_type(&mut ctx, "vector<4xi1>").unwrap()]);
+    assert!(cmpi.attributes.is_empty());
+    let cmpi = ctx.create_operation(cmpi);
+    assert_eq!(cmpi.attribute(&ctx, "predicate"), Some(predicate));
+
+    let i32 = ctx.integer_type(32);
+    let value = ctx.integer_attr(42, i32);
+    let constant = arith::ConstantOp::build(&mut ctx, value);
+    assert_eq!(constant.types, [i32]);
+
+    let cond_br = cf::CondBranchOp::build(&mut ctx, cond, vec![], vec![a, b]);
+    let cond_br = ctx.create_operation(cond_br);
+    let groups = cond_br.operand_groups(
+        &ctx,
+        &[Variadicity::Single, Variadicity::Variadic, Variadicity::Variadic],
+    );
+    assert_eq!(groups, [&[cond][..], &[], &[a, b]]);
+}
+
+#[test]
+fn should_insert_implicit_terminators() {
+    let mut ctx = context();
+    let op = parse_snippet(&mut ctx, "", "scf.forall.in_parallel {\n}");
+    let block = op.regions(&ctx)[0].blocks(&ctx)[0];
+    let names: Vec<&str> = block.operations(&ctx).iter().map(|op| op.name(&ctx)).collect();
+    assert_eq!(names, ["scf.yield"]);
+}
+
+#[test]
+fn should_print_unregistered_operations_generically() {
+    let mut ctx = context();
+    assert_eq!(
+        test::SKIPPED,
+        &[("test.format_custom_cpp", "operation has a custom C++ assembly format")]
+    );
+    assert_round_trip(
+        &mut ctx,
+        &wrap("%arg0: i64", "\"test.format_custom_cpp\"(%arg0) : (i64) -> ()"),
+    );
+}
@@ -0,0 +1,198 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Round trips the generated attribute and type structs through the snippets of the upstream MLIR tests.
+//!
+
+use std::collections::HashSet;
+
+use fljuga_handahofi_mlir_ir::{parse_type, AttrOrTypeDef, Context, Printer, PrinterOptions};
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "src/testdata/ods/TestAttrDefs.td",
+    includes = ["include", "src/testdata/ods"],
+    hooks = hooks,
+    custom = ["PrettyLLVMType", "CompoundAAttr"]
+}
+
+/// Hand-written `custom<...>` directives and C++ assembly formats, mirroring the upstream `LLVMTypeSyntax.cpp` and `TestAttributes.cpp`.
+mod hooks {
+    use fljuga_handahofi_mlir_ir::{AsmParameter, ParseError, Parser, Printer, Type};
+
+    pub fn print_pretty_llvmtype(p: &mut Printer<'_>, r#type: &Type) {
+        p.print_stripped_type(*r#type, "llvm");
+    }
+
+    pub fn parse_pretty_llvmtype(parser: &mut Parser<'_, '_>, r#type: &mut Option<Type>) -> Result<(), ParseError> {
+        *r#type = match parser.parse_optional_type()? {
+            Some(builtin) => Some(builtin),
+            None => Some(parser.parse_stripped_type("llvm")?),
+        };
+        Ok(())
+    }
+
+    pub fn print_compound_aattr(attr: &super::test::CompoundAAttr, p: &mut Printer<'_>) {
+        p.write("<");
+        attr.width_of_something.print(p);
+        p.write(", ");
+        attr.one_type.print(p);
+        p.write(", [");
+        attr.array_of_ints.print(p);
+        p.write("]>");
+    }
+
+    pub fn parse_compound_aattr(parser: &mut Parser<'_, '_>) -> Result<super::test::CompoundAAttr, ParseError> {
+        parser.parse_punct("<")?;
+        let width_of_something = i32::parse(parser)?;
+        parser.parse_punct(",")?;
+        let one_type = Type::parse(parser)?;
+        parser.parse_punct(",")?;
+        parser.parse_punct("[")?;
+        let array_of_ints = Vec::parse(parser)?;
+        parser.parse_punct("]")?;
+        parser.parse_punct(">")?;
+        Ok(super::test::CompoundAAttr::new(
+            width_of_something,
+            one_type,
+            array_of_ints,
+        ))
+    }
+}
+
+fn assert_round_trip<T: AttrOrTypeDef>(ctx: &mut Context, source: &str) -> T {
+    let def = T::from_text(ctx, source).unwrap_or_else(|error| panic!("{source}: {error}"));
+    assert_eq!(def.to_text(ctx), source);
+    def
+}
+
+#[test]
+fn should_round_trip_upstream_snippets() {
+    let mut ctx = Context::new();
+    let (i32, i64) = (ctx.integer_type(32), ctx.integer_type(64));
+    // mlir/test/Dialect/LLVMIR/types.mlir
+    assert_eq!(
+        assert_round_trip::<llvm::LLVMPointerType>(&mut ctx, "!llvm.ptr"),
+        llvm::LLVMPointerType::new(0)
+    );
+    assert_eq!(
+        assert_round_trip::<llvm::LLVMPointerType>(&mut ctx, "!llvm.ptr<1>"),
+        llvm::LLVMPointerType::new(1)
+    );
+    assert_eq!(
+        assert_round_trip::<llvm::LLVMArrayType>(&mut ctx, "!llvm.array<4 x i32>"),
+        llvm::LLVMArrayType::new(i32, 4)
+    );
+    assert_round_trip::<llvm::LLVMArrayType>(&mut ctx, "!llvm.array<2 x ptr>");
+    // mlir/test/Dialect/GPU/ops.mlir
+    assert_eq!(
+        assert_round_trip::<gpu::AsyncTokenType>(&mut ctx, "!gpu.async.token"),
+        gpu::AsyncTokenType
+    );
+    // mlir/test/IR/attribute.mlir
+    assert_round_trip::<test::CompoundAAttr>(&mut ctx, "#test.cmpnd_a<1, i32, [1, 2, 3]>");
+    // mlir/test/mlir-tblgen/attr-or-type-format.mlir
+    let forty_two = ctx.integer_attr(42, i64);
+    assert_eq!(
+        assert_round_trip::<test::TestAttrWithFormatAttr>(
+            &mut ctx,
+            "#test.attr_with_format<3 : two = \"hello\", four = 1, 2, 3 : 42 : i64>"
+        ),
+        test::TestAttrWithFormatAttr::new(3, "hello", forty_two, vec![1, 2, 3])
+    );
+    assert_round_trip::<test::TestTypeWithFormatType>(
+        &mut ctx,
+        "!test.type_with_format<2147, three = \"hi\", two = \"hi\">",
+    );
+    assert_round_trip::<test::TestTypeOptionalStructType>(&mut ctx, "!test.optional_type_struct<a = 1, b = \"foo\">");
+    assert_eq!(
+        assert_round_trip::<test::TestTypeOptionalStructType>(&mut ctx, "!test.optional_type_struct<b = \"foo\">"),
+        test::TestTypeOptionalStructType::new(None, "foo")
+    );
+    assert_round_trip::<test::TestTypeParamsType>(&mut ctx, "!test.type_params<1, i32, f32>");
+}
+
+#[test]
+fn should_parse_struct_parameters_in_any_order() {
+    let mut ctx = Context::new();
+    let parsed =
+        test::TestTypeWithFormatType::from_text(&mut ctx, "!test.type_with_format<1, two = \"a\", three = \"b\">")
+            .unwrap();
+    assert_eq!(
+        parsed.to_text(&ctx),
+        "!test.type_with_format<1, three = \"b\", two = \"a\">"
+    );
+}
+
+#[test]
+fn should_reject_malformed_definitions() {
+    let mut ctx = Context::new();
+    let ctx = &mut ctx;
+    let errors = [
+        test::TestTypeWithFormatType::from_text(ctx, "!test.type_with_format<1, two = \"a\">").err(),
+        test::TestTypeWithFormatType::from_text(ctx, "!test.type_with_format<1, two = \"a\", two = \"b\">").err(),
+        test::TestTypeOptionalStructType::from_text(ctx, "!test.optional_type_struct<c = 1>").err(),
+        llvm::LLVMPointerType::from_text(ctx, "!llvm.ptr<1> x").err(),
+        llvm::LLVMPointerType::from_text(ctx, "!llvm.ptrx").err(),
+        llvm::LLVMPointerType::from_text(ctx, "#llvm.ptr").err(),
+    ];
+    for error in errors {
+        assert!(error.is_some());
+    }
+}
+
+#[test]
+fn should_unique_equal_definitions() {
+    let mut ctx = Context::new();
+    let types: HashSet<_> = ["!llvm.ptr", "!llvm.ptr<0>", "!llvm.ptr<1>"]
+        .into_iter()
+        .map(|source| {
+            llvm::LLVMPointerType::from_text(&mut ctx, source)
+                .unwrap()
+                .get(&mut ctx)
+        })
+        .collect();
+    assert_eq!(types.len(), 2);
+}
+
+#[test]
+fn should_convert_to_attributes_and_types() {
+    let mut ctx = Context::new();
+    let (f32, i8) = (parse_type(&mut ctx, "f32").unwrap(), ctx.integer_type(8));
+    let r#type = llvm::LLVMArrayType::new(f32, 8).get(&mut ctx);
+    assert_eq!(r#type, parse_type(&mut ctx, "!llvm.array<8 x f32>").unwrap());
+    assert_eq!(llvm::LLVMArrayType::cast(&mut ctx, r#type).unwrap().num_elements, 8);
+    assert!(llvm::LLVMPointerType::cast(&mut ctx, r#type).is_err());
+
+    let attr = test::CompoundAAttr::new(4, i8, vec![]).get(&mut ctx);
+    let mut printer = Printer::new(&ctx, PrinterOptions::default());
+    printer.print_attribute(attr);
+    assert_eq!(printer.finish(), "#test.cmpnd_a<4, i8, []>");
+    assert_eq!(test::CompoundAAttr::cast(&mut ctx, attr).unwrap().width_of_something, 4);
+}
+
+#[test]
+fn should_list_skipped_definitions() {
+    assert_eq!(sparse_tensor::SKIPPED_DEFS.len(), 1);
+    assert_eq!(sparse_tensor::SKIPPED_DEFS[0].0, "sparse_tensor.encoding");
+    assert_eq!(
+        test::SKIPPED_DEFS,
+        &[(
+            "test.default_valued_type",
+            "parameter \"type\" has the unsupported default value \"mlir::IntegerType::get($_ctxt, 32)\""
+        )]
+    );
+}
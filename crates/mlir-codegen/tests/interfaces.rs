@@ -0,0 +1,158 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Looks up the generated interface traits implemented by the operations declaring their methods.
+//!
+
+use fljuga_handahofi_mlir_ir::{parse, Context, Dialect, InterfaceRegistry, OpAsmRegistry, Operation};
+
+use td::interfaces::{BranchOpInterface, InferTypeOpInterface, LoopLikeOpInterface, MemoryEffectOpInterface};
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "src/testdata/ods/TestOps.td",
+    includes = ["include", "src/testdata/ods"],
+    hooks = hooks,
+    interfaces = td::interfaces,
+    methods = ["ForOp::getLoopRegions", "AddIOp::inferReturnTypes", "BranchOp::getSuccessorOperands"]
+}
+
+mod td {
+    fljuga_handahofi_mlir_codegen::dialect! {
+        file = "src/testdata/ods/TestInterfaces.td",
+        includes = ["include", "src/testdata/ods"]
+    }
+}
+
+/// Hand-written interface methods.
+mod hooks {
+    use fljuga_handahofi_mlir_ir::{
+        Context, InterfaceError, LogicalResult, NamedAttribute, Operation, Region, Type, Value,
+    };
+
+    pub fn for_op_get_loop_regions(ctx: &Context, op: Operation) -> Vec<Region> {
+        op.regions(ctx).to_vec()
+    }
+
+    pub fn add_iop_infer_return_types(
+        ctx: &Context,
+        operands: &[Value],
+        _attributes: &[NamedAttribute],
+        _regions: &[Region],
+        inferred_return_types: &mut Vec<Type>,
+    ) -> LogicalResult {
+        let operand = operands
+            .first()
+            .ok_or_else(|| InterfaceError::new("expected the operands"))?;
+        inferred_return_types.push(operand.r#type(ctx));
+        Ok(())
+    }
+
+    pub fn branch_op_get_successor_operands(ctx: &Context, op: Operation, _index: u32) -> Vec<Value> {
+        op.operands(ctx).to_vec()
+    }
+}
+
+/// Registers the generated interfaces, the operations keep their generic form.
+struct TestDialect;
+
+impl Dialect for TestDialect {
+    fn namespace(&self) -> &str {
+        "test"
+    }
+
+    fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+        register_interfaces(registry);
+    }
+
+    fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+        register(registry);
+    }
+}
+
+fn context() -> Context {
+    let mut ctx = Context::new();
+    ctx.register_dialect(TestDialect);
+    ctx
+}
+
+/// Operations of the `test.region` body.
+fn parse_operations(ctx: &mut Context, source: &str) -> Vec<Operation> {
+    let module = parse(ctx, source).unwrap();
+    let region = module.walk(ctx)[1];
+    region.regions(ctx)[0].blocks(ctx)[0].operations(ctx).to_vec()
+}
+
+#[test]
+fn should_lookup_declared_interfaces() {
+    let mut ctx = context();
+    let operations = parse_operations(
+        &mut ctx,
+        r#""test.region"() ({
+^bb0(%lb: index, %ub: index, %step: index, %arg0: i32, %a: i64, %b: i64):
+  %0 = "scf.for"(%lb, %ub, %step) ({
+  }) : (index, index, index) -> (i32)
+  %1 = arith.addi %a, %b : i64
+  cf.br ^bb1(%arg0 : i32)
+^bb1(%2: i32):
+  func.return
+}) : () -> ()
+"#,
+    );
+    let [for_op, addi, br] = operations[..] else {
+        panic!("expected three operations")
+    };
+    let arg0 = br.parent_block(&ctx).unwrap().arguments(&ctx)[3];
+
+    let loop_like = for_op.as_interface::<dyn LoopLikeOpInterface>(&ctx).unwrap();
+    assert_eq!(loop_like.get_loop_regions(&ctx, for_op).len(), 1);
+
+    let branch = br.as_interface::<dyn BranchOpInterface>(&ctx).unwrap();
+    assert_eq!(branch.get_successor_operands(&ctx, br, 0), vec![arg0]);
+    assert!(br.as_interface::<dyn LoopLikeOpInterface>(&ctx).is_none());
+
+    let infer = addi.as_interface::<dyn InferTypeOpInterface>(&ctx).unwrap();
+    let mut types = vec![];
+    infer
+        .infer_return_types(&ctx, addi.operands(&ctx), &[], &[], &mut types)
+        .unwrap();
+    assert_eq!(types, vec![ctx.integer_type(64)]);
+    assert!(infer.infer_return_types(&ctx, &[], &[], &[], &mut types).is_err());
+}
+
+#[test]
+fn should_list_implementors() {
+    let mut ctx = context();
+    let interfaces = ctx.dialects().interfaces();
+    let mut branches: Vec<&str> = interfaces.implementors::<dyn BranchOpInterface>().collect();
+    branches.sort();
+    assert_eq!(branches, vec!["cf.br", "cf.cond_br"]);
+    assert!(!interfaces.implements::<dyn MemoryEffectOpInterface>("cf.br"));
+    let yield_op = parse_operations(&mut ctx, "\"test.region\"() ({\n  scf.yield\n}) : () -> ()\n")[0];
+    assert!(yield_op.as_interface::<dyn LoopLikeOpInterface>(&ctx).is_none());
+}
+
+#[test]
+#[should_panic(expected = "`ForOp::getLoopInductionVars`")]
+fn should_stub_undeclared_hooks() {
+    let mut ctx = context();
+    let for_op = parse_operations(
+        &mut ctx,
+        "\"test.region\"() ({\n  \"scf.for\"() ({\n  }) : () -> ()\n}) : () -> ()\n",
+    )[0];
+    let loop_like = for_op.as_interface::<dyn LoopLikeOpInterface>(&ctx).unwrap();
+    loop_like.get_loop_induction_vars(&ctx, for_op);
+}
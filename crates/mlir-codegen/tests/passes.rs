@@ -0,0 +1,107 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//!
+//! Parses and prints the options of the generated passes in the textual pipeline syntax.
+//!
+
+use fljuga_handahofi_mlir_ir::passes::{PassOptionError, PassRegistry};
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "src/testdata/ods/TestPasses.td",
+    includes = ["include", "src/testdata/ods"]
+}
+
+use passes::{
+    AffineLoopFusionOptions, ArithExpandOpsPassOptions, ArithIntRangeNarrowingOptions, CSEOptions, CanonicalizerOptions,
+    ConvertFuncToLLVMPassOptions, InlinerOptions, SparsificationPassOptions,
+};
+
+#[test]
+fn should_parse_pipeline_options() {
+    let options: CanonicalizerOptions = "canonicalize{top-down=false max-iterations=3 disable-patterns=A,B}"
+        .parse()
+        .unwrap();
+    assert!(!options.top_down_processing_enabled);
+    assert_eq!(options.max_iterations, 3);
+    assert_eq!(options.max_num_rewrites, -1);
+    assert_eq!(options.disabled_patterns, vec!["A", "B"]);
+    assert_eq!(options.enable_region_simplification, None);
+
+    let options: InlinerOptions = "inline{default-pipeline={cse, canonicalize} op-pipelines={func.func(cse)}}"
+        .parse()
+        .unwrap();
+    assert_eq!(options.default_pipeline_str, "cse, canonicalize");
+    assert_eq!(options.op_pipeline_list.as_deref(), Some("func.func(cse)"));
+    assert_eq!(options.inlining_threshold, u32::MAX);
+
+    let options: AffineLoopFusionOptions = "affine-loop-fusion{fusion-maximal fusion-compute-tolerance=0.5}".parse().unwrap();
+    assert!(options.maximal_fusion);
+    assert_eq!(options.compute_tolerance_threshold, 0.5);
+    assert_eq!("cse".parse::<CSEOptions>(), Ok(CSEOptions));
+}
+
+#[test]
+fn should_print_pipeline_options() {
+    assert_eq!(
+        CanonicalizerOptions::default().to_string(),
+        "canonicalize{top-down=true max-iterations=10 max-num-rewrites=-1 test-convergence=false disable-patterns= enable-patterns=}"
+    );
+    assert_eq!(CSEOptions.to_string(), "cse");
+    assert_eq!(
+        ArithIntRangeNarrowingOptions {
+            bitwidths_supported: vec![8, 16, 32],
+        }
+        .to_string(),
+        "arith-int-range-narrowing{int-bitwidths-supported=8,16,32}"
+    );
+    let pipeline = "sparsification{parallelization-strategy=any-storage-any-loop enable-runtime-library=false}";
+    let options: SparsificationPassOptions = pipeline.parse().unwrap();
+    assert_eq!(options.parallelization.as_deref(), Some("any-storage-any-loop"));
+    assert_eq!(options.to_string(), pipeline);
+}
+
+#[test]
+fn should_reject_invalid_options() {
+    assert!(matches!(
+        "arith-expand{include-f8=true}".parse::<ArithExpandOpsPassOptions>(),
+        Err(PassOptionError::UnknownOption { .. })
+    ));
+    assert!(matches!(
+        "convert-func-to-llvm{index-bitwidth=wide}".parse::<ConvertFuncToLLVMPassOptions>(),
+        Err(PassOptionError::InvalidValue { .. })
+    ));
+    assert!(matches!("cse{a=1}".parse::<CSEOptions>(), Err(PassOptionError::UnknownOption { .. })));
+    assert!(matches!("cse".parse::<InlinerOptions>(), Err(PassOptionError::UnexpectedPass { .. })));
+}
+
+#[test]
+fn should_register_passes() {
+    let mut registry = PassRegistry::new();
+    passes::register(&mut registry);
+    assert_eq!(registry.passes().count(), 9);
+    let sparsification = registry.get("sparsification").unwrap();
+    assert_eq!(
+        sparsification.dependent_dialects,
+        &["affine", "arith", "bufferization", "llvm", "linalg", "memref", "scf", "sparse_tensor"]
+    );
+    assert_eq!(registry.get("convert-func-to-llvm").unwrap().dependent_dialects, &["llvm", "dlti"]);
+    let canonicalize = registry.get("canonicalize").unwrap();
+    assert_eq!(canonicalize.summary, "Canonicalize operations");
+    assert_eq!(canonicalize.options[2].argument, "max-iterations");
+    assert!(registry.get("loop-invariant-code-motion").unwrap().options.is_empty());
+}
@@ -0,0 +1,310 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Applies the generated arith canonicalization patterns to the snippets of the upstream MLIR tests.
+//!
+
+use fljuga_handahofi_mlir_ir::{
+    apply_patterns_and_fold_greedily, parse, print, Attribute, Context, Dialect, FrozenRewritePatternSet,
+    GreedyRewriteConfig, OpAsmRegistry, Operation, PrinterOptions, RewritePatternSet,
+};
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "../targets/src/arith/ArithCanonicalization.td",
+    includes = ["include"],
+    hooks = hooks,
+    custom = [
+        "AddIntegerAttrs",
+        "SubIntegerAttrs",
+        "MulIntegerAttrs",
+        "GetZeroAttr",
+        "IsScalarOrSplatNegativeOne",
+        "IsEqOrNe"
+    ]
+}
+
+/// Custom forms of the arith operations.
+mod arith {
+    fljuga_handahofi_mlir_codegen::dialect! {
+        file = "include/mlir/Dialect/Arith/IR/ArithOps.td",
+        includes = ["include"]
+    }
+}
+
+/// Hand-written native code calls and constraints, mirroring the upstream `ArithOps.cpp`.
+mod hooks {
+    use fljuga_handahofi_mlir_ir::{Attribute, AttributeKind, Context, Value};
+
+    fn integer_attr(ctx: &mut Context, res: Value, value: i128) -> Attribute {
+        let r#type = res.r#type(ctx);
+        ctx.integer_attr(value, r#type)
+    }
+
+    fn integer(ctx: &Context, attr: Attribute) -> i128 {
+        attr.as_integer(ctx).unwrap_or_default()
+    }
+
+    pub fn add_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs) + integer(ctx, rhs);
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn sub_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs) - integer(ctx, rhs);
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn mul_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs) * integer(ctx, rhs);
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn get_zero_attr(ctx: &mut Context, value: Value) -> Attribute {
+        integer_attr(ctx, value, 0)
+    }
+
+    pub fn is_scalar_or_splat_negative_one(ctx: &Context, attr: Attribute) -> bool {
+        matches!(
+            attr.kind(ctx),
+            AttributeKind::Integer { value: -1, .. } | AttributeKind::Bool(true)
+        )
+    }
+
+    /// `eq` and `ne` cases of the `CmpIPredicate`.
+    pub fn is_eq_or_ne(ctx: &Context, attr: Attribute) -> bool {
+        matches!(attr.as_integer(ctx), Some(0 | 1))
+    }
+}
+
+/// The arith custom forms with the `arith.constant` values the `ConstantAttr` matchers look through.
+struct ArithDialect;
+
+impl Dialect for ArithDialect {
+    fn namespace(&self) -> &str {
+        "arith"
+    }
+
+    fn constant_value(&self, ctx: &Context, op: Operation) -> Option<Attribute> {
+        (op.name(ctx) == "arith.constant").then(|| op.attribute(ctx, "value"))?
+    }
+
+    fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+        arith::register(registry);
+    }
+}
+
+fn patterns() -> FrozenRewritePatternSet {
+    let mut patterns = RewritePatternSet::new();
+    patterns::populate_with_generated(&mut patterns);
+    patterns.into()
+}
+
+/// Applies the patterns to the `test.region` with the block `arguments`, returning whether any matched and the
+/// printed body.
+fn apply(arguments: &str, source: &str) -> (bool, String) {
+    let mut ctx = Context::new();
+    ctx.register_dialect(ArithDialect);
+    let source = format!("\"test.region\"() ({{\n^bb0({arguments}):\n{source}\n}}) : () -> ()\n");
+    let region = parse(&mut ctx, &source).unwrap_or_else(|error| panic!("{source}: {error}"));
+    let changed = apply_patterns_and_fold_greedily(&mut ctx, region, &patterns(), &GreedyRewriteConfig::default())
+        .unwrap_or_else(|error| panic!("{source}: {error}"));
+    let printed = print(&ctx, region, PrinterOptions::default());
+    let body = printed
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("^bb0"))
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('}'))
+        .map(|line| format!("{}\n", line.trim_start()));
+    (changed, body.collect())
+}
+
+#[test]
+fn should_populate_patterns() {
+    let mut patterns = RewritePatternSet::new();
+    patterns::populate_with_generated(&mut patterns);
+    assert_eq!(patterns.len(), 20);
+    let pattern = |name: &str| patterns.iter().find(|pattern| pattern.name() == name);
+    assert_eq!(pattern("AddIAddConstant").map(|pattern| pattern.benefit()), Some(2));
+    assert_eq!(
+        pattern("BitcastOfBitcast").and_then(|pattern| pattern.root_name()),
+        Some("arith.bitcast")
+    );
+    assert_eq!(
+        patterns::SKIPPED,
+        [("TruncIExtSIToExtSI", "constraint ValueWiderThan has no hook")]
+    );
+}
+
+#[test]
+fn should_apply_patterns() {
+    let cases = [
+        // AddIAddConstant
+        (
+            "%x: i32",
+            "%c1 = arith.constant 1 : i32\n%c2 = arith.constant 2 : i32\n%0 = arith.addi %x, %c1 : i32\n%1 = arith.addi %0, %c2 : i32",
+            "%0 = arith.constant 1 : i32\n%1 = arith.constant 2 : i32\n%2 = arith.addi %arg0, %0 : i32\n%3 = arith.constant 3 : i32\n%4 = arith.addi %arg0, %3 : i32\n",
+        ),
+        // AddISubConstantRHS
+        (
+            "%x: i32",
+            "%c1 = arith.constant 1 : i32\n%c5 = arith.constant 5 : i32\n%0 = arith.subi %x, %c1 : i32\n%1 = arith.addi %0, %c5 : i32",
+            "%0 = arith.constant 1 : i32\n%1 = arith.constant 5 : i32\n%2 = arith.subi %arg0, %0 : i32\n%3 = arith.constant 4 : i32\n%4 = arith.addi %arg0, %3 : i32\n",
+        ),
+        // AddISubConstantLHS
+        (
+            "%x: i32",
+            "%c1 = arith.constant 1 : i32\n%c5 = arith.constant 5 : i32\n%0 = arith.subi %c1, %x : i32\n%1 = arith.addi %0, %c5 : i32",
+            "%0 = arith.constant 1 : i32\n%1 = arith.constant 5 : i32\n%2 = arith.subi %0, %arg0 : i32\n%3 = arith.constant 6 : i32\n%4 = arith.subi %3, %arg0 : i32\n",
+        ),
+        // AddIMulNegativeOneRhs
+        (
+            "%x: i64, %y: i64",
+            "%c-1 = arith.constant -1 : i64\n%0 = arith.muli %y, %c-1 : i64\n%1 = arith.addi %x, %0 : i64",
+            "%0 = arith.constant -1 : i64\n%1 = arith.muli %arg1, %0 : i64\n%2 = arith.subi %arg0, %arg1 : i64\n",
+        ),
+        // AddIMulNegativeOneLhs
+        (
+            "%x: i64, %y: i64",
+            "%c-1 = arith.constant -1 : i64\n%0 = arith.muli %x, %c-1 : i64\n%1 = arith.addi %0, %y : i64",
+            "%0 = arith.constant -1 : i64\n%1 = arith.muli %arg0, %0 : i64\n%2 = arith.subi %arg1, %arg0 : i64\n",
+        ),
+        // MulIMulIConstant
+        (
+            "%x: i32",
+            "%c3 = arith.constant 3 : i32\n%c4 = arith.constant 4 : i32\n%0 = arith.muli %x, %c3 : i32\n%1 = arith.muli %0, %c4 : i32",
+            "%0 = arith.constant 3 : i32\n%1 = arith.constant 4 : i32\n%2 = arith.muli %arg0, %0 : i32\n%3 = arith.constant 12 : i32\n%4 = arith.muli %arg0, %3 : i32\n",
+        ),
+        // SubIRHSAddConstant
+        (
+            "%x: i32",
+            "%c7 = arith.constant 7 : i32\n%c2 = arith.constant 2 : i32\n%0 = arith.addi %x, %c7 : i32\n%1 = arith.subi %0, %c2 : i32",
+            "%0 = arith.constant 7 : i32\n%1 = arith.constant 2 : i32\n%2 = arith.addi %arg0, %0 : i32\n%3 = arith.constant 5 : i32\n%4 = arith.addi %arg0, %3 : i32\n",
+        ),
+        // SubILHSAddConstant
+        (
+            "%x: i32",
+            "%c7 = arith.constant 7 : i32\n%c2 = arith.constant 2 : i32\n%0 = arith.addi %x, %c2 : i32\n%1 = arith.subi %c7, %0 : i32",
+            "%0 = arith.constant 7 : i32\n%1 = arith.constant 2 : i32\n%2 = arith.addi %arg0, %1 : i32\n%3 = arith.constant 5 : i32\n%4 = arith.subi %3, %arg0 : i32\n",
+        ),
+        // SubISubILHSRHSLHS
+        (
+            "%x: i32, %y: i32",
+            "%0 = arith.subi %x, %y : i32\n%1 = arith.subi %0, %x : i32",
+            "%0 = arith.subi %arg0, %arg1 : i32\n%1 = arith.constant 0 : i32\n%2 = arith.subi %1, %arg1 : i32\n",
+        ),
+        // CmpIExtSI
+        (
+            "%a: i8, %b: i8",
+            "%0 = arith.extsi %a : i8 to i32\n%1 = arith.extsi %b : i8 to i32\n%2 = arith.cmpi eq, %0, %1 : i32",
+            "%0 = arith.extsi %arg0 : i8 to i32\n%1 = arith.extsi %arg1 : i8 to i32\n%2 = arith.cmpi eq, %arg0, %arg1 : i8\n",
+        ),
+        // CmpIExtUI
+        (
+            "%a: i8, %b: i8",
+            "%0 = arith.extui %a : i8 to i32\n%1 = arith.extui %b : i8 to i32\n%2 = arith.cmpi ne, %0, %1 : i32",
+            "%0 = arith.extui %arg0 : i8 to i32\n%1 = arith.extui %arg1 : i8 to i32\n%2 = arith.cmpi ne, %arg0, %arg1 : i8\n",
+        ),
+        // SelectNotCond
+        (
+            "%c: i1, %a: i32, %b: i32",
+            "%true = arith.constant true\n%0 = arith.xori %c, %true : i1\n%1 = arith.select %0, %a, %b : i32",
+            "%0 = arith.constant true\n%1 = arith.xori %arg0, %0 : i1\n%2 = arith.select %arg0, %arg2, %arg1 : i32\n",
+        ),
+        // RedundantSelectTrue
+        (
+            "%c: i1, %a: i32, %b: i32, %d: i32",
+            "%0 = arith.select %c, %a, %b : i32\n%1 = arith.select %c, %0, %d : i32",
+            "%0 = arith.select %arg0, %arg1, %arg2 : i32\n%1 = arith.select %arg0, %arg1, %arg3 : i32\n",
+        ),
+        // RedundantSelectFalse
+        (
+            "%c: i1, %a: i32, %b: i32, %d: i32",
+            "%0 = arith.select %c, %b, %d : i32\n%1 = arith.select %c, %a, %0 : i32",
+            "%0 = arith.select %arg0, %arg2, %arg3 : i32\n%1 = arith.select %arg0, %arg1, %arg3 : i32\n",
+        ),
+        // SelectI1ToNot
+        (
+            "%c: i1",
+            "%false = arith.constant false\n%true = arith.constant true\n%0 = arith.select %c, %false, %true : i1",
+            "%0 = arith.constant false\n%1 = arith.constant true\n%2 = arith.constant true\n%3 = arith.xori %arg0, %2 : i1\n",
+        ),
+        // IndexCastOfIndexCast
+        (
+            "%x: i32",
+            "%0 = arith.index_cast %x : i32 to index\n%1 = arith.index_cast %0 : index to i32\n%2 = arith.addi %1, %1 : i32",
+            "%0 = arith.index_cast %arg0 : i32 to index\n%1 = arith.addi %arg0, %arg0 : i32\n",
+        ),
+        // BitcastOfBitcast
+        (
+            "%x: f32",
+            "%0 = arith.bitcast %x : f32 to i32\n%1 = arith.bitcast %0 : i32 to f32",
+            "%0 = arith.bitcast %arg0 : f32 to i32\n",
+        ),
+        // ExtSIOfExtUI
+        (
+            "%x: i8",
+            "%0 = arith.extui %x : i8 to i16\n%1 = arith.extsi %0 : i16 to i32",
+            "%0 = arith.extui %arg0 : i8 to i16\n%1 = arith.extui %arg0 : i8 to i32\n",
+        ),
+        // AndOfExtUI
+        (
+            "%x: i8, %y: i8",
+            "%0 = arith.extui %x : i8 to i32\n%1 = arith.extui %y : i8 to i32\n%2 = arith.andi %0, %1 : i32",
+            "%0 = arith.extui %arg0 : i8 to i32\n%1 = arith.extui %arg1 : i8 to i32\n%2 = arith.andi %arg0, %arg1 : i8\n%3 = arith.extui %2 : i8 to i32\n",
+        ),
+        // OrOfExtSI
+        (
+            "%x: i8, %y: i8",
+            "%0 = arith.extsi %x : i8 to i32\n%1 = arith.extsi %y : i8 to i32\n%2 = arith.ori %0, %1 : i32",
+            "%0 = arith.extsi %arg0 : i8 to i32\n%1 = arith.extsi %arg1 : i8 to i32\n%2 = arith.ori %arg0, %arg1 : i8\n%3 = arith.extsi %2 : i8 to i32\n",
+        ),
+    ];
+    for (arguments, source, expected) in cases {
+        let (changed, body) = apply(arguments, source);
+        assert!(changed, "no pattern matched {source}");
+        assert_eq!(body, expected, "{source}");
+    }
+}
+
+#[test]
+fn should_not_apply_patterns() {
+    let sources = [
+        // IndexCastOfIndexCast of the distinct types
+        (
+            "%x: i64",
+            "%0 = arith.index_cast %x : i64 to index\n%1 = arith.index_cast %0 : index to i32",
+        ),
+        // CmpIExtSI of the ordering predicates
+        (
+            "%a: i8, %b: i8",
+            "%0 = arith.extsi %a : i8 to i32\n%1 = arith.extsi %b : i8 to i32\n%2 = arith.cmpi slt, %0, %1 : i32",
+        ),
+        // AddIMulNegativeOneRhs of the other constants
+        (
+            "%x: i64, %y: i64",
+            "%c2 = arith.constant 2 : i64\n%0 = arith.muli %y, %c2 : i64\n%1 = arith.addi %x, %0 : i64",
+        ),
+        // TruncIExtSIToExtSI is skipped
+        (
+            "%x: i8",
+            "%0 = arith.extsi %x : i8 to i64\n%1 = arith.trunci %0 : i64 to i32",
+        ),
+    ];
+    for (arguments, source) in sources {
+        assert!(!apply(arguments, source).0, "{source}");
+    }
+}
@@ -0,0 +1,322 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Affine maps of the `affine_map<...>` attributes and the memref layouts.
+//!
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Rem, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AffineBinaryKind {
+    Add,
+    Mul,
+    Mod,
+    FloorDiv,
+    CeilDiv,
+}
+
+impl AffineBinaryKind {
+    fn keyword(self) -> &'static str {
+        match self {
+            AffineBinaryKind::Add => "+",
+            AffineBinaryKind::Mul => "*",
+            AffineBinaryKind::Mod => "mod",
+            AffineBinaryKind::FloorDiv => "floordiv",
+            AffineBinaryKind::CeilDiv => "ceildiv",
+        }
+    }
+}
+
+/// Affine expression over the `d` dimensions and the `s` symbols.
+/// [AffineExpr::binary] keeps it in the upstream canonical form, folding the constants
+/// and moving them to the right hand side of the commutative operations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AffineExpr {
+    Dim(u32),
+    Symbol(u32),
+    Constant(i64),
+    Binary {
+        kind: AffineBinaryKind,
+        lhs: Box<AffineExpr>,
+        rhs: Box<AffineExpr>,
+    },
+}
+
+impl AffineExpr {
+    pub fn binary(kind: AffineBinaryKind, lhs: AffineExpr, rhs: AffineExpr) -> Self {
+        use AffineBinaryKind::*;
+        use AffineExpr::Constant;
+
+        let (lhs, rhs) = match (kind, lhs, rhs) {
+            (Add | Mul, Constant(lhs), rhs) if !matches!(rhs, Constant(_)) => (rhs, Constant(lhs)),
+            (_, lhs, rhs) => (lhs, rhs),
+        };
+        match (kind, lhs, rhs) {
+            (Add, Constant(lhs), Constant(rhs)) => Constant(lhs + rhs),
+            (Mul, Constant(lhs), Constant(rhs)) => Constant(lhs * rhs),
+            (Mod, Constant(lhs), Constant(rhs)) if rhs > 0 => Constant(lhs.rem_euclid(rhs)),
+            (FloorDiv, Constant(lhs), Constant(rhs)) if rhs > 0 => Constant(lhs.div_euclid(rhs)),
+            (CeilDiv, Constant(lhs), Constant(rhs)) if rhs > 0 => Constant(-(-lhs).div_euclid(rhs)),
+            (Add, lhs, Constant(0)) | (Mul | FloorDiv | CeilDiv, lhs, Constant(1)) => lhs,
+            (Mul, _, Constant(0)) | (Mod, _, Constant(1)) => Constant(0),
+            (
+                Add | Mul,
+                AffineExpr::Binary {
+                    kind: inner,
+                    lhs,
+                    rhs: inner_rhs,
+                },
+                Constant(rhs),
+            ) if inner == kind && matches!(*inner_rhs, Constant(_)) => {
+                AffineExpr::binary(kind, *lhs, AffineExpr::binary(kind, *inner_rhs, Constant(rhs)))
+            }
+            (kind, lhs, rhs) => AffineExpr::Binary {
+                kind,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        }
+    }
+
+    pub fn floor_div(self, rhs: AffineExpr) -> Self {
+        AffineExpr::binary(AffineBinaryKind::FloorDiv, self, rhs)
+    }
+
+    pub fn ceil_div(self, rhs: AffineExpr) -> Self {
+        AffineExpr::binary(AffineBinaryKind::CeilDiv, self, rhs)
+    }
+
+    /// Highest dimension and symbol positions used by the expression.
+    fn positions(&self, dims: &mut u32, symbols: &mut u32) {
+        match self {
+            AffineExpr::Dim(position) => *dims = (*dims).max(position + 1),
+            AffineExpr::Symbol(position) => *symbols = (*symbols).max(position + 1),
+            AffineExpr::Constant(_) => {}
+            AffineExpr::Binary { lhs, rhs, .. } => {
+                lhs.positions(dims, symbols);
+                rhs.positions(dims, symbols);
+            }
+        }
+    }
+
+    /// Prints the expression like the upstream `printAffineExprInternal`,
+    /// the `strong` binding wraps the binary expressions into the parentheses.
+    fn print(&self, f: &mut fmt::Formatter<'_>, strong: bool) -> fmt::Result {
+        let (kind, lhs, rhs) = match self {
+            AffineExpr::Dim(position) => return write!(f, "d{position}"),
+            AffineExpr::Symbol(position) => return write!(f, "s{position}"),
+            AffineExpr::Constant(value) => return write!(f, "{value}"),
+            AffineExpr::Binary { kind, lhs, rhs } => (*kind, lhs, rhs),
+        };
+        if kind == AffineBinaryKind::Mul && **rhs == AffineExpr::Constant(-1) {
+            f.write_str("-")?;
+            return lhs.print(f, true);
+        }
+        if strong {
+            f.write_str("(")?;
+        }
+        if kind != AffineBinaryKind::Add {
+            lhs.print(f, true)?;
+            write!(f, " {} ", kind.keyword())?;
+            rhs.print(f, true)?;
+        } else {
+            lhs.print(f, false)?;
+            let negated = match &**rhs {
+                AffineExpr::Binary {
+                    kind: AffineBinaryKind::Mul,
+                    lhs: negated,
+                    rhs: factor,
+                } => factor
+                    .as_constant()
+                    .filter(|factor| *factor < 0)
+                    .map(|factor| (negated, factor)),
+                _ => None,
+            };
+            match (negated, &**rhs) {
+                (Some((negated, -1)), _) => {
+                    f.write_str(" - ")?;
+                    let strong = matches!(
+                        **negated,
+                        AffineExpr::Binary {
+                            kind: AffineBinaryKind::Add,
+                            ..
+                        }
+                    );
+                    negated.print(f, strong)?;
+                }
+                (Some((negated, factor)), _) => {
+                    f.write_str(" - ")?;
+                    negated.print(f, true)?;
+                    write!(f, " * {}", -factor)?;
+                }
+                (None, AffineExpr::Constant(value)) if *value < 0 => write!(f, " - {}", -value)?,
+                (None, rhs) => {
+                    f.write_str(" + ")?;
+                    rhs.print(f, false)?;
+                }
+            }
+        }
+        if strong {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+
+    pub fn as_constant(&self) -> Option<i64> {
+        match self {
+            AffineExpr::Constant(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AffineExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.print(f, false)
+    }
+}
+
+impl Add for AffineExpr {
+    type Output = AffineExpr;
+
+    fn add(self, rhs: AffineExpr) -> AffineExpr {
+        AffineExpr::binary(AffineBinaryKind::Add, self, rhs)
+    }
+}
+
+impl Sub for AffineExpr {
+    type Output = AffineExpr;
+
+    fn sub(self, rhs: AffineExpr) -> AffineExpr {
+        self + -rhs
+    }
+}
+
+impl Mul for AffineExpr {
+    type Output = AffineExpr;
+
+    fn mul(self, rhs: AffineExpr) -> AffineExpr {
+        AffineExpr::binary(AffineBinaryKind::Mul, self, rhs)
+    }
+}
+
+impl Rem for AffineExpr {
+    type Output = AffineExpr;
+
+    fn rem(self, rhs: AffineExpr) -> AffineExpr {
+        AffineExpr::binary(AffineBinaryKind::Mod, self, rhs)
+    }
+}
+
+impl Neg for AffineExpr {
+    type Output = AffineExpr;
+
+    fn neg(self) -> AffineExpr {
+        self * AffineExpr::Constant(-1)
+    }
+}
+
+/// `(d0, d1)[s0] -> (d0 + s0, d1)` map of the dimensions and symbols to the results.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AffineMap {
+    pub dims: u32,
+    pub symbols: u32,
+    pub results: Vec<AffineExpr>,
+}
+
+impl AffineMap {
+    /// Map of the results, inferring the number of dimensions and symbols from them.
+    pub fn new(results: Vec<AffineExpr>) -> Self {
+        let (mut dims, mut symbols) = (0, 0);
+        for result in &results {
+            result.positions(&mut dims, &mut symbols);
+        }
+        Self { dims, symbols, results }
+    }
+
+    /// `(d0, ..., dn) -> (d0, ..., dn)`
+    pub fn identity(dims: u32) -> Self {
+        Self {
+            dims,
+            symbols: 0,
+            results: (0..dims).map(AffineExpr::Dim).collect(),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == AffineMap::identity(self.dims)
+    }
+}
+
+impl fmt::Display for AffineMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |prefix: char, count: u32| (0..count).map(|index| format!("{prefix}{index}")).collect::<Vec<_>>();
+        write!(f, "({})", list('d', self.dims).join(", "))?;
+        if self.symbols > 0 {
+            write!(f, "[{}]", list('s', self.symbols).join(", "))?;
+        }
+        let results: Vec<String> = self.results.iter().map(ToString::to_string).collect();
+        write!(f, " -> ({})", results.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use AffineExpr::{Constant, Dim, Symbol};
+
+    #[test]
+    fn should_canonicalize_expressions() {
+        assert_eq!(Constant(2) * Dim(0), Dim(0) * Constant(2));
+        assert_eq!(Dim(0) + Constant(0), Dim(0));
+        assert_eq!(Dim(0) * Constant(1), Dim(0));
+        assert_eq!((Dim(0) + Constant(1)) + Constant(2), Dim(0) + Constant(3));
+        assert_eq!(Constant(7).floor_div(Constant(2)), Constant(3));
+        assert_eq!(Constant(-7).floor_div(Constant(2)), Constant(-4));
+        assert_eq!(Constant(7).ceil_div(Constant(2)), Constant(4));
+        assert_eq!(Constant(-7) % Constant(2), Constant(1));
+    }
+
+    #[test]
+    fn should_print_expressions() {
+        let print = |expr: AffineExpr| expr.to_string();
+        assert_eq!(print(Dim(0) + Symbol(0) * Constant(2)), "d0 + s0 * 2");
+        assert_eq!(print(Dim(0) - Dim(1)), "d0 - d1");
+        assert_eq!(print(Dim(0) - Dim(1) * Constant(4)), "d0 - d1 * 4");
+        assert_eq!(print(Dim(0) - Constant(1)), "d0 - 1");
+        assert_eq!(print(-Dim(0) + Constant(3)), "-d0 + 3");
+        assert_eq!(print((Dim(0) + Dim(1)).floor_div(Constant(2))), "(d0 + d1) floordiv 2");
+        assert_eq!(
+            print(Dim(0).floor_div(Constant(2)) * Constant(4)),
+            "(d0 floordiv 2) * 4"
+        );
+        assert_eq!(print(Dim(0) - (Dim(1) + Dim(2))), "d0 - (d1 + d2)");
+        assert_eq!(print(Dim(0) % Constant(4)), "d0 mod 4");
+    }
+
+    #[test]
+    fn should_print_maps() {
+        assert_eq!(AffineMap::identity(2).to_string(), "(d0, d1) -> (d0, d1)");
+        let map = AffineMap::new(vec![Dim(1) * Symbol(0) + Dim(0)]);
+        assert_eq!((map.dims, map.symbols), (2, 1));
+        assert_eq!(map.to_string(), "(d0, d1)[s0] -> (d1 * s0 + d0)");
+        assert!(!map.is_identity());
+        assert_eq!(AffineMap::new(vec![Constant(0)]).to_string(), "() -> (0)");
+    }
+}
@@ -0,0 +1,33 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir ir asm
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Custom forms of the operations, mirroring the upstream `OpAsmPrinter` and `OpAsmParser`.
+//! The generated printers and parsers are registered in the [OpAsmRegistry] by the operation name,
+//! through the [crate::Dialect::register_assembly] hook, the [crate::Printer] and the [crate::Parser]
+//! fall back to the generic form for the operations without one.
+//! The generated dialect attributes and types implement [AttrOrTypeDef], converting from and to their textual form.
+//!
+
+mod definitions;
+mod registry;
+
+pub use definitions::{AsmParameter, AttrOrTypeDef};
+pub use registry::{OpAsm, OpAsmRegistry, ParseFn, PrintFn};
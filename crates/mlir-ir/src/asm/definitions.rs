@@ -0,0 +1,263 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Dialect attributes and types generated out of the `AttrDef` and `TypeDef` records, with their parameters.
+//!
+
+use crate::{Attribute, Context, ParseError, Parser, Printer, PrinterOptions, Type};
+
+/// Attribute or type parameter, printed and parsed like the upstream `FieldParser` defaults.
+pub trait AsmParameter: Sized {
+    fn print(&self, p: &mut Printer<'_>);
+
+    /// Parses the parameter, `None` if the input doesn't start with one.
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError>;
+
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, ParseError> {
+        Self::parse_optional(parser)?.ok_or_else(|| parser.error("expected parameter value"))
+    }
+}
+
+macro_rules! integer_parameters {
+    ($($type:ty),*) => {$(
+        impl AsmParameter for $type {
+            fn print(&self, p: &mut Printer<'_>) {
+                p.write(&self.to_string());
+            }
+
+            fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+                match parser.parse_optional_integer()? {
+                    Some(value) => <$type>::try_from(value)
+                        .map(Some)
+                        .map_err(|_| parser.error(concat!("integer value out of the `", stringify!($type), "` range"))),
+                    None => Ok(None),
+                }
+            }
+        }
+    )*};
+}
+
+integer_parameters!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl AsmParameter for bool {
+    fn print(&self, p: &mut Printer<'_>) {
+        p.write(&self.to_string());
+    }
+
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+        if parser.parse_optional_keyword("true") {
+            return Ok(Some(true));
+        }
+        Ok(parser.parse_optional_keyword("false").then_some(false))
+    }
+}
+
+impl AsmParameter for String {
+    fn print(&self, p: &mut Printer<'_>) {
+        p.print_string(self);
+    }
+
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+        parser.parse_optional_string()
+    }
+}
+
+impl AsmParameter for Type {
+    fn print(&self, p: &mut Printer<'_>) {
+        p.print_type(*self);
+    }
+
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+        parser.parse_optional_type()
+    }
+}
+
+impl AsmParameter for Attribute {
+    fn print(&self, p: &mut Printer<'_>) {
+        p.print_attribute(*self);
+    }
+
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+        parser.parse_optional_attribute(None)
+    }
+}
+
+/// Comma separated elements without the brackets, like the `ArrayRefParameter`, possibly empty.
+impl<T: AsmParameter> AsmParameter for Vec<T> {
+    fn print(&self, p: &mut Printer<'_>) {
+        for (index, element) in self.iter().enumerate() {
+            if index > 0 {
+                p.write(", ");
+            }
+            element.print(p);
+        }
+    }
+
+    fn parse_optional(parser: &mut Parser<'_, '_>) -> Result<Option<Self>, ParseError> {
+        let mut elements = vec![];
+        if let Some(element) = T::parse_optional(parser)? {
+            elements.push(element);
+            while parser.parse_optional_punct(",") {
+                elements.push(T::parse(parser)?);
+            }
+        }
+        Ok(Some(elements))
+    }
+}
+
+/// Attribute or type defined by the dialect, its parameters follow the `#dialect.mnemonic` or `!dialect.mnemonic`.
+///
+/// The [Context] keeps them as the opaque dialect attributes and types, [AttrOrTypeDef::get] and
+/// [AttrOrTypeDef::cast] convert between them and the definitions through their textual form.
+pub trait AttrOrTypeDef: Sized {
+    /// `#` for the attributes, `!` for the types.
+    const PREFIX: char;
+    /// `dialect.mnemonic`
+    const NAME: &'static str;
+
+    /// [Attribute] or [Type] of the definition.
+    type Handle: AsmParameter;
+
+    /// Prints the parameters after the mnemonic.
+    fn print(&self, p: &mut Printer<'_>);
+
+    /// Parses the parameters after the mnemonic.
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, ParseError>;
+
+    /// Textual form with the dialect prefix, like `!llvm.ptr<1>`.
+    fn to_text(&self, ctx: &Context) -> String {
+        let mut printer = Printer::new(ctx, PrinterOptions::default());
+        printer.write(&format!("{}{}", Self::PREFIX, Self::NAME));
+        self.print(&mut printer);
+        printer.finish()
+    }
+
+    /// Parses the textual form with the dialect prefix.
+    fn from_text(ctx: &mut Context, text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser::new(ctx, text);
+        let prefix = format!("{}{}", Self::PREFIX, Self::NAME);
+        let mnemonic_end = text
+            .trim_start()
+            .strip_prefix(&prefix)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.')));
+        if !mnemonic_end {
+            return Err(parser.error(format!("expected '{prefix}'")));
+        }
+        parser.parse_punct(&prefix)?;
+        let parsed = Self::parse(&mut parser)?;
+        if !parser.is_at_end() {
+            return Err(parser.error(format!("unexpected trailing characters of '{prefix}'")));
+        }
+        Ok(parsed)
+    }
+
+    /// Uniques the definition in the context.
+    ///
+    /// # Panics
+    ///
+    /// If the printed parameters aren't the balanced body of the dialect attribute or type.
+    fn get(&self, ctx: &mut Context) -> Self::Handle {
+        let text = self.to_text(ctx);
+        let mut parser = Parser::new(ctx, &text);
+        match Self::Handle::parse(&mut parser) {
+            Ok(handle) => handle,
+            Err(error) => panic!("`{text}` is not a dialect attribute or type: {error}"),
+        }
+    }
+
+    /// Definition of the attribute or type, like the upstream `dyn_cast`.
+    fn cast(ctx: &mut Context, handle: Self::Handle) -> Result<Self, ParseError> {
+        let mut printer = Printer::new(ctx, PrinterOptions::default());
+        handle.print(&mut printer);
+        let text = printer.finish();
+        Self::from_text(ctx, &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `!test.pair<a, b>`, hand-written like the generated definitions.
+    #[derive(Debug, PartialEq)]
+    struct PairType {
+        first: u32,
+        second: Vec<Type>,
+    }
+
+    impl AttrOrTypeDef for PairType {
+        const PREFIX: char = '!';
+        const NAME: &'static str = "test.pair";
+
+        type Handle = Type;
+
+        fn print(&self, p: &mut Printer<'_>) {
+            p.write("<");
+            self.first.print(p);
+            p.write(", [");
+            self.second.print(p);
+            p.write("]>");
+        }
+
+        fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, ParseError> {
+            parser.parse_punct("<")?;
+            let first = u32::parse(parser)?;
+            parser.parse_punct(",")?;
+            parser.parse_punct("[")?;
+            let second = Vec::<Type>::parse(parser)?;
+            parser.parse_punct("]")?;
+            parser.parse_punct(">")?;
+            Ok(Self { first, second })
+        }
+    }
+
+    #[test]
+    fn should_round_trip_definitions() {
+        let mut ctx = Context::new();
+        let pair = PairType {
+            first: 4,
+            second: vec![ctx.integer_type(32), ctx.opaque_type("llvm", "ptr")],
+        };
+        assert_eq!(pair.to_text(&ctx), "!test.pair<4, [i32, !llvm.ptr]>");
+        assert_eq!(PairType::from_text(&mut ctx, " !test.pair<4, [i32, !llvm.ptr]>"), Ok(pair));
+        assert_eq!(PairType::from_text(&mut ctx, "!test.pair<1, []>").map(|pair| pair.second), Ok(vec![]));
+        assert!(PairType::from_text(&mut ctx, "!test.pair<-1, []>").is_err());
+        assert!(PairType::from_text(&mut ctx, "!test.pairs<1, []>").is_err());
+        assert!(PairType::from_text(&mut ctx, "!test.pair<1, []> x").is_err());
+    }
+
+    #[test]
+    fn should_convert_definitions_from_and_to_the_context() {
+        let mut ctx = Context::new();
+        let index = ctx.index_type();
+        let pair = PairType {
+            first: 2,
+            second: vec![index],
+        };
+        let r#type = pair.get(&mut ctx);
+        assert_eq!(
+            r#type.kind(&ctx),
+            &crate::TypeKind::Opaque {
+                dialect: "test".into(),
+                data: "pair<2, [index]>".into()
+            }
+        );
+        assert_eq!(PairType::cast(&mut ctx, r#type), Ok(pair));
+        let i32 = ctx.integer_type(32);
+        assert!(PairType::cast(&mut ctx, i32).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Custom operation form printers and parsers registry.
+//!
+
+use std::collections::HashMap;
+
+use crate::{Operation, OperationState, ParseError, Parser, Printer};
+
+/// Prints the operation after its name, the results are printed by the caller.
+pub type PrintFn = fn(Operation, &mut Printer<'_>);
+
+/// Parses the operation after its name, the results are defined by the caller.
+pub type ParseFn = fn(&mut Parser<'_, '_>, &mut OperationState) -> Result<(), ParseError>;
+
+/// Custom form of the operation.
+#[derive(Debug, Clone, Copy)]
+pub struct OpAsm {
+    pub print: PrintFn,
+    pub parse: ParseFn,
+}
+
+/// Operation custom forms by the operation name.
+#[derive(Debug, Clone, Default)]
+pub struct OpAsmRegistry {
+    operations: HashMap<&'static str, OpAsm>,
+    /// Dialects the operations in the regions of the operation are printed and parsed without the prefix of,
+    /// like the upstream `OpAsmOpInterface::getDefaultDialect`.
+    default_dialects: HashMap<&'static str, &'static str>,
+}
+
+impl OpAsmRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &'static str, print: PrintFn, parse: ParseFn) {
+        self.operations.insert(name, OpAsm { print, parse });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OpAsm> {
+        self.operations.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.operations.contains_key(name)
+    }
+
+    /// Sets the default dialect of the regions of the operation, like `func` for the `func.func` bodies.
+    pub fn set_default_dialect(&mut self, name: &'static str, dialect: &'static str) {
+        self.default_dialects.insert(name, dialect);
+    }
+
+    pub fn default_dialect(&self, name: &str) -> Option<&'static str> {
+        self.default_dialects.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, print, types, Dialect, Operation, OperationState, ParseError, Parser, Printer, PrinterOptions};
+    use crate::{Context, OpAsmRegistry};
+
+    /// `test.add %a, %b {attributes} : type`, hand-written like the generated custom forms.
+    struct TestDialect;
+
+    fn print_add(op: Operation, p: &mut Printer<'_>) {
+        let ctx = p.ctx();
+        p.write(" ");
+        p.print_operands(op.operands(ctx));
+        p.print_optional_attr_dict(&op.all_attributes(ctx), &[]);
+        p.write(" : ");
+        p.print_types(&op.result_types(ctx));
+    }
+
+    fn parse_add(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+        let operands = parser.parse_operand_list()?;
+        state.attributes.extend(parser.parse_optional_attr_dict()?);
+        parser.parse_punct(":")?;
+        let r#type = parser.parse_type()?;
+        parser.resolve_operands(&operands, &[r#type, r#type], state)?;
+        state.add_types([r#type]);
+        Ok(())
+    }
+
+    /// `test.func(%arg0: i32) {...}`, the `test` operations of the body are named without the prefix.
+    fn print_func(op: Operation, p: &mut Printer<'_>) {
+        let ctx = p.ctx();
+        let [body] = op.regions(ctx) else {
+            return;
+        };
+        let entry = body.entry_block(ctx);
+        let inputs = entry.map(|entry| types(ctx, entry.arguments(ctx))).unwrap_or_default();
+        p.print_function_signature(entry, &inputs, &[], &[]);
+        p.write(" ");
+        p.print_region(*body, false, true);
+    }
+
+    fn parse_func(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+        let (arguments, _) = parser.parse_function_signature()?;
+        let body = parser.parse_region_with_arguments(&arguments)?;
+        state.add_region(body);
+        Ok(())
+    }
+
+    impl Dialect for TestDialect {
+        fn namespace(&self) -> &str {
+            "test"
+        }
+
+        fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+            registry.register("test.add", print_add, parse_add);
+            registry.register("test.func", print_func, parse_func);
+            registry.set_default_dialect("test.func", "test");
+        }
+    }
+
+    #[test]
+    fn should_round_trip_custom_forms() {
+        let source = r#"module {
+  "test.region"() ({
+  ^bb0(%arg0: i32, %arg1: i32):
+    %0 = test.add %arg0, %arg1 {fast} : i32
+    %1 = test.add %0, %0 : i32
+    "test.use"(%1) : (i32) -> ()
+  }) : () -> ()
+}"#;
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let op = parse(&mut ctx, source).unwrap();
+        assert_eq!(print(&ctx, op, PrinterOptions::default()).trim_end(), source);
+        let add = op.walk(&ctx)[2];
+        assert_eq!(types(&ctx, add.operands(&ctx)), add.result_types(&ctx).repeat(2));
+        let generic = PrinterOptions {
+            generic_op_form: true,
+            ..PrinterOptions::default()
+        };
+        assert!(print(&ctx, op, generic).contains(r#"%1 = "test.add"(%0, %0) : (i32, i32) -> i32"#));
+
+        let mut unregistered = Context::new();
+        let error = parse(&mut unregistered, source).unwrap_err();
+        assert_eq!(error.message, "custom op 'test.add' is unknown");
+        assert_eq!(
+            parse(&mut ctx, "%0 = test.add %0 : i32").unwrap_err().message,
+            "1 operands present, but expected 2"
+        );
+    }
+
+    #[test]
+    fn should_name_the_operations_of_the_default_dialect() {
+        let source = r#"module @outer attributes {test.unit} {
+  test.func(%arg0: i32, %arg1: i32) {
+    %0 = add %arg0, %arg1 : i32
+    "test.use"(%0) : (i32) -> ()
+  }
+}"#;
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let op = parse(&mut ctx, source).unwrap();
+        assert_eq!(print(&ctx, op, PrinterOptions::default()).trim_end(), source);
+        let generic = PrinterOptions {
+            generic_op_form: true,
+            ..PrinterOptions::default()
+        };
+        let generic = print(&ctx, op, generic);
+        assert!(generic.starts_with(r#""builtin.module"() ({"#), "{generic}");
+        assert!(generic.contains(r#"%0 = "test.add"(%arg0, %arg1) : (i32, i32) -> i32"#), "{generic}");
+
+        assert_eq!(
+            parse(&mut ctx, "%0 = add %1, %1 : i32").unwrap_err().message,
+            "custom op 'add' is unknown"
+        );
+        assert_eq!(
+            parse(&mut ctx, "test.func(%arg0: i32) {\n^bb0:\n}").unwrap_err().message,
+            "invalid block name in region with named arguments"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Uniqued builtin attributes, the dialect attributes without a dedicated representation are kept opaque.
+//!
+
+use crate::{AffineMap, Context, Type};
+
+/// Uniqued attribute handle, the equal attributes of a [Context] share it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Attribute(pub(crate) u32);
+
+/// Attribute of the operation attribute dictionary, or of the `DictionaryAttr`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamedAttribute {
+    pub name: String,
+    pub value: Attribute,
+}
+
+impl NamedAttribute {
+    pub fn new(name: impl Into<String>, value: Attribute) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+/// Structure of the uniqued attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttributeKind {
+    Unit,
+    Bool(bool),
+    Integer {
+        value: i128,
+        r#type: Type,
+    },
+    /// Floating point attribute, keeping the bits of its `f64` value to be uniqued.
+    Float {
+        bits: u64,
+        r#type: Type,
+    },
+    String(String),
+    Type(Type),
+    Array(Vec<Attribute>),
+    /// Dictionary sorted by the attribute names.
+    Dictionary(Vec<NamedAttribute>),
+    /// `@root::@nested` symbol reference.
+    SymbolRef {
+        root: String,
+        nested: Vec<String>,
+    },
+    /// Elements of the shaped `r#type`, kept as the element typed attributes,
+    /// a single element stands for the splat of it.
+    DenseElements {
+        r#type: Type,
+        elements: Vec<Attribute>,
+    },
+    /// `array<i32: 1, 2>` of the element typed attributes.
+    DenseArray {
+        element: Type,
+        elements: Vec<Attribute>,
+    },
+    AffineMap(AffineMap),
+    /// `strided<[strides], offset: offset>` memref layout, with the [DYNAMIC](crate::DYNAMIC) ones.
+    StridedLayout {
+        offset: i64,
+        strides: Vec<i64>,
+    },
+    UnknownLoc,
+    FileLineColLoc {
+        filename: String,
+        line: u32,
+        column: u32,
+    },
+    NameLoc {
+        name: String,
+        child: Option<Attribute>,
+    },
+    CallSiteLoc {
+        callee: Attribute,
+        caller: Attribute,
+    },
+    FusedLoc {
+        locations: Vec<Attribute>,
+        metadata: Option<Attribute>,
+    },
+    /// Dialect attribute without a dedicated representation, `data` is the text following the `#dialect.` prefix,
+    /// or the `<...>` body of the `#dialect<...>` ones.
+    Opaque {
+        dialect: String,
+        data: String,
+    },
+}
+
+impl Attribute {
+    pub fn kind(self, ctx: &Context) -> &AttributeKind {
+        ctx.attributes.get(self.0)
+    }
+
+    pub fn as_bool(self, ctx: &Context) -> Option<bool> {
+        match self.kind(ctx) {
+            AttributeKind::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(self, ctx: &Context) -> Option<i128> {
+        match self.kind(ctx) {
+            AttributeKind::Integer { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(self, ctx: &Context) -> Option<f64> {
+        match self.kind(ctx) {
+            AttributeKind::Float { bits, .. } => Some(f64::from_bits(*bits)),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(self, ctx: &Context) -> Option<&str> {
+        match self.kind(ctx) {
+            AttributeKind::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_type(self, ctx: &Context) -> Option<Type> {
+        match self.kind(ctx) {
+            AttributeKind::Type(r#type) => Some(*r#type),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(self, ctx: &Context) -> Option<&[Attribute]> {
+        match self.kind(ctx) {
+            AttributeKind::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_affine_map(self, ctx: &Context) -> Option<&AffineMap> {
+        match self.kind(ctx) {
+            AttributeKind::AffineMap(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Location attributes, the operations are annotated with.
+    pub fn is_location(self, ctx: &Context) -> bool {
+        matches!(
+            self.kind(ctx),
+            AttributeKind::UnknownLoc
+                | AttributeKind::FileLineColLoc { .. }
+                | AttributeKind::NameLoc { .. }
+                | AttributeKind::CallSiteLoc { .. }
+                | AttributeKind::FusedLoc { .. }
+        )
+    }
+
+    /// Type of the typed attributes, like `i32` of `42 : i32`.
+    pub fn r#type(self, ctx: &Context) -> Option<Type> {
+        match self.kind(ctx) {
+            AttributeKind::Integer { r#type, .. }
+            | AttributeKind::Float { r#type, .. }
+            | AttributeKind::DenseElements { r#type, .. } => Some(*r#type),
+            AttributeKind::Bool(_) => Some(ctx.i1),
+            _ => None,
+        }
+    }
+}
+
+impl Context {
+    /// Uniques the attribute, the dictionaries are sorted by the attribute names first.
+    pub fn get_attribute(&mut self, kind: AttributeKind) -> Attribute {
+        let kind = match kind {
+            AttributeKind::Dictionary(mut attributes) => {
+                attributes.sort_by(|a, b| a.name.cmp(&b.name));
+                AttributeKind::Dictionary(attributes)
+            }
+            kind => kind,
+        };
+        Attribute(self.attributes.intern(kind))
+    }
+
+    pub fn unit_attr(&mut self) -> Attribute {
+        self.get_attribute(AttributeKind::Unit)
+    }
+
+    pub fn bool_attr(&mut self, value: bool) -> Attribute {
+        self.get_attribute(AttributeKind::Bool(value))
+    }
+
+    pub fn integer_attr(&mut self, value: i128, r#type: Type) -> Attribute {
+        self.get_attribute(AttributeKind::Integer { value, r#type })
+    }
+
+    pub fn index_attr(&mut self, value: i128) -> Attribute {
+        let index = self.index_type();
+        self.integer_attr(value, index)
+    }
+
+    pub fn float_attr(&mut self, value: f64, r#type: Type) -> Attribute {
+        self.get_attribute(AttributeKind::Float {
+            bits: value.to_bits(),
+            r#type,
+        })
+    }
+
+    pub fn string_attr(&mut self, value: &str) -> Attribute {
+        self.get_attribute(AttributeKind::String(value.to_string()))
+    }
+
+    pub fn type_attr(&mut self, r#type: Type) -> Attribute {
+        self.get_attribute(AttributeKind::Type(r#type))
+    }
+
+    pub fn array_attr(&mut self, elements: &[Attribute]) -> Attribute {
+        self.get_attribute(AttributeKind::Array(elements.to_vec()))
+    }
+
+    pub fn dictionary_attr(&mut self, attributes: Vec<NamedAttribute>) -> Attribute {
+        self.get_attribute(AttributeKind::Dictionary(attributes))
+    }
+
+    /// Dense elements of the shaped type, the equal elements are uniqued into their splat.
+    pub fn dense_elements_attr(&mut self, r#type: Type, elements: &[Attribute]) -> Attribute {
+        let elements = match elements {
+            [first, rest @ ..] if rest.iter().all(|element| element == first) => vec![*first],
+            elements => elements.to_vec(),
+        };
+        self.get_attribute(AttributeKind::DenseElements { r#type, elements })
+    }
+
+    pub fn dense_array_attr(&mut self, element: Type, elements: &[Attribute]) -> Attribute {
+        self.get_attribute(AttributeKind::DenseArray {
+            element,
+            elements: elements.to_vec(),
+        })
+    }
+
+    /// `array<i32: ...>`, like the `operandSegmentSizes`.
+    pub fn dense_i32_array_attr(&mut self, values: &[i32]) -> Attribute {
+        let element = self.integer_type(32);
+        let elements: Vec<Attribute> = values
+            .iter()
+            .map(|value| self.integer_attr(i128::from(*value), element))
+            .collect();
+        self.dense_array_attr(element, &elements)
+    }
+
+    pub fn affine_map_attr(&mut self, map: AffineMap) -> Attribute {
+        self.get_attribute(AttributeKind::AffineMap(map))
+    }
+
+    pub fn strided_layout_attr(&mut self, offset: i64, strides: &[i64]) -> Attribute {
+        self.get_attribute(AttributeKind::StridedLayout {
+            offset,
+            strides: strides.to_vec(),
+        })
+    }
+
+    /// Flat `@symbol` reference.
+    pub fn symbol_ref_attr(&mut self, symbol: &str) -> Attribute {
+        self.get_attribute(AttributeKind::SymbolRef {
+            root: symbol.to_string(),
+            nested: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_unique_attributes() {
+        let mut ctx = Context::new();
+        let i64 = ctx.integer_type(64);
+        let i32 = ctx.integer_type(32);
+        let answer = ctx.integer_attr(42, i64);
+        assert_eq!(ctx.integer_attr(42, i64), answer);
+        assert_ne!(ctx.integer_attr(42, i32), answer);
+        assert_eq!(answer.as_integer(&ctx), Some(42));
+        assert_eq!(answer.r#type(&ctx), Some(i64));
+
+        let half = ctx.float_attr(0.5, i64);
+        assert_eq!(half.as_float(&ctx), Some(0.5));
+        let name = ctx.string_attr("main");
+        let array = ctx.array_attr(&[answer, name]);
+        assert_eq!(array.as_array(&ctx), Some(&[answer, name][..]));
+        assert_eq!(ctx.array_attr(&[answer, name]), array);
+    }
+
+    #[test]
+    fn should_sort_dictionaries() {
+        let mut ctx = Context::new();
+        let (unit, yes) = (ctx.unit_attr(), ctx.bool_attr(true));
+        let dictionary = ctx.dictionary_attr(vec![NamedAttribute::new("b", unit), NamedAttribute::new("a", yes)]);
+        assert_eq!(
+            ctx.dictionary_attr(vec![NamedAttribute::new("a", yes), NamedAttribute::new("b", unit)]),
+            dictionary
+        );
+        assert_eq!(
+            dictionary.kind(&ctx),
+            &AttributeKind::Dictionary(vec![NamedAttribute::new("a", yes), NamedAttribute::new("b", unit)])
+        );
+    }
+}
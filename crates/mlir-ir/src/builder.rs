@@ -0,0 +1,213 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Builder creating the operations and blocks at its insertion point, like the upstream `OpBuilder`.
+//!
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{Block, Context, Operation, OperationState, Region, Type};
+
+/// Where the builder inserts, before the `before` operation or at the end of the `block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertionPoint {
+    pub block: Block,
+    pub before: Option<Operation>,
+}
+
+impl InsertionPoint {
+    pub fn at_end(block: Block) -> Self {
+        Self { block, before: None }
+    }
+
+    pub fn at_start(ctx: &Context, block: Block) -> Self {
+        Self {
+            block,
+            before: block.operations(ctx).first().copied(),
+        }
+    }
+
+    pub fn before(ctx: &Context, op: Operation) -> Self {
+        Self {
+            block: op
+                .parent_block(ctx)
+                .expect("insertion point before a detached operation"),
+            before: Some(op),
+        }
+    }
+
+    pub fn after(ctx: &Context, op: Operation) -> Self {
+        Self {
+            block: op
+                .parent_block(ctx)
+                .expect("insertion point after a detached operation"),
+            before: op.next(ctx),
+        }
+    }
+}
+
+/// Builder of the operations, dereferencing to its [Context] for the types and attributes.
+#[derive(Debug)]
+pub struct Builder<'c> {
+    ctx: &'c mut Context,
+    insertion_point: Option<InsertionPoint>,
+}
+
+impl<'c> Builder<'c> {
+    /// Builder without an insertion point, creating the detached operations.
+    pub fn new(ctx: &'c mut Context) -> Self {
+        Self {
+            ctx,
+            insertion_point: None,
+        }
+    }
+
+    pub fn at_end(ctx: &'c mut Context, block: Block) -> Self {
+        Self {
+            ctx,
+            insertion_point: Some(InsertionPoint::at_end(block)),
+        }
+    }
+
+    pub fn insertion_point(&self) -> Option<InsertionPoint> {
+        self.insertion_point
+    }
+
+    pub fn set_insertion_point(&mut self, insertion_point: InsertionPoint) {
+        self.insertion_point = Some(insertion_point);
+    }
+
+    pub fn clear_insertion_point(&mut self) {
+        self.insertion_point = None;
+    }
+
+    pub fn set_insertion_point_to_end(&mut self, block: Block) {
+        self.set_insertion_point(InsertionPoint::at_end(block));
+    }
+
+    pub fn set_insertion_point_to_start(&mut self, block: Block) {
+        self.set_insertion_point(InsertionPoint::at_start(self.ctx, block));
+    }
+
+    pub fn set_insertion_point_before(&mut self, op: Operation) {
+        self.set_insertion_point(InsertionPoint::before(self.ctx, op));
+    }
+
+    pub fn set_insertion_point_after(&mut self, op: Operation) {
+        self.set_insertion_point(InsertionPoint::after(self.ctx, op));
+    }
+
+    /// Inserts the detached operation at the insertion point, if any.
+    pub fn insert(&mut self, op: Operation) -> Operation {
+        if let Some(InsertionPoint { block, before }) = self.insertion_point {
+            self.ctx
+                .insert_operation(block, before, op)
+                .expect("inserting an operation already in a block");
+        }
+        op
+    }
+
+    /// Creates the operation and inserts it at the insertion point.
+    pub fn create(&mut self, state: OperationState) -> Operation {
+        let op = self.ctx.create_operation(state);
+        self.insert(op)
+    }
+
+    /// Appends a new block to the region and moves the insertion point to its end.
+    pub fn create_block(&mut self, region: Region, argument_types: &[Type]) -> Block {
+        let block = self.ctx.create_block(argument_types);
+        self.ctx.append_block(region, block);
+        self.set_insertion_point_to_end(block);
+        block
+    }
+
+    /// Inserts a new block before the `anchor` one and moves the insertion point to its end.
+    pub fn create_block_before(&mut self, anchor: Block, argument_types: &[Type]) -> Block {
+        let block = self.ctx.create_block(argument_types);
+        self.ctx.insert_block_before(anchor, block);
+        self.set_insertion_point_to_end(block);
+        block
+    }
+}
+
+impl Deref for Builder<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.ctx
+    }
+}
+
+impl DerefMut for Builder<'_> {
+    fn deref_mut(&mut self) -> &mut Context {
+        self.ctx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(builder: &mut Builder<'_>, name: &str) -> Operation {
+        builder.create(OperationState::new(name))
+    }
+
+    #[test]
+    fn should_insert_at_insertion_point() {
+        let mut ctx = Context::new();
+        let module = ctx.create_module();
+        let region = module.region(&ctx, 0);
+        let body = region.entry_block(&ctx).unwrap();
+        let mut builder = Builder::at_end(&mut ctx, body);
+        let first = op(&mut builder, "test.first");
+        let last = op(&mut builder, "test.last");
+        builder.set_insertion_point_after(first);
+        let second = op(&mut builder, "test.second");
+        let third = op(&mut builder, "test.third");
+        builder.set_insertion_point_to_start(body);
+        let zeroth = op(&mut builder, "test.zeroth");
+        builder.set_insertion_point_before(last);
+        let fourth = op(&mut builder, "test.fourth");
+        assert_eq!(body.operations(&builder), &[zeroth, first, second, third, fourth, last]);
+
+        builder.clear_insertion_point();
+        let detached = op(&mut builder, "test.detached");
+        assert_eq!(detached.parent_block(&builder), None);
+        builder.set_insertion_point_to_end(body);
+        builder.insert(detached);
+        assert_eq!(body.terminator(&builder), Some(detached));
+    }
+
+    #[test]
+    fn should_create_blocks() {
+        let mut ctx = Context::new();
+        let mut state = OperationState::new("func.func");
+        state.add_regions(1);
+        let func = ctx.create_operation(state);
+        let region = func.region(&ctx, 0);
+        let mut builder = Builder::new(&mut ctx);
+        let i64 = builder.integer_type(64);
+        let exit = builder.create_block(region, &[i64]);
+        let ret = op(&mut builder, "func.return");
+        let entry = builder.create_block_before(exit, &[]);
+        let br = op(&mut builder, "cf.br");
+        assert_eq!(region.blocks(&builder), &[entry, exit]);
+        assert_eq!(entry.operations(&builder), &[br]);
+        assert_eq!(exit.operations(&builder), &[ret]);
+        assert_eq!(exit.argument_types(&builder), [i64]);
+    }
+}
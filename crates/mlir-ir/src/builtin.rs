@@ -0,0 +1,107 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! The always registered `builtin` dialect of the `builtin.module` and the `builtin.unrealized_conversion_cast`.
+//!
+
+use crate::{
+    Context, Dialect, OpAsmRegistry, OpTrait, Operation, OperationState, ParseError, Parser, Printer, RegionKind,
+};
+
+/// The `builtin` dialect, like the upstream `BuiltinDialect`.
+pub struct BuiltinDialect;
+
+impl Dialect for BuiltinDialect {
+    fn namespace(&self) -> &str {
+        "builtin"
+    }
+
+    fn is_pure(&self, ctx: &Context, op: Operation) -> bool {
+        op.name(ctx) == "builtin.unrealized_conversion_cast"
+    }
+
+    fn has_trait(&self, ctx: &Context, op: Operation, r#trait: OpTrait) -> bool {
+        match op.name(ctx) {
+            "builtin.module" => matches!(
+                r#trait,
+                OpTrait::NoTerminator | OpTrait::IsolatedFromAbove | OpTrait::SymbolTable
+            ),
+            _ => false,
+        }
+    }
+
+    fn region_kind(&self, ctx: &Context, op: Operation, _index: usize) -> RegionKind {
+        match op.name(ctx) {
+            "builtin.module" => RegionKind::Graph,
+            _ => RegionKind::SsaCfg,
+        }
+    }
+
+    fn verify(&self, ctx: &Context, op: Operation) -> Result<(), String> {
+        match op.name(ctx) {
+            "builtin.module" => {
+                if !op.operands(ctx).is_empty() || !op.results(ctx).is_empty() {
+                    return Err("requires zero operands and results".to_string());
+                }
+                let [body] = op.regions(ctx) else {
+                    return Err("requires one region".to_string());
+                };
+                match body.blocks(ctx) {
+                    [block] if block.arguments(ctx).is_empty() => Ok(()),
+                    [_] => Err("expects the body to have no arguments".to_string()),
+                    _ => Err("expects the body to have one block".to_string()),
+                }
+            }
+            "builtin.unrealized_conversion_cast" if !op.regions(ctx).is_empty() => {
+                Err("requires zero regions".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+        registry.register("builtin.module", print_module, parse_module);
+        registry.set_default_dialect("builtin.module", "builtin");
+    }
+}
+
+/// `module @name attributes {...} {...}`, like the upstream `ModuleOp` assembly format.
+fn print_module(op: Operation, p: &mut Printer<'_>) {
+    let ctx = p.ctx();
+    if let Some(name) = op.attribute(ctx, "sym_name") {
+        p.write(" ");
+        p.print_symbol_name(name);
+    }
+    p.print_optional_attr_dict_with_keyword(&op.all_attributes(ctx), &["sym_name"]);
+    p.write(" ");
+    if let [body] = op.regions(ctx) {
+        p.print_region(*body, false, true);
+    }
+}
+
+fn parse_module(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+    if let Some(name) = parser.parse_optional_symbol_name()? {
+        state.add_attribute("sym_name", name);
+    }
+    state.attributes.extend(parser.parse_optional_attr_dict_with_keyword()?);
+    let mut body = parser.parse_region()?;
+    if body.is_empty() {
+        body.push(parser.ctx().create_block(&[]));
+    }
+    state.add_region(body);
+    Ok(())
+}
@@ -0,0 +1,274 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! MLIR bytecode, the compact and versioned `.mlirbc` counterpart of the textual form.
+//! The layout follows the upstream `BytecodeFormat.md`: the `MLïR` magic number, the version, the producer string,
+//! and the string, dialect, attribute and type, IR and properties sections.
+//! The builtin attributes and types get their upstream encodings, the ones of the other dialects are stored
+//! in their generic textual form. The regions of the operations not using the values defined above them are
+//! encoded within their own sections, the [BytecodeReader] may leave them out until they are materialized.
+//!
+
+mod builtin;
+mod encoding;
+mod reader;
+mod writer;
+
+pub use reader::{BytecodeReader, BytecodeReaderConfig};
+pub use writer::{BytecodeWriter, BytecodeWriterConfig};
+
+/// `MLïR` magic number the bytecode starts with.
+pub const MAGIC: [u8; 4] = *b"ML\xEFR";
+
+/// Version the writer emits by default, and the newest one the reader accepts, the upstream `kVersion`.
+pub const VERSION: u64 = 6;
+
+/// Versions introducing the bytecode features.
+pub(crate) mod version {
+    pub const DIALECT_VERSIONING: u64 = 1;
+    pub const LAZY_LOADING: u64 = 2;
+    pub const USE_LIST_ORDERING: u64 = 3;
+    pub const ELIDE_UNKNOWN_BLOCK_ARG_LOCATION: u64 = 4;
+    pub const NATIVE_PROPERTIES_ENCODING: u64 = 5;
+}
+
+/// Identifiers of the sections.
+pub(crate) mod section {
+    pub const STRING: u8 = 0;
+    pub const DIALECT: u8 = 1;
+    pub const ATTR_TYPE: u8 = 2;
+    pub const ATTR_TYPE_OFFSET: u8 = 3;
+    pub const IR: u8 = 4;
+    pub const DIALECT_VERSIONS: u8 = 7;
+    pub const PROPERTIES: u8 = 8;
+    /// Number of the section identifiers, including the resource ones which are skipped.
+    pub const COUNT: u8 = 9;
+}
+
+/// Flags of the components present in the operation encoding.
+pub(crate) mod op_mask {
+    pub const HAS_ATTRS: u8 = 0x01;
+    pub const HAS_RESULTS: u8 = 0x02;
+    pub const HAS_OPERANDS: u8 = 0x04;
+    pub const HAS_SUCCESSORS: u8 = 0x08;
+    pub const HAS_INLINE_REGIONS: u8 = 0x10;
+    pub const HAS_USE_LIST_ORDERS: u8 = 0x20;
+    pub const HAS_PROPERTIES: u8 = 0x40;
+}
+
+/// Whether the data starts with the bytecode magic number.
+pub fn is_bytecode(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encoding::Emitter;
+    use super::*;
+    use crate::{parse, print, read_bytecode, write_bytecode, BytecodeError, Context, Operation, PrinterOptions};
+
+    const DEBUG_INFO: PrinterOptions = PrinterOptions {
+        debug_info: true,
+        local_scope: false,
+        generic_op_form: false,
+    };
+
+    const SOURCE: &str = r#"#map = affine_map<(d0)[s0] -> (d0 + s0)>
+module {
+  "func.func"() ({
+  ^bb0(%arg0: i32, %arg1: memref<?xf32, #map>):
+    %2 = "arith.constant"() {value = -7 : i32} : () -> i32 loc(#loc)
+    %3 = "arith.addi"(%arg0, %2) : (i32, i32) -> i32 loc(#loc)
+    "cf.cond_br"(%3, %3)[^bb1, ^bb2] : (i32, i32) -> () loc(#loc)
+  ^bb1:  // pred: ^bb0
+    "func.return"(%2) : (i32) -> () loc(#loc)
+  ^bb2(%4: i32):  // pred: ^bb0
+    "func.return"(%4) : (i32) -> () loc(#loc)
+  }) {function_type = (i32, memref<?xf32, #map>) -> i32, sym_name = "f"} : () -> () loc(#loc1)
+  %0 = "test.outer"() : () -> index loc(#loc)
+  "test.graph"() ({
+    %2 = "test.use"(%3, %0) : (i64, index) -> i64 loc(#loc)
+    %3 = "test.def"(%2) : (i64) -> i64 loc(#loc)
+  }) : () -> () loc(#loc)
+  "test.attributes"() {array = [1, 2.500000e+00, "s", unit, @a::@b, @c], bool = true, dense = dense<[[1, 2], [3, 4]]> : tensor<2x2xi16>, dense_array = array<i64: 1, -2, 3>, flag = dense<[true, false, true]> : vector<3xi1>, float = 5.000000e-01 : f32, linkage = #llvm.linkage<internal>, splat = dense<1.500000e+00> : tensor<4xf64>, strided = strided<[?, 1], offset: ?>, type = !llvm.ptr, wide = 170141183460469231731687303715884105727 : i128} : () -> () loc(#loc2)
+  %1:6 = "test.types"() : () -> (tuple<si8, ui16, complex<f32>, none>, tensor<?x4xf32, "encoding">, vector<[4]x2xf32>, memref<*xf32, 1>, memref<2xtf32, 3 : i32>, tensor<*xbf16>) loc(#loc3)
+} loc(#loc)
+#loc = loc(unknown)
+#loc1 = loc("lib.rs":1:2)
+#loc2 = loc("value"("lib.rs":3:4))
+#loc3 = loc(fused<"inlined">["lib.rs":5:6, callsite("g" at "lib.rs":7:8)])
+"#;
+
+    fn read<'a>(ctx: &mut Context, data: &'a [u8], config: BytecodeReaderConfig) -> (BytecodeReader<'a>, Operation) {
+        let mut reader = BytecodeReader::new(data, config).unwrap();
+        let op = reader.read(ctx).unwrap();
+        (reader, op)
+    }
+
+    #[test]
+    fn should_round_trip_through_text_and_bytecode() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        assert_eq!(print(&ctx, module, DEBUG_INFO), SOURCE);
+        let bytecode = write_bytecode(&mut ctx, module).unwrap();
+        assert!(is_bytecode(&bytecode));
+
+        let mut ctx = Context::new();
+        let module = read_bytecode(&mut ctx, &bytecode).unwrap();
+        assert_eq!(print(&ctx, module, DEBUG_INFO), SOURCE);
+        assert_eq!(write_bytecode(&mut ctx, module).unwrap(), bytecode);
+    }
+
+    #[test]
+    fn should_negotiate_versions() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        for version in 0..=VERSION {
+            let config = BytecodeWriterConfig {
+                version,
+                producer: "test".to_string(),
+            };
+            let bytecode = BytecodeWriter::new(&mut ctx, config).write(module).unwrap();
+            let mut read_ctx = Context::new();
+            let (reader, read) = read(&mut read_ctx, &bytecode, BytecodeReaderConfig::default());
+            assert_eq!((reader.version(), reader.producer()), (version, "test"));
+            assert_eq!(print(&read_ctx, read, DEBUG_INFO), SOURCE, "version {version}");
+        }
+
+        let newer = BytecodeWriterConfig {
+            version: VERSION + 1,
+            ..BytecodeWriterConfig::default()
+        };
+        let unsupported = BytecodeError::UnsupportedVersion {
+            version: VERSION + 1,
+            current: VERSION,
+        };
+        assert_eq!(
+            BytecodeWriter::new(&mut ctx, newer).write(module),
+            Err(unsupported.clone())
+        );
+        let mut bytecode = write_bytecode(&mut ctx, module).unwrap();
+        bytecode[MAGIC.len()] = ((VERSION + 1) << 1 | 1) as u8;
+        assert_eq!(read_bytecode(&mut ctx, &bytecode).err(), Some(unsupported));
+        assert_eq!(
+            read_bytecode(&mut ctx, b"MLIR").err(),
+            Some(BytecodeError::Malformed {
+                offset: 0,
+                message: "input buffer is not an MLIR bytecode file".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn should_round_trip_the_properties() {
+        let source = r#"module {
+  "func.func"() <{function_type = () -> i32, sym_name = "f"}> ({
+    %0 = "arith.constant"() <{value = 1 : i32}> : () -> i32
+    %1 = "arith.constant"() <{value = 1 : i32}> {tag} : () -> i32
+    %2 = "arith.addi"(%0, %1) <{overflowFlags = #arith.overflow<nsw>}> : (i32, i32) -> i32
+    "func.return"(%2) : (i32) -> ()
+  }) : () -> ()
+}
+"#;
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        let bytecode = write_bytecode(&mut ctx, module).unwrap();
+        // The equal properties of the constants share their entry.
+        let mut section = Emitter::default();
+        section.emit_byte(section::PROPERTIES);
+        section.emit_var_int(7);
+        section.emit_var_int(3);
+        assert_eq!(bytecode[bytecode.len() - 9..bytecode.len() - 6], section.into_bytes());
+
+        let mut read_ctx = Context::new();
+        let read = read_bytecode(&mut read_ctx, &bytecode).unwrap();
+        assert_eq!(print(&read_ctx, read, PrinterOptions::default()), source);
+        let function = read.region(&read_ctx, 0).blocks(&read_ctx)[0].operations(&read_ctx)[0];
+        assert!(function.attributes(&read_ctx).is_empty());
+        let name = function.attribute(&read_ctx, "sym_name").unwrap();
+        assert_eq!(name, read_ctx.string_attr("f"));
+        assert_eq!(write_bytecode(&mut read_ctx, read).unwrap(), bytecode);
+
+        // The older versions merge the properties into the attributes.
+        let config = BytecodeWriterConfig {
+            version: version::NATIVE_PROPERTIES_ENCODING - 1,
+            ..BytecodeWriterConfig::default()
+        };
+        let bytecode = BytecodeWriter::new(&mut ctx, config).write(module).unwrap();
+        let mut read_ctx = Context::new();
+        let read = read_bytecode(&mut read_ctx, &bytecode).unwrap();
+        assert_eq!(
+            print(&read_ctx, read, PrinterOptions::default()),
+            r#"module {
+  "func.func"() ({
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.constant"() {tag, value = 1 : i32} : () -> i32
+    %2 = "arith.addi"(%0, %1) {overflowFlags = #arith.overflow<nsw>} : (i32, i32) -> i32
+    "func.return"(%2) : (i32) -> ()
+  }) {function_type = () -> i32, sym_name = "f"} : () -> ()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_load_isolated_regions_lazily() {
+        let source = r#"module {
+  "func.func"() ({
+    "test.nested"() ({
+      "test.inner"() : () -> ()
+    }) : () -> ()
+  }) {sym_name = "a"} : () -> ()
+  "func.func"() ({
+    "func.return"() : () -> ()
+  }) {sym_name = "b"} : () -> ()
+}
+"#;
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        let bytecode = write_bytecode(&mut ctx, module).unwrap();
+
+        let mut ctx = Context::new();
+        let lazy = BytecodeReaderConfig { lazy_loading: true };
+        let (mut reader, module) = read(&mut ctx, &bytecode, lazy);
+        assert_eq!(reader.materializable(), [module]);
+        reader.materialize(&mut ctx, module).unwrap();
+        let functions = reader.materializable();
+        assert_eq!(functions.len(), 2);
+        assert!(functions[0].region(&ctx, 0).is_empty(&ctx));
+
+        reader.materialize(&mut ctx, functions[0]).unwrap();
+        let nested = reader.materializable();
+        assert_eq!(nested.len(), 2);
+        assert!(!reader.is_materializable(functions[0]));
+        reader
+            .finalize(&mut ctx, |ctx, op| op.name(ctx) == "test.nested")
+            .unwrap();
+        assert!(reader.materializable().is_empty());
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "func.func"() ({
+    "test.nested"() ({
+      "test.inner"() : () -> ()
+    }) : () -> ()
+  }) {sym_name = "a"} : () -> ()
+}
+"#
+        );
+    }
+}
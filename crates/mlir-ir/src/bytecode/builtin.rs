@@ -0,0 +1,792 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Encodings of the builtin attributes and types, following the upstream `BuiltinDialectBytecode.td`.
+//! The ones without an encoding here, like the affine maps, are stored in their textual form instead.
+//!
+
+use std::ops::{Deref, DerefMut};
+
+use crate::bytecode::encoding::{Emitter, EncodingReader};
+use crate::{
+    AffineMap, Attribute, AttributeKind, BytecodeError, Context, FloatKind, NamedAttribute, Signedness, Type, TypeKind,
+};
+
+mod attribute_code {
+    pub const ARRAY: u64 = 0;
+    pub const DICTIONARY: u64 = 1;
+    pub const STRING: u64 = 2;
+    pub const STRING_WITH_TYPE: u64 = 3;
+    pub const FLAT_SYMBOL_REF: u64 = 4;
+    pub const SYMBOL_REF: u64 = 5;
+    pub const TYPE: u64 = 6;
+    pub const UNIT: u64 = 7;
+    pub const INTEGER: u64 = 8;
+    pub const FLOAT: u64 = 9;
+    pub const CALL_SITE_LOC: u64 = 10;
+    pub const FILE_LINE_COL_LOC: u64 = 11;
+    pub const FUSED_LOC: u64 = 12;
+    pub const FUSED_LOC_WITH_METADATA: u64 = 13;
+    pub const NAME_LOC: u64 = 14;
+    pub const UNKNOWN_LOC: u64 = 15;
+    pub const DENSE_RESOURCE_ELEMENTS: u64 = 16;
+    pub const DENSE_ARRAY: u64 = 17;
+    pub const DENSE_INT_OR_FP_ELEMENTS: u64 = 18;
+}
+
+mod type_code {
+    pub const INTEGER: u64 = 0;
+    pub const INDEX: u64 = 1;
+    pub const FUNCTION: u64 = 2;
+    pub const BF16: u64 = 3;
+    pub const F16: u64 = 4;
+    pub const F32: u64 = 5;
+    pub const F64: u64 = 6;
+    pub const F80: u64 = 7;
+    pub const F128: u64 = 8;
+    pub const COMPLEX: u64 = 9;
+    pub const MEMREF: u64 = 10;
+    pub const MEMREF_WITH_MEMORY_SPACE: u64 = 11;
+    pub const NONE: u64 = 12;
+    pub const RANKED_TENSOR: u64 = 13;
+    pub const RANKED_TENSOR_WITH_ENCODING: u64 = 14;
+    pub const TUPLE: u64 = 15;
+    pub const UNRANKED_MEMREF: u64 = 16;
+    pub const UNRANKED_MEMREF_WITH_MEMORY_SPACE: u64 = 17;
+    pub const UNRANKED_TENSOR: u64 = 18;
+    pub const VECTOR: u64 = 19;
+}
+
+/// Indices of the attributes, types and strings the encodings refer to.
+pub(crate) trait Numbering {
+    fn attribute(&mut self, ctx: &mut Context, attribute: Attribute) -> u64;
+
+    fn r#type(&mut self, ctx: &mut Context, r#type: Type) -> u64;
+
+    fn string(&mut self, string: &str) -> u64;
+}
+
+/// Attributes, types and strings the encodings refer to, by their indices.
+pub(crate) trait Resolver {
+    fn attribute(&mut self, ctx: &mut Context, index: u64) -> Result<Attribute, BytecodeError>;
+
+    fn r#type(&mut self, ctx: &mut Context, index: u64) -> Result<Type, BytecodeError>;
+
+    fn string(&self, index: u64) -> Result<String, BytecodeError>;
+}
+
+/// Emitter of the encodings, with the numbering of the entries they refer to.
+pub(crate) struct Out<'n> {
+    emitter: Emitter,
+    numbering: &'n mut dyn Numbering,
+}
+
+impl<'n> Out<'n> {
+    pub fn new(numbering: &'n mut dyn Numbering) -> Self {
+        Self {
+            emitter: Emitter::default(),
+            numbering,
+        }
+    }
+
+    pub fn into_emitter(self) -> Emitter {
+        self.emitter
+    }
+
+    pub fn emit_attribute(&mut self, ctx: &mut Context, attribute: Attribute) {
+        let index = self.numbering.attribute(ctx, attribute);
+        self.emitter.emit_var_int(index);
+    }
+
+    pub fn emit_type(&mut self, ctx: &mut Context, r#type: Type) {
+        let index = self.numbering.r#type(ctx, r#type);
+        self.emitter.emit_var_int(index);
+    }
+
+    pub fn emit_string(&mut self, string: &str) {
+        let index = self.numbering.string(string);
+        self.emitter.emit_var_int(index);
+    }
+
+    fn emit_attributes(&mut self, ctx: &mut Context, attributes: &[Attribute]) {
+        self.emitter.emit_var_int(attributes.len() as u64);
+        for attribute in attributes {
+            self.emit_attribute(ctx, *attribute);
+        }
+    }
+
+    fn emit_types(&mut self, ctx: &mut Context, types: &[Type]) {
+        self.emitter.emit_var_int(types.len() as u64);
+        for r#type in types {
+            self.emit_type(ctx, *r#type);
+        }
+    }
+
+    fn emit_shape(&mut self, shape: &[i64]) {
+        self.emitter.emit_var_int(shape.len() as u64);
+        for dim in shape {
+            self.emitter.emit_signed_var_int(*dim);
+        }
+    }
+}
+
+impl Deref for Out<'_> {
+    type Target = Emitter;
+
+    fn deref(&self) -> &Emitter {
+        &self.emitter
+    }
+}
+
+impl DerefMut for Out<'_> {
+    fn deref_mut(&mut self) -> &mut Emitter {
+        &mut self.emitter
+    }
+}
+
+/// Encodes the builtin attribute, `false` if it has no encoding and is to be stored in its textual form.
+pub(crate) fn write_attribute(ctx: &mut Context, attribute: Attribute, out: &mut Out) -> bool {
+    use attribute_code::*;
+    match attribute.kind(ctx).clone() {
+        AttributeKind::Unit => out.emit_var_int(UNIT),
+        AttributeKind::Bool(value) => {
+            out.emit_var_int(INTEGER);
+            let i1 = ctx.integer_type(1);
+            out.emit_type(ctx, i1);
+            out.emit_byte(value as u8);
+        }
+        AttributeKind::Integer { value, r#type } => {
+            let Some(width) = integer_width(ctx, r#type) else {
+                return false;
+            };
+            out.emit_var_int(INTEGER);
+            out.emit_type(ctx, r#type);
+            write_integer(out, width, value);
+        }
+        AttributeKind::Float { bits, r#type } => {
+            let Some(bits) = float_bits(ctx, r#type, f64::from_bits(bits)) else {
+                return false;
+            };
+            out.emit_var_int(FLOAT);
+            out.emit_type(ctx, r#type);
+            out.emit_signed_var_int(bits);
+        }
+        AttributeKind::String(value) => {
+            out.emit_var_int(STRING);
+            out.emit_string(&value);
+        }
+        AttributeKind::Type(r#type) => {
+            out.emit_var_int(TYPE);
+            out.emit_type(ctx, r#type);
+        }
+        AttributeKind::Array(elements) => {
+            out.emit_var_int(ARRAY);
+            out.emit_attributes(ctx, &elements);
+        }
+        AttributeKind::Dictionary(attributes) => {
+            out.emit_var_int(DICTIONARY);
+            out.emit_var_int(attributes.len() as u64);
+            for NamedAttribute { name, value } in attributes {
+                let name = ctx.string_attr(&name);
+                out.emit_attribute(ctx, name);
+                out.emit_attribute(ctx, value);
+            }
+        }
+        AttributeKind::SymbolRef { root, nested } => {
+            let root = ctx.string_attr(&root);
+            if nested.is_empty() {
+                out.emit_var_int(FLAT_SYMBOL_REF);
+                out.emit_attribute(ctx, root);
+            } else {
+                out.emit_var_int(SYMBOL_REF);
+                out.emit_attribute(ctx, root);
+                let nested: Vec<Attribute> = nested.iter().map(|symbol| ctx.symbol_ref_attr(symbol)).collect();
+                out.emit_attributes(ctx, &nested);
+            }
+        }
+        AttributeKind::DenseArray { element, elements } => {
+            let Some(data) = raw_elements(ctx, element, &elements, false) else {
+                return false;
+            };
+            out.emit_var_int(DENSE_ARRAY);
+            out.emit_type(ctx, element);
+            out.emit_var_int(elements.len() as u64);
+            out.emit_blob(&data);
+        }
+        AttributeKind::DenseElements { r#type, elements } => {
+            let element = r#type.element_type(ctx).unwrap_or(r#type);
+            let Some(data) = raw_elements(ctx, element, &elements, true) else {
+                return false;
+            };
+            out.emit_var_int(DENSE_INT_OR_FP_ELEMENTS);
+            out.emit_type(ctx, r#type);
+            out.emit_blob(&data);
+        }
+        AttributeKind::UnknownLoc => out.emit_var_int(UNKNOWN_LOC),
+        AttributeKind::FileLineColLoc { filename, line, column } => {
+            out.emit_var_int(FILE_LINE_COL_LOC);
+            let filename = ctx.string_attr(&filename);
+            out.emit_attribute(ctx, filename);
+            out.emit_var_int(line as u64);
+            out.emit_var_int(column as u64);
+        }
+        AttributeKind::NameLoc { name, child } => {
+            out.emit_var_int(NAME_LOC);
+            let name = ctx.string_attr(&name);
+            out.emit_attribute(ctx, name);
+            let child = child.unwrap_or_else(|| ctx.unknown_loc());
+            out.emit_attribute(ctx, child);
+        }
+        AttributeKind::CallSiteLoc { callee, caller } => {
+            out.emit_var_int(CALL_SITE_LOC);
+            out.emit_attribute(ctx, callee);
+            out.emit_attribute(ctx, caller);
+        }
+        AttributeKind::FusedLoc { locations, metadata } => {
+            out.emit_var_int(match metadata {
+                Some(_) => FUSED_LOC_WITH_METADATA,
+                None => FUSED_LOC,
+            });
+            out.emit_attributes(ctx, &locations);
+            if let Some(metadata) = metadata {
+                out.emit_attribute(ctx, metadata);
+            }
+        }
+        AttributeKind::AffineMap(_) | AttributeKind::StridedLayout { .. } | AttributeKind::Opaque { .. } => {
+            return false
+        }
+    }
+    true
+}
+
+/// Encodes the builtin type, `false` if it has no encoding and is to be stored in its textual form.
+pub(crate) fn write_type(ctx: &mut Context, r#type: Type, out: &mut Out) -> bool {
+    use type_code::*;
+    match r#type.kind(ctx).clone() {
+        TypeKind::Integer { width, signedness } => {
+            out.emit_var_int(INTEGER);
+            let signedness = match signedness {
+                Signedness::Signless => 0,
+                Signedness::Signed => 1,
+                Signedness::Unsigned => 2,
+            };
+            out.emit_var_int(((width as u64) << 2) | signedness);
+        }
+        TypeKind::Index => out.emit_var_int(INDEX),
+        TypeKind::Float(kind) => out.emit_var_int(match kind {
+            FloatKind::BF16 => BF16,
+            FloatKind::F16 => F16,
+            FloatKind::F32 => F32,
+            FloatKind::F64 => F64,
+            FloatKind::F80 => F80,
+            FloatKind::F128 => F128,
+            FloatKind::TF32 => return false,
+        }),
+        TypeKind::None => out.emit_var_int(NONE),
+        TypeKind::Complex(element) => {
+            out.emit_var_int(COMPLEX);
+            out.emit_type(ctx, element);
+        }
+        TypeKind::Function { inputs, results } => {
+            out.emit_var_int(FUNCTION);
+            out.emit_types(ctx, &inputs);
+            out.emit_types(ctx, &results);
+        }
+        TypeKind::Tuple(types) => {
+            out.emit_var_int(TUPLE);
+            out.emit_types(ctx, &types);
+        }
+        TypeKind::Vector {
+            shape,
+            scalable,
+            element,
+        } => {
+            if scalable.contains(&true) {
+                return false;
+            }
+            out.emit_var_int(VECTOR);
+            out.emit_shape(&shape);
+            out.emit_type(ctx, element);
+        }
+        TypeKind::RankedTensor {
+            shape,
+            element,
+            encoding,
+        } => {
+            match encoding {
+                Some(encoding) => {
+                    out.emit_var_int(RANKED_TENSOR_WITH_ENCODING);
+                    out.emit_attribute(ctx, encoding);
+                }
+                None => out.emit_var_int(RANKED_TENSOR),
+            }
+            out.emit_shape(&shape);
+            out.emit_type(ctx, element);
+        }
+        TypeKind::UnrankedTensor { element } => {
+            out.emit_var_int(UNRANKED_TENSOR);
+            out.emit_type(ctx, element);
+        }
+        TypeKind::MemRef {
+            shape,
+            element,
+            layout,
+            memory_space,
+        } => {
+            match memory_space {
+                Some(memory_space) => {
+                    out.emit_var_int(MEMREF_WITH_MEMORY_SPACE);
+                    out.emit_attribute(ctx, memory_space);
+                }
+                None => out.emit_var_int(MEMREF),
+            }
+            out.emit_shape(&shape);
+            out.emit_type(ctx, element);
+            let layout = layout.unwrap_or_else(|| ctx.affine_map_attr(AffineMap::identity(shape.len() as u32)));
+            out.emit_attribute(ctx, layout);
+        }
+        TypeKind::UnrankedMemRef { element, memory_space } => {
+            match memory_space {
+                Some(memory_space) => {
+                    out.emit_var_int(UNRANKED_MEMREF_WITH_MEMORY_SPACE);
+                    out.emit_attribute(ctx, memory_space);
+                }
+                None => out.emit_var_int(UNRANKED_MEMREF),
+            }
+            out.emit_type(ctx, element);
+        }
+        TypeKind::Opaque { .. } => return false,
+    }
+    true
+}
+
+/// Decodes the builtin attribute of the `reader` contents.
+pub(crate) fn read_attribute(
+    ctx: &mut Context,
+    reader: &mut EncodingReader,
+    resolver: &mut dyn Resolver,
+) -> Result<Attribute, BytecodeError> {
+    use attribute_code::*;
+    let code = reader.parse_var_int()?;
+    let attribute = match code {
+        ARRAY => {
+            let elements = read_attributes(ctx, reader, resolver)?;
+            ctx.array_attr(&elements)
+        }
+        DICTIONARY => {
+            let mut attributes = vec![];
+            for _ in 0..reader.parse_var_int()? {
+                let name = read_string_attribute(ctx, reader, resolver)?;
+                let value = resolver.attribute(ctx, reader.parse_var_int()?)?;
+                attributes.push(NamedAttribute::new(name, value));
+            }
+            ctx.dictionary_attr(attributes)
+        }
+        STRING => {
+            let value = resolver.string(reader.parse_var_int()?)?;
+            ctx.string_attr(&value)
+        }
+        FLAT_SYMBOL_REF => {
+            let root = read_string_attribute(ctx, reader, resolver)?;
+            ctx.symbol_ref_attr(&root)
+        }
+        SYMBOL_REF => {
+            let root = read_string_attribute(ctx, reader, resolver)?;
+            let mut nested = vec![];
+            for reference in read_attributes(ctx, reader, resolver)? {
+                match reference.kind(ctx) {
+                    AttributeKind::SymbolRef { root, nested: flat } if flat.is_empty() => nested.push(root.clone()),
+                    _ => return Err(reader.error("expected a flat symbol reference")),
+                }
+            }
+            ctx.get_attribute(AttributeKind::SymbolRef { root, nested })
+        }
+        TYPE => {
+            let r#type = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            ctx.type_attr(r#type)
+        }
+        UNIT => ctx.unit_attr(),
+        INTEGER => {
+            let r#type = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            let Some(width) = integer_width(ctx, r#type) else {
+                return Err(reader.error("expected an integer or index type of the integer attribute"));
+            };
+            let value = read_integer(reader, width, is_unsigned(ctx, r#type))?;
+            match r#type.is_signless_integer(ctx, 1) {
+                true => ctx.bool_attr(value != 0),
+                false => ctx.integer_attr(value, r#type),
+            }
+        }
+        FLOAT => {
+            let r#type = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            let bits = reader.parse_signed_var_int()?;
+            let value = match r#type.kind(ctx) {
+                TypeKind::Float(FloatKind::F32) => f32::from_bits(bits as u32) as f64,
+                TypeKind::Float(FloatKind::F64) => f64::from_bits(bits as u64),
+                _ => return Err(reader.error("only the f32 and f64 float attributes are supported")),
+            };
+            ctx.float_attr(value, r#type)
+        }
+        CALL_SITE_LOC => {
+            let callee = resolver.attribute(ctx, reader.parse_var_int()?)?;
+            let caller = resolver.attribute(ctx, reader.parse_var_int()?)?;
+            ctx.call_site_loc(callee, caller)
+        }
+        FILE_LINE_COL_LOC => {
+            let filename = read_string_attribute(ctx, reader, resolver)?;
+            let line = read_u32(reader)?;
+            let column = read_u32(reader)?;
+            ctx.file_line_col_loc(&filename, line, column)
+        }
+        FUSED_LOC | FUSED_LOC_WITH_METADATA => {
+            let locations = read_attributes(ctx, reader, resolver)?;
+            let metadata = match code {
+                FUSED_LOC_WITH_METADATA => Some(resolver.attribute(ctx, reader.parse_var_int()?)?),
+                _ => None,
+            };
+            ctx.fused_loc(&locations, metadata)
+        }
+        NAME_LOC => {
+            let name = read_string_attribute(ctx, reader, resolver)?;
+            let child = resolver.attribute(ctx, reader.parse_var_int()?)?;
+            ctx.name_loc(&name, Some(child))
+        }
+        UNKNOWN_LOC => ctx.unknown_loc(),
+        DENSE_ARRAY => {
+            let element = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            let count = reader.parse_usize()?;
+            let data = reader.parse_blob()?;
+            let elements = read_raw_elements(ctx, reader, element, data, count, false)?;
+            ctx.dense_array_attr(element, &elements)
+        }
+        DENSE_INT_OR_FP_ELEMENTS => {
+            let r#type = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            let element = r#type.element_type(ctx).unwrap_or(r#type);
+            let count = r#type.num_elements(ctx).unwrap_or(1) as usize;
+            let data = reader.parse_blob()?;
+            let elements = read_raw_elements(ctx, reader, element, data, count, true)?;
+            ctx.dense_elements_attr(r#type, &elements)
+        }
+        STRING_WITH_TYPE | DENSE_RESOURCE_ELEMENTS => {
+            return Err(BytecodeError::Unsupported(format!("builtin attribute encoding {code}")))
+        }
+        code => return Err(reader.error(format!("unknown builtin attribute code: {code}"))),
+    };
+    Ok(attribute)
+}
+
+/// Decodes the builtin type of the `reader` contents.
+pub(crate) fn read_type(
+    ctx: &mut Context,
+    reader: &mut EncodingReader,
+    resolver: &mut dyn Resolver,
+) -> Result<Type, BytecodeError> {
+    use type_code::*;
+    let code = reader.parse_var_int()?;
+    let kind = match code {
+        INTEGER => {
+            let encoded = reader.parse_var_int()?;
+            let signedness = match encoded & 3 {
+                0 => Signedness::Signless,
+                1 => Signedness::Signed,
+                2 => Signedness::Unsigned,
+                _ => return Err(reader.error("invalid integer signedness")),
+            };
+            let width = u32::try_from(encoded >> 2).map_err(|_| reader.error("integer width is out of range"))?;
+            TypeKind::Integer { width, signedness }
+        }
+        INDEX => TypeKind::Index,
+        FUNCTION => TypeKind::Function {
+            inputs: read_types(ctx, reader, resolver)?,
+            results: read_types(ctx, reader, resolver)?,
+        },
+        BF16 => TypeKind::Float(FloatKind::BF16),
+        F16 => TypeKind::Float(FloatKind::F16),
+        F32 => TypeKind::Float(FloatKind::F32),
+        F64 => TypeKind::Float(FloatKind::F64),
+        F80 => TypeKind::Float(FloatKind::F80),
+        F128 => TypeKind::Float(FloatKind::F128),
+        COMPLEX => TypeKind::Complex(resolver.r#type(ctx, reader.parse_var_int()?)?),
+        MEMREF | MEMREF_WITH_MEMORY_SPACE => {
+            let memory_space = match code {
+                MEMREF_WITH_MEMORY_SPACE => Some(resolver.attribute(ctx, reader.parse_var_int()?)?),
+                _ => None,
+            };
+            let shape = read_shape(reader)?;
+            let element = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            let layout = resolver.attribute(ctx, reader.parse_var_int()?)?;
+            let identity = layout.as_affine_map(ctx).is_some_and(|map| map.is_identity());
+            TypeKind::MemRef {
+                shape,
+                element,
+                layout: (!identity).then_some(layout),
+                memory_space,
+            }
+        }
+        NONE => TypeKind::None,
+        RANKED_TENSOR | RANKED_TENSOR_WITH_ENCODING => {
+            let encoding = match code {
+                RANKED_TENSOR_WITH_ENCODING => Some(resolver.attribute(ctx, reader.parse_var_int()?)?),
+                _ => None,
+            };
+            let shape = read_shape(reader)?;
+            let element = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            TypeKind::RankedTensor {
+                shape,
+                element,
+                encoding,
+            }
+        }
+        TUPLE => TypeKind::Tuple(read_types(ctx, reader, resolver)?),
+        UNRANKED_MEMREF | UNRANKED_MEMREF_WITH_MEMORY_SPACE => {
+            let memory_space = match code {
+                UNRANKED_MEMREF_WITH_MEMORY_SPACE => Some(resolver.attribute(ctx, reader.parse_var_int()?)?),
+                _ => None,
+            };
+            let element = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            TypeKind::UnrankedMemRef { element, memory_space }
+        }
+        UNRANKED_TENSOR => TypeKind::UnrankedTensor {
+            element: resolver.r#type(ctx, reader.parse_var_int()?)?,
+        },
+        VECTOR => {
+            let shape = read_shape(reader)?;
+            let element = resolver.r#type(ctx, reader.parse_var_int()?)?;
+            TypeKind::Vector {
+                scalable: vec![false; shape.len()],
+                shape,
+                element,
+            }
+        }
+        code => return Err(BytecodeError::Unsupported(format!("builtin type encoding {code}"))),
+    };
+    Ok(ctx.get_type(kind))
+}
+
+fn read_attributes(
+    ctx: &mut Context,
+    reader: &mut EncodingReader,
+    resolver: &mut dyn Resolver,
+) -> Result<Vec<Attribute>, BytecodeError> {
+    (0..reader.parse_var_int()?)
+        .map(|_| resolver.attribute(ctx, reader.parse_var_int()?))
+        .collect()
+}
+
+fn read_types(
+    ctx: &mut Context,
+    reader: &mut EncodingReader,
+    resolver: &mut dyn Resolver,
+) -> Result<Vec<Type>, BytecodeError> {
+    (0..reader.parse_var_int()?)
+        .map(|_| resolver.r#type(ctx, reader.parse_var_int()?))
+        .collect()
+}
+
+fn read_string_attribute(
+    ctx: &mut Context,
+    reader: &mut EncodingReader,
+    resolver: &mut dyn Resolver,
+) -> Result<String, BytecodeError> {
+    let attribute = resolver.attribute(ctx, reader.parse_var_int()?)?;
+    match attribute.as_string(ctx) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(reader.error("expected a string attribute")),
+    }
+}
+
+fn read_shape(reader: &mut EncodingReader) -> Result<Vec<i64>, BytecodeError> {
+    (0..reader.parse_var_int()?)
+        .map(|_| reader.parse_signed_var_int())
+        .collect()
+}
+
+fn read_u32(reader: &mut EncodingReader) -> Result<u32, BytecodeError> {
+    let value = reader.parse_var_int()?;
+    u32::try_from(value).map_err(|_| reader.error(format!("value {value} is out of range")))
+}
+
+/// Width of the integer attribute values, the index ones are 64 bits wide.
+fn integer_width(ctx: &Context, r#type: Type) -> Option<u32> {
+    match r#type.kind(ctx) {
+        TypeKind::Integer { width, .. } => Some(*width),
+        TypeKind::Index => Some(64),
+        _ => None,
+    }
+}
+
+fn is_unsigned(ctx: &Context, r#type: Type) -> bool {
+    matches!(
+        r#type.kind(ctx),
+        TypeKind::Integer {
+            signedness: Signedness::Unsigned,
+            ..
+        }
+    )
+}
+
+/// Bits of the `f32` and `f64` values, sign extended like the upstream `APInt::getSExtValue`.
+fn float_bits(ctx: &Context, r#type: Type, value: f64) -> Option<i64> {
+    match r#type.kind(ctx) {
+        TypeKind::Float(FloatKind::F32) => Some((value as f32).to_bits() as i32 as i64),
+        TypeKind::Float(FloatKind::F64) => Some(value.to_bits() as i64),
+        _ => None,
+    }
+}
+
+/// Integer of the known width, a byte up to 8 bits, a signed varint up to 64 bits, or the active 64 bit words.
+fn write_integer(out: &mut Out, width: u32, value: i128) {
+    match width {
+        0..=8 => out.emit_byte(value as u8),
+        9..=64 => out.emit_signed_var_int(value as i64),
+        _ => {
+            let bits = value as u128;
+            let words = [bits as u64, (bits >> 64) as u64];
+            let active = if words[1] == 0 { 1 } else { 2 };
+            out.emit_var_int(active);
+            for word in &words[..active as usize] {
+                out.emit_signed_var_int(*word as i64);
+            }
+        }
+    }
+}
+
+/// Integer of the known width, sign extended unless it's `unsigned`.
+fn read_integer(reader: &mut EncodingReader, width: u32, unsigned: bool) -> Result<i128, BytecodeError> {
+    let bits = match width {
+        0..=8 => reader.parse_byte()? as u128,
+        9..=64 => reader.parse_signed_var_int()? as u64 as u128,
+        _ => {
+            let active = reader.parse_var_int()?;
+            if active > 2 {
+                return Err(BytecodeError::Unsupported(format!("{width} bit wide integers")));
+            }
+            let mut bits = 0u128;
+            for index in 0..active {
+                bits |= (reader.parse_signed_var_int()? as u64 as u128) << (64 * index);
+            }
+            bits
+        }
+    };
+    Ok(extend(bits, width, unsigned))
+}
+
+/// Value of the `width` low bits, the `i1` ones are never sign extended.
+fn extend(bits: u128, width: u32, unsigned: bool) -> i128 {
+    if width <= 1 {
+        return (bits & 1) as i128;
+    }
+    if width >= 128 {
+        return bits as i128;
+    }
+    let bits = bits & ((1u128 << width) - 1);
+    match !unsigned && bits >> (width - 1) & 1 == 1 {
+        true => (bits | !((1u128 << width) - 1)) as i128,
+        false => bits as i128,
+    }
+}
+
+/// Bytes of the element storage, the `i1` elements are bit packed unless it's a dense array.
+fn storage_bytes(ctx: &Context, element: Type) -> Option<usize> {
+    match element.kind(ctx) {
+        TypeKind::Integer { width, .. } => Some((*width as usize).div_ceil(8)),
+        TypeKind::Index => Some(8),
+        TypeKind::Float(FloatKind::F32) => Some(4),
+        TypeKind::Float(FloatKind::F64) => Some(8),
+        _ => None,
+    }
+}
+
+/// Raw little-endian storage of the elements, like the upstream `getRawData`.
+fn raw_elements(ctx: &Context, element: Type, elements: &[Attribute], packed: bool) -> Option<Vec<u8>> {
+    let size = storage_bytes(ctx, element)?;
+    let bit = |attribute: &Attribute| match attribute.kind(ctx) {
+        AttributeKind::Bool(value) => Some(*value),
+        AttributeKind::Integer { value, .. } => Some(*value != 0),
+        _ => None,
+    };
+    if packed && element.is_signless_integer(ctx, 1) {
+        if let [splat] = elements {
+            return Some(vec![if bit(splat)? { 0xFF } else { 0 }]);
+        }
+        let mut data = vec![0; elements.len().div_ceil(8)];
+        for (index, element) in elements.iter().enumerate() {
+            data[index / 8] |= (bit(element)? as u8) << (index % 8);
+        }
+        return Some(data);
+    }
+    let mut data = Vec::with_capacity(size * elements.len());
+    for attribute in elements {
+        let bits = match attribute.kind(ctx) {
+            AttributeKind::Bool(value) => *value as u128,
+            AttributeKind::Integer { value, .. } => *value as u128,
+            AttributeKind::Float { bits, .. } => float_bits(ctx, element, f64::from_bits(*bits))? as u64 as u128,
+            _ => return None,
+        };
+        data.extend_from_slice(&bits.to_le_bytes()[..size]);
+    }
+    Some(data)
+}
+
+/// Element attributes of the raw storage, a single stored element stands for the splat of the dense elements.
+fn read_raw_elements(
+    ctx: &mut Context,
+    reader: &EncodingReader,
+    element: Type,
+    data: &[u8],
+    count: usize,
+    packed: bool,
+) -> Result<Vec<Attribute>, BytecodeError> {
+    let Some(size) = storage_bytes(ctx, element) else {
+        return Err(BytecodeError::Unsupported(
+            "dense elements of this element type".to_string(),
+        ));
+    };
+    let unsigned = is_unsigned(ctx, element);
+    if packed && element.is_signless_integer(ctx, 1) {
+        let splat = data.len() == 1 && (data[0] == 0 || data[0] == 0xFF);
+        let bits: Vec<bool> = match splat {
+            true => vec![data[0] != 0],
+            false if data.len() == count.div_ceil(8) => (0..count)
+                .map(|index| data[index / 8] >> (index % 8) & 1 == 1)
+                .collect(),
+            false => return Err(reader.error("dense elements data size doesn't match the type")),
+        };
+        return Ok(bits
+            .into_iter()
+            .map(|bit| ctx.integer_attr(bit as i128, element))
+            .collect());
+    }
+    if !(data.len() == size * count || (packed && data.len() == size)) {
+        return Err(reader.error("dense elements data size doesn't match the type"));
+    }
+    let mut elements = vec![];
+    for chunk in data.chunks(size) {
+        let mut bytes = [0; 16];
+        bytes[..size].copy_from_slice(chunk);
+        let bits = u128::from_le_bytes(bytes);
+        elements.push(match element.kind(ctx) {
+            TypeKind::Float(FloatKind::F32) => ctx.float_attr(f32::from_bits(bits as u32) as f64, element),
+            TypeKind::Float(FloatKind::F64) => ctx.float_attr(f64::from_bits(bits as u64), element),
+            _ => {
+                let width = integer_width(ctx, element).unwrap_or(64);
+                ctx.integer_attr(extend(bits, width, unsigned), element)
+            }
+        });
+    }
+    Ok(elements)
+}
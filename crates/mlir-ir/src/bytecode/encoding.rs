@@ -0,0 +1,259 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Byte level encoding of the bytecode, the prefix varints, the blobs, the null-terminated strings and the sections.
+//!
+
+use crate::BytecodeError;
+
+/// Padding byte of the aligned sections.
+const PADDING: u8 = 0xCB;
+
+/// Sink of the encoded bytes.
+#[derive(Debug, Default)]
+pub(crate) struct Emitter {
+    bytes: Vec<u8>,
+}
+
+impl Emitter {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn emit_byte(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    pub fn patch_byte(&mut self, offset: usize, byte: u8) {
+        self.bytes[offset] = byte;
+    }
+
+    pub fn emit_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Prefix varint, the trailing zeros of the first byte tell the number of the following bytes,
+    /// the values wider than 56 bits follow a zero byte.
+    pub fn emit_var_int(&mut self, value: u64) {
+        if value >> 7 == 0 {
+            return self.emit_byte(((value << 1) | 1) as u8);
+        }
+        let mut rest = value >> 7;
+        for length in 2..9 {
+            rest >>= 7;
+            if rest == 0 {
+                let encoded = ((value << 1) | 1) << (length - 1);
+                return self.emit_bytes(&encoded.to_le_bytes()[..length]);
+            }
+        }
+        self.emit_byte(0);
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Zigzag encoded varint.
+    pub fn emit_signed_var_int(&mut self, value: i64) {
+        self.emit_var_int(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    /// Varint carrying the `flag` in its low bit.
+    pub fn emit_var_int_with_flag(&mut self, value: u64, flag: bool) {
+        self.emit_var_int((value << 1) | flag as u64);
+    }
+
+    pub fn emit_blob(&mut self, blob: &[u8]) {
+        self.emit_var_int(blob.len() as u64);
+        self.emit_bytes(blob);
+    }
+
+    pub fn emit_null_terminated_string(&mut self, string: &str) {
+        self.emit_bytes(string.as_bytes());
+        self.emit_byte(0);
+    }
+
+    /// Section of the `id` with the contents of the other emitter, never aligned.
+    pub fn emit_section(&mut self, id: u8, section: Emitter) {
+        self.emit_byte(id);
+        self.emit_var_int(section.len() as u64);
+        self.emit_bytes(&section.bytes);
+    }
+}
+
+/// Cursor over the encoded bytes, reporting the errors at the offsets within the whole bytecode.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EncodingReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// Offset of the `data` within the bytecode.
+    base: usize,
+}
+
+impl<'a> EncodingReader<'a> {
+    pub fn new(data: &'a [u8], base: usize) -> Self {
+        Self {
+            data,
+            position: 0,
+            base,
+        }
+    }
+
+    /// Offset of the cursor within the bytecode.
+    pub fn offset(&self) -> usize {
+        self.base + self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.data.len()
+    }
+
+    /// Whole data of the reader, regardless of the cursor.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> BytecodeError {
+        BytecodeError::Malformed {
+            offset: self.offset(),
+            message: message.into(),
+        }
+    }
+
+    pub fn parse_byte(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.parse_bytes(1)?[0])
+    }
+
+    pub fn parse_bytes(&mut self, length: usize) -> Result<&'a [u8], BytecodeError> {
+        if self.data.len() - self.position < length {
+            return Err(self.error(format!(
+                "attempting to parse {length} bytes when only {} remain",
+                self.data.len() - self.position
+            )));
+        }
+        let bytes = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn parse_var_int(&mut self) -> Result<u64, BytecodeError> {
+        let first = self.parse_byte()?;
+        if first & 1 == 1 {
+            return Ok((first >> 1) as u64);
+        }
+        if first == 0 {
+            let bytes = self.parse_bytes(8)?;
+            return Ok(u64::from_le_bytes(bytes.try_into().expect("eight bytes")));
+        }
+        let length = first.trailing_zeros() as usize + 1;
+        let mut encoded = [0; 8];
+        encoded[0] = first;
+        encoded[1..length].copy_from_slice(self.parse_bytes(length - 1)?);
+        Ok(u64::from_le_bytes(encoded) >> length)
+    }
+
+    pub fn parse_signed_var_int(&mut self) -> Result<i64, BytecodeError> {
+        let value = self.parse_var_int()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    pub fn parse_var_int_with_flag(&mut self) -> Result<(u64, bool), BytecodeError> {
+        let value = self.parse_var_int()?;
+        Ok((value >> 1, value & 1 == 1))
+    }
+
+    /// Varint used as the size or the index of something.
+    pub fn parse_usize(&mut self) -> Result<usize, BytecodeError> {
+        let value = self.parse_var_int()?;
+        usize::try_from(value).map_err(|_| self.error(format!("value {value} is out of range")))
+    }
+
+    pub fn parse_blob(&mut self) -> Result<&'a [u8], BytecodeError> {
+        let length = self.parse_usize()?;
+        self.parse_bytes(length)
+    }
+
+    pub fn parse_null_terminated_string(&mut self) -> Result<&'a str, BytecodeError> {
+        let rest = &self.data[self.position..];
+        let Some(length) = rest.iter().position(|byte| *byte == 0) else {
+            return Err(self.error("malformed null-terminated string, no null character found"));
+        };
+        let string = std::str::from_utf8(&rest[..length]).map_err(|_| self.error("invalid UTF-8 string"))?;
+        self.position += length + 1;
+        Ok(string)
+    }
+
+    /// Section `id` and the reader of its data, the padding of the aligned sections is skipped.
+    pub fn parse_section(&mut self) -> Result<(u8, EncodingReader<'a>), BytecodeError> {
+        let id_and_alignment = self.parse_byte()?;
+        let length = self.parse_usize()?;
+        let id = id_and_alignment & 0x7F;
+        if id >= super::section::COUNT {
+            return Err(self.error(format!("invalid section ID: {id}")));
+        }
+        if id_and_alignment & 0x80 != 0 {
+            let alignment = self.parse_usize()?;
+            if !alignment.is_power_of_two() {
+                return Err(self.error(format!("expected alignment to be a power-of-two: {alignment}")));
+            }
+            while self.offset() % alignment != 0 {
+                if self.parse_byte()? != PADDING {
+                    return Err(self.error("expected padding bytes before the aligned section"));
+                }
+            }
+        }
+        let base = self.offset();
+        Ok((id, EncodingReader::new(self.parse_bytes(length)?, base)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_var_ints() {
+        let values = [0, 1, 127, 128, 300, 1 << 20, (1 << 56) - 1, 1 << 56, u64::MAX];
+        let mut emitter = Emitter::default();
+        for value in values {
+            emitter.emit_var_int(value);
+            emitter.emit_signed_var_int(-(value as i64));
+        }
+        let bytes = emitter.into_bytes();
+        assert_eq!(&bytes[..4], &[0x01, 0x01, 0x03, 0x03]);
+
+        let mut reader = EncodingReader::new(&bytes, 0);
+        for value in values {
+            assert_eq!(reader.parse_var_int(), Ok(value));
+            assert_eq!(reader.parse_signed_var_int(), Ok(-(value as i64)));
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn should_report_truncated_input() {
+        let mut reader = EncodingReader::new(&[0x02], 10);
+        assert_eq!(
+            reader.parse_var_int(),
+            Err(BytecodeError::Malformed {
+                offset: 11,
+                message: "attempting to parse 1 bytes when only 0 remain".to_string()
+            })
+        );
+    }
+}
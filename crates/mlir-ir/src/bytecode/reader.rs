@@ -0,0 +1,673 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Bytecode reader, resolving the attributes and types on their first use and materializing the IR section.
+//!
+
+use std::collections::HashMap;
+
+use crate::bytecode::builtin::{self, Resolver};
+use crate::bytecode::encoding::EncodingReader;
+use crate::bytecode::{op_mask, section, version, MAGIC, VERSION};
+use crate::{
+    parse_attribute, parse_type, Attribute, AttributeKind, Block, BytecodeError, Context, NamedAttribute, Operation,
+    OperationState, Region, Type, Value,
+};
+
+/// Placeholder of the values used before their definition.
+const FORWARD_REFERENCE: &str = "builtin.forward_reference";
+
+/// Options of the reading, like the upstream `ParserConfig`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BytecodeReaderConfig {
+    /// Leaves the regions of the isolated operations out until they are materialized.
+    pub lazy_loading: bool,
+}
+
+/// Reads the operations of the bytecode, like the upstream `BytecodeReader`.
+pub struct BytecodeReader<'a> {
+    config: BytecodeReaderConfig,
+    version: u64,
+    producer: &'a str,
+    strings: Vec<&'a str>,
+    dialects: Vec<&'a str>,
+    op_names: Vec<OpName>,
+    attributes: Vec<Entry<'a, Attribute>>,
+    types: Vec<Entry<'a, Type>>,
+    /// Encodings of the operation properties, referred to by their index.
+    properties: Vec<EncodingReader<'a>>,
+    ir: Option<EncodingReader<'a>>,
+    /// Operations with their regions left out by the lazy loading, and the sections of the regions.
+    lazy: Vec<(Operation, EncodingReader<'a>)>,
+}
+
+#[derive(Debug, Clone)]
+struct OpName {
+    name: String,
+    /// Whether the operation was registered by the producer, since the native properties encoding.
+    registered: Option<bool>,
+}
+
+/// Attribute or type, resolved on its first use.
+#[derive(Debug)]
+struct Entry<'a, T> {
+    dialect: usize,
+    data: EncodingReader<'a>,
+    custom: bool,
+    resolved: Option<T>,
+}
+
+impl<'a, T> Entry<'a, T> {
+    fn new((dialect, data, custom): (usize, EncodingReader<'a>, bool)) -> Self {
+        Self {
+            dialect,
+            data,
+            custom,
+            resolved: None,
+        }
+    }
+}
+
+/// Values of the regions sharing their numbering, the regions of the isolated operations get a scope of their own.
+#[derive(Debug, Default)]
+struct Scope {
+    values: Vec<Option<Value>>,
+    /// Placeholders of the values used before their definition.
+    forward: HashMap<usize, Operation>,
+}
+
+impl<'a> BytecodeReader<'a> {
+    /// Reads the header and the sections, the attributes, the types and the IR are read on demand.
+    pub fn new(data: &'a [u8], config: BytecodeReaderConfig) -> Result<Self, BytecodeError> {
+        let mut reader = EncodingReader::new(data, 0);
+        if !data.starts_with(&MAGIC) {
+            return Err(reader.error("input buffer is not an MLIR bytecode file"));
+        }
+        reader.parse_bytes(MAGIC.len())?;
+        let version = reader.parse_var_int()?;
+        if version > VERSION {
+            return Err(BytecodeError::UnsupportedVersion {
+                version,
+                current: VERSION,
+            });
+        }
+        let producer = reader.parse_null_terminated_string()?;
+
+        let mut sections: [Option<EncodingReader>; section::COUNT as usize] = Default::default();
+        while !reader.is_empty() {
+            let (id, section) = reader.parse_section()?;
+            if sections[id as usize].replace(section).is_some() {
+                return Err(reader.error(format!("duplicate top-level section: {id}")));
+            }
+        }
+        let mut required = |id: u8| {
+            sections[id as usize]
+                .take()
+                .ok_or_else(|| reader.error(format!("missing data for top-level section: {id}")))
+        };
+        let string = required(section::STRING)?;
+        let dialect = required(section::DIALECT)?;
+        let attr_type = required(section::ATTR_TYPE)?;
+        let attr_type_offset = required(section::ATTR_TYPE_OFFSET)?;
+        let ir = required(section::IR)?;
+        let properties = match version < version::NATIVE_PROPERTIES_ENCODING {
+            true => None,
+            false => Some(required(section::PROPERTIES)?),
+        };
+
+        let mut bytecode = Self {
+            config,
+            version,
+            producer,
+            strings: parse_strings(string)?,
+            dialects: vec![],
+            op_names: vec![],
+            attributes: vec![],
+            types: vec![],
+            properties: match properties {
+                Some(properties) => parse_properties(properties)?,
+                None => vec![],
+            },
+            ir: Some(ir),
+            lazy: vec![],
+        };
+        bytecode.parse_dialects(dialect)?;
+        bytecode.parse_attr_type_offsets(attr_type_offset, attr_type)?;
+        Ok(bytecode)
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn producer(&self) -> &'a str {
+        self.producer
+    }
+
+    /// Reads the top level operations of the IR section, wrapping them into a `builtin.module`
+    /// unless there's only one.
+    pub fn read(&mut self, ctx: &mut Context) -> Result<Operation, BytecodeError> {
+        let Some(mut reader) = self.ir.take() else {
+            return Err(BytecodeError::Unsupported("reading the IR section twice".to_string()));
+        };
+        let (count, has_arguments) = reader.parse_var_int_with_flag()?;
+        if has_arguments {
+            return Err(reader.error("the top level block may not have arguments"));
+        }
+        let mut scope = Scope::default();
+        let mut operations = vec![];
+        for _ in 0..count {
+            operations.push(self.read_operation(ctx, &mut reader, &mut scope, &[], &mut 0)?);
+        }
+        check_resolved(&reader, &scope, 0)?;
+        match operations.as_slice() {
+            [op] => Ok(*op),
+            _ => {
+                let module = ctx.create_module();
+                let body = module.region(ctx, 0).blocks(ctx)[0];
+                for op in operations {
+                    ctx.insert_operation(body, None, op)?;
+                }
+                Ok(module)
+            }
+        }
+    }
+
+    /// Operations with their regions left out by the lazy loading.
+    pub fn materializable(&self) -> Vec<Operation> {
+        self.lazy.iter().map(|(op, _)| *op).collect()
+    }
+
+    pub fn is_materializable(&self, op: Operation) -> bool {
+        self.lazy.iter().any(|(candidate, _)| *candidate == op)
+    }
+
+    /// Reads the regions of the lazily loaded operation, the nested isolated ones are left out in turn.
+    pub fn materialize(&mut self, ctx: &mut Context, op: Operation) -> Result<(), BytecodeError> {
+        let Some(position) = self.lazy.iter().position(|(candidate, _)| *candidate == op) else {
+            return Ok(());
+        };
+        let (_, mut reader) = self.lazy.remove(position);
+        self.read_isolated_regions(ctx, &mut reader, op)
+    }
+
+    /// Materializes the remaining operations the predicate accepts and erases the other ones,
+    /// like the upstream `BytecodeReader::finalize`.
+    pub fn finalize(
+        &mut self,
+        ctx: &mut Context,
+        mut should_materialize: impl FnMut(&Context, Operation) -> bool,
+    ) -> Result<(), BytecodeError> {
+        while let Some((op, _)) = self.lazy.first() {
+            let op = *op;
+            match should_materialize(ctx, op) {
+                true => self.materialize(ctx, op)?,
+                false => {
+                    self.lazy.remove(0);
+                    ctx.erase_operation(op)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_dialects(&mut self, mut reader: EncodingReader<'a>) -> Result<(), BytecodeError> {
+        for _ in 0..reader.parse_var_int()? {
+            let name = match self.version < version::DIALECT_VERSIONING {
+                true => reader.parse_var_int()?,
+                false => {
+                    let (name, has_version) = reader.parse_var_int_with_flag()?;
+                    if has_version {
+                        let (id, _) = reader.parse_section()?;
+                        if id != section::DIALECT_VERSIONS {
+                            return Err(reader.error("expected dialect version section"));
+                        }
+                    }
+                    name
+                }
+            };
+            self.dialects.push(self.string_at(name)?);
+        }
+        if self.version >= version::ELIDE_UNKNOWN_BLOCK_ARG_LOCATION {
+            reader.parse_var_int()?;
+        }
+        while !reader.is_empty() {
+            let dialect = self.dialect_at(reader.parse_var_int()?)?;
+            for _ in 0..reader.parse_var_int()? {
+                let (name, registered) = match self.version < version::NATIVE_PROPERTIES_ENCODING {
+                    true => (reader.parse_var_int()?, None),
+                    false => {
+                        let (name, registered) = reader.parse_var_int_with_flag()?;
+                        (name, Some(registered))
+                    }
+                };
+                let name = self.string_at(name)?;
+                let name = match self.dialects[dialect] {
+                    "" => name.to_string(),
+                    dialect => format!("{dialect}.{name}"),
+                };
+                self.op_names.push(OpName { name, registered });
+            }
+        }
+        Ok(())
+    }
+
+    /// Slices the entries of the attribute and type section, the attributes go first.
+    fn parse_attr_type_offsets(
+        &mut self,
+        mut offsets: EncodingReader<'a>,
+        data: EncodingReader<'a>,
+    ) -> Result<(), BytecodeError> {
+        let attributes = offsets.parse_usize()?;
+        let types = offsets.parse_usize()?;
+        let mut entries = vec![];
+        let mut offset = 0;
+        for count in [attributes, types] {
+            let first = entries.len();
+            while entries.len() - first < count {
+                let dialect = self.dialect_at(offsets.parse_var_int()?)?;
+                for _ in 0..offsets.parse_var_int()? {
+                    let (size, custom) = offsets.parse_var_int_with_flag()?;
+                    let size = size as usize;
+                    if data.data().len() - offset < size {
+                        return Err(offsets.error("attribute or type entry offset points past the end of section"));
+                    }
+                    let entry = EncodingReader::new(&data.data()[offset..offset + size], data.offset() + offset);
+                    entries.push((dialect, entry, custom));
+                    offset += size;
+                }
+            }
+        }
+        let types = entries.split_off(attributes);
+        self.attributes = entries.into_iter().map(Entry::new).collect();
+        self.types = types.into_iter().map(Entry::new).collect();
+        Ok(())
+    }
+
+    fn string_at(&self, index: u64) -> Result<&'a str, BytecodeError> {
+        self.strings
+            .get(index as usize)
+            .copied()
+            .ok_or(BytecodeError::InvalidIndex { kind: "string", index })
+    }
+
+    fn dialect_at(&self, index: u64) -> Result<usize, BytecodeError> {
+        match (index as usize) < self.dialects.len() {
+            true => Ok(index as usize),
+            false => Err(BytecodeError::InvalidIndex { kind: "dialect", index }),
+        }
+    }
+
+    fn read_operation(
+        &mut self,
+        ctx: &mut Context,
+        reader: &mut EncodingReader<'a>,
+        scope: &mut Scope,
+        blocks: &[Block],
+        next: &mut usize,
+    ) -> Result<Operation, BytecodeError> {
+        let index = reader.parse_var_int()?;
+        let op_name = self
+            .op_names
+            .get(index as usize)
+            .cloned()
+            .ok_or(BytecodeError::InvalidIndex {
+                kind: "operation name",
+                index,
+            })?;
+        let mask = reader.parse_byte()?;
+        let location = self.attribute(ctx, reader.parse_var_int()?)?;
+        if !location.is_location(ctx) {
+            return Err(reader.error("expected a location attribute"));
+        }
+        let mut state = OperationState::new(op_name.name.as_str());
+        state.set_location(location);
+        if mask & op_mask::HAS_ATTRS != 0 {
+            state.attributes = self.dictionary(ctx, reader)?;
+        }
+        if mask & op_mask::HAS_PROPERTIES != 0 {
+            if op_name.registered.is_none() {
+                return Err(reader.error(format!("unexpected properties at bytecode version {}", self.version)));
+            }
+            state.set_properties(self.properties(ctx, reader.parse_var_int()?)?);
+        }
+        if mask & op_mask::HAS_RESULTS != 0 {
+            for _ in 0..reader.parse_var_int()? {
+                let r#type = self.r#type(ctx, reader.parse_var_int()?)?;
+                state.add_types([r#type]);
+            }
+        }
+        if mask & op_mask::HAS_OPERANDS != 0 {
+            for _ in 0..reader.parse_var_int()? {
+                let index = reader.parse_usize()?;
+                let operand = operand(ctx, reader, scope, index)?;
+                state.add_operands([operand]);
+            }
+        }
+        if mask & op_mask::HAS_SUCCESSORS != 0 {
+            for _ in 0..reader.parse_var_int()? {
+                let index = reader.parse_var_int()?;
+                let successor = blocks.get(index as usize).ok_or(BytecodeError::InvalidIndex {
+                    kind: "successor",
+                    index,
+                })?;
+                state.add_successors([*successor]);
+            }
+        }
+        if self.version >= version::USE_LIST_ORDERING && mask & op_mask::HAS_USE_LIST_ORDERS != 0 {
+            skip_use_list_orders(reader, state.types.len())?;
+        }
+        let isolated = match mask & op_mask::HAS_INLINE_REGIONS != 0 {
+            true => {
+                let (count, isolated) = reader.parse_var_int_with_flag()?;
+                state.add_regions(count as usize);
+                isolated
+            }
+            false => false,
+        };
+
+        let op = ctx.create_operation(state);
+        let results = op.results(ctx).to_vec();
+        for result in results {
+            define(ctx, reader, scope, *next, result)?;
+            *next += 1;
+        }
+        if op.regions(ctx).is_empty() {
+            return Ok(op);
+        }
+        if !isolated {
+            let regions = op.regions(ctx).to_vec();
+            for region in regions {
+                self.read_region(ctx, reader, scope, region)?;
+            }
+        } else if self.version < version::LAZY_LOADING {
+            self.read_isolated_regions(ctx, reader, op)?;
+        } else {
+            let (id, mut section) = reader.parse_section()?;
+            if id != section::IR {
+                return Err(reader.error("expected IR section for region"));
+            }
+            match self.config.lazy_loading {
+                true => self.lazy.push((op, section)),
+                false => self.read_isolated_regions(ctx, &mut section, op)?,
+            }
+        }
+        Ok(op)
+    }
+
+    /// Reads the regions of the isolated operation within a value scope of their own.
+    fn read_isolated_regions(
+        &mut self,
+        ctx: &mut Context,
+        reader: &mut EncodingReader<'a>,
+        op: Operation,
+    ) -> Result<(), BytecodeError> {
+        let mut scope = Scope::default();
+        let regions = op.regions(ctx).to_vec();
+        for region in regions {
+            self.read_region(ctx, reader, &mut scope, region)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the blocks of the region, its values follow the ones of the enclosing regions of the scope.
+    fn read_region(
+        &mut self,
+        ctx: &mut Context,
+        reader: &mut EncodingReader<'a>,
+        scope: &mut Scope,
+        region: Region,
+    ) -> Result<(), BytecodeError> {
+        let count = reader.parse_usize()?;
+        if count == 0 {
+            return Ok(());
+        }
+        let values = reader.parse_usize()?;
+        let blocks: Vec<Block> = (0..count)
+            .map(|_| {
+                let block = ctx.create_block(&[]);
+                ctx.append_block(region, block);
+                block
+            })
+            .collect();
+        let first = scope.values.len();
+        scope.values.resize(first + values, None);
+        let mut next = first;
+        for block in &blocks {
+            let (operations, has_arguments) = reader.parse_var_int_with_flag()?;
+            if has_arguments {
+                self.read_block_arguments(ctx, reader, scope, *block, &mut next)?;
+            }
+            for _ in 0..operations {
+                let op = self.read_operation(ctx, reader, scope, &blocks, &mut next)?;
+                ctx.insert_operation(*block, None, op)?;
+            }
+        }
+        check_resolved(reader, scope, first)?;
+        scope.values.truncate(first);
+        Ok(())
+    }
+
+    /// Reads the arguments of the block, their locations are dropped.
+    fn read_block_arguments(
+        &mut self,
+        ctx: &mut Context,
+        reader: &mut EncodingReader<'a>,
+        scope: &mut Scope,
+        block: Block,
+        next: &mut usize,
+    ) -> Result<(), BytecodeError> {
+        let count = reader.parse_usize()?;
+        for _ in 0..count {
+            let r#type = match self.version < version::ELIDE_UNKNOWN_BLOCK_ARG_LOCATION {
+                true => {
+                    let r#type = reader.parse_var_int()?;
+                    reader.parse_var_int()?;
+                    r#type
+                }
+                false => {
+                    let (r#type, has_location) = reader.parse_var_int_with_flag()?;
+                    if has_location {
+                        reader.parse_var_int()?;
+                    }
+                    r#type
+                }
+            };
+            let r#type = self.r#type(ctx, r#type)?;
+            let argument = ctx.add_argument(block, r#type);
+            define(ctx, reader, scope, *next, argument)?;
+            *next += 1;
+        }
+        if self.version >= version::USE_LIST_ORDERING && reader.parse_byte()? != 0 {
+            skip_use_list_orders(reader, count)?;
+        }
+        Ok(())
+    }
+
+    /// Properties of the index, stored as their dictionary attribute since no dialect has a native encoding
+    /// of its own, like the upstream fallback of the operations without the `BytecodeOpInterface`.
+    fn properties(&mut self, ctx: &mut Context, index: u64) -> Result<Attribute, BytecodeError> {
+        let mut entry = *self.properties.get(index as usize).ok_or(BytecodeError::InvalidIndex {
+            kind: "properties",
+            index,
+        })?;
+        let properties = self.attribute(ctx, entry.parse_var_int()?)?;
+        match properties.kind(ctx) {
+            AttributeKind::Dictionary(_) => Ok(properties),
+            _ => Err(entry.error("expected a dictionary attribute")),
+        }
+    }
+
+    fn dictionary(
+        &mut self,
+        ctx: &mut Context,
+        reader: &mut EncodingReader<'a>,
+    ) -> Result<Vec<NamedAttribute>, BytecodeError> {
+        let dictionary = self.attribute(ctx, reader.parse_var_int()?)?;
+        match dictionary.kind(ctx) {
+            AttributeKind::Dictionary(attributes) => Ok(attributes.clone()),
+            _ => Err(reader.error("expected a dictionary attribute")),
+        }
+    }
+}
+
+impl Resolver for BytecodeReader<'_> {
+    fn attribute(&mut self, ctx: &mut Context, index: u64) -> Result<Attribute, BytecodeError> {
+        let entry = self.attributes.get(index as usize).ok_or(BytecodeError::InvalidIndex {
+            kind: "attribute",
+            index,
+        })?;
+        if let Some(attribute) = entry.resolved {
+            return Ok(attribute);
+        }
+        let (dialect, mut data, custom) = (self.dialects[entry.dialect], entry.data, entry.custom);
+        let attribute = match (custom, dialect) {
+            (false, _) => parse_attribute(ctx, data.parse_null_terminated_string()?)?,
+            (true, "builtin") => builtin::read_attribute(ctx, &mut data, self)?,
+            (true, dialect) => {
+                return Err(BytecodeError::Unsupported(format!(
+                    "custom encoding of the `{dialect}` dialect attributes"
+                )))
+            }
+        };
+        self.attributes[index as usize].resolved = Some(attribute);
+        Ok(attribute)
+    }
+
+    fn r#type(&mut self, ctx: &mut Context, index: u64) -> Result<Type, BytecodeError> {
+        let entry = self
+            .types
+            .get(index as usize)
+            .ok_or(BytecodeError::InvalidIndex { kind: "type", index })?;
+        if let Some(r#type) = entry.resolved {
+            return Ok(r#type);
+        }
+        let (dialect, mut data, custom) = (self.dialects[entry.dialect], entry.data, entry.custom);
+        let r#type = match (custom, dialect) {
+            (false, _) => parse_type(ctx, data.parse_null_terminated_string()?)?,
+            (true, "builtin") => builtin::read_type(ctx, &mut data, self)?,
+            (true, dialect) => {
+                return Err(BytecodeError::Unsupported(format!(
+                    "custom encoding of the `{dialect}` dialect types"
+                )))
+            }
+        };
+        self.types[index as usize].resolved = Some(r#type);
+        Ok(r#type)
+    }
+
+    fn string(&self, index: u64) -> Result<String, BytecodeError> {
+        self.string_at(index).map(str::to_string)
+    }
+}
+
+/// Strings of the section, their sizes go in the reverse order and the null-terminated strings fill its end.
+fn parse_strings(mut reader: EncodingReader) -> Result<Vec<&str>, BytecodeError> {
+    let data = reader.data();
+    let mut strings = vec![""; reader.parse_usize()?];
+    let mut end = data.len();
+    for string in strings.iter_mut().rev() {
+        let size = reader.parse_usize()?;
+        if size == 0 || size > end || data[end - 1] != 0 {
+            return Err(reader.error("string size exceeds the available data size"));
+        }
+        *string = std::str::from_utf8(&data[end - size..end - 1]).map_err(|_| reader.error("invalid UTF-8 string"))?;
+        end -= size;
+    }
+    Ok(strings)
+}
+
+/// Number of the entries, followed by the size-prefixed encodings of the operation properties.
+fn parse_properties(mut reader: EncodingReader) -> Result<Vec<EncodingReader>, BytecodeError> {
+    let count = reader.parse_usize()?;
+    let mut properties = Vec::with_capacity(count.min(reader.data().len()));
+    for _ in 0..count {
+        let data = reader.parse_blob()?;
+        properties.push(EncodingReader::new(data, reader.offset() - data.len()));
+    }
+    Ok(properties)
+}
+
+/// Value of the scope, or the placeholder of the one not defined yet.
+fn operand(
+    ctx: &mut Context,
+    reader: &EncodingReader,
+    scope: &mut Scope,
+    index: usize,
+) -> Result<Value, BytecodeError> {
+    match scope.values.get(index) {
+        Some(Some(value)) => Ok(*value),
+        Some(None) => {
+            let placeholder = match scope.forward.get(&index) {
+                Some(placeholder) => *placeholder,
+                None => {
+                    let none = ctx.none_type();
+                    let mut state = OperationState::new(FORWARD_REFERENCE);
+                    state.add_types([none]);
+                    let placeholder = ctx.create_operation(state);
+                    scope.forward.insert(index, placeholder);
+                    placeholder
+                }
+            };
+            Ok(placeholder.result(ctx, 0))
+        }
+        None => Err(reader.error(format!("invalid value index: {index}"))),
+    }
+}
+
+/// Defines the value of the scope, replacing the uses of its placeholder.
+fn define(
+    ctx: &mut Context,
+    reader: &EncodingReader,
+    scope: &mut Scope,
+    index: usize,
+    value: Value,
+) -> Result<(), BytecodeError> {
+    let Some(slot) = scope.values.get_mut(index) else {
+        return Err(reader.error(format!("value index {index} is out of range of the region values")));
+    };
+    *slot = Some(value);
+    if let Some(placeholder) = scope.forward.remove(&index) {
+        let forward = placeholder.result(ctx, 0);
+        ctx.replace_all_uses_with(forward, value);
+        ctx.erase_operation(placeholder)?;
+    }
+    Ok(())
+}
+
+/// Fails on the placeholders of the values of the scope, starting at `first`, never defined.
+fn check_resolved(reader: &EncodingReader, scope: &Scope, first: usize) -> Result<(), BytecodeError> {
+    match scope.forward.keys().any(|index| *index >= first) {
+        true => Err(reader.error("not all forward unresolved forward operand references")),
+        false => Ok(()),
+    }
+}
+
+/// Skips the use-list orders of the values, the uses are kept in their order of creation.
+fn skip_use_list_orders(reader: &mut EncodingReader, values: usize) -> Result<(), BytecodeError> {
+    let count = if values > 1 { reader.parse_var_int()? } else { 1 };
+    for _ in 0..count {
+        if values > 1 {
+            reader.parse_var_int()?;
+        }
+        let (uses, _) = reader.parse_var_int_with_flag()?;
+        for _ in 0..uses {
+            reader.parse_var_int()?;
+        }
+    }
+    Ok(())
+}
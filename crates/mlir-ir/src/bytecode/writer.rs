@@ -0,0 +1,628 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Bytecode writer, numbering the IR entities first and then emitting the sections referring to them.
+//!
+
+use std::collections::{HashMap, HashSet};
+
+use crate::bytecode::builtin::{self, Numbering, Out};
+use crate::bytecode::encoding::Emitter;
+use crate::bytecode::{op_mask, section, version, MAGIC, VERSION};
+use crate::{
+    Attribute, AttributeKind, Block, BytecodeError, Context, Operation, Printer, PrinterOptions, Region, Type,
+    TypeKind, Value,
+};
+
+/// Options of the emitted bytecode, like the upstream `BytecodeWriterConfig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytecodeWriterConfig {
+    /// Version to emit, the features of the newer ones are left out of the older versions.
+    pub version: u64,
+    /// Producer string of the header.
+    pub producer: String,
+}
+
+impl Default for BytecodeWriterConfig {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            producer: format!("rustc_codegen_mlir {}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+}
+
+/// Writes the operation into the bytecode, like the upstream `writeBytecodeToFile`.
+pub struct BytecodeWriter<'c> {
+    ctx: &'c mut Context,
+    config: BytecodeWriterConfig,
+    dialects: Vec<String>,
+    op_names: Entries<String>,
+    attributes: Entries<Attribute>,
+    types: Entries<Type>,
+    strings: Strings,
+    /// Encodings of the operation properties, deduplicated.
+    properties: Properties,
+    values: HashMap<Value, u64>,
+    blocks: HashMap<Block, u64>,
+    /// Number of the blocks and the values defined directly within the region.
+    regions: HashMap<Region, (u64, u64)>,
+    isolated: HashSet<Operation>,
+}
+
+/// Entries grouped by their dialects, indexed in the order of the groups.
+#[derive(Debug)]
+struct Entries<K> {
+    items: Vec<(usize, K)>,
+    indices: HashMap<K, u64>,
+}
+
+impl<K> Default for Entries<K> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + std::hash::Hash> Entries<K> {
+    /// Groups the entries of the `dialect` indices, keeping their order within the groups.
+    fn new(items: Vec<(usize, K)>) -> Self {
+        let mut items = items;
+        items.sort_by_key(|(dialect, _)| *dialect);
+        let indices = items
+            .iter()
+            .enumerate()
+            .map(|(index, (_, item))| (item.clone(), index as u64))
+            .collect();
+        Self { items, indices }
+    }
+
+    fn index(&self, item: &K) -> u64 {
+        *self.indices.get(item).expect("the entry is numbered")
+    }
+
+    /// Dialect index and the number of the entries of each group.
+    fn groups(&self) -> Vec<(usize, usize)> {
+        let mut groups: Vec<(usize, usize)> = vec![];
+        for (dialect, _) in &self.items {
+            match groups.last_mut() {
+                Some((last, count)) if last == dialect => *count += 1,
+                _ => groups.push((*dialect, 1)),
+            }
+        }
+        groups
+    }
+}
+
+/// Properties section contents, indexed in the order of their first use.
+#[derive(Debug, Default)]
+struct Properties {
+    blobs: Vec<Vec<u8>>,
+    indices: HashMap<Vec<u8>, u64>,
+}
+
+impl Properties {
+    fn insert(&mut self, blob: Vec<u8>) -> u64 {
+        if let Some(index) = self.indices.get(&blob) {
+            return *index;
+        }
+        let index = self.blobs.len() as u64;
+        self.indices.insert(blob.clone(), index);
+        self.blobs.push(blob);
+        index
+    }
+}
+
+/// String section contents, indexed in the order of their first use.
+#[derive(Debug, Default)]
+struct Strings {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl Strings {
+    fn insert(&mut self, string: &str) -> u64 {
+        if let Some(index) = self.indices.get(string) {
+            return *index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        index
+    }
+}
+
+/// Attributes and types the IR refers to, directly or from the encodings of the other ones.
+#[derive(Debug, Default)]
+struct Collector {
+    attributes: Vec<Attribute>,
+    types: Vec<Type>,
+    seen_attributes: HashSet<Attribute>,
+    seen_types: HashSet<Type>,
+}
+
+impl Numbering for Collector {
+    fn attribute(&mut self, ctx: &mut Context, attribute: Attribute) -> u64 {
+        if self.seen_attributes.insert(attribute) {
+            self.attributes.push(attribute);
+            builtin::write_attribute(ctx, attribute, &mut Out::new(self));
+        }
+        0
+    }
+
+    fn r#type(&mut self, ctx: &mut Context, r#type: Type) -> u64 {
+        if self.seen_types.insert(r#type) {
+            self.types.push(r#type);
+            builtin::write_type(ctx, r#type, &mut Out::new(self));
+        }
+        0
+    }
+
+    fn string(&mut self, _: &str) -> u64 {
+        0
+    }
+}
+
+/// Indices of the numbered attributes and types, and of the strings inserted on the first use.
+struct Numbered<'w> {
+    attributes: &'w Entries<Attribute>,
+    types: &'w Entries<Type>,
+    strings: &'w mut Strings,
+}
+
+impl Numbering for Numbered<'_> {
+    fn attribute(&mut self, _: &mut Context, attribute: Attribute) -> u64 {
+        self.attributes.index(&attribute)
+    }
+
+    fn r#type(&mut self, _: &mut Context, r#type: Type) -> u64 {
+        self.types.index(&r#type)
+    }
+
+    fn string(&mut self, string: &str) -> u64 {
+        self.strings.insert(string)
+    }
+}
+
+impl<'c> BytecodeWriter<'c> {
+    pub fn new(ctx: &'c mut Context, config: BytecodeWriterConfig) -> Self {
+        Self {
+            ctx,
+            config,
+            dialects: vec![],
+            op_names: Entries::default(),
+            attributes: Entries::default(),
+            types: Entries::default(),
+            strings: Strings::default(),
+            properties: Properties::default(),
+            values: HashMap::new(),
+            blocks: HashMap::new(),
+            regions: HashMap::new(),
+            isolated: HashSet::new(),
+        }
+    }
+
+    /// Writes the bytecode of the operation, which may not have results.
+    pub fn write(mut self, op: Operation) -> Result<Vec<u8>, BytecodeError> {
+        if self.config.version > VERSION {
+            return Err(BytecodeError::UnsupportedVersion {
+                version: self.config.version,
+                current: VERSION,
+            });
+        }
+        if !op.results(self.ctx).is_empty() {
+            return Err(BytecodeError::Unsupported(format!(
+                "writing the `{}` top level operation with results",
+                op.name(self.ctx)
+            )));
+        }
+        self.number(op);
+
+        let mut attr_type = Emitter::default();
+        let mut offsets = Emitter::default();
+        offsets.emit_var_int(self.attributes.items.len() as u64);
+        offsets.emit_var_int(self.types.items.len() as u64);
+        let attributes: Vec<Attribute> = self.attributes.items.iter().map(|(_, attribute)| *attribute).collect();
+        let entries = attributes
+            .into_iter()
+            .map(|attribute| self.encode_attribute(attribute))
+            .collect();
+        emit_entries(&mut attr_type, &mut offsets, &self.attributes.groups(), entries);
+        let types: Vec<Type> = self.types.items.iter().map(|(_, r#type)| *r#type).collect();
+        let entries = types.into_iter().map(|r#type| self.encode_type(r#type)).collect();
+        emit_entries(&mut attr_type, &mut offsets, &self.types.groups(), entries);
+
+        let dialect = self.dialect_section();
+        let mut ir = Emitter::default();
+        ir.emit_var_int_with_flag(1, false);
+        self.write_operation(&mut ir, op);
+
+        let mut bytecode = Emitter::default();
+        bytecode.emit_bytes(&MAGIC);
+        bytecode.emit_var_int(self.config.version);
+        bytecode.emit_null_terminated_string(&self.config.producer);
+        bytecode.emit_section(section::STRING, self.string_section());
+        bytecode.emit_section(section::DIALECT, dialect);
+        bytecode.emit_section(section::ATTR_TYPE, attr_type);
+        bytecode.emit_section(section::ATTR_TYPE_OFFSET, offsets);
+        bytecode.emit_section(section::IR, ir);
+        if self.config.version >= version::NATIVE_PROPERTIES_ENCODING {
+            let mut properties = Emitter::default();
+            properties.emit_var_int(self.properties.blobs.len() as u64);
+            for blob in &self.properties.blobs {
+                properties.emit_blob(blob);
+            }
+            bytecode.emit_section(section::PROPERTIES, properties);
+        }
+        Ok(bytecode.into_bytes())
+    }
+
+    /// Numbers the operation names, the attributes, the types, the values and the blocks.
+    fn number(&mut self, root: Operation) {
+        let mut collector = Collector::default();
+        let mut op_names = vec![];
+        let unknown = self.ctx.unknown_loc();
+        for op in root.walk(self.ctx) {
+            let name = op.name(self.ctx).to_string();
+            if !op_names.contains(&name) {
+                op_names.push(name);
+            }
+            let location = op.location(self.ctx);
+            collector.attribute(self.ctx, location);
+            if let Some(dictionary) = self.attribute_dictionary(op) {
+                collector.attribute(self.ctx, dictionary);
+            }
+            if let Some(properties) = op.properties(self.ctx) {
+                if self.config.version >= version::NATIVE_PROPERTIES_ENCODING {
+                    collector.attribute(self.ctx, properties);
+                }
+            }
+            for r#type in op.result_types(self.ctx) {
+                collector.r#type(self.ctx, r#type);
+            }
+            let regions = op.regions(self.ctx).to_vec();
+            for region in regions {
+                let blocks = region.blocks(self.ctx).to_vec();
+                for block in blocks {
+                    for r#type in block.argument_types(self.ctx) {
+                        collector.r#type(self.ctx, r#type);
+                        if self.config.version < version::ELIDE_UNKNOWN_BLOCK_ARG_LOCATION {
+                            collector.attribute(self.ctx, unknown);
+                        }
+                    }
+                }
+            }
+            if !op.regions(self.ctx).is_empty() && op.is_isolated_from_above(self.ctx) {
+                self.isolated.insert(op);
+            }
+        }
+
+        let op_names = op_names
+            .into_iter()
+            .map(|name| {
+                let dialect = name.split_once('.').map(|(dialect, _)| dialect).unwrap_or("");
+                (self.dialect(dialect), name)
+            })
+            .collect();
+        self.op_names = Entries::new(op_names);
+        let attributes = collector
+            .attributes
+            .into_iter()
+            .map(|attribute| {
+                let dialect = match attribute.kind(self.ctx) {
+                    AttributeKind::Opaque { dialect, .. } => dialect.clone(),
+                    _ => "builtin".to_string(),
+                };
+                (self.dialect(&dialect), attribute)
+            })
+            .collect();
+        self.attributes = Entries::new(attributes);
+        let types = collector
+            .types
+            .into_iter()
+            .map(|r#type| {
+                let dialect = match r#type.kind(self.ctx) {
+                    TypeKind::Opaque { dialect, .. } => dialect.clone(),
+                    _ => "builtin".to_string(),
+                };
+                (self.dialect(&dialect), r#type)
+            })
+            .collect();
+        self.types = Entries::new(types);
+
+        let results = root.results(self.ctx).to_vec();
+        for (index, result) in results.into_iter().enumerate() {
+            self.values.insert(result, index as u64);
+        }
+        self.number_regions(root, root.results(self.ctx).len() as u64);
+    }
+
+    fn dialect(&mut self, dialect: &str) -> usize {
+        match self.dialects.iter().position(|candidate| candidate == dialect) {
+            Some(index) => index,
+            None => {
+                self.dialects.push(dialect.to_string());
+                self.dialects.len() - 1
+            }
+        }
+    }
+
+    /// Numbers the values of the regions of the operation starting at `next`, or at zero if it's isolated,
+    /// the values of the nested regions continue after the ones of their parent region.
+    fn number_regions(&mut self, op: Operation, next: u64) {
+        let first = if self.isolated.contains(&op) { 0 } else { next };
+        let regions = op.regions(self.ctx).to_vec();
+        for region in regions {
+            let mut next = first;
+            let blocks = region.blocks(self.ctx).to_vec();
+            for (index, block) in blocks.iter().enumerate() {
+                self.blocks.insert(*block, index as u64);
+                let arguments = block.arguments(self.ctx).to_vec();
+                for argument in arguments {
+                    self.values.insert(argument, next);
+                    next += 1;
+                }
+                let operations = block.operations(self.ctx).to_vec();
+                for nested in operations {
+                    let results = nested.results(self.ctx).to_vec();
+                    for result in results {
+                        self.values.insert(result, next);
+                        next += 1;
+                    }
+                }
+            }
+            self.regions.insert(region, (blocks.len() as u64, next - first));
+            for block in blocks {
+                let operations = block.operations(self.ctx).to_vec();
+                for nested in operations {
+                    self.number_regions(nested, next);
+                }
+            }
+        }
+    }
+
+    /// Builtin encoding of the attribute, or its textual form.
+    fn encode_attribute(&mut self, attribute: Attribute) -> (Vec<u8>, bool) {
+        let mut numbered = Numbered {
+            attributes: &self.attributes,
+            types: &self.types,
+            strings: &mut self.strings,
+        };
+        let mut out = Out::new(&mut numbered);
+        if builtin::write_attribute(self.ctx, attribute, &mut out) {
+            return (out.into_emitter().into_bytes(), true);
+        }
+        let mut printer = Printer::new(self.ctx, local_scope());
+        printer.print_attribute(attribute);
+        (text_entry(printer.finish()), false)
+    }
+
+    /// Builtin encoding of the type, or its textual form.
+    fn encode_type(&mut self, r#type: Type) -> (Vec<u8>, bool) {
+        let mut numbered = Numbered {
+            attributes: &self.attributes,
+            types: &self.types,
+            strings: &mut self.strings,
+        };
+        let mut out = Out::new(&mut numbered);
+        if builtin::write_type(self.ctx, r#type, &mut out) {
+            return (out.into_emitter().into_bytes(), true);
+        }
+        let mut printer = Printer::new(self.ctx, local_scope());
+        printer.print_type(r#type);
+        (text_entry(printer.finish()), false)
+    }
+
+    /// Dictionary of the inherent and discardable attributes, the properties are merged into it before
+    /// their native encoding.
+    fn attribute_dictionary(&mut self, op: Operation) -> Option<Attribute> {
+        let mut attributes = op.attributes(self.ctx).to_vec();
+        if self.config.version < version::NATIVE_PROPERTIES_ENCODING {
+            if let Some(properties) = op.properties(self.ctx) {
+                if let AttributeKind::Dictionary(properties) = properties.kind(self.ctx) {
+                    attributes.extend(properties.iter().cloned());
+                }
+            }
+        }
+        match attributes.is_empty() {
+            true => None,
+            false => Some(self.ctx.dictionary_attr(attributes)),
+        }
+    }
+
+    /// Index of the properties encoding, the attribute index of their dictionary like the upstream one
+    /// of the operations without a native encoding.
+    fn properties_index(&mut self, properties: Attribute) -> u64 {
+        let mut blob = Emitter::default();
+        blob.emit_var_int(self.attributes.index(&properties));
+        self.properties.insert(blob.into_bytes())
+    }
+
+    fn dialect_section(&mut self) -> Emitter {
+        let mut emitter = Emitter::default();
+        emitter.emit_var_int(self.dialects.len() as u64);
+        for dialect in &self.dialects {
+            let name = self.strings.insert(dialect);
+            match self.config.version < version::DIALECT_VERSIONING {
+                true => emitter.emit_var_int(name),
+                false => emitter.emit_var_int_with_flag(name, false),
+            }
+        }
+        if self.config.version >= version::ELIDE_UNKNOWN_BLOCK_ARG_LOCATION {
+            emitter.emit_var_int(self.op_names.items.len() as u64);
+        }
+        let mut names = self.op_names.items.iter();
+        for (dialect, count) in self.op_names.groups() {
+            emitter.emit_var_int(dialect as u64);
+            emitter.emit_var_int(count as u64);
+            for (_, name) in names.by_ref().take(count) {
+                let name = name.split_once('.').map(|(_, name)| name).unwrap_or(name);
+                let index = self.strings.insert(name);
+                match self.config.version < version::NATIVE_PROPERTIES_ENCODING {
+                    true => emitter.emit_var_int(index),
+                    false => emitter.emit_var_int_with_flag(index, false),
+                }
+            }
+        }
+        emitter
+    }
+
+    /// String sizes in the reverse order, followed by the null-terminated strings.
+    fn string_section(&self) -> Emitter {
+        let mut emitter = Emitter::default();
+        emitter.emit_var_int(self.strings.strings.len() as u64);
+        for string in self.strings.strings.iter().rev() {
+            emitter.emit_var_int(string.len() as u64 + 1);
+        }
+        for string in &self.strings.strings {
+            emitter.emit_null_terminated_string(string);
+        }
+        emitter
+    }
+
+    fn write_operation(&mut self, emitter: &mut Emitter, op: Operation) {
+        let ctx = &*self.ctx;
+        emitter.emit_var_int(self.op_names.index(&op.name(ctx).to_string()));
+        let mask_offset = emitter.len();
+        let mut mask = 0;
+        emitter.emit_byte(0);
+        emitter.emit_var_int(self.attributes.index(&op.location(ctx)));
+        if let Some(dictionary) = self.attribute_dictionary(op) {
+            mask |= op_mask::HAS_ATTRS;
+            emitter.emit_var_int(self.attributes.index(&dictionary));
+        }
+        if let Some(properties) = op.properties(self.ctx) {
+            if self.config.version >= version::NATIVE_PROPERTIES_ENCODING {
+                mask |= op_mask::HAS_PROPERTIES;
+                let index = self.properties_index(properties);
+                emitter.emit_var_int(index);
+            }
+        }
+        let ctx = &*self.ctx;
+        if !op.results(ctx).is_empty() {
+            mask |= op_mask::HAS_RESULTS;
+            emitter.emit_var_int(op.results(ctx).len() as u64);
+            for r#type in op.result_types(ctx) {
+                emitter.emit_var_int(self.types.index(&r#type));
+            }
+        }
+        if !op.operands(ctx).is_empty() {
+            mask |= op_mask::HAS_OPERANDS;
+            emitter.emit_var_int(op.operands(ctx).len() as u64);
+            for operand in op.operands(ctx) {
+                emitter.emit_var_int(self.values[operand]);
+            }
+        }
+        if !op.successors(ctx).is_empty() {
+            mask |= op_mask::HAS_SUCCESSORS;
+            emitter.emit_var_int(op.successors(ctx).len() as u64);
+            for successor in op.successors(ctx) {
+                emitter.emit_var_int(self.blocks[successor]);
+            }
+        }
+        let regions = op.regions(ctx).to_vec();
+        if !regions.is_empty() {
+            mask |= op_mask::HAS_INLINE_REGIONS;
+        }
+        emitter.patch_byte(mask_offset, mask);
+        if regions.is_empty() {
+            return;
+        }
+        let isolated = self.isolated.contains(&op);
+        emitter.emit_var_int_with_flag(regions.len() as u64, isolated);
+        if isolated && self.config.version >= version::LAZY_LOADING {
+            let mut section = Emitter::default();
+            for region in regions {
+                self.write_region(&mut section, region);
+            }
+            emitter.emit_section(section::IR, section);
+        } else {
+            for region in regions {
+                self.write_region(emitter, region);
+            }
+        }
+    }
+
+    fn write_region(&mut self, emitter: &mut Emitter, region: Region) {
+        let (count, values) = self.regions[&region];
+        emitter.emit_var_int(count);
+        if count == 0 {
+            return;
+        }
+        emitter.emit_var_int(values);
+        let blocks = region.blocks(self.ctx).to_vec();
+        for block in blocks {
+            self.write_block(emitter, block);
+        }
+    }
+
+    fn write_block(&mut self, emitter: &mut Emitter, block: Block) {
+        let arguments = block.argument_types(self.ctx);
+        let operations = block.operations(self.ctx).to_vec();
+        emitter.emit_var_int_with_flag(operations.len() as u64, !arguments.is_empty());
+        if !arguments.is_empty() {
+            emitter.emit_var_int(arguments.len() as u64);
+            for r#type in arguments {
+                let r#type = self.types.index(&r#type);
+                match self.config.version < version::ELIDE_UNKNOWN_BLOCK_ARG_LOCATION {
+                    true => {
+                        emitter.emit_var_int(r#type);
+                        let unknown = self.ctx.unknown_loc();
+                        emitter.emit_var_int(self.attributes.index(&unknown));
+                    }
+                    false => emitter.emit_var_int_with_flag(r#type, false),
+                }
+            }
+            if self.config.version >= version::USE_LIST_ORDERING {
+                emitter.emit_byte(0);
+            }
+        }
+        for op in operations {
+            self.write_operation(emitter, op);
+        }
+    }
+}
+
+/// Appends the entries to the data and their sizes to the offsets, group by group.
+fn emit_entries(data: &mut Emitter, offsets: &mut Emitter, groups: &[(usize, usize)], entries: Vec<(Vec<u8>, bool)>) {
+    let mut entries = entries.into_iter();
+    for (dialect, count) in groups {
+        offsets.emit_var_int(*dialect as u64);
+        offsets.emit_var_int(*count as u64);
+        for (bytes, custom) in entries.by_ref().take(*count) {
+            offsets.emit_var_int_with_flag(bytes.len() as u64, custom);
+            data.emit_bytes(&bytes);
+        }
+    }
+}
+
+fn local_scope() -> PrinterOptions {
+    PrinterOptions {
+        debug_info: false,
+        local_scope: true,
+        generic_op_form: false,
+    }
+}
+
+/// Null-terminated textual form of the entry without an encoding.
+fn text_entry(text: String) -> Vec<u8> {
+    let mut emitter = Emitter::default();
+    emitter.emit_null_terminated_string(&text);
+    emitter.into_bytes()
+}
@@ -0,0 +1,145 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Context owning the uniqued types and attributes, and the arenas of the IR entities.
+//!
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::attributes::AttributeKind;
+use crate::dialect::DialectRegistry;
+use crate::operation::OperationData;
+use crate::region::{BlockData, RegionData};
+use crate::types::{Signedness, Type, TypeKind};
+use crate::value::ValueData;
+
+/// Uniquer of the immutable types and attributes, equal ones get the same index.
+#[derive(Debug)]
+pub(crate) struct Interner<K> {
+    items: Vec<K>,
+    indices: HashMap<K, u32>,
+}
+
+impl<K> Default for Interner<K> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> Interner<K> {
+    pub fn intern(&mut self, item: K) -> u32 {
+        if let Some(index) = self.indices.get(&item) {
+            return *index;
+        }
+        let index = self.items.len() as u32;
+        self.items.push(item.clone());
+        self.indices.insert(item, index);
+        index
+    }
+
+    pub fn get(&self, index: u32) -> &K {
+        &self.items[index as usize]
+    }
+}
+
+/// Arena of the mutable IR entities, the erased slots are left empty.
+#[derive(Debug)]
+pub(crate) struct Arena<T> {
+    slots: Vec<Option<T>>,
+    kind: &'static str,
+}
+
+impl<T> Arena<T> {
+    fn new(kind: &'static str) -> Self {
+        Self { slots: vec![], kind }
+    }
+
+    pub fn alloc(&mut self, item: T) -> u32 {
+        self.slots.push(Some(item));
+        (self.slots.len() - 1) as u32
+    }
+
+    pub fn get(&self, index: u32) -> &T {
+        match self.slots.get(index as usize) {
+            Some(Some(item)) => item,
+            _ => panic!("use of an erased {}", self.kind),
+        }
+    }
+
+    pub fn get_mut(&mut self, index: u32) -> &mut T {
+        let kind = self.kind;
+        match self.slots.get_mut(index as usize) {
+            Some(Some(item)) => item,
+            _ => panic!("use of an erased {kind}"),
+        }
+    }
+
+    pub fn free(&mut self, index: u32) -> T {
+        let kind = self.kind;
+        self.slots[index as usize]
+            .take()
+            .unwrap_or_else(|| panic!("double erasure of a {kind}"))
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        matches!(self.slots.get(index as usize), Some(Some(_)))
+    }
+}
+
+/// MLIR context counterpart, every IR entity lives in it and is referred to by its handle.
+#[derive(Debug)]
+pub struct Context {
+    pub(crate) types: Interner<TypeKind>,
+    pub(crate) attributes: Interner<AttributeKind>,
+    pub(crate) operations: Arena<OperationData>,
+    pub(crate) blocks: Arena<BlockData>,
+    pub(crate) regions: Arena<RegionData>,
+    pub(crate) values: Arena<ValueData>,
+    pub(crate) dialects: DialectRegistry,
+    /// `i1` of the [AttributeKind::Bool] attributes, the upstream `BoolAttr` is an `IntegerAttr` of it.
+    pub(crate) i1: Type,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        let mut types = Interner::default();
+        let i1 = Type(types.intern(TypeKind::Integer {
+            width: 1,
+            signedness: Signedness::Signless,
+        }));
+        Self {
+            types,
+            attributes: Interner::default(),
+            operations: Arena::new("operation"),
+            blocks: Arena::new("block"),
+            regions: Arena::new("region"),
+            values: Arena::new("value"),
+            dialects: DialectRegistry::default(),
+            i1,
+        }
+    }
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
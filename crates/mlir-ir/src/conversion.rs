@@ -0,0 +1,220 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Dialect conversion of the core IR. The [ConversionPattern]s rewrite the operations the [ConversionTarget]
+//! deems illegal, getting their operands remapped and converted by the [TypeConverter] in the [OpAdaptor],
+//! and the [ConversionPatternRewriter] journals every change, rolling back the failed patterns and the whole
+//! conversion once any operation fails to legalize.
+//!
+
+mod driver;
+mod pattern;
+mod target;
+mod type_converter;
+
+pub use driver::{apply_full_conversion, apply_partial_conversion};
+pub use pattern::{ConversionPattern, ConversionPatternRewriter, ConversionPatternSet, OpAdaptor};
+pub use target::ConversionTarget;
+pub use type_converter::{unrealized_conversion_cast, TypeConverter};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse, print, Context, ConversionError, Location, MatchFailure, Operation, OperationState, PrinterOptions,
+    };
+
+    /// `i32` to `i64`, keeping the other types.
+    fn converter() -> TypeConverter {
+        let mut converter = TypeConverter::new();
+        converter
+            .add_conversion(|_, r#type| Some(r#type))
+            .add_conversion(|ctx, r#type| match r#type.is_signless_integer(ctx, 32) {
+                true => Some(ctx.integer_type(64)),
+                false => None,
+            });
+        converter
+    }
+
+    /// Converts the `src` operation into the `dst` one of the same name, the `src.func` body is moved along.
+    struct Lowering {
+        name: &'static str,
+        benefit: usize,
+        converter: TypeConverter,
+    }
+
+    impl Lowering {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                benefit: 1,
+                converter: converter(),
+            }
+        }
+    }
+
+    impl ConversionPattern for Lowering {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            Some(self.name)
+        }
+
+        fn benefit(&self) -> usize {
+            self.benefit
+        }
+
+        fn type_converter(&self) -> Option<&TypeConverter> {
+            Some(&self.converter)
+        }
+
+        fn match_and_rewrite(
+            &self,
+            op: Operation,
+            adaptor: OpAdaptor,
+            rewriter: &mut ConversionPatternRewriter<'_>,
+        ) -> Result<(), MatchFailure> {
+            let types = op.result_types(rewriter);
+            let types = self
+                .converter
+                .convert_types(rewriter, &types)
+                .expect("the integer types");
+            let mut state = OperationState::new(self.name.replace("src.", "dst."));
+            state
+                .add_operands(adaptor.operands().iter().copied())
+                .add_types(types)
+                .add_regions(op.regions(rewriter).len());
+            let attributes = op.attributes(rewriter).to_vec();
+            for attribute in attributes {
+                let value = match attribute.value.as_integer(rewriter) {
+                    Some(value) => {
+                        let i64 = rewriter.integer_type(64);
+                        rewriter.integer_attr(value, i64)
+                    }
+                    None => attribute.value,
+                };
+                state.add_attribute(attribute.name, value);
+            }
+            let new = rewriter.replace_op_with_new(op, state);
+            let regions = op.regions(rewriter).to_vec();
+            let parents = new.regions(rewriter).to_vec();
+            for (region, parent) in regions.into_iter().zip(parents) {
+                rewriter.inline_region_before(region, parent, None);
+                rewriter.convert_region_types(parent, &self.converter)?;
+            }
+            match self.benefit {
+                1 => Ok(()),
+                _ => Err(rewriter.notify_match_failure(op, "the higher benefit lowering always fails")),
+            }
+        }
+    }
+
+    fn patterns() -> ConversionPatternSet {
+        let mut patterns = ConversionPatternSet::new();
+        for name in ["src.func", "src.constant", "src.add", "src.return"] {
+            patterns.add(Lowering::new(name));
+        }
+        patterns.add(Lowering {
+            benefit: 2,
+            ..Lowering::new("src.add")
+        });
+        patterns
+    }
+
+    fn target() -> ConversionTarget {
+        let mut target = ConversionTarget::new();
+        target
+            .add_legal_dialect("dst")
+            .add_illegal_dialect("src")
+            .add_dynamically_legal_op("dst.add", |ctx, op| {
+                op.result(ctx, 0).r#type(ctx).is_signless_integer(ctx, 64)
+            });
+        target
+    }
+
+    const SOURCE: &str = r#"module {
+  "src.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "src.constant"() {value = 1 : i32} : () -> i32
+    %1 = "src.add"(%arg0, %0) : (i32, i32) -> i32
+    "other.use"(%1) : (i32) -> ()
+    "src.return"(%1) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+}
+"#;
+
+    #[test]
+    fn should_convert_partially() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        apply_partial_conversion(&mut ctx, module, &target(), &patterns()).unwrap();
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "dst.func"() ({
+  ^bb0(%arg0: i64):
+    %0 = "dst.constant"() {value = 1 : i64} : () -> i64
+    %1 = "dst.add"(%arg0, %0) : (i64, i64) -> i64
+    %2 = "builtin.unrealized_conversion_cast"(%1) : (i64) -> i32
+    "other.use"(%2) : (i32) -> ()
+    "dst.return"(%1) : (i64) -> ()
+  }) {sym_name = "f"} : () -> ()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_roll_back_failed_full_conversion() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let error = apply_full_conversion(&mut ctx, module, &target(), &patterns()).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::FailedToLegalize {
+                op: "other.use".to_string(),
+                location: Location::Unknown,
+            }
+        );
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), SOURCE);
+    }
+
+    #[test]
+    fn should_report_illegal_operations() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let ret = module.walk(&ctx).into_iter().find(|op| op.name(&ctx) == "src.return");
+        let location = ctx.file_line_col_loc("lib.rs", 3, 7);
+        ctx.set_location(ret.unwrap(), location);
+        let mut patterns = ConversionPatternSet::new();
+        for name in ["src.func", "src.constant", "src.add"] {
+            patterns.add(Lowering::new(name));
+        }
+        patterns.add(Lowering {
+            benefit: 2,
+            ..Lowering::new("src.return")
+        });
+        let error = apply_partial_conversion(&mut ctx, module, &target(), &patterns).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"loc("lib.rs":3:7): failed to legalize operation 'src.return' that was explicitly marked illegal"#
+        );
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), SOURCE);
+    }
+}
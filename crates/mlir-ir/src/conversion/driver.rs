@@ -0,0 +1,314 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Partial and full conversion drivers, like the upstream `applyPartialConversion` and `applyFullConversion`.
+//!
+
+use crate::conversion::pattern::CAST;
+use crate::conversion::{ConversionPattern, ConversionPatternRewriter, ConversionPatternSet, ConversionTarget};
+use crate::{Context, ConversionError, Location, Operation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// The unknown operations may stay unconverted.
+    Partial,
+    /// Every operation has to end up legal.
+    Full,
+}
+
+/// Converts the operations nested in the `op` into the legal ones, keeping the unknown operations that have
+/// no matching patterns. The IR is left untouched when any illegal operation fails to legalize.
+pub fn apply_partial_conversion(
+    ctx: &mut Context,
+    op: Operation,
+    target: &ConversionTarget,
+    patterns: &ConversionPatternSet,
+) -> Result<(), ConversionError> {
+    convert(ctx, op, target, patterns, Mode::Partial)
+}
+
+/// Converts the operations nested in the `op` into the legal ones, the unknown operations are illegal.
+/// The IR is left untouched when any operation fails to legalize.
+pub fn apply_full_conversion(
+    ctx: &mut Context,
+    op: Operation,
+    target: &ConversionTarget,
+    patterns: &ConversionPatternSet,
+) -> Result<(), ConversionError> {
+    convert(ctx, op, target, patterns, Mode::Full)
+}
+
+fn convert(
+    ctx: &mut Context,
+    root: Operation,
+    target: &ConversionTarget,
+    patterns: &ConversionPatternSet,
+    mode: Mode,
+) -> Result<(), ConversionError> {
+    let mut operations = root.walk(ctx);
+    operations.retain(|op| *op != root);
+    let mut driver = Driver {
+        rewriter: ConversionPatternRewriter::new(ctx),
+        target,
+        patterns,
+        mode,
+        applying: vec![],
+    };
+    for op in operations {
+        if driver.rewriter.is_replaced(op) {
+            continue;
+        }
+        if let Err(error) = driver.legalize(op) {
+            driver.rewriter.rollback(0);
+            return Err(error);
+        }
+    }
+    Ok(driver.rewriter.commit()?)
+}
+
+struct Driver<'c> {
+    rewriter: ConversionPatternRewriter<'c>,
+    target: &'c ConversionTarget,
+    patterns: &'c ConversionPatternSet,
+    mode: Mode,
+    /// Patterns being applied, they aren't applied again to the operations they create.
+    applying: Vec<&'c str>,
+}
+
+impl<'c> Driver<'c> {
+    fn legalize(&mut self, op: Operation) -> Result<(), ConversionError> {
+        let ctx: &Context = &self.rewriter;
+        let legality = match op.name(ctx) {
+            CAST => Some(true),
+            _ => self.target.is_legal(ctx, op),
+        };
+        match (legality, self.mode) {
+            (Some(true), _) => Ok(()),
+            (None, Mode::Partial) => {
+                self.apply_pattern(op);
+                Ok(())
+            }
+            _ if self.apply_pattern(op) => Ok(()),
+            _ => Err(self.failure(op, legality == Some(false))),
+        }
+    }
+
+    /// Applies the first pattern legalizing the operation with the ones it creates, rolling back the others.
+    fn apply_pattern(&mut self, op: Operation) -> bool {
+        let patterns: &'c ConversionPatternSet = self.patterns;
+        for pattern in patterns.matching(op.name(&self.rewriter)) {
+            if self.applying.contains(&pattern.name()) {
+                continue;
+            }
+            let checkpoint = self.rewriter.checkpoint();
+            match self.try_pattern(pattern, op, checkpoint) {
+                true => return true,
+                false => self.rewriter.rollback(checkpoint),
+            }
+        }
+        false
+    }
+
+    fn try_pattern(&mut self, pattern: &'c dyn ConversionPattern, op: Operation, checkpoint: usize) -> bool {
+        let Ok(adaptor) = self.rewriter.adaptor(op, pattern.type_converter()) else {
+            return false;
+        };
+        self.rewriter.set_type_converter(pattern.type_converter());
+        if pattern.match_and_rewrite(op, adaptor, &mut self.rewriter).is_err() {
+            return false;
+        }
+        self.applying.push(pattern.name());
+        let legalized = self.legalize_rewrite(op, checkpoint);
+        self.applying.pop();
+        legalized
+    }
+
+    /// Legalizes the operations created by the pattern and the root it modified in place.
+    fn legalize_rewrite(&mut self, op: Operation, checkpoint: usize) -> bool {
+        for created in self.rewriter.created_since(checkpoint) {
+            if created.is_alive(&self.rewriter)
+                && !self.rewriter.is_replaced(created)
+                && self.legalize(created).is_err()
+            {
+                return false;
+            }
+        }
+        let ctx: &Context = &self.rewriter;
+        match self.rewriter.is_replaced(op) {
+            true => true,
+            false => match self.target.is_legal(ctx, op) {
+                Some(legal) => legal,
+                None => self.mode == Mode::Partial,
+            },
+        }
+    }
+
+    fn failure(&self, op: Operation, illegal: bool) -> ConversionError {
+        let ctx: &Context = &self.rewriter;
+        let (op, location) = (op.name(ctx).to_string(), Location::new(ctx, op.location(ctx)));
+        match illegal {
+            true => ConversionError::IllegalOperation { op, location },
+            false => ConversionError::FailedToLegalize { op, location },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, print, MatchFailure, OpAdaptor, OperationState, PrinterOptions};
+
+    type Rewrite = fn(Operation, OpAdaptor, &mut ConversionPatternRewriter<'_>) -> Result<(), MatchFailure>;
+
+    /// Rewrites the root operation of the name through the function.
+    struct Pattern(&'static str, Rewrite);
+
+    impl ConversionPattern for Pattern {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            Some(self.0)
+        }
+
+        fn match_and_rewrite(
+            &self,
+            op: Operation,
+            adaptor: OpAdaptor,
+            rewriter: &mut ConversionPatternRewriter<'_>,
+        ) -> Result<(), MatchFailure> {
+            (self.1)(op, adaptor, rewriter)
+        }
+    }
+
+    fn replace(name: &str, op: Operation, adaptor: OpAdaptor, rewriter: &mut ConversionPatternRewriter<'_>) {
+        let mut state = OperationState::new(name);
+        state
+            .add_operands(adaptor.operands().iter().copied())
+            .add_types(op.result_types(rewriter));
+        rewriter.replace_op_with_new(op, state);
+    }
+
+    /// `src.a` to `src.b` to `dst.b`, the `other.mark` marked legal in place, `other.unknown` to `dst.unknown`
+    /// and `src.loop` to itself.
+    fn patterns() -> ConversionPatternSet {
+        let mut patterns = ConversionPatternSet::new();
+        patterns
+            .add(Pattern("src.a", |op, adaptor, rewriter| {
+                replace("src.b", op, adaptor, rewriter);
+                Ok(())
+            }))
+            .add(Pattern("src.b", |op, adaptor, rewriter| {
+                replace("dst.b", op, adaptor, rewriter);
+                Ok(())
+            }))
+            .add(Pattern("other.mark", |op, _, rewriter| {
+                rewriter.modify_op_in_place(op, |ctx| {
+                    let unit = ctx.unit_attr();
+                    ctx.set_attribute(op, "legal", unit);
+                });
+                Ok(())
+            }))
+            .add(Pattern("other.unknown", |op, adaptor, rewriter| {
+                replace("dst.unknown", op, adaptor, rewriter);
+                Ok(())
+            }))
+            .add(Pattern("src.loop", |op, adaptor, rewriter| {
+                replace("src.loop", op, adaptor, rewriter);
+                Ok(())
+            }));
+        patterns
+    }
+
+    fn target() -> ConversionTarget {
+        let mut target = ConversionTarget::new();
+        target
+            .add_legal_dialect("dst")
+            .add_illegal_dialect("src")
+            .add_dynamically_legal_op("other.mark", |ctx, op| op.attribute(ctx, "legal").is_some());
+        target
+    }
+
+    const SOURCE: &str = r#"module {
+  %0 = "src.a"() : () -> i32
+  "other.mark"(%0) : (i32) -> ()
+  "other.unknown"(%0) : (i32) -> ()
+  "other.kept"(%0) : (i32) -> ()
+}
+"#;
+
+    #[test]
+    fn should_legalize_the_created_and_the_modified_operations() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        apply_partial_conversion(&mut ctx, module, &target(), &patterns()).unwrap();
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  %0 = "dst.b"() : () -> i32
+  "other.mark"(%0) {legal} : (i32) -> ()
+  "dst.unknown"(%0) : (i32) -> ()
+  "other.kept"(%0) : (i32) -> ()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_fail_the_full_conversion_on_the_unknown_operations() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let error = apply_full_conversion(&mut ctx, module, &target(), &patterns()).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::FailedToLegalize {
+                op: "other.kept".to_string(),
+                location: Location::Unknown,
+            }
+        );
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), SOURCE);
+
+        let mut target = target();
+        target.add_legal_op("other.kept");
+        apply_full_conversion(&mut ctx, module, &target, &patterns()).unwrap();
+        assert!(module
+            .walk(&ctx)
+            .iter()
+            .all(|op| target.is_legal(&ctx, *op) != Some(false)));
+    }
+
+    #[test]
+    fn should_not_apply_the_patterns_to_their_own_operations() {
+        let source = r#"module {
+  %0 = "src.a"() : () -> i32
+  "src.loop"(%0) : (i32) -> ()
+}
+"#;
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        let error = apply_partial_conversion(&mut ctx, module, &target(), &patterns()).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::IllegalOperation {
+                op: "src.loop".to_string(),
+                location: Location::Unknown,
+            }
+        );
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), source);
+    }
+}
@@ -0,0 +1,705 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Conversion patterns and the rewriter journaling their changes to be rolled back,
+//! like the upstream `ConversionPattern` and `ConversionPatternRewriter`.
+//!
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::conversion::TypeConverter;
+use crate::{
+    Attribute, Block, Builder, Context, InsertionPoint, IrError, MatchFailure, NamedAttribute, OpOperand, Operation,
+    OperationState, Region, Type, Value,
+};
+
+pub(crate) const CAST: &str = "builtin.unrealized_conversion_cast";
+
+/// Conversion pattern of a root operation, like the upstream `ConversionPattern`.
+pub trait ConversionPattern: Send + Sync {
+    /// Debug name of the pattern, like `AddIOpLowering`.
+    fn name(&self) -> &str;
+
+    /// Name of the matched root operation, like `arith.addi`, `None` to match any.
+    fn root_name(&self) -> Option<&str>;
+
+    /// The patterns with the higher benefit are tried first.
+    fn benefit(&self) -> usize {
+        1
+    }
+
+    /// Converter of the adaptor operand types, `None` keeps the remapped operands as is.
+    fn type_converter(&self) -> Option<&TypeConverter> {
+        None
+    }
+
+    /// Matches the root operation, rewriting it on success, the changes of the failed matches are rolled back.
+    fn match_and_rewrite(
+        &self,
+        op: Operation,
+        adaptor: OpAdaptor,
+        rewriter: &mut ConversionPatternRewriter<'_>,
+    ) -> Result<(), MatchFailure>;
+}
+
+/// Operands of the matched operation, remapped to the replacement values of the already converted operations
+/// and materialized to the converted types, like the upstream `OpAdaptor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpAdaptor {
+    operands: Vec<Value>,
+}
+
+impl OpAdaptor {
+    pub fn operands(&self) -> &[Value] {
+        &self.operands
+    }
+
+    pub fn operand(&self, index: usize) -> Value {
+        self.operands[index]
+    }
+}
+
+/// Conversion patterns, like the upstream `RewritePatternSet` of the conversion patterns.
+#[derive(Default)]
+pub struct ConversionPatternSet {
+    patterns: Vec<Box<dyn ConversionPattern>>,
+}
+
+impl ConversionPatternSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, pattern: impl ConversionPattern + 'static) -> &mut Self {
+        self.patterns.push(Box::new(pattern));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Patterns of the root operation, the higher benefit ones go first, the equal ones keep their order.
+    pub(crate) fn matching(&self, root_name: &str) -> Vec<&dyn ConversionPattern> {
+        let mut patterns: Vec<&dyn ConversionPattern> = self
+            .patterns
+            .iter()
+            .map(Box::as_ref)
+            .filter(|pattern| pattern.root_name().is_none_or(|name| name == root_name))
+            .collect();
+        patterns.sort_by_key(|pattern| std::cmp::Reverse(pattern.benefit()));
+        patterns
+    }
+}
+
+impl fmt::Debug for ConversionPatternSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.patterns.iter().map(|pattern| pattern.name()))
+            .finish()
+    }
+}
+
+/// Undoable change of the IR.
+enum Change {
+    Created(Operation),
+    Replaced(Operation),
+    Mapped(Value),
+    Modified {
+        op: Operation,
+        operands: Vec<Value>,
+        attributes: Vec<NamedAttribute>,
+        successors: Vec<Block>,
+        location: Attribute,
+        result_types: Vec<Type>,
+    },
+    BlockMoved {
+        block: Block,
+        region: Region,
+        next: Option<Block>,
+    },
+    ArgumentType {
+        argument: Value,
+        r#type: Type,
+    },
+    UsesReplaced {
+        from: Value,
+        uses: Vec<OpOperand>,
+    },
+}
+
+/// Builder of the conversions, journaling the changes to roll back the failed patterns.
+/// The replaced and the erased operations are kept in place until the whole conversion succeeds,
+/// their results are remapped to the replacement values handed to the later patterns in their [OpAdaptor]s.
+/// The patterns change the IR through the rewriter, the [Context] it dereferences to is for the types
+/// and the attributes.
+pub struct ConversionPatternRewriter<'c> {
+    builder: Builder<'c>,
+    journal: Vec<Change>,
+    mapping: HashMap<Value, Value>,
+    /// Replaced operations with the converter of their pattern, materializing the original result types.
+    replaced: Vec<(Operation, Option<&'c TypeConverter>)>,
+    converter: Option<&'c TypeConverter>,
+}
+
+impl<'c> ConversionPatternRewriter<'c> {
+    pub fn new(ctx: &'c mut Context) -> Self {
+        Self {
+            builder: Builder::new(ctx),
+            journal: vec![],
+            mapping: HashMap::new(),
+            replaced: vec![],
+            converter: None,
+        }
+    }
+
+    /// Converter of the pattern being applied.
+    pub(crate) fn set_type_converter(&mut self, converter: Option<&'c TypeConverter>) {
+        self.converter = converter;
+    }
+
+    pub fn insertion_point(&self) -> Option<InsertionPoint> {
+        self.builder.insertion_point()
+    }
+
+    pub fn set_insertion_point(&mut self, insertion_point: InsertionPoint) {
+        self.builder.set_insertion_point(insertion_point);
+    }
+
+    pub fn set_insertion_point_to_start(&mut self, block: Block) {
+        self.builder.set_insertion_point_to_start(block);
+    }
+
+    pub fn set_insertion_point_to_end(&mut self, block: Block) {
+        self.builder.set_insertion_point_to_end(block);
+    }
+
+    pub fn set_insertion_point_before(&mut self, op: Operation) {
+        self.builder.set_insertion_point_before(op);
+    }
+
+    pub fn set_insertion_point_after(&mut self, op: Operation) {
+        self.builder.set_insertion_point_after(op);
+    }
+
+    /// Creates the operation and inserts it at the insertion point.
+    pub fn create(&mut self, state: OperationState) -> Operation {
+        let op = self.builder.create(state);
+        self.journal.push(Change::Created(op));
+        op
+    }
+
+    /// Changes the operation through the closure, its operands, attributes, successors, location and result
+    /// types are restored on the rollback, like the upstream `modifyOpInPlace`.
+    pub fn modify_op_in_place<R>(&mut self, op: Operation, modify: impl FnOnce(&mut Context) -> R) -> R {
+        let ctx: &Context = &self.builder;
+        self.journal.push(Change::Modified {
+            op,
+            operands: op.operands(ctx).to_vec(),
+            attributes: op.attributes(ctx).to_vec(),
+            successors: op.successors(ctx).to_vec(),
+            location: op.location(ctx),
+            result_types: op.result_types(ctx),
+        });
+        modify(&mut self.builder)
+    }
+
+    /// Maps the results of the operation to the values, the operation is erased once the conversion succeeds.
+    pub fn replace_op(&mut self, op: Operation, values: &[Value]) {
+        let results = op.results(&self.builder).to_vec();
+        assert_eq!(
+            results.len(),
+            values.len(),
+            "replacing `{}` with a different number of values",
+            op.name(&self.builder)
+        );
+        for (result, value) in results.into_iter().zip(values) {
+            self.mapping.insert(result, *value);
+            self.journal.push(Change::Mapped(result));
+        }
+        self.replaced.push((op, self.converter));
+        self.journal.push(Change::Replaced(op));
+    }
+
+    /// Creates the operation before the replaced one, replacing its results.
+    pub fn replace_op_with_new(&mut self, op: Operation, state: OperationState) -> Operation {
+        self.set_insertion_point_before(op);
+        let new = self.create(state);
+        let results = new.results(&self.builder).to_vec();
+        self.replace_op(op, &results);
+        new
+    }
+
+    /// Erases the operation once the conversion succeeds, its results may not have uses by then.
+    pub fn erase_op(&mut self, op: Operation) {
+        self.replaced.push((op, None));
+        self.journal.push(Change::Replaced(op));
+    }
+
+    /// Moves the blocks of the region before the `before` block, or to the end of the `parent` region.
+    pub fn inline_region_before(&mut self, region: Region, parent: Region, before: Option<Block>) {
+        let blocks = region.blocks(&self.builder).to_vec();
+        for (index, block) in blocks.iter().enumerate() {
+            // The blocks are moved back in the reverse order, each one before the block that followed it.
+            let next = blocks.get(index + 1).copied();
+            self.journal.push(Change::BlockMoved {
+                block: *block,
+                region,
+                next,
+            });
+            match before {
+                Some(before) => self.builder.insert_block_before(before, *block),
+                None => self.builder.append_block(parent, *block),
+            }
+        }
+    }
+
+    /// Converts the argument types of the region blocks, their former uses take the source materializations
+    /// of the converted arguments, like the upstream `convertRegionTypes`.
+    pub fn convert_region_types(&mut self, region: Region, converter: &TypeConverter) -> Result<(), MatchFailure> {
+        let blocks = region.blocks(&self.builder).to_vec();
+        for block in blocks {
+            let arguments = block.arguments(&self.builder).to_vec();
+            for (index, argument) in arguments.into_iter().enumerate() {
+                let r#type = argument.r#type(&self.builder);
+                let converted = converter
+                    .convert_type(&mut self.builder, r#type)
+                    .ok_or_else(|| MatchFailure(format!("the block argument #{index} type isn't convertible")))?;
+                if converted == r#type {
+                    continue;
+                }
+                self.builder.set_type(argument, converted);
+                self.journal.push(Change::ArgumentType { argument, r#type });
+                let uses = argument.uses(&self.builder).to_vec();
+                let location = self.block_location(block);
+                let insertion_point = self.builder.insertion_point();
+                self.builder.set_insertion_point_to_start(block);
+                let cast = converter.materialize_source(&mut self.builder, r#type, &[argument], location);
+                self.restore_insertion_point(insertion_point);
+                if let Some(materialization) = cast.defining_op(&self.builder) {
+                    self.journal.push(Change::Created(materialization));
+                }
+                for operand in &uses {
+                    self.builder.set_operand(operand.owner, operand.index, cast);
+                }
+                self.journal.push(Change::UsesReplaced { from: argument, uses });
+            }
+        }
+        Ok(())
+    }
+
+    /// The replacement value of the already converted one, or the value itself.
+    pub fn remapped(&self, mut value: Value) -> Value {
+        while let Some(mapped) = self.mapping.get(&value) {
+            value = *mapped;
+        }
+        value
+    }
+
+    /// Reason the pattern didn't match the operation.
+    pub fn notify_match_failure(&self, op: Operation, reason: impl fmt::Display) -> MatchFailure {
+        MatchFailure(format!("`{}`: {reason}", op.name(&self.builder)))
+    }
+
+    /// Whether the operation is replaced or erased, or is nested in such one.
+    pub(crate) fn is_replaced(&self, op: Operation) -> bool {
+        let ctx: &Context = &self.builder;
+        self.replaced
+            .iter()
+            .any(|(replaced, _)| *replaced == op || replaced.is_ancestor_of(ctx, op))
+    }
+
+    pub(crate) fn checkpoint(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Operations created since the checkpoint, with the materializations.
+    pub(crate) fn created_since(&self, checkpoint: usize) -> Vec<Operation> {
+        let created = self.journal[checkpoint..].iter().filter_map(|change| match change {
+            Change::Created(op) => Some(*op),
+            _ => None,
+        });
+        created.collect()
+    }
+
+    /// Operands of the operation remapped and materialized to the types of the converter.
+    pub(crate) fn adaptor(
+        &mut self,
+        op: Operation,
+        converter: Option<&TypeConverter>,
+    ) -> Result<OpAdaptor, MatchFailure> {
+        let mut operands = vec![];
+        let values = op.operands(&self.builder).to_vec();
+        for (index, operand) in values.into_iter().enumerate() {
+            let remapped = self.remapped(operand);
+            let Some(converter) = converter else {
+                operands.push(remapped);
+                continue;
+            };
+            let original = operand.r#type(&self.builder);
+            let Some(r#type) = converter.convert_type(&mut self.builder, original) else {
+                return Err(self.notify_match_failure(op, format!("the operand #{index} type isn't convertible")));
+            };
+            operands.push(self.materialize_target(converter, remapped, r#type, op));
+        }
+        Ok(OpAdaptor { operands })
+    }
+
+    /// The value of the converted type, looking through the casts of the converted values.
+    fn materialize_target(&mut self, converter: &TypeConverter, value: Value, r#type: Type, op: Operation) -> Value {
+        let ctx: &Context = &self.builder;
+        if value.r#type(ctx) == r#type {
+            return value;
+        }
+        if let Some(cast) = value.defining_op(ctx).filter(|cast| cast.name(ctx) == CAST) {
+            if let [input] = cast.operands(ctx) {
+                if input.r#type(ctx) == r#type {
+                    return *input;
+                }
+            }
+        }
+        let location = op.location(ctx);
+        let insertion_point = self.builder.insertion_point();
+        self.builder.set_insertion_point_before(op);
+        let materialized = converter.materialize_target(&mut self.builder, r#type, &[value], location);
+        self.restore_insertion_point(insertion_point);
+        if let Some(materialization) = materialized.defining_op(&self.builder) {
+            self.journal.push(Change::Created(materialization));
+        }
+        materialized
+    }
+
+    /// Undoes the changes since the checkpoint, in the reverse order.
+    pub(crate) fn rollback(&mut self, checkpoint: usize) {
+        while self.journal.len() > checkpoint {
+            let change = self.journal.pop().expect("the journal is longer than the checkpoint");
+            let ctx: &mut Context = &mut self.builder;
+            match change {
+                Change::Created(op) => {
+                    ctx.drop_all_references(op);
+                    ctx.erase_operation(op)
+                        .expect("the later uses of the created operation are rolled back");
+                }
+                Change::Replaced(op) => {
+                    let position = self.replaced.iter().rposition(|(replaced, _)| *replaced == op);
+                    self.replaced.remove(position.expect("the replaced operation"));
+                }
+                Change::Mapped(value) => {
+                    self.mapping.remove(&value);
+                }
+                Change::Modified {
+                    op,
+                    operands,
+                    attributes,
+                    successors,
+                    location,
+                    result_types,
+                } => {
+                    ctx.set_operands(op, operands);
+                    let names: Vec<String> = op
+                        .attributes(ctx)
+                        .iter()
+                        .map(|attribute| attribute.name.clone())
+                        .collect();
+                    for name in names {
+                        ctx.remove_attribute(op, &name);
+                    }
+                    for attribute in attributes {
+                        ctx.set_attribute(op, &attribute.name, attribute.value);
+                    }
+                    for (index, successor) in successors.into_iter().enumerate() {
+                        ctx.set_successor(op, index, successor);
+                    }
+                    ctx.set_location(op, location);
+                    let results = op.results(ctx).to_vec();
+                    for (result, r#type) in results.into_iter().zip(result_types) {
+                        ctx.set_type(result, r#type);
+                    }
+                }
+                Change::BlockMoved { block, region, next } => match next {
+                    Some(next) => ctx.insert_block_before(next, block),
+                    None => ctx.append_block(region, block),
+                },
+                Change::ArgumentType { argument, r#type } => ctx.set_type(argument, r#type),
+                Change::UsesReplaced { from, uses } => {
+                    for operand in uses {
+                        ctx.set_operand(operand.owner, operand.index, from);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces the uses of the replaced results, materializing the original types for the unconverted users,
+    /// then erases the replaced operations and the unused materializations.
+    pub(crate) fn commit(mut self) -> Result<(), IrError> {
+        let replaced = std::mem::take(&mut self.replaced);
+        for (op, converter) in &replaced {
+            let results = op.results(&self.builder).to_vec();
+            for result in results {
+                let value = self.remapped(result);
+                if value == result {
+                    continue;
+                }
+                let r#type = result.r#type(&self.builder);
+                let value = match value.r#type(&self.builder) == r#type || result.use_empty(&self.builder) {
+                    true => value,
+                    false => {
+                        let location = op.location(&self.builder);
+                        self.builder.set_insertion_point_before(*op);
+                        let materialized = match converter {
+                            Some(converter) => {
+                                converter.materialize_source(&mut self.builder, r#type, &[value], location)
+                            }
+                            None => super::unrealized_conversion_cast(&mut self.builder, r#type, &[value], location),
+                        };
+                        if let Some(materialization) = materialized.defining_op(&self.builder) {
+                            self.journal.push(Change::Created(materialization));
+                        }
+                        materialized
+                    }
+                };
+                self.builder.replace_all_uses_with(result, value);
+            }
+        }
+        let ctx: &mut Context = &mut self.builder;
+        let alive: Vec<Operation> = replaced
+            .into_iter()
+            .map(|(op, _)| op)
+            .filter(|op| op.is_alive(ctx))
+            .collect();
+        for op in &alive {
+            ctx.drop_all_references(*op);
+        }
+        for op in alive {
+            if op.is_alive(ctx) {
+                ctx.erase_operation(op)?;
+            }
+        }
+        for change in self.journal.iter().rev() {
+            if let Change::Created(op) = change {
+                let unused = |op: Operation| op.results(ctx).iter().all(|result| result.use_empty(ctx));
+                if op.is_alive(ctx) && op.name(ctx) == CAST && unused(*op) {
+                    ctx.erase_operation(*op)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn block_location(&mut self, block: Block) -> Attribute {
+        match block.parent_op(&self.builder) {
+            Some(op) => op.location(&self.builder),
+            None => self.builder.unknown_loc(),
+        }
+    }
+
+    fn restore_insertion_point(&mut self, insertion_point: Option<InsertionPoint>) {
+        match insertion_point {
+            Some(insertion_point) => self.builder.set_insertion_point(insertion_point),
+            None => self.builder.clear_insertion_point(),
+        }
+    }
+}
+
+impl Deref for ConversionPatternRewriter<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.builder
+    }
+}
+
+impl DerefMut for ConversionPatternRewriter<'_> {
+    fn deref_mut(&mut self) -> &mut Context {
+        &mut self.builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, print, PrinterOptions};
+
+    /// Matches nothing, named after its root and benefit.
+    struct Named(&'static str, Option<&'static str>, usize);
+
+    impl ConversionPattern for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            self.1
+        }
+
+        fn benefit(&self) -> usize {
+            self.2
+        }
+
+        fn match_and_rewrite(
+            &self,
+            op: Operation,
+            _adaptor: OpAdaptor,
+            rewriter: &mut ConversionPatternRewriter<'_>,
+        ) -> Result<(), MatchFailure> {
+            Err(rewriter.notify_match_failure(op, "never matches"))
+        }
+    }
+
+    const SOURCE: &str = r#""builtin.module"() ({
+  "test.region"() ({
+  ^bb0(%arg0: i32, %arg1: i64):
+    %0 = "test.def"(%arg0) : (i32) -> i32
+    %1 = "builtin.unrealized_conversion_cast"(%arg1) : (i64) -> i32
+    "test.use"(%0, %arg0, %1) : (i32, i32, i32) -> ()
+  }) : () -> ()
+  "test.target"() ({
+    "test.terminator"() : () -> ()
+  }) : () -> ()
+}) : () -> ()
+"#;
+
+    /// `i32` to `i64`, keeping the other types.
+    fn converter() -> TypeConverter {
+        let mut converter = TypeConverter::new();
+        converter
+            .add_conversion(|_, r#type| Some(r#type))
+            .add_conversion(|ctx, r#type| match r#type.is_signless_integer(ctx, 32) {
+                true => Some(ctx.integer_type(64)),
+                false => None,
+            });
+        converter
+    }
+
+    fn find(ctx: &Context, module: Operation, name: &str) -> Operation {
+        let op = module.walk(ctx).into_iter().find(|op| op.name(ctx) == name);
+        op.expect("the operation of the name")
+    }
+
+    #[test]
+    fn should_match_the_patterns_by_benefit() {
+        let mut patterns = ConversionPatternSet::new();
+        patterns
+            .add(Named("a", Some("test.def"), 1))
+            .add(Named("any", None, 2))
+            .add(Named("b", Some("test.def"), 2))
+            .add(Named("c", Some("test.use"), 3));
+        let names = |root: &str| {
+            patterns
+                .matching(root)
+                .iter()
+                .map(|pattern| pattern.name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("test.def"), vec!["any", "b", "a"]);
+        assert_eq!(names("test.use"), vec!["c", "any"]);
+        assert_eq!(names("test.unknown"), vec!["any"]);
+        assert_eq!(format!("{patterns:?}"), r#"["a", "any", "b", "c"]"#);
+    }
+
+    #[test]
+    fn should_remap_and_materialize_the_adaptor_operands() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let (def, r#use) = (find(&ctx, module, "test.def"), find(&ctx, module, "test.use"));
+        let [_, argument, cast] = r#use.operands(&ctx)[..] else {
+            panic!("expected three operands");
+        };
+        let converter = converter();
+        let mut rewriter = ConversionPatternRewriter::new(&mut ctx);
+        let i64 = rewriter.integer_type(64);
+        let mut state = OperationState::new("test.new");
+        state.add_operands([argument]).add_types([i64]);
+        let new = rewriter.replace_op_with_new(def, state);
+        let replacement = new.result(&rewriter, 0);
+        assert!(rewriter.is_replaced(def) && !rewriter.is_replaced(new));
+        assert_eq!(rewriter.remapped(def.result(&rewriter, 0)), replacement);
+
+        let adaptor = rewriter.adaptor(r#use, None).unwrap();
+        assert_eq!(adaptor.operands(), [replacement, argument, cast]);
+
+        let checkpoint = rewriter.checkpoint();
+        let adaptor = rewriter.adaptor(r#use, Some(&converter)).unwrap();
+        let [materialized] = rewriter.created_since(checkpoint)[..] else {
+            panic!("expected the materialization of the block argument");
+        };
+        assert_eq!(materialized.name(&rewriter), CAST);
+        assert_eq!(materialized.operands(&rewriter), [argument]);
+        let input = cast.defining_op(&rewriter).unwrap().operand(&rewriter, 0);
+        assert_eq!(
+            adaptor.operands(),
+            [replacement, materialized.result(&rewriter, 0), input],
+            "the casts of the converted values are looked through"
+        );
+    }
+
+    #[test]
+    fn should_roll_back_to_the_checkpoint() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let original = print(&ctx, module, PrinterOptions::default());
+        let (region, target) = (find(&ctx, module, "test.region"), find(&ctx, module, "test.target"));
+        let (def, r#use) = (find(&ctx, module, "test.def"), find(&ctx, module, "test.use"));
+        let converter = converter();
+        let mut rewriter = ConversionPatternRewriter::new(&mut ctx);
+
+        rewriter.modify_op_in_place(r#use, |ctx| {
+            let unit = ctx.unit_attr();
+            ctx.set_attribute(r#use, "test.modified", unit);
+        });
+        let checkpoint = rewriter.checkpoint();
+        let argument = def.operand(&rewriter, 0);
+        rewriter.modify_op_in_place(r#use, |ctx| ctx.set_operands(r#use, vec![argument; 3]));
+        let mut state = OperationState::new("test.new");
+        let i32 = rewriter.integer_type(32);
+        state.add_types([i32]);
+        let new = rewriter.replace_op_with_new(def, state);
+        let (source, parent) = (region.region(&rewriter, 0), target.region(&rewriter, 0));
+        let terminator = parent.blocks(&rewriter)[0];
+        rewriter.inline_region_before(source, parent, Some(terminator));
+        rewriter.convert_region_types(parent, &converter).unwrap();
+        assert_eq!(
+            print(&rewriter, target, PrinterOptions::default()),
+            r#""test.target"() ({
+^bb0(%arg0: i64, %arg1: i64):
+  %0 = "builtin.unrealized_conversion_cast"(%arg0) : (i64) -> i32
+  %1 = "test.new"() : () -> i32
+  %2 = "test.def"(%0) : (i32) -> i32
+  %3 = "builtin.unrealized_conversion_cast"(%arg1) : (i64) -> i32
+  "test.use"(%0, %0, %0) {test.modified} : (i32, i32, i32) -> ()
+^bb1:  // no predecessors
+  "test.terminator"() : () -> ()
+}) : () -> ()
+"#
+        );
+
+        rewriter.rollback(checkpoint);
+        assert!(!new.is_alive(&rewriter) && !rewriter.is_replaced(def));
+        assert!(region.region(&rewriter, 0).blocks(&rewriter).len() == 1);
+        let unit = rewriter.unit_attr();
+        assert_eq!(r#use.attribute(&rewriter, "test.modified"), Some(unit));
+        rewriter.rollback(0);
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), original);
+    }
+}
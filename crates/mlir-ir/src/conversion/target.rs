@@ -0,0 +1,184 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Legality of the operations after the conversion, like the upstream `ConversionTarget`.
+//!
+
+use std::collections::HashMap;
+
+use crate::{Context, Operation};
+
+type LegalityCallback = Box<dyn Fn(&Context, Operation) -> bool + Send + Sync>;
+
+enum Legality {
+    Legal,
+    Illegal,
+    Dynamic(LegalityCallback),
+}
+
+impl Legality {
+    fn is_legal(&self, ctx: &Context, op: Operation) -> bool {
+        match self {
+            Legality::Legal => true,
+            Legality::Illegal => false,
+            Legality::Dynamic(callback) => callback(ctx, op),
+        }
+    }
+}
+
+/// Legal, illegal and dynamically legal operations and dialects, the operation names take precedence
+/// over their dialects. The operations left out are unknown, the partial conversion keeps them as is.
+#[derive(Default)]
+pub struct ConversionTarget {
+    ops: HashMap<String, Legality>,
+    dialects: HashMap<String, Legality>,
+    unknown: Option<LegalityCallback>,
+}
+
+impl ConversionTarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_legal_op(&mut self, name: &str) -> &mut Self {
+        self.ops.insert(name.to_string(), Legality::Legal);
+        self
+    }
+
+    pub fn add_illegal_op(&mut self, name: &str) -> &mut Self {
+        self.ops.insert(name.to_string(), Legality::Illegal);
+        self
+    }
+
+    /// The operation is legal if the callback accepts it, like the upstream `addDynamicallyLegalOp`.
+    pub fn add_dynamically_legal_op(
+        &mut self,
+        name: &str,
+        callback: impl Fn(&Context, Operation) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.ops.insert(name.to_string(), Legality::Dynamic(Box::new(callback)));
+        self
+    }
+
+    pub fn add_legal_dialect(&mut self, namespace: &str) -> &mut Self {
+        self.dialects.insert(namespace.to_string(), Legality::Legal);
+        self
+    }
+
+    pub fn add_illegal_dialect(&mut self, namespace: &str) -> &mut Self {
+        self.dialects.insert(namespace.to_string(), Legality::Illegal);
+        self
+    }
+
+    pub fn add_dynamically_legal_dialect(
+        &mut self,
+        namespace: &str,
+        callback: impl Fn(&Context, Operation) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.dialects
+            .insert(namespace.to_string(), Legality::Dynamic(Box::new(callback)));
+        self
+    }
+
+    /// Legality of the operations of neither the listed names nor the listed dialects.
+    pub fn mark_unknown_op_dynamically_legal(
+        &mut self,
+        callback: impl Fn(&Context, Operation) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.unknown = Some(Box::new(callback));
+        self
+    }
+
+    /// Whether the operation is legal, `None` for the unknown ones.
+    pub fn is_legal(&self, ctx: &Context, op: Operation) -> Option<bool> {
+        let legality = self
+            .ops
+            .get(op.name(ctx))
+            .or_else(|| self.dialects.get(op.dialect(ctx)));
+        match (legality, &self.unknown) {
+            (Some(legality), _) => Some(legality.is_legal(ctx, op)),
+            (None, Some(callback)) => Some(callback(ctx, op)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const SOURCE: &str = r#""builtin.module"() ({
+  "dst.legal"() : () -> ()
+  "dst.banned"() : () -> ()
+  "dyn.op"() {legal} : () -> ()
+  "dyn.op"() : () -> ()
+  "src.op"() {legal} : () -> ()
+  "other.op"() {legal} : () -> ()
+  "other.op"() : () -> ()
+}) : () -> ()
+"#;
+
+    fn legality(ctx: &Context, module: Operation, target: &ConversionTarget) -> Vec<Option<bool>> {
+        module.walk(ctx)[1..]
+            .iter()
+            .map(|op| target.is_legal(ctx, *op))
+            .collect()
+    }
+
+    #[test]
+    fn should_prefer_the_operation_legality_over_the_dialect_one() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let is_marked = |ctx: &Context, op: Operation| op.attribute(ctx, "legal").is_some();
+        let mut target = ConversionTarget::new();
+        target
+            .add_legal_dialect("dst")
+            .add_illegal_op("dst.banned")
+            .add_dynamically_legal_dialect("dyn", is_marked)
+            .add_illegal_dialect("src")
+            .add_legal_op("src.other");
+        assert_eq!(
+            legality(&ctx, module, &target),
+            vec![
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(false),
+                None,
+                None
+            ]
+        );
+
+        target
+            .add_dynamically_legal_op("src.op", is_marked)
+            .mark_unknown_op_dynamically_legal(is_marked);
+        assert_eq!(
+            legality(&ctx, module, &target),
+            vec![
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(true),
+                Some(false)
+            ]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Type conversions and the materializations bridging the converted values, like the upstream `TypeConverter`.
+//!
+
+use crate::{Attribute, Builder, Context, Operation, OperationState, Type, Value};
+
+type Conversion = Box<dyn Fn(&mut Context, Type) -> Option<Type> + Send + Sync>;
+type Materialization = Box<dyn Fn(&mut Builder<'_>, Type, &[Value], Attribute) -> Option<Value> + Send + Sync>;
+
+/// One-to-one type conversions, the ones added last are tried first, the types none of them converts are illegal.
+#[derive(Default)]
+pub struct TypeConverter {
+    conversions: Vec<Conversion>,
+    source_materializations: Vec<Materialization>,
+    target_materializations: Vec<Materialization>,
+}
+
+impl TypeConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the conversion, `None` passes the type to the conversions added before.
+    pub fn add_conversion(
+        &mut self,
+        conversion: impl Fn(&mut Context, Type) -> Option<Type> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.conversions.push(Box::new(conversion));
+        self
+    }
+
+    /// Adds the materialization of the original type out of the converted values, for their unconverted users.
+    pub fn add_source_materialization(
+        &mut self,
+        materialization: impl Fn(&mut Builder<'_>, Type, &[Value], Attribute) -> Option<Value> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.source_materializations.push(Box::new(materialization));
+        self
+    }
+
+    /// Adds the materialization of the converted type out of the original values, for the pattern operands.
+    pub fn add_target_materialization(
+        &mut self,
+        materialization: impl Fn(&mut Builder<'_>, Type, &[Value], Attribute) -> Option<Value> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.target_materializations.push(Box::new(materialization));
+        self
+    }
+
+    pub fn convert_type(&self, ctx: &mut Context, r#type: Type) -> Option<Type> {
+        self.conversions
+            .iter()
+            .rev()
+            .find_map(|conversion| conversion(ctx, r#type))
+    }
+
+    pub fn convert_types(&self, ctx: &mut Context, types: &[Type]) -> Option<Vec<Type>> {
+        types.iter().map(|r#type| self.convert_type(ctx, *r#type)).collect()
+    }
+
+    /// Whether the type converts to itself.
+    pub fn is_legal(&self, ctx: &mut Context, r#type: Type) -> bool {
+        self.convert_type(ctx, r#type) == Some(r#type)
+    }
+
+    /// Whether the operand and the result types of the operation are legal.
+    pub fn is_legal_op(&self, ctx: &mut Context, op: Operation) -> bool {
+        let operands: Vec<Type> = op.operands(ctx).iter().map(|operand| operand.r#type(ctx)).collect();
+        let types = [operands, op.result_types(ctx)].concat();
+        types.into_iter().all(|r#type| self.is_legal(ctx, r#type))
+    }
+
+    /// Materializes the original type, falling back to the `builtin.unrealized_conversion_cast`.
+    pub fn materialize_source(
+        &self,
+        builder: &mut Builder<'_>,
+        r#type: Type,
+        inputs: &[Value],
+        location: Attribute,
+    ) -> Value {
+        materialize(&self.source_materializations, builder, r#type, inputs, location)
+    }
+
+    /// Materializes the converted type, falling back to the `builtin.unrealized_conversion_cast`.
+    pub fn materialize_target(
+        &self,
+        builder: &mut Builder<'_>,
+        r#type: Type,
+        inputs: &[Value],
+        location: Attribute,
+    ) -> Value {
+        materialize(&self.target_materializations, builder, r#type, inputs, location)
+    }
+}
+
+fn materialize(
+    materializations: &[Materialization],
+    builder: &mut Builder<'_>,
+    r#type: Type,
+    inputs: &[Value],
+    location: Attribute,
+) -> Value {
+    let materialized = materializations
+        .iter()
+        .rev()
+        .find_map(|materialization| materialization(builder, r#type, inputs, location));
+    materialized.unwrap_or_else(|| unrealized_conversion_cast(builder, r#type, inputs, location))
+}
+
+/// Creates the `builtin.unrealized_conversion_cast` of the inputs to the type.
+pub fn unrealized_conversion_cast(
+    builder: &mut Builder<'_>,
+    r#type: Type,
+    inputs: &[Value],
+    location: Attribute,
+) -> Value {
+    let mut state = OperationState::new("builtin.unrealized_conversion_cast");
+    state
+        .add_operands(inputs.iter().copied())
+        .add_types([r#type])
+        .set_location(location);
+    let cast = builder.create(state);
+    cast.result(builder, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, print, PrinterOptions};
+
+    /// `i32` to `i64` and `i16` to nothing, keeping the other integers.
+    fn converter() -> TypeConverter {
+        let mut converter = TypeConverter::new();
+        converter
+            .add_conversion(|ctx, r#type| r#type.is_signless_integer(ctx, 16).then_some(r#type))
+            .add_conversion(|ctx, r#type| match r#type.is_signless_integer(ctx, 32) {
+                true => Some(ctx.integer_type(64)),
+                false => None,
+            })
+            .add_conversion(|ctx, r#type| match r#type.is_signless_integer(ctx, 64) {
+                true => Some(r#type),
+                false => None,
+            });
+        converter
+    }
+
+    #[test]
+    fn should_try_the_last_conversions_first() {
+        let mut ctx = Context::new();
+        let converter = converter();
+        let [i16, i32, i64, index] = [
+            ctx.integer_type(16),
+            ctx.integer_type(32),
+            ctx.integer_type(64),
+            ctx.index_type(),
+        ];
+        assert_eq!(converter.convert_type(&mut ctx, i32), Some(i64));
+        assert_eq!(converter.convert_type(&mut ctx, i16), Some(i16));
+        assert_eq!(converter.convert_type(&mut ctx, index), None);
+        assert_eq!(converter.convert_types(&mut ctx, &[i32, i64]), Some(vec![i64, i64]));
+        assert_eq!(converter.convert_types(&mut ctx, &[i32, index]), None);
+        assert!(converter.is_legal(&mut ctx, i64) && !converter.is_legal(&mut ctx, i32));
+
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  %0 = "test.def"() : () -> i64
+  "test.legal"(%0) : (i64) -> ()
+  %1 = "test.illegal"(%0) : (i64) -> i32
+}
+"#,
+        )
+        .unwrap();
+        let legal: Vec<bool> = module.walk(&ctx)[1..]
+            .iter()
+            .map(|op| converter.is_legal_op(&mut ctx, *op))
+            .collect();
+        assert_eq!(legal, vec![true, true, false]);
+    }
+
+    #[test]
+    fn should_materialize_through_the_last_materialization_or_the_cast() {
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  %0 = "test.def"() : () -> i64
+}
+"#,
+        )
+        .unwrap();
+        let value = module.walk(&ctx)[1].result(&ctx, 0);
+        let block = module.region(&ctx, 0).blocks(&ctx)[0];
+        let mut converter = converter();
+        converter
+            .add_source_materialization(|builder, r#type, inputs, location| {
+                let mut state = OperationState::new("test.trunc");
+                state
+                    .add_operands(inputs.iter().copied())
+                    .add_types([r#type])
+                    .set_location(location);
+                Some(builder.create(state).result(builder, 0))
+            })
+            .add_source_materialization(|_, _, _, _| None);
+
+        let mut builder = Builder::at_end(&mut ctx, block);
+        let [i32, index] = [builder.integer_type(32), builder.index_type()];
+        let location = builder.unknown_loc();
+        let truncated = converter.materialize_source(&mut builder, i32, &[value], location);
+        converter.materialize_target(&mut builder, index, &[truncated], location);
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  %0 = "test.def"() : () -> i64
+  %1 = "test.trunc"(%0) : (i64) -> i32
+  %2 = "builtin.unrealized_conversion_cast"(%1) : (i32) -> index
+}
+"#
+        );
+    }
+}
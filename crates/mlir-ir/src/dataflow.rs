@@ -0,0 +1,402 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Data-flow analyses of the core IR, like the upstream `mlir/Analysis/DataFlow`.
+//! The [DataFlowSolver] runs the loaded analyses to the fixpoint: the [DeadCodeAnalysis] finds the executable
+//! blocks and the predecessors of the region entries and the call results, the [SparseForward] and
+//! [SparseBackward] analyses propagate the [LatticeValue]s of the values through them.
+//! The [SparseConstantPropagation], the [IntegerRangeAnalysis] of the `arith` and `index` operations and the
+//! [LivenessAnalysis] are built on them, the dead code and the constant propagation analyses are to be loaded
+//! along the other ones, like upstream.
+//!
+
+mod constant_propagation;
+mod dead_code;
+mod integer_range;
+mod lattice;
+mod liveness;
+mod solver;
+mod sparse;
+
+pub use constant_propagation::{ConstantValue, SparseConstantPropagation};
+pub use dead_code::{DeadCodeAnalysis, Executable, Predecessor, PredecessorState};
+pub use integer_range::{ConstantIntRanges, IntegerRangeAnalysis, IntegerValueRange};
+pub use lattice::LatticeValue;
+pub use liveness::{Liveness, LivenessAnalysis};
+pub use solver::{AnalysisState, ChangeResult, DataFlowAnalysis, DataFlowSolver, LatticeAnchor, ProgramPoint};
+pub use sparse::{SparseBackward, SparseBackwardAnalysis, SparseForward, SparseForwardAnalysis};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        parse, Attribute, Block, BranchOpInterface, Builder, CallOpInterface, CallableOpInterface, Context, Dialect,
+        InterfaceRegistry, OpFoldResult, OpTrait, Operation, OperationState, Region, RegionBranchOpInterface,
+        RegionSuccessor, Type, Value,
+    };
+
+    /// Creates the `arith.constant` of the value, for the constants of both test dialects.
+    fn materialize_arith_constant(
+        builder: &mut Builder<'_>,
+        value: Attribute,
+        r#type: Type,
+        location: Attribute,
+    ) -> Option<Operation> {
+        let mut state = OperationState::new("arith.constant");
+        state
+            .add_attribute("value", value)
+            .add_types([r#type])
+            .set_location(location);
+        Some(builder.create(state))
+    }
+
+    /// `arith.constant` and the folding `arith.addi`.
+    pub(crate) struct ArithDialect;
+
+    impl Dialect for ArithDialect {
+        fn namespace(&self) -> &str {
+            "arith"
+        }
+
+        fn fold(&self, ctx: &mut Context, op: Operation, operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+            let (Some(lhs), Some(rhs)) = (operands.first()?.as_ref(), operands.get(1)?.as_ref()) else {
+                return None;
+            };
+            if op.name(ctx) != "arith.addi" {
+                return None;
+            }
+            let value = lhs.as_integer(ctx)? + rhs.as_integer(ctx)?;
+            let r#type = op.result(ctx, 0).r#type(ctx);
+            Some(vec![OpFoldResult::Attribute(ctx.integer_attr(value, r#type))])
+        }
+
+        fn constant_value(&self, ctx: &Context, op: Operation) -> Option<Attribute> {
+            match op.name(ctx) {
+                "arith.constant" => op.attribute(ctx, "value"),
+                _ => None,
+            }
+        }
+
+        fn materialize_constant(
+            &self,
+            builder: &mut Builder<'_>,
+            value: Attribute,
+            r#type: Type,
+            location: Attribute,
+        ) -> Option<Operation> {
+            materialize_arith_constant(builder, value, r#type, location)
+        }
+
+        fn is_pure(&self, _ctx: &Context, _op: Operation) -> bool {
+            true
+        }
+    }
+
+    /// `test.func` callables, `test.call`, `test.return`, the `test.br` and `test.cond_br` branches
+    /// and the `test.if` region branch of the `test.yield` terminators.
+    pub(crate) struct TestDialect;
+
+    impl Dialect for TestDialect {
+        fn namespace(&self) -> &str {
+            "test"
+        }
+
+        fn has_trait(&self, ctx: &Context, op: Operation, r#trait: OpTrait) -> bool {
+            matches!(
+                (op.name(ctx), r#trait),
+                ("test.return" | "test.yield", OpTrait::Terminator | OpTrait::ReturnLike)
+                    | ("test.br" | "test.cond_br", OpTrait::Terminator)
+                    | ("test.func", OpTrait::IsolatedFromAbove)
+            )
+        }
+
+        fn materialize_constant(
+            &self,
+            builder: &mut Builder<'_>,
+            value: Attribute,
+            r#type: Type,
+            location: Attribute,
+        ) -> Option<Operation> {
+            materialize_arith_constant(builder, value, r#type, location)
+        }
+
+        fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+            registry.register::<dyn BranchOpInterface>("test.br", &BrOp);
+            registry.register::<dyn BranchOpInterface>("test.cond_br", &CondBrOp);
+            registry.register::<dyn RegionBranchOpInterface>("test.if", &IfOp);
+            registry.register::<dyn CallOpInterface>("test.call", &CallOp);
+            registry.register::<dyn CallableOpInterface>("test.func", &FuncOp);
+        }
+    }
+
+    struct BrOp;
+
+    impl BranchOpInterface for BrOp {
+        fn get_successor_operands(&self, ctx: &Context, op: Operation, _index: u32) -> Vec<Value> {
+            op.operands(ctx).to_vec()
+        }
+    }
+
+    struct CondBrOp;
+
+    impl BranchOpInterface for CondBrOp {
+        fn get_successor_operands(&self, _ctx: &Context, _op: Operation, _index: u32) -> Vec<Value> {
+            vec![]
+        }
+
+        fn get_successor_for_operands(
+            &self,
+            ctx: &Context,
+            op: Operation,
+            operands: &[Option<Attribute>],
+        ) -> Option<Block> {
+            let condition = operands[0]?.as_integer(ctx)?;
+            Some(op.successors(ctx)[usize::from(condition == 0)])
+        }
+    }
+
+    struct IfOp;
+
+    impl RegionBranchOpInterface for IfOp {
+        fn get_successor_regions(
+            &self,
+            ctx: &Context,
+            op: Operation,
+            point: Option<Operation>,
+            operands: &[Option<Attribute>],
+        ) -> Vec<RegionSuccessor> {
+            match point {
+                Some(terminator) => vec![RegionSuccessor {
+                    region: None,
+                    operands: terminator.operands(ctx).to_vec(),
+                    inputs: op.results(ctx).to_vec(),
+                }],
+                None => {
+                    let regions = match operands[0].and_then(|condition| condition.as_integer(ctx)) {
+                        Some(0) => vec![1],
+                        Some(_) => vec![0],
+                        None => vec![0, 1],
+                    };
+                    let entry = |region| RegionSuccessor {
+                        region: Some(region),
+                        operands: vec![],
+                        inputs: vec![],
+                    };
+                    regions.into_iter().map(entry).collect()
+                }
+            }
+        }
+    }
+
+    struct CallOp;
+
+    impl CallOpInterface for CallOp {
+        fn get_callable_for_callee(&self, ctx: &Context, op: Operation) -> Option<Attribute> {
+            op.attribute(ctx, "callee")
+        }
+    }
+
+    struct FuncOp;
+
+    impl CallableOpInterface for FuncOp {
+        fn get_callable_region(&self, ctx: &Context, op: Operation) -> Option<Region> {
+            Some(op.region(ctx, 0))
+        }
+    }
+
+    pub(crate) fn run(
+        ctx: &mut Context,
+        source: &str,
+        load: impl FnOnce(&mut DataFlowSolver),
+    ) -> (DataFlowSolver, Operation) {
+        ctx.register_dialect(ArithDialect);
+        ctx.register_dialect(TestDialect);
+        let module = parse(ctx, source).unwrap();
+        let mut solver = DataFlowSolver::new();
+        solver
+            .load(DeadCodeAnalysis::new())
+            .load(SparseForward::new(SparseConstantPropagation));
+        load(&mut solver);
+        solver.initialize_and_run(ctx, module);
+        (solver, module)
+    }
+
+    /// The results of the operations of the name, in the pre-order.
+    pub(crate) fn results(ctx: &Context, module: Operation, name: &str) -> Vec<Value> {
+        let ops = module.walk(ctx).into_iter().filter(|op| op.name(ctx) == name);
+        ops.map(|op| op.result(ctx, 0)).collect()
+    }
+
+    /// The arguments of the entry blocks of the `test.func`s, in the pre-order.
+    pub(crate) fn arguments(ctx: &Context, module: Operation) -> Vec<Value> {
+        let funcs = module.walk(ctx).into_iter().filter(|op| op.name(ctx) == "test.func");
+        let entries = funcs.map(|func| func.region(ctx, 0).entry_block(ctx).unwrap());
+        entries.flat_map(|entry| entry.arguments(ctx).to_vec()).collect()
+    }
+
+    #[test]
+    fn should_propagate_constants_through_branches_regions_and_calls() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.addi"(%arg0, %0) : (i32, i32) -> i32
+    "test.return"(%1) : (i32) -> ()
+  }) {sym_name = "inc", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.constant"() {value = 1 : i1} : () -> i1
+    "test.cond_br"(%1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:
+    "test.br"(%0)[^bb3] : (i32) -> ()
+  ^bb2:
+    "test.br"(%arg0)[^bb3] : (i32) -> ()
+  ^bb3(%2: i32):
+    %3 = "test.call"(%2) {callee = @inc} : (i32) -> i32
+    %4 = "test.if"(%1) ({
+      "test.yield"(%3) : (i32) -> ()
+    }, {
+      "test.yield"(%arg0) : (i32) -> ()
+    }) : (i1) -> i32
+    "test.return"(%4) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |_| {},
+        );
+        let i32 = ctx.integer_type(32);
+        let one = ConstantValue::Constant(ctx.integer_attr(1, i32));
+        let two = ConstantValue::Constant(ctx.integer_attr(2, i32));
+        let constant = |value: Value| *solver.lookup::<ConstantValue>(value).unwrap();
+        let [inc_argument, main_argument] = arguments(&ctx, module)[..] else {
+            panic!("two functions");
+        };
+        assert_eq!(constant(inc_argument), one);
+        assert_eq!(constant(main_argument), ConstantValue::Unknown);
+        assert_eq!(constant(results(&ctx, module, "test.call")[0]), two);
+        assert_eq!(constant(results(&ctx, module, "test.if")[0]), two);
+
+        let main = module
+            .walk(&ctx)
+            .into_iter()
+            .find(|op| op.name(&ctx) == "test.if")
+            .unwrap();
+        let blocks = main.parent_region(&ctx).unwrap().blocks(&ctx).to_vec();
+        let executable = |block: Block| solver.lookup::<Executable>(block).is_some_and(|state| state.is_live());
+        assert_eq!(
+            blocks.iter().map(|block| executable(*block)).collect::<Vec<_>>(),
+            [true, true, false, true]
+        );
+        let else_entry = main.region(&ctx, 1).entry_block(&ctx).unwrap();
+        assert!(!executable(else_entry));
+    }
+
+    #[test]
+    fn should_infer_integer_ranges_and_widen_loops() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i8):
+    %0 = "arith.constant"() {value = 0 : index} : () -> index
+    %1 = "arith.constant"() {value = 10 : index} : () -> index
+    %2 = "arith.constant"() {value = 1 : index} : () -> index
+    "test.br"(%0)[^bb1] : (index) -> ()
+  ^bb1(%3: index):
+    %4 = "arith.cmpi"(%3, %1) {predicate = 6 : i64} : (index, index) -> i1
+    "test.cond_br"(%4)[^bb2, ^bb3] : (i1) -> ()
+  ^bb2:
+    %5 = "arith.addi"(%3, %2) : (index, index) -> index
+    "test.br"(%5)[^bb1] : (index) -> ()
+  ^bb3:
+    %6 = "arith.extui"(%arg0) : (i8) -> i32
+    %7 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %8 = "arith.addi"(%6, %7) : (i32, i32) -> i32
+    %9 = "arith.constant"() {value = 1000 : i32} : () -> i32
+    %10 = "arith.cmpi"(%8, %9) {predicate = 6 : i64} : (i32, i32) -> i1
+    %11 = "arith.minui"(%3, %1) : (index, index) -> index
+    "test.return"(%11) : (index) -> ()
+  }) {sym_name = "f"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseForward::new(IntegerRangeAnalysis));
+            },
+        );
+        let range = |value: Value| solver.lookup::<IntegerValueRange>(value).unwrap().range().unwrap();
+        let comparison = module
+            .walk(&ctx)
+            .into_iter()
+            .find(|op| op.name(&ctx) == "arith.cmpi")
+            .unwrap();
+        let induction = comparison.operand(&ctx, 0);
+        assert_eq!(range(induction), ConstantIntRanges::max_range(64));
+        let additions = results(&ctx, module, "arith.addi");
+        assert_eq!(range(additions[1]), ConstantIntRanges::from_unsigned(1, 256, 32));
+        let comparisons = results(&ctx, module, "arith.cmpi");
+        assert_eq!(range(comparisons[0]), ConstantIntRanges::max_range(1));
+        assert_eq!(range(comparisons[1]).constant_value(), Some(-1));
+        let minimum = results(&ctx, module, "arith.minui")[0];
+        assert_eq!(range(minimum), ConstantIntRanges::from_unsigned(0, 10, 64));
+    }
+
+    #[test]
+    fn should_compute_liveness() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    "test.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "id", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32, %arg1: i32):
+    %0 = "arith.addi"(%arg0, %arg0) : (i32, i32) -> i32
+    %1 = "arith.addi"(%arg1, %arg1) : (i32, i32) -> i32
+    %2 = "test.call"(%1) {callee = @id} : (i32) -> i32
+    "other.print"(%arg0) : (i32) -> ()
+    "test.return"(%0) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseBackward::new(LivenessAnalysis));
+            },
+        );
+        let live = |value: Value| {
+            solver
+                .lookup::<Liveness>(value)
+                .is_some_and(|liveness| liveness.is_live())
+        };
+        let [id_argument, main_live, main_dead] = arguments(&ctx, module)[..] else {
+            panic!("three arguments");
+        };
+        let additions = results(&ctx, module, "arith.addi");
+        assert!(live(main_live));
+        assert!(live(additions[0]));
+        assert!(!live(main_dead));
+        assert!(!live(additions[1]));
+        assert!(!live(results(&ctx, module, "test.call")[0]));
+        assert!(!live(id_argument));
+    }
+}
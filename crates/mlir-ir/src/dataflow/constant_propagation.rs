@@ -0,0 +1,192 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Sparse constant propagation folding the operations of the constant operands, like the upstream
+//! `SparseConstantPropagation`.
+//!
+
+use crate::dataflow::{AnalysisState, LatticeValue, SparseForwardAnalysis};
+use crate::{Attribute, Context, OpFoldResult, Operation, Value};
+
+/// Constant of a value, like the upstream `ConstantValue`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConstantValue {
+    /// Not computed yet.
+    #[default]
+    Uninitialized,
+    Constant(Attribute),
+    /// May take different values.
+    Unknown,
+}
+
+impl AnalysisState for ConstantValue {}
+
+impl LatticeValue for ConstantValue {
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (ConstantValue::Uninitialized, other) | (other, ConstantValue::Uninitialized) => *other,
+            (ConstantValue::Constant(lhs), ConstantValue::Constant(rhs)) if lhs == rhs => *self,
+            _ => ConstantValue::Unknown,
+        }
+    }
+}
+
+impl ConstantValue {
+    pub fn constant(self) -> Option<Attribute> {
+        match self {
+            ConstantValue::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Forward analysis of the [ConstantValue]s, evaluating the constant-like operations and the folders of the
+/// registered dialects.
+#[derive(Debug, Default)]
+pub struct SparseConstantPropagation;
+
+impl SparseForwardAnalysis for SparseConstantPropagation {
+    type Lattice = ConstantValue;
+
+    fn visit_operation(&mut self, ctx: &mut Context, op: Operation, operands: &[ConstantValue]) -> Vec<ConstantValue> {
+        let results = op.results(ctx).to_vec();
+        let unknown = vec![ConstantValue::Unknown; results.len()];
+        let Some(dialect) = ctx.dialect_of(op) else {
+            return unknown;
+        };
+        if let Some(value) = dialect.constant_value(ctx, op) {
+            return vec![ConstantValue::Constant(value)];
+        }
+        // The in-place folds are undone, the analysis doesn't change the IR.
+        let original_operands = op.operands(ctx).to_vec();
+        let original_attributes = op.attributes(ctx).to_vec();
+        let constants: Vec<Option<Attribute>> = operands.iter().map(|operand| operand.constant()).collect();
+        let Some(folded) = dialect.fold(ctx, op, &constants) else {
+            return unknown;
+        };
+        if folded
+            .iter()
+            .any(|result| matches!(result, OpFoldResult::Value(value) if results.contains(value)))
+        {
+            ctx.set_operands(op, original_operands);
+            let attributes = op.attributes(ctx).to_vec();
+            for attribute in attributes {
+                ctx.remove_attribute(op, &attribute.name);
+            }
+            for attribute in original_attributes {
+                ctx.set_attribute(op, &attribute.name, attribute.value);
+            }
+            return unknown;
+        }
+        folded
+            .into_iter()
+            .map(|result| match result {
+                OpFoldResult::Attribute(value) => ConstantValue::Constant(value),
+                OpFoldResult::Value(value) => original_operands
+                    .iter()
+                    .position(|operand| *operand == value)
+                    .map_or(ConstantValue::Unknown, |index| operands[index]),
+            })
+            .collect()
+    }
+
+    fn entry_state(&self, _ctx: &Context, _value: Value) -> ConstantValue {
+        ConstantValue::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::tests::{results, run};
+    use crate::Dialect;
+
+    /// `commute.addi` folding the zero right hand side away, and moving the constant left hand side to the
+    /// right in place.
+    struct CommuteDialect;
+
+    impl Dialect for CommuteDialect {
+        fn namespace(&self) -> &str {
+            "commute"
+        }
+
+        fn fold(&self, ctx: &mut Context, op: Operation, operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+            let (lhs, rhs) = (op.operand(ctx, 0), op.operand(ctx, 1));
+            match (operands[0], operands[1]) {
+                (_, Some(rhs)) if rhs.as_integer(ctx) == Some(0) => Some(vec![OpFoldResult::Value(lhs)]),
+                (Some(_), None) => {
+                    ctx.set_operands(op, vec![rhs, lhs]);
+                    Some(vec![OpFoldResult::Value(op.result(ctx, 0))])
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn should_join_the_constants() {
+        let mut ctx = Context::new();
+        let i32 = ctx.integer_type(32);
+        let one = ConstantValue::Constant(ctx.integer_attr(1, i32));
+        let two = ConstantValue::Constant(ctx.integer_attr(2, i32));
+        let uninitialized = ConstantValue::Uninitialized;
+        let unknown = ConstantValue::Unknown;
+
+        assert_eq!(uninitialized.join(&one), one);
+        assert_eq!(one.join(&uninitialized), one);
+        assert_eq!(one.join(&one), one);
+        assert_eq!(one.join(&two), unknown);
+        assert_eq!(unknown.join(&one), unknown);
+        assert_eq!(uninitialized.join(&unknown), unknown);
+        assert_eq!(one.constant(), Some(ctx.integer_attr(1, i32)));
+        assert_eq!(unknown.constant(), None);
+    }
+
+    #[test]
+    fn should_forward_the_folded_operands_and_undo_the_in_place_folds() {
+        let mut ctx = Context::new();
+        ctx.register_dialect(CommuteDialect);
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 5 : i32} : () -> i32
+    %1 = "arith.constant"() {value = 0 : i32} : () -> i32
+    %2 = "commute.addi"(%0, %1) : (i32, i32) -> i32
+    %3 = "commute.addi"(%0, %arg0) : (i32, i32) -> i32
+    "test.return"(%3) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |_| {},
+        );
+        let i32 = ctx.integer_type(32);
+        let additions = results(&ctx, module, "commute.addi");
+        let constant = |value: Value| *solver.lookup::<ConstantValue>(value).unwrap();
+        assert_eq!(
+            constant(additions[0]),
+            ConstantValue::Constant(ctx.integer_attr(5, i32))
+        );
+        assert_eq!(constant(additions[1]), ConstantValue::Unknown);
+
+        let commuted = additions[1].defining_op(&ctx).unwrap();
+        let five = results(&ctx, module, "arith.constant")[0];
+        let argument = commuted.parent_block(&ctx).unwrap().arguments(&ctx)[0];
+        assert_eq!(commuted.operands(&ctx), [five, argument]);
+    }
+}
@@ -0,0 +1,465 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Executable blocks and control flow edges, with the predecessors of the region entries and of the results
+//! of the region branches and the calls, like the upstream `DeadCodeAnalysis`.
+//!
+
+use std::collections::HashSet;
+
+use crate::dataflow::{
+    AnalysisState, ChangeResult, ConstantValue, DataFlowAnalysis, DataFlowSolver, LatticeAnchor, ProgramPoint,
+};
+use crate::{
+    Attribute, AttributeKind, Block, BranchOpInterface, CallOpInterface, CallableOpInterface, Context, OpTrait,
+    Operation, Region, RegionBranchOpInterface, RegionSuccessor, SymbolTable, SymbolTableCollection, Value,
+};
+
+/// Whether the block or the control flow edge may be executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Executable(pub bool);
+
+impl AnalysisState for Executable {}
+
+impl Executable {
+    pub fn is_live(self) -> bool {
+        self.0
+    }
+}
+
+/// Operation transferring the control, with its operands forwarded to the inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Predecessor {
+    pub op: Operation,
+    pub operands: Vec<Value>,
+    pub inputs: Vec<Value>,
+}
+
+/// Predecessors of a region entry block, or of the results of an operation, like the upstream
+/// `PredecessorState`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PredecessorState {
+    known: Vec<Predecessor>,
+    all_known: bool,
+}
+
+impl Default for PredecessorState {
+    fn default() -> Self {
+        Self {
+            known: vec![],
+            all_known: true,
+        }
+    }
+}
+
+impl AnalysisState for PredecessorState {}
+
+impl PredecessorState {
+    pub fn known(&self) -> &[Predecessor] {
+        &self.known
+    }
+
+    /// Whether the control may come from the unknown places, like the callers of the public functions.
+    pub fn all_known(&self) -> bool {
+        self.all_known
+    }
+
+    fn add(&mut self, predecessor: Predecessor) -> ChangeResult {
+        match self.known.contains(&predecessor) {
+            true => ChangeResult::NoChange,
+            false => {
+                self.known.push(predecessor);
+                ChangeResult::Change
+            }
+        }
+    }
+
+    fn set_unknown(&mut self) -> ChangeResult {
+        match std::mem::replace(&mut self.all_known, false) {
+            true => ChangeResult::Change,
+            false => ChangeResult::NoChange,
+        }
+    }
+}
+
+/// Marks the blocks reachable from the entries of the analyzed operation and of the callables that may be
+/// called from the outside, following the branches given the constant operands of the [ConstantValue]
+/// lattice, so the sparse constant propagation has to be loaded along.
+#[derive(Debug, Default)]
+pub struct DeadCodeAnalysis {
+    /// Symbols referred to by the operations other than the calls.
+    escaping: HashSet<String>,
+}
+
+impl DeadCodeAnalysis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn mark_entry_unknown(solver: &mut DataFlowSolver, ctx: &Context, region: Region) {
+        if let Some(entry) = region.entry_block(ctx) {
+            mark_executable(solver, entry);
+            solver.update::<PredecessorState>(entry, PredecessorState::set_unknown);
+        }
+    }
+
+    /// Whether the callable may be called from the outside, or through an escaping reference.
+    fn is_externally_callable(&self, ctx: &Context, op: Operation) -> bool {
+        let private = op
+            .attribute(ctx, "sym_visibility")
+            .and_then(|visibility| visibility.as_string(ctx))
+            == Some("private");
+        let escaping = SymbolTable::symbol_name(ctx, op).is_some_and(|name| self.escaping.contains(name));
+        !private || escaping
+    }
+
+    fn visit_region_branch(&mut self, ctx: &Context, solver: &mut DataFlowSolver, op: Operation, point: ProgramPoint) {
+        let Some(operands) = constant_operands(ctx, solver, op, point) else {
+            return;
+        };
+        let region_branch = op
+            .as_interface::<dyn RegionBranchOpInterface>(ctx)
+            .expect("the region branches implement the interface");
+        let successors = region_branch.get_successor_regions(ctx, op, None, &operands);
+        add_region_successors(ctx, solver, op, op, successors);
+    }
+
+    fn visit_branch(&mut self, ctx: &Context, solver: &mut DataFlowSolver, op: Operation, point: ProgramPoint) {
+        let Some(operands) = constant_operands(ctx, solver, op, point) else {
+            return;
+        };
+        let block = op
+            .parent_block(ctx)
+            .expect("the visited operations are within the blocks");
+        let taken = op
+            .as_interface::<dyn BranchOpInterface>(ctx)
+            .and_then(|branch| branch.get_successor_for_operands(ctx, op, &operands));
+        let successors = match taken {
+            Some(successor) => vec![successor],
+            None => op.successors(ctx).to_vec(),
+        };
+        for successor in successors {
+            solver.update::<Executable>(LatticeAnchor::Edge(block, successor), set_live);
+            mark_executable(solver, successor);
+        }
+    }
+
+    fn visit_call(&mut self, ctx: &Context, solver: &mut DataFlowSolver, op: Operation, callee: Attribute) {
+        let callable = SymbolTableCollection::new().lookup_nearest_symbol_from(ctx, op, callee);
+        let body = callable.and_then(|callable| callable_body(ctx, callable));
+        match body.and_then(|body| body.entry_block(ctx)) {
+            Some(entry) => {
+                mark_executable(solver, entry);
+                let predecessor = Predecessor {
+                    op,
+                    operands: op.operands(ctx).to_vec(),
+                    inputs: entry.arguments(ctx).to_vec(),
+                };
+                solver.update::<PredecessorState>(entry, |state| state.add(predecessor));
+            }
+            None => {
+                solver.update::<PredecessorState>(op, PredecessorState::set_unknown);
+            }
+        }
+    }
+
+    /// Forwards the terminator operands to the callers of the callable, or to the successors of the region
+    /// branch enclosing it.
+    fn visit_terminator(&mut self, ctx: &Context, solver: &mut DataFlowSolver, op: Operation, point: ProgramPoint) {
+        let region = op
+            .parent_region(ctx)
+            .expect("the visited operations are within the regions");
+        let parent = region.parent_op(ctx);
+        if callable_body(ctx, parent) == Some(region) {
+            if !op.has_trait(ctx, OpTrait::ReturnLike) {
+                return;
+            }
+            let Some(entry) = region.entry_block(ctx) else {
+                return;
+            };
+            let callers = solver.read::<PredecessorState>(entry, point);
+            for caller in callers.known() {
+                let predecessor = Predecessor {
+                    op,
+                    operands: op.operands(ctx).to_vec(),
+                    inputs: caller.op.results(ctx).to_vec(),
+                };
+                solver.update::<PredecessorState>(caller.op, |state| state.add(predecessor));
+            }
+            return;
+        }
+        let Some(operands) = constant_operands(ctx, solver, op, point) else {
+            return;
+        };
+        if let Some(region_branch) = parent.as_interface::<dyn RegionBranchOpInterface>(ctx) {
+            let successors = region_branch.get_successor_regions(ctx, parent, Some(op), &operands);
+            add_region_successors(ctx, solver, parent, op, successors);
+        }
+    }
+}
+
+impl DataFlowAnalysis for DeadCodeAnalysis {
+    fn initialize(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, top: Operation) {
+        let ctx: &Context = ctx;
+        for op in top.walk(ctx) {
+            if callee(ctx, op).is_some() {
+                continue;
+            }
+            for attribute in op.attributes(ctx) {
+                collect_symbols(ctx, attribute.value, &mut self.escaping);
+            }
+        }
+        for region in top.regions(ctx) {
+            Self::mark_entry_unknown(solver, ctx, *region);
+        }
+        for op in top.walk(ctx).into_iter().filter(|op| *op != top) {
+            let body = callable_body(ctx, op);
+            match body {
+                Some(body) if self.is_externally_callable(ctx, op) => Self::mark_entry_unknown(solver, ctx, body),
+                _ => {}
+            }
+            solver.enqueue(ProgramPoint::Operation(op));
+        }
+    }
+
+    fn visit(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, point: ProgramPoint) {
+        let ctx: &Context = ctx;
+        let ProgramPoint::Operation(op) = point else {
+            return;
+        };
+        let Some(block) = op.parent_block(ctx) else {
+            return;
+        };
+        if !solver.read::<Executable>(block, point).is_live() {
+            return;
+        }
+        if op.as_interface::<dyn RegionBranchOpInterface>(ctx).is_some() {
+            self.visit_region_branch(ctx, solver, op, point);
+        } else if callable_body(ctx, op).is_none() {
+            // The regions of the operations of unknown semantics may be entered in any way.
+            for region in op.regions(ctx) {
+                Self::mark_entry_unknown(solver, ctx, *region);
+            }
+        }
+        if !op.successors(ctx).is_empty() {
+            self.visit_branch(ctx, solver, op, point);
+        }
+        if let Some(callee) = callee(ctx, op) {
+            self.visit_call(ctx, solver, op, callee);
+        }
+        if op.next(ctx).is_none() && op.parent_op(ctx).is_some() {
+            self.visit_terminator(ctx, solver, op, point);
+        }
+    }
+}
+
+fn set_live(executable: &mut Executable) -> ChangeResult {
+    match std::mem::replace(executable, Executable(true)) {
+        Executable(true) => ChangeResult::NoChange,
+        Executable(false) => ChangeResult::Change,
+    }
+}
+
+fn mark_executable(solver: &mut DataFlowSolver, block: Block) {
+    solver.update::<Executable>(block, set_live);
+}
+
+/// Body of the callable operation, `None` for the other operations.
+pub(crate) fn callable_body(ctx: &Context, op: Operation) -> Option<Region> {
+    op.as_interface::<dyn CallableOpInterface>(ctx)?.get_callable_region(ctx, op)
+}
+
+/// Symbol reference of the callee of the call operation, `None` for the other operations.
+pub(crate) fn callee(ctx: &Context, op: Operation) -> Option<Attribute> {
+    op.as_interface::<dyn CallOpInterface>(ctx)?.get_callable_for_callee(ctx, op)
+}
+
+fn add_region_successors(
+    ctx: &Context,
+    solver: &mut DataFlowSolver,
+    parent: Operation,
+    op: Operation,
+    successors: Vec<RegionSuccessor>,
+) {
+    for successor in successors {
+        let predecessor = Predecessor {
+            op,
+            operands: successor.operands,
+            inputs: successor.inputs,
+        };
+        match successor.region {
+            Some(index) => {
+                let Some(entry) = parent.region(ctx, index).entry_block(ctx) else {
+                    continue;
+                };
+                mark_executable(solver, entry);
+                solver.update::<PredecessorState>(entry, |state| state.add(predecessor));
+            }
+            None => {
+                solver.update::<PredecessorState>(parent, |state| state.add(predecessor));
+            }
+        }
+    }
+}
+
+/// Constants of the operands, `None` until every operand lattice is initialized.
+pub(crate) fn constant_operands(
+    ctx: &Context,
+    solver: &mut DataFlowSolver,
+    op: Operation,
+    point: ProgramPoint,
+) -> Option<Vec<Option<Attribute>>> {
+    let mut constants = vec![];
+    for operand in op.operands(ctx) {
+        match solver.read::<ConstantValue>(*operand, point) {
+            ConstantValue::Uninitialized => return None,
+            ConstantValue::Constant(value) => constants.push(Some(value)),
+            ConstantValue::Unknown => constants.push(None),
+        }
+    }
+    Some(constants)
+}
+
+fn collect_symbols(ctx: &Context, attribute: Attribute, symbols: &mut HashSet<String>) {
+    match attribute.kind(ctx) {
+        AttributeKind::SymbolRef { root, .. } => {
+            symbols.insert(root.clone());
+        }
+        AttributeKind::Array(elements) => {
+            for element in elements {
+                collect_symbols(ctx, *element, symbols);
+            }
+        }
+        AttributeKind::Dictionary(entries) => {
+            for entry in entries {
+                collect_symbols(ctx, entry.value, symbols);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::tests::run;
+
+    fn func(ctx: &Context, module: Operation, name: &str) -> Operation {
+        let mut funcs = module.walk(ctx).into_iter().filter(|op| op.name(ctx) == "test.func");
+        funcs
+            .find(|func| SymbolTable::symbol_name(ctx, *func) == Some(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn should_follow_the_taken_branches_and_the_loops() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0:
+    %0 = "arith.constant"() {value = 0 : i1} : () -> i1
+    "test.cond_br"(%0)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:
+    "test.return"() : () -> ()
+  ^bb2:
+    "test.br"()[^bb3] : () -> ()
+  ^bb3:
+    "test.br"()[^bb2] : () -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |_| {},
+        );
+        let blocks = func(&ctx, module, "main").region(&ctx, 0).blocks(&ctx).to_vec();
+        let live = |anchor: LatticeAnchor| solver.lookup::<Executable>(anchor).is_some_and(|state| state.is_live());
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| live(LatticeAnchor::Block(*block)))
+                .collect::<Vec<_>>(),
+            [true, false, true, true]
+        );
+        assert!(!live(LatticeAnchor::Edge(blocks[0], blocks[1])));
+        assert!(live(LatticeAnchor::Edge(blocks[0], blocks[2])));
+        assert!(live(LatticeAnchor::Edge(blocks[2], blocks[3])));
+        assert!(live(LatticeAnchor::Edge(blocks[3], blocks[2])));
+    }
+
+    #[test]
+    fn should_collect_the_predecessors_of_the_callables_and_the_calls() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    "test.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "callee", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    "test.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "escaping", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    "test.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "dead", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.call"(%arg0) {callee = @callee} : (i32) -> i32
+    %1 = "test.call"(%0) {callee = @callee} : (i32) -> i32
+    "other.use"() {ref = @escaping} : () -> ()
+    "test.return"(%1) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |_| {},
+        );
+        let entry = |name: &str| func(&ctx, module, name).region(&ctx, 0).entry_block(&ctx).unwrap();
+        let executable = |name: &str| {
+            solver
+                .lookup::<Executable>(entry(name))
+                .is_some_and(|state| state.is_live())
+        };
+        let predecessors = |name: &str| solver.lookup::<PredecessorState>(entry(name)).unwrap();
+        assert!(["callee", "escaping", "main"].into_iter().all(executable));
+        assert!(!executable("dead"));
+
+        let calls: Vec<Operation> = module
+            .walk(&ctx)
+            .into_iter()
+            .filter(|op| op.name(&ctx) == "test.call")
+            .collect();
+        let callers: Vec<Operation> = predecessors("callee").known().iter().map(|caller| caller.op).collect();
+        assert_eq!(callers, calls);
+        assert!(predecessors("callee").all_known());
+        assert!(!predecessors("escaping").all_known());
+        assert!(!predecessors("main").all_known());
+
+        let returned = entry("callee").terminator(&ctx).unwrap();
+        let results = solver.lookup::<PredecessorState>(calls[0]).unwrap();
+        assert_eq!(
+            results.known(),
+            [Predecessor {
+                op: returned,
+                operands: returned.operands(&ctx).to_vec(),
+                inputs: calls[0].results(&ctx).to_vec(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,509 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Integer range analysis of the `arith` and `index` operations, tracking both the signed and the unsigned
+//! bounds of the values, like the upstream `IntegerRangeAnalysis` with the `InferIntRangeInterface`
+//! implementations of the two dialects.
+//! The other operations produce the maximal ranges of their result types.
+//!
+
+use crate::dataflow::{AnalysisState, LatticeValue, SparseForwardAnalysis};
+use crate::{AttributeKind, Context, Operation, Type, TypeKind, Value};
+
+/// Bit width of the `index` type.
+const INDEX_WIDTH: u32 = 64;
+
+/// Signed and unsigned bounds of the integers of a bit width, like the upstream `ConstantIntRanges`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstantIntRanges {
+    umin: u128,
+    umax: u128,
+    smin: i128,
+    smax: i128,
+    width: u32,
+}
+
+impl ConstantIntRanges {
+    pub fn new(umin: u128, umax: u128, smin: i128, smax: i128, width: u32) -> Self {
+        Self {
+            umin,
+            umax,
+            smin,
+            smax,
+            width,
+        }
+    }
+
+    /// Range of the single value, truncated to the width.
+    pub fn constant(value: i128, width: u32) -> Self {
+        let unsigned = value as u128 & unsigned_max(width);
+        let signed = to_signed(unsigned, width);
+        Self::new(unsigned, unsigned, signed, signed, width)
+    }
+
+    /// Range of all the values of the width.
+    pub fn max_range(width: u32) -> Self {
+        Self::new(0, unsigned_max(width), signed_min(width), signed_max(width), width)
+    }
+
+    /// Range of the signed bounds, the unsigned ones are derived from them.
+    pub fn from_signed(smin: i128, smax: i128, width: u32) -> Self {
+        let (umin, umax) = if smin >= 0 || smax < 0 {
+            (smin as u128 & unsigned_max(width), smax as u128 & unsigned_max(width))
+        } else {
+            (0, unsigned_max(width))
+        };
+        Self::new(umin, umax, smin, smax, width)
+    }
+
+    /// Range of the unsigned bounds, the signed ones are derived from them.
+    pub fn from_unsigned(umin: u128, umax: u128, width: u32) -> Self {
+        let positive = signed_max(width) as u128;
+        let (smin, smax) = if umax <= positive || umin > positive {
+            (to_signed(umin, width), to_signed(umax, width))
+        } else {
+            (signed_min(width), signed_max(width))
+        };
+        Self::new(umin, umax, smin, smax, width)
+    }
+
+    pub fn umin(&self) -> u128 {
+        self.umin
+    }
+
+    pub fn umax(&self) -> u128 {
+        self.umax
+    }
+
+    pub fn smin(&self) -> i128 {
+        self.smin
+    }
+
+    pub fn smax(&self) -> i128 {
+        self.smax
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The signed value of the single value range.
+    pub fn constant_value(&self) -> Option<i128> {
+        (self.smin == self.smax).then_some(self.smin)
+    }
+
+    /// Smallest range containing both.
+    pub fn range_union(&self, other: &Self) -> Self {
+        Self::new(
+            self.umin.min(other.umin),
+            self.umax.max(other.umax),
+            self.smin.min(other.smin),
+            self.smax.max(other.smax),
+            self.width,
+        )
+    }
+
+    /// Range of the values within both.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(
+            self.umin.max(other.umin),
+            self.umax.min(other.umax),
+            self.smin.max(other.smin),
+            self.smax.min(other.smax),
+            self.width,
+        )
+    }
+}
+
+/// Range of an integer or `index` value, like the upstream `IntegerValueRange`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IntegerValueRange {
+    /// Not computed yet.
+    #[default]
+    Uninitialized,
+    Range(ConstantIntRanges),
+    /// Value of a non-integer type.
+    Unknown,
+}
+
+impl AnalysisState for IntegerValueRange {}
+
+impl LatticeValue for IntegerValueRange {
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (IntegerValueRange::Uninitialized, other) | (other, IntegerValueRange::Uninitialized) => *other,
+            (IntegerValueRange::Range(lhs), IntegerValueRange::Range(rhs)) if lhs.width == rhs.width => {
+                IntegerValueRange::Range(lhs.range_union(rhs))
+            }
+            _ => IntegerValueRange::Unknown,
+        }
+    }
+}
+
+impl IntegerValueRange {
+    pub fn range(self) -> Option<ConstantIntRanges> {
+        match self {
+            IntegerValueRange::Range(range) => Some(range),
+            _ => None,
+        }
+    }
+
+    /// Maximal range of the integer and `index` types, unknown for the others.
+    pub fn max_range(ctx: &Context, r#type: Type) -> Self {
+        width(ctx, r#type).map_or(IntegerValueRange::Unknown, |width| {
+            IntegerValueRange::Range(ConstantIntRanges::max_range(width))
+        })
+    }
+}
+
+/// Forward analysis of the [IntegerValueRange]s, widening the ranges changing along the loop back edges to
+/// the maximal ones.
+#[derive(Debug, Default)]
+pub struct IntegerRangeAnalysis;
+
+impl SparseForwardAnalysis for IntegerRangeAnalysis {
+    type Lattice = IntegerValueRange;
+
+    fn visit_operation(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        operands: &[IntegerValueRange],
+    ) -> Vec<IntegerValueRange> {
+        let ctx: &Context = ctx;
+        let mut ranges = vec![];
+        for result in op.results(ctx) {
+            let r#type = result.r#type(ctx);
+            let range = width(ctx, r#type)
+                .and_then(|width| infer(ctx, op, operands, width))
+                .map_or_else(|| IntegerValueRange::max_range(ctx, r#type), IntegerValueRange::Range);
+            ranges.push(range);
+        }
+        ranges
+    }
+
+    fn entry_state(&self, ctx: &Context, value: Value) -> IntegerValueRange {
+        IntegerValueRange::max_range(ctx, value.r#type(ctx))
+    }
+
+    fn widen(
+        &self,
+        ctx: &Context,
+        value: Value,
+        _previous: &IntegerValueRange,
+        _joined: IntegerValueRange,
+    ) -> IntegerValueRange {
+        IntegerValueRange::max_range(ctx, value.r#type(ctx))
+    }
+}
+
+fn width(ctx: &Context, r#type: Type) -> Option<u32> {
+    match r#type.kind(ctx) {
+        TypeKind::Integer { width, .. } => Some(*width),
+        TypeKind::Index => Some(INDEX_WIDTH),
+        _ => None,
+    }
+}
+
+fn unsigned_max(width: u32) -> u128 {
+    match width {
+        128.. => u128::MAX,
+        _ => (1 << width) - 1,
+    }
+}
+
+fn signed_min(width: u32) -> i128 {
+    match width {
+        0 => 0,
+        128.. => i128::MIN,
+        _ => -(1 << (width - 1)),
+    }
+}
+
+fn signed_max(width: u32) -> i128 {
+    match width {
+        0 => 0,
+        128.. => i128::MAX,
+        _ => (1 << (width - 1)) - 1,
+    }
+}
+
+/// Two's complement value of the unsigned bits of the width.
+fn to_signed(unsigned: u128, width: u32) -> i128 {
+    match unsigned > signed_max(width) as u128 {
+        true => (unsigned | !unsigned_max(width)) as i128,
+        false => unsigned as i128,
+    }
+}
+
+/// Range of the signed and the unsigned bounds, each dropped once out of the width.
+fn combine(signed: Option<(i128, i128)>, unsigned: Option<(u128, u128)>, width: u32) -> ConstantIntRanges {
+    let max = ConstantIntRanges::max_range(width);
+    let signed = signed
+        .filter(|(min, max)| *min >= signed_min(width) && *max <= signed_max(width))
+        .map_or(max, |(min, max)| ConstantIntRanges::from_signed(min, max, width));
+    let unsigned = unsigned
+        .filter(|(_, max)| *max <= unsigned_max(width))
+        .map_or(max, |(min, max)| ConstantIntRanges::from_unsigned(min, max, width));
+    signed.intersection(&unsigned)
+}
+
+/// Range of the integer cast to the width, extending or truncating it.
+fn cast(range: ConstantIntRanges, width: u32, signed: bool) -> ConstantIntRanges {
+    match (width >= range.width, signed) {
+        (true, true) => ConstantIntRanges::from_signed(range.smin, range.smax, width),
+        (true, false) => ConstantIntRanges::from_unsigned(range.umin, range.umax, width),
+        (false, _) => combine(Some((range.smin, range.smax)), Some((range.umin, range.umax)), width),
+    }
+}
+
+/// Result of the `arith.cmpi` predicate, if the ranges decide it.
+fn compare(predicate: i128, lhs: ConstantIntRanges, rhs: ConstantIntRanges) -> Option<bool> {
+    let slt = |lhs: ConstantIntRanges, rhs: ConstantIntRanges| {
+        (lhs.smax < rhs.smin)
+            .then_some(true)
+            .or((lhs.smin >= rhs.smax).then_some(false))
+    };
+    let sle = |lhs: ConstantIntRanges, rhs: ConstantIntRanges| {
+        (lhs.smax <= rhs.smin)
+            .then_some(true)
+            .or((lhs.smin > rhs.smax).then_some(false))
+    };
+    let ult = |lhs: ConstantIntRanges, rhs: ConstantIntRanges| {
+        (lhs.umax < rhs.umin)
+            .then_some(true)
+            .or((lhs.umin >= rhs.umax).then_some(false))
+    };
+    let ule = |lhs: ConstantIntRanges, rhs: ConstantIntRanges| {
+        (lhs.umax <= rhs.umin)
+            .then_some(true)
+            .or((lhs.umin > rhs.umax).then_some(false))
+    };
+    let eq = || {
+        let disjoint = lhs.umax < rhs.umin || rhs.umax < lhs.umin || lhs.smax < rhs.smin || rhs.smax < lhs.smin;
+        match (lhs.constant_value(), rhs.constant_value()) {
+            (Some(lhs), Some(rhs)) => Some(lhs == rhs),
+            _ => disjoint.then_some(false),
+        }
+    };
+    match predicate {
+        0 => eq(),
+        1 => eq().map(|eq| !eq),
+        2 => slt(lhs, rhs),
+        3 => sle(lhs, rhs),
+        4 => slt(rhs, lhs),
+        5 => sle(rhs, lhs),
+        6 => ult(lhs, rhs),
+        7 => ule(lhs, rhs),
+        8 => ult(rhs, lhs),
+        9 => ule(rhs, lhs),
+        _ => None,
+    }
+}
+
+/// Range of the result of the width, `None` for the operations of the unknown semantics.
+fn infer(ctx: &Context, op: Operation, operands: &[IntegerValueRange], width: u32) -> Option<ConstantIntRanges> {
+    let operand = |index: usize| {
+        let value = op.operand(ctx, index);
+        operands[index]
+            .range()
+            .or_else(|| IntegerValueRange::max_range(ctx, value.r#type(ctx)).range())
+    };
+    let binary = || Some((operand(0)?, operand(1)?));
+    let range = match op.name(ctx) {
+        "arith.constant" | "index.constant" => match op.attribute(ctx, "value")?.kind(ctx) {
+            AttributeKind::Integer { value, .. } => ConstantIntRanges::constant(*value, width),
+            AttributeKind::Bool(value) => ConstantIntRanges::constant(*value as i128, width),
+            _ => return None,
+        },
+        "arith.addi" | "index.add" => {
+            let (lhs, rhs) = binary()?;
+            let signed = lhs.smin.checked_add(rhs.smin).zip(lhs.smax.checked_add(rhs.smax));
+            let unsigned = lhs.umin.checked_add(rhs.umin).zip(lhs.umax.checked_add(rhs.umax));
+            combine(signed, unsigned, width)
+        }
+        "arith.subi" | "index.sub" => {
+            let (lhs, rhs) = binary()?;
+            let signed = lhs.smin.checked_sub(rhs.smax).zip(lhs.smax.checked_sub(rhs.smin));
+            let unsigned = (lhs.umin >= rhs.umax).then(|| (lhs.umin - rhs.umax, lhs.umax - rhs.umin));
+            combine(signed, unsigned, width)
+        }
+        "arith.muli" | "index.mul" => {
+            let (lhs, rhs) = binary()?;
+            let corners = [
+                lhs.smin.checked_mul(rhs.smin),
+                lhs.smin.checked_mul(rhs.smax),
+                lhs.smax.checked_mul(rhs.smin),
+                lhs.smax.checked_mul(rhs.smax),
+            ];
+            let signed = corners
+                .into_iter()
+                .collect::<Option<Vec<i128>>>()
+                .and_then(|corners| corners.iter().min().copied().zip(corners.iter().max().copied()));
+            let unsigned = lhs.umin.checked_mul(rhs.umin).zip(lhs.umax.checked_mul(rhs.umax));
+            combine(signed, unsigned, width)
+        }
+        "arith.divui" | "index.divu" => {
+            let (lhs, rhs) = binary()?;
+            let unsigned = (rhs.umax > 0).then(|| (lhs.umin / rhs.umax, lhs.umax / rhs.umin.max(1)));
+            combine(None, unsigned, width)
+        }
+        "arith.remui" | "index.remu" => {
+            let (lhs, rhs) = binary()?;
+            let unsigned = (rhs.umax > 0).then(|| (0, lhs.umax.min(rhs.umax - 1)));
+            combine(None, unsigned, width)
+        }
+        "arith.andi" | "index.and" => {
+            let (lhs, rhs) = binary()?;
+            combine(None, Some((0, lhs.umax.min(rhs.umax))), width)
+        }
+        "arith.minsi" | "index.mins" => {
+            let (lhs, rhs) = binary()?;
+            ConstantIntRanges::from_signed(lhs.smin.min(rhs.smin), lhs.smax.min(rhs.smax), width)
+        }
+        "arith.maxsi" | "index.maxs" => {
+            let (lhs, rhs) = binary()?;
+            ConstantIntRanges::from_signed(lhs.smin.max(rhs.smin), lhs.smax.max(rhs.smax), width)
+        }
+        "arith.minui" | "index.minu" => {
+            let (lhs, rhs) = binary()?;
+            ConstantIntRanges::from_unsigned(lhs.umin.min(rhs.umin), lhs.umax.min(rhs.umax), width)
+        }
+        "arith.maxui" | "index.maxu" => {
+            let (lhs, rhs) = binary()?;
+            ConstantIntRanges::from_unsigned(lhs.umin.max(rhs.umin), lhs.umax.max(rhs.umax), width)
+        }
+        "arith.extsi" | "arith.index_cast" | "index.casts" => cast(operand(0)?, width, true),
+        "arith.extui" | "arith.trunci" | "arith.index_castui" | "index.castu" => cast(operand(0)?, width, false),
+        "arith.cmpi" => {
+            let (lhs, rhs) = binary()?;
+            let predicate = op.attribute(ctx, "predicate")?.as_integer(ctx)?;
+            match compare(predicate, lhs, rhs) {
+                Some(result) => ConstantIntRanges::constant(result as i128, width),
+                None => ConstantIntRanges::max_range(width),
+            }
+        }
+        "arith.select" => {
+            let (condition, lhs, rhs) = (operand(0)?, operand(1)?, operand(2)?);
+            match condition.constant_value() {
+                Some(0) => rhs,
+                Some(_) => lhs,
+                None => lhs.range_union(&rhs),
+            }
+        }
+        _ => return None,
+    };
+    Some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::tests::{results, run};
+    use crate::dataflow::SparseForward;
+
+    #[test]
+    fn should_derive_the_bounds_of_the_other_signedness() {
+        assert_eq!(
+            ConstantIntRanges::from_signed(-1, 1, 8),
+            ConstantIntRanges::new(0, 255, -1, 1, 8)
+        );
+        assert_eq!(
+            ConstantIntRanges::from_signed(-3, -1, 8),
+            ConstantIntRanges::new(253, 255, -3, -1, 8)
+        );
+        assert_eq!(
+            ConstantIntRanges::from_unsigned(200, 255, 8),
+            ConstantIntRanges::new(200, 255, -56, -1, 8)
+        );
+        assert_eq!(
+            ConstantIntRanges::from_unsigned(100, 200, 8),
+            ConstantIntRanges::new(100, 200, -128, 127, 8)
+        );
+        assert_eq!(ConstantIntRanges::max_range(1), ConstantIntRanges::new(0, 1, -1, 0, 1));
+    }
+
+    #[test]
+    fn should_truncate_the_constants_to_the_width() {
+        assert_eq!(
+            ConstantIntRanges::constant(300, 8),
+            ConstantIntRanges::new(44, 44, 44, 44, 8)
+        );
+        assert_eq!(
+            ConstantIntRanges::constant(-1, 8),
+            ConstantIntRanges::new(255, 255, -1, -1, 8)
+        );
+        assert_eq!(ConstantIntRanges::constant(-1, 8).constant_value(), Some(-1));
+        assert_eq!(ConstantIntRanges::max_range(8).constant_value(), None);
+    }
+
+    #[test]
+    fn should_join_and_meet_the_ranges() {
+        let lhs = ConstantIntRanges::from_unsigned(0, 10, 32);
+        let rhs = ConstantIntRanges::from_unsigned(5, 20, 32);
+        assert_eq!(lhs.range_union(&rhs), ConstantIntRanges::from_unsigned(0, 20, 32));
+        assert_eq!(lhs.intersection(&rhs), ConstantIntRanges::from_unsigned(5, 10, 32));
+
+        let range = IntegerValueRange::Range(lhs);
+        let uninitialized = IntegerValueRange::Uninitialized;
+        assert_eq!(uninitialized.join(&range), range);
+        assert_eq!(range.join(&uninitialized), range);
+        assert_eq!(
+            range.join(&IntegerValueRange::Range(rhs)),
+            IntegerValueRange::Range(lhs.range_union(&rhs))
+        );
+        let narrow = IntegerValueRange::Range(ConstantIntRanges::max_range(8));
+        assert_eq!(range.join(&narrow), IntegerValueRange::Unknown);
+        assert_eq!(IntegerValueRange::Unknown.join(&range), IntegerValueRange::Unknown);
+    }
+
+    #[test]
+    fn should_infer_the_ranges_of_the_arithmetic() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i8, %arg1: i1):
+    %0 = "arith.extui"(%arg0) : (i8) -> i32
+    %1 = "arith.constant"() {value = 2 : i32} : () -> i32
+    %2 = "arith.muli"(%0, %1) : (i32, i32) -> i32
+    %3 = "arith.subi"(%0, %1) : (i32, i32) -> i32
+    %4 = "arith.trunci"(%2) : (i32) -> i8
+    %5 = "arith.select"(%arg1, %0, %1) : (i1, i32, i32) -> i32
+    %6 = "arith.remui"(%2, %1) : (i32, i32) -> i32
+    %7 = "other.op"(%0) : (i32) -> i32
+    "test.return"(%3) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseForward::new(IntegerRangeAnalysis));
+            },
+        );
+        let range = |name: &str| {
+            let value = results(&ctx, module, name)[0];
+            solver.lookup::<IntegerValueRange>(value).unwrap().range().unwrap()
+        };
+        assert_eq!(range("arith.muli"), ConstantIntRanges::from_unsigned(0, 510, 32));
+        assert_eq!(range("arith.subi"), ConstantIntRanges::from_signed(-2, 253, 32));
+        assert_eq!(range("arith.trunci"), ConstantIntRanges::max_range(8));
+        assert_eq!(range("arith.select"), ConstantIntRanges::from_unsigned(0, 255, 32));
+        assert_eq!(range("arith.remui"), ConstantIntRanges::from_unsigned(0, 1, 32));
+        assert_eq!(range("other.op"), ConstantIntRanges::max_range(32));
+    }
+}
@@ -0,0 +1,79 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Lattice elements of the sparse analyses attached to the values, like the upstream `Lattice`.
+//!
+
+use crate::dataflow::{AnalysisState, ChangeResult, DataFlowSolver};
+use crate::Value;
+
+/// Element of a join semi-lattice, the default is the uninitialized bottom, like the upstream lattice
+/// value classes.
+pub trait LatticeValue: AnalysisState + PartialEq {
+    /// Least upper bound of the two elements.
+    fn join(&self, other: &Self) -> Self;
+
+    /// Whether nothing is known yet, the operations are visited once all their operands are initialized.
+    fn is_uninitialized(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl DataFlowSolver {
+    /// Joins the lattice element of the value with the given one.
+    pub fn join<V: LatticeValue>(&mut self, value: Value, element: &V) -> ChangeResult {
+        self.update::<V>(value, |current| {
+            let joined = current.join(element);
+            match joined == *current {
+                true => ChangeResult::NoChange,
+                false => {
+                    *current = joined;
+                    ChangeResult::Change
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::Liveness;
+    use crate::{parse, Context};
+
+    #[test]
+    fn should_join_the_elements_of_the_values() {
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#""builtin.module"() ({
+  %0 = "other.op"() : () -> i32
+}) : () -> ()
+"#,
+        )
+        .unwrap();
+        let value = module.walk(&ctx)[1].result(&ctx, 0);
+        let mut solver = DataFlowSolver::new();
+        assert!(Liveness::default().is_uninitialized());
+
+        assert_eq!(solver.join(value, &Liveness(false)), ChangeResult::NoChange);
+        assert_eq!(solver.join(value, &Liveness(true)), ChangeResult::Change);
+        assert_eq!(solver.join(value, &Liveness(false)), ChangeResult::NoChange);
+        assert_eq!(solver.lookup::<Liveness>(value), Some(&Liveness(true)));
+        assert!(!Liveness(true).is_uninitialized());
+    }
+}
@@ -0,0 +1,114 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Liveness of the values, the ones contributing to the side effects, the branch conditions or the results
+//! returned to the unknown callers, like the upstream `LivenessAnalysis`.
+//!
+
+use crate::dataflow::{AnalysisState, LatticeValue, SparseBackwardAnalysis};
+use crate::{Context, Operation, Value};
+
+/// Whether the value may be needed, like the upstream `Liveness` lattice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Liveness(pub bool);
+
+impl AnalysisState for Liveness {}
+
+impl LatticeValue for Liveness {
+    fn join(&self, other: &Self) -> Self {
+        Liveness(self.0 || other.0)
+    }
+}
+
+impl Liveness {
+    pub fn is_live(self) -> bool {
+        self.0
+    }
+}
+
+/// Backward analysis of the [Liveness], the operands of the impure operations are live, the ones of the pure
+/// operations are live once any result is.
+#[derive(Debug, Default)]
+pub struct LivenessAnalysis;
+
+impl SparseBackwardAnalysis for LivenessAnalysis {
+    type Lattice = Liveness;
+
+    fn visit_operation(&mut self, ctx: &mut Context, op: Operation, results: &[Liveness]) -> Vec<Liveness> {
+        let pure = ctx.dialect_of(op).is_some_and(|dialect| dialect.is_pure(ctx, op));
+        let live = !pure || results.iter().any(|result| result.is_live());
+        vec![Liveness(live); op.operands(ctx).len()]
+    }
+
+    fn visit_branch_operand(&mut self, _ctx: &Context, _op: Operation, _index: usize) -> Liveness {
+        Liveness(true)
+    }
+
+    fn exit_state(&self, _ctx: &Context, _value: Value) -> Liveness {
+        Liveness(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::tests::{arguments, results, run};
+    use crate::dataflow::SparseBackward;
+
+    #[test]
+    fn should_join_the_liveness() {
+        assert_eq!(Liveness(false).join(&Liveness(false)), Liveness(false));
+        assert_eq!(Liveness(false).join(&Liveness(true)), Liveness(true));
+        assert_eq!(Liveness(true).join(&Liveness(false)), Liveness(true));
+    }
+
+    #[test]
+    fn should_keep_the_branch_conditions_live() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "arith.addi"(%arg0, %arg0) : (i32, i32) -> i32
+    "test.cond_br"(%arg1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:
+    "test.br"(%0)[^bb3] : (i32) -> ()
+  ^bb2:
+    "test.br"(%arg0)[^bb3] : (i32) -> ()
+  ^bb3(%1: i32):
+    "test.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseBackward::new(LivenessAnalysis));
+            },
+        );
+        let live = |value: Value| {
+            solver
+                .lookup::<Liveness>(value)
+                .is_some_and(|liveness| liveness.is_live())
+        };
+        let [returned, condition] = arguments(&ctx, module)[..] else {
+            panic!("two arguments");
+        };
+        assert!(live(returned));
+        assert!(live(condition));
+        assert!(!live(results(&ctx, module, "arith.addi")[0]));
+    }
+}
@@ -0,0 +1,284 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Solver of the data-flow analyses running to the fixpoint, like the upstream `DataFlowSolver`.
+//! The analyses read the states of the lattice anchors on behalf of the program points they visit,
+//! so every point is revisited once any state it read changes.
+//!
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::{Block, Context, Operation, Value};
+
+/// Point the analyses visit, like the upstream `ProgramPoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgramPoint {
+    /// The operation, computing its results or its operands.
+    Operation(Operation),
+    /// Start of the block, computing its arguments.
+    Block(Block),
+}
+
+/// IR entity an analysis state is attached to, like the upstream `LatticeAnchor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LatticeAnchor {
+    Value(Value),
+    Block(Block),
+    Operation(Operation),
+    /// Control flow edge from the block to its successor.
+    Edge(Block, Block),
+}
+
+impl From<Value> for LatticeAnchor {
+    fn from(value: Value) -> Self {
+        LatticeAnchor::Value(value)
+    }
+}
+
+impl From<Block> for LatticeAnchor {
+    fn from(block: Block) -> Self {
+        LatticeAnchor::Block(block)
+    }
+}
+
+impl From<Operation> for LatticeAnchor {
+    fn from(op: Operation) -> Self {
+        LatticeAnchor::Operation(op)
+    }
+}
+
+/// Whether an update changed the state, like the upstream `ChangeResult`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeResult {
+    NoChange,
+    Change,
+}
+
+/// State of a lattice anchor, the default is the initial state of the anchors not visited yet.
+pub trait AnalysisState: Any + fmt::Debug + Clone + Default {}
+
+/// Analysis visiting the program points, like the upstream `DataFlowAnalysis`.
+pub trait DataFlowAnalysis {
+    /// Seeds the states and enqueues the points of the IR nested in the `top` operation.
+    fn initialize(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, top: Operation);
+
+    /// Updates the states depending on the point.
+    fn visit(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, point: ProgramPoint);
+}
+
+struct Entry {
+    state: Box<dyn Any>,
+    /// Points of the analyses to revisit once the state changes.
+    dependents: Vec<(ProgramPoint, usize)>,
+}
+
+/// Runs the loaded analyses to the fixpoint, keeping the states of the lattice anchors by their types.
+#[derive(Default)]
+pub struct DataFlowSolver {
+    analyses: Vec<Box<dyn DataFlowAnalysis>>,
+    states: HashMap<(LatticeAnchor, TypeId), Entry>,
+    worklist: VecDeque<(ProgramPoint, usize)>,
+    /// Analysis being initialized or visiting a point.
+    current: usize,
+}
+
+impl DataFlowSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&mut self, analysis: impl DataFlowAnalysis + 'static) -> &mut Self {
+        self.analyses.push(Box::new(analysis));
+        self
+    }
+
+    /// Initializes the analyses on the operation, then visits the enqueued points until no state changes.
+    pub fn initialize_and_run(&mut self, ctx: &mut Context, top: Operation) {
+        let mut analyses = std::mem::take(&mut self.analyses);
+        for (index, analysis) in analyses.iter_mut().enumerate() {
+            self.current = index;
+            analysis.initialize(ctx, self, top);
+        }
+        while let Some((point, index)) = self.worklist.pop_front() {
+            self.current = index;
+            analyses[index].visit(ctx, self, point);
+        }
+        self.analyses = analyses;
+    }
+
+    /// State of the anchor, `None` if no analysis has touched it.
+    pub fn lookup<S: AnalysisState>(&self, anchor: impl Into<LatticeAnchor>) -> Option<&S> {
+        let entry = self.states.get(&(anchor.into(), TypeId::of::<S>()))?;
+        entry.state.downcast_ref()
+    }
+
+    /// State of the anchor, the `dependent` point of the current analysis is revisited once it changes.
+    pub fn read<S: AnalysisState>(&mut self, anchor: impl Into<LatticeAnchor>, dependent: ProgramPoint) -> S {
+        let subscriber = (dependent, self.current);
+        let entry = self.entry::<S>(anchor.into());
+        if !entry.dependents.contains(&subscriber) {
+            entry.dependents.push(subscriber);
+        }
+        entry.state.downcast_ref::<S>().expect("the state of its type").clone()
+    }
+
+    /// Updates the state of the anchor, enqueuing its dependents once it changes.
+    pub fn update<S: AnalysisState>(
+        &mut self,
+        anchor: impl Into<LatticeAnchor>,
+        update: impl FnOnce(&mut S) -> ChangeResult,
+    ) -> ChangeResult {
+        let entry = self.entry::<S>(anchor.into());
+        let change = update(entry.state.downcast_mut::<S>().expect("the state of its type"));
+        if change == ChangeResult::Change {
+            let dependents = entry.dependents.clone();
+            self.worklist.extend(dependents);
+        }
+        change
+    }
+
+    /// Enqueues the point for the current analysis.
+    pub fn enqueue(&mut self, point: ProgramPoint) {
+        self.worklist.push_back((point, self.current));
+    }
+
+    fn entry<S: AnalysisState>(&mut self, anchor: LatticeAnchor) -> &mut Entry {
+        self.states.entry((anchor, TypeId::of::<S>())).or_insert_with(|| Entry {
+            state: Box::new(S::default()),
+            dependents: vec![],
+        })
+    }
+}
+
+impl fmt::Debug for DataFlowSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DataFlowSolver")
+            .field("analyses", &self.analyses.len())
+            .field("states", &self.states.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parse;
+
+    const SOURCE: &str = r#""builtin.module"() ({
+  "other.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "other.constant"() : () -> i32
+    %1 = "other.op"(%arg0) : (i32) -> i32
+    %2 = "other.op"(%1, %0) : (i32, i32) -> i32
+    %3 = "other.op"(%0) : (i32) -> i32
+    "other.return"(%2) : (i32) -> ()
+  }) : () -> ()
+}) : () -> ()
+"#;
+
+    /// Whether the value depends on a block argument.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    struct Reached(bool);
+
+    impl AnalysisState for Reached {}
+
+    /// Reaches the results of the operations of the reached operands, visiting them in the reverse order
+    /// so the dependents have to be revisited.
+    struct Reachability {
+        visits: Rc<Cell<usize>>,
+    }
+
+    impl DataFlowAnalysis for Reachability {
+        fn initialize(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, top: Operation) {
+            let ops = top.walk(ctx);
+            for op in ops.iter().rev().filter(|op| **op != top) {
+                solver.enqueue(ProgramPoint::Operation(*op));
+            }
+            for op in ops {
+                for region in op.regions(ctx) {
+                    for block in region.blocks(ctx) {
+                        for argument in block.arguments(ctx) {
+                            solver.update::<Reached>(*argument, reach);
+                        }
+                    }
+                }
+            }
+        }
+
+        fn visit(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, point: ProgramPoint) {
+            let ProgramPoint::Operation(op) = point else {
+                return;
+            };
+            self.visits.set(self.visits.get() + 1);
+            let mut reached = false;
+            for operand in op.operands(ctx).iter().copied() {
+                reached |= solver.read::<Reached>(operand, point).0;
+            }
+            for result in op.results(ctx).iter().copied() {
+                solver.update::<Reached>(result, |state| match reached {
+                    true => reach(state),
+                    false => ChangeResult::NoChange,
+                });
+            }
+        }
+    }
+
+    fn reach(state: &mut Reached) -> ChangeResult {
+        match std::mem::replace(&mut state.0, true) {
+            true => ChangeResult::NoChange,
+            false => ChangeResult::Change,
+        }
+    }
+
+    #[test]
+    fn should_revisit_the_dependents_until_the_fixpoint() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let visits = Rc::new(Cell::new(0));
+        let mut solver = DataFlowSolver::new();
+        solver.load(Reachability { visits: visits.clone() });
+        solver.initialize_and_run(&mut ctx, module);
+
+        let ops = module.walk(&ctx);
+        let reached = |op: &Operation| solver.lookup::<Reached>(op.result(&ctx, 0)).unwrap().0;
+        assert_eq!(ops[3..6].iter().map(reached).collect::<Vec<_>>(), [true, true, false]);
+        assert!(!reached(&ops[2]));
+        // The six operations, then the reached `%2` and the `other.return` reading it.
+        assert_eq!(visits.get(), 8);
+    }
+
+    #[test]
+    fn should_keep_the_states_by_the_anchor_and_the_type() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let op = module.walk(&ctx)[2];
+        let value = op.result(&ctx, 0);
+        let mut solver = DataFlowSolver::new();
+        assert_eq!(solver.lookup::<Reached>(value), None);
+
+        assert_eq!(solver.update::<Reached>(value, reach), ChangeResult::Change);
+        assert_eq!(solver.update::<Reached>(value, reach), ChangeResult::NoChange);
+        assert_eq!(solver.lookup::<Reached>(value), Some(&Reached(true)));
+        assert_eq!(solver.lookup::<Reached>(op), None);
+        assert_eq!(solver.lookup::<crate::dataflow::Liveness>(value), None);
+    }
+}
@@ -0,0 +1,607 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Sparse analyses propagating the lattice elements of the values along the def-use chains, through the
+//! branches, the region branches and the calls, like the upstream `SparseForwardDataFlowAnalysis` and
+//! `SparseBackwardDataFlowAnalysis`.
+//! Both skip the blocks the [DeadCodeAnalysis](crate::dataflow::DeadCodeAnalysis) hasn't proven
+//! executable, so it has to be loaded along.
+//!
+
+use crate::dataflow::dead_code::{callable_body, callee};
+use crate::dataflow::{
+    ChangeResult, DataFlowAnalysis, DataFlowSolver, Executable, LatticeAnchor, LatticeValue, PredecessorState,
+    ProgramPoint,
+};
+use crate::{
+    Block, BranchOpInterface, Context, DominanceInfo, Operation, Region, RegionBranchOpInterface,
+    SymbolTableCollection, Value,
+};
+
+/// Transfer functions of a sparse forward analysis, computing the results from the operands.
+pub trait SparseForwardAnalysis {
+    type Lattice: LatticeValue;
+
+    /// Lattice elements of the results given the initialized ones of the operands.
+    fn visit_operation(&mut self, ctx: &mut Context, op: Operation, operands: &[Self::Lattice]) -> Vec<Self::Lattice>;
+
+    /// Pessimistic lattice element of the values of unknown origin, like the arguments of the public functions.
+    fn entry_state(&self, ctx: &Context, value: Value) -> Self::Lattice;
+
+    /// Element replacing the `joined` one of the block argument on a loop back edge, so the infinite
+    /// ascending chains terminate.
+    fn widen(&self, _ctx: &Context, _value: Value, _previous: &Self::Lattice, joined: Self::Lattice) -> Self::Lattice {
+        joined
+    }
+}
+
+/// Transfer functions of a sparse backward analysis, computing the operands from the results.
+pub trait SparseBackwardAnalysis {
+    type Lattice: LatticeValue;
+
+    /// Lattice elements of the operands given the ones of the results.
+    fn visit_operation(&mut self, ctx: &mut Context, op: Operation, results: &[Self::Lattice]) -> Vec<Self::Lattice>;
+
+    /// Lattice element of the operand of a control flow operation not forwarded to any successor,
+    /// like a branch condition.
+    fn visit_branch_operand(&mut self, ctx: &Context, op: Operation, index: usize) -> Self::Lattice;
+
+    /// Lattice element of the values flowing to the unknown places, like the results of the public functions.
+    fn exit_state(&self, ctx: &Context, value: Value) -> Self::Lattice;
+}
+
+/// [DataFlowAnalysis] running the sparse forward analysis.
+#[derive(Debug)]
+pub struct SparseForward<A> {
+    analysis: A,
+    dominance: Option<DominanceInfo>,
+}
+
+impl<A: SparseForwardAnalysis> SparseForward<A> {
+    pub fn new(analysis: A) -> Self {
+        Self {
+            analysis,
+            dominance: None,
+        }
+    }
+
+    fn join(&self, ctx: &Context, solver: &mut DataFlowSolver, value: Value, element: &A::Lattice, back_edge: bool) {
+        solver.update::<A::Lattice>(value, |current| {
+            let joined = current.join(element);
+            if joined == *current {
+                return ChangeResult::NoChange;
+            }
+            *current = match back_edge && !current.is_uninitialized() {
+                true => self.analysis.widen(ctx, value, current, joined),
+                false => joined,
+            };
+            ChangeResult::Change
+        });
+    }
+
+    fn join_entry_state(&self, ctx: &Context, solver: &mut DataFlowSolver, value: Value) {
+        let element = self.analysis.entry_state(ctx, value);
+        self.join(ctx, solver, value, &element, false);
+    }
+
+    /// Joins the forwarded operands of the known predecessors with the inputs among the `values`.
+    fn join_predecessors(
+        &self,
+        ctx: &Context,
+        solver: &mut DataFlowSolver,
+        values: &[Value],
+        state: &PredecessorState,
+        point: ProgramPoint,
+        region: Option<Region>,
+    ) {
+        for predecessor in state.known() {
+            let back_edge = region.is_some() && predecessor.op.parent_region(ctx) == region;
+            for (operand, input) in predecessor.operands.iter().zip(&predecessor.inputs) {
+                if values.contains(input) {
+                    let element = solver.read::<A::Lattice>(*operand, point);
+                    self.join(ctx, solver, *input, &element, back_edge);
+                }
+            }
+        }
+    }
+
+    fn visit_operation(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, op: Operation, point: ProgramPoint) {
+        let results = op.results(ctx).to_vec();
+        if results.is_empty() {
+            return;
+        }
+        if is_control_flow_result(ctx, op) {
+            let state = solver.read::<PredecessorState>(op, point);
+            match state.all_known() {
+                true => self.join_predecessors(ctx, solver, &results, &state, point, None),
+                false => {
+                    for result in results {
+                        self.join_entry_state(ctx, solver, result);
+                    }
+                }
+            }
+            return;
+        }
+        let mut operands = vec![];
+        let values = op.operands(ctx).to_vec();
+        for operand in values {
+            let element = solver.read::<A::Lattice>(operand, point);
+            if element.is_uninitialized() {
+                return;
+            }
+            operands.push(element);
+        }
+        let elements = self.analysis.visit_operation(ctx, op, &operands);
+        for (result, element) in results.into_iter().zip(elements) {
+            self.join(ctx, solver, result, &element, false);
+        }
+    }
+
+    fn visit_block(&mut self, ctx: &Context, solver: &mut DataFlowSolver, block: Block, point: ProgramPoint) {
+        let arguments = block.arguments(ctx).to_vec();
+        if arguments.is_empty() {
+            return;
+        }
+        if block.is_entry_block(ctx) {
+            let state = solver.read::<PredecessorState>(block, point);
+            if !state.all_known() {
+                for argument in arguments {
+                    self.join_entry_state(ctx, solver, argument);
+                }
+                return;
+            }
+            if state.known().is_empty() {
+                return;
+            }
+            self.join_predecessors(ctx, solver, &arguments, &state, point, block.parent_region(ctx));
+            // The arguments no predecessor forwards to, like the induction variables.
+            for argument in arguments {
+                if !state
+                    .known()
+                    .iter()
+                    .any(|predecessor| predecessor.inputs.contains(&argument))
+                {
+                    self.join_entry_state(ctx, solver, argument);
+                }
+            }
+            return;
+        }
+        let dominance = self.dominance.as_ref().expect("the analysis is initialized");
+        for predecessor in block.predecessors(ctx) {
+            if !solver
+                .read::<Executable>(LatticeAnchor::Edge(predecessor, block), point)
+                .is_live()
+            {
+                continue;
+            }
+            let back_edge = dominance.dominates_block(ctx, block, predecessor);
+            let terminator = predecessor
+                .terminator(ctx)
+                .expect("the predecessors end with the branches");
+            let branch = terminator.as_interface::<dyn BranchOpInterface>(ctx);
+            for (index, successor) in terminator.successors(ctx).iter().enumerate() {
+                if *successor != block {
+                    continue;
+                }
+                match branch.map(|branch| branch.get_successor_operands(ctx, terminator, index as u32)) {
+                    Some(operands) => {
+                        for (argument, operand) in arguments.iter().zip(operands) {
+                            let element = solver.read::<A::Lattice>(operand, point);
+                            self.join(ctx, solver, *argument, &element, back_edge);
+                        }
+                    }
+                    None => {
+                        for argument in &arguments {
+                            self.join_entry_state(ctx, solver, *argument);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<A: SparseForwardAnalysis> DataFlowAnalysis for SparseForward<A> {
+    fn initialize(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, top: Operation) {
+        self.dominance = Some(DominanceInfo::new(ctx, top));
+        for op in top.walk(ctx) {
+            if op != top {
+                solver.enqueue(ProgramPoint::Operation(op));
+            }
+            for region in op.regions(ctx) {
+                for block in region.blocks(ctx) {
+                    solver.enqueue(ProgramPoint::Block(*block));
+                }
+            }
+        }
+    }
+
+    fn visit(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, point: ProgramPoint) {
+        match point {
+            ProgramPoint::Operation(op) => {
+                let Some(block) = op.parent_block(ctx) else {
+                    return;
+                };
+                if solver.read::<Executable>(block, point).is_live() {
+                    self.visit_operation(ctx, solver, op, point);
+                }
+            }
+            ProgramPoint::Block(block) => {
+                if solver.read::<Executable>(block, point).is_live() {
+                    self.visit_block(ctx, solver, block, point);
+                }
+            }
+        }
+    }
+}
+
+/// [DataFlowAnalysis] running the sparse backward analysis.
+#[derive(Debug)]
+pub struct SparseBackward<A> {
+    analysis: A,
+}
+
+impl<A: SparseBackwardAnalysis> SparseBackward<A> {
+    pub fn new(analysis: A) -> Self {
+        Self { analysis }
+    }
+
+    /// Joins the operands of the operation forwarded to the `targets` with their elements,
+    /// marking them as `forwarded`.
+    fn forward(
+        solver: &mut DataFlowSolver,
+        operands: &[Value],
+        forwarded: &mut [bool],
+        values: &[Value],
+        targets: &[Value],
+        point: ProgramPoint,
+    ) {
+        for (value, target) in values.iter().zip(targets) {
+            let element = solver.read::<A::Lattice>(*target, point);
+            solver.join(*value, &element);
+            for (index, operand) in operands.iter().enumerate() {
+                forwarded[index] |= operand == value;
+            }
+        }
+    }
+
+    /// Propagates the elements of the values the operation forwards its operands to, `false` if the
+    /// operation doesn't transfer the control.
+    fn visit_control_flow(
+        &mut self,
+        ctx: &Context,
+        solver: &mut DataFlowSolver,
+        op: Operation,
+        point: ProgramPoint,
+    ) -> bool {
+        let operands = op.operands(ctx).to_vec();
+        let mut forwarded = vec![false; operands.len()];
+        let mut control_flow = false;
+        let no_constants = vec![None; operands.len()];
+        let branch = op.as_interface::<dyn BranchOpInterface>(ctx);
+        for (index, successor) in op.successors(ctx).iter().enumerate() {
+            control_flow = true;
+            if let Some(values) = branch.map(|branch| branch.get_successor_operands(ctx, op, index as u32)) {
+                Self::forward(
+                    solver,
+                    &operands,
+                    &mut forwarded,
+                    &values,
+                    successor.arguments(ctx),
+                    point,
+                );
+            }
+        }
+        if let Some(successors) = op
+            .as_interface::<dyn RegionBranchOpInterface>(ctx)
+            .map(|region_branch| region_branch.get_successor_regions(ctx, op, None, &no_constants))
+        {
+            control_flow = true;
+            for successor in successors {
+                Self::forward(
+                    solver,
+                    &operands,
+                    &mut forwarded,
+                    &successor.operands,
+                    &successor.inputs,
+                    point,
+                );
+            }
+        }
+        let callable =
+            callee(ctx, op).and_then(|callee| SymbolTableCollection::new().lookup_nearest_symbol_from(ctx, op, callee));
+        if let Some(entry) = callable
+            .and_then(|callable| callable_body(ctx, callable))
+            .and_then(|body| body.entry_block(ctx))
+        {
+            control_flow = true;
+            Self::forward(
+                solver,
+                &operands,
+                &mut forwarded,
+                &operands,
+                entry.arguments(ctx),
+                point,
+            );
+        }
+        // The branches ending the blocks forward to their successors only, not to the parent.
+        let exits = op.next(ctx).is_none() && op.successors(ctx).is_empty();
+        if let Some(region) = exits.then(|| op.parent_region(ctx)).flatten() {
+            let parent = region.parent_op(ctx);
+            if callable_body(ctx, parent) == Some(region) {
+                control_flow = true;
+                let entry = region.entry_block(ctx).expect("the callable body has the entry block");
+                let callers = solver.read::<PredecessorState>(entry, point);
+                if callers.all_known() {
+                    for caller in callers.known() {
+                        Self::forward(
+                            solver,
+                            &operands,
+                            &mut forwarded,
+                            &operands,
+                            caller.op.results(ctx),
+                            point,
+                        );
+                    }
+                } else {
+                    for operand in &operands {
+                        let element = self.analysis.exit_state(ctx, *operand);
+                        solver.join(*operand, &element);
+                    }
+                    forwarded.fill(true);
+                }
+            } else if let Some(successors) = parent
+                .as_interface::<dyn RegionBranchOpInterface>(ctx)
+                .map(|region_branch| region_branch.get_successor_regions(ctx, parent, Some(op), &no_constants))
+            {
+                control_flow = true;
+                for successor in successors {
+                    Self::forward(
+                        solver,
+                        &operands,
+                        &mut forwarded,
+                        &successor.operands,
+                        &successor.inputs,
+                        point,
+                    );
+                }
+            }
+        }
+        if control_flow {
+            for (index, operand) in operands.iter().enumerate() {
+                if !forwarded[index] {
+                    let element = self.analysis.visit_branch_operand(ctx, op, index);
+                    solver.join(*operand, &element);
+                }
+            }
+        }
+        control_flow
+    }
+}
+
+impl<A: SparseBackwardAnalysis> DataFlowAnalysis for SparseBackward<A> {
+    fn initialize(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, top: Operation) {
+        for op in top.walk(ctx).into_iter().filter(|op| *op != top) {
+            solver.enqueue(ProgramPoint::Operation(op));
+        }
+    }
+
+    fn visit(&mut self, ctx: &mut Context, solver: &mut DataFlowSolver, point: ProgramPoint) {
+        let ProgramPoint::Operation(op) = point else {
+            return;
+        };
+        let Some(block) = op.parent_block(ctx) else {
+            return;
+        };
+        if !solver.read::<Executable>(block, point).is_live() || self.visit_control_flow(ctx, solver, op, point) {
+            return;
+        }
+        let mut results = vec![];
+        let values = op.results(ctx).to_vec();
+        for result in values {
+            results.push(solver.read::<A::Lattice>(result, point));
+        }
+        let elements = self.analysis.visit_operation(ctx, op, &results);
+        let operands = op.operands(ctx).to_vec();
+        for (operand, element) in operands.into_iter().zip(elements) {
+            solver.join(operand, &element);
+        }
+    }
+}
+
+/// Whether the results come from the terminators of the regions or of the callee, rather than from the
+/// operands.
+fn is_control_flow_result(ctx: &Context, op: Operation) -> bool {
+    callee(ctx, op).is_some() || op.as_interface::<dyn RegionBranchOpInterface>(ctx).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataflow::tests::{arguments, results, run};
+    use crate::dataflow::{AnalysisState, ConstantValue, Liveness, LivenessAnalysis};
+
+    /// Upper bound of the number of the operations a value is computed through.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    enum Depth {
+        #[default]
+        Uninitialized,
+        Bounded(u32),
+        Unknown,
+    }
+
+    impl AnalysisState for Depth {}
+
+    impl LatticeValue for Depth {
+        fn join(&self, other: &Self) -> Self {
+            match (self, other) {
+                (Depth::Uninitialized, other) | (other, Depth::Uninitialized) => *other,
+                (Depth::Bounded(lhs), Depth::Bounded(rhs)) => Depth::Bounded(*lhs.max(rhs)),
+                _ => Depth::Unknown,
+            }
+        }
+    }
+
+    /// Ascends infinitely along the loops unless widened.
+    struct DepthAnalysis;
+
+    impl SparseForwardAnalysis for DepthAnalysis {
+        type Lattice = Depth;
+
+        fn visit_operation(&mut self, ctx: &mut Context, op: Operation, operands: &[Depth]) -> Vec<Depth> {
+            let depth = match operands
+                .iter()
+                .fold(Depth::Bounded(0), |depth, operand| depth.join(operand))
+            {
+                Depth::Bounded(depth) => Depth::Bounded(depth + 1),
+                depth => depth,
+            };
+            vec![depth; op.results(ctx).len()]
+        }
+
+        fn entry_state(&self, _ctx: &Context, _value: Value) -> Depth {
+            Depth::Bounded(0)
+        }
+
+        fn widen(&self, _ctx: &Context, _value: Value, _previous: &Depth, _joined: Depth) -> Depth {
+            Depth::Unknown
+        }
+    }
+
+    /// The arguments of the blocks after the entry ones, in the pre-order.
+    fn block_arguments(ctx: &Context, module: Operation) -> Vec<Value> {
+        let funcs = module.walk(ctx).into_iter().filter(|op| op.name(ctx) == "test.func");
+        let blocks = funcs.flat_map(|func| func.region(ctx, 0).blocks(ctx)[1..].to_vec());
+        blocks.flat_map(|block| block.arguments(ctx).to_vec()).collect()
+    }
+
+    #[test]
+    fn should_propagate_to_the_fixpoint_of_the_loops() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i1):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    "test.br"(%0, %0)[^bb1] : (i32, i32) -> ()
+  ^bb1(%1: i32, %2: i32):
+    "test.cond_br"(%arg0)[^bb2, ^bb3] : (i1) -> ()
+  ^bb2:
+    %3 = "arith.addi"(%2, %0) : (i32, i32) -> i32
+    "test.br"(%1, %3)[^bb1] : (i32, i32) -> ()
+  ^bb3:
+    "test.return"(%1) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |_| {},
+        );
+        let i32 = ctx.integer_type(32);
+        let constant = |value: Value| *solver.lookup::<ConstantValue>(value).unwrap();
+        let [invariant, induction] = block_arguments(&ctx, module)[..] else {
+            panic!("two loop arguments");
+        };
+        assert_eq!(constant(invariant), ConstantValue::Constant(ctx.integer_attr(1, i32)));
+        assert_eq!(constant(induction), ConstantValue::Unknown);
+        assert_eq!(constant(results(&ctx, module, "arith.addi")[0]), ConstantValue::Unknown);
+    }
+
+    #[test]
+    fn should_widen_along_the_back_edges_only() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "other.op"(%arg0) : (i32) -> i32
+    "test.cond_br"(%arg1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:
+    %1 = "other.op"(%0) : (i32) -> i32
+    "test.br"(%1)[^bb3] : (i32) -> ()
+  ^bb2:
+    "test.br"(%0)[^bb3] : (i32) -> ()
+  ^bb3(%2: i32):
+    "test.br"(%2)[^bb4] : (i32) -> ()
+  ^bb4(%3: i32):
+    %4 = "other.op"(%3) : (i32) -> i32
+    "test.cond_br"(%arg1)[^bb5, ^bb6] : (i1) -> ()
+  ^bb5:
+    "test.br"(%4)[^bb4] : (i32) -> ()
+  ^bb6:
+    "test.return"(%4) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseForward::new(DepthAnalysis));
+            },
+        );
+        let depth = |value: Value| *solver.lookup::<Depth>(value).unwrap();
+        let [merge, header] = block_arguments(&ctx, module)[..] else {
+            panic!("two block arguments");
+        };
+        let ops = results(&ctx, module, "other.op");
+        assert_eq!(depth(ops[0]), Depth::Bounded(1));
+        assert_eq!(depth(merge), Depth::Bounded(2));
+        assert_eq!(depth(header), Depth::Unknown);
+        assert_eq!(depth(ops[2]), Depth::Unknown);
+    }
+
+    #[test]
+    fn should_propagate_backward_through_the_region_branches() {
+        let mut ctx = Context::new();
+        let (solver, module) = run(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "test.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "arith.addi"(%arg0, %arg0) : (i32, i32) -> i32
+    %1 = "arith.addi"(%0, %0) : (i32, i32) -> i32
+    %2 = "test.if"(%arg1) ({
+      "test.yield"(%0) : (i32) -> ()
+    }, {
+      "test.yield"(%0) : (i32) -> ()
+    }) : (i1) -> i32
+    %3 = "test.if"(%arg1) ({
+      "test.yield"(%1) : (i32) -> ()
+    }, {
+      "test.yield"(%1) : (i32) -> ()
+    }) : (i1) -> i32
+    "test.return"(%2) : (i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}) : () -> ()
+"#,
+            |solver| {
+                solver.load(SparseBackward::new(LivenessAnalysis));
+            },
+        );
+        let live = |value: Value| {
+            solver
+                .lookup::<Liveness>(value)
+                .is_some_and(|liveness| liveness.is_live())
+        };
+        let additions = results(&ctx, module, "arith.addi");
+        let branches = results(&ctx, module, "test.if");
+        assert!(live(arguments(&ctx, module)[1]));
+        assert!(live(branches[0]));
+        assert!(live(additions[0]));
+        assert!(!live(branches[1]));
+        assert!(!live(additions[1]));
+    }
+}
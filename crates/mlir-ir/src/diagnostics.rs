@@ -0,0 +1,296 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Diagnostics of the core IR, reported on the [Location]s of the operations, like the upstream `Diagnostic`.
+//! The locations are kept apart from the [Context], so the errors raised within the pass manager worker
+//! threads are reported by the embedding compiler, mapping them back to its own source positions.
+//!
+
+use std::fmt;
+
+use crate::printer::escape_string;
+use crate::{Attribute, AttributeKind, Context, ConversionError, PassError, Printer, PrinterOptions, VerifyError};
+
+/// Location of an operation, owning the location attribute structure.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Location {
+    #[default]
+    Unknown,
+    FileLineCol {
+        filename: String,
+        line: u32,
+        column: u32,
+    },
+    Name {
+        name: String,
+        child: Option<Box<Location>>,
+    },
+    /// The `callee` location inlined at the `caller` one.
+    CallSite {
+        callee: Box<Location>,
+        caller: Box<Location>,
+    },
+    /// Locations of the merged or expanded code, the `metadata` is kept as its textual form.
+    Fused {
+        locations: Vec<Location>,
+        metadata: Option<String>,
+    },
+}
+
+impl Location {
+    /// Location of the location attribute, the other attributes are unknown locations.
+    pub fn new(ctx: &Context, location: Attribute) -> Self {
+        match location.kind(ctx) {
+            AttributeKind::FileLineColLoc { filename, line, column } => Location::FileLineCol {
+                filename: filename.clone(),
+                line: *line,
+                column: *column,
+            },
+            AttributeKind::NameLoc { name, child } => Location::Name {
+                name: name.clone(),
+                child: child.map(|child| Box::new(Location::new(ctx, child))),
+            },
+            AttributeKind::CallSiteLoc { callee, caller } => Location::CallSite {
+                callee: Box::new(Location::new(ctx, *callee)),
+                caller: Box::new(Location::new(ctx, *caller)),
+            },
+            AttributeKind::FusedLoc { locations, metadata } => Location::Fused {
+                locations: locations.iter().map(|location| Location::new(ctx, *location)).collect(),
+                metadata: metadata.map(|metadata| {
+                    let mut printer = Printer::new(ctx, PrinterOptions::default());
+                    printer.print_attribute(metadata);
+                    printer.finish()
+                }),
+            },
+            _ => Location::Unknown,
+        }
+    }
+
+    /// Innermost file location, the callee of the call sites and the first of the fused locations.
+    pub fn file_line_col(&self) -> Option<(&str, u32, u32)> {
+        match self {
+            Location::Unknown => None,
+            Location::FileLineCol { filename, line, column } => Some((filename, *line, *column)),
+            Location::Name { child, .. } => child.as_ref()?.file_line_col(),
+            Location::CallSite { callee, .. } => callee.file_line_col(),
+            Location::Fused { locations, .. } => locations.iter().find_map(Location::file_line_col),
+        }
+    }
+
+    fn fmt_body(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Unknown => f.write_str("unknown"),
+            Location::FileLineCol { filename, line, column } => {
+                write!(f, "{}:{line}:{column}", escape_string(filename))
+            }
+            Location::Name { name, child } => {
+                f.write_str(&escape_string(name))?;
+                match child {
+                    Some(child) => {
+                        f.write_str("(")?;
+                        child.fmt_body(f)?;
+                        f.write_str(")")
+                    }
+                    None => Ok(()),
+                }
+            }
+            Location::CallSite { callee, caller } => {
+                f.write_str("callsite(")?;
+                callee.fmt_body(f)?;
+                f.write_str(" at ")?;
+                caller.fmt_body(f)?;
+                f.write_str(")")
+            }
+            Location::Fused { locations, metadata } => {
+                f.write_str("fused")?;
+                if let Some(metadata) = metadata {
+                    write!(f, "<{metadata}>")?;
+                }
+                f.write_str("[")?;
+                for (index, location) in locations.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    location.fmt_body(f)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// `loc(...)` of the location, like the printed location attributes.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("loc(")?;
+        self.fmt_body(f)?;
+        f.write_str(")")
+    }
+}
+
+/// Severity of the diagnostic, like the upstream `DiagnosticSeverity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Remark,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Remark => "remark",
+        })
+    }
+}
+
+/// Message reported on a location, with the attached notes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub location: Location,
+    pub message: String,
+    pub notes: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, location: Location, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            location,
+            message: message.into(),
+            notes: vec![],
+        }
+    }
+
+    pub fn error(location: Location, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, location, message)
+    }
+
+    /// Attaches the note, like the upstream `attachNote`.
+    pub fn with_note(mut self, location: Location, message: impl Into<String>) -> Self {
+        self.notes.push(Diagnostic::new(Severity::Note, location, message));
+        self
+    }
+}
+
+/// `loc(...): error: message` lines of the diagnostic and its notes, like the `mlir-opt` output.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.location, self.severity, self.message)?;
+        for note in &self.notes {
+            write!(f, "\n{note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&VerifyError> for Diagnostic {
+    fn from(error: &VerifyError) -> Self {
+        Diagnostic::error(error.location.clone(), format!("'{}' op {}", error.op, error.message))
+    }
+}
+
+impl From<&ConversionError> for Diagnostic {
+    fn from(error: &ConversionError) -> Self {
+        match error {
+            ConversionError::FailedToLegalize { op, location } => {
+                Diagnostic::error(location.clone(), format!("failed to legalize operation '{op}'"))
+            }
+            ConversionError::IllegalOperation { op, location } => Diagnostic::error(
+                location.clone(),
+                format!("failed to legalize operation '{op}' that was explicitly marked illegal"),
+            ),
+            ConversionError::Ir(error) => Diagnostic::error(Location::Unknown, error.to_string()),
+        }
+    }
+}
+
+/// The failures and the verification errors are reported on their operations, noting the pass,
+/// the other errors are unknown locations.
+impl From<&PassError> for Diagnostic {
+    fn from(error: &PassError) -> Self {
+        match error {
+            PassError::Failed {
+                pass,
+                op,
+                location,
+                message,
+            } => Diagnostic::error(location.clone(), message.clone())
+                .with_note(Location::Unknown, format!("while running the `{pass}` pass on `{op}`")),
+            PassError::Crashed {
+                pass,
+                op,
+                location,
+                message,
+            } => Diagnostic::error(location.clone(), format!("`{pass}` pass crashed: {message}"))
+                .with_note(Location::Unknown, format!("while running the `{pass}` pass on `{op}`")),
+            PassError::Verification { pass, error } => Diagnostic::from(error).with_note(
+                Location::Unknown,
+                format!("verification failed after the `{pass}` pass"),
+            ),
+            error => Diagnostic::error(Location::Unknown, error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn should_print_locations_like_the_location_attributes() {
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#""test.op"() : () -> () loc(fused<"inlined">["lib.rs":3:7, callsite("f"("lib.rs":1:2) at "main.rs":9:5)])
+"#,
+        )
+        .unwrap();
+        let op = module.region(&ctx, 0).entry_block(&ctx).unwrap().operations(&ctx)[0];
+        let location = Location::new(&ctx, op.location(&ctx));
+        let mut printer = Printer::new(&ctx, PrinterOptions::default());
+        printer.print_location(op.location(&ctx));
+        assert_eq!(location.to_string(), printer.finish());
+        assert_eq!(location.file_line_col(), Some(("lib.rs", 3, 7)));
+    }
+
+    #[test]
+    fn should_report_errors_on_their_locations() {
+        let location = Location::FileLineCol {
+            filename: "lib.rs".to_string(),
+            line: 4,
+            column: 9,
+        };
+        let error = PassError::Failed {
+            pass: "convert-vector-to-llvm".to_string(),
+            op: "builtin.module".to_string(),
+            location: location.clone(),
+            message: "failed to legalize operation 'vector.contract'".to_string(),
+        };
+        assert_eq!(
+            Diagnostic::from(&error).to_string(),
+            "loc(\"lib.rs\":4:9): error: failed to legalize operation 'vector.contract'\n\
+             loc(unknown): note: while running the `convert-vector-to-llvm` pass on `builtin.module`"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Dialects registered in the [Context], the hooks of their operations folding, materializing the constants,
+//! providing the canonicalization patterns, declaring the traits and verifying the invariants, like the
+//! upstream `Dialect` and the ODS `fold` and `verify` methods, the control flow is described by the registered
+//! interfaces.
+//! The operations of the unregistered dialects are left as is, the `builtin` dialect is always registered.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{
+    Attribute, Builder, BuiltinDialect, Context, InterfaceRegistry, OpAsmRegistry, Operation, RewritePatternSet,
+    Type, Value, Variadicity,
+};
+
+/// Result of the operation folding, like the upstream `OpFoldResult`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpFoldResult {
+    /// Constant, materialized by the dialect of the folded operation.
+    Attribute(Attribute),
+    /// Existing value, or the result of the operation itself for the in-place folding.
+    Value(Value),
+}
+
+/// Traits of the operations the verifier and the transformations rely on, like the upstream `OpTrait`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpTrait {
+    /// Ends its block, like `func.return`.
+    Terminator,
+    /// The blocks of the regions don't end with the terminators, like `builtin.module`.
+    NoTerminator,
+    /// The nested operations use only the values defined within the operation, like `func.func`.
+    IsolatedFromAbove,
+    /// Scope of the uniquely named symbols nested in its single block, like `builtin.module`.
+    SymbolTable,
+    /// Returns the control to the caller or the parent operation, like `func.return`.
+    ReturnLike,
+}
+
+/// Whether the values of a region follow the SSA dominance, like the upstream `RegionKind`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    /// Control flow graph of the blocks, the values dominate their uses.
+    #[default]
+    SsaCfg,
+    /// Graph of the operations, the values may be used before they are defined.
+    Graph,
+}
+
+/// ODS declaration of an operation generated by the `dialect!` macro, the traits and the numbers of the operands,
+/// results, regions and successors the generated dialects verify, like the upstream `verifyInvariants`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpDefinition {
+    pub name: &'static str,
+    pub traits: &'static [OpTrait],
+    /// No side effects, like the ODS `Pure` operations.
+    pub pure: bool,
+    pub operands: &'static [Variadicity],
+    pub results: &'static [Variadicity],
+    pub regions: &'static [Variadicity],
+    pub successors: &'static [Variadicity],
+}
+
+impl OpDefinition {
+    /// Definition of the operation by its name.
+    pub fn lookup(definitions: &'static [OpDefinition], name: &str) -> Option<&'static OpDefinition> {
+        definitions.iter().find(|definition| definition.name == name)
+    }
+
+    /// Checks the numbers of the operands, results, regions and successors against the ODS ones,
+    /// and the `operandSegmentSizes` and `resultSegmentSizes` of the `AttrSized*Segments` operations.
+    pub fn verify(&self, ctx: &Context, op: Operation) -> Result<(), String> {
+        let groups = [
+            ("operand", self.operands, op.operands(ctx).len(), op.segment_sizes(ctx, "operandSegmentSizes")),
+            ("result", self.results, op.results(ctx).len(), op.segment_sizes(ctx, "resultSegmentSizes")),
+            ("region", self.regions, op.regions(ctx).len(), None),
+            ("successor", self.successors, op.successors(ctx).len(), None),
+        ];
+        for (kind, spec, found, sizes) in groups {
+            let single = spec.iter().filter(|variadicity| **variadicity == Variadicity::Single).count();
+            if single == spec.len() && found != single {
+                let plural = if single == 1 { "" } else { "s" };
+                return Err(format!("expected {single} {kind}{plural}, but found {found}"));
+            }
+            if found < single {
+                return Err(format!("expected {single} or more {kind}s, but found {found}"));
+            }
+            let Some(sizes) = sizes else {
+                continue;
+            };
+            if sizes.len() != spec.len() {
+                return Err(format!(
+                    "'{kind}SegmentSizes' attribute for specifying {kind} segments must have {} elements, but got {}",
+                    spec.len(),
+                    sizes.len()
+                ));
+            }
+            if sizes.iter().sum::<i64>() != found as i64 {
+                return Err(format!("{kind} segment sizes don't add up to the {found} {kind}s"));
+            }
+            for (index, (variadicity, size)) in spec.iter().zip(&sizes).enumerate() {
+                match variadicity {
+                    Variadicity::Single if *size != 1 => {
+                        return Err(format!("{kind} group #{index} requires 1 element, but found {size}"))
+                    }
+                    Variadicity::Optional if !(0..=1).contains(size) => {
+                        return Err(format!("{kind} group #{index} requires 0 or 1 element, but found {size}"))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hooks of the dialect operations, dispatched on the operation names of the namespace.
+pub trait Dialect: Send + Sync {
+    /// Namespace of the operation names, like `arith`.
+    fn namespace(&self) -> &str;
+
+    /// Folds the operation given the constant operands, `None` if it doesn't fold.
+    /// Returning the results of the operation itself signals it was updated in place.
+    fn fold(&self, _ctx: &mut Context, _op: Operation, _operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+        None
+    }
+
+    /// Value of the constant-like operation, like the upstream `m_Constant` matcher.
+    fn constant_value(&self, _ctx: &Context, _op: Operation) -> Option<Attribute> {
+        None
+    }
+
+    /// Creates the constant operation of the folded value at the builder insertion point,
+    /// like the upstream `materializeConstant`.
+    fn materialize_constant(
+        &self,
+        _builder: &mut Builder<'_>,
+        _value: Attribute,
+        _type: Type,
+        _location: Attribute,
+    ) -> Option<Operation> {
+        None
+    }
+
+    /// Whether the operation has no side effects, so it's erased once its results are unused.
+    fn is_pure(&self, _ctx: &Context, _op: Operation) -> bool {
+        false
+    }
+
+    /// Canonicalization patterns of the dialect and its operations.
+    fn canonicalization_patterns(&self, _patterns: &mut RewritePatternSet) {}
+
+    /// Registers the interface implementations of the operations, like the upstream `addInterfaces`.
+    fn register_interfaces(&self, _registry: &mut InterfaceRegistry) {}
+
+    /// Registers the custom forms of the operations, printed and parsed instead of the generic one.
+    fn register_assembly(&self, _registry: &mut OpAsmRegistry) {}
+
+    fn has_trait(&self, _ctx: &Context, _op: Operation, _trait: OpTrait) -> bool {
+        false
+    }
+
+    /// Kind of the region of the operation, like the upstream `RegionKindInterface`.
+    fn region_kind(&self, _ctx: &Context, _op: Operation, _index: usize) -> RegionKind {
+        RegionKind::SsaCfg
+    }
+
+    /// Verifies the invariants of the operation, like the ODS `verify` methods, the message is reported
+    /// on the operation.
+    fn verify(&self, _ctx: &Context, _op: Operation) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Registered dialects by their namespaces, with the interface implementations and the custom forms of their
+/// operations, shared by the contexts of the pass manager worker threads.
+#[derive(Clone)]
+pub struct DialectRegistry {
+    dialects: BTreeMap<String, Arc<dyn Dialect>>,
+    interfaces: InterfaceRegistry,
+    assembly: OpAsmRegistry,
+}
+
+/// Registry of the `builtin` dialect.
+impl Default for DialectRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            dialects: BTreeMap::new(),
+            interfaces: InterfaceRegistry::new(),
+            assembly: OpAsmRegistry::new(),
+        };
+        registry.insert(BuiltinDialect);
+        registry
+    }
+}
+
+impl DialectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the dialect, replacing the one of the same namespace along with its interfaces and custom forms.
+    pub fn insert(&mut self, dialect: impl Dialect + 'static) {
+        self.dialects.insert(dialect.namespace().to_string(), Arc::new(dialect));
+        self.interfaces = InterfaceRegistry::new();
+        self.assembly = OpAsmRegistry::new();
+        for dialect in self.dialects.values() {
+            dialect.register_interfaces(&mut self.interfaces);
+            dialect.register_assembly(&mut self.assembly);
+        }
+    }
+
+    pub fn get(&self, namespace: &str) -> Option<&Arc<dyn Dialect>> {
+        self.dialects.get(namespace)
+    }
+
+    /// Interface implementations of the registered dialects.
+    pub fn interfaces(&self) -> &InterfaceRegistry {
+        &self.interfaces
+    }
+
+    /// Custom forms of the operations of the registered dialects.
+    pub fn assembly(&self) -> &OpAsmRegistry {
+        &self.assembly
+    }
+
+    /// Registered dialects, ordered by their namespaces.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Dialect>> {
+        self.dialects.values()
+    }
+}
+
+impl fmt::Debug for DialectRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.dialects.keys()).finish()
+    }
+}
+
+impl Context {
+    /// Context of the dialects of the registry.
+    pub fn with_dialects(dialects: DialectRegistry) -> Self {
+        Self {
+            dialects,
+            ..Self::default()
+        }
+    }
+
+    pub fn register_dialect(&mut self, dialect: impl Dialect + 'static) {
+        self.dialects.insert(dialect);
+    }
+
+    pub fn dialects(&self) -> &DialectRegistry {
+        &self.dialects
+    }
+
+    /// Dialect of the operation, `None` for the unregistered ones.
+    pub fn dialect_of(&self, op: Operation) -> Option<Arc<dyn Dialect>> {
+        self.dialects.get(op.dialect(self)).cloned()
+    }
+}
+
+impl Operation {
+    /// Whether the dialect of the operation is registered.
+    pub fn is_registered(self, ctx: &Context) -> bool {
+        ctx.dialects.get(self.dialect(ctx)).is_some()
+    }
+
+    /// Whether the registered dialect declares the trait of the operation.
+    pub fn has_trait(self, ctx: &Context, r#trait: OpTrait) -> bool {
+        ctx.dialects
+            .get(self.dialect(ctx))
+            .is_some_and(|dialect| dialect.has_trait(ctx, self, r#trait))
+    }
+
+    /// Kind of the region, the regions of the unregistered operations are the control flow graphs.
+    pub fn region_kind(self, ctx: &Context, index: usize) -> RegionKind {
+        ctx.dialects
+            .get(self.dialect(ctx))
+            .map(|dialect| dialect.region_kind(ctx, self, index))
+            .unwrap_or_default()
+    }
+}
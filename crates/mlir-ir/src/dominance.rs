@@ -0,0 +1,453 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Dominator and post-dominator trees of the region blocks, like the upstream `DominanceInfo` and
+//! `PostDominanceInfo`, computed by the iterative Cooper, Harvey and Kennedy algorithm.
+//! The queries on the operations of different regions lift them into the common region first,
+//! and any operation of a graph region dominates the others.
+//!
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Analysis, Block, Context, Operation, Region, RegionKind, Value, ValueOwner};
+
+/// Immediate dominators of the region blocks, the unreachable blocks are dominated by every block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DominatorTree {
+    /// `None` for the roots of the tree: the entry block, or the exit blocks of the post-dominance.
+    idoms: HashMap<Block, Option<Block>>,
+}
+
+impl DominatorTree {
+    fn new(ctx: &Context, region: Region, post: bool) -> Self {
+        let blocks = region.blocks(ctx);
+        let index: HashMap<Block, usize> = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (*block, index))
+            .collect();
+        // The virtual root goes last, it leads to the entry block, or to the blocks without successors.
+        let root = blocks.len();
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; blocks.len() + 1];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; blocks.len() + 1];
+        let mut edge = |from: usize, to: usize| {
+            successors[from].push(to);
+            predecessors[to].push(from);
+        };
+        for (from, block) in blocks.iter().enumerate() {
+            let targets = block
+                .successors(ctx)
+                .iter()
+                .filter_map(|target| index.get(target).copied());
+            for to in targets {
+                match post {
+                    true => edge(to, from),
+                    false => edge(from, to),
+                }
+            }
+            if post && block.successors(ctx).is_empty() {
+                edge(root, from);
+            }
+        }
+        if !post && !blocks.is_empty() {
+            edge(root, 0);
+        }
+
+        let order = reverse_post_order(root, &successors);
+        let position: HashMap<usize, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(position, node)| (*node, position))
+            .collect();
+        let mut idoms: Vec<Option<usize>> = vec![None; blocks.len() + 1];
+        idoms[root] = Some(root);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in order.iter().skip(1) {
+                let mut processed = predecessors[*node]
+                    .iter()
+                    .filter(|predecessor| idoms[**predecessor].is_some());
+                let Some(first) = processed.next() else {
+                    continue;
+                };
+                let idom = processed.fold(*first, |idom, predecessor| {
+                    intersect(idom, *predecessor, &idoms, &position)
+                });
+                if idoms[*node] != Some(idom) {
+                    idoms[*node] = Some(idom);
+                    changed = true;
+                }
+            }
+        }
+        let idoms = order
+            .iter()
+            .skip(1)
+            .map(|node| {
+                let idom = idoms[*node].filter(|idom| *idom != root).map(|idom| blocks[idom]);
+                (blocks[*node], idom)
+            })
+            .collect();
+        Self { idoms }
+    }
+
+    fn is_reachable(&self, block: Block) -> bool {
+        self.idoms.contains_key(&block)
+    }
+
+    /// Whether `a` dominates `b` of the same region, every block dominates itself.
+    fn dominates(&self, a: Block, b: Block) -> bool {
+        if !self.is_reachable(b) {
+            return true;
+        }
+        let mut current = Some(b);
+        while let Some(block) = current {
+            if block == a {
+                return true;
+            }
+            current = self.idoms.get(&block).copied().flatten();
+        }
+        false
+    }
+}
+
+fn reverse_post_order(root: usize, successors: &[Vec<usize>]) -> Vec<usize> {
+    let mut visited = vec![false; successors.len()];
+    let mut order = vec![];
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    while let Some((node, next)) = stack.pop() {
+        match successors[node].get(next) {
+            Some(successor) => {
+                stack.push((node, next + 1));
+                if !visited[*successor] {
+                    visited[*successor] = true;
+                    stack.push((*successor, 0));
+                }
+            }
+            None => order.push(node),
+        }
+    }
+    order.reverse();
+    order
+}
+
+fn intersect(mut a: usize, mut b: usize, idoms: &[Option<usize>], position: &HashMap<usize, usize>) -> usize {
+    while a != b {
+        while position[&a] > position[&b] {
+            a = idoms[a].expect("the processed nodes have the dominators");
+        }
+        while position[&b] > position[&a] {
+            b = idoms[b].expect("the processed nodes have the dominators");
+        }
+    }
+    a
+}
+
+/// Trees of every region nested in the operation, with the kinds of their regions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DominatorTrees {
+    trees: HashMap<Region, DominatorTree>,
+    graphs: HashSet<Region>,
+}
+
+impl DominatorTrees {
+    /// Trees of the regions nested in the operation and of the ones enclosing it, for its operands.
+    fn new(ctx: &Context, op: Operation, post: bool) -> Self {
+        let mut trees = Self::default();
+        for nested in op.walk(ctx) {
+            for (index, region) in nested.regions(ctx).iter().enumerate() {
+                trees.insert(ctx, nested, index, *region, post);
+            }
+        }
+        let mut current = op;
+        while let Some(region) = current.parent_region(ctx) {
+            current = region.parent_op(ctx);
+            let index = current.regions(ctx).iter().position(|candidate| *candidate == region);
+            trees.insert(ctx, current, index.expect("the region of its parent"), region, post);
+        }
+        trees
+    }
+
+    fn insert(&mut self, ctx: &Context, op: Operation, index: usize, region: Region, post: bool) {
+        self.trees.insert(region, DominatorTree::new(ctx, region, post));
+        if op.region_kind(ctx, index) == RegionKind::Graph {
+            self.graphs.insert(region);
+        }
+    }
+
+    fn tree(&self, region: Region) -> &DominatorTree {
+        self.trees
+            .get(&region)
+            .expect("querying the dominance of a region unrelated to the analyzed operation")
+    }
+
+    /// Whether `a` dominates `b`, or the block enclosing it within the region of `a`.
+    fn dominates_block(&self, ctx: &Context, a: Block, b: Block) -> bool {
+        let Some(region) = a.parent_region(ctx) else {
+            return false;
+        };
+        match ancestor_block_in(ctx, region, b) {
+            Some(b) => self.tree(region).dominates(a, b),
+            None => false,
+        }
+    }
+
+    /// Whether `a` comes before `b` in the order of the dominance, the operations enclosing `b` count
+    /// when `enclosing` is set.
+    fn precedes(&self, ctx: &Context, a: Operation, b: Operation, enclosing: bool, post: bool) -> bool {
+        if a == b {
+            return false;
+        }
+        let Some(block) = a.parent_block(ctx) else {
+            return false;
+        };
+        let region = block
+            .parent_region(ctx)
+            .expect("the blocks of the operations are in the regions");
+        let Some(b) = ancestor_op_in(ctx, region, b) else {
+            return false;
+        };
+        if a == b {
+            return enclosing;
+        }
+        if self.graphs.contains(&region) {
+            return true;
+        }
+        let other = b.parent_block(ctx).expect("the ancestor is in the region");
+        if block != other {
+            return self.tree(region).dominates(block, other);
+        }
+        let operations = block.operations(ctx);
+        let position = |op: Operation| operations.iter().position(|candidate| *candidate == op);
+        match post {
+            true => position(a) > position(b),
+            false => position(a) < position(b),
+        }
+    }
+}
+
+/// The block of the region enclosing the `block`, or the block itself.
+fn ancestor_block_in(ctx: &Context, region: Region, mut block: Block) -> Option<Block> {
+    loop {
+        let parent = block.parent_region(ctx)?;
+        if parent == region {
+            return Some(block);
+        }
+        block = parent.parent_op(ctx).parent_block(ctx)?;
+    }
+}
+
+/// The operation of the region enclosing the `op`, or the operation itself.
+fn ancestor_op_in(ctx: &Context, region: Region, mut op: Operation) -> Option<Operation> {
+    loop {
+        if op.parent_region(ctx)? == region {
+            return Some(op);
+        }
+        op = op.parent_op(ctx)?;
+    }
+}
+
+/// Dominator trees of the regions nested in the operation, like the upstream `DominanceInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominanceInfo {
+    trees: DominatorTrees,
+}
+
+impl DominanceInfo {
+    pub fn new(ctx: &Context, op: Operation) -> Self {
+        Self {
+            trees: DominatorTrees::new(ctx, op, false),
+        }
+    }
+
+    /// Immediate dominator of the block, `None` for the entry and the unreachable blocks.
+    pub fn immediate_dominator(&self, ctx: &Context, block: Block) -> Option<Block> {
+        let region = block.parent_region(ctx)?;
+        self.trees.tree(region).idoms.get(&block).copied().flatten()
+    }
+
+    pub fn is_reachable_from_entry(&self, ctx: &Context, block: Block) -> bool {
+        block
+            .parent_region(ctx)
+            .is_some_and(|region| self.trees.tree(region).is_reachable(block))
+    }
+
+    /// Whether every path from the entry block to `b` goes through `a`, or the ancestor block of `b` within
+    /// the region of `a`.
+    pub fn dominates_block(&self, ctx: &Context, a: Block, b: Block) -> bool {
+        self.trees.dominates_block(ctx, a, b)
+    }
+
+    pub fn properly_dominates_block(&self, ctx: &Context, a: Block, b: Block) -> bool {
+        a != b && self.dominates_block(ctx, a, b)
+    }
+
+    /// Whether `a` is executed before `b`, or encloses it.
+    pub fn properly_dominates(&self, ctx: &Context, a: Operation, b: Operation) -> bool {
+        self.trees.precedes(ctx, a, b, true, false)
+    }
+
+    pub fn dominates(&self, ctx: &Context, a: Operation, b: Operation) -> bool {
+        a == b || self.properly_dominates(ctx, a, b)
+    }
+
+    /// Whether the value is available to the operation, the results aren't available within their operation.
+    pub fn value_dominates(&self, ctx: &Context, value: Value, op: Operation) -> bool {
+        match value.owner(ctx) {
+            ValueOwner::Result { op: owner, .. } => self.trees.precedes(ctx, owner, op, false, false),
+            ValueOwner::Argument { block, .. } => op
+                .parent_block(ctx)
+                .is_some_and(|other| self.dominates_block(ctx, block, other)),
+        }
+    }
+}
+
+impl Analysis for DominanceInfo {
+    fn compute(ctx: &Context, op: Operation) -> Self {
+        Self::new(ctx, op)
+    }
+}
+
+/// Post-dominator trees of the regions nested in the operation, like the upstream `PostDominanceInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostDominanceInfo {
+    trees: DominatorTrees,
+}
+
+impl PostDominanceInfo {
+    pub fn new(ctx: &Context, op: Operation) -> Self {
+        Self {
+            trees: DominatorTrees::new(ctx, op, true),
+        }
+    }
+
+    /// Immediate post-dominator of the block, `None` for the exit blocks, the blocks of several exits
+    /// and the blocks never reaching an exit.
+    pub fn immediate_post_dominator(&self, ctx: &Context, block: Block) -> Option<Block> {
+        let region = block.parent_region(ctx)?;
+        self.trees.tree(region).idoms.get(&block).copied().flatten()
+    }
+
+    /// Whether every path from `b` to an exit block goes through `a`.
+    pub fn post_dominates_block(&self, ctx: &Context, a: Block, b: Block) -> bool {
+        self.trees.dominates_block(ctx, a, b)
+    }
+
+    pub fn properly_post_dominates_block(&self, ctx: &Context, a: Block, b: Block) -> bool {
+        a != b && self.post_dominates_block(ctx, a, b)
+    }
+
+    /// Whether `a` is executed after `b`, or encloses it.
+    pub fn properly_post_dominates(&self, ctx: &Context, a: Operation, b: Operation) -> bool {
+        self.trees.precedes(ctx, a, b, true, true)
+    }
+
+    pub fn post_dominates(&self, ctx: &Context, a: Operation, b: Operation) -> bool {
+        a == b || self.properly_post_dominates(ctx, a, b)
+    }
+}
+
+impl Analysis for PostDominanceInfo {
+    fn compute(ctx: &Context, op: Operation) -> Self {
+        Self::new(ctx, op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const SOURCE: &str = r#""test.func"() ({
+^bb0(%arg0: i1):
+  %0 = "test.a"() : () -> i32
+  "test.cond_br"(%arg0)[^bb1, ^bb2] : (i1) -> ()
+^bb1:
+  %1 = "test.b"() : () -> i32
+  "test.br"()[^bb3] : () -> ()
+^bb2:
+  "test.region"() ({
+    "test.use"(%0) : (i32) -> ()
+  }) : () -> ()
+  "test.br"()[^bb3] : () -> ()
+^bb3:
+  "test.return"() : () -> ()
+^bb4:
+  "test.br"()[^bb3] : () -> ()
+}) : () -> ()
+"#;
+
+    fn find(ctx: &Context, root: Operation, name: &str) -> Operation {
+        root.walk(ctx).into_iter().find(|op| op.name(ctx) == name).unwrap()
+    }
+
+    #[test]
+    fn should_compute_dominators() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let func = find(&ctx, module, "test.func");
+        let blocks = func.region(&ctx, 0).blocks(&ctx).to_vec();
+        let dominance = DominanceInfo::new(&ctx, func);
+        let idoms: Vec<Option<Block>> = blocks
+            .iter()
+            .map(|block| dominance.immediate_dominator(&ctx, *block))
+            .collect();
+        assert_eq!(
+            idoms,
+            vec![None, Some(blocks[0]), Some(blocks[0]), Some(blocks[0]), None]
+        );
+        assert!(!dominance.is_reachable_from_entry(&ctx, blocks[4]));
+        assert!(dominance.dominates_block(&ctx, blocks[1], blocks[4]));
+        assert!(!dominance.dominates_block(&ctx, blocks[1], blocks[3]));
+        let (a, b, cond_br, region, user) = (
+            find(&ctx, func, "test.a"),
+            find(&ctx, func, "test.b"),
+            find(&ctx, func, "test.cond_br"),
+            find(&ctx, func, "test.region"),
+            find(&ctx, func, "test.use"),
+        );
+        assert!(dominance.properly_dominates(&ctx, a, cond_br));
+        assert!(!dominance.properly_dominates(&ctx, cond_br, a));
+        assert!(dominance.properly_dominates(&ctx, region, user));
+        assert!(dominance.value_dominates(&ctx, a.result(&ctx, 0), user));
+        assert!(!dominance.value_dominates(&ctx, b.result(&ctx, 0), user));
+        assert!(dominance.value_dominates(&ctx, blocks[0].argument(&ctx, 0), user));
+    }
+
+    #[test]
+    fn should_compute_post_dominators() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let func = find(&ctx, module, "test.func");
+        let blocks = func.region(&ctx, 0).blocks(&ctx).to_vec();
+        let post_dominance = PostDominanceInfo::new(&ctx, func);
+        let ipdoms: Vec<Option<Block>> = blocks
+            .iter()
+            .map(|block| post_dominance.immediate_post_dominator(&ctx, *block))
+            .collect();
+        assert_eq!(
+            ipdoms,
+            vec![Some(blocks[3]), Some(blocks[3]), Some(blocks[3]), None, Some(blocks[3])]
+        );
+        assert!(post_dominance.post_dominates_block(&ctx, blocks[3], blocks[0]));
+        assert!(!post_dominance.post_dominates_block(&ctx, blocks[1], blocks[0]));
+        let (a, ret) = (find(&ctx, func, "test.a"), find(&ctx, func, "test.return"));
+        assert!(post_dominance.properly_post_dominates(&ctx, ret, a));
+        assert!(!post_dominance.properly_post_dominates(&ctx, a, ret));
+    }
+}
@@ -0,0 +1,161 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Runtime of the traits generated out of the `OpInterface`, `AttrInterface` and `TypeInterface` records.
+//! The dialects register the implementations in the [InterfaceRegistry] of the [crate::DialectRegistry]
+//! by the operation, attribute or type name, so the passes are written against the interfaces,
+//! like `op.as_interface::<dyn LoopLikeOpInterface>(ctx)`.
+//! The control flow, call and symbol interfaces of the core IR are declared here, the data-flow analyses and
+//! the verifier follow them.
+//!
+
+mod call;
+mod control_flow;
+mod symbol;
+
+pub use call::{CallOpInterface, CallableOpInterface};
+pub use control_flow::{BranchOpInterface, RegionBranchOpInterface, RegionSuccessor};
+pub use symbol::SymbolUserOpInterface;
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Context, Operation};
+
+/// Derived [thiserror::Error] for the interface methods failures, like the upstream `LogicalResult::failure`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{0}")]
+pub struct InterfaceError(pub String);
+
+impl InterfaceError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+/// `::mlir::LogicalResult`
+pub type LogicalResult = Result<(), InterfaceError>;
+
+/// `::mlir::FailureOr<T>`
+pub type FailureOr<T> = Result<T, InterfaceError>;
+
+/// Interface implementations by the interface trait object type and the implementing entity name.
+#[derive(Clone, Default)]
+pub struct InterfaceRegistry {
+    implementations: HashMap<TypeId, HashMap<&'static str, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl InterfaceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the implementation of the interface `I`, like `registry.register::<dyn BranchOpInterface>("cf.br", &BranchOp)`.
+    pub fn register<I: ?Sized + Sync + 'static>(&mut self, name: &'static str, implementation: &'static I) {
+        self.implementations
+            .entry(TypeId::of::<I>())
+            .or_default()
+            .insert(name, Arc::new(implementation));
+    }
+
+    pub fn get<I: ?Sized + Sync + 'static>(&self, name: &str) -> Option<&'static I> {
+        self.implementations
+            .get(&TypeId::of::<I>())?
+            .get(name)?
+            .downcast_ref::<&'static I>()
+            .copied()
+    }
+
+    pub fn implements<I: ?Sized + Sync + 'static>(&self, name: &str) -> bool {
+        self.get::<I>(name).is_some()
+    }
+
+    /// Names implementing the interface `I`.
+    pub fn implementors<I: ?Sized + 'static>(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.implementations
+            .get(&TypeId::of::<I>())
+            .into_iter()
+            .flat_map(|implementations| implementations.keys().copied())
+    }
+}
+
+impl fmt::Debug for InterfaceRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&str> = self.implementations.values().flat_map(HashMap::keys).copied().collect();
+        names.sort_unstable();
+        names.dedup();
+        f.debug_set().entries(names).finish()
+    }
+}
+
+impl Operation {
+    /// Looks up the interface implementation of the registered operation, like the upstream
+    /// `dyn_cast<Interface>(op)`.
+    pub fn as_interface<I: ?Sized + Sync + 'static>(self, ctx: &Context) -> Option<&'static I> {
+        ctx.dialects().interfaces().get::<I>(self.name(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dialect, OperationState};
+
+    trait Named: Sync {
+        fn name(&self, ctx: &Context, op: Operation) -> String;
+    }
+
+    trait Unused: Sync {}
+
+    struct Constant;
+
+    impl Named for Constant {
+        fn name(&self, ctx: &Context, op: Operation) -> String {
+            format!("constant {}", op.name(ctx))
+        }
+    }
+
+    struct TestDialect;
+
+    impl Dialect for TestDialect {
+        fn namespace(&self) -> &str {
+            "test"
+        }
+
+        fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+            registry.register::<dyn Named>("test.constant", &Constant);
+        }
+    }
+
+    #[test]
+    fn should_lookup_interfaces() {
+        let mut ctx = Context::new();
+        let op = ctx.create_operation(OperationState::new("test.constant"));
+        assert!(op.as_interface::<dyn Named>(&ctx).is_none());
+
+        ctx.register_dialect(TestDialect);
+        let named = op.as_interface::<dyn Named>(&ctx).unwrap();
+        assert_eq!(named.name(&ctx, op), "constant test.constant");
+        assert!(op.as_interface::<dyn Unused>(&ctx).is_none());
+
+        let registry = ctx.dialects().interfaces();
+        assert!(!registry.implements::<dyn Named>("test.addi"));
+        assert_eq!(registry.implementors::<dyn Named>().collect::<Vec<_>>(), vec!["test.constant"]);
+    }
+}
@@ -0,0 +1,41 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Call interfaces of the calls and the callables, like the upstream `CallInterfaces.td`, the data-flow
+//! analyses follow the calls to the bodies of the callables they reference.
+//!
+
+use crate::{Attribute, Context, Operation, Region};
+
+/// `CallOpInterface` op interface.
+///
+/// A call-like operation is one that transfers control from one sub-routine to
+/// another, the operands are the arguments of the callee.
+pub trait CallOpInterface: Sync {
+    /// Returns the symbol reference of the callee, `None` for the indirect calls.
+    fn get_callable_for_callee(&self, ctx: &Context, op: Operation) -> Option<Attribute>;
+}
+
+/// `CallableOpInterface` op interface.
+///
+/// A callable operation is one who represents a potential sub-routine, and may
+/// be a target for a call-like operation.
+pub trait CallableOpInterface: Sync {
+    /// Returns the region on the current operation that is callable, `None` for
+    /// the external callables.
+    fn get_callable_region(&self, ctx: &Context, op: Operation) -> Option<Region>;
+}
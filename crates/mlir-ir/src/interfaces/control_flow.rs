@@ -0,0 +1,73 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Control flow interfaces of the branches and the region branches, like the upstream
+//! `ControlFlowInterfaces.td`, the data-flow analyses follow the edges they describe.
+//!
+
+use crate::{Attribute, Block, Context, Operation, Value};
+
+/// Destination of the control flow entering or leaving the regions of an operation, like the upstream
+/// `RegionSuccessor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionSuccessor {
+    /// Index of the entered region, `None` for the results of the parent operation.
+    pub region: Option<usize>,
+    /// Values forwarded to the inputs, the operands of the parent operation or of the region terminator.
+    pub operands: Vec<Value>,
+    /// Region arguments or parent results taking the forwarded values, the others are unknown to the analyses.
+    pub inputs: Vec<Value>,
+}
+
+/// `BranchOpInterface` op interface.
+///
+/// This interface provides information for branching terminator operations,
+/// i.e. terminator operations with successors.
+#[allow(unused_variables)]
+pub trait BranchOpInterface: Sync {
+    /// Returns the operands that correspond to the arguments of the successor
+    /// at the given index.
+    fn get_successor_operands(&self, ctx: &Context, op: Operation, index: u32) -> Vec<Value>;
+
+    /// Returns the successor that would be chosen with the given constant
+    /// operands, `None` for the unknown ones. Returns `None` if a single successor could not be chosen.
+    fn get_successor_for_operands(
+        &self,
+        ctx: &Context,
+        op: Operation,
+        operands: &[Option<Attribute>],
+    ) -> Option<Block> {
+        None
+    }
+}
+
+/// `RegionBranchOpInterface` op interface.
+///
+/// This interface provides information for region operations that exhibit
+/// branching behavior between held regions.
+pub trait RegionBranchOpInterface: Sync {
+    /// Returns the successors of the control flow entering the regions from the operation, or leaving
+    /// the `point` terminator nested in them, given the constant operands of the operation or of the
+    /// terminator, `None` for the unknown ones.
+    fn get_successor_regions(
+        &self,
+        ctx: &Context,
+        op: Operation,
+        point: Option<Operation>,
+        operands: &[Option<Attribute>],
+    ) -> Vec<RegionSuccessor>;
+}
@@ -0,0 +1,37 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Symbol interfaces of the operations referencing the symbols, like the upstream `SymbolInterfaces.td`.
+//!
+
+use crate::{Context, LogicalResult, Operation, SymbolTableCollection};
+
+/// `SymbolUserOpInterface` op interface.
+///
+/// This interface describes an operation that may use a `Symbol`. This
+/// interface allows for users of symbols to hook into verification and other
+/// symbol related utilities that are either costly or otherwise disallowed
+/// within a traditional operation.
+pub trait SymbolUserOpInterface: Sync {
+    /// Verify the symbol uses held by this operation.
+    fn verify_symbol_uses(
+        &self,
+        ctx: &Context,
+        op: Operation,
+        symbol_table: &mut SymbolTableCollection,
+    ) -> LogicalResult;
+}
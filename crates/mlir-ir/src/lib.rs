@@ -0,0 +1,277 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir ir
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Safe, arena-based counterpart of the MLIR core IR the `targets` dialects are built on.
+//! The [Context] uniques the types and attributes, and owns the operations, blocks, regions and values,
+//! referred to by the `Copy` handles, like the upstream value-semantic `Operation *` and `Value` wrappers.
+//! The handles of the erased IR entities are never reused, accessing them panics.
+//! The IR is read from and written to the `.mlir` textual form by the [Parser] and the [Printer],
+//! in the generic form diffed against the upstream `mlir-opt --mlir-print-op-generic` output,
+//! or the custom forms of the operations registered in the [OpAsmRegistry] by the generated dialects,
+//! and from and to the `.mlirbc` bytecode by the [BytecodeReader] and the [BytecodeWriter].
+//! The [PassManager] runs the pass pipelines on it, parsed from the upstream textual pipeline syntax,
+//! and the greedy driver applies the [RewritePattern]s and the folders of the registered [Dialect]s.
+//! The verifier checks the invariants relying on the [DominanceInfo] and the [SymbolTable]s,
+//! and the dialect conversion legalizes the operations for the [ConversionTarget] through the [ConversionPattern]s.
+//! The [DataFlowSolver] runs the sparse data-flow analyses, like the [SparseConstantPropagation],
+//! the [IntegerRangeAnalysis] and the [LivenessAnalysis].
+//! The passes query the generated op interfaces of the operations through the [InterfaceRegistry].
+//! The errors are reported as the [Diagnostic]s on the [Location]s of the operations.
+//!
+
+mod affine_map;
+mod asm;
+mod attributes;
+mod builder;
+mod builtin;
+mod bytecode;
+mod context;
+mod conversion;
+mod dataflow;
+mod diagnostics;
+mod dialect;
+mod dominance;
+mod interfaces;
+mod location;
+mod operation;
+mod parser;
+mod pass;
+pub mod passes;
+mod printer;
+mod region;
+mod rewrite;
+mod symbol_table;
+mod transforms;
+mod types;
+mod value;
+mod verifier;
+
+pub use affine_map::{AffineBinaryKind, AffineExpr, AffineMap};
+pub use asm::{AsmParameter, AttrOrTypeDef, OpAsm, OpAsmRegistry, ParseFn, PrintFn};
+pub use attributes::{Attribute, AttributeKind, NamedAttribute};
+pub use builder::{Builder, InsertionPoint};
+pub use builtin::BuiltinDialect;
+pub use bytecode::{is_bytecode, BytecodeReader, BytecodeReaderConfig, BytecodeWriter, BytecodeWriterConfig};
+pub use context::Context;
+pub use conversion::{
+    apply_full_conversion, apply_partial_conversion, unrealized_conversion_cast, ConversionPattern,
+    ConversionPatternRewriter, ConversionPatternSet, ConversionTarget, OpAdaptor, TypeConverter,
+};
+pub use dataflow::{
+    AnalysisState, ChangeResult, ConstantIntRanges, ConstantValue, DataFlowAnalysis, DataFlowSolver, DeadCodeAnalysis,
+    Executable, IntegerRangeAnalysis, IntegerValueRange, LatticeAnchor, LatticeValue, Liveness, LivenessAnalysis,
+    Predecessor, PredecessorState, ProgramPoint, SparseBackward, SparseBackwardAnalysis, SparseConstantPropagation,
+    SparseForward, SparseForwardAnalysis,
+};
+pub use diagnostics::{Diagnostic, Location, Severity};
+pub use dialect::{Dialect, DialectRegistry, OpDefinition, OpFoldResult, OpTrait, RegionKind};
+pub use dominance::{DominanceInfo, PostDominanceInfo};
+pub use interfaces::{
+    BranchOpInterface, CallOpInterface, CallableOpInterface, FailureOr, InterfaceError, InterfaceRegistry,
+    LogicalResult, RegionBranchOpInterface, RegionSuccessor, SymbolUserOpInterface,
+};
+pub use operation::{segment_ranges, Operation, OperationState, Variadicity};
+pub use parser::{Argument, Parser, UnresolvedOperand};
+pub use pass::{
+    Analysis, AnalysisManager, IrPrinting, IrPrintingConfig, OpPassManager, Pass, PassClone, PassFailure,
+    PassInstrumentation, PassManager, PassRegistry, PassTime, PassTiming, PreservedAnalyses, RegisteredPass,
+    ReproducerConfig,
+};
+pub use printer::{Printer, PrinterOptions};
+pub use region::{Block, BlockOperand, Region};
+pub use rewrite::{
+    apply_patterns_and_fold_greedily, FrozenRewritePatternSet, GreedyRewriteConfig, MatchFailure, PatternRewriter,
+    RewritePattern, RewritePatternSet,
+};
+pub use symbol_table::{SymbolTable, SymbolTableCollection};
+pub use transforms::{register_transforms, Canonicalizer, CanonicalizerOptions, Cse, Sccp, SymbolDce};
+pub use types::{FloatKind, Signedness, Type, TypeKind, DYNAMIC};
+pub use value::{types, OpOperand, Value, ValueOwner};
+pub use verifier::verify;
+
+use passes::PassOptionError;
+
+/// Derived [thiserror::Error] for the IR mutations breaking the use-def chains
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum IrError {
+    #[error("cannot erase `{op}`, its result #{index} still has uses")]
+    ResultInUse { op: String, index: usize },
+
+    #[error("cannot erase the block argument #{index}, it still has uses")]
+    ArgumentInUse { index: usize },
+
+    #[error("cannot erase the block, it is still a successor of `{op}`")]
+    BlockInUse { op: String },
+
+    #[error("`{0}` is already inserted into a block")]
+    AlreadyInserted(String),
+}
+
+/// Derived [thiserror::Error] for the textual form parsing errors
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{line}:{column}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Derived [thiserror::Error] for the bytecode reading and writing errors
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeError {
+    #[error("offset {offset}: {message}")]
+    Malformed { offset: usize, message: String },
+
+    #[error("bytecode version {version} is newer than the current version {current}")]
+    UnsupportedVersion { version: u64, current: u64 },
+
+    #[error("invalid {kind} index: {index}")]
+    InvalidIndex { kind: &'static str, index: u64 },
+
+    #[error("unsupported {0}")]
+    Unsupported(String),
+
+    #[error("textual attribute or type entry, {0}")]
+    Text(#[from] ParseError),
+
+    #[error(transparent)]
+    Ir(#[from] IrError),
+}
+
+/// Derived [thiserror::Error] for the pass pipeline parsing and running errors
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum PassError {
+    #[error("pass pipeline column {column}: {message}")]
+    Pipeline { column: usize, message: String },
+
+    #[error("`{0}` does not refer to a registered pass or pass pipeline")]
+    UnknownPass(String),
+
+    #[error(transparent)]
+    Options(#[from] PassOptionError),
+
+    #[error("can't run the `{anchor}` pass pipeline on `{op}`")]
+    PipelineAnchor { anchor: String, op: String },
+
+    #[error("can't run the `{pass}` pass restricted to `{anchor}` on `{op}`")]
+    PassAnchor { pass: String, anchor: String, op: String },
+
+    #[error("`{pass}` pass failed on `{op}`: {message}")]
+    Failed {
+        pass: String,
+        op: String,
+        location: Location,
+        message: String,
+    },
+
+    #[error("`{pass}` pass crashed on `{op}`: {message}")]
+    Crashed {
+        pass: String,
+        op: String,
+        location: Location,
+        message: String,
+    },
+
+    #[error("verification failed after the `{pass}` pass: {error}")]
+    Verification { pass: String, error: VerifyError },
+}
+
+/// Derived [thiserror::Error] for the greedy rewrite driver errors
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    #[error("rewriting within `{op}` didn't converge after {iterations} iterations, the last one kept {}", .applied.join(", "))]
+    NotConverged {
+        op: String,
+        iterations: usize,
+        applied: Vec<String>,
+    },
+
+    #[error("rewriting within `{op}` stopped after {limit} rewrites")]
+    TooManyRewrites { op: String, limit: usize },
+
+    #[error(transparent)]
+    Ir(#[from] IrError),
+}
+
+/// Derived [thiserror::Error] for the violated IR invariants, reported on the offending operation
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{location}: '{op}' op {message}")]
+pub struct VerifyError {
+    pub op: String,
+    pub location: Location,
+    pub message: String,
+}
+
+/// Derived [thiserror::Error] for the dialect conversion errors, the IR is rolled back when they occur
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("{location}: failed to legalize operation '{op}'")]
+    FailedToLegalize { op: String, location: Location },
+
+    #[error("{location}: failed to legalize operation '{op}' that was explicitly marked illegal")]
+    IllegalOperation { op: String, location: Location },
+
+    #[error(transparent)]
+    Ir(#[from] IrError),
+}
+
+/// Parses the `.mlir` source, the top level operations are wrapped into a `builtin.module`
+/// unless it's the only one.
+pub fn parse(ctx: &mut Context, source: &str) -> Result<Operation, ParseError> {
+    Parser::new(ctx, source).parse_top_level()
+}
+
+/// Parses the whole text as a type.
+pub fn parse_type(ctx: &mut Context, text: &str) -> Result<Type, ParseError> {
+    let mut parser = Parser::new(ctx, text);
+    let r#type = parser.parse_type()?;
+    match parser.is_at_end() {
+        true => Ok(r#type),
+        false => Err(parser.error("unexpected trailing characters")),
+    }
+}
+
+/// Parses the whole text as an attribute.
+pub fn parse_attribute(ctx: &mut Context, text: &str) -> Result<Attribute, ParseError> {
+    let mut parser = Parser::new(ctx, text);
+    let attribute = parser.parse_attribute()?;
+    match parser.is_at_end() {
+        true => Ok(attribute),
+        false => Err(parser.error("unexpected trailing characters")),
+    }
+}
+
+/// Reads the bytecode, the top level operations are wrapped into a `builtin.module` unless it's the only one.
+pub fn read_bytecode(ctx: &mut Context, data: &[u8]) -> Result<Operation, BytecodeError> {
+    BytecodeReader::new(data, BytecodeReaderConfig::default())?.read(ctx)
+}
+
+/// Writes the bytecode of the current version.
+pub fn write_bytecode(ctx: &mut Context, op: Operation) -> Result<Vec<u8>, BytecodeError> {
+    BytecodeWriter::new(ctx, BytecodeWriterConfig::default()).write(op)
+}
+
+/// Prints the operation, the registered ones in their custom form unless the [PrinterOptions::generic_op_form] is set.
+pub fn print(ctx: &Context, op: Operation, options: PrinterOptions) -> String {
+    let mut printer = Printer::new(ctx, options);
+    printer.print_top_level(op);
+    printer.finish()
+}
@@ -0,0 +1,110 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Location attributes, tracking where the operations come from.
+//!
+
+use crate::{Attribute, AttributeKind, Context};
+
+impl Context {
+    /// `loc(unknown)`, the location of the operations created without one.
+    pub fn unknown_loc(&mut self) -> Attribute {
+        self.get_attribute(AttributeKind::UnknownLoc)
+    }
+
+    /// `loc("file":line:column)`
+    pub fn file_line_col_loc(&mut self, filename: &str, line: u32, column: u32) -> Attribute {
+        self.get_attribute(AttributeKind::FileLineColLoc {
+            filename: filename.to_string(),
+            line,
+            column,
+        })
+    }
+
+    /// `loc("name"(child))`, the child is left out if it's unknown.
+    pub fn name_loc(&mut self, name: &str, child: Option<Attribute>) -> Attribute {
+        let child = child.filter(|child| *child.kind(self) != AttributeKind::UnknownLoc);
+        self.get_attribute(AttributeKind::NameLoc {
+            name: name.to_string(),
+            child,
+        })
+    }
+
+    /// `loc(callsite(callee at caller))`
+    pub fn call_site_loc(&mut self, callee: Attribute, caller: Attribute) -> Attribute {
+        self.get_attribute(AttributeKind::CallSiteLoc { callee, caller })
+    }
+
+    /// `loc(fused<metadata>[locations])`, simplified like the upstream `FusedLoc::get`:
+    /// the nested fused locations with the same metadata are flattened, the unknown and duplicate ones dropped,
+    /// and a single location left without metadata stands for itself.
+    pub fn fused_loc(&mut self, locations: &[Attribute], metadata: Option<Attribute>) -> Attribute {
+        let mut fused = vec![];
+        for location in locations {
+            match location.kind(self) {
+                AttributeKind::UnknownLoc => {}
+                AttributeKind::FusedLoc {
+                    locations: nested,
+                    metadata: nested_metadata,
+                } if *nested_metadata == metadata => fused.extend(nested.iter().copied()),
+                _ => fused.push(*location),
+            }
+        }
+        let mut seen = vec![];
+        fused.retain(|location| {
+            let first = !seen.contains(location);
+            seen.push(*location);
+            first
+        });
+        match (fused.as_slice(), metadata) {
+            ([], None) => self.unknown_loc(),
+            ([location], None) => *location,
+            _ => self.get_attribute(AttributeKind::FusedLoc {
+                locations: fused,
+                metadata,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_simplify_fused_locations() {
+        let mut ctx = Context::new();
+        let unknown = ctx.unknown_loc();
+        let (a, b) = (ctx.file_line_col_loc("a.rs", 1, 2), ctx.file_line_col_loc("b.rs", 3, 4));
+        assert_eq!(ctx.fused_loc(&[], None), unknown);
+        assert_eq!(ctx.fused_loc(&[unknown, a, a], None), a);
+
+        let ab = ctx.fused_loc(&[a, b], None);
+        assert_eq!(ctx.fused_loc(&[ab, b, unknown], None), ab);
+        let metadata = ctx.string_attr("inlined");
+        let tagged = ctx.fused_loc(&[ab], Some(metadata));
+        assert_eq!(
+            tagged.kind(&ctx),
+            &AttributeKind::FusedLoc {
+                locations: vec![ab],
+                metadata: Some(metadata)
+            }
+        );
+        assert!(tagged.is_location(&ctx));
+        assert_eq!(ctx.name_loc("x", Some(unknown)), ctx.name_loc("x", None));
+    }
+}
@@ -0,0 +1,758 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Operations with their operands, results, attributes, successors and regions.
+//!
+
+use std::ops::Range;
+
+use crate::region::RegionData;
+use crate::value::{OpOperand, ValueOwner};
+use crate::{
+    Attribute, AttributeKind, Block, BlockOperand, Context, IrError, NamedAttribute, Region, Type, Value,
+};
+
+/// Operation handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Operation(pub(crate) u32);
+
+/// Everything the operation is created with, like the upstream `OperationState`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OperationState {
+    pub name: String,
+    pub operands: Vec<Value>,
+    pub types: Vec<Type>,
+    pub attributes: Vec<NamedAttribute>,
+    /// Dictionary of the inherent attributes printed as `<{...}>`, kept apart from the discardable ones.
+    pub properties: Option<Attribute>,
+    pub successors: Vec<Block>,
+    /// Blocks of each region the operation is created with, appended to the created regions.
+    pub regions: Vec<Vec<Block>>,
+    /// Location attribute, `loc(unknown)` if it's left out.
+    pub location: Option<Attribute>,
+}
+
+impl OperationState {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn add_operands(&mut self, operands: impl IntoIterator<Item = Value>) -> &mut Self {
+        self.operands.extend(operands);
+        self
+    }
+
+    pub fn add_types(&mut self, types: impl IntoIterator<Item = Type>) -> &mut Self {
+        self.types.extend(types);
+        self
+    }
+
+    pub fn add_attribute(&mut self, name: impl Into<String>, value: Attribute) -> &mut Self {
+        self.attributes.push(NamedAttribute::new(name, value));
+        self
+    }
+
+    pub fn set_properties(&mut self, properties: Attribute) -> &mut Self {
+        self.properties = Some(properties);
+        self
+    }
+
+    pub fn add_successors(&mut self, successors: impl IntoIterator<Item = Block>) -> &mut Self {
+        self.successors.extend(successors);
+        self
+    }
+
+    /// Adds the empty regions.
+    pub fn add_regions(&mut self, count: usize) -> &mut Self {
+        self.regions.extend((0..count).map(|_| vec![]));
+        self
+    }
+
+    /// Adds the region of the detached blocks, like the parsed ones.
+    pub fn add_region(&mut self, blocks: Vec<Block>) -> &mut Self {
+        self.regions.push(blocks);
+        self
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value)
+    }
+
+    /// Sets the attribute, replacing the existing one.
+    pub fn set_attribute(&mut self, name: &str, value: Attribute) -> &mut Self {
+        match self.attributes.iter_mut().find(|attribute| attribute.name == name) {
+            Some(attribute) => attribute.value = value,
+            None => self.attributes.push(NamedAttribute::new(name, value)),
+        }
+        self
+    }
+
+    /// Moves the inherent attributes of the names into the properties, like the ODS operations storing
+    /// their declared attributes as the properties, the discardable ones are left in the attributes.
+    pub fn move_into_properties(&mut self, ctx: &mut Context, names: &[&str]) -> &mut Self {
+        let (mut inherent, discardable): (Vec<_>, Vec<_>) = std::mem::take(&mut self.attributes)
+            .into_iter()
+            .partition(|attribute| names.contains(&attribute.name.as_str()));
+        self.attributes = discardable;
+        if inherent.is_empty() {
+            return self;
+        }
+        if let Some(AttributeKind::Dictionary(properties)) = self.properties.map(|properties| properties.kind(ctx)) {
+            let properties = properties.clone();
+            inherent.retain(|attribute| !properties.iter().any(|property| property.name == attribute.name));
+            inherent.extend(properties);
+        }
+        inherent.sort_by(|a, b| a.name.cmp(&b.name));
+        self.properties = Some(ctx.dictionary_attr(inherent));
+        self
+    }
+
+    pub fn set_location(&mut self, location: Attribute) -> &mut Self {
+        self.location = Some(location);
+        self
+    }
+}
+
+/// ODS operand, result, region or successor variadicity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variadicity {
+    Single,
+    Optional,
+    Variadic,
+}
+
+/// Splits the values between the ODS declarations, like the generated `getODSOperandIndexAndLength`.
+///
+/// The explicit segment sizes are used for `AttrSizedOperandSegments`,
+/// otherwise the variadic declarations share the rest of the values equally.
+pub fn segment_ranges(total: usize, spec: &[Variadicity], sizes: Option<&[i64]>) -> Vec<Range<usize>> {
+    let lengths: Vec<usize> = match sizes {
+        Some(sizes) if sizes.len() == spec.len() => sizes.iter().map(|size| (*size).max(0) as usize).collect(),
+        _ => {
+            let variadic = spec.iter().filter(|v| **v != Variadicity::Single).count();
+            let single = spec.len() - variadic;
+            let shared = total.saturating_sub(single).checked_div(variadic).unwrap_or(0);
+            spec.iter()
+                .map(|v| match v {
+                    Variadicity::Single => 1,
+                    _ => shared,
+                })
+                .collect()
+        }
+    };
+    let mut start = 0;
+    lengths
+        .into_iter()
+        .map(|length| {
+            let end = (start + length).min(total);
+            let range = start.min(total)..end;
+            start += length;
+            range
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub(crate) struct OperationData {
+    pub name: String,
+    pub operands: Vec<Value>,
+    pub results: Vec<Value>,
+    /// Sorted by the attribute names, like the upstream attribute dictionary.
+    pub attributes: Vec<NamedAttribute>,
+    pub properties: Option<Attribute>,
+    pub successors: Vec<Block>,
+    pub regions: Vec<Region>,
+    pub location: Attribute,
+    pub parent: Option<Block>,
+}
+
+impl Operation {
+    fn data(self, ctx: &Context) -> &OperationData {
+        ctx.operations.get(self.0)
+    }
+
+    /// Whether the operation is not erased yet.
+    pub fn is_alive(self, ctx: &Context) -> bool {
+        ctx.operations.contains(self.0)
+    }
+
+    /// Full name, like `arith.addi`.
+    pub fn name(self, ctx: &Context) -> &str {
+        &self.data(ctx).name
+    }
+
+    /// Dialect namespace of the name, like `arith`.
+    pub fn dialect(self, ctx: &Context) -> &str {
+        let name = self.name(ctx);
+        name.split_once('.').map(|(dialect, _)| dialect).unwrap_or(name)
+    }
+
+    pub fn operands(self, ctx: &Context) -> &[Value] {
+        &self.data(ctx).operands
+    }
+
+    pub fn operand(self, ctx: &Context, index: usize) -> Value {
+        self.operands(ctx)[index]
+    }
+
+    pub fn results(self, ctx: &Context) -> &[Value] {
+        &self.data(ctx).results
+    }
+
+    pub fn result(self, ctx: &Context, index: usize) -> Value {
+        self.results(ctx)[index]
+    }
+
+    pub fn result_types(self, ctx: &Context) -> Vec<Type> {
+        self.results(ctx).iter().map(|result| result.r#type(ctx)).collect()
+    }
+
+    pub fn attributes(self, ctx: &Context) -> &[NamedAttribute] {
+        &self.data(ctx).attributes
+    }
+
+    /// Discardable attribute, or the inherent one of the properties, like the upstream `getAttr`.
+    pub fn attribute(self, ctx: &Context, name: &str) -> Option<Attribute> {
+        let properties = match self.properties(ctx).map(|properties| properties.kind(ctx)) {
+            Some(AttributeKind::Dictionary(properties)) => properties.as_slice(),
+            _ => &[],
+        };
+        self.attributes(ctx)
+            .iter()
+            .chain(properties)
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value)
+    }
+
+    /// Properties dictionary of the `<{...}>` generic form.
+    pub fn properties(self, ctx: &Context) -> Option<Attribute> {
+        self.data(ctx).properties
+    }
+
+    /// Inherent attributes of the properties and the discardable ones, sorted by name,
+    /// like the attribute dictionary of the custom forms.
+    pub fn all_attributes(self, ctx: &Context) -> Vec<NamedAttribute> {
+        let mut attributes = self.attributes(ctx).to_vec();
+        if let Some(AttributeKind::Dictionary(properties)) = self.properties(ctx).map(|properties| properties.kind(ctx)) {
+            attributes.extend(properties.iter().cloned());
+        }
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+        attributes
+    }
+
+    /// Operands of each ODS operand, using the `operandSegmentSizes` if present.
+    pub fn operand_groups<'c>(self, ctx: &'c Context, spec: &[Variadicity]) -> Vec<&'c [Value]> {
+        let sizes = self.segment_sizes(ctx, "operandSegmentSizes");
+        let operands = self.operands(ctx);
+        segment_ranges(operands.len(), spec, sizes.as_deref())
+            .into_iter()
+            .map(|range| &operands[range])
+            .collect()
+    }
+
+    /// Results of each ODS result, using the `resultSegmentSizes` if present.
+    pub fn result_groups<'c>(self, ctx: &'c Context, spec: &[Variadicity]) -> Vec<&'c [Value]> {
+        let sizes = self.segment_sizes(ctx, "resultSegmentSizes");
+        let results = self.results(ctx);
+        segment_ranges(results.len(), spec, sizes.as_deref())
+            .into_iter()
+            .map(|range| &results[range])
+            .collect()
+    }
+
+    pub fn region_groups<'c>(self, ctx: &'c Context, spec: &[Variadicity]) -> Vec<&'c [Region]> {
+        let regions = self.regions(ctx);
+        segment_ranges(regions.len(), spec, None)
+            .into_iter()
+            .map(|range| &regions[range])
+            .collect()
+    }
+
+    pub fn successor_groups<'c>(self, ctx: &'c Context, spec: &[Variadicity]) -> Vec<&'c [Block]> {
+        let successors = self.successors(ctx);
+        segment_ranges(successors.len(), spec, None)
+            .into_iter()
+            .map(|range| &successors[range])
+            .collect()
+    }
+
+    pub(crate) fn segment_sizes(self, ctx: &Context, name: &str) -> Option<Vec<i64>> {
+        match self.attribute(ctx, name)?.kind(ctx) {
+            AttributeKind::DenseArray { elements, .. } => elements
+                .iter()
+                .map(|element| element.as_integer(ctx).map(|size| size as i64))
+                .collect(),
+            _ => None,
+        }
+    }
+
+    pub fn successors(self, ctx: &Context) -> &[Block] {
+        &self.data(ctx).successors
+    }
+
+    pub fn regions(self, ctx: &Context) -> &[Region] {
+        &self.data(ctx).regions
+    }
+
+    pub fn region(self, ctx: &Context, index: usize) -> Region {
+        self.regions(ctx)[index]
+    }
+
+    pub fn location(self, ctx: &Context) -> Attribute {
+        ctx.operations.get(self.0).location
+    }
+
+    pub fn parent_block(self, ctx: &Context) -> Option<Block> {
+        self.data(ctx).parent
+    }
+
+    pub fn parent_region(self, ctx: &Context) -> Option<Region> {
+        self.parent_block(ctx).and_then(|block| block.parent_region(ctx))
+    }
+
+    /// Operation owning the region of the block the operation is in.
+    pub fn parent_op(self, ctx: &Context) -> Option<Operation> {
+        self.parent_region(ctx).map(|region| region.parent_op(ctx))
+    }
+
+    /// Whether the operation is the `other` one or contains it in its regions.
+    pub fn is_ancestor_of(self, ctx: &Context, other: Operation) -> bool {
+        let mut current = Some(other);
+        while let Some(op) = current {
+            if op == self {
+                return true;
+            }
+            current = op.parent_op(ctx);
+        }
+        false
+    }
+
+    /// Whether the nested operations use only the values defined within the operation,
+    /// the structural counterpart of the upstream `IsolatedFromAbove` trait.
+    pub fn is_isolated_from_above(self, ctx: &Context) -> bool {
+        let defined_within = |value: &Value| match value.owner(ctx) {
+            ValueOwner::Result { op: owner, .. } => owner != self && self.is_ancestor_of(ctx, owner),
+            ValueOwner::Argument { block, .. } => block
+                .parent_op(ctx)
+                .is_some_and(|owner| self.is_ancestor_of(ctx, owner)),
+        };
+        self.walk(ctx)
+            .into_iter()
+            .skip(1)
+            .all(|nested| nested.operands(ctx).iter().all(defined_within))
+    }
+
+    /// Operation following this one in its block.
+    pub fn next(self, ctx: &Context) -> Option<Operation> {
+        let operations = self.parent_block(ctx)?.operations(ctx);
+        let position = operations.iter().position(|op| *op == self)?;
+        operations.get(position + 1).copied()
+    }
+
+    /// Operation preceding this one in its block.
+    pub fn prev(self, ctx: &Context) -> Option<Operation> {
+        let operations = self.parent_block(ctx)?.operations(ctx);
+        let position = operations.iter().position(|op| *op == self)?;
+        position.checked_sub(1).map(|position| operations[position])
+    }
+
+    /// The operation and the nested ones, in pre-order.
+    pub fn walk(self, ctx: &Context) -> Vec<Operation> {
+        let mut operations = vec![];
+        let mut stack = vec![self];
+        while let Some(op) = stack.pop() {
+            operations.push(op);
+            for region in op.regions(ctx).iter().rev() {
+                for block in region.blocks(ctx).iter().rev() {
+                    stack.extend(block.operations(ctx).iter().rev());
+                }
+            }
+        }
+        operations
+    }
+
+    /// The nested operations and then the operation itself, in post-order.
+    pub fn walk_post_order(self, ctx: &Context) -> Vec<Operation> {
+        let mut operations = vec![];
+        for region in self.regions(ctx) {
+            for block in region.blocks(ctx) {
+                for op in block.operations(ctx) {
+                    operations.extend(op.walk_post_order(ctx));
+                }
+            }
+        }
+        operations.push(self);
+        operations
+    }
+}
+
+impl Context {
+    /// Creates the operation out of the state, not inserted into any block.
+    pub fn create_operation(&mut self, state: OperationState) -> Operation {
+        let mut attributes = state.attributes;
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+        attributes.dedup_by(|later, earlier| later.name == earlier.name);
+        let location = state.location.unwrap_or_else(|| self.unknown_loc());
+        let op = Operation(self.operations.alloc(OperationData {
+            name: state.name,
+            operands: state.operands.clone(),
+            results: vec![],
+            attributes,
+            properties: state.properties,
+            successors: state.successors.clone(),
+            regions: vec![],
+            location,
+            parent: None,
+        }));
+        for (index, operand) in state.operands.into_iter().enumerate() {
+            self.add_use(operand, OpOperand { owner: op, index });
+        }
+        for (index, successor) in state.successors.into_iter().enumerate() {
+            self.add_block_use(successor, BlockOperand { owner: op, index });
+        }
+        let results = state
+            .types
+            .into_iter()
+            .enumerate()
+            .map(|(index, r#type)| self.new_value(r#type, ValueOwner::Result { op, index }))
+            .collect();
+        let regions: Vec<Region> = state
+            .regions
+            .iter()
+            .map(|_| {
+                Region(self.regions.alloc(RegionData {
+                    blocks: vec![],
+                    parent: op,
+                }))
+            })
+            .collect();
+        let data = self.operations.get_mut(op.0);
+        data.results = results;
+        data.regions = regions.clone();
+        for (region, blocks) in regions.into_iter().zip(state.regions) {
+            for block in blocks {
+                self.append_block(region, block);
+            }
+        }
+        op
+    }
+
+    /// Creates the `builtin.module` with its single empty block.
+    pub fn create_module(&mut self) -> Operation {
+        let mut state = OperationState::new("builtin.module");
+        state.add_regions(1);
+        let module = self.create_operation(state);
+        let body = self.create_block(&[]);
+        self.append_block(module.region(self, 0), body);
+        module
+    }
+
+    pub fn set_operand(&mut self, op: Operation, index: usize, value: Value) {
+        let operand = OpOperand { owner: op, index };
+        let previous = std::mem::replace(&mut self.operations.get_mut(op.0).operands[index], value);
+        self.remove_use(previous, operand);
+        self.add_use(value, operand);
+    }
+
+    /// Replaces all the operands of the operation.
+    pub fn set_operands(&mut self, op: Operation, operands: Vec<Value>) {
+        let previous = std::mem::replace(&mut self.operations.get_mut(op.0).operands, operands.clone());
+        for (index, value) in previous.into_iter().enumerate() {
+            self.remove_use(value, OpOperand { owner: op, index });
+        }
+        for (index, value) in operands.into_iter().enumerate() {
+            self.add_use(value, OpOperand { owner: op, index });
+        }
+    }
+
+    pub fn set_successor(&mut self, op: Operation, index: usize, block: Block) {
+        let operand = BlockOperand { owner: op, index };
+        let previous = std::mem::replace(&mut self.operations.get_mut(op.0).successors[index], block);
+        self.remove_block_use(previous, operand);
+        self.add_block_use(block, operand);
+    }
+
+    /// Sets or replaces the attribute, keeping the attributes sorted by name.
+    pub fn set_attribute(&mut self, op: Operation, name: &str, value: Attribute) {
+        let attributes = &mut self.operations.get_mut(op.0).attributes;
+        match attributes.binary_search_by(|attribute| attribute.name.as_str().cmp(name)) {
+            Ok(position) => attributes[position].value = value,
+            Err(position) => attributes.insert(position, NamedAttribute::new(name, value)),
+        }
+    }
+
+    pub fn remove_attribute(&mut self, op: Operation, name: &str) -> Option<Attribute> {
+        let attributes = &mut self.operations.get_mut(op.0).attributes;
+        let position = attributes.iter().position(|attribute| attribute.name == name)?;
+        Some(attributes.remove(position).value)
+    }
+
+    pub fn set_properties(&mut self, op: Operation, properties: Option<Attribute>) {
+        self.operations.get_mut(op.0).properties = properties;
+    }
+
+    pub fn set_location(&mut self, op: Operation, location: Attribute) {
+        self.operations.get_mut(op.0).location = location;
+    }
+
+    /// Unlinks the operation from its block, keeping it alive to be inserted elsewhere.
+    pub fn remove_operation(&mut self, op: Operation) {
+        if let Some(block) = self.operations.get_mut(op.0).parent.take() {
+            self.blocks
+                .get_mut(block.0)
+                .operations
+                .retain(|candidate| *candidate != op);
+        }
+    }
+
+    /// Drops the operand and successor uses of the operation and of the nested ones.
+    pub fn drop_all_references(&mut self, op: Operation) {
+        for nested in op.walk(self) {
+            let data = self.operations.get_mut(nested.0);
+            let operands = std::mem::take(&mut data.operands);
+            let successors = std::mem::take(&mut data.successors);
+            for (index, value) in operands.into_iter().enumerate() {
+                self.remove_use(value, OpOperand { owner: nested, index });
+            }
+            for (index, block) in successors.into_iter().enumerate() {
+                self.remove_block_use(block, BlockOperand { owner: nested, index });
+            }
+        }
+    }
+
+    /// Erases the operation with its regions, its results may only be used within them.
+    pub fn erase_operation(&mut self, op: Operation) -> Result<(), IrError> {
+        let nested = op.walk(self);
+        for inner in &nested {
+            for (index, result) in inner.results(self).iter().enumerate() {
+                if result.uses(self).iter().any(|operand| !nested.contains(&operand.owner)) {
+                    return Err(IrError::ResultInUse {
+                        op: inner.name(self).to_string(),
+                        index,
+                    });
+                }
+            }
+            for block in inner.regions(self).iter().flat_map(|region| region.blocks(self)) {
+                if let Some(operand) = block.uses(self).iter().find(|operand| !nested.contains(&operand.owner)) {
+                    return Err(IrError::BlockInUse {
+                        op: operand.owner.name(self).to_string(),
+                    });
+                }
+            }
+        }
+        self.remove_operation(op);
+        self.drop_all_references(op);
+        for inner in nested.into_iter().rev() {
+            let data = self.operations.free(inner.0);
+            for result in data.results {
+                self.values.free(result.0);
+            }
+            for region in data.regions {
+                for block in self.regions.free(region.0).blocks {
+                    for argument in self.blocks.free(block.0).arguments {
+                        self.values.free(argument.0);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the uses of the results with the values, then erases the operation.
+    pub fn replace_operation(&mut self, op: Operation, values: &[Value]) -> Result<(), IrError> {
+        let results = op.results(self).to_vec();
+        assert_eq!(
+            results.len(),
+            values.len(),
+            "replacing `{}` with a different number of values",
+            op.name(self)
+        );
+        for (result, value) in results.into_iter().zip(values) {
+            self.replace_all_uses_with(result, *value);
+        }
+        self.erase_operation(op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `%sum = arith.addi %a, %b` and `func.return %sum` in a module block taking `%a` and `%b`.
+    fn sum(ctx: &mut Context) -> (Block, Operation, Operation) {
+        let i32 = ctx.integer_type(32);
+        let block = ctx.create_block(&[i32, i32]);
+        let (a, b) = (block.argument(ctx, 0), block.argument(ctx, 1));
+        let mut state = OperationState::new("arith.addi");
+        state.add_operands([a, b]).add_types([i32]);
+        let add = ctx.create_operation(state);
+        ctx.insert_operation(block, None, add).unwrap();
+        let mut state = OperationState::new("func.return");
+        state.add_operands([add.result(ctx, 0)]);
+        let ret = ctx.create_operation(state);
+        ctx.insert_operation(block, None, ret).unwrap();
+        (block, add, ret)
+    }
+
+    #[test]
+    fn should_create_operations() {
+        let mut ctx = Context::new();
+        let (block, add, ret) = sum(&mut ctx);
+        assert_eq!(add.name(&ctx), "arith.addi");
+        assert_eq!(add.dialect(&ctx), "arith");
+        assert_eq!(add.operands(&ctx), block.arguments(&ctx));
+        assert_eq!(add.result(&ctx, 0).defining_op(&ctx), Some(add));
+        assert_eq!(add.result(&ctx, 0).uses(&ctx), &[OpOperand { owner: ret, index: 0 }]);
+        assert_eq!(
+            block.argument(&ctx, 1).uses(&ctx),
+            &[OpOperand { owner: add, index: 1 }]
+        );
+        assert_eq!(add.next(&ctx), Some(ret));
+        assert_eq!(ret.prev(&ctx), Some(add));
+        assert_eq!(block.terminator(&ctx), Some(ret));
+        assert_eq!(
+            ctx.insert_operation(block, None, add),
+            Err(IrError::AlreadyInserted("arith.addi".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_keep_attributes_sorted() {
+        let mut ctx = Context::new();
+        let (unit, yes) = (ctx.unit_attr(), ctx.bool_attr(true));
+        let mut state = OperationState::new("test.op");
+        state.add_attribute("b", unit).add_attribute("a", yes);
+        let op = ctx.create_operation(state);
+        let names = |ctx: &Context| {
+            op.attributes(ctx)
+                .iter()
+                .map(|attribute| attribute.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&ctx), ["a", "b"]);
+        ctx.set_attribute(op, "aa", unit);
+        ctx.set_attribute(op, "a", unit);
+        assert_eq!(names(&ctx), ["a", "aa", "b"]);
+        assert_eq!(op.attribute(&ctx, "a"), Some(unit));
+        assert_eq!(ctx.remove_attribute(op, "b"), Some(unit));
+        assert_eq!(op.attribute(&ctx, "b"), None);
+    }
+
+    #[test]
+    fn should_erase_unused_operations() {
+        let mut ctx = Context::new();
+        let (block, add, ret) = sum(&mut ctx);
+        assert_eq!(
+            ctx.erase_operation(add),
+            Err(IrError::ResultInUse {
+                op: "arith.addi".to_string(),
+                index: 0
+            })
+        );
+        ctx.erase_operation(ret).unwrap();
+        assert!(add.result(&ctx, 0).use_empty(&ctx));
+        ctx.erase_operation(add).unwrap();
+        assert!(!add.is_alive(&ctx));
+        assert!(block.is_empty(&ctx));
+        assert!(block.argument(&ctx, 0).use_empty(&ctx));
+    }
+
+    #[test]
+    fn should_walk_nested_operations() {
+        let mut ctx = Context::new();
+        let module = ctx.create_module();
+        let body = module.region(&ctx, 0).entry_block(&ctx).unwrap();
+        let mut state = OperationState::new("func.func");
+        state.add_regions(1);
+        let func = ctx.create_operation(state);
+        ctx.insert_operation(body, None, func).unwrap();
+        let (block, add, ret) = sum(&mut ctx);
+        ctx.append_block(func.region(&ctx, 0), block);
+
+        assert_eq!(module.walk(&ctx), [module, func, add, ret]);
+        assert_eq!(module.walk_post_order(&ctx), [add, ret, func, module]);
+        assert_eq!(add.parent_op(&ctx), Some(func));
+        assert!(module.is_ancestor_of(&ctx, add));
+        assert!(!func.is_ancestor_of(&ctx, module));
+
+        ctx.erase_operation(module).unwrap();
+        assert!(!add.is_alive(&ctx));
+    }
+
+    #[test]
+    fn should_replace_operations() {
+        let mut ctx = Context::new();
+        let (block, add, ret) = sum(&mut ctx);
+        let a = block.argument(&ctx, 0);
+        ctx.replace_operation(add, &[a]).unwrap();
+        assert_eq!(ret.operands(&ctx), &[a]);
+        assert_eq!(block.operations(&ctx), &[ret]);
+        assert_eq!(a.users(&ctx), [ret]);
+    }
+
+    #[test]
+    fn should_split_segments() {
+        use Variadicity::*;
+
+        assert_eq!(segment_ranges(3, &[Single, Variadic], None), vec![0..1, 1..3]);
+        assert_eq!(segment_ranges(5, &[Single, Variadic, Variadic], None), vec![0..1, 1..3, 3..5]);
+        assert_eq!(segment_ranges(1, &[Single, Optional], None), vec![0..1, 1..1]);
+        assert_eq!(
+            segment_ranges(4, &[Single, Variadic, Optional], Some(&[1, 3, 0])),
+            vec![0..1, 1..4, 4..4]
+        );
+    }
+
+    #[test]
+    fn should_move_inherent_attributes_into_properties() {
+        let mut ctx = Context::new();
+        let i32 = ctx.integer_type(32);
+        let block = ctx.create_block(&[i32, i32, i32]);
+        let arguments = block.arguments(&ctx).to_vec();
+        let sizes = {
+            let elements: Vec<Attribute> = [1, 2].iter().map(|size| ctx.integer_attr(*size, i32)).collect();
+            ctx.dense_array_attr(i32, &elements)
+        };
+        let (unit, yes) = (ctx.unit_attr(), ctx.bool_attr(true));
+        let mut state = OperationState::new("test.op");
+        state
+            .add_operands(arguments.clone())
+            .set_attribute("operandSegmentSizes", sizes)
+            .set_attribute("flag", unit)
+            .set_attribute("flag", yes)
+            .set_attribute("discardable", unit)
+            .move_into_properties(&mut ctx, &["operandSegmentSizes", "flag"]);
+        assert_eq!(state.attribute("flag"), None);
+        let op = ctx.create_operation(state);
+
+        let names: Vec<String> = op.attributes(&ctx).iter().map(|attribute| attribute.name.clone()).collect();
+        assert_eq!(names, ["discardable"]);
+        let names: Vec<String> = op.all_attributes(&ctx).into_iter().map(|attribute| attribute.name).collect();
+        assert_eq!(names, ["discardable", "flag", "operandSegmentSizes"]);
+        assert_eq!(op.attribute(&ctx, "flag"), Some(yes));
+        assert_eq!(
+            op.operand_groups(&ctx, &[Variadicity::Single, Variadicity::Variadic]),
+            [&arguments[..1], &arguments[1..]]
+        );
+    }
+}
@@ -0,0 +1,1811 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Generic form parser, mirroring the upstream `Parser` of the `.mlir` files.
+//!
+
+use std::collections::HashMap;
+
+use crate::printer::float_keyword;
+mod custom;
+
+pub use custom::{Argument, UnresolvedOperand};
+
+use crate::{
+    AffineExpr, AffineMap, Attribute, AttributeKind, Block, Context, FloatKind, NamedAttribute, Operation,
+    OperationState, ParseError, Signedness, Type, TypeKind, Value, DYNAMIC,
+};
+
+const FLOAT_KINDS: [FloatKind; 7] = [
+    FloatKind::BF16,
+    FloatKind::F16,
+    FloatKind::TF32,
+    FloatKind::F32,
+    FloatKind::F64,
+    FloatKind::F80,
+    FloatKind::F128,
+];
+
+/// Name of the placeholder operations standing for the values used before their definition.
+const FORWARD_REFERENCE: &str = "builtin.forward_reference";
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.')
+}
+
+fn is_suffix_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.' | '-')
+}
+
+/// Values visible in a region, and the placeholders of the ones used before their definition.
+#[derive(Default)]
+struct ValueScope {
+    values: HashMap<String, Vec<Value>>,
+    forward: HashMap<(String, usize), (Operation, usize)>,
+}
+
+/// Blocks of a region by their labels, with the position of the first reference to the undefined ones.
+#[derive(Default)]
+struct BlockScope {
+    blocks: HashMap<String, (Block, Option<usize>)>,
+}
+
+/// Trailing `loc(...)` of the operation, or its `#loc` alias defined after it.
+enum TrailingLocation {
+    Resolved(Attribute),
+    Deferred(String, usize),
+}
+
+/// Element literal of the `dense<...>` attributes.
+enum DenseLiteral {
+    Integer(i128),
+    /// `0x` hexadecimal literal, the bits of the floating point elements.
+    Bits(i128),
+    Float(f64),
+    List(Vec<DenseLiteral>),
+}
+
+/// Parses the generic form of the operations, along with the builtin types and attributes.
+pub struct Parser<'a, 'c> {
+    ctx: &'c mut Context,
+    source: &'a str,
+    position: usize,
+    type_aliases: HashMap<String, Type>,
+    attribute_aliases: HashMap<String, Attribute>,
+    values: Vec<ValueScope>,
+    blocks: Vec<BlockScope>,
+    /// Operations referring to the `#loc` aliases defined after them, at the end of the file.
+    deferred_locations: Vec<(Operation, String, usize)>,
+    /// Name of the source file and the line number of the source start, locating the operations
+    /// without the trailing `loc(...)`.
+    file: Option<(String, usize)>,
+    /// Dialects of the custom operation names without the prefix, `builtin` at the top level,
+    /// none in the regions of the operations without the default dialect.
+    default_dialects: Vec<&'static str>,
+}
+
+impl<'a, 'c> Parser<'a, 'c> {
+    pub fn new(ctx: &'c mut Context, source: &'a str) -> Self {
+        Self {
+            ctx,
+            source,
+            position: 0,
+            type_aliases: HashMap::new(),
+            attribute_aliases: HashMap::new(),
+            values: vec![],
+            blocks: vec![],
+            deferred_locations: vec![],
+            file: None,
+            default_dialects: vec!["builtin"],
+        }
+    }
+
+    /// Locates the operations without the trailing `loc(...)` at their `"file":line:column` in the source
+    /// starting at the line of the file, like the upstream `mlir-opt`.
+    pub fn with_file_locations(mut self, filename: impl Into<String>, first_line: usize) -> Self {
+        self.file = Some((filename.into(), first_line));
+        self
+    }
+
+    /// 1-based line and column of the source position.
+    fn line_column(&self, position: usize) -> (usize, usize) {
+        let consumed = &self.source[..position];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed
+            .rfind('\n')
+            .map_or(consumed.len(), |newline| consumed.len() - newline - 1)
+            + 1;
+        (line, column)
+    }
+
+    fn error_at(&self, position: usize, message: impl Into<String>) -> ParseError {
+        let (line, column) = self.line_column(position);
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.position, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.position += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    pub fn is_at_end(&mut self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+
+    fn peek_identifier(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return None;
+        }
+        let end = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+        Some(&rest[..end])
+    }
+
+    fn parse_identifier(&mut self) -> Result<&'a str, ParseError> {
+        let identifier = self
+            .peek_identifier()
+            .ok_or_else(|| self.error("expected identifier"))?;
+        self.position += identifier.len();
+        Ok(identifier)
+    }
+
+    pub fn parse_optional_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_identifier() == Some(keyword) {
+            self.position += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn parse_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.parse_optional_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{keyword}'")))
+        }
+    }
+
+    /// Punctuation, like `,`, `->` or `(`.
+    pub fn parse_optional_punct(&mut self, punct: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.starts_with(punct) && !(punct == "-" && rest.starts_with("->")) {
+            self.position += punct.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn parse_punct(&mut self, punct: &str) -> Result<(), ParseError> {
+        if self.parse_optional_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{punct}'")))
+        }
+    }
+
+    /// Comma separated list, the `close` punctuation ends it, an empty one included.
+    fn parse_list(
+        &mut self,
+        close: &str,
+        mut element: impl FnMut(&mut Self) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        if self.parse_optional_punct(close) {
+            return Ok(());
+        }
+        loop {
+            element(self)?;
+            if !self.parse_optional_punct(",") {
+                return self.parse_punct(close);
+            }
+        }
+    }
+
+    /// `"..."` with the `\n`, `\t`, `\"`, `\\` and `\XX` escapes.
+    pub fn parse_string(&mut self) -> Result<String, ParseError> {
+        if self.peek() != Some('"') {
+            return Err(self.error("expected string literal"));
+        }
+        self.position += 1;
+        let mut bytes = vec![];
+        loop {
+            let rest = self.rest().as_bytes();
+            match rest.first() {
+                None | Some(b'\n') => return Err(self.error("expected '\"' in string literal")),
+                Some(b'"') => {
+                    self.position += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    let (byte, length) = match rest.get(1) {
+                        Some(b'n') => (b'\n', 2),
+                        Some(b't') => (b'\t', 2),
+                        Some(b'"') => (b'"', 2),
+                        Some(b'\\') => (b'\\', 2),
+                        _ => {
+                            let hex = rest
+                                .get(1..3)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                                .ok_or_else(|| self.error("unknown escape in string literal"))?;
+                            (hex, 3)
+                        }
+                    };
+                    bytes.push(byte);
+                    self.position += length;
+                }
+                Some(byte) => {
+                    bytes.push(*byte);
+                    self.position += 1;
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("string literal is not valid UTF-8"))
+    }
+
+    /// Decimal, or `0x` hexadecimal integer, the hexadecimal ones are returned as their bits.
+    pub fn parse_integer(&mut self) -> Result<i128, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let negative = self.parse_optional_punct("-");
+        if self.rest().starts_with("0x") {
+            self.position += 2;
+            let digits = self.take_while(|c| c.is_ascii_hexdigit());
+            let value =
+                u128::from_str_radix(digits, 16).map_err(|_| self.error_at(start, "invalid hexadecimal integer"))?;
+            return Ok(if negative { -(value as i128) } else { value as i128 });
+        }
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        let value: i128 = digits.parse().map_err(|_| self.error_at(start, "expected integer"))?;
+        Ok(if negative { -value } else { value })
+    }
+
+    /// Integer or floating point literal, the hexadecimal ones are integers.
+    fn parse_number(&mut self) -> Result<DenseLiteral, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        if rest[sign..].starts_with("0x") {
+            return self.parse_integer().map(DenseLiteral::Bits);
+        }
+        let digits = |text: &str| text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        let mut end = sign + digits(&rest[sign..]);
+        if end == sign {
+            return Err(self.error("expected integer or floating point literal"));
+        }
+        let mut float = false;
+        if rest[end..].starts_with('.') {
+            float = true;
+            end += 1 + digits(&rest[end + 1..]);
+        }
+        if rest[end..].starts_with(['e', 'E']) {
+            let exponent = &rest[end + 1..];
+            let sign = usize::from(exponent.starts_with(['+', '-']));
+            if digits(&exponent[sign..]) > 0 {
+                float = true;
+                end += 1 + sign + digits(&exponent[sign..]);
+            }
+        }
+        self.position += end;
+        let text = &rest[..end];
+        if float {
+            text.parse()
+                .map(DenseLiteral::Float)
+                .map_err(|_| self.error_at(start, "invalid floating point literal"))
+        } else {
+            text.parse()
+                .map(DenseLiteral::Integer)
+                .map_err(|_| self.error_at(start, "integer literal is out of range"))
+        }
+    }
+
+    /// Top level operations, wrapped into a `builtin.module` unless it's the only one.
+    /// Skips the `{-# ... #-}` file metadata, the dialect and external resources aren't used.
+    fn skip_file_metadata(&mut self) -> Result<(), ParseError> {
+        match self.rest().find("#-}") {
+            Some(end) => {
+                self.position += end + "#-}".len();
+                Ok(())
+            }
+            None => Err(self.error("expected '#-}' closing the file metadata")),
+        }
+    }
+
+    pub fn parse_top_level(&mut self) -> Result<Operation, ParseError> {
+        let mut operations = vec![];
+        self.values.push(ValueScope::default());
+        while let Some(c) = self.peek() {
+            match c {
+                '#' | '!' => self.parse_alias_definition()?,
+                '{' if self.rest().starts_with("{-#") => self.skip_file_metadata()?,
+                _ => operations.push(self.parse_operation()?),
+            }
+        }
+        self.pop_value_scope()?;
+        for (op, alias, position) in std::mem::take(&mut self.deferred_locations) {
+            let location = self.location_alias(&alias).ok_or_else(|| {
+                self.error_at(
+                    position,
+                    format!("operation location alias was never defined: '#{alias}'"),
+                )
+            })?;
+            self.ctx.set_location(op, location);
+        }
+        if let [op] = operations.as_slice() {
+            if op.name(self.ctx) == "builtin.module" {
+                return Ok(*op);
+            }
+        }
+        let module = self.ctx.create_module();
+        let body = module.region(self.ctx, 0).blocks(self.ctx)[0];
+        for op in operations {
+            self.ctx
+                .insert_operation(body, None, op)
+                .map_err(|error| self.error(error.to_string()))?;
+        }
+        Ok(module)
+    }
+
+    fn location_alias(&self, alias: &str) -> Option<Attribute> {
+        self.attribute_aliases
+            .get(alias)
+            .copied()
+            .filter(|location| location.is_location(self.ctx))
+    }
+
+    /// `#alias = attribute` or `!alias = type`
+    fn parse_alias_definition(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        let prefix = self.rest().chars().next();
+        self.position += 1;
+        let alias = self.take_while(is_identifier_char);
+        if alias.is_empty() || alias.contains('.') {
+            return Err(self.error_at(start, "expected alias definition"));
+        }
+        self.parse_punct("=")?;
+        let redefined = if prefix == Some('#') {
+            let attribute = self.parse_attribute()?;
+            self.attribute_aliases.insert(alias.to_string(), attribute).is_some()
+        } else {
+            let r#type = self.parse_type()?;
+            self.type_aliases.insert(alias.to_string(), r#type).is_some()
+        };
+        if redefined {
+            return Err(self.error_at(start, format!("redefinition of alias id '{alias}'")));
+        }
+        Ok(())
+    }
+
+    fn pop_value_scope(&mut self) -> Result<(), ParseError> {
+        let scope = self.values.pop().unwrap_or_default();
+        match self.values.last_mut() {
+            Some(parent) => parent.forward.extend(scope.forward),
+            None => {
+                if let Some(((name, _), (_, position))) =
+                    scope.forward.iter().min_by_key(|(_, (_, position))| *position)
+                {
+                    return Err(self.error_at(*position, format!("use of undeclared SSA value name '%{name}'")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `%name` without the `%`.
+    fn parse_value_name(&mut self) -> Result<String, ParseError> {
+        self.parse_punct("%")?;
+        let name = self.take_while(is_suffix_char);
+        if name.is_empty() {
+            return Err(self.error("expected SSA value name"));
+        }
+        Ok(name.to_string())
+    }
+
+    /// `%name` or `%name#index` use of the result group.
+    fn parse_value_use(&mut self) -> Result<(String, usize, usize), ParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        let name = self.parse_value_name()?;
+        let index = match self.rest().starts_with('#') {
+            true => {
+                self.position += 1;
+                self.take_while(|c| c.is_ascii_digit())
+                    .parse()
+                    .map_err(|_| self.error("expected result number"))?
+            }
+            false => 0,
+        };
+        Ok((name, index, position))
+    }
+
+    /// Looks the value up, creating the placeholder of the yet undefined one.
+    fn resolve_value(&mut self, name: &str, index: usize, position: usize, r#type: Type) -> Result<Value, ParseError> {
+        let defined = self.values.iter().rev().find_map(|scope| scope.values.get(name));
+        let value = match defined {
+            Some(group) => *group.get(index).ok_or_else(|| {
+                self.error_at(position, format!("result number {index} of '%{name}' is out of range"))
+            })?,
+            None => {
+                let key = (name.to_string(), index);
+                let forward = self.values.iter().rev().find_map(|scope| scope.forward.get(&key));
+                match forward {
+                    Some((placeholder, _)) => placeholder.result(self.ctx, 0),
+                    None => {
+                        let mut state = OperationState::new(FORWARD_REFERENCE);
+                        state.add_types([r#type]);
+                        let placeholder = self.ctx.create_operation(state);
+                        if let Some(scope) = self.values.last_mut() {
+                            scope.forward.insert(key, (placeholder, position));
+                        }
+                        placeholder.result(self.ctx, 0)
+                    }
+                }
+            }
+        };
+        let expected = value.r#type(self.ctx);
+        if expected != r#type {
+            return Err(self.error_at(
+                position,
+                format!(
+                    "use of value '%{name}' expects different type than prior uses: '{}' vs '{}'",
+                    self.type_text(r#type),
+                    self.type_text(expected)
+                ),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn type_text(&self, r#type: Type) -> String {
+        let mut printer = crate::Printer::new(self.ctx, Default::default());
+        printer.print_type(r#type);
+        printer.finish()
+    }
+
+    /// Defines the values of the `%name`, replacing the placeholders of their forward references.
+    fn define_values(&mut self, name: String, values: Vec<Value>, position: usize) -> Result<(), ParseError> {
+        if self.values.last().is_some_and(|scope| scope.values.contains_key(&name)) {
+            return Err(self.error_at(position, format!("redefinition of SSA value '%{name}'")));
+        }
+        let mut placeholders = vec![];
+        if let Some(scope) = self.values.last_mut() {
+            for (index, value) in values.iter().enumerate() {
+                if let Some((placeholder, _)) = scope.forward.remove(&(name.clone(), index)) {
+                    placeholders.push((placeholder, *value));
+                }
+            }
+            scope.values.insert(name.clone(), values);
+        }
+        for (placeholder, value) in placeholders {
+            let forward = placeholder.result(self.ctx, 0);
+            if forward.r#type(self.ctx) != value.r#type(self.ctx) {
+                return Err(self.error_at(
+                    position,
+                    format!(
+                        "definition of SSA value '%{name}' has type '{}', but prior uses expect '{}'",
+                        self.type_text(value.r#type(self.ctx)),
+                        self.type_text(forward.r#type(self.ctx))
+                    ),
+                ));
+            }
+            self.ctx.replace_all_uses_with(forward, value);
+            self.ctx
+                .erase_operation(placeholder)
+                .map_err(|error| self.error_at(position, error.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// `^label` reference, creating the block of the yet undefined label.
+    fn block_reference(&mut self) -> Result<Block, ParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        self.parse_punct("^")?;
+        let label = self.take_while(is_suffix_char).to_string();
+        let Some(scope) = self.blocks.last() else {
+            return Err(self.error_at(position, "block references are only allowed within a region"));
+        };
+        if let Some((block, _)) = scope.blocks.get(&label) {
+            return Ok(*block);
+        }
+        let block = self.ctx.create_block(&[]);
+        if let Some(scope) = self.blocks.last_mut() {
+            scope.blocks.insert(label, (block, Some(position)));
+        }
+        Ok(block)
+    }
+
+    /// `%0:2 = "dialect.op"(%a) ... loc(...)` results, the operation in its generic or custom form, and its location.
+    pub fn parse_operation(&mut self) -> Result<Operation, ParseError> {
+        self.skip_whitespace();
+        let op_start = self.position;
+        let mut results = vec![];
+        if self.peek() == Some('%') {
+            self.parse_list("=", |parser| {
+                parser.skip_whitespace();
+                let position = parser.position;
+                let name = parser.parse_value_name()?;
+                let count = match parser.parse_optional_punct(":") {
+                    true => parser.parse_integer()? as usize,
+                    false => 1,
+                };
+                results.push((name, count, position));
+                Ok(())
+            })?;
+        }
+        self.skip_whitespace();
+        let start = self.position;
+        let state = match self.peek_identifier() {
+            Some(name) => self.parse_custom_operation(name)?,
+            None => self.parse_generic_operation()?,
+        };
+        let defined: usize = results.iter().map(|(_, count, _)| count).sum();
+        if !results.is_empty() && defined != state.types.len() {
+            return Err(self.error_at(
+                start,
+                format!(
+                    "operation defines {} results but was provided {defined} to bind",
+                    state.types.len()
+                ),
+            ));
+        }
+        let location = self.parse_optional_location()?;
+        let op = self.ctx.create_operation(state);
+        match location {
+            Some(TrailingLocation::Resolved(location)) => self.ctx.set_location(op, location),
+            Some(TrailingLocation::Deferred(alias, position)) => self.deferred_locations.push((op, alias, position)),
+            None => {
+                if let Some((filename, first_line)) = &self.file {
+                    let (line, column) = self.line_column(op_start);
+                    let filename = filename.clone();
+                    let line = (first_line + line - 1) as u32;
+                    let location = self.ctx.file_line_col_loc(&filename, line, column as u32);
+                    self.ctx.set_location(op, location);
+                }
+            }
+        }
+        let values = op.results(self.ctx).to_vec();
+        let mut values = values.into_iter();
+        for (name, count, position) in results {
+            let group = values.by_ref().take(count).collect();
+            self.define_values(name, group, position)?;
+        }
+        Ok(op)
+    }
+
+    /// Custom form of the registered operation, after its name, the name without the prefix is looked up
+    /// in the default dialect of the enclosing operation.
+    fn parse_custom_operation(&mut self, name: &str) -> Result<OperationState, ParseError> {
+        let assembly = self.ctx.dialects().assembly();
+        let qualified = match (assembly.contains(name), self.default_dialects.last()) {
+            (false, Some(dialect)) if !name.contains('.') && !dialect.is_empty() => format!("{dialect}.{name}"),
+            _ => name.to_string(),
+        };
+        let Some(asm) = assembly.get(&qualified).copied() else {
+            return Err(self.error(format!("custom op '{name}' is unknown")));
+        };
+        let default_dialect = assembly.default_dialect(&qualified);
+        self.position += name.len();
+        let mut state = OperationState::new(qualified);
+        self.default_dialects.push(default_dialect.unwrap_or_default());
+        let parsed = (asm.parse)(self, &mut state);
+        self.default_dialects.pop();
+        parsed?;
+        Ok(state)
+    }
+
+    /// `"dialect.op"(%a)[^bb1] <{properties}> ({...}) {attributes} : (i32) -> (i32, i32)`
+    fn parse_generic_operation(&mut self) -> Result<OperationState, ParseError> {
+        let mut state = OperationState::new(self.parse_string()?);
+        self.parse_punct("(")?;
+        let mut operands = vec![];
+        self.parse_list(")", |parser| {
+            operands.push(parser.parse_value_use()?);
+            Ok(())
+        })?;
+        if self.parse_optional_punct("[") {
+            self.parse_list("]", |parser| {
+                let block = parser.block_reference()?;
+                state.add_successors([block]);
+                Ok(())
+            })?;
+        }
+        if self.parse_optional_punct("<") {
+            let properties = self.parse_attribute()?;
+            if !matches!(properties.kind(self.ctx), AttributeKind::Dictionary(_)) {
+                return Err(self.error("expected the properties dictionary"));
+            }
+            state.set_properties(properties);
+            self.parse_punct(">")?;
+        }
+        let mut regions = vec![];
+        if self.parse_optional_punct("(") {
+            let default_dialect = self.ctx.dialects().assembly().default_dialect(&state.name);
+            self.default_dialects.push(default_dialect.unwrap_or_default());
+            let parsed = self.parse_list(")", |parser| {
+                regions.push(parser.parse_region()?);
+                Ok(())
+            });
+            self.default_dialects.pop();
+            parsed?;
+        }
+        if self.peek() == Some('{') {
+            state.attributes.extend(self.parse_dictionary()?);
+        }
+        self.parse_punct(":")?;
+        let position = self.position;
+        let function = self.parse_type()?;
+        let TypeKind::Function { inputs, results: types } = function.kind(self.ctx).clone() else {
+            return Err(self.error_at(position, "expected function type"));
+        };
+        if inputs.len() != operands.len() {
+            return Err(self.error_at(
+                position,
+                format!("{} operands present, but expected {}", operands.len(), inputs.len()),
+            ));
+        }
+        for ((name, index, position), r#type) in operands.into_iter().zip(inputs) {
+            let operand = self.resolve_value(&name, index, position, r#type)?;
+            state.add_operands([operand]);
+        }
+        state.add_types(types).regions = regions;
+        Ok(state)
+    }
+
+    /// `{ ^bb0(%arg0: i32): ... }` blocks, the entry block label may be left out.
+    pub fn parse_region(&mut self) -> Result<Vec<Block>, ParseError> {
+        self.parse_region_with_arguments(&[])
+    }
+
+    /// `{ ... }` blocks of the region whose entry block takes the arguments parsed ahead of it,
+    /// like the function bodies, the entry block label is then left out.
+    pub fn parse_region_with_arguments(&mut self, arguments: &[Argument]) -> Result<Vec<Block>, ParseError> {
+        self.parse_punct("{")?;
+        self.values.push(ValueScope::default());
+        self.blocks.push(BlockScope::default());
+        let mut blocks = vec![];
+        if !arguments.is_empty() {
+            if self.peek() == Some('^') {
+                return Err(self.error("invalid block name in region with named arguments"));
+            }
+            let types: Vec<Type> = arguments.iter().map(|argument| argument.r#type).collect();
+            let entry = self.ctx.create_block(&types);
+            for (argument, value) in arguments.iter().zip(entry.arguments(self.ctx).to_vec()) {
+                self.define_values(argument.name.clone(), vec![value], argument.position)?;
+            }
+            self.parse_block_body(entry)?;
+            blocks.push(entry);
+        } else if !matches!(self.peek(), Some('^' | '}')) {
+            let entry = self.ctx.create_block(&[]);
+            self.parse_block_body(entry)?;
+            blocks.push(entry);
+        }
+        while self.peek() == Some('^') {
+            blocks.push(self.parse_block()?);
+        }
+        self.parse_punct("}")?;
+        let scope = self.blocks.pop().unwrap_or_default();
+        let undefined = scope
+            .blocks
+            .iter()
+            .filter_map(|(label, (_, position))| position.map(|position| (position, label)));
+        if let Some((position, label)) = undefined.min() {
+            return Err(self.error_at(position, format!("reference to an undefined block '^{label}'")));
+        }
+        self.pop_value_scope()?;
+        Ok(blocks)
+    }
+
+    /// `^label(%arg: type loc(...)):` header and the operations of the block.
+    fn parse_block(&mut self) -> Result<Block, ParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        let block = self.block_reference()?;
+        let label = self.source[position + 1..self.position].to_string();
+        if let Some((_, reference)) = self.blocks.last_mut().and_then(|scope| scope.blocks.get_mut(&label)) {
+            if reference.take().is_none() {
+                return Err(self.error_at(position, format!("redefinition of block '^{label}'")));
+            }
+        }
+        if self.parse_optional_punct("(") {
+            self.parse_list(")", |parser| {
+                parser.skip_whitespace();
+                let position = parser.position;
+                let name = parser.parse_value_name()?;
+                parser.parse_punct(":")?;
+                let r#type = parser.parse_type()?;
+                parser.parse_optional_location()?;
+                let argument = parser.ctx.add_argument(block, r#type);
+                parser.define_values(name, vec![argument], position)
+            })?;
+        }
+        self.parse_punct(":")?;
+        self.parse_block_body(block)?;
+        Ok(block)
+    }
+
+    fn parse_block_body(&mut self, block: Block) -> Result<(), ParseError> {
+        while !matches!(self.peek(), Some('^' | '}') | None) {
+            let op = self.parse_operation()?;
+            self.ctx
+                .insert_operation(block, None, op)
+                .map_err(|error| self.error(error.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Trailing `loc(...)`, the `#loc` aliases yet to be defined are deferred.
+    fn parse_optional_location(&mut self) -> Result<Option<TrailingLocation>, ParseError> {
+        if !self.parse_optional_keyword("loc") {
+            return Ok(None);
+        }
+        self.parse_punct("(")?;
+        self.skip_whitespace();
+        let position = self.position;
+        if self.parse_optional_punct("#") {
+            let alias = self.take_while(is_identifier_char).to_string();
+            self.parse_punct(")")?;
+            return Ok(Some(match self.location_alias(&alias) {
+                Some(location) => TrailingLocation::Resolved(location),
+                None => TrailingLocation::Deferred(alias, position),
+            }));
+        }
+        let location = self.parse_location_body()?;
+        self.parse_punct(")")?;
+        Ok(Some(TrailingLocation::Resolved(location)))
+    }
+
+    /// `unknown`, `"file":1:2`, `"name"(child)`, `callsite(callee at caller)` or `fused<metadata>[locations]`
+    fn parse_location_body(&mut self) -> Result<Attribute, ParseError> {
+        if self.parse_optional_keyword("unknown") {
+            return Ok(self.ctx.unknown_loc());
+        }
+        if self.parse_optional_keyword("callsite") {
+            self.parse_punct("(")?;
+            let callee = self.parse_location_body()?;
+            self.parse_keyword("at")?;
+            let caller = self.parse_location_body()?;
+            self.parse_punct(")")?;
+            return Ok(self.ctx.call_site_loc(callee, caller));
+        }
+        if self.parse_optional_keyword("fused") {
+            let metadata = match self.parse_optional_punct("<") {
+                true => {
+                    let metadata = self.parse_attribute()?;
+                    self.parse_punct(">")?;
+                    Some(metadata)
+                }
+                false => None,
+            };
+            self.parse_punct("[")?;
+            let mut locations = vec![];
+            self.parse_list("]", |parser| {
+                locations.push(parser.parse_location_body()?);
+                Ok(())
+            })?;
+            return Ok(self.ctx.fused_loc(&locations, metadata));
+        }
+        if self.peek() == Some('#') {
+            let attribute = self.parse_attribute()?;
+            if !attribute.is_location(self.ctx) {
+                return Err(self.error("expected location"));
+            }
+            return Ok(attribute);
+        }
+        let name = self.parse_string()?;
+        if self.parse_optional_punct(":") {
+            let line = self.parse_integer()?;
+            self.parse_punct(":")?;
+            let column = self.parse_integer()?;
+            return Ok(self.ctx.file_line_col_loc(&name, line as u32, column as u32));
+        }
+        let child = match self.parse_optional_punct("(") {
+            true => {
+                let child = self.parse_location_body()?;
+                self.parse_punct(")")?;
+                Some(child)
+            }
+            false => None,
+        };
+        Ok(self.ctx.name_loc(&name, child))
+    }
+
+    /// `{name = value, unit}` entries.
+    fn parse_dictionary(&mut self) -> Result<Vec<NamedAttribute>, ParseError> {
+        self.parse_punct("{")?;
+        let mut attributes = vec![];
+        self.parse_list("}", |parser| {
+            let name = match parser.peek() {
+                Some('"') => parser.parse_string()?,
+                _ => parser.parse_identifier()?.to_string(),
+            };
+            let value = match parser.parse_optional_punct("=") {
+                true => parser.parse_attribute()?,
+                false => parser.ctx.unit_attr(),
+            };
+            attributes.push(NamedAttribute::new(name, value));
+            Ok(())
+        })?;
+        Ok(attributes)
+    }
+
+    pub fn parse_type(&mut self) -> Result<Type, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => return self.parse_function_type(),
+            Some('!') => return self.parse_dialect_type(),
+            _ => {}
+        }
+        let position = self.position;
+        let keyword = self.parse_identifier().map_err(|_| self.error("expected type"))?;
+        let integer = |prefix: &str| {
+            keyword
+                .strip_prefix(prefix)
+                .filter(|width| !width.is_empty() && width.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|width| width.parse::<u32>().ok())
+        };
+        for (prefix, signedness) in [
+            ("i", Signedness::Signless),
+            ("si", Signedness::Signed),
+            ("ui", Signedness::Unsigned),
+        ] {
+            if let Some(width) = integer(prefix) {
+                return Ok(self.ctx.get_type(TypeKind::Integer { width, signedness }));
+            }
+        }
+        if let Some(kind) = FLOAT_KINDS.into_iter().find(|kind| float_keyword(*kind) == keyword) {
+            return Ok(self.ctx.float_type(kind));
+        }
+        let kind = match keyword {
+            "index" => TypeKind::Index,
+            "none" => TypeKind::None,
+            "complex" => {
+                self.parse_punct("<")?;
+                let element = self.parse_type()?;
+                self.parse_punct(">")?;
+                TypeKind::Complex(element)
+            }
+            "tuple" => {
+                self.parse_punct("<")?;
+                let mut types = vec![];
+                self.parse_list(">", |parser| {
+                    types.push(parser.parse_type()?);
+                    Ok(())
+                })?;
+                TypeKind::Tuple(types)
+            }
+            "vector" => {
+                self.parse_punct("<")?;
+                let (shape, scalable) = self.parse_dimensions(true)?;
+                let element = self.parse_type()?;
+                self.parse_punct(">")?;
+                TypeKind::Vector {
+                    shape,
+                    scalable,
+                    element,
+                }
+            }
+            "tensor" => {
+                self.parse_punct("<")?;
+                if self.parse_optional_punct("*") {
+                    self.parse_punct("x")?;
+                    let element = self.parse_type()?;
+                    self.parse_punct(">")?;
+                    TypeKind::UnrankedTensor { element }
+                } else {
+                    let (shape, _) = self.parse_dimensions(false)?;
+                    let element = self.parse_type()?;
+                    let encoding = match self.parse_optional_punct(",") {
+                        true => Some(self.parse_attribute()?),
+                        false => None,
+                    };
+                    self.parse_punct(">")?;
+                    TypeKind::RankedTensor {
+                        shape,
+                        element,
+                        encoding,
+                    }
+                }
+            }
+            "memref" => {
+                self.parse_punct("<")?;
+                if self.parse_optional_punct("*") {
+                    self.parse_punct("x")?;
+                    let element = self.parse_type()?;
+                    let memory_space = match self.parse_optional_punct(",") {
+                        true => Some(self.parse_attribute()?),
+                        false => None,
+                    };
+                    self.parse_punct(">")?;
+                    TypeKind::UnrankedMemRef { element, memory_space }
+                } else {
+                    let (shape, _) = self.parse_dimensions(false)?;
+                    let element = self.parse_type()?;
+                    let (mut layout, mut memory_space) = (None, None);
+                    while self.parse_optional_punct(",") {
+                        let attribute = self.parse_attribute()?;
+                        match attribute.kind(self.ctx) {
+                            AttributeKind::AffineMap(map) if layout.is_none() && memory_space.is_none() => {
+                                layout = (!map.is_identity()).then_some(attribute);
+                            }
+                            AttributeKind::StridedLayout { .. } if layout.is_none() && memory_space.is_none() => {
+                                layout = Some(attribute);
+                            }
+                            _ if memory_space.is_none() => memory_space = Some(attribute),
+                            _ => return Err(self.error("unexpected memref layout or memory space")),
+                        }
+                    }
+                    self.parse_punct(">")?;
+                    TypeKind::MemRef {
+                        shape,
+                        element,
+                        layout,
+                        memory_space,
+                    }
+                }
+            }
+            keyword => return Err(self.error_at(position, format!("unknown type '{keyword}'"))),
+        };
+        Ok(self.ctx.get_type(kind))
+    }
+
+    /// `(inputs) -> results`, a single result may be left unwrapped.
+    fn parse_function_type(&mut self) -> Result<Type, ParseError> {
+        self.parse_punct("(")?;
+        let mut inputs = vec![];
+        self.parse_list(")", |parser| {
+            inputs.push(parser.parse_type()?);
+            Ok(())
+        })?;
+        self.parse_punct("->")?;
+        let mut results = vec![];
+        if self.parse_optional_punct("(") {
+            self.parse_list(")", |parser| {
+                results.push(parser.parse_type()?);
+                Ok(())
+            })?;
+        } else {
+            results.push(self.parse_type()?);
+        }
+        Ok(self.ctx.function_type(&inputs, &results))
+    }
+
+    /// `4x?x[8]x` dimensions prefix of the shaped types, the bracketed scalable ones only for the vectors.
+    fn parse_dimensions(&mut self, vector: bool) -> Result<(Vec<i64>, Vec<bool>), ParseError> {
+        let (mut shape, mut scalable) = (vec![], vec![]);
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let (dim, is_scalable) = if rest.starts_with('?') {
+                self.position += 1;
+                (DYNAMIC, false)
+            } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                (
+                    self.take_while(|c| c.is_ascii_digit())
+                        .parse()
+                        .map_err(|_| self.error("invalid dimension"))?,
+                    false,
+                )
+            } else if vector && rest.starts_with('[') {
+                self.position += 1;
+                let dim = self.parse_integer()? as i64;
+                self.parse_punct("]")?;
+                (dim, true)
+            } else {
+                return Ok((shape, scalable));
+            };
+            if !self.rest().starts_with('x') {
+                return Err(self.error("expected 'x' in dimension list"));
+            }
+            self.position += 1;
+            shape.push(dim);
+            scalable.push(is_scalable);
+        }
+    }
+
+    /// Balanced `<...>` body of the dialect types and attributes, skipping the strings and the `->` arrows.
+    fn parse_dialect_body(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.position;
+        let bytes = self.source.as_bytes();
+        let mut depth = 0;
+        let mut position = self.position;
+        loop {
+            match bytes.get(position) {
+                None => return Err(self.error_at(start, "unbalanced '<' in dialect body")),
+                Some(b'<' | b'(' | b'[' | b'{') => depth += 1,
+                Some(b'>' | b')' | b']' | b'}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        position += 1;
+                        break;
+                    }
+                }
+                Some(b'-') if bytes.get(position + 1) == Some(&b'>') => position += 1,
+                Some(b'"') => {
+                    position += 1;
+                    while !matches!(bytes.get(position), Some(b'"') | None) {
+                        position += if bytes[position] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                _ => {}
+            }
+            position += 1;
+        }
+        self.position = position;
+        Ok(&self.source[start..position])
+    }
+
+    /// `!alias`, `!dialect.data` or `!dialect<data>`
+    fn parse_dialect_type(&mut self) -> Result<Type, ParseError> {
+        let position = self.position;
+        self.parse_punct("!")?;
+        let (dialect, data) = self.parse_dialect_symbol()?;
+        if let Some(data) = data {
+            return Ok(self.ctx.opaque_type(dialect, &data));
+        }
+        self.type_aliases
+            .get(dialect)
+            .copied()
+            .ok_or_else(|| self.error_at(position, format!("undefined symbol alias id '{dialect}'")))
+    }
+
+    /// Dialect namespace and the data following it, no data stands for an alias.
+    fn parse_dialect_symbol(&mut self) -> Result<(&'a str, Option<String>), ParseError> {
+        let identifier = self.take_while(is_identifier_char);
+        if identifier.is_empty() {
+            return Err(self.error("expected alias or dialect identifier"));
+        }
+        let body = match self.rest().starts_with('<') {
+            true => self.parse_dialect_body()?,
+            false => "",
+        };
+        Ok(match identifier.split_once('.') {
+            Some((dialect, name)) => (dialect, Some(format!("{name}{body}"))),
+            None if !body.is_empty() => (identifier, Some(body.to_string())),
+            None => (identifier, None),
+        })
+    }
+
+    pub fn parse_attribute(&mut self) -> Result<Attribute, ParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        match self.peek() {
+            Some('"') => {
+                let value = self.parse_string()?;
+                return Ok(self.ctx.string_attr(&value));
+            }
+            Some('[') => {
+                self.position += 1;
+                let mut elements = vec![];
+                self.parse_list("]", |parser| {
+                    elements.push(parser.parse_attribute()?);
+                    Ok(())
+                })?;
+                return Ok(self.ctx.array_attr(&elements));
+            }
+            Some('{') => {
+                let attributes = self.parse_dictionary()?;
+                return Ok(self.ctx.dictionary_attr(attributes));
+            }
+            Some('@') => {
+                let mut names = vec![];
+                loop {
+                    self.parse_punct("@")?;
+                    names.push(match self.peek() {
+                        Some('"') => self.parse_string()?,
+                        _ => self.take_while(is_suffix_char).to_string(),
+                    });
+                    if !self.parse_optional_punct("::") {
+                        break;
+                    }
+                }
+                let root = names.remove(0);
+                return Ok(self.ctx.get_attribute(AttributeKind::SymbolRef { root, nested: names }));
+            }
+            Some('#') => {
+                self.position += 1;
+                let (dialect, data) = self.parse_dialect_symbol()?;
+                return match data {
+                    Some(data) => Ok(self.ctx.get_attribute(AttributeKind::Opaque {
+                        dialect: dialect.to_string(),
+                        data,
+                    })),
+                    None => self
+                        .attribute_aliases
+                        .get(dialect)
+                        .copied()
+                        .ok_or_else(|| self.error_at(position, format!("undefined symbol alias id '{dialect}'"))),
+                };
+            }
+            Some('-' | '0'..='9') => {
+                let literal = self.parse_number()?;
+                let r#type = match self.parse_optional_punct(":") {
+                    true => Some(self.parse_type()?),
+                    false => None,
+                };
+                return self.number_attribute(literal, r#type, position);
+            }
+            _ => {}
+        }
+        match self.peek_identifier() {
+            Some("true" | "false") => {
+                let value = self.parse_identifier()? == "true";
+                Ok(self.ctx.bool_attr(value))
+            }
+            Some("unit") => {
+                self.position += 4;
+                Ok(self.ctx.unit_attr())
+            }
+            Some("dense") => self.parse_dense_elements(),
+            Some("array") => self.parse_dense_array(),
+            Some("affine_map") => {
+                self.position += "affine_map".len();
+                self.parse_punct("<")?;
+                let map = self.parse_affine_map()?;
+                self.parse_punct(">")?;
+                Ok(self.ctx.affine_map_attr(map))
+            }
+            Some("strided") => {
+                self.position += "strided".len();
+                self.parse_punct("<")?;
+                self.parse_punct("[")?;
+                let mut strides = vec![];
+                self.parse_list("]", |parser| {
+                    strides.push(parser.parse_dynamic_integer()?);
+                    Ok(())
+                })?;
+                let offset = match self.parse_optional_punct(",") {
+                    true => {
+                        self.parse_keyword("offset")?;
+                        self.parse_punct(":")?;
+                        self.parse_dynamic_integer()?
+                    }
+                    false => 0,
+                };
+                self.parse_punct(">")?;
+                Ok(self.ctx.strided_layout_attr(offset, &strides))
+            }
+            Some("loc") => match self.parse_optional_location()? {
+                Some(TrailingLocation::Resolved(location)) => Ok(location),
+                Some(TrailingLocation::Deferred(alias, position)) => {
+                    Err(self.error_at(position, format!("undefined symbol alias id '{alias}'")))
+                }
+                None => Err(self.error("expected location")),
+            },
+            _ => {
+                let r#type = self
+                    .parse_type()
+                    .map_err(|_| self.error_at(position, "expected attribute value"))?;
+                Ok(self.ctx.type_attr(r#type))
+            }
+        }
+    }
+
+    /// Integer, or the `?` dynamic one.
+    fn parse_dynamic_integer(&mut self) -> Result<i64, ParseError> {
+        match self.parse_optional_punct("?") {
+            true => Ok(DYNAMIC),
+            false => Ok(self.parse_integer()? as i64),
+        }
+    }
+
+    /// Integer attribute of the `i64` type, or the floating point one of the `f64` type, by default.
+    fn number_attribute(
+        &mut self,
+        literal: DenseLiteral,
+        r#type: Option<Type>,
+        position: usize,
+    ) -> Result<Attribute, ParseError> {
+        let r#type = match (r#type, &literal) {
+            (Some(r#type), _) => r#type,
+            (None, DenseLiteral::Float(_)) => self.ctx.float_type(FloatKind::F64),
+            (None, _) => self.ctx.integer_type(64),
+        };
+        self.element_attribute(&literal, r#type)
+            .map_err(|message| self.error_at(position, message))
+    }
+
+    /// Element of the integer, index or floating point type, the hexadecimal integers are the bits of the floats.
+    fn element_attribute(&mut self, literal: &DenseLiteral, r#type: Type) -> Result<Attribute, String> {
+        let value = match (literal, r#type.kind(self.ctx)) {
+            (DenseLiteral::Integer(value) | DenseLiteral::Bits(value), TypeKind::Integer { .. } | TypeKind::Index) => {
+                return Ok(self.ctx.integer_attr(*value, r#type))
+            }
+            (DenseLiteral::Bits(bits), TypeKind::Float(FloatKind::F32)) => f32::from_bits(*bits as u32) as f64,
+            (DenseLiteral::Bits(bits), TypeKind::Float(FloatKind::F64)) => f64::from_bits(*bits as u64),
+            (DenseLiteral::Integer(value), TypeKind::Float(FloatKind::F32)) => *value as f32 as f64,
+            (DenseLiteral::Integer(value), TypeKind::Float(_)) => *value as f64,
+            (DenseLiteral::Float(value), TypeKind::Float(FloatKind::F32)) => *value as f32 as f64,
+            (DenseLiteral::Float(value), TypeKind::Float(_)) => *value,
+            (DenseLiteral::List(_), _) => return Err("unexpected nested literal".to_string()),
+            _ => return Err(format!("unexpected literal of the '{}' type", self.type_text(r#type))),
+        };
+        Ok(self.ctx.float_attr(value, r#type))
+    }
+
+    /// `true`, `false`, integer or floating point literal, or their nested `[...]` lists.
+    fn parse_dense_literal(&mut self) -> Result<DenseLiteral, ParseError> {
+        if self.parse_optional_punct("[") {
+            let mut elements = vec![];
+            self.parse_list("]", |parser| {
+                elements.push(parser.parse_dense_literal()?);
+                Ok(())
+            })?;
+            return Ok(DenseLiteral::List(elements));
+        }
+        if self.parse_optional_keyword("true") {
+            return Ok(DenseLiteral::Integer(1));
+        }
+        if self.parse_optional_keyword("false") {
+            return Ok(DenseLiteral::Integer(0));
+        }
+        self.parse_number()
+    }
+
+    /// `dense<[[1, 2], [3, 4]]> : tensor<2x2xi32>`, `dense<1> : vector<4xi32>` splat,
+    /// or the `dense<"0x...">` little endian hexadecimal bytes of the elements.
+    fn parse_dense_elements(&mut self) -> Result<Attribute, ParseError> {
+        self.parse_keyword("dense")?;
+        self.parse_punct("<")?;
+        let position = self.position;
+        let literal = match self.peek() {
+            Some('>') => None,
+            Some('"') => Some(Err(self.parse_string()?)),
+            _ => Some(Ok(self.parse_dense_literal()?)),
+        };
+        self.parse_punct(">")?;
+        self.parse_punct(":")?;
+        let r#type = self.parse_type()?;
+        let (Some(count), Some(element)) = (r#type.num_elements(self.ctx), r#type.element_type(self.ctx)) else {
+            return Err(self.error_at(position, "dense elements require a statically shaped type"));
+        };
+        let literals = match literal {
+            None if count == 0 => vec![],
+            None => return Err(self.error_at(position, "expected the elements of a non-empty shape")),
+            Some(Ok(literal)) => {
+                let mut shape = vec![];
+                let mut literals = vec![];
+                flatten_dense_literal(literal, 0, &mut shape, &mut literals)
+                    .map_err(|message| self.error_at(position, message))?;
+                let expected = r#type.shape(self.ctx).unwrap_or_default();
+                if !(literals.len() == 1 && shape.is_empty()) && shape != expected {
+                    return Err(self.error_at(
+                        position,
+                        format!("inferred shape of elements literal ({shape:?}) does not match type ({expected:?})"),
+                    ));
+                }
+                literals
+            }
+            Some(Err(hex)) => self
+                .hex_dense_literals(&hex, element, count)
+                .map_err(|message| self.error_at(position, message))?,
+        };
+        let elements = literals
+            .iter()
+            .map(|literal| self.element_attribute(literal, element))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|message| self.error_at(position, message))?;
+        Ok(self.ctx.dense_elements_attr(r#type, &elements))
+    }
+
+    /// Elements of the `0x` little endian bytes, a single element stands for the splat.
+    fn hex_dense_literals(&self, hex: &str, element: Type, count: i64) -> Result<Vec<DenseLiteral>, String> {
+        let digits = hex.strip_prefix("0x").ok_or("expected '0x' hexadecimal elements")?;
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|index| {
+                digits
+                    .get(index..index + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or("invalid hexadecimal elements")?;
+        let width = match element.kind(self.ctx) {
+            TypeKind::Index => 64,
+            _ => element
+                .int_or_float_width(self.ctx)
+                .filter(|width| width % 8 == 0)
+                .ok_or("hexadecimal elements require a byte sized integer or float type")?,
+        } as usize
+            / 8;
+        if bytes.len() != width && bytes.len() != width * count as usize {
+            return Err("hexadecimal elements do not match the type".to_string());
+        }
+        let signed = matches!(element.kind(self.ctx), TypeKind::Integer { signedness, .. } if *signedness != Signedness::Unsigned);
+        Ok(bytes
+            .chunks(width)
+            .map(|chunk| {
+                let bits = chunk.iter().rev().fold(0u128, |bits, byte| bits << 8 | *byte as u128);
+                let shift = 128 - chunk.len() * 8;
+                match signed {
+                    true => DenseLiteral::Bits(((bits << shift) as i128) >> shift),
+                    false => DenseLiteral::Bits(bits as i128),
+                }
+            })
+            .collect())
+    }
+
+    /// `array<i32: 1, 2>`
+    fn parse_dense_array(&mut self) -> Result<Attribute, ParseError> {
+        self.parse_keyword("array")?;
+        self.parse_punct("<")?;
+        let element = self.parse_type()?;
+        let mut elements = vec![];
+        if self.parse_optional_punct(":") {
+            loop {
+                let position = self.position;
+                let literal = self.parse_dense_literal()?;
+                let attribute = self
+                    .element_attribute(&literal, element)
+                    .map_err(|message| self.error_at(position, message))?;
+                elements.push(attribute);
+                if !self.parse_optional_punct(",") {
+                    break;
+                }
+            }
+        }
+        self.parse_punct(">")?;
+        Ok(self.ctx.dense_array_attr(element, &elements))
+    }
+
+    /// `(d0, d1)[s0] -> (d0 + s0, d1)`, the dimensions and symbols may have any names.
+    fn parse_affine_map(&mut self) -> Result<AffineMap, ParseError> {
+        let mut names = vec![];
+        self.parse_punct("(")?;
+        self.parse_list(")", |parser| {
+            let name = parser.parse_identifier()?;
+            names.push((name, AffineExpr::Dim(names.len() as u32)));
+            Ok(())
+        })?;
+        let dims = names.len() as u32;
+        if self.parse_optional_punct("[") {
+            self.parse_list("]", |parser| {
+                let name = parser.parse_identifier()?;
+                names.push((name, AffineExpr::Symbol(names.len() as u32 - dims)));
+                Ok(())
+            })?;
+        }
+        let symbols = names.len() as u32 - dims;
+        self.parse_punct("->")?;
+        self.parse_punct("(")?;
+        let mut results = vec![];
+        self.parse_list(")", |parser| {
+            results.push(parser.parse_affine_sum(&names)?);
+            Ok(())
+        })?;
+        Ok(AffineMap { dims, symbols, results })
+    }
+
+    fn parse_affine_sum(&mut self, names: &[(&str, AffineExpr)]) -> Result<AffineExpr, ParseError> {
+        let mut expr = self.parse_affine_product(names)?;
+        loop {
+            if self.parse_optional_punct("+") {
+                expr = expr + self.parse_affine_product(names)?;
+            } else if self.parse_optional_punct("-") {
+                expr = expr - self.parse_affine_product(names)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_affine_product(&mut self, names: &[(&str, AffineExpr)]) -> Result<AffineExpr, ParseError> {
+        let mut expr = self.parse_affine_operand(names)?;
+        loop {
+            if self.parse_optional_punct("*") {
+                expr = expr * self.parse_affine_operand(names)?;
+            } else if self.parse_optional_keyword("floordiv") {
+                expr = expr.floor_div(self.parse_affine_operand(names)?);
+            } else if self.parse_optional_keyword("ceildiv") {
+                expr = expr.ceil_div(self.parse_affine_operand(names)?);
+            } else if self.parse_optional_keyword("mod") {
+                expr = expr % self.parse_affine_operand(names)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_affine_operand(&mut self, names: &[(&str, AffineExpr)]) -> Result<AffineExpr, ParseError> {
+        if self.parse_optional_punct("-") {
+            return Ok(-self.parse_affine_operand(names)?);
+        }
+        if self.parse_optional_punct("(") {
+            let expr = self.parse_affine_sum(names)?;
+            self.parse_punct(")")?;
+            return Ok(expr);
+        }
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return Ok(AffineExpr::Constant(self.parse_integer()? as i64));
+        }
+        let position = self.position;
+        let name = self.parse_identifier()?;
+        names
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, expr)| expr.clone())
+            .ok_or_else(|| self.error_at(position, format!("use of undeclared identifier '{name}'")))
+    }
+}
+
+/// Flattens the nested literal lists, inferring their shape.
+fn flatten_dense_literal(
+    literal: DenseLiteral,
+    depth: usize,
+    shape: &mut Vec<i64>,
+    literals: &mut Vec<DenseLiteral>,
+) -> Result<(), String> {
+    let DenseLiteral::List(elements) = literal else {
+        if shape.len() != depth {
+            return Err("tensor literal is invalid; ranks are not consistent between elements".to_string());
+        }
+        literals.push(literal);
+        return Ok(());
+    };
+    match shape.get(depth) {
+        None if shape.len() == depth && literals.is_empty() => shape.push(elements.len() as i64),
+        Some(dim) if *dim == elements.len() as i64 => {}
+        _ => return Err("tensor literal is invalid; ranks are not consistent between elements".to_string()),
+    }
+    elements
+        .into_iter()
+        .try_for_each(|element| flatten_dense_literal(element, depth + 1, shape, literals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, parse_attribute, parse_type, print, Printer, PrinterOptions};
+
+    const LOCAL_SCOPE: PrinterOptions = PrinterOptions {
+        debug_info: false,
+        local_scope: true,
+        generic_op_form: false,
+    };
+
+    fn round_trip(source: &str, options: PrinterOptions) -> String {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        print(&ctx, module, options)
+    }
+
+    fn type_round_trip(ctx: &mut Context, text: &str) -> String {
+        let r#type = parse_type(ctx, text).unwrap();
+        let mut printer = Printer::new(ctx, LOCAL_SCOPE);
+        printer.print_type(r#type);
+        printer.finish()
+    }
+
+    fn attribute_round_trip(ctx: &mut Context, text: &str) -> String {
+        let attribute = parse_attribute(ctx, text).unwrap();
+        let mut printer = Printer::new(ctx, LOCAL_SCOPE);
+        printer.print_attribute(attribute);
+        printer.finish()
+    }
+
+    #[test]
+    fn should_round_trip_types() {
+        let mut ctx = Context::new();
+        for text in [
+            "i32",
+            "si8",
+            "ui64",
+            "index",
+            "bf16",
+            "tf32",
+            "f128",
+            "none",
+            "complex<f32>",
+            "tuple<i32, tuple<>>",
+            "(i32, f32) -> i1",
+            "() -> ()",
+            "(i1) -> ((i8) -> i8)",
+            "vector<4x[8]xf32>",
+            "vector<f16>",
+            "tensor<4x?xf32>",
+            "tensor<*xi8>",
+            "tensor<8xf32, #sparse_tensor.encoding<{ map = (d0) -> (d0 : compressed) }>>",
+            "memref<4x?xf32, strided<[?, 1], offset: ?>>",
+            "memref<8xf32, affine_map<(d0)[s0] -> (d0 + s0)>>",
+            "memref<4xi32, 1>",
+            "memref<2xf32, #gpu.address_space<workgroup>>",
+            "memref<*xf32, 3>",
+            "memref<f32>",
+            "!llvm.ptr<1>",
+            "!llvm.struct<(i32, f32)>",
+            "!llvm.func<void (ptr)>",
+            "!spirv.array<4 x f32>",
+        ] {
+            assert_eq!(type_round_trip(&mut ctx, text), text);
+        }
+        assert_eq!(
+            type_round_trip(&mut ctx, "memref<4xf32, affine_map<(i) -> (i)>>"),
+            "memref<4xf32>"
+        );
+        assert_eq!(parse_type(&mut ctx, "i32"), parse_type(&mut ctx, " i32 "));
+    }
+
+    #[test]
+    fn should_round_trip_attributes() {
+        let mut ctx = Context::new();
+        for text in [
+            "unit",
+            "true",
+            "42 : i32",
+            "-7 : i64",
+            "1.500000e+00 : f32",
+            "1.000000e-01 : f64",
+            "0x7FC00000 : f32",
+            r#""s\0A\"""#,
+            r#"[1, 2.500000e+00, "x", unit]"#,
+            r#"{a = 1 : i32, b, "c d" = i8}"#,
+            r#"@f::@"g h""#,
+            "array<i32: 1, 0>",
+            "array<i64>",
+            "array<f32: 1.000000e+00>",
+            "dense<[[1, 2], [3, 4]]> : tensor<2x2xi32>",
+            "dense<1.000000e+00> : vector<4xf32>",
+            "dense<[true, false]> : tensor<2xi1>",
+            "dense<> : tensor<0xi32>",
+            "affine_map<(d0, d1)[s0] -> (d0 + s0, d1 floordiv 2, -d0 + d1 * 4 - 1)>",
+            "affine_map<() -> (0)>",
+            "strided<[4, 1]>",
+            "strided<[?, 1], offset: 2>",
+            "#gpu<address_space global>",
+            "#llvm.linkage<internal>",
+            "i8",
+            "(i32) -> i32",
+            r#"loc(callsite("f" at "a.rs":3:4))"#,
+            r#"loc(fused<"inlined">["a.rs":1:2, "b"("c.rs":5:6)])"#,
+        ] {
+            assert_eq!(attribute_round_trip(&mut ctx, text), text);
+        }
+        for (text, canonical) in [
+            ("7", "7 : i64"),
+            ("0.5", "5.000000e-01 : f64"),
+            ("0x3F800000 : f32", "1.000000e+00 : f32"),
+            ("1 : i1", "true"),
+            ("dense<[1, 1]> : tensor<2xi32>", "dense<1> : tensor<2xi32>"),
+            (
+                r#"dense<"0x01000000FEFFFFFF"> : tensor<2xi32>"#,
+                "dense<[1, -2]> : tensor<2xi32>",
+            ),
+            ("affine_map<(i, j) -> (j, i)>", "affine_map<(d0, d1) -> (d1, d0)>"),
+            (
+                "affine_map<(d0) -> (2 * d0 + 0, d0 - (d0 + 1))>",
+                "affine_map<(d0) -> (d0 * 2, d0 - (d0 + 1))>",
+            ),
+            ("strided<[1], offset: 0>", "strided<[1]>"),
+        ] {
+            assert_eq!(attribute_round_trip(&mut ctx, text), canonical);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_operations() {
+        let source = r#"#map = affine_map<(d0)[s0] -> (d0 + s0)>
+module {
+  "func.func"() ({
+  ^bb0(%arg0: i32, %arg1: memref<?xf32, #map>):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.addi"(%arg0, %0) : (i32, i32) -> i32
+    "cf.br"(%1)[^bb1] : (i32) -> ()
+  ^bb1(%2: i32):  // pred: ^bb0
+    "func.return"(%2) : (i32) -> ()
+  }) {function_type = (i32, memref<?xf32, #map>) -> i32, sym_name = "f"} : () -> ()
+  "func.func"() ({
+  ^bb0(%arg0: index):
+    %0 = "arith.constant"() {value = 0 : index} : () -> index
+    %1:2 = "test.pair"() : () -> (i32, i32)
+    "scf.for"(%0, %arg0, %0) ({
+    ^bb0(%arg1: index):
+      %3 = "arith.addi"(%1#0, %1#1) : (i32, i32) -> i32
+      "scf.yield"() : () -> ()
+    }) : (index, index, index) -> ()
+    %2 = "test.last"() : () -> i32
+    "func.return"() : () -> ()
+  }) {function_type = (index) -> (), sym_name = "loop"} : () -> ()
+}
+"#;
+        assert_eq!(round_trip(source, PrinterOptions::default()), source);
+    }
+
+    #[test]
+    fn should_resolve_forward_references() {
+        let source = r#"module {
+  "test.graph"() ({
+    %0 = "test.use"(%1) : (i32) -> i32
+    %1 = "test.def"(%0) : (i32) -> i32
+  }) : () -> ()
+  "test.cfg"() ({
+    "cf.br"()[^bb2] : () -> ()
+  ^bb1:  // no predecessors
+    "cf.br"()[^bb2] : () -> ()
+  ^bb2:  // 2 preds: ^bb0, ^bb1
+    "test.return"() : () -> ()
+  }) : () -> ()
+}
+"#;
+        let wrapped = source
+            .strip_prefix("module {\n")
+            .and_then(|source| source.strip_suffix("}\n"))
+            .unwrap();
+        assert_eq!(round_trip(source, PrinterOptions::default()), source);
+        assert_eq!(round_trip(wrapped, PrinterOptions::default()), source);
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        assert!(module.walk(&ctx).iter().all(|op| op.name(&ctx) != FORWARD_REFERENCE));
+    }
+
+    #[test]
+    fn should_parse_properties_and_locations() {
+        let source = r#""test.op"() <{sym_name = "f"}> {a} : () -> () loc(#loc1)
+"test.op"() : () -> () loc(callsite("f" at "a.rs":3:4))
+"test.op"() ({
+^bb0(%arg0: i32 loc("a.rs":5:6)):
+  "test.op"() : () -> () loc(#loc1)
+}) : () -> () loc("b.rs":7:8)
+#loc1 = loc("a.rs":1:2)
+"#;
+        let printed = r#"module {
+  "test.op"() <{sym_name = "f"}> {a} : () -> () loc(#loc)
+  "test.op"() : () -> () loc(#loc1)
+  "test.op"() ({
+  ^bb0(%arg0: i32):
+    "test.op"() : () -> () loc(#loc)
+  }) : () -> () loc(#loc2)
+} loc(#loc3)
+#loc = loc("a.rs":1:2)
+#loc1 = loc(callsite("f" at "a.rs":3:4))
+#loc2 = loc("b.rs":7:8)
+#loc3 = loc(unknown)
+"#;
+        let debug_info = PrinterOptions {
+            debug_info: true,
+            local_scope: false,
+            generic_op_form: false,
+        };
+        assert_eq!(round_trip(source, debug_info), printed);
+        assert_eq!(round_trip(printed, debug_info), printed);
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        let first = module.walk(&ctx)[1];
+        let names: Vec<&str> = first.attributes(&ctx).iter().map(|attribute| attribute.name.as_str()).collect();
+        assert_eq!(names, ["a"]);
+        let sym_name = ctx.string_attr("f");
+        let properties = ctx.dictionary_attr(vec![NamedAttribute::new("sym_name", sym_name)]);
+        assert_eq!(first.properties(&ctx), Some(properties));
+        assert_eq!(first.attribute(&ctx, "sym_name"), Some(sym_name));
+        assert_eq!(
+            first.location(&ctx).kind(&ctx),
+            &AttributeKind::FileLineColLoc {
+                filename: "a.rs".to_string(),
+                line: 1,
+                column: 2
+            }
+        );
+        assert!(!print(&ctx, module, PrinterOptions::default()).contains("loc("));
+    }
+
+    #[test]
+    fn should_locate_operations_in_the_source_file() {
+        let source = "\"test.a\"() ({\n  %0 = \"test.b\"() : () -> i32\n}) : () -> ()\n\"test.c\"() : () -> () loc(\"a.rs\":1:2)";
+        let mut ctx = Context::new();
+        let module = Parser::new(&mut ctx, source)
+            .with_file_locations("input.mlir", 10)
+            .parse_top_level()
+            .unwrap();
+        let locations: Vec<_> = module.walk(&ctx)[1..]
+            .iter()
+            .map(|op| op.location(&ctx).kind(&ctx).clone())
+            .collect();
+        let location = |filename: &str, line, column| AttributeKind::FileLineColLoc {
+            filename: filename.to_string(),
+            line,
+            column,
+        };
+        assert_eq!(
+            locations,
+            [
+                location("input.mlir", 10, 1),
+                location("input.mlir", 11, 3),
+                location("a.rs", 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_errors() {
+        let error = |source: &str| parse(&mut Context::new(), source).unwrap_err().to_string();
+        assert_eq!(error("%0 = test.op %1"), "1:6: custom op 'test.op' is unknown");
+        assert_eq!(
+            error(r#""test.op"(%0) : () -> ()"#),
+            "1:16: 1 operands present, but expected 0"
+        );
+        assert_eq!(
+            error(r#""test.op"(%0) : (i32) -> ()"#),
+            "1:11: use of undeclared SSA value name '%0'"
+        );
+        assert_eq!(
+            error("\"test.a\"(%0) : (i32) -> ()\n\"test.b\"(%0) : (i64) -> ()"),
+            "2:10: use of value '%0' expects different type than prior uses: 'i64' vs 'i32'"
+        );
+        assert_eq!(
+            error("\"test.a\"(%0) : (i32) -> ()\n%0 = \"test.b\"() : () -> i64"),
+            "2:1: definition of SSA value '%0' has type 'i64', but prior uses expect 'i32'"
+        );
+        assert_eq!(
+            error("%0 = \"test.a\"() : () -> i1\n%0 = \"test.b\"() : () -> i1"),
+            "2:1: redefinition of SSA value '%0'"
+        );
+        assert_eq!(
+            error(r#""test.r"() ({ "cf.br"()[^bb1] : () -> () }) : () -> ()"#),
+            "1:25: reference to an undefined block '^bb1'"
+        );
+        assert_eq!(
+            error(r#""test.op"() : () -> () loc(#loc9)"#),
+            "1:28: operation location alias was never defined: '#loc9'"
+        );
+        assert_eq!(
+            error(r#""test.op"() {a = dense<[1, [2]]> : tensor<2xi32>} : () -> ()"#),
+            "1:24: tensor literal is invalid; ranks are not consistent between elements"
+        );
+        assert_eq!(
+            error(r#""test.op"() {a = dense<[1, 2, 3]> : tensor<2xi32>} : () -> ()"#),
+            "1:24: inferred shape of elements literal ([3]) does not match type ([2])"
+        );
+    }
+}
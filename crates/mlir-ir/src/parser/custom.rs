@@ -0,0 +1,486 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Custom form parsing helpers, mirroring the upstream `OpAsmParser`.
+//!
+
+use super::{is_identifier_char, Parser, FLOAT_KINDS};
+use crate::printer::float_keyword;
+use crate::{Attribute, Block, Context, NamedAttribute, OperationState, ParseError, Type};
+
+/// Attribute keywords besides the builtin types, the types are the type attributes.
+const ATTRIBUTE_KEYWORDS: [&str; 8] = ["true", "false", "unit", "dense", "array", "affine_map", "strided", "loc"];
+
+/// `%name` or `%name#index` operand, resolved once its type is known, like the upstream `UnresolvedOperand`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedOperand {
+    name: String,
+    index: usize,
+    position: usize,
+}
+
+/// `%name: type {attributes}` argument of the region entry block, like the upstream `OpAsmParser::Argument`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    pub name: String,
+    pub r#type: Type,
+    pub attributes: Vec<NamedAttribute>,
+    pub(super) position: usize,
+}
+
+/// Keyword of the builtin types, like `i32`, `f16`, `index` or `memref`.
+fn is_type_keyword(keyword: &str) -> bool {
+    let integer = ["i", "si", "ui"].iter().any(|prefix| {
+        keyword
+            .strip_prefix(prefix)
+            .is_some_and(|width| !width.is_empty() && width.chars().all(|c| c.is_ascii_digit()))
+    });
+    integer
+        || FLOAT_KINDS.iter().any(|kind| float_keyword(*kind) == keyword)
+        || matches!(keyword, "index" | "none" | "complex" | "tuple" | "vector" | "tensor" | "memref")
+}
+
+impl<'a> Parser<'a, '_> {
+    pub fn ctx(&mut self) -> &mut Context {
+        self.ctx
+    }
+
+    /// Bare identifier, like the struct parameter keys.
+    pub fn parse_optional_identifier(&mut self) -> Option<&'a str> {
+        let identifier = self.peek_identifier()?;
+        self.position += identifier.len();
+        Some(identifier)
+    }
+
+    pub fn parse_optional_string(&mut self) -> Result<Option<String>, ParseError> {
+        match self.peek() {
+            Some('"') => self.parse_string().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn parse_keyword_or_string(&mut self) -> Result<String, ParseError> {
+        match self.parse_optional_identifier() {
+            Some(keyword) => Ok(keyword.to_string()),
+            None => self.parse_string(),
+        }
+    }
+
+    pub fn parse_optional_integer(&mut self) -> Result<Option<i128>, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        match rest[sign..].starts_with(|c: char| c.is_ascii_digit()) {
+            true => self.parse_integer().map(Some),
+            false => Ok(None),
+        }
+    }
+
+    pub fn parse_optional_operand(&mut self) -> Result<Option<UnresolvedOperand>, ParseError> {
+        if self.peek() != Some('%') {
+            return Ok(None);
+        }
+        let (name, index, position) = self.parse_value_use()?;
+        Ok(Some(UnresolvedOperand { name, index, position }))
+    }
+
+    pub fn parse_operand(&mut self) -> Result<UnresolvedOperand, ParseError> {
+        self.parse_optional_operand()?
+            .ok_or_else(|| self.error("expected SSA operand"))
+    }
+
+    /// Zero or more comma separated operands.
+    pub fn parse_operand_list(&mut self) -> Result<Vec<UnresolvedOperand>, ParseError> {
+        let mut operands = vec![];
+        if let Some(operand) = self.parse_optional_operand()? {
+            operands.push(operand);
+            while self.parse_optional_punct(",") {
+                operands.push(self.parse_operand()?);
+            }
+        }
+        Ok(operands)
+    }
+
+    /// `%name: type {attributes} loc(...)` argument if present, its location is dropped.
+    pub fn parse_optional_argument(&mut self) -> Result<Option<Argument>, ParseError> {
+        if self.peek() != Some('%') {
+            return Ok(None);
+        }
+        let position = self.position;
+        let name = self.parse_value_name()?;
+        self.parse_punct(":")?;
+        let r#type = self.parse_type()?;
+        let attributes = self.parse_optional_attr_dict()?;
+        self.parse_optional_location()?;
+        Ok(Some(Argument {
+            name,
+            r#type,
+            attributes,
+            position,
+        }))
+    }
+
+    pub fn parse_argument(&mut self) -> Result<Argument, ParseError> {
+        self.parse_optional_argument()?
+            .ok_or_else(|| self.error("expected SSA identifier"))
+    }
+
+    /// `(%arg0: i32, %arg1: i32 {attributes}) -> results` signature of the function with the body,
+    /// or `(i32, i32 {attributes}) -> results` of the declaration, like the upstream `parseFunctionSignature`,
+    /// the results may be left out. Returns the arguments, without the names for the declaration, and the results.
+    pub fn parse_function_signature(&mut self) -> Result<(Vec<Argument>, Vec<Type>), ParseError> {
+        self.parse_punct("(")?;
+        let mut arguments = vec![];
+        let named = self.peek() == Some('%');
+        self.parse_list(")", |parser| {
+            let argument = match named {
+                true => parser.parse_argument()?,
+                false => Argument {
+                    name: String::new(),
+                    r#type: parser.parse_type()?,
+                    attributes: parser.parse_optional_attr_dict()?,
+                    position: parser.position,
+                },
+            };
+            arguments.push(argument);
+            Ok(())
+        })?;
+        let results = match self.rest().trim_start().starts_with("->") {
+            true => self.parse_arrow_type_list()?,
+            false => vec![],
+        };
+        Ok((arguments, results))
+    }
+
+    /// Resolves the operands of the types, adding them to the state.
+    pub fn resolve_operands(
+        &mut self,
+        operands: &[UnresolvedOperand],
+        types: &[Type],
+        state: &mut OperationState,
+    ) -> Result<(), ParseError> {
+        if operands.len() != types.len() {
+            return Err(self.error(format!(
+                "{} operands present, but expected {}",
+                operands.len(),
+                types.len()
+            )));
+        }
+        for (operand, r#type) in operands.iter().zip(types) {
+            let value = self.resolve_value(&operand.name, operand.index, operand.position, *r#type)?;
+            state.add_operands([value]);
+        }
+        Ok(())
+    }
+
+    /// `^label`
+    pub fn parse_optional_successor(&mut self) -> Result<Option<Block>, ParseError> {
+        match self.peek() {
+            Some('^') => self.block_reference().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn parse_successor(&mut self) -> Result<Block, ParseError> {
+        self.parse_optional_successor()?
+            .ok_or_else(|| self.error("expected block name"))
+    }
+
+    /// Zero or more comma separated successors.
+    pub fn parse_successor_list(&mut self) -> Result<Vec<Block>, ParseError> {
+        let mut successors = vec![];
+        if let Some(successor) = self.parse_optional_successor()? {
+            successors.push(successor);
+            while self.parse_optional_punct(",") {
+                successors.push(self.parse_successor()?);
+            }
+        }
+        Ok(successors)
+    }
+
+    /// Whether a type follows, the `(` only if a type or `)` follows it.
+    fn peek_type(&mut self) -> bool {
+        match self.peek() {
+            Some('!') => true,
+            Some('(') => {
+                let start = self.position;
+                self.position += 1;
+                let function = self.peek() == Some(')') || self.peek_type();
+                self.position = start;
+                function
+            }
+            _ => self.peek_identifier().is_some_and(is_type_keyword),
+        }
+    }
+
+    pub fn parse_optional_type(&mut self) -> Result<Option<Type>, ParseError> {
+        match self.peek_type() {
+            true => self.parse_type().map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// Zero or more comma separated types.
+    pub fn parse_type_list(&mut self) -> Result<Vec<Type>, ParseError> {
+        let mut types = vec![];
+        if let Some(r#type) = self.parse_optional_type()? {
+            types.push(r#type);
+            while self.parse_optional_punct(",") {
+                types.push(self.parse_type()?);
+            }
+        }
+        Ok(types)
+    }
+
+    /// `(inputs) -> results` types.
+    pub fn parse_functional_type(&mut self) -> Result<(Vec<Type>, Vec<Type>), ParseError> {
+        self.parse_punct("(")?;
+        let inputs = self.parse_type_list()?;
+        self.parse_punct(")")?;
+        let results = self.parse_arrow_type_list()?;
+        Ok((inputs, results))
+    }
+
+    /// `-> results`, a single result may be left unwrapped.
+    pub fn parse_arrow_type_list(&mut self) -> Result<Vec<Type>, ParseError> {
+        self.parse_punct("->")?;
+        if self.parse_optional_punct("(") {
+            let results = self.parse_type_list()?;
+            self.parse_punct(")")?;
+            return Ok(results);
+        }
+        Ok(vec![self.parse_type()?])
+    }
+
+    /// `mnemonic<...>` data of the dialect attribute or type without its prefix.
+    fn parse_optional_stripped_data(&mut self) -> Result<Option<String>, ParseError> {
+        let Some(mnemonic) = self.parse_optional_identifier() else {
+            return Ok(None);
+        };
+        let body = match self.rest().starts_with('<') {
+            true => self.parse_dialect_body()?,
+            false => "",
+        };
+        Ok(Some(format!("{mnemonic}{body}")))
+    }
+
+    /// Type of the dialect with or without its `!dialect.` prefix.
+    pub fn parse_optional_stripped_type(&mut self, dialect: &str) -> Result<Option<Type>, ParseError> {
+        if self.peek() == Some('!') {
+            return self.parse_type().map(Some);
+        }
+        let data = self.parse_optional_stripped_data()?;
+        Ok(data.map(|data| self.ctx.opaque_type(dialect, &data)))
+    }
+
+    pub fn parse_stripped_type(&mut self, dialect: &str) -> Result<Type, ParseError> {
+        self.parse_optional_stripped_type(dialect)?
+            .ok_or_else(|| self.error("expected type"))
+    }
+
+    /// Whether an attribute follows.
+    fn peek_attribute(&mut self) -> bool {
+        match self.peek() {
+            Some('"' | '[' | '{' | '@' | '#' | '0'..='9') => true,
+            Some('-') => self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()),
+            _ => self.peek_identifier().is_some_and(|keyword| ATTRIBUTE_KEYWORDS.contains(&keyword)) || self.peek_type(),
+        }
+    }
+
+    /// Parses the attribute, the integers and floats without the explicit type get the given one.
+    pub fn parse_optional_attribute(&mut self, r#type: Option<Type>) -> Result<Option<Attribute>, ParseError> {
+        if !self.peek_attribute() {
+            return Ok(None);
+        }
+        let Some(r#type) = r#type.filter(|_| matches!(self.peek(), Some('-' | '0'..='9'))) else {
+            return self.parse_attribute().map(Some);
+        };
+        let position = self.position;
+        let literal = self.parse_number()?;
+        let r#type = match self.parse_optional_punct(":") {
+            true => self.parse_type()?,
+            false => r#type,
+        };
+        self.number_attribute(literal, Some(r#type), position).map(Some)
+    }
+
+    /// Parses the attribute, the integers and floats without the explicit type get the given one.
+    pub fn parse_attribute_of_type(&mut self, r#type: Type) -> Result<Attribute, ParseError> {
+        self.parse_optional_attribute(Some(r#type))?
+            .ok_or_else(|| self.error("expected attribute value"))
+    }
+
+    /// `[1, 2]` dense array without its `array<type: ...>` wrapper.
+    pub fn parse_optional_stripped_dense_array(&mut self, element: Type) -> Result<Option<Attribute>, ParseError> {
+        if !self.parse_optional_punct("[") {
+            return Ok(None);
+        }
+        let mut elements = vec![];
+        self.parse_list("]", |parser| {
+            parser.skip_whitespace();
+            let position = parser.position;
+            let literal = parser.parse_dense_literal()?;
+            let attribute = parser
+                .element_attribute(&literal, element)
+                .map_err(|message| parser.error_at(position, message))?;
+            elements.push(attribute);
+            Ok(())
+        })?;
+        Ok(Some(self.ctx.dense_array_attr(element, &elements)))
+    }
+
+    pub fn parse_stripped_dense_array(&mut self, element: Type) -> Result<Attribute, ParseError> {
+        self.parse_optional_stripped_dense_array(element)?
+            .ok_or_else(|| self.error("expected '['"))
+    }
+
+    /// Attribute of the dialect with or without its `#dialect.` prefix.
+    pub fn parse_optional_stripped_attribute(&mut self, dialect: &str) -> Result<Option<Attribute>, ParseError> {
+        if self.peek() == Some('#') {
+            return self.parse_attribute().map(Some);
+        }
+        let data = self.parse_optional_stripped_data()?;
+        Ok(data.map(|data| {
+            self.ctx.get_attribute(crate::AttributeKind::Opaque {
+                dialect: dialect.to_string(),
+                data,
+            })
+        }))
+    }
+
+    pub fn parse_stripped_attribute(&mut self, dialect: &str) -> Result<Attribute, ParseError> {
+        self.parse_optional_stripped_attribute(dialect)?
+            .ok_or_else(|| self.error("expected attribute value"))
+    }
+
+    /// `@symbol` as the string attribute.
+    pub fn parse_optional_symbol_name(&mut self) -> Result<Option<Attribute>, ParseError> {
+        if !self.parse_optional_punct("@") {
+            return Ok(None);
+        }
+        let name = match self.rest().starts_with('"') {
+            true => self.parse_string()?,
+            false => self.take_while(is_identifier_char).to_string(),
+        };
+        if name.is_empty() {
+            return Err(self.error("expected valid '@'-identifier for symbol name"));
+        }
+        Ok(Some(self.ctx.string_attr(&name)))
+    }
+
+    pub fn parse_symbol_name(&mut self) -> Result<Attribute, ParseError> {
+        self.parse_optional_symbol_name()?
+            .ok_or_else(|| self.error("expected valid '@'-identifier for symbol name"))
+    }
+
+    /// Enum case keyword or string, stored as the integer attribute of the given type.
+    pub fn parse_optional_enum(&mut self, cases: &[(i128, &str)], r#type: Type) -> Result<Option<Attribute>, ParseError> {
+        let case = match self.parse_optional_identifier() {
+            Some(keyword) => keyword.to_string(),
+            None => match self.parse_optional_string()? {
+                Some(case) => case,
+                None => return Ok(None),
+            },
+        };
+        match cases.iter().find(|(_, name)| *name == case) {
+            Some((value, _)) => Ok(Some(self.ctx.integer_attr(*value, r#type))),
+            None => {
+                let names: Vec<&str> = cases.iter().map(|(_, name)| *name).collect();
+                Err(self.error(format!(
+                    "expected one of the following enum values [{}], got {case:?}",
+                    names.join(", ")
+                )))
+            }
+        }
+    }
+
+    pub fn parse_enum(&mut self, cases: &[(i128, &str)], r#type: Type) -> Result<Attribute, ParseError> {
+        self.parse_optional_enum(cases, r#type)?.ok_or_else(|| {
+            let names: Vec<&str> = cases.iter().map(|(_, name)| *name).collect();
+            self.error(format!(
+                "expected string or keyword containing one of the following enum values [{}]",
+                names.join(", ")
+            ))
+        })
+    }
+
+    /// `{name = value, ...}` if present.
+    pub fn parse_optional_attr_dict(&mut self) -> Result<Vec<NamedAttribute>, ParseError> {
+        match self.peek() {
+            Some('{') => self.parse_dictionary(),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// `attributes {name = value, ...}` if present.
+    pub fn parse_optional_attr_dict_with_keyword(&mut self) -> Result<Vec<NamedAttribute>, ParseError> {
+        match self.parse_optional_keyword("attributes") {
+            true => self.parse_dictionary(),
+            false => Ok(vec![]),
+        }
+    }
+
+    pub fn parse_optional_region(&mut self) -> Result<Option<Vec<Block>>, ParseError> {
+        match self.peek() {
+            Some('{') => self.parse_region().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Region whose entry block takes the arguments if present, like the optional function bodies.
+    pub fn parse_optional_region_with_arguments(
+        &mut self,
+        arguments: &[Argument],
+    ) -> Result<Option<Vec<Block>>, ParseError> {
+        match self.peek() {
+            Some('{') => self.parse_region_with_arguments(arguments).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Zero or more comma separated regions.
+    pub fn parse_region_list(&mut self) -> Result<Vec<Vec<Block>>, ParseError> {
+        let mut regions = vec![];
+        if let Some(region) = self.parse_optional_region()? {
+            regions.push(region);
+            while self.parse_optional_punct(",") {
+                regions.push(self.parse_region()?);
+            }
+        }
+        Ok(regions)
+    }
+
+    /// Adds the implicit terminator if the single block region lacks it, like `ensureTerminator`.
+    pub fn ensure_terminator(&mut self, region: &mut Vec<Block>, name: &str) -> Result<(), ParseError> {
+        if region.is_empty() {
+            region.push(self.ctx.create_block(&[]));
+        }
+        if let [block] = region.as_slice() {
+            let terminated = block
+                .operations(self.ctx)
+                .last()
+                .is_some_and(|op| op.name(self.ctx) == name);
+            if !terminated {
+                let terminator = self.ctx.create_operation(OperationState::new(name));
+                self.ctx
+                    .insert_operation(*block, None, terminator)
+                    .map_err(|error| self.error(error.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
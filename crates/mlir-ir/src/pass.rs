@@ -0,0 +1,535 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Pass infrastructure, the counterpart of the upstream `PassManager`.
+//! The [OpPassManager] runs its passes on the operations of its anchor name, and its nested pipelines on the matching
+//! operations directly within their regions, like `builtin.module(func.func(canonicalize,cse))`.
+//! The pipelines are parsed from and printed to the upstream textual pipeline syntax, the [PassRegistry] builds
+//! the passes by their arguments. The [AnalysisManager] keeps the analyses until a pass doesn't preserve them,
+//! and the [PassInstrumentation] hooks time the passes and dump the IR around them.
+//! The arena [Context] can't be shared mutably, so the multithreaded [PassManager] moves the isolated from above
+//! operations into the contexts of the worker threads and back through the bytecode, erasing the handles of the
+//! moved operations and of everything within them, see [PassManager::enable_multithreading].
+//!
+
+mod analysis;
+mod instrumentation;
+mod manager;
+mod pipeline;
+mod registry;
+
+pub use analysis::{Analysis, AnalysisManager, PreservedAnalyses};
+pub use instrumentation::{IrPrinting, IrPrintingConfig, PassInstrumentation, PassTime, PassTiming, ReproducerConfig};
+pub use manager::{OpPassManager, PassManager};
+pub use registry::{PassRegistry, RegisteredPass};
+
+use crate::{Context, Location, Operation};
+
+/// Transformation of the operations scheduled by the [OpPassManager], like the upstream `OperationPass`.
+/// Every worker thread runs its own clone of the pass, deriving [Clone] implements [PassClone].
+pub trait Pass: PassClone + Send {
+    /// Argument of the textual pipeline, like `canonicalize`.
+    fn argument(&self) -> &str;
+
+    /// Name of the IR dumps and the timing reports, like `Canonicalizer`.
+    fn name(&self) -> &str {
+        self.argument()
+    }
+
+    /// Name of the operations the pass is restricted to, like `func.func`, the op-agnostic passes run on any.
+    fn operation_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Pipeline element with the options, like `canonicalize{max-iterations=10}`.
+    fn print_pipeline(&self) -> String {
+        self.argument().to_string()
+    }
+
+    /// Runs on the operation, the analyses left out of the returned [PreservedAnalyses] are dropped.
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        analyses: &mut AnalysisManager,
+    ) -> Result<PreservedAnalyses, PassFailure>;
+}
+
+/// Cloning of the boxed passes, like the upstream `clonePass`.
+pub trait PassClone {
+    fn clone_pass(&self) -> Box<dyn Pass>;
+}
+
+impl<T: Pass + Clone + 'static> PassClone for T {
+    fn clone_pass(&self) -> Box<dyn Pass> {
+        Box::new(self.clone())
+    }
+}
+
+/// Failure signalled by the pass, like the upstream `signalPassFailure` with the emitted error.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{message}")]
+pub struct PassFailure {
+    pub message: String,
+    /// Location of the offending operation, the one the pass runs on if it's left out.
+    pub location: Option<Location>,
+}
+
+impl PassFailure {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    /// Failure reported on the operation, like the upstream `op->emitError()` before `signalPassFailure`.
+    pub fn on(ctx: &Context, op: Operation, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: Some(Location::new(ctx, op.location(ctx))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use crate::passes::{parse_option, PassOptionError, PassOptionValue, PassOptions};
+
+    use super::*;
+    use crate::{parse, print, PassError, PrinterOptions};
+
+    pub(crate) const SOURCE: &str = r#"module {
+  "func.func"() ({
+    "func.return"() : () -> ()
+  }) {sym_name = "first"} : () -> ()
+  "test.global"() : () -> ()
+  "func.func"() ({
+    "func.return"() : () -> ()
+  }) {sym_name = "second"} : () -> ()
+}
+"#;
+
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct MarkOptions {
+        pub(crate) label: String,
+    }
+
+    impl PassOptions for MarkOptions {
+        const ARGUMENT: &'static str = "test-mark";
+
+        fn set_option(&mut self, option: &str, value: &str) -> Result<(), PassOptionError> {
+            match option {
+                "label" => self.label = parse_option(Self::ARGUMENT, option, value)?,
+                _ => {
+                    return Err(PassOptionError::UnknownOption {
+                        pass: Self::ARGUMENT,
+                        option: option.to_string(),
+                    })
+                }
+            }
+            Ok(())
+        }
+
+        fn options(&self) -> Vec<(&'static str, String)> {
+            vec![("label", self.label.print_value())]
+        }
+    }
+
+    /// Appends the label to the `test.marks` attribute.
+    #[derive(Clone)]
+    pub(crate) struct Mark(pub(crate) MarkOptions);
+
+    impl Pass for Mark {
+        fn argument(&self) -> &str {
+            MarkOptions::ARGUMENT
+        }
+
+        fn print_pipeline(&self) -> String {
+            self.0.print_options()
+        }
+
+        fn run(
+            &mut self,
+            ctx: &mut Context,
+            op: Operation,
+            _analyses: &mut AnalysisManager,
+        ) -> Result<PreservedAnalyses, PassFailure> {
+            let marks = op
+                .attribute(ctx, "test.marks")
+                .and_then(|marks| marks.as_string(ctx))
+                .unwrap_or("");
+            let marks = ctx.string_attr(&format!("{marks}{}", self.0.label));
+            ctx.set_attribute(op, "test.marks", marks);
+            Ok(PreservedAnalyses::none())
+        }
+    }
+
+    /// Fails on the `second` function and panics on the `test.global` operation.
+    #[derive(Clone, Default)]
+    pub(crate) struct Fail;
+
+    impl Pass for Fail {
+        fn argument(&self) -> &str {
+            "test-fail"
+        }
+
+        fn run(
+            &mut self,
+            ctx: &mut Context,
+            op: Operation,
+            _analyses: &mut AnalysisManager,
+        ) -> Result<PreservedAnalyses, PassFailure> {
+            match op.attribute(ctx, "sym_name").and_then(|name| name.as_string(ctx)) {
+                Some("second") => Err(PassFailure::new("the second function is unsupported")),
+                _ if op.name(ctx) == "test.global" => panic!("the globals are unsupported"),
+                _ => Ok(PreservedAnalyses::all()),
+            }
+        }
+    }
+
+    pub(crate) fn registry() -> PassRegistry {
+        let mut registry = PassRegistry::new();
+        registry.register_with_options("Appends the label to `test.marks`", Mark);
+        registry.register::<Fail>("test-fail", "Fails on the `second` function");
+        registry.register_pipeline(
+            "test-marks",
+            "Appends `a` and `b`",
+            "test-mark{label=a},test-mark{label=b}",
+        );
+        registry
+    }
+
+    pub(crate) fn marks(ctx: &Context, module: Operation) -> Vec<Option<String>> {
+        module
+            .walk(ctx)
+            .into_iter()
+            .map(|op| {
+                op.attribute(ctx, "test.marks")
+                    .and_then(|marks| marks.as_string(ctx))
+                    .map(str::to_string)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_and_print_pipelines() {
+        let registry = registry();
+        let pm = PassManager::parse(
+            "builtin.module(func.func(test-mark{label=x}, test-marks), func.func(test-fail), any(test-fail))",
+            &registry,
+        )
+        .unwrap();
+        assert_eq!(
+            pm.to_string(),
+            "builtin.module(func.func(test-mark{label=x},test-mark{label=a},test-mark{label=b},test-fail),any(test-fail))"
+        );
+
+        let error = |pipeline: &str| PassManager::parse(pipeline, &registry).err().unwrap();
+        assert_eq!(
+            error("builtin.module(test-unknown)"),
+            PassError::Pipeline {
+                column: 16,
+                message: "`test-unknown` does not refer to a registered pass or pass pipeline".to_string()
+            }
+        );
+        assert_eq!(
+            error("builtin.module(func.func(test-fail{verbose=1}))").to_string(),
+            "pass pipeline column 26: `test-fail` pass has no option `verbose`"
+        );
+        assert!(matches!(error("test-mark"), PassError::Pipeline { column: 1, .. }));
+        assert!(matches!(
+            error("builtin.module(test-mark{label=a"),
+            PassError::Pipeline { column: 25, .. }
+        ));
+    }
+
+    #[test]
+    fn should_run_nested_pipelines() {
+        let registry = registry();
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let mut pm = PassManager::parse("builtin.module(func.func(test-marks),test-mark{label=m})", &registry).unwrap();
+        pm.nest_any().add_pipeline("test-mark{label=c}", &registry).unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        let mark = |marks: &str| Some(marks.to_string());
+        assert_eq!(
+            marks(&ctx, module),
+            vec![mark("m"), mark("abc"), None, mark("c"), mark("abc"), None]
+        );
+
+        let mut pm = PassManager::new("func.func");
+        assert_eq!(
+            pm.run(&mut ctx, module),
+            Err(PassError::PipelineAnchor {
+                anchor: "func.func".to_string(),
+                op: "builtin.module".to_string()
+            })
+        );
+
+        #[derive(Clone)]
+        struct Restricted;
+        impl Pass for Restricted {
+            fn argument(&self) -> &str {
+                "test-restricted"
+            }
+            fn operation_name(&self) -> Option<&str> {
+                Some("func.func")
+            }
+            fn run(
+                &mut self,
+                _: &mut Context,
+                _: Operation,
+                _: &mut AnalysisManager,
+            ) -> Result<PreservedAnalyses, PassFailure> {
+                Ok(PreservedAnalyses::all())
+            }
+        }
+        let mut pm = PassManager::new("builtin.module");
+        pm.nest_any().add_pass(Box::new(Restricted));
+        assert_eq!(
+            pm.run(&mut ctx, module).unwrap_err().to_string(),
+            "can't run the `test-restricted` pass restricted to `func.func` on `test.global`"
+        );
+    }
+
+    #[test]
+    fn should_run_isolated_operations_on_worker_threads() {
+        let registry = registry();
+        const FUNCTION: &str = r#"  "func.func"() ({
+    %0 = "test.value"() : () -> i32
+    "func.return"(%0) : (i32) -> ()
+  }) : () -> ()
+"#;
+        let functions = FUNCTION.repeat(16);
+        let source = format!("\"builtin.module\"() ({{\n{functions}}}) : () -> ()\n");
+        let pipeline = "builtin.module(func.func(test-marks,test-mark{label=c}),test-mark{label=m})";
+
+        let mut sequential = Context::new();
+        let module = parse(&mut sequential, &source).unwrap();
+        PassManager::parse(pipeline, &registry)
+            .unwrap()
+            .run(&mut sequential, module)
+            .unwrap();
+        let expected = print(&sequential, module, PrinterOptions::default());
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, &source).unwrap();
+        let first = module.walk(&ctx)[1];
+        let mut pm = PassManager::parse(pipeline, &registry).unwrap();
+        pm.enable_multithreading(4);
+        pm.run(&mut ctx, module).unwrap();
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), expected);
+        assert!(!first.is_alive(&ctx), "the functions are moved to the workers and back");
+    }
+
+    #[test]
+    fn should_replace_the_results_of_the_operations_moved_to_the_workers() {
+        let registry = registry();
+        const ISOLATED: &str = r#"  %{0} = "test.isolated"() ({
+    %0 = "test.value"() : () -> i32
+    "test.yield"(%0) : (i32) -> ()
+  }) {sym_name = "{0}"} : () -> i32
+"#;
+        let isolated: String = ["a", "b", "second", "d"]
+            .iter()
+            .map(|name| ISOLATED.replace("{0}", name))
+            .collect();
+        let source = format!(
+            "\"builtin.module\"() ({{\n{isolated}  \"test.use\"(%a, %b, %second, %d) : (i32, i32, i32, i32) -> ()\n}}) : () -> ()\n"
+        );
+        let run = |pipeline: &str, threads: usize| {
+            let mut ctx = Context::new();
+            let module = parse(&mut ctx, &source).unwrap();
+            let first = module.walk(&ctx)[1];
+            let mut pm = PassManager::parse(pipeline, &registry).unwrap();
+            pm.enable_multithreading(threads);
+            let result = pm.run(&mut ctx, module);
+            (print(&ctx, module, PrinterOptions::default()), first.is_alive(&ctx), result)
+        };
+
+        let pipeline = "builtin.module(test.isolated(test-mark{label=c}))";
+        let (expected, _, _) = run(pipeline, 1);
+        let (printed, alive, result) = run(pipeline, 4);
+        result.unwrap();
+        assert_eq!(printed, expected);
+        assert!(printed.contains(r#""test.use"(%0, %1, %2, %3)"#), "{printed}");
+        assert!(!alive, "the operations with results are moved to the workers and back");
+
+        let pipeline = "builtin.module(test.isolated(test-mark{label=c},test-fail))";
+        let (expected, _, expected_error) = run(pipeline, 1);
+        let (printed, _, error) = run(pipeline, 4);
+        assert_eq!(printed, expected, "the operations are replaced until the first failure");
+        assert_eq!(error.unwrap_err(), expected_error.unwrap_err());
+
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#""builtin.module"() ({
+  %0 = "test.value"() : () -> i32
+  "test.isolated"(%0) ({
+  }) : (i32) -> ()
+  "test.isolated"(%0) ({
+  }) : (i32) -> ()
+}) : () -> ()
+"#,
+        )
+        .unwrap();
+        let first = module.walk(&ctx)[2];
+        let mut pm = PassManager::parse("builtin.module(test.isolated(test-mark{label=c}))", &registry).unwrap();
+        pm.enable_multithreading(4);
+        pm.run(&mut ctx, module).unwrap();
+        assert!(first.is_alive(&ctx), "the operations with operands are run on in the shared context");
+        assert_eq!(marks(&ctx, module)[2].as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn should_cache_analyses_until_invalidated() {
+        static COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+        struct OpCount(usize);
+        impl Analysis for OpCount {
+            fn compute(ctx: &Context, op: Operation) -> Self {
+                COMPUTED.fetch_add(1, Ordering::Relaxed);
+                OpCount(op.walk(ctx).len())
+            }
+        }
+
+        struct Unused;
+        impl Analysis for Unused {
+            fn compute(_: &Context, _: Operation) -> Self {
+                Unused
+            }
+        }
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let function = module.walk(&ctx)[1];
+        let mut analyses = AnalysisManager::new();
+        assert_eq!(analyses.get::<OpCount>(&ctx, module).0, 6);
+        assert_eq!(analyses.get::<OpCount>(&ctx, function).0, 2);
+        assert_eq!(analyses.get::<OpCount>(&ctx, module).0, 6);
+        assert_eq!(COMPUTED.load(Ordering::Relaxed), 2);
+
+        analyses.invalidate(&ctx, function, &PreservedAnalyses::none().preserve::<OpCount>());
+        assert!(analyses.cached::<OpCount>(module).is_some());
+        let preserved = PreservedAnalyses::none().preserve::<Unused>();
+        assert!(preserved.is_preserved::<Unused>() && !preserved.is_preserved::<OpCount>());
+        analyses.invalidate(&ctx, function, &preserved);
+        assert!(analyses.cached::<OpCount>(module).is_none() && analyses.cached::<OpCount>(function).is_none());
+
+        let sibling = module.walk(&ctx)[4];
+        analyses.get::<OpCount>(&ctx, sibling);
+        analyses.invalidate(&ctx, function, &PreservedAnalyses::none());
+        assert!(
+            analyses.cached::<OpCount>(sibling).is_some(),
+            "the sibling operations are unaffected"
+        );
+        assert_eq!(COMPUTED.load(Ordering::Relaxed), 3);
+
+        let mut kept = PreservedAnalyses::all();
+        kept.intersect(&PreservedAnalyses::none().preserve::<OpCount>().preserve::<Unused>());
+        kept.intersect(&PreservedAnalyses::none().preserve::<OpCount>());
+        assert_eq!(kept, PreservedAnalyses::none().preserve::<OpCount>());
+    }
+
+    #[test]
+    fn should_instrument_passes() {
+        let registry = registry();
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let dumps = Arc::new(Mutex::new(String::new()));
+        let sink = dumps.clone();
+        let config = IrPrintingConfig {
+            after_all: true,
+            before: vec!["test-fail".to_string()],
+            ..IrPrintingConfig::default()
+        };
+        let timing = PassTiming::new();
+        let mut pm = PassManager::parse("builtin.module(test-mark{label=m},test-fail)", &registry).unwrap();
+        pm.add_instrumentation(timing.clone())
+            .add_instrumentation(IrPrinting::new(config, move |dump| sink.lock().unwrap().push_str(dump)));
+        pm.run(&mut ctx, module).unwrap();
+
+        let dumps = dumps.lock().unwrap();
+        let headers: Vec<&str> = dumps.lines().filter(|line| line.starts_with("// -----//")).collect();
+        assert_eq!(
+            headers,
+            vec![
+                "// -----// IR Dump After test-mark (test-mark) ('builtin.module' operation) //----- //",
+                "// -----// IR Dump Before test-fail (test-fail) ('builtin.module' operation) //----- //",
+                "// -----// IR Dump After test-fail (test-fail) ('builtin.module' operation) //----- //",
+            ]
+        );
+        assert!(dumps.contains(r#"module attributes {test.marks = "m"} {"#), "{dumps}");
+
+        let times = timing.times();
+        assert_eq!(
+            times.iter().map(|time| time.argument.as_str()).collect::<Vec<_>>(),
+            vec!["test-mark", "test-fail"]
+        );
+        let report = timing.report();
+        assert!(report.contains("Execution time report"), "{report}");
+        assert!(
+            report.contains("  test-mark (test-mark)\n") && report.contains("  Total\n"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn should_write_crash_reproducers() {
+        let registry = registry();
+        for (pipeline, crashed) in [
+            ("builtin.module(func.func(test-mark{label=\"a b\"},test-fail))", false),
+            ("builtin.module(any(test-fail))", true),
+        ] {
+            let mut ctx = Context::new();
+            let module = parse(&mut ctx, SOURCE).unwrap();
+            let original = print(&ctx, module, PrinterOptions::default());
+            let reproducers = Arc::new(Mutex::new(vec![]));
+            let sink = reproducers.clone();
+            let mut pm = PassManager::parse(pipeline, &registry).unwrap();
+            pm.enable_crash_reproducer(move |reproducer| sink.lock().unwrap().push(reproducer.to_string()));
+            let error = pm.run(&mut ctx, module).unwrap_err();
+            match crashed {
+                true => assert_eq!(
+                    error.to_string(),
+                    "`test-fail` pass crashed on `test.global`: the globals are unsupported"
+                ),
+                false => assert_eq!(
+                    error.to_string(),
+                    "`test-fail` pass failed on `func.func`: the second function is unsupported"
+                ),
+            }
+
+            let reproducers = reproducers.lock().unwrap();
+            let [reproducer] = reproducers.as_slice() else {
+                panic!("{reproducers:?}");
+            };
+            let config = ReproducerConfig::read(reproducer).unwrap();
+            assert_eq!(config.pipeline, pm.to_string());
+            assert!(config.disable_threading);
+            assert_eq!(config.verify_each, cfg!(debug_assertions));
+            let mut ctx = Context::new();
+            let module = parse(&mut ctx, reproducer).unwrap();
+            assert_eq!(print(&ctx, module, PrinterOptions::default()), original);
+        }
+    }
+}
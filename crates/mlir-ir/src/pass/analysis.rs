@@ -0,0 +1,123 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Analyses cached by the operations they are computed on, like the upstream `AnalysisManager`.
+//!
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+
+use crate::{Context, Operation};
+
+/// Analysis computed on the first request and cached until a pass doesn't preserve it.
+pub trait Analysis: Any {
+    fn compute(ctx: &Context, op: Operation) -> Self
+    where
+        Self: Sized;
+}
+
+/// Analyses a pass keeps valid, like the upstream `PreservedAnalyses`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreservedAnalyses {
+    all: bool,
+    preserved: HashSet<TypeId>,
+}
+
+impl PreservedAnalyses {
+    /// Nothing is preserved, the default of the passes changing the IR.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Everything is preserved, for the passes leaving the IR untouched.
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            preserved: HashSet::new(),
+        }
+    }
+
+    pub fn preserve<A: Analysis>(mut self) -> Self {
+        self.preserved.insert(TypeId::of::<A>());
+        self
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.all
+    }
+
+    pub fn is_preserved<A: Analysis>(&self) -> bool {
+        self.contains(TypeId::of::<A>())
+    }
+
+    /// Keeps the analyses preserved by both, for the pipelines of several passes.
+    pub fn intersect(&mut self, other: &PreservedAnalyses) {
+        match (self.all, other.all) {
+            (_, true) => {}
+            (true, false) => *self = other.clone(),
+            (false, false) => self.preserved.retain(|id| other.preserved.contains(id)),
+        }
+    }
+
+    fn contains(&self, id: TypeId) -> bool {
+        self.all || self.preserved.contains(&id)
+    }
+}
+
+/// Analyses by the operations they are computed on.
+#[derive(Default)]
+pub struct AnalysisManager {
+    cache: HashMap<(Operation, TypeId), Box<dyn Any>>,
+}
+
+impl AnalysisManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cached analysis of the operation, computing it on the first request.
+    pub fn get<A: Analysis>(&mut self, ctx: &Context, op: Operation) -> &A {
+        self.cache
+            .entry((op, TypeId::of::<A>()))
+            .or_insert_with(|| Box::new(A::compute(ctx, op)))
+            .downcast_ref()
+            .expect("analyses are cached by their type")
+    }
+
+    /// Analysis of the operation if it's cached.
+    pub fn cached<A: Analysis>(&self, op: Operation) -> Option<&A> {
+        self.cache
+            .get(&(op, TypeId::of::<A>()))
+            .and_then(|analysis| analysis.downcast_ref())
+    }
+
+    /// Drops the analyses not preserved by the pass run on the operation: the ones of the operation,
+    /// of the nested operations and of its ancestors, and the ones of the erased operations.
+    pub fn invalidate(&mut self, ctx: &Context, op: Operation, preserved: &PreservedAnalyses) {
+        if preserved.is_all() {
+            return;
+        }
+        self.cache.retain(|(cached, id), _| {
+            cached.is_alive(ctx)
+                && (preserved.contains(*id) || !(op.is_ancestor_of(ctx, *cached) || cached.is_ancestor_of(ctx, op)))
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
@@ -0,0 +1,422 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Hooks around the pass runs, like the upstream `PassInstrumentation`, with the `-mlir-timing` and
+//! the `-mlir-print-ir-*` ones, and the crash reproducers of the failed pipelines.
+//!
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+use crate::{print, Context, Operation, Pass, PassError, PrinterOptions};
+
+/// Hooks around the pass runs, called from the worker threads of the multithreaded [crate::PassManager]
+/// with their own contexts.
+pub trait PassInstrumentation: Send {
+    fn run_before_pass(&mut self, _ctx: &Context, _pass: &dyn Pass, _op: Operation) {}
+
+    fn run_after_pass(&mut self, _ctx: &Context, _pass: &dyn Pass, _op: Operation) {}
+
+    /// Called instead of [PassInstrumentation::run_after_pass], the IR may be left invalid by the crashed passes.
+    fn run_after_pass_failed(&mut self, _ctx: &Context, _pass: &dyn Pass, _op: Operation, _error: &PassError) {}
+}
+
+/// Instrumentations shared by the worker threads.
+#[derive(Default)]
+pub(crate) struct Instrumentor {
+    instrumentations: Mutex<Vec<Box<dyn PassInstrumentation>>>,
+}
+
+impl Instrumentor {
+    pub fn add(&mut self, instrumentation: Box<dyn PassInstrumentation>) {
+        self.instrumentations
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner())
+            .push(instrumentation);
+    }
+
+    fn each(&self, mut hook: impl FnMut(&mut dyn PassInstrumentation)) {
+        let mut instrumentations = self.instrumentations.lock().unwrap_or_else(|error| error.into_inner());
+        for instrumentation in instrumentations.iter_mut() {
+            hook(instrumentation.as_mut());
+        }
+    }
+
+    pub fn run_before_pass(&self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+        self.each(|instrumentation| instrumentation.run_before_pass(ctx, pass, op));
+    }
+
+    /// Called in the reverse order, like the upstream `PassInstrumentor`.
+    pub fn run_after_pass(&self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+        let mut instrumentations = self.instrumentations.lock().unwrap_or_else(|error| error.into_inner());
+        for instrumentation in instrumentations.iter_mut().rev() {
+            instrumentation.run_after_pass(ctx, pass, op);
+        }
+    }
+
+    pub fn run_after_pass_failed(&self, ctx: &Context, pass: &dyn Pass, op: Operation, error: &PassError) {
+        let mut instrumentations = self.instrumentations.lock().unwrap_or_else(|error| error.into_inner());
+        for instrumentation in instrumentations.iter_mut().rev() {
+            instrumentation.run_after_pass_failed(ctx, pass, op, error);
+        }
+    }
+}
+
+/// Wall time of a pass run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassTime {
+    pub name: String,
+    pub argument: String,
+    pub duration: Duration,
+}
+
+/// Times the pass runs, like `-mlir-timing`, the clones share the recorded times.
+#[derive(Debug, Clone, Default)]
+pub struct PassTiming {
+    times: Arc<Mutex<Vec<PassTime>>>,
+    started: HashMap<ThreadId, Instant>,
+}
+
+impl PassTiming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded pass runs, in the order they finished.
+    pub fn times(&self) -> Vec<PassTime> {
+        self.times.lock().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+
+    /// Execution time report of the passes, summed over their runs, like the upstream `-mlir-timing` one.
+    pub fn report(&self) -> String {
+        let mut passes: Vec<(String, Duration)> = vec![];
+        for time in self.times() {
+            let name = format!("{} ({})", time.name, time.argument);
+            match passes.iter_mut().find(|(pass, _)| *pass == name) {
+                Some((_, duration)) => *duration += time.duration,
+                None => passes.push((name, time.duration)),
+            }
+        }
+        let total: Duration = passes.iter().map(|(_, duration)| *duration).sum();
+        let percent = |duration: Duration| match total.is_zero() {
+            true => 100.0,
+            false => duration.as_secs_f64() * 100.0 / total.as_secs_f64(),
+        };
+        let rule = format!("===-{}-===\n", "-".repeat(72));
+        let mut report = rule.clone();
+        report.push_str(&format!("{:^78}\n", "... Execution time report ..."));
+        report.push_str(&rule);
+        let _ = writeln!(report, "  Total Execution Time: {:.4} seconds\n", total.as_secs_f64());
+        report.push_str("  ----Wall Time----  ----Name----\n");
+        for (name, duration) in passes {
+            let _ = writeln!(
+                report,
+                "  {:>8.4} ({:>5.1}%)  {name}",
+                duration.as_secs_f64(),
+                percent(duration)
+            );
+        }
+        let _ = writeln!(report, "  {:>8.4} ({:>5.1}%)  Total", total.as_secs_f64(), 100.0);
+        report
+    }
+
+    fn finish(&mut self, pass: &dyn Pass) {
+        if let Some(started) = self.started.remove(&std::thread::current().id()) {
+            self.times
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .push(PassTime {
+                    name: pass.name().to_string(),
+                    argument: pass.argument().to_string(),
+                    duration: started.elapsed(),
+                });
+        }
+    }
+}
+
+impl PassInstrumentation for PassTiming {
+    fn run_before_pass(&mut self, _ctx: &Context, _pass: &dyn Pass, _op: Operation) {
+        self.started.insert(std::thread::current().id(), Instant::now());
+    }
+
+    fn run_after_pass(&mut self, _ctx: &Context, pass: &dyn Pass, _op: Operation) {
+        self.finish(pass);
+    }
+
+    fn run_after_pass_failed(&mut self, _ctx: &Context, pass: &dyn Pass, _op: Operation, _error: &PassError) {
+        self.finish(pass);
+    }
+}
+
+/// Passes to dump the IR around, like the `-mlir-print-ir-*` flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IrPrintingConfig {
+    /// `-mlir-print-ir-before-all`
+    pub before_all: bool,
+    /// `-mlir-print-ir-after-all`
+    pub after_all: bool,
+    /// `-mlir-print-ir-after-failure`
+    pub after_failure: bool,
+    /// Pass arguments of `-mlir-print-ir-before`.
+    pub before: Vec<String>,
+    /// Pass arguments of `-mlir-print-ir-after`.
+    pub after: Vec<String>,
+    pub printer: PrinterOptions,
+}
+
+/// Dumps the operation the pass runs on, with the upstream `// -----// IR Dump After ...` headers.
+pub struct IrPrinting {
+    config: IrPrintingConfig,
+    sink: Box<dyn FnMut(&str) + Send>,
+}
+
+impl IrPrinting {
+    pub fn new(config: IrPrintingConfig, sink: impl FnMut(&str) + Send + 'static) -> Self {
+        Self {
+            config,
+            sink: Box::new(sink),
+        }
+    }
+
+    fn dump(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation, when: &str) {
+        let target = match op.attribute(ctx, "sym_name").and_then(|name| name.as_string(ctx)) {
+            Some(symbol) => format!("('{}' operation: @{symbol})", op.name(ctx)),
+            None => format!("('{}' operation)", op.name(ctx)),
+        };
+        let dump = format!(
+            "// -----// IR Dump {when} {} ({}) {target} //----- //\n{}\n",
+            pass.name(),
+            pass.argument(),
+            print(ctx, op, self.config.printer)
+        );
+        (self.sink)(&dump);
+    }
+}
+
+impl PassInstrumentation for IrPrinting {
+    fn run_before_pass(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+        if self.config.before_all || self.config.before.iter().any(|argument| argument == pass.argument()) {
+            self.dump(ctx, pass, op, "Before");
+        }
+    }
+
+    fn run_after_pass(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+        if self.config.after_all || self.config.after.iter().any(|argument| argument == pass.argument()) {
+            self.dump(ctx, pass, op, "After");
+        }
+    }
+
+    fn run_after_pass_failed(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation, _error: &PassError) {
+        if self.config.after_failure {
+            self.dump(ctx, pass, op, "After Failed");
+        }
+    }
+}
+
+/// Configuration of the pipeline reproducing the failure, stored in the `mlir_reproducer` external resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReproducerConfig {
+    pub pipeline: String,
+    pub disable_threading: bool,
+    pub verify_each: bool,
+}
+
+impl ReproducerConfig {
+    /// Appends the `{-# external_resources: { mlir_reproducer: ... } #-}` file metadata to the IR.
+    pub fn write(&self, ir: &str) -> String {
+        let mut reproducer = ir.trim_end().to_string();
+        let _ = write!(
+            reproducer,
+            "\n\n{{-#\n  external_resources: {{\n    mlir_reproducer: {{\n      pipeline: {},\n      \
+             disable_threading: {},\n      verify_each: {}\n    }}\n  }}\n#-}}\n",
+            quote(&self.pipeline),
+            self.disable_threading,
+            self.verify_each
+        );
+        reproducer
+    }
+
+    /// Reads the configuration from the file metadata of the reproducer.
+    pub fn read(source: &str) -> Option<Self> {
+        let metadata = &source[source.find("{-#")?..];
+        let resource = &metadata[metadata.find("mlir_reproducer")?..metadata.find("#-}")?];
+        let flag = |key: &str| {
+            let value = resource[resource.find(&format!("{key}:"))? + key.len() + 1..].trim_start();
+            Some(value.starts_with("true"))
+        };
+        Some(Self {
+            pipeline: unquote(resource[resource.find("pipeline:")? + "pipeline:".len()..].trim_start())?,
+            disable_threading: flag("disable_threading").unwrap_or(false),
+            verify_each: flag("verify_each").unwrap_or(false),
+        })
+    }
+}
+
+/// Quotes the pipeline, escaping the quotes and the backslashes.
+fn quote(pipeline: &str) -> String {
+    let mut quoted = String::with_capacity(pipeline.len() + 2);
+    quoted.push('"');
+    for c in pipeline.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads the leading quoted string, undoing the escapes.
+fn unquote(text: &str) -> Option<String> {
+    let mut chars = text.strip_prefix('"')?.chars();
+    let mut unquoted = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(unquoted),
+            '\\' => unquoted.push(chars.next()?),
+            c => unquoted.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::tests::{registry, SOURCE};
+    use crate::{parse, PassManager};
+
+    /// Records the hooks it's called with, tagged by its name.
+    struct Recorder(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl Recorder {
+        fn record(&self, hook: &str, ctx: &Context, pass: &dyn Pass, op: Operation) {
+            let target = op
+                .attribute(ctx, "sym_name")
+                .and_then(|name| name.as_string(ctx))
+                .unwrap_or(op.name(ctx));
+            let event = format!("{} {hook} {} on {target}", self.0, pass.argument());
+            self.1.lock().unwrap().push(event);
+        }
+    }
+
+    impl PassInstrumentation for Recorder {
+        fn run_before_pass(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+            self.record("before", ctx, pass, op);
+        }
+
+        fn run_after_pass(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation) {
+            self.record("after", ctx, pass, op);
+        }
+
+        fn run_after_pass_failed(&mut self, ctx: &Context, pass: &dyn Pass, op: Operation, _error: &PassError) {
+            self.record("failed", ctx, pass, op);
+        }
+    }
+
+    #[test]
+    fn should_call_the_hooks_in_order() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let mut pm =
+            PassManager::parse("builtin.module(test-mark{label=m},func.func(test-fail))", &registry()).unwrap();
+        pm.add_instrumentation(Recorder("a", events.clone()))
+            .add_instrumentation(Recorder("b", events.clone()));
+        pm.run(&mut ctx, module).unwrap_err();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "a before test-mark on builtin.module",
+                "b before test-mark on builtin.module",
+                "b after test-mark on builtin.module",
+                "a after test-mark on builtin.module",
+                "a before test-fail on first",
+                "b before test-fail on first",
+                "b after test-fail on first",
+                "a after test-fail on first",
+                "a before test-fail on second",
+                "b before test-fail on second",
+                "b failed test-fail on second",
+                "a failed test-fail on second",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_dump_the_failed_operation() {
+        let dumps = Arc::new(Mutex::new(String::new()));
+        let sink = dumps.clone();
+        let config = IrPrintingConfig {
+            after_failure: true,
+            after: vec!["test-mark".to_string()],
+            ..IrPrintingConfig::default()
+        };
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let mut pm = PassManager::parse("builtin.module(func.func(test-fail),test-mark)", &registry()).unwrap();
+        pm.add_instrumentation(IrPrinting::new(config, move |dump| sink.lock().unwrap().push_str(dump)));
+        pm.run(&mut ctx, module).unwrap_err();
+        assert_eq!(
+            *dumps.lock().unwrap(),
+            r#"// -----// IR Dump After Failed test-fail (test-fail) ('func.func' operation: @second) //----- //
+"func.func"() ({
+  "func.return"() : () -> ()
+}) {sym_name = "second"} : () -> ()
+
+"#
+        );
+    }
+
+    #[test]
+    fn should_sum_the_pass_times() {
+        let timing = PassTiming::new();
+        for (name, seconds) in [("A", 1), ("B", 1), ("A", 2)] {
+            timing.times.lock().unwrap().push(PassTime {
+                name: name.to_string(),
+                argument: name.to_lowercase(),
+                duration: Duration::from_secs(seconds),
+            });
+        }
+        let report = timing.report();
+        assert!(report.contains("  Total Execution Time: 4.0000 seconds\n"), "{report}");
+        assert!(
+            report.ends_with("    3.0000 ( 75.0%)  A (a)\n    1.0000 ( 25.0%)  B (b)\n    4.0000 (100.0%)  Total\n")
+        );
+        assert_eq!(timing.clone().times().len(), 3, "the clones share the times");
+    }
+
+    #[test]
+    fn should_quote_the_reproducer_pipeline() {
+        let config = ReproducerConfig {
+            pipeline: r#"builtin.module(test-mark{label="a \ b"})"#.to_string(),
+            disable_threading: false,
+            verify_each: true,
+        };
+        let reproducer = config.write("\"builtin.module\"() ({\n}) : () -> ()\n");
+        assert!(
+            reproducer.contains(r#"pipeline: "builtin.module(test-mark{label=\"a \\ b\"})","#),
+            "{reproducer}"
+        );
+        assert_eq!(ReproducerConfig::read(&reproducer), Some(config));
+        assert_eq!(ReproducerConfig::read("\"builtin.module\"() ({\n}) : () -> ()\n"), None);
+        assert_eq!(unquote(r#""unterminated \"#), None);
+    }
+}
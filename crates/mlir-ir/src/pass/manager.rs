@@ -0,0 +1,672 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Pass managers nested on the operation names, like the upstream `OpPassManager` and `OpToOpPassAdaptor`.
+//!
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::passes::split_pass_options;
+
+use crate::pass::instrumentation::Instrumentor;
+use crate::pass::pipeline::{parse_elements, Element};
+use crate::{
+    print, read_bytecode, verify, write_bytecode, AnalysisManager, BytecodeError, Context, DialectRegistry, Location,
+    Operation, Pass, PassError, PassInstrumentation, PassRegistry, PreservedAnalyses, PrinterOptions,
+    ReproducerConfig,
+};
+
+/// Bytecode of the transformed operation with the analyses its pipeline preserved or its failure, unless
+/// the operation couldn't be moved to the worker and back.
+type WorkerOutput = Result<(Vec<u8>, Result<PreservedAnalyses, PassError>), BytecodeError>;
+
+/// Receiver of the crash reproducers.
+type Sink = Box<dyn FnMut(&str) + Send>;
+
+/// Passes and pipelines run on the operations of the anchor name, or on any for the op-agnostic ones.
+pub struct OpPassManager {
+    anchor: Option<String>,
+    entries: Vec<Entry>,
+}
+
+enum Entry {
+    Pass(Box<dyn Pass>),
+    Nested(OpPassManager),
+}
+
+impl Clone for OpPassManager {
+    fn clone(&self) -> Self {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Pass(pass) => Entry::Pass(pass.clone_pass()),
+                Entry::Nested(nested) => Entry::Nested(nested.clone()),
+            })
+            .collect();
+        Self {
+            anchor: self.anchor.clone(),
+            entries,
+        }
+    }
+}
+
+impl OpPassManager {
+    /// Pass manager anchored on the operation name, like `func.func`.
+    pub fn new(anchor: impl Into<String>) -> Self {
+        Self {
+            anchor: Some(anchor.into()),
+            entries: vec![],
+        }
+    }
+
+    /// Op-agnostic pass manager, the `any` anchor of the textual pipeline.
+    pub fn any() -> Self {
+        Self {
+            anchor: None,
+            entries: vec![],
+        }
+    }
+
+    /// Parses the anchored textual pipeline, like `builtin.module(func.func(cse))`.
+    pub fn parse(pipeline: &str, registry: &PassRegistry) -> Result<Self, PassError> {
+        match parse_elements(pipeline)?.as_slice() {
+            [Element::Nested { name, elements }] => {
+                let mut pm = match *name {
+                    "any" => Self::any(),
+                    name => Self::new(name),
+                };
+                pm.add_elements(elements, registry)?;
+                Ok(pm)
+            }
+            _ => Err(PassError::Pipeline {
+                column: 1,
+                message: "expected the pipeline anchored on an operation name, like `builtin.module(...)`".to_string(),
+            }),
+        }
+    }
+
+    /// Anchor operation name, `None` for the op-agnostic pass manager.
+    pub fn anchor(&self) -> Option<&str> {
+        self.anchor.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn add_pass(&mut self, pass: Box<dyn Pass>) -> &mut Self {
+        self.entries.push(Entry::Pass(pass));
+        self
+    }
+
+    /// Pipeline run on the operations of the name within the regions, reusing the last one if it's nested
+    /// on the same name.
+    pub fn nest(&mut self, anchor: &str) -> &mut OpPassManager {
+        self.nest_on(Some(anchor))
+    }
+
+    /// Op-agnostic pipeline run on all the operations within the regions.
+    pub fn nest_any(&mut self) -> &mut OpPassManager {
+        self.nest_on(None)
+    }
+
+    fn nest_on(&mut self, anchor: Option<&str>) -> &mut OpPassManager {
+        let reusable = matches!(self.entries.last(), Some(Entry::Nested(nested)) if nested.anchor() == anchor);
+        if !reusable {
+            self.entries.push(Entry::Nested(OpPassManager {
+                anchor: anchor.map(str::to_string),
+                entries: vec![],
+            }));
+        }
+        match self.entries.last_mut() {
+            Some(Entry::Nested(nested)) => nested,
+            _ => unreachable!("the nested pass manager was just added"),
+        }
+    }
+
+    /// Appends the comma separated pipeline elements, like `func.func(cse),canonicalize`.
+    pub fn add_pipeline(&mut self, pipeline: &str, registry: &PassRegistry) -> Result<&mut Self, PassError> {
+        self.add_elements(&parse_elements(pipeline)?, registry)?;
+        Ok(self)
+    }
+
+    fn add_elements(&mut self, elements: &[Element], registry: &PassRegistry) -> Result<(), PassError> {
+        for element in elements {
+            match element {
+                Element::Nested { name: "any", elements } => self.nest_any().add_elements(elements, registry)?,
+                Element::Nested { name, elements } => self.nest(name).add_elements(elements, registry)?,
+                Element::Pass { text, column } => {
+                    self.add_pass_element(text, registry)
+                        .map_err(|error| PassError::Pipeline {
+                            column: *column,
+                            message: error.to_string(),
+                        })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_pass_element(&mut self, text: &str, registry: &PassRegistry) -> Result<(), PassError> {
+        let (argument, _) = split_pass_options(text)?;
+        match registry.get(argument).and_then(|registered| registered.pipeline()) {
+            Some(pipeline) => self.add_elements(&parse_elements(pipeline)?, registry),
+            None => {
+                self.add_pass(registry.build(text)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        analyses: &mut AnalysisManager,
+        runner: &Runner,
+    ) -> Result<PreservedAnalyses, PassError> {
+        let mut preserved = PreservedAnalyses::all();
+        for entry in &mut self.entries {
+            let entry_preserved = match entry {
+                Entry::Pass(pass) => run_pass(pass.as_mut(), ctx, op, analyses, runner)?,
+                Entry::Nested(nested) => nested.run_nested(ctx, op, analyses, runner)?,
+            };
+            preserved.intersect(&entry_preserved);
+        }
+        Ok(preserved)
+    }
+
+    /// Runs on the matching operations directly within the regions of the parent one, on the worker threads
+    /// if they are all isolated from above and without operands.
+    fn run_nested(
+        &mut self,
+        ctx: &mut Context,
+        parent: Operation,
+        analyses: &mut AnalysisManager,
+        runner: &Runner,
+    ) -> Result<PreservedAnalyses, PassError> {
+        let mut targets = vec![];
+        for region in parent.regions(ctx) {
+            for block in region.blocks(ctx) {
+                for op in block.operations(ctx) {
+                    if self.anchor.as_deref().is_none_or(|anchor| anchor == op.name(ctx)) {
+                        targets.push(*op);
+                    }
+                }
+            }
+        }
+        let parallel = runner.threads > 1
+            && targets.len() > 1
+            && targets
+                .iter()
+                .all(|op| op.operands(ctx).is_empty() && op.is_isolated_from_above(ctx));
+        let moved = match parallel {
+            true => self.run_on_workers(ctx, &targets, runner),
+            false => None,
+        };
+        let mut preserved = PreservedAnalyses::all();
+        match moved {
+            Some(moved) => preserved = moved?,
+            None => {
+                for op in targets {
+                    preserved.intersect(&self.run(ctx, op, analyses, runner)?);
+                }
+            }
+        }
+        analyses.invalidate(ctx, parent, &preserved);
+        Ok(preserved)
+    }
+
+    /// Moves every operation into the context of a worker thread and back through the bytecode, the operations
+    /// are replaced by the transformed ones in their order up to the first failed one, along with the uses of their
+    /// results. The handles of the moved operations and of everything within them are erased.
+    /// Nothing is replaced and `None` is returned if any of them can't be moved, to rerun the pipeline on them
+    /// within the shared context.
+    fn run_on_workers(
+        &self,
+        ctx: &mut Context,
+        targets: &[Operation],
+        runner: &Runner,
+    ) -> Option<Result<PreservedAnalyses, PassError>> {
+        let mut inputs = vec![];
+        for op in targets {
+            inputs.push(write_wrapped(ctx, *op).ok()?);
+        }
+        let next = AtomicUsize::new(0);
+        let outputs: Vec<Mutex<Option<WorkerOutput>>> = targets.iter().map(|_| Mutex::new(None)).collect();
+        let worker = Runner {
+            threads: 1,
+            instrumentor: runner.instrumentor,
+            verify_each: runner.verify_each,
+        };
+        std::thread::scope(|scope| {
+            for mut pm in (0..runner.threads.min(targets.len())).map(|_| self.clone()) {
+                let (dialects, inputs, next, outputs, worker) = (ctx.dialects(), &inputs, &next, &outputs, &worker);
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(index) else {
+                        break;
+                    };
+                    let output = pm.run_isolated(dialects, input, worker);
+                    *outputs[index].lock().unwrap_or_else(|error| error.into_inner()) = Some(output);
+                });
+            }
+        });
+        let mut transformed = vec![];
+        for output in outputs {
+            let output = output.into_inner().unwrap_or_else(|error| error.into_inner());
+            let (data, result) = output.expect("every operation is run by a worker").ok()?;
+            let failed = result.is_err();
+            transformed.push((data, result));
+            if failed {
+                break;
+            }
+        }
+        let mut moved = vec![];
+        for (data, _) in &transformed {
+            match read_wrapped(ctx, data) {
+                Ok(op) => moved.push(op),
+                Err(_) => {
+                    for op in moved {
+                        ctx.erase_operation(op).expect("the moved operations are detached");
+                    }
+                    return None;
+                }
+            }
+        }
+        let mut preserved = Ok(PreservedAnalyses::all());
+        for ((op, moved), (_, result)) in targets.iter().zip(moved).zip(transformed) {
+            let block = op
+                .parent_block(ctx)
+                .expect("the nested operations are within the blocks");
+            ctx.insert_operation(block, Some(*op), moved)
+                .expect("the moved operations are detached");
+            let results: Vec<_> = op.results(ctx).iter().copied().zip(moved.results(ctx).iter().copied()).collect();
+            for (from, to) in results {
+                ctx.replace_all_uses_with(from, to);
+            }
+            ctx.erase_operation(*op)
+                .expect("the uses of the moved operations are replaced");
+            preserved = match (preserved, result) {
+                (Ok(mut preserved), Ok(output_preserved)) => {
+                    preserved.intersect(&output_preserved);
+                    Ok(preserved)
+                }
+                (_, Err(error)) | (Err(error), _) => Err(error),
+            };
+        }
+        Some(preserved)
+    }
+
+    fn run_isolated(&mut self, dialects: &DialectRegistry, data: &[u8], runner: &Runner) -> WorkerOutput {
+        let mut ctx = Context::with_dialects(dialects.clone());
+        let op = read_wrapped(&mut ctx, data)?;
+        let result = self.run(&mut ctx, op, &mut AnalysisManager::new(), runner);
+        Ok((write_wrapped(&mut ctx, op)?, result))
+    }
+}
+
+/// Bytecode of the operation within a `builtin.module`, the top level operation of the bytecode can't have results.
+/// The operation is moved into the module for the writing and back into its block, if any.
+fn write_wrapped(ctx: &mut Context, op: Operation) -> Result<Vec<u8>, BytecodeError> {
+    let position = op.parent_block(ctx).map(|block| {
+        let operations = block.operations(ctx);
+        let index = operations.iter().position(|candidate| *candidate == op);
+        (block, index.and_then(|index| operations.get(index + 1)).copied())
+    });
+    let module = ctx.create_module();
+    let body = module.region(ctx, 0).blocks(ctx)[0];
+    ctx.remove_operation(op);
+    ctx.insert_operation(body, None, op)
+        .expect("the removed operation is detached");
+    let data = write_bytecode(ctx, module);
+    ctx.remove_operation(op);
+    if let Some((block, next)) = position {
+        ctx.insert_operation(block, next, op)
+            .expect("the removed operation is detached");
+    }
+    ctx.erase_operation(module).expect("the module is left empty");
+    data
+}
+
+/// Reads the operation written by [write_wrapped], detached from the module.
+fn read_wrapped(ctx: &mut Context, data: &[u8]) -> Result<Operation, BytecodeError> {
+    let module = read_bytecode(ctx, data)?;
+    let body = module.region(ctx, 0).blocks(ctx)[0];
+    let op = body.operations(ctx)[0];
+    ctx.remove_operation(op);
+    ctx.erase_operation(module).expect("the module is left empty");
+    Ok(op)
+}
+
+/// Prints the upstream textual pipeline, like `builtin.module(func.func(canonicalize,cse))`.
+impl fmt::Display for OpPassManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.anchor.as_deref().unwrap_or("any"))?;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            match entry {
+                Entry::Pass(pass) => f.write_str(&pass.print_pipeline())?,
+                Entry::Nested(nested) => write!(f, "{nested}")?,
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+/// Execution state shared by the nested pass managers.
+struct Runner<'i> {
+    threads: usize,
+    instrumentor: &'i Instrumentor,
+    verify_each: bool,
+}
+
+fn run_pass(
+    pass: &mut dyn Pass,
+    ctx: &mut Context,
+    op: Operation,
+    analyses: &mut AnalysisManager,
+    runner: &Runner,
+) -> Result<PreservedAnalyses, PassError> {
+    let name = op.name(ctx).to_string();
+    if let Some(anchor) = pass.operation_name().filter(|anchor| *anchor != name) {
+        return Err(PassError::PassAnchor {
+            pass: pass.argument().to_string(),
+            anchor: anchor.to_string(),
+            op: name,
+        });
+    }
+    runner.instrumentor.run_before_pass(ctx, pass, op);
+    let error = match panic::catch_unwind(AssertUnwindSafe(|| pass.run(ctx, op, analyses))) {
+        Ok(Ok(preserved)) => {
+            let verified = match runner.verify_each {
+                true => verify(ctx, op),
+                false => Ok(()),
+            };
+            match verified {
+                Ok(()) => {
+                    analyses.invalidate(ctx, op, &preserved);
+                    runner.instrumentor.run_after_pass(ctx, pass, op);
+                    return Ok(preserved);
+                }
+                Err(error) => PassError::Verification {
+                    pass: pass.argument().to_string(),
+                    error,
+                },
+            }
+        }
+        Ok(Err(failure)) => PassError::Failed {
+            pass: pass.argument().to_string(),
+            op: name,
+            location: failure.location.unwrap_or_else(|| Location::new(ctx, op.location(ctx))),
+            message: failure.message,
+        },
+        Err(payload) => PassError::Crashed {
+            pass: pass.argument().to_string(),
+            op: name,
+            location: Location::new(ctx, op.location(ctx)),
+            message: payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic payload".to_string()),
+        },
+    };
+    analyses.clear();
+    runner.instrumentor.run_after_pass_failed(ctx, pass, op, &error);
+    Err(error)
+}
+
+/// Top level [OpPassManager] with the instrumentations, the threading and the crash reproducer.
+pub struct PassManager {
+    root: OpPassManager,
+    instrumentor: Instrumentor,
+    threads: usize,
+    reproducer: Option<Sink>,
+    verify_each: bool,
+}
+
+impl PassManager {
+    /// Pass manager anchored on the operation name, like `builtin.module`.
+    pub fn new(anchor: impl Into<String>) -> Self {
+        Self::from(OpPassManager::new(anchor))
+    }
+
+    /// Parses the anchored textual pipeline, like `builtin.module(func.func(cse))`.
+    pub fn parse(pipeline: &str, registry: &PassRegistry) -> Result<Self, PassError> {
+        OpPassManager::parse(pipeline, registry).map(Self::from)
+    }
+
+    /// Runs the nested pipelines on the worker threads, like `-mlir-disable-threading=false`.
+    ///
+    /// The [Context] can't be shared across the threads, so the isolated from above operations without operands
+    /// the nested pipelines run on are moved to the contexts of the workers and back: **their [Operation] handles,
+    /// and the handles of the operations, blocks, regions and values within them, are erased** and the uses of
+    /// their results are replaced. The handles held across [PassManager::run] are to be looked up again
+    /// afterwards, like through a [crate::SymbolTable]. The operations that can't be moved this way are run on
+    /// sequentially in the shared context.
+    pub fn enable_multithreading(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Verifies the IR after every pass, like `-verify-each`, enabled by default in the debug builds.
+    pub fn enable_verifier(&mut self, enabled: bool) -> &mut Self {
+        self.verify_each = enabled;
+        self
+    }
+
+    pub fn add_instrumentation(&mut self, instrumentation: impl PassInstrumentation + 'static) -> &mut Self {
+        self.instrumentor.add(Box::new(instrumentation));
+        self
+    }
+
+    /// Passes the IR and the pipeline reproducing the failure to the sink, like `-mlir-pass-pipeline-crash-reproducer`.
+    pub fn enable_crash_reproducer(&mut self, sink: impl FnMut(&str) + Send + 'static) -> &mut Self {
+        self.reproducer = Some(Box::new(sink));
+        self
+    }
+
+    pub fn run(&mut self, ctx: &mut Context, op: Operation) -> Result<(), PassError> {
+        if let Some(anchor) = self.root.anchor().filter(|anchor| *anchor != op.name(ctx)) {
+            return Err(PassError::PipelineAnchor {
+                anchor: anchor.to_string(),
+                op: op.name(ctx).to_string(),
+            });
+        }
+        let printer = PrinterOptions {
+            debug_info: true,
+            ..PrinterOptions::default()
+        };
+        let snapshot = self.reproducer.as_ref().map(|_| print(ctx, op, printer));
+        let runner = Runner {
+            threads: self.threads,
+            instrumentor: &self.instrumentor,
+            verify_each: self.verify_each,
+        };
+        let result = self.root.run(ctx, op, &mut AnalysisManager::new(), &runner);
+        if let (Err(_), Some(snapshot), Some(reproducer)) = (&result, snapshot, &mut self.reproducer) {
+            let config = ReproducerConfig {
+                pipeline: self.root.to_string(),
+                disable_threading: self.threads == 1,
+                verify_each: self.verify_each,
+            };
+            reproducer(&config.write(&snapshot));
+        }
+        result.map(|_| ())
+    }
+}
+
+impl From<OpPassManager> for PassManager {
+    fn from(root: OpPassManager) -> Self {
+        Self {
+            root,
+            instrumentor: Instrumentor::default(),
+            threads: 1,
+            reproducer: None,
+            verify_each: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl Deref for PassManager {
+    type Target = OpPassManager;
+
+    fn deref(&self) -> &OpPassManager {
+        &self.root
+    }
+}
+
+impl DerefMut for PassManager {
+    fn deref_mut(&mut self) -> &mut OpPassManager {
+        &mut self.root
+    }
+}
+
+impl fmt::Display for PassManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::tests::{marks, registry, Fail, Mark, MarkOptions, SOURCE};
+    use crate::{parse, PassFailure};
+
+    fn mark(label: &str) -> Box<dyn Pass> {
+        Box::new(Mark(MarkOptions {
+            label: label.to_string(),
+        }))
+    }
+
+    /// Renames every function of the module after the first one.
+    #[derive(Clone)]
+    struct Rename;
+
+    impl Pass for Rename {
+        fn argument(&self) -> &str {
+            "test-rename"
+        }
+
+        fn run(
+            &mut self,
+            ctx: &mut Context,
+            op: Operation,
+            _analyses: &mut AnalysisManager,
+        ) -> Result<PreservedAnalyses, PassFailure> {
+            let name = ctx.string_attr("first");
+            for nested in op.walk(ctx) {
+                if nested.name(ctx) == "func.func" {
+                    ctx.set_attribute(nested, "sym_name", name);
+                }
+            }
+            Ok(PreservedAnalyses::none())
+        }
+    }
+
+    #[test]
+    fn should_nest_on_the_anchors() {
+        let mut pm = OpPassManager::new("builtin.module");
+        pm.nest("func.func").add_pass(mark("a"));
+        pm.nest("func.func").add_pass(mark("b"));
+        pm.nest_any().add_pass(mark("c"));
+        pm.nest("func.func").add_pass(mark("d"));
+        assert_eq!(
+            pm.to_string(),
+            "builtin.module(func.func(test-mark{label=a},test-mark{label=b}),any(test-mark{label=c}),\
+             func.func(test-mark{label=d}))"
+        );
+        assert_eq!(pm.anchor(), Some("builtin.module"));
+        assert_eq!(OpPassManager::any().anchor(), None);
+        assert!(OpPassManager::any().is_empty() && !pm.is_empty());
+    }
+
+    #[test]
+    fn should_run_on_the_operations_directly_within_the_regions() {
+        let source = r#""builtin.module"() ({
+  "func.func"() ({
+    "func.return"() : () -> ()
+  }) {sym_name = "outer"} : () -> ()
+  "test.wrapper"() ({
+    "func.func"() ({
+      "func.return"() : () -> ()
+    }) {sym_name = "inner"} : () -> ()
+  }) : () -> ()
+}) : () -> ()
+"#;
+        let registry = registry();
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, source).unwrap();
+        let mut pm = PassManager::parse("builtin.module(func.func(test-mark{label=f}))", &registry).unwrap();
+        pm.nest("test.wrapper")
+            .nest("func.func")
+            .add_pipeline("test-mark{label=w}", &registry)
+            .unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        let mark = |marks: &str| Some(marks.to_string());
+        assert_eq!(marks(&ctx, module), vec![None, mark("f"), None, None, mark("w"), None]);
+    }
+
+    #[test]
+    fn should_stop_at_the_first_failure() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let second = module.walk(&ctx)[4];
+        let mut pm = PassManager::new("builtin.module");
+        pm.nest("func.func").add_pass(Box::new(Fail)).add_pass(mark("a"));
+        pm.add_pass(mark("m"));
+        let error = pm.run(&mut ctx, module).unwrap_err();
+        assert!(
+            matches!(&error, PassError::Failed { pass, op, location, .. }
+                if pass == "test-fail" && op == "func.func" && *location == Location::new(&ctx, second.location(&ctx))),
+            "{error:?}"
+        );
+        assert_eq!(
+            marks(&ctx, module),
+            vec![None, Some("a".to_string()), None, None, None, None],
+            "the passes after the failed one aren't run"
+        );
+    }
+
+    #[test]
+    fn should_verify_after_each_pass() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let mut pm = PassManager::new("builtin.module");
+        pm.enable_verifier(true).add_pass(Box::new(Rename));
+        let error = pm.run(&mut ctx, module).unwrap_err();
+        assert!(
+            matches!(&error, PassError::Verification { pass, error }
+                if pass == "test-rename" && error.message == "redefinition of symbol named 'first'"),
+            "{error:?}"
+        );
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        pm.enable_verifier(false);
+        pm.run(&mut ctx, module).unwrap();
+    }
+}
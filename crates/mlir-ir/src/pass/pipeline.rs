@@ -0,0 +1,197 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Textual pass pipeline syntax, like `builtin.module(func.func(canonicalize{max-iterations=10},cse))`.
+//!
+
+use crate::PassError;
+
+/// Element of the textual pipeline, before the passes are built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Element<'a> {
+    /// Pass or registered pipeline with its options, like `canonicalize{max-iterations=10}`.
+    Pass { text: &'a str, column: usize },
+    /// Pipeline nested on the operation name, like `func.func(cse)`.
+    Nested { name: &'a str, elements: Vec<Element<'a>> },
+}
+
+/// Parses the comma separated pipeline elements.
+pub(crate) fn parse_elements(text: &str) -> Result<Vec<Element<'_>>, PassError> {
+    let mut parser = PipelineParser { text, position: 0 };
+    let elements = parser.parse_elements()?;
+    match parser.peek() {
+        None => Ok(elements),
+        Some(_) => Err(parser.error("expected `,` or the end of the pipeline")),
+    }
+}
+
+struct PipelineParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> PipelineParser<'a> {
+    fn error(&self, message: impl Into<String>) -> PassError {
+        PassError::Pipeline {
+            column: self.text[..self.position].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+        self.rest().chars().next()
+    }
+
+    fn parse_elements(&mut self) -> Result<Vec<Element<'a>>, PassError> {
+        let mut elements = vec![];
+        if matches!(self.peek(), None | Some(')')) {
+            return Ok(elements);
+        }
+        loop {
+            elements.push(self.parse_element()?);
+            match self.peek() {
+                Some(',') => self.position += 1,
+                _ => return Ok(elements),
+            }
+        }
+    }
+
+    fn parse_element(&mut self) -> Result<Element<'a>, PassError> {
+        self.peek();
+        let start = self.position;
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || "(){},".contains(c))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected a pass, a pass pipeline or an operation name"));
+        }
+        let name = &rest[..end];
+        self.position += end;
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let elements = self.parse_elements()?;
+                match self.peek() {
+                    Some(')') => self.position += 1,
+                    _ => return Err(self.error(format!("expected `)` closing the `{name}` pipeline"))),
+                }
+                Ok(Element::Nested { name, elements })
+            }
+            Some('{') => {
+                self.skip_options()?;
+                Ok(Element::Pass {
+                    text: &self.text[start..self.position],
+                    column: self.text[..start].chars().count() + 1,
+                })
+            }
+            _ => Ok(Element::Pass {
+                text: name,
+                column: self.text[..start].chars().count() + 1,
+            }),
+        }
+    }
+
+    /// Skips the braced options, with the nested braces and the quoted values.
+    fn skip_options(&mut self) -> Result<(), PassError> {
+        let mut depth = 0usize;
+        let mut quote = None;
+        for (index, c) in self.rest().char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, '}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.position += index + 1;
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("expected `}` closing the pass options"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_pipeline_elements() {
+        let elements = parse_elements(
+            "builtin.module(func.func(canonicalize{max-iterations=10 region-simplify='a,b'}, cse), inline)",
+        )
+        .unwrap();
+        assert_eq!(
+            elements,
+            vec![Element::Nested {
+                name: "builtin.module",
+                elements: vec![
+                    Element::Nested {
+                        name: "func.func",
+                        elements: vec![
+                            Element::Pass {
+                                text: "canonicalize{max-iterations=10 region-simplify='a,b'}",
+                                column: 26
+                            },
+                            Element::Pass {
+                                text: "cse",
+                                column: 81
+                            },
+                        ]
+                    },
+                    Element::Pass {
+                        text: "inline",
+                        column: 87
+                    },
+                ]
+            }]
+        );
+        assert_eq!(
+            parse_elements(" func.func() ").unwrap(),
+            vec![Element::Nested {
+                name: "func.func",
+                elements: vec![]
+            }]
+        );
+    }
+
+    #[test]
+    fn should_reject_malformed_pipelines() {
+        for (pipeline, column) in [
+            ("builtin.module(cse", 19),
+            ("builtin.module(cse))", 20),
+            ("cse,,canonicalize", 5),
+            ("canonicalize{max-iterations=10", 13),
+        ] {
+            match parse_elements(pipeline) {
+                Err(PassError::Pipeline { column: actual, .. }) => assert_eq!(actual, column, "{pipeline}"),
+                other => panic!("{pipeline}: {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Passes and pass pipelines by their textual pipeline arguments, like the upstream `PassRegistration`
+//! and `PassPipelineRegistration`.
+//!
+
+use std::collections::BTreeMap;
+
+use crate::passes::{split_pass_options, PassOptionError, PassOptions};
+
+use crate::{Pass, PassError};
+
+type Constructor = Box<dyn Fn(&str) -> Result<Box<dyn Pass>, PassOptionError> + Send + Sync>;
+
+/// Pass or pass pipeline listed in the [PassRegistry].
+pub struct RegisteredPass {
+    /// Argument of the textual pipeline, like `canonicalize`.
+    pub argument: &'static str,
+    pub summary: &'static str,
+    kind: Kind,
+}
+
+enum Kind {
+    Pass(Constructor),
+    Pipeline(&'static str),
+}
+
+impl RegisteredPass {
+    /// Textual pipeline the registered pipeline expands to.
+    pub fn pipeline(&self) -> Option<&'static str> {
+        match self.kind {
+            Kind::Pass(_) => None,
+            Kind::Pipeline(pipeline) => Some(pipeline),
+        }
+    }
+}
+
+/// Registered passes and pipelines by their arguments.
+#[derive(Default)]
+pub struct PassRegistry {
+    passes: BTreeMap<&'static str, RegisteredPass>,
+}
+
+impl PassRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the pass without options.
+    pub fn register<P: Pass + Default + 'static>(&mut self, argument: &'static str, summary: &'static str) {
+        let build = move |text: &str| {
+            let (_, options) = split_pass_options(text)?;
+            match options.first() {
+                Some((option, _)) => Err(PassOptionError::UnknownOption {
+                    pass: argument,
+                    option: option.to_string(),
+                }),
+                None => Ok(Box::new(P::default()) as Box<dyn Pass>),
+            }
+        };
+        self.insert(argument, summary, Kind::Pass(Box::new(build)));
+    }
+
+    /// Registers the pass built from its options, the argument is the one of the options.
+    pub fn register_with_options<O, P>(&mut self, summary: &'static str, build: fn(O) -> P)
+    where
+        O: PassOptions + 'static,
+        P: Pass + 'static,
+    {
+        let build = move |text: &str| Ok(Box::new(build(O::parse_options(text)?)) as Box<dyn Pass>);
+        self.insert(O::ARGUMENT, summary, Kind::Pass(Box::new(build)));
+    }
+
+    /// Registers the textual pipeline under the argument, like `test-pipeline` expanding to `cse,canonicalize`.
+    pub fn register_pipeline(&mut self, argument: &'static str, summary: &'static str, pipeline: &'static str) {
+        self.insert(argument, summary, Kind::Pipeline(pipeline));
+    }
+
+    fn insert(&mut self, argument: &'static str, summary: &'static str, kind: Kind) {
+        self.passes.insert(
+            argument,
+            RegisteredPass {
+                argument,
+                summary,
+                kind,
+            },
+        );
+    }
+
+    pub fn get(&self, argument: &str) -> Option<&RegisteredPass> {
+        self.passes.get(argument)
+    }
+
+    /// Registered passes and pipelines, ordered by their arguments.
+    pub fn passes(&self) -> impl Iterator<Item = &RegisteredPass> + '_ {
+        self.passes.values()
+    }
+
+    /// Builds the pass of the `pass-name{opt=val ...}` pipeline element.
+    pub fn build(&self, text: &str) -> Result<Box<dyn Pass>, PassError> {
+        let (argument, _) = split_pass_options(text)?;
+        match self.get(argument).map(|registered| &registered.kind) {
+            Some(Kind::Pass(build)) => Ok(build(text)?),
+            _ => Err(PassError::UnknownPass(argument.to_string())),
+        }
+    }
+}
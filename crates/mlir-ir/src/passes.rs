@@ -0,0 +1,415 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+
+//! ## fljúga handahófi mlir ir passes
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Runtime of the pass option structs generated out of the `Passes.td` records.
+//! The options are parsed from and printed to the textual pass pipeline element, like `arith-expand{include-bf16=true}`,
+//! and the [PassInfo] of every generated pass is listed in the [PassRegistry] by its argument.
+//!
+
+use std::collections::BTreeMap;
+
+/// Derived [thiserror::Error] for the textual pass pipeline element errors.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum PassOptionError {
+    #[error("expected the `{expected}` pass, found {found:?}")]
+    UnexpectedPass { expected: &'static str, found: String },
+    #[error("`{pass}` pass has no option `{option}`")]
+    UnknownOption { pass: &'static str, option: String },
+    #[error("invalid value {value:?} of the `{pass}` pass option `{option}`: {reason}")]
+    InvalidValue {
+        pass: &'static str,
+        option: String,
+        value: String,
+        reason: String,
+    },
+    #[error("malformed pass pipeline element {0:?}")]
+    Malformed(String),
+}
+
+/// Value of the `Option` and `ListOption` pass options, like the `llvm::cl::parser` of the C++ type.
+pub trait PassOptionValue: Sized {
+    fn parse_value(text: &str) -> Result<Self, String>;
+
+    fn print_value(&self) -> String;
+}
+
+/// The flags given without the value, like `canonicalize{test-convergence}`, are set.
+impl PassOptionValue for bool {
+    fn parse_value(text: &str) -> Result<Self, String> {
+        match text {
+            "" | "true" | "TRUE" | "True" | "1" => Ok(true),
+            "false" | "FALSE" | "False" | "0" => Ok(false),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+
+    fn print_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+macro_rules! number_option_values {
+    ($($number:ty),*) => {
+        $(
+            impl PassOptionValue for $number {
+                fn parse_value(text: &str) -> Result<Self, String> {
+                    text.parse().map_err(|error| format!("{error}"))
+                }
+
+                fn print_value(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+number_option_values!(i8, i16, i32, i64, u8, u16, u32, u64, usize, f32, f64);
+
+/// The quoted and braced values are unwrapped, the printed ones are quoted if they contain the separators.
+impl PassOptionValue for String {
+    fn parse_value(text: &str) -> Result<Self, String> {
+        Ok(unquote(text).to_string())
+    }
+
+    fn print_value(&self) -> String {
+        match self.is_empty() || self.contains(|c: char| c.is_whitespace() || "{}=,\"'".contains(c)) {
+            true => format!("{{{self}}}"),
+            false => self.clone(),
+        }
+    }
+}
+
+/// `ListOption` values are separated by the commas outside the quotes, braces and parentheses.
+impl<T: PassOptionValue> PassOptionValue for Vec<T> {
+    fn parse_value(text: &str) -> Result<Self, String> {
+        let text = unquote(text);
+        match text.trim().is_empty() {
+            true => Ok(vec![]),
+            false => split_top_level(text, ',').map(|value| T::parse_value(value.trim())).collect(),
+        }
+    }
+
+    fn print_value(&self) -> String {
+        self.iter().map(T::print_value).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Options struct of the pass, generated with the `FromStr` and `Display` implementations calling
+/// [PassOptions::parse_options] and [PassOptions::print_options].
+pub trait PassOptions: Default {
+    /// Pass argument of the pipeline, like `arith-expand`.
+    const ARGUMENT: &'static str;
+
+    /// Sets the option by its argument, like `include-bf16`.
+    fn set_option(&mut self, option: &str, value: &str) -> Result<(), PassOptionError>;
+
+    /// Options by their arguments, with the printed values.
+    fn options(&self) -> Vec<(&'static str, String)>;
+
+    /// Parses the `pass-name{opt=val ...}` pipeline element, the options left out keep their defaults.
+    fn parse_options(text: &str) -> Result<Self, PassOptionError> {
+        let (name, options) = split_pass_options(text)?;
+        if name != Self::ARGUMENT {
+            return Err(PassOptionError::UnexpectedPass {
+                expected: Self::ARGUMENT,
+                found: name.to_string(),
+            });
+        }
+        let mut parsed = Self::default();
+        for (option, value) in options {
+            parsed.set_option(option, value)?;
+        }
+        Ok(parsed)
+    }
+
+    /// Prints the pipeline element with all the options, like the upstream `printAsTextualPipeline`.
+    fn print_options(&self) -> String {
+        let options = self.options();
+        if options.is_empty() {
+            return Self::ARGUMENT.to_string();
+        }
+        let options: Vec<String> = options
+            .into_iter()
+            .map(|(option, value)| format!("{option}={value}"))
+            .collect();
+        format!("{}{{{}}}", Self::ARGUMENT, options.join(" "))
+    }
+}
+
+/// Parses the option value for the generated [PassOptions::set_option].
+pub fn parse_option<T: PassOptionValue>(pass: &'static str, option: &str, value: &str) -> Result<T, PassOptionError> {
+    T::parse_value(value).map_err(|reason| PassOptionError::InvalidValue {
+        pass,
+        option: option.to_string(),
+        value: value.to_string(),
+        reason,
+    })
+}
+
+/// The `option=value` pairs of a pipeline element, the value is empty for the bare flags.
+pub type RawPassOptions<'a> = Vec<(&'a str, &'a str)>;
+
+/// Splits the `pass-name{opt=val ...}` pipeline element into the pass name and the options,
+/// the options are separated by the whitespace outside the quotes, braces and parentheses.
+pub fn split_pass_options(text: &str) -> Result<(&str, RawPassOptions<'_>), PassOptionError> {
+    let malformed = || PassOptionError::Malformed(text.to_string());
+    let text = text.trim();
+    let Some(open) = text.find('{') else {
+        return match text.is_empty() || text.contains(|c: char| c.is_whitespace() || "}=,".contains(c)) {
+            true => Err(malformed()),
+            false => Ok((text, vec![])),
+        };
+    };
+    let name = text[..open].trim_end();
+    let body = text[open + 1..].strip_suffix('}').ok_or_else(malformed)?;
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "}=,".contains(c)) || !balanced(body) {
+        return Err(malformed());
+    }
+    let mut options = vec![];
+    for option in split_top_level(body, ' ').map(str::trim).filter(|option| !option.is_empty()) {
+        let (key, value) = option.split_once('=').unwrap_or((option, ""));
+        if key.is_empty() {
+            return Err(malformed());
+        }
+        options.push((key, value));
+    }
+    Ok((name, options))
+}
+
+/// Strips the quotes or braces around the whole value.
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    for (open, close) in [('"', '"'), ('\'', '\''), ('{', '}')] {
+        if let Some(inner) = text.strip_prefix(open).and_then(|text| text.strip_suffix(close)) {
+            if open != '{' || balanced(inner) {
+                return inner;
+            }
+        }
+    }
+    text
+}
+
+fn balanced(text: &str) -> bool {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return false,
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0 && quote.is_none()
+}
+
+/// Splits by the separator outside the quotes, braces and parentheses, the whitespace separator matches any whitespace.
+fn split_top_level(text: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{' | '(') => depth += 1,
+            (None, '}' | ')') => depth = depth.saturating_sub(1),
+            (None, c) if depth == 0 && (c == separator || separator == ' ' && c.is_whitespace()) => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts.into_iter()
+}
+
+/// `Option` or `ListOption` of the pass, for the `--help` like listings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassOptionInfo {
+    pub argument: &'static str,
+    pub description: &'static str,
+}
+
+/// Pass listed in the [PassRegistry], mirroring the upstream `PassInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassInfo {
+    /// Pass argument of the pipeline, like `arith-expand`.
+    pub argument: &'static str,
+    pub summary: &'static str,
+    /// Names of the dialects the pass may create the entities of, like `arith`.
+    pub dependent_dialects: &'static [&'static str],
+    pub options: &'static [PassOptionInfo],
+}
+
+/// Generated passes by their arguments.
+#[derive(Debug, Default)]
+pub struct PassRegistry {
+    passes: BTreeMap<&'static str, &'static PassInfo>,
+}
+
+impl PassRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, info: &'static PassInfo) {
+        self.passes.insert(info.argument, info);
+    }
+
+    pub fn get(&self, argument: &str) -> Option<&'static PassInfo> {
+        self.passes.get(argument).copied()
+    }
+
+    /// Registered passes, ordered by their arguments.
+    pub fn passes(&self) -> impl Iterator<Item = &'static PassInfo> + '_ {
+        self.passes.values().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct InlinerOptions {
+        default_pipeline: String,
+        max_iterations: u32,
+        op_pipelines: Vec<String>,
+    }
+
+    impl Default for InlinerOptions {
+        fn default() -> Self {
+            Self {
+                default_pipeline: String::from("canonicalize"),
+                max_iterations: 4,
+                op_pipelines: vec![],
+            }
+        }
+    }
+
+    impl PassOptions for InlinerOptions {
+        const ARGUMENT: &'static str = "inline";
+
+        fn set_option(&mut self, option: &str, value: &str) -> Result<(), PassOptionError> {
+            match option {
+                "default-pipeline" => self.default_pipeline = parse_option(Self::ARGUMENT, option, value)?,
+                "max-iterations" => self.max_iterations = parse_option(Self::ARGUMENT, option, value)?,
+                "op-pipelines" => self.op_pipelines = parse_option(Self::ARGUMENT, option, value)?,
+                _ => {
+                    return Err(PassOptionError::UnknownOption {
+                        pass: Self::ARGUMENT,
+                        option: option.to_string(),
+                    })
+                }
+            }
+            Ok(())
+        }
+
+        fn options(&self) -> Vec<(&'static str, String)> {
+            vec![
+                ("default-pipeline", self.default_pipeline.print_value()),
+                ("max-iterations", self.max_iterations.print_value()),
+                ("op-pipelines", self.op_pipelines.print_value()),
+            ]
+        }
+    }
+
+    #[test]
+    fn should_parse_pass_options() {
+        assert_eq!(InlinerOptions::parse_options("inline").unwrap(), InlinerOptions::default());
+        let options = InlinerOptions::parse_options(
+            "inline{default-pipeline={cse, canonicalize} max-iterations=8 op-pipelines=func.func(cse, canonicalize),{a b}}",
+        )
+        .unwrap();
+        assert_eq!(options.default_pipeline, "cse, canonicalize");
+        assert_eq!(options.max_iterations, 8);
+        assert_eq!(options.op_pipelines, vec!["func.func(cse, canonicalize)", "a b"]);
+        assert_eq!(
+            options.print_options(),
+            "inline{default-pipeline={cse, canonicalize} max-iterations=8 op-pipelines={func.func(cse, canonicalize)},{a b}}"
+        );
+        assert_eq!(
+            InlinerOptions::default().print_options(),
+            "inline{default-pipeline=canonicalize max-iterations=4 op-pipelines=}"
+        );
+        assert_eq!(InlinerOptions::parse_options("inline{op-pipelines=}").unwrap(), InlinerOptions::default());
+    }
+
+    #[test]
+    fn should_reject_malformed_pass_options() {
+        assert!(matches!(
+            InlinerOptions::parse_options("cse"),
+            Err(PassOptionError::UnexpectedPass { expected: "inline", .. })
+        ));
+        assert!(matches!(
+            InlinerOptions::parse_options("inline{unknown=1}"),
+            Err(PassOptionError::UnknownOption { .. })
+        ));
+        assert!(matches!(
+            InlinerOptions::parse_options("inline{max-iterations=-1}"),
+            Err(PassOptionError::InvalidValue { .. })
+        ));
+        for malformed in ["inline{", "inline{max-iterations=1", "{a=1}", "inline{a={b}", "inline x"] {
+            assert_eq!(
+                InlinerOptions::parse_options(malformed),
+                Err(PassOptionError::Malformed(malformed.to_string()))
+            );
+        }
+        assert_eq!(bool::parse_value(""), Ok(true));
+        assert!(bool::parse_value("yes").is_err());
+    }
+
+    #[test]
+    fn should_register_passes() {
+        static PASSES: &[PassInfo] = &[
+            PassInfo {
+                argument: "inline",
+                summary: "Inline function calls",
+                dependent_dialects: &[],
+                options: &[],
+            },
+            PassInfo {
+                argument: "arith-expand",
+                summary: "Legalize Arith ops to be convertible to LLVM.",
+                dependent_dialects: &["vector"],
+                options: &[],
+            },
+        ];
+        let mut registry = PassRegistry::new();
+        for info in PASSES {
+            registry.register(info);
+        }
+        assert_eq!(registry.get("arith-expand").map(|info| info.dependent_dialects), Some(&["vector"][..]));
+        assert_eq!(
+            registry.passes().map(|info| info.argument).collect::<Vec<_>>(),
+            vec!["arith-expand", "inline"]
+        );
+    }
+}
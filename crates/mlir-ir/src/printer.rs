@@ -0,0 +1,878 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Generic form printer, mirroring the upstream `AsmPrinter` with `-mlir-print-op-generic`.
+//!
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{
+    Attribute, AttributeKind, Block, Context, FloatKind, NamedAttribute, Operation, Region, Signedness, Type, TypeKind,
+    Value, DYNAMIC,
+};
+
+mod custom;
+
+const INDENT_WIDTH: usize = 2;
+
+/// Printing flags, like the upstream `OpPrintingFlags`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrinterOptions {
+    /// Prints the operation locations, like `-mlir-print-debuginfo`.
+    pub debug_info: bool,
+    /// Prints the attributes inline instead of the `#map` and `#loc` aliases, like `-mlir-print-local-scope`.
+    pub local_scope: bool,
+    /// Prints the operations with the registered custom forms generically too, like `-mlir-print-op-generic`.
+    pub generic_op_form: bool,
+}
+
+/// Prints the IR in the generic form, or the custom forms of the registered operations, naming the values and blocks like the upstream `SSANameState`.
+pub struct Printer<'c> {
+    ctx: &'c Context,
+    options: PrinterOptions,
+    out: String,
+    indent: usize,
+    aliases: HashMap<Attribute, String>,
+    /// Location aliases, printed after the operations.
+    locations: Vec<Attribute>,
+    values: HashMap<Value, String>,
+    blocks: HashMap<Block, String>,
+    /// Dialects the custom operation names are printed without the prefix of, `builtin` at the top level,
+    /// none in the regions of the operations without the default dialect.
+    default_dialects: Vec<&'static str>,
+}
+
+impl<'c> Printer<'c> {
+    pub fn new(ctx: &'c Context, options: PrinterOptions) -> Self {
+        Self {
+            ctx,
+            options,
+            out: String::new(),
+            indent: 0,
+            aliases: HashMap::new(),
+            locations: vec![],
+            values: HashMap::new(),
+            blocks: HashMap::new(),
+            default_dialects: vec!["builtin"],
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    pub fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Prints the operation with the `#map` aliases defined before it and the `#loc` ones after it.
+    pub fn print_top_level(&mut self, op: Operation) {
+        self.number_operation(op);
+        if !self.options.local_scope {
+            let mut maps = vec![];
+            for op in op.walk(self.ctx) {
+                self.collect_operation_maps(op, &mut maps);
+            }
+            for (index, map) in maps.into_iter().enumerate() {
+                let alias = alias_name("map", index);
+                self.write(&format!("{alias} = "));
+                self.print_attribute(map);
+                self.write("\n");
+                self.aliases.insert(map, alias);
+            }
+        }
+        self.print_operation(op);
+        self.write("\n");
+        for index in 0..self.locations.len() {
+            let location = self.locations[index];
+            self.write(&format!("{} = ", self.aliases[&location]));
+            self.print_location(location);
+            self.write("\n");
+        }
+    }
+
+    /// Names the values of the operation and its regions, like the upstream `SSANameState`:
+    /// the results are numbered in the order of the operations, the entry block arguments are `%argN`,
+    /// and the nested regions continue the numbering of the enclosing region's values.
+    fn number_operation(&mut self, op: Operation) {
+        let (mut next_value, next_argument) = (0, 0);
+        self.number_results(op, &mut next_value);
+        for region in op.regions(self.ctx) {
+            self.number_region(*region, next_value, next_argument);
+        }
+    }
+
+    fn number_results(&mut self, op: Operation, next_value: &mut usize) {
+        let results = op.results(self.ctx);
+        match results {
+            [] => return,
+            [result] => {
+                self.values.insert(*result, format!("%{next_value}"));
+            }
+            results => {
+                for (index, result) in results.iter().enumerate() {
+                    self.values.insert(*result, format!("%{next_value}#{index}"));
+                }
+            }
+        }
+        *next_value += 1;
+    }
+
+    fn number_region(&mut self, region: Region, mut next_value: usize, mut next_argument: usize) {
+        let ctx = self.ctx;
+        for (index, block) in region.blocks(ctx).iter().enumerate() {
+            self.blocks.insert(*block, format!("^bb{index}"));
+            for argument in block.arguments(ctx) {
+                let name = if index == 0 {
+                    next_argument += 1;
+                    format!("%arg{}", next_argument - 1)
+                } else {
+                    next_value += 1;
+                    format!("%{}", next_value - 1)
+                };
+                self.values.insert(*argument, name);
+            }
+            for op in block.operations(ctx) {
+                self.number_results(*op, &mut next_value);
+            }
+        }
+        for block in region.blocks(ctx) {
+            for op in block.operations(ctx) {
+                for nested in op.regions(ctx) {
+                    self.number_region(*nested, next_value, next_argument);
+                }
+            }
+        }
+    }
+
+    fn collect_operation_maps(&self, op: Operation, maps: &mut Vec<Attribute>) {
+        let ctx = self.ctx;
+        for attribute in op.attributes(ctx).iter().map(|attribute| attribute.value).chain(op.properties(ctx)) {
+            self.collect_attribute_maps(attribute, maps);
+        }
+        for value in op.operands(ctx).iter().chain(op.results(ctx)) {
+            self.collect_type_maps(value.r#type(ctx), maps);
+        }
+        for region in op.regions(ctx) {
+            for block in region.blocks(ctx) {
+                for argument in block.arguments(ctx) {
+                    self.collect_type_maps(argument.r#type(ctx), maps);
+                }
+            }
+        }
+    }
+
+    fn collect_attribute_maps(&self, attribute: Attribute, maps: &mut Vec<Attribute>) {
+        match attribute.kind(self.ctx) {
+            AttributeKind::AffineMap(_) if !maps.contains(&attribute) => maps.push(attribute),
+            AttributeKind::Array(elements) => elements
+                .iter()
+                .for_each(|element| self.collect_attribute_maps(*element, maps)),
+            AttributeKind::Dictionary(attributes) => attributes
+                .iter()
+                .for_each(|attribute| self.collect_attribute_maps(attribute.value, maps)),
+            AttributeKind::Type(r#type) => self.collect_type_maps(*r#type, maps),
+            _ => {}
+        }
+    }
+
+    fn collect_type_maps(&self, r#type: Type, maps: &mut Vec<Attribute>) {
+        match r#type.kind(self.ctx) {
+            TypeKind::MemRef {
+                layout: Some(layout), ..
+            } => self.collect_attribute_maps(*layout, maps),
+            TypeKind::Function { inputs, results } => inputs
+                .iter()
+                .chain(results)
+                .for_each(|r#type| self.collect_type_maps(*r#type, maps)),
+            TypeKind::Tuple(types) => types.iter().for_each(|r#type| self.collect_type_maps(*r#type, maps)),
+            _ => {}
+        }
+    }
+
+    /// Starts a new line at the current indentation, like `printNewline`.
+    pub fn print_newline(&mut self) {
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat(' ').take(self.indent));
+    }
+
+    /// Prints the results and the operation, in its custom form if it's registered with one.
+    pub fn print_operation(&mut self, op: Operation) {
+        let ctx = self.ctx;
+        if let Some(first) = op.results(ctx).first() {
+            let name = self.value_name(*first);
+            let name = name.split('#').next().unwrap_or_default();
+            match op.results(ctx).len() {
+                1 => self.write(&format!("{name} = ")),
+                count => self.write(&format!("{name}:{count} = ")),
+            }
+        }
+        let assembly = ctx.dialects().assembly();
+        let custom = assembly.get(op.name(ctx)).filter(|_| !self.options.generic_op_form);
+        if custom.is_some() {
+            self.print_op_name(op.name(ctx));
+        }
+        self.default_dialects.push(assembly.default_dialect(op.name(ctx)).unwrap_or_default());
+        match custom {
+            Some(asm) => (asm.print)(op, self),
+            None => self.print_generic_operation(op),
+        }
+        self.default_dialects.pop();
+        if self.options.debug_info {
+            self.write(" ");
+            self.print_attribute(op.location(ctx));
+        }
+    }
+
+    /// Name of the custom operation, without the prefix of the default dialect, like the upstream `printOpName`.
+    fn print_op_name(&mut self, name: &str) {
+        let stripped = self
+            .default_dialects
+            .last()
+            .and_then(|dialect| name.strip_prefix(dialect)?.strip_prefix('.'))
+            .filter(|stripped| !stripped.contains('.'));
+        self.write(stripped.unwrap_or(name));
+    }
+
+    /// `"dialect.op"(%a)[^bb1] <{property = ...}> ({...}) {attribute = ...} : (i32) -> (i32, i32)`
+    pub fn print_generic_operation(&mut self, op: Operation) {
+        let ctx = self.ctx;
+        self.write(&escape_string(op.name(ctx)));
+        self.write("(");
+        self.print_values(op.operands(ctx));
+        self.write(")");
+        if !op.successors(ctx).is_empty() {
+            let successors: Vec<String> = op.successors(ctx).iter().map(|block| self.block_name(*block)).collect();
+            self.write(&format!("[{}]", successors.join(", ")));
+        }
+        if let Some(properties) = op.properties(ctx) {
+            self.write(" <");
+            self.print_attribute(properties);
+            self.write(">");
+        }
+        if !op.regions(ctx).is_empty() {
+            self.write(" (");
+            for (index, region) in op.regions(ctx).iter().enumerate() {
+                if index > 0 {
+                    self.write(", ");
+                }
+                self.print_region_with(*region, true, true, true);
+            }
+            self.write(")");
+        }
+        if !op.attributes(ctx).is_empty() {
+            self.write(" ");
+            self.print_dictionary(op.attributes(ctx));
+        }
+        self.write(" : ");
+        let inputs: Vec<Type> = op.operands(ctx).iter().map(|operand| operand.r#type(ctx)).collect();
+        self.print_function_type(&inputs, &op.result_types(ctx));
+    }
+
+    fn value_name(&self, value: Value) -> String {
+        self.values
+            .get(&value)
+            .cloned()
+            .unwrap_or_else(|| "<<UNKNOWN SSA VALUE>>".to_string())
+    }
+
+    fn block_name(&self, block: Block) -> String {
+        self.blocks
+            .get(&block)
+            .cloned()
+            .unwrap_or_else(|| "<<UNKNOWN BLOCK>>".to_string())
+    }
+
+    fn print_values(&mut self, values: &[Value]) {
+        let names: Vec<String> = values.iter().map(|value| self.value_name(*value)).collect();
+        self.write(&names.join(", "));
+    }
+
+    /// `{` blocks `}`, the entry block header is printed if it has the printed arguments,
+    /// or no operations and the empty blocks are printed, like the upstream `printRegion`.
+    fn print_region_with(&mut self, region: Region, entry_arguments: bool, terminators: bool, empty_block: bool) {
+        let ctx = self.ctx;
+        self.write("{");
+        for (index, block) in region.blocks(ctx).iter().enumerate() {
+            let header = index > 0
+                || (entry_arguments && !block.arguments(ctx).is_empty())
+                || (empty_block && block.is_empty(ctx));
+            if header {
+                self.print_newline();
+                self.print_block_header(*block);
+            }
+            let operations = block.operations(ctx);
+            let count = operations.len() - usize::from(index == 0 && !terminators && !operations.is_empty());
+            self.indent += INDENT_WIDTH;
+            for op in &operations[..count] {
+                self.print_newline();
+                self.print_operation(*op);
+            }
+            self.indent -= INDENT_WIDTH;
+        }
+        self.print_newline();
+        self.write("}");
+    }
+
+    /// `^bb1(%0: i32):  // 2 preds: ^bb0, ^bb2`
+    fn print_block_header(&mut self, block: Block) {
+        let ctx = self.ctx;
+        self.write(&self.block_name(block));
+        if !block.arguments(ctx).is_empty() {
+            self.write("(");
+            for (index, argument) in block.arguments(ctx).iter().enumerate() {
+                if index > 0 {
+                    self.write(", ");
+                }
+                self.write(&format!("{}: ", self.value_name(*argument)));
+                self.print_type(argument.r#type(ctx));
+            }
+            self.write(")");
+        }
+        self.write(":");
+        let mut predecessors: Vec<(usize, String)> = block
+            .uses(ctx)
+            .iter()
+            .filter_map(|operand| operand.owner.parent_block(ctx))
+            .map(|predecessor| {
+                let position = predecessor
+                    .parent_region(ctx)
+                    .and_then(|region| region.blocks(ctx).iter().position(|block| *block == predecessor))
+                    .unwrap_or_default();
+                (position, self.block_name(predecessor))
+            })
+            .collect();
+        predecessors.sort();
+        match predecessors.as_slice() {
+            [] if !block.is_entry_block(ctx) => self.write("  // no predecessors"),
+            [] => {}
+            [(_, predecessor)] => self.write(&format!("  // pred: {predecessor}")),
+            predecessors => {
+                let names: Vec<&str> = predecessors.iter().map(|(_, name)| name.as_str()).collect();
+                self.write(&format!("  // {} preds: {}", names.len(), names.join(", ")));
+            }
+        }
+    }
+
+    /// `{name = value, unit}`
+    fn print_dictionary(&mut self, attributes: &[NamedAttribute]) {
+        self.write("{");
+        for (index, attribute) in attributes.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            self.write(&keyword_or_string(&attribute.name));
+            if *attribute.value.kind(self.ctx) != AttributeKind::Unit {
+                self.write(" = ");
+                self.print_attribute(attribute.value);
+            }
+        }
+        self.write("}");
+    }
+
+    /// `(inputs) -> results`, the results are wrapped unless there's a single non-function one.
+    pub fn print_function_type(&mut self, inputs: &[Type], results: &[Type]) {
+        self.write("(");
+        self.print_types(inputs);
+        self.write(") -> ");
+        match results {
+            [result] if !matches!(result.kind(self.ctx), TypeKind::Function { .. }) => self.print_type(*result),
+            results => {
+                self.write("(");
+                self.print_types(results);
+                self.write(")");
+            }
+        }
+    }
+
+    /// Comma separated types.
+    pub fn print_types(&mut self, types: &[Type]) {
+        for (index, r#type) in types.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            self.print_type(*r#type);
+        }
+    }
+
+    /// `4x?x` dimensions prefix of the shaped types.
+    fn print_shape(&mut self, shape: &[i64], scalable: &[bool]) {
+        for (index, dim) in shape.iter().enumerate() {
+            let dim = match *dim {
+                DYNAMIC => "?".to_string(),
+                dim => dim.to_string(),
+            };
+            match scalable.get(index) {
+                Some(true) => self.write(&format!("[{dim}]x")),
+                _ => self.write(&format!("{dim}x")),
+            }
+        }
+    }
+
+    pub fn print_type(&mut self, r#type: Type) {
+        match r#type.kind(self.ctx) {
+            TypeKind::Integer { width, signedness } => {
+                let prefix = match signedness {
+                    Signedness::Signless => "i",
+                    Signedness::Signed => "si",
+                    Signedness::Unsigned => "ui",
+                };
+                self.write(&format!("{prefix}{width}"));
+            }
+            TypeKind::Index => self.write("index"),
+            TypeKind::Float(kind) => self.write(float_keyword(*kind)),
+            TypeKind::None => self.write("none"),
+            TypeKind::Complex(element) => {
+                self.write("complex<");
+                self.print_type(*element);
+                self.write(">");
+            }
+            TypeKind::Function { inputs, results } => self.print_function_type(inputs, results),
+            TypeKind::Tuple(types) => {
+                self.write("tuple<");
+                self.print_types(types);
+                self.write(">");
+            }
+            TypeKind::Vector {
+                shape,
+                scalable,
+                element,
+            } => {
+                self.write("vector<");
+                self.print_shape(shape, scalable);
+                self.print_type(*element);
+                self.write(">");
+            }
+            TypeKind::RankedTensor {
+                shape,
+                element,
+                encoding,
+            } => {
+                self.write("tensor<");
+                self.print_shape(shape, &[]);
+                self.print_type(*element);
+                if let Some(encoding) = encoding {
+                    self.write(", ");
+                    self.print_attribute(*encoding);
+                }
+                self.write(">");
+            }
+            TypeKind::UnrankedTensor { element } => {
+                self.write("tensor<*x");
+                self.print_type(*element);
+                self.write(">");
+            }
+            TypeKind::MemRef {
+                shape,
+                element,
+                layout,
+                memory_space,
+            } => {
+                self.write("memref<");
+                self.print_shape(shape, &[]);
+                self.print_type(*element);
+                if let Some(layout) = layout {
+                    self.write(", ");
+                    self.print_attribute(*layout);
+                }
+                if let Some(memory_space) = memory_space {
+                    self.write(", ");
+                    self.print_attribute_eliding_type(*memory_space);
+                }
+                self.write(">");
+            }
+            TypeKind::UnrankedMemRef { element, memory_space } => {
+                self.write("memref<*x");
+                self.print_type(*element);
+                if let Some(memory_space) = memory_space {
+                    self.write(", ");
+                    self.print_attribute_eliding_type(*memory_space);
+                }
+                self.write(">");
+            }
+            TypeKind::Opaque { dialect, data } => self.write(&dialect_symbol('!', dialect, data)),
+        }
+    }
+
+    pub fn print_attribute(&mut self, attribute: Attribute) {
+        self.print_attribute_with_elision(attribute, false);
+    }
+
+    /// Prints the attribute leaving out its `i64` or `f64` type, like the upstream `AttrTypeElision::May`.
+    pub fn print_attribute_eliding_type(&mut self, attribute: Attribute) {
+        self.print_attribute_with_elision(attribute, true);
+    }
+
+    fn print_attribute_with_elision(&mut self, attribute: Attribute, elide: bool) {
+        if attribute.is_location(self.ctx) {
+            return self.print_location_reference(attribute);
+        }
+        if let Some(alias) = self.aliases.get(&attribute) {
+            let alias = alias.clone();
+            return self.write(&alias);
+        }
+        let ctx = self.ctx;
+        match attribute.kind(ctx) {
+            AttributeKind::Unit => self.write("unit"),
+            AttributeKind::Bool(value) => self.write(&value.to_string()),
+            AttributeKind::Integer { value, r#type } if r#type.is_signless_integer(ctx, 1) => {
+                self.write(&(*value != 0).to_string())
+            }
+            AttributeKind::Integer { value, r#type } => {
+                self.write(&value.to_string());
+                if !(elide && r#type.is_signless_integer(ctx, 64)) {
+                    self.write(" : ");
+                    self.print_type(*r#type);
+                }
+            }
+            AttributeKind::Float { bits, r#type } => {
+                self.write(&format_float(f64::from_bits(*bits), r#type.kind(ctx)));
+                if !(elide && *r#type.kind(ctx) == TypeKind::Float(FloatKind::F64)) {
+                    self.write(" : ");
+                    self.print_type(*r#type);
+                }
+            }
+            AttributeKind::String(value) => self.write(&escape_string(value)),
+            AttributeKind::Type(r#type) => self.print_type(*r#type),
+            AttributeKind::Array(elements) => {
+                self.write("[");
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        self.write(", ");
+                    }
+                    self.print_attribute_eliding_type(*element);
+                }
+                self.write("]");
+            }
+            AttributeKind::Dictionary(attributes) => self.print_dictionary(attributes),
+            AttributeKind::SymbolRef { root, nested } => {
+                self.write(&symbol_name(root));
+                for nested in nested {
+                    self.write(&format!("::{}", symbol_name(nested)));
+                }
+            }
+            AttributeKind::DenseElements { r#type, elements } => {
+                self.write("dense<");
+                match elements.as_slice() {
+                    [] => {}
+                    [splat] => self.print_dense_element(*splat),
+                    elements => {
+                        let shape = r#type.shape(ctx).unwrap_or_default();
+                        self.print_dense_elements(shape, elements);
+                    }
+                }
+                self.write("> : ");
+                self.print_type(*r#type);
+            }
+            AttributeKind::DenseArray { element, elements } => {
+                self.write("array<");
+                self.print_type(*element);
+                for (index, value) in elements.iter().enumerate() {
+                    self.write(if index > 0 { ", " } else { ": " });
+                    self.print_dense_element(*value);
+                }
+                self.write(">");
+            }
+            AttributeKind::AffineMap(map) => self.write(&format!("affine_map<{map}>")),
+            AttributeKind::StridedLayout { offset, strides } => {
+                let dim = |dim: &i64| match *dim {
+                    DYNAMIC => "?".to_string(),
+                    dim => dim.to_string(),
+                };
+                let strides: Vec<String> = strides.iter().map(dim).collect();
+                self.write(&format!("strided<[{}]", strides.join(", ")));
+                if *offset != 0 {
+                    self.write(&format!(", offset: {}", dim(offset)));
+                }
+                self.write(">");
+            }
+            AttributeKind::Opaque { dialect, data } => self.write(&dialect_symbol('#', dialect, data)),
+            AttributeKind::UnknownLoc
+            | AttributeKind::FileLineColLoc { .. }
+            | AttributeKind::NameLoc { .. }
+            | AttributeKind::CallSiteLoc { .. }
+            | AttributeKind::FusedLoc { .. } => unreachable!("locations are printed by print_location"),
+        }
+    }
+
+    /// Nested `[[1, 2], [3, 4]]` elements of the shape.
+    fn print_dense_elements(&mut self, shape: &[i64], elements: &[Attribute]) {
+        let Some((_, inner)) = shape.split_first() else {
+            return elements.iter().for_each(|element| self.print_dense_element(*element));
+        };
+        let stride = inner.iter().product::<i64>().max(1) as usize;
+        self.write("[");
+        for (index, chunk) in elements.chunks(stride).enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            match inner {
+                [] => self.print_dense_element(chunk[0]),
+                inner => self.print_dense_elements(inner, chunk),
+            }
+        }
+        self.write("]");
+    }
+
+    fn print_dense_element(&mut self, element: Attribute) {
+        let ctx = self.ctx;
+        match element.kind(ctx) {
+            AttributeKind::Integer { value, r#type } if r#type.is_signless_integer(ctx, 1) => {
+                self.write(&(*value != 0).to_string())
+            }
+            AttributeKind::Integer { value, .. } => self.write(&value.to_string()),
+            AttributeKind::Float { bits, r#type } => self.write(&format_float(f64::from_bits(*bits), r#type.kind(ctx))),
+            _ => self.print_attribute(element),
+        }
+    }
+
+    /// `loc(#loc)` alias of the location, or `loc(...)` with the local scope.
+    fn print_location_reference(&mut self, location: Attribute) {
+        if self.options.local_scope {
+            return self.print_location(location);
+        }
+        let alias = match self.aliases.get(&location) {
+            Some(alias) => alias.clone(),
+            None => {
+                let alias = alias_name("loc", self.locations.len());
+                self.aliases.insert(location, alias.clone());
+                self.locations.push(location);
+                alias
+            }
+        };
+        self.write(&format!("loc({alias})"));
+    }
+
+    /// `loc(...)` of the location.
+    pub fn print_location(&mut self, location: Attribute) {
+        self.write("loc(");
+        self.print_location_body(location);
+        self.write(")");
+    }
+
+    fn print_location_body(&mut self, location: Attribute) {
+        match location.kind(self.ctx) {
+            AttributeKind::UnknownLoc => self.write("unknown"),
+            AttributeKind::FileLineColLoc { filename, line, column } => {
+                self.write(&format!("{}:{line}:{column}", escape_string(filename)))
+            }
+            AttributeKind::NameLoc { name, child } => {
+                self.write(&escape_string(name));
+                if let Some(child) = child {
+                    self.write("(");
+                    self.print_location_body(*child);
+                    self.write(")");
+                }
+            }
+            AttributeKind::CallSiteLoc { callee, caller } => {
+                self.write("callsite(");
+                self.print_location_body(*callee);
+                self.write(" at ");
+                self.print_location_body(*caller);
+                self.write(")");
+            }
+            AttributeKind::FusedLoc { locations, metadata } => {
+                self.write("fused");
+                if let Some(metadata) = metadata {
+                    self.write("<");
+                    self.print_attribute(*metadata);
+                    self.write(">");
+                }
+                self.write("[");
+                for (index, location) in locations.iter().enumerate() {
+                    if index > 0 {
+                        self.write(", ");
+                    }
+                    self.print_location_body(*location);
+                }
+                self.write("]");
+            }
+            _ => self.print_attribute(location),
+        }
+    }
+}
+
+/// `#map`, `#map1`, ... upstream alias names.
+fn alias_name(prefix: &str, index: usize) -> String {
+    match index {
+        0 => format!("#{prefix}"),
+        index => format!("#{prefix}{index}"),
+    }
+}
+
+pub(crate) fn float_keyword(kind: FloatKind) -> &'static str {
+    match kind {
+        FloatKind::BF16 => "bf16",
+        FloatKind::F16 => "f16",
+        FloatKind::TF32 => "tf32",
+        FloatKind::F32 => "f32",
+        FloatKind::F64 => "f64",
+        FloatKind::F80 => "f80",
+        FloatKind::F128 => "f128",
+    }
+}
+
+/// Prints the float like the upstream `printFloatValue`, in the `1.000000e+00` scientific notation
+/// if it's read back as the same value of the type, in the hexadecimal bits of it otherwise.
+fn format_float(value: f64, r#type: &TypeKind) -> String {
+    if value.is_finite() {
+        let text = format!("{value:.6e}");
+        if let Some((mantissa, exponent)) = text.split_once('e') {
+            let exponent: i32 = exponent.parse().unwrap_or_default();
+            let sign = if exponent < 0 { '-' } else { '+' };
+            let text = format!("{mantissa}e{sign}{:02}", exponent.abs());
+            let exact = match r#type {
+                TypeKind::Float(FloatKind::F32) => text.parse::<f32>() == Ok(value as f32),
+                _ => text.parse::<f64>() == Ok(value),
+            };
+            if exact {
+                return text;
+            }
+        }
+    }
+    match r#type {
+        TypeKind::Float(FloatKind::F32) => format!("0x{:08X}", (value as f32).to_bits()),
+        _ => format!("0x{:016X}", value.to_bits()),
+    }
+}
+
+/// Identifiers printed without the quotes, `[a-zA-Z_][a-zA-Z0-9_$.]*`.
+pub(crate) fn is_bare_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.'))
+}
+
+fn keyword_or_string(text: &str) -> String {
+    if is_bare_identifier(text) {
+        text.to_string()
+    } else {
+        escape_string(text)
+    }
+}
+
+fn symbol_name(name: &str) -> String {
+    format!("@{}", keyword_or_string(name))
+}
+
+fn dialect_symbol(prefix: char, dialect: &str, data: &str) -> String {
+    if data.starts_with('<') {
+        format!("{prefix}{dialect}{data}")
+    } else {
+        format!("{prefix}{dialect}.{data}")
+    }
+}
+
+/// Quoted string, escaping the quotes, backslashes and non-printable bytes as `\XX`, like `printEscapedString`.
+pub(crate) fn escape_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for byte in text.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            byte => {
+                let _ = write!(escaped, "\\{byte:02X}");
+            }
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{print, Builder, OperationState};
+
+    #[test]
+    fn should_print_built_operations() {
+        let mut ctx = Context::new();
+        let module = ctx.create_module();
+        let body = module.region(&ctx, 0).entry_block(&ctx).unwrap();
+        let mut builder = Builder::at_end(&mut ctx, body);
+        let i64 = builder.integer_type(64);
+        let f32 = builder.float_type(FloatKind::F32);
+        let vector = builder.vector_type(&[4], f32);
+        let one = builder.float_attr(1.0, f32);
+        let splat = builder.dense_elements_attr(vector, &[one, one, one, one]);
+        let mut state = OperationState::new("arith.constant");
+        state.add_attribute("value", splat).add_types([vector]);
+        let constant = builder.create(state);
+        let mut state = OperationState::new("test.multi");
+        state.add_types([i64, i64]).add_regions(1);
+        let multi = builder.create(state);
+        let region = multi.region(&builder, 0);
+        let entry = builder.create_block(region, &[i64]);
+        let mut state = OperationState::new("test.use");
+        state.add_operands([
+            constant.result(&builder, 0),
+            multi.result(&builder, 1),
+            entry.argument(&builder, 0),
+        ]);
+        builder.create(state);
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  %0 = "arith.constant"() {value = dense<1.000000e+00> : vector<4xf32>} : () -> vector<4xf32>
+  %1:2 = "test.multi"() ({
+  ^bb0(%arg0: i64):
+    "test.use"(%0, %1#1, %arg0) : (vector<4xf32>, i64, i64) -> ()
+  }) : () -> (i64, i64)
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_print_empty_regions_and_blocks() {
+        let mut ctx = Context::new();
+        let mut state = OperationState::new("test.regions");
+        state.add_regions(2);
+        let op = ctx.create_operation(state);
+        let block = ctx.create_block(&[]);
+        ctx.append_block(op.region(&ctx, 1), block);
+        assert_eq!(
+            print(&ctx, op, PrinterOptions::default()),
+            "\"test.regions\"() ({\n}, {\n^bb0:\n}) : () -> ()\n"
+        );
+    }
+
+    #[test]
+    fn should_format_floats() {
+        let f32 = TypeKind::Float(FloatKind::F32);
+        let f64 = TypeKind::Float(FloatKind::F64);
+        assert_eq!(format_float(1.0, &f64), "1.000000e+00");
+        assert_eq!(format_float(-0.25, &f64), "-2.500000e-01");
+        assert_eq!(format_float(1e100, &f64), "1.000000e+100");
+        assert_eq!(format_float(0.1f32 as f64, &f32), "1.000000e-01");
+        assert_eq!(format_float(0.1f32 as f64, &f64), "0x3FB99999A0000000");
+        assert_eq!(format_float(f64::INFINITY, &f32), "0x7F800000");
+    }
+
+    #[test]
+    fn should_escape_strings() {
+        assert_eq!(escape_string("a\"b\\c\nd"), r#""a\"b\\c\0Ad""#);
+        assert_eq!(escape_string("ú"), r#""\C3\BA""#);
+        assert!(is_bare_identifier("sym_name.x$"));
+        assert!(!is_bare_identifier("0x") && !is_bare_identifier("c d"));
+    }
+}
@@ -0,0 +1,222 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Custom form printing helpers, mirroring the upstream `OpAsmPrinter`.
+//!
+
+use super::{escape_string, is_bare_identifier, keyword_or_string, symbol_name, Printer};
+use crate::{Attribute, AttributeKind, Block, Context, NamedAttribute, Region, Type, TypeKind, Value};
+
+impl<'c> Printer<'c> {
+    pub fn ctx(&self) -> &'c Context {
+        self.ctx
+    }
+
+    /// Comma separated value names.
+    pub fn print_operands(&mut self, values: &[Value]) {
+        self.print_values(values);
+    }
+
+    /// Comma separated types of the values.
+    pub fn print_value_types(&mut self, values: &[Value]) {
+        let types: Vec<Type> = values.iter().map(|value| value.r#type(self.ctx)).collect();
+        self.print_types(&types);
+    }
+
+    /// Prints the type of the dialect without its `!dialect.` prefix, like `printStrippedAttrOrType`.
+    pub fn print_stripped_type(&mut self, r#type: Type, dialect: &str) {
+        match r#type.kind(self.ctx) {
+            TypeKind::Opaque { dialect: namespace, data } if namespace == dialect && !data.starts_with('<') => {
+                self.write(data)
+            }
+            _ => self.print_type(r#type),
+        }
+    }
+
+    /// Prints the integer and floating point attributes without their type, like `printAttributeWithoutType`.
+    pub fn print_attribute_without_type(&mut self, attribute: Attribute) {
+        match attribute.kind(self.ctx) {
+            AttributeKind::Integer { .. } | AttributeKind::Float { .. } => self.print_dense_element(attribute),
+            _ => self.print_attribute(attribute),
+        }
+    }
+
+    /// Prints the attribute of the dialect without its `#dialect.` prefix, and the dense arrays without
+    /// their `array<type: ...>` wrapper.
+    pub fn print_stripped_attribute(&mut self, attribute: Attribute, dialect: Option<&str>) {
+        match attribute.kind(self.ctx) {
+            AttributeKind::DenseArray { elements, .. } => {
+                self.write("[");
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        self.write(", ");
+                    }
+                    self.print_dense_element(*element);
+                }
+                self.write("]");
+            }
+            AttributeKind::Opaque { dialect: namespace, data }
+                if Some(namespace.as_str()) == dialect && !data.starts_with('<') =>
+            {
+                self.write(data)
+            }
+            _ => self.print_attribute(attribute),
+        }
+    }
+
+    /// `@symbol` of the symbol name string attribute.
+    pub fn print_symbol_name(&mut self, attribute: Attribute) {
+        match attribute.kind(self.ctx) {
+            AttributeKind::String(name) => self.write(&symbol_name(name)),
+            _ => self.print_attribute(attribute),
+        }
+    }
+
+    /// Quoted string, escaping the quotes and non-printable characters.
+    pub fn print_string(&mut self, text: &str) {
+        self.write(&escape_string(text));
+    }
+
+    pub fn print_keyword_or_string(&mut self, text: &str) {
+        self.write(&keyword_or_string(text));
+    }
+
+    /// Prints the enum case of the integer attribute by its value.
+    pub fn print_enum(&mut self, attribute: Attribute, cases: &[(i128, &str)]) {
+        let case = attribute
+            .as_integer(self.ctx)
+            .and_then(|value| cases.iter().find(|(case, _)| *case == value));
+        match case {
+            Some((_, name)) if is_bare_identifier(name) => self.write(name),
+            Some((_, name)) => self.print_string(name),
+            None => self.print_attribute(attribute),
+        }
+    }
+
+    /// ` {name = value, ...}` without the elided attributes, nothing if there are no attributes left.
+    pub fn print_optional_attr_dict(&mut self, attributes: &[NamedAttribute], elided: &[&str]) {
+        self.print_filtered_attr_dict(attributes, elided, false);
+    }
+
+    /// ` attributes {name = value, ...}`, like the `attr-dict-with-keyword` directive.
+    pub fn print_optional_attr_dict_with_keyword(&mut self, attributes: &[NamedAttribute], elided: &[&str]) {
+        self.print_filtered_attr_dict(attributes, elided, true);
+    }
+
+    fn print_filtered_attr_dict(&mut self, attributes: &[NamedAttribute], elided: &[&str], keyword: bool) {
+        let attributes: Vec<NamedAttribute> = attributes
+            .iter()
+            .filter(|attribute| !elided.contains(&attribute.name.as_str()))
+            .cloned()
+            .collect();
+        if attributes.is_empty() {
+            return;
+        }
+        if keyword {
+            self.write(" attributes");
+        }
+        self.write(" ");
+        self.print_dictionary(&attributes);
+    }
+
+    /// ` -> results`, wrapped unless there's a single non-function one.
+    pub fn print_arrow_type_list(&mut self, types: &[Type]) {
+        self.write(" -> ");
+        match types {
+            [r#type] if !matches!(r#type.kind(self.ctx), TypeKind::Function { .. }) => self.print_type(*r#type),
+            types => {
+                self.write("(");
+                self.print_types(types);
+                self.write(")");
+            }
+        }
+    }
+
+    /// `%name: type {attributes}` argument of the region entry block, like the upstream `printRegionArgument`.
+    pub fn print_region_argument(&mut self, argument: Value, attributes: &[NamedAttribute]) {
+        self.write(&format!("{}: ", self.value_name(argument)));
+        self.print_type(argument.r#type(self.ctx));
+        self.print_optional_attr_dict(attributes, &[]);
+    }
+
+    /// `(%arg0: i32, %arg1: i32) -> results` signature of the function with the entry block, or `(i32, i32) -> results`
+    /// of the declaration, like the upstream `printFunctionSignature`, the results are left out if there are none.
+    pub fn print_function_signature(
+        &mut self,
+        entry: Option<Block>,
+        inputs: &[Type],
+        results: &[Type],
+        argument_attributes: &[Vec<NamedAttribute>],
+    ) {
+        self.write("(");
+        for (index, r#type) in inputs.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            let attributes = argument_attributes.get(index).map(Vec::as_slice).unwrap_or_default();
+            match entry.and_then(|entry| entry.arguments(self.ctx).get(index).copied()) {
+                Some(argument) => self.print_region_argument(argument, attributes),
+                None => {
+                    self.print_type(*r#type);
+                    self.print_optional_attr_dict(attributes, &[]);
+                }
+            }
+        }
+        self.write(")");
+        if !results.is_empty() {
+            self.print_arrow_type_list(results);
+        }
+    }
+
+    /// Comma separated block labels.
+    pub fn print_successors(&mut self, successors: &[Block]) {
+        let names: Vec<String> = successors.iter().map(|block| self.block_name(*block)).collect();
+        self.write(&names.join(", "));
+    }
+
+    /// `{ ... }` region, the entry block header is printed only if it has the printed arguments.
+    pub fn print_region(&mut self, region: Region, entry_arguments: bool, terminators: bool) {
+        self.print_region_with(region, entry_arguments, terminators, false);
+    }
+
+    /// Prints the region of the `SingleBlockImplicitTerminator` operations,
+    /// the terminator is elided unless it has any operands, results or attributes.
+    pub fn print_region_eliding_terminator(&mut self, region: Region) {
+        let ctx = self.ctx;
+        let terminator = region.entry_block(ctx).and_then(|block| block.terminator(ctx));
+        let terminators = terminator.is_none_or(|terminator| {
+            !terminator.all_attributes(ctx).is_empty()
+                || !terminator.operands(ctx).is_empty()
+                || !terminator.results(ctx).is_empty()
+        });
+        self.print_region(region, true, terminators);
+    }
+
+    /// Comma separated regions.
+    pub fn print_regions(&mut self, regions: &[Region], implicit_terminator: bool) {
+        for (index, region) in regions.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            if implicit_terminator {
+                self.print_region_eliding_terminator(*region);
+            } else {
+                self.print_region(*region, true, true);
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Blocks with their arguments and operations, and the regions holding the blocks.
+//!
+
+use crate::value::ValueOwner;
+use crate::{Context, IrError, Operation, Type, Value};
+
+/// Block handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Block(pub(crate) u32);
+
+/// Region handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Region(pub(crate) u32);
+
+/// Use of a block, the successor `index` of its `owner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockOperand {
+    pub owner: Operation,
+    pub index: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct BlockData {
+    pub arguments: Vec<Value>,
+    pub operations: Vec<Operation>,
+    pub parent: Option<Region>,
+    pub uses: Vec<BlockOperand>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RegionData {
+    pub blocks: Vec<Block>,
+    pub parent: Operation,
+}
+
+impl Block {
+    fn data(self, ctx: &Context) -> &BlockData {
+        ctx.blocks.get(self.0)
+    }
+
+    pub fn arguments(self, ctx: &Context) -> &[Value] {
+        &self.data(ctx).arguments
+    }
+
+    pub fn argument(self, ctx: &Context, index: usize) -> Value {
+        self.arguments(ctx)[index]
+    }
+
+    pub fn argument_types(self, ctx: &Context) -> Vec<Type> {
+        self.arguments(ctx)
+            .iter()
+            .map(|argument| argument.r#type(ctx))
+            .collect()
+    }
+
+    pub fn operations(self, ctx: &Context) -> &[Operation] {
+        &self.data(ctx).operations
+    }
+
+    pub fn is_empty(self, ctx: &Context) -> bool {
+        self.operations(ctx).is_empty()
+    }
+
+    /// Last operation of the block, the terminator of the well-formed ones.
+    pub fn terminator(self, ctx: &Context) -> Option<Operation> {
+        self.operations(ctx).last().copied()
+    }
+
+    pub fn parent_region(self, ctx: &Context) -> Option<Region> {
+        self.data(ctx).parent
+    }
+
+    pub fn parent_op(self, ctx: &Context) -> Option<Operation> {
+        self.parent_region(ctx).map(|region| region.parent_op(ctx))
+    }
+
+    /// Whether the block is the first one of its region.
+    pub fn is_entry_block(self, ctx: &Context) -> bool {
+        self.parent_region(ctx)
+            .is_some_and(|region| region.blocks(ctx).first() == Some(&self))
+    }
+
+    /// Successor uses of the block.
+    pub fn uses(self, ctx: &Context) -> &[BlockOperand] {
+        &self.data(ctx).uses
+    }
+
+    /// Blocks branching to this one, once per branching terminator.
+    pub fn predecessors(self, ctx: &Context) -> Vec<Block> {
+        let mut predecessors: Vec<Block> = vec![];
+        for operand in self.uses(ctx) {
+            if let Some(block) = operand.owner.parent_block(ctx) {
+                if !predecessors.contains(&block) {
+                    predecessors.push(block);
+                }
+            }
+        }
+        predecessors
+    }
+
+    /// Successors of the block terminator.
+    pub fn successors(self, ctx: &Context) -> &[Block] {
+        match self.terminator(ctx) {
+            Some(terminator) => terminator.successors(ctx),
+            None => &[],
+        }
+    }
+}
+
+impl Region {
+    pub fn blocks(self, ctx: &Context) -> &[Block] {
+        &ctx.regions.get(self.0).blocks
+    }
+
+    pub fn is_empty(self, ctx: &Context) -> bool {
+        self.blocks(ctx).is_empty()
+    }
+
+    pub fn entry_block(self, ctx: &Context) -> Option<Block> {
+        self.blocks(ctx).first().copied()
+    }
+
+    pub fn parent_op(self, ctx: &Context) -> Operation {
+        ctx.regions.get(self.0).parent
+    }
+}
+
+impl Context {
+    /// Creates the block with the arguments of the given types, not inserted into any region.
+    pub fn create_block(&mut self, argument_types: &[Type]) -> Block {
+        let block = Block(self.blocks.alloc(BlockData {
+            arguments: vec![],
+            operations: vec![],
+            parent: None,
+            uses: vec![],
+        }));
+        for r#type in argument_types {
+            self.add_argument(block, *r#type);
+        }
+        block
+    }
+
+    pub fn add_argument(&mut self, block: Block, r#type: Type) -> Value {
+        let index = block.arguments(self).len();
+        let argument = self.new_value(r#type, ValueOwner::Argument { block, index });
+        self.blocks.get_mut(block.0).arguments.push(argument);
+        argument
+    }
+
+    /// Erases the unused argument, renumbering the following ones.
+    pub fn erase_argument(&mut self, block: Block, index: usize) -> Result<(), IrError> {
+        let argument = block.argument(self, index);
+        if !argument.use_empty(self) {
+            return Err(IrError::ArgumentInUse { index });
+        }
+        self.blocks.get_mut(block.0).arguments.remove(index);
+        self.values.free(argument.0);
+        let arguments = self.blocks.get(block.0).arguments.clone();
+        for (index, argument) in arguments.into_iter().enumerate().skip(index) {
+            self.values.get_mut(argument.0).owner = ValueOwner::Argument { block, index };
+        }
+        Ok(())
+    }
+
+    pub fn append_block(&mut self, region: Region, block: Block) {
+        self.remove_block(block);
+        self.regions.get_mut(region.0).blocks.push(block);
+        self.blocks.get_mut(block.0).parent = Some(region);
+    }
+
+    /// Inserts the block before the `anchor` one, into the region of the anchor.
+    pub fn insert_block_before(&mut self, anchor: Block, block: Block) {
+        self.remove_block(block);
+        let region = anchor
+            .parent_region(self)
+            .expect("inserting before a block without a region");
+        let blocks = &mut self.regions.get_mut(region.0).blocks;
+        let position = blocks
+            .iter()
+            .position(|candidate| *candidate == anchor)
+            .unwrap_or(blocks.len());
+        blocks.insert(position, block);
+        self.blocks.get_mut(block.0).parent = Some(region);
+    }
+
+    /// Unlinks the block from its region, keeping it alive to be inserted elsewhere.
+    pub fn remove_block(&mut self, block: Block) {
+        if let Some(region) = self.blocks.get_mut(block.0).parent.take() {
+            self.regions
+                .get_mut(region.0)
+                .blocks
+                .retain(|candidate| *candidate != block);
+        }
+    }
+
+    /// Erases the block with its operations, its values may only be used within it and it may not be a successor.
+    pub fn erase_block(&mut self, block: Block) -> Result<(), IrError> {
+        if let Some(operand) = block.uses(self).first() {
+            return Err(IrError::BlockInUse {
+                op: operand.owner.name(self).to_string(),
+            });
+        }
+        let operations = block.operations(self).to_vec();
+        let nested: Vec<Operation> = operations.iter().flat_map(|op| op.walk(self)).collect();
+        let escapes = |value: &Value| value.uses(self).iter().any(|operand| !nested.contains(&operand.owner));
+        if let Some(index) = block.arguments(self).iter().position(escapes) {
+            return Err(IrError::ArgumentInUse { index });
+        }
+        for op in &nested {
+            if let Some(index) = op.results(self).iter().position(escapes) {
+                return Err(IrError::ResultInUse {
+                    op: op.name(self).to_string(),
+                    index,
+                });
+            }
+        }
+        for op in &operations {
+            self.drop_all_references(*op);
+        }
+        for op in operations.into_iter().rev() {
+            self.erase_operation(op)?;
+        }
+        self.remove_block(block);
+        for argument in self.blocks.free(block.0).arguments {
+            self.values.free(argument.0);
+        }
+        Ok(())
+    }
+
+    /// Inserts the detached operation before the `before` one, or at the end of the block.
+    pub fn insert_operation(&mut self, block: Block, before: Option<Operation>, op: Operation) -> Result<(), IrError> {
+        if op.parent_block(self).is_some() {
+            return Err(IrError::AlreadyInserted(op.name(self).to_string()));
+        }
+        let operations = &mut self.blocks.get_mut(block.0).operations;
+        let position = before
+            .and_then(|before| operations.iter().position(|candidate| *candidate == before))
+            .unwrap_or(operations.len());
+        operations.insert(position, op);
+        self.operations.get_mut(op.0).parent = Some(block);
+        Ok(())
+    }
+
+    /// Moves the operation right before the `anchor` one.
+    pub fn move_operation_before(&mut self, op: Operation, anchor: Operation) {
+        self.remove_operation(op);
+        let block = anchor
+            .parent_block(self)
+            .expect("moving before an operation without a block");
+        self.insert_operation(block, Some(anchor), op)
+            .expect("the removed operation is detached");
+    }
+
+    pub(crate) fn add_block_use(&mut self, block: Block, operand: BlockOperand) {
+        self.blocks.get_mut(block.0).uses.push(operand);
+    }
+
+    pub(crate) fn remove_block_use(&mut self, block: Block, operand: BlockOperand) {
+        let uses = &mut self.blocks.get_mut(block.0).uses;
+        if let Some(position) = uses.iter().position(|candidate| *candidate == operand) {
+            uses.remove(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OperationState;
+
+    use super::*;
+
+    /// Region of `test.region` with the `^entry` block branching to `^exit` taking an `i1`.
+    fn branch(ctx: &mut Context) -> (Region, Block, Block, Operation) {
+        let i1 = ctx.integer_type(1);
+        let mut state = OperationState::new("test.region");
+        state.add_regions(1);
+        let region = ctx.create_operation(state).region(ctx, 0);
+        let entry = ctx.create_block(&[i1]);
+        let exit = ctx.create_block(&[i1]);
+        ctx.append_block(region, exit);
+        ctx.insert_block_before(exit, entry);
+        let mut state = OperationState::new("cf.br");
+        state.add_operands([entry.argument(ctx, 0)]).add_successors([exit]);
+        let br = ctx.create_operation(state);
+        ctx.insert_operation(entry, None, br).unwrap();
+        (region, entry, exit, br)
+    }
+
+    #[test]
+    fn should_link_blocks() {
+        let mut ctx = Context::new();
+        let (region, entry, exit, br) = branch(&mut ctx);
+        assert_eq!(region.blocks(&ctx), &[entry, exit]);
+        assert_eq!(region.entry_block(&ctx), Some(entry));
+        assert!(entry.is_entry_block(&ctx) && !exit.is_entry_block(&ctx));
+        assert_eq!(entry.successors(&ctx), &[exit]);
+        assert_eq!(exit.predecessors(&ctx), [entry]);
+        assert_eq!(exit.uses(&ctx), &[BlockOperand { owner: br, index: 0 }]);
+
+        ctx.set_successor(br, 0, entry);
+        assert_eq!(entry.predecessors(&ctx), [entry]);
+        assert!(exit.uses(&ctx).is_empty());
+    }
+
+    #[test]
+    fn should_erase_blocks_and_arguments() {
+        let mut ctx = Context::new();
+        let (region, entry, exit, br) = branch(&mut ctx);
+        assert_eq!(
+            ctx.erase_block(exit),
+            Err(IrError::BlockInUse {
+                op: "cf.br".to_string()
+            })
+        );
+        assert_eq!(ctx.erase_argument(entry, 0), Err(IrError::ArgumentInUse { index: 0 }));
+
+        let index = ctx.index_type();
+        let extra = ctx.add_argument(exit, index);
+        ctx.erase_argument(exit, 0).unwrap();
+        assert_eq!(exit.arguments(&ctx), &[extra]);
+        assert_eq!(extra.owner(&ctx), ValueOwner::Argument { block: exit, index: 0 });
+
+        ctx.erase_block(entry).unwrap();
+        assert!(!br.is_alive(&ctx));
+        assert_eq!(region.blocks(&ctx), &[exit]);
+        ctx.erase_block(exit).unwrap();
+        assert!(region.is_empty(&ctx));
+    }
+}
@@ -0,0 +1,282 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Pattern rewriting of the core IR. The [RewritePattern]s of the root operations are applied by the
+//! [PatternRewriter], in the order of their benefit, and the greedy driver of
+//! [apply_patterns_and_fold_greedily] revisits the operations changed by the rewrites, erasing the dead ones
+//! and folding the others through the [crate::Dialect] hooks, until the IR stops changing.
+//!
+
+mod greedy;
+mod pattern;
+
+pub use greedy::{apply_patterns_and_fold_greedily, GreedyRewriteConfig};
+pub use pattern::{FrozenRewritePatternSet, MatchFailure, PatternRewriter, RewritePattern, RewritePatternSet};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        parse, print, Attribute, Builder, Context, Dialect, OpFoldResult, Operation, OperationState, PrinterOptions,
+        RewriteError, Type,
+    };
+
+    /// `test.constant`, `test.addi` folding the constants and the zero addends, the in-place commuting
+    /// `test.muli`, `test.neg` of the double negation pattern, `test.pick` and the never converging `test.flip`.
+    pub(crate) struct TestDialect;
+
+    impl Dialect for TestDialect {
+        fn namespace(&self) -> &str {
+            "test"
+        }
+
+        fn fold(&self, ctx: &mut Context, op: Operation, operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+            let integers: Vec<Option<i128>> = operands
+                .iter()
+                .map(|operand| operand.and_then(|operand| operand.as_integer(ctx)))
+                .collect();
+            match (op.name(ctx), integers.as_slice()) {
+                ("test.addi", [Some(lhs), Some(rhs)]) => {
+                    let r#type = op.result(ctx, 0).r#type(ctx);
+                    Some(vec![OpFoldResult::Attribute(ctx.integer_attr(lhs + rhs, r#type))])
+                }
+                ("test.addi", [_, Some(0)]) => Some(vec![OpFoldResult::Value(op.operand(ctx, 0))]),
+                ("test.muli", [Some(_), None]) => {
+                    let operands = op.operands(ctx).iter().rev().copied().collect();
+                    ctx.set_operands(op, operands);
+                    Some(vec![OpFoldResult::Value(op.result(ctx, 0))])
+                }
+                _ => None,
+            }
+        }
+
+        fn constant_value(&self, ctx: &Context, op: Operation) -> Option<Attribute> {
+            match op.name(ctx) {
+                "test.constant" => op.attribute(ctx, "value"),
+                _ => None,
+            }
+        }
+
+        fn materialize_constant(
+            &self,
+            builder: &mut Builder<'_>,
+            value: Attribute,
+            r#type: Type,
+            location: Attribute,
+        ) -> Option<Operation> {
+            let mut state = OperationState::new("test.constant");
+            state
+                .add_attribute("value", value)
+                .add_types([r#type])
+                .set_location(location);
+            Some(builder.create(state))
+        }
+
+        fn is_pure(&self, ctx: &Context, op: Operation) -> bool {
+            matches!(op.name(ctx), "test.constant" | "test.addi" | "test.muli" | "test.neg")
+        }
+
+        fn canonicalization_patterns(&self, patterns: &mut RewritePatternSet) {
+            patterns
+                .add(NegNeg)
+                .add(Flip)
+                .add(Pick("PickLow", 1))
+                .add(Pick("PickHigh", 2));
+        }
+    }
+
+    /// `-(-x)` to `x`.
+    struct NegNeg;
+
+    impl RewritePattern for NegNeg {
+        fn name(&self) -> &str {
+            "NegNeg"
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            Some("test.neg")
+        }
+
+        fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure> {
+            let inner = op.operand(rewriter, 0).defining_op(rewriter);
+            match inner.filter(|inner| inner.name(rewriter) == "test.neg") {
+                Some(inner) => {
+                    let value = inner.operand(rewriter, 0);
+                    rewriter.replace_op(op, &[value]).expect("replacing the negation");
+                    Ok(())
+                }
+                None => Err(rewriter.notify_match_failure(op, "the operand isn't negated")),
+            }
+        }
+    }
+
+    /// Toggles the `flipped` attribute forever.
+    struct Flip;
+
+    impl RewritePattern for Flip {
+        fn name(&self) -> &str {
+            "Flip"
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            Some("test.flip")
+        }
+
+        fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure> {
+            rewriter.modify_op_in_place(op, |ctx| match ctx.remove_attribute(op, "flipped") {
+                Some(_) => {}
+                None => {
+                    let unit = ctx.unit_attr();
+                    ctx.set_attribute(op, "flipped", unit);
+                }
+            });
+            Ok(())
+        }
+    }
+
+    /// Names the `picked` attribute of any operation after the pattern, once.
+    struct Pick(&'static str, usize);
+
+    impl RewritePattern for Pick {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            None
+        }
+
+        fn benefit(&self) -> usize {
+            self.1
+        }
+
+        fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure> {
+            if op.name(rewriter) != "test.pick" || op.attribute(rewriter, "picked").is_some() {
+                return Err(rewriter.notify_match_failure(op, "not an unpicked `test.pick`"));
+            }
+            let name = rewriter.string_attr(self.0);
+            rewriter.modify_op_in_place(op, |ctx| ctx.set_attribute(op, "picked", name));
+            Ok(())
+        }
+    }
+
+    pub(crate) const SOURCE: &str = r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    %1 = "test.constant"() {value = 2 : i32} : () -> i32
+    %2 = "test.addi"(%0, %1) : (i32, i32) -> i32
+    %3 = "test.constant"() {value = 0 : i32} : () -> i32
+    %4 = "test.addi"(%arg0, %3) : (i32, i32) -> i32
+    %5 = "test.neg"(%4) : (i32) -> i32
+    %6 = "test.neg"(%5) : (i32) -> i32
+    %7 = "test.neg"(%arg0) : (i32) -> i32
+    %8 = "test.muli"(%2, %6) : (i32, i32) -> i32
+    "test.pick"() : () -> ()
+    "func.return"(%8) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+}
+"#;
+
+    pub(crate) const CANONICAL: &str = r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.constant"() {value = 3 : i32} : () -> i32
+    %1 = "test.muli"(%arg0, %0) : (i32, i32) -> i32
+    "test.pick"() {picked = "PickHigh"} : () -> ()
+    "func.return"(%1) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+}
+"#;
+
+    fn patterns(ctx: &Context) -> FrozenRewritePatternSet {
+        let mut patterns = RewritePatternSet::new();
+        for dialect in ctx.dialects().iter() {
+            dialect.canonicalization_patterns(&mut patterns);
+        }
+        patterns.into()
+    }
+
+    #[test]
+    fn should_fold_and_erase_dead_operations() {
+        for top_down in [true, false] {
+            let mut ctx = Context::new();
+            ctx.register_dialect(TestDialect);
+            let module = parse(&mut ctx, SOURCE).unwrap();
+            let patterns = patterns(&ctx);
+            let config = GreedyRewriteConfig {
+                top_down,
+                ..GreedyRewriteConfig::default()
+            };
+            assert!(apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap());
+            assert_eq!(print(&ctx, module, PrinterOptions::default()), CANONICAL);
+            assert!(!apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_apply_patterns_by_benefit() {
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let patterns = patterns(&ctx);
+        let names = |root: &str| {
+            patterns
+                .matching(root)
+                .map(|pattern| pattern.name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("test.neg"), vec!["PickHigh", "NegNeg", "PickLow"]);
+        assert_eq!(names("test.pick"), vec!["PickHigh", "PickLow"]);
+        assert_eq!(patterns.len(), 4);
+    }
+
+    #[test]
+    fn should_report_non_convergence() {
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  "test.flip"() : () -> ()
+}
+"#,
+        )
+        .unwrap();
+        let patterns = patterns(&ctx);
+        let config = GreedyRewriteConfig {
+            max_iterations: 3,
+            ..GreedyRewriteConfig::default()
+        };
+        let error = apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "rewriting within `builtin.module` didn't converge after 3 iterations, the last one kept `Flip` on `test.flip`"
+        );
+
+        let config = GreedyRewriteConfig {
+            max_rewrites: Some(2),
+            ..GreedyRewriteConfig::default()
+        };
+        assert_eq!(
+            apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config),
+            Err(RewriteError::TooManyRewrites {
+                op: "builtin.module".to_string(),
+                limit: 2
+            })
+        );
+    }
+}
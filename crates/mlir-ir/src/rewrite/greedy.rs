@@ -0,0 +1,343 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Worklist-driven greedy rewrite driver, like the upstream `applyPatternsAndFoldGreedily`.
+//!
+
+use std::collections::HashSet;
+
+use crate::rewrite::pattern::{FrozenRewritePatternSet, PatternRewriter};
+use crate::{Attribute, Context, Dialect, OpFoldResult, Operation, RewriteError};
+
+/// Traversal and limits of the greedy rewriting, like the upstream `GreedyRewriteConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GreedyRewriteConfig {
+    /// Visits the operations in pre-order, or in post-order.
+    pub top_down: bool,
+    /// Sweeps over the regions the driver makes until they stop changing.
+    pub max_iterations: usize,
+    /// Rewrites, folds and dead operation erasures the driver applies at most, `None` for no limit.
+    pub max_rewrites: Option<usize>,
+    /// Folds the operations before applying the patterns.
+    pub fold: bool,
+}
+
+impl Default for GreedyRewriteConfig {
+    fn default() -> Self {
+        Self {
+            top_down: true,
+            max_iterations: 10,
+            max_rewrites: None,
+            fold: true,
+        }
+    }
+}
+
+/// Erases the dead operations, folds the operations and applies the patterns within the regions of the operation
+/// until they stop changing. Returns whether anything changed, failing once the limits of the config are hit.
+pub fn apply_patterns_and_fold_greedily(
+    ctx: &mut Context,
+    op: Operation,
+    patterns: &FrozenRewritePatternSet,
+    config: &GreedyRewriteConfig,
+) -> Result<bool, RewriteError> {
+    let mut rewriter = PatternRewriter::new(ctx);
+    let mut changed = false;
+    let mut rewrites = 0;
+    let mut applied = vec![];
+    for _ in 0..config.max_iterations {
+        let mut worklist = Worklist::default();
+        let nested = match config.top_down {
+            true => op.walk(&rewriter).split_off(1),
+            false => {
+                let mut nested = op.walk_post_order(&rewriter);
+                nested.pop();
+                nested
+            }
+        };
+        for nested in nested.into_iter().rev() {
+            worklist.push(nested);
+        }
+        applied.clear();
+        while let Some(current) = worklist.pop() {
+            if !current.is_alive(&rewriter) {
+                continue;
+            }
+            if let Some(rewrite) = process(&mut rewriter, current, patterns, config)? {
+                rewrites += 1;
+                if !applied.contains(&rewrite) {
+                    applied.push(rewrite);
+                }
+                if config.max_rewrites.is_some_and(|limit| rewrites >= limit) {
+                    return Err(RewriteError::TooManyRewrites {
+                        op: op.name(&rewriter).to_string(),
+                        limit: rewrites,
+                    });
+                }
+            }
+            for changed in rewriter.take_changed() {
+                // The rewritten operation is revisited by the next sweep, bounding the patterns always applying to it.
+                if changed != op
+                    && changed != current
+                    && changed.is_alive(&rewriter)
+                    && op.is_ancestor_of(&rewriter, changed)
+                {
+                    worklist.push(changed);
+                }
+            }
+        }
+        if applied.is_empty() {
+            return Ok(changed);
+        }
+        changed = true;
+    }
+    Err(RewriteError::NotConverged {
+        op: op.name(&rewriter).to_string(),
+        iterations: config.max_iterations,
+        applied,
+    })
+}
+
+/// Stack of the operations to visit, without the duplicates.
+#[derive(Default)]
+struct Worklist {
+    stack: Vec<Operation>,
+    pending: HashSet<Operation>,
+}
+
+impl Worklist {
+    fn push(&mut self, op: Operation) {
+        if self.pending.insert(op) {
+            self.stack.push(op);
+        }
+    }
+
+    fn pop(&mut self) -> Option<Operation> {
+        let op = self.stack.pop()?;
+        self.pending.remove(&op);
+        Some(op)
+    }
+}
+
+/// Erases, folds or rewrites the operation, describing what was done for the convergence diagnostics.
+fn process(
+    rewriter: &mut PatternRewriter<'_>,
+    op: Operation,
+    patterns: &FrozenRewritePatternSet,
+    config: &GreedyRewriteConfig,
+) -> Result<Option<String>, RewriteError> {
+    let name = op.name(rewriter).to_string();
+    let dialect = rewriter.dialect_of(op);
+    if let Some(dialect) = &dialect {
+        let unused = op.results(rewriter).iter().all(|result| result.use_empty(rewriter));
+        if unused && dialect.is_pure(rewriter, op) {
+            rewriter.erase_op(op)?;
+            return Ok(Some(format!("erasing the dead `{name}`")));
+        }
+        if config.fold && dialect.constant_value(rewriter, op).is_none() && fold(rewriter, dialect.as_ref(), op)? {
+            return Ok(Some(format!("folding `{name}`")));
+        }
+    }
+    for pattern in patterns.matching(&name) {
+        rewriter.set_insertion_point_before(op);
+        match pattern.match_and_rewrite(op, rewriter) {
+            Ok(()) => return Ok(Some(format!("`{}` on `{name}`", pattern.name()))),
+            Err(_) => {
+                rewriter.take_changed();
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Folds the operation, materializing the constant results before it.
+fn fold(rewriter: &mut PatternRewriter<'_>, dialect: &dyn Dialect, op: Operation) -> Result<bool, RewriteError> {
+    let operands: Vec<Option<Attribute>> = op
+        .operands(rewriter)
+        .iter()
+        .map(|operand| operand.constant_value(rewriter))
+        .collect();
+    let Some(folded) = dialect.fold(rewriter, op, &operands) else {
+        return Ok(false);
+    };
+    let results = op.results(rewriter).to_vec();
+    let in_place = folded.is_empty()
+        || folded
+            .iter()
+            .any(|result| matches!(result, OpFoldResult::Value(value) if results.contains(value)));
+    if in_place {
+        rewriter.modify_op_in_place(op, |_| ());
+        return Ok(true);
+    }
+    rewriter.set_insertion_point_before(op);
+    let location = op.location(rewriter);
+    let mut values = vec![];
+    let mut constants = vec![];
+    for (result, folded) in results.iter().zip(folded) {
+        match folded {
+            OpFoldResult::Value(value) => values.push(value),
+            OpFoldResult::Attribute(attribute) => {
+                let r#type = result.r#type(rewriter);
+                match rewriter.materialize_constant(dialect, attribute, r#type, location) {
+                    Some(constant) => {
+                        constants.push(constant);
+                        values.push(constant.result(rewriter, 0));
+                    }
+                    None => {
+                        for constant in constants {
+                            rewriter.erase_op(constant)?;
+                        }
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+    }
+    rewriter.replace_op(op, &values)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::tests::{TestDialect, SOURCE};
+    use crate::{parse, print, PrinterOptions, RewritePatternSet};
+
+    fn patterns(ctx: &Context) -> FrozenRewritePatternSet {
+        let mut patterns = RewritePatternSet::new();
+        for dialect in ctx.dialects().iter() {
+            dialect.canonicalization_patterns(&mut patterns);
+        }
+        patterns.into()
+    }
+
+    fn parse_test(source: &str) -> (Context, Operation) {
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(&mut ctx, source).unwrap();
+        (ctx, module)
+    }
+
+    #[test]
+    fn should_apply_the_patterns_without_folding() {
+        let (mut ctx, module) = parse_test(SOURCE);
+        let patterns = patterns(&ctx);
+        let config = GreedyRewriteConfig {
+            fold: false,
+            ..GreedyRewriteConfig::default()
+        };
+        assert!(apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap());
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    %1 = "test.constant"() {value = 2 : i32} : () -> i32
+    %2 = "test.addi"(%0, %1) : (i32, i32) -> i32
+    %3 = "test.constant"() {value = 0 : i32} : () -> i32
+    %4 = "test.addi"(%arg0, %3) : (i32, i32) -> i32
+    %5 = "test.muli"(%2, %4) : (i32, i32) -> i32
+    "test.pick"() {picked = "PickHigh"} : () -> ()
+    "func.return"(%5) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_rewrite_only_within_the_operation() {
+        let source = r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.neg"(%arg0) : (i32) -> i32
+    %1 = "test.neg"(%0) : (i32) -> i32
+    "func.return"(%1) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.neg"(%arg0) : (i32) -> i32
+    %1 = "test.neg"(%0) : (i32) -> i32
+    "func.return"(%1) : (i32) -> ()
+  }) {sym_name = "g"} : () -> ()
+}
+"#;
+        let (mut ctx, module) = parse_test(source);
+        let patterns = patterns(&ctx);
+        let function = module.walk(&ctx)[1];
+        assert!(
+            apply_patterns_and_fold_greedily(&mut ctx, function, &patterns, &GreedyRewriteConfig::default()).unwrap()
+        );
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    "func.return"(%arg0) : (i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+  "func.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "test.neg"(%arg0) : (i32) -> i32
+    %1 = "test.neg"(%0) : (i32) -> i32
+    "func.return"(%1) : (i32) -> ()
+  }) {sym_name = "g"} : () -> ()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn should_converge_within_the_iteration_limit() {
+        let (mut ctx, module) = parse_test(SOURCE);
+        let patterns = patterns(&ctx);
+        let config = GreedyRewriteConfig {
+            max_iterations: 1,
+            ..GreedyRewriteConfig::default()
+        };
+        let error = apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap_err();
+        assert!(
+            matches!(&error, RewriteError::NotConverged { iterations: 1, applied, .. } if !applied.is_empty()),
+            "{error:?}"
+        );
+
+        let (mut ctx, module) = parse_test(SOURCE);
+        let config = GreedyRewriteConfig {
+            max_iterations: 2,
+            ..GreedyRewriteConfig::default()
+        };
+        assert!(apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap());
+    }
+
+    #[test]
+    fn should_leave_the_unchanged_ir_alone() {
+        let (mut ctx, module) = parse_test(
+            r#"module {
+  %0 = "test.constant"() {value = 1 : i32} : () -> i32
+  "test.use"(%0) : (i32) -> ()
+}
+"#,
+        );
+        let patterns = patterns(&ctx);
+        let config = GreedyRewriteConfig {
+            max_iterations: 1,
+            max_rewrites: Some(1),
+            ..GreedyRewriteConfig::default()
+        };
+        assert!(!apply_patterns_and_fold_greedily(&mut ctx, module, &patterns, &config).unwrap());
+    }
+}
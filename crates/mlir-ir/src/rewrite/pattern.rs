@@ -0,0 +1,384 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Rewrite patterns and the rewriter notifying the driver of the changed operations,
+//! like the upstream `RewritePattern` and `PatternRewriter`.
+//!
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    Attribute, Builder, Context, Dialect, InsertionPoint, IrError, Operation, OperationState, Type, Value,
+};
+
+/// Rewrite pattern of a root operation, like the upstream `RewritePattern`.
+pub trait RewritePattern: Send + Sync {
+    /// Debug name of the pattern, like `NegNeg`, for the diagnostics and the `disable-patterns` option.
+    fn name(&self) -> &str;
+
+    /// Name of the matched root operation, like `arith.addi`, `None` to match any.
+    fn root_name(&self) -> Option<&str>;
+
+    /// The patterns with the higher benefit are tried first.
+    fn benefit(&self) -> usize {
+        1
+    }
+
+    /// Matches the root operation, rewriting it on success, the failures must leave the IR untouched.
+    fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure>;
+}
+
+/// Reason the pattern didn't match, like the upstream `notifyMatchFailure`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0}")]
+pub struct MatchFailure(pub String);
+
+/// Rewrite patterns, like the upstream `RewritePatternSet`.
+#[derive(Default)]
+pub struct RewritePatternSet {
+    patterns: Vec<Box<dyn RewritePattern>>,
+}
+
+impl RewritePatternSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, pattern: impl RewritePattern + 'static) -> &mut Self {
+        self.patterns.push(Box::new(pattern));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn RewritePattern> {
+        self.patterns.iter().map(Box::as_ref)
+    }
+
+    /// Keeps the patterns the predicate accepts, like the `disable-patterns` and `enable-patterns` options.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&dyn RewritePattern) -> bool) {
+        self.patterns.retain(|pattern| predicate(pattern.as_ref()));
+    }
+}
+
+/// Patterns ordered by their benefit for every root name, like the upstream `FrozenRewritePatternSet`.
+pub struct FrozenRewritePatternSet {
+    patterns: Vec<Box<dyn RewritePattern>>,
+    /// Patterns of the root names, with the ones matching any operation.
+    by_root: HashMap<String, Vec<usize>>,
+    any: Vec<usize>,
+}
+
+impl FrozenRewritePatternSet {
+    /// Patterns of the root operation, the higher benefit ones go first, the equal ones keep their order.
+    pub fn matching(&self, root_name: &str) -> impl Iterator<Item = &dyn RewritePattern> {
+        self.by_root
+            .get(root_name)
+            .unwrap_or(&self.any)
+            .iter()
+            .map(|index| self.patterns[*index].as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+impl From<RewritePatternSet> for FrozenRewritePatternSet {
+    fn from(set: RewritePatternSet) -> Self {
+        let patterns = set.patterns;
+        let by_benefit =
+            |indices: &mut Vec<usize>| indices.sort_by_key(|index| std::cmp::Reverse(patterns[*index].benefit()));
+        let mut any: Vec<usize> = (0..patterns.len())
+            .filter(|index| patterns[*index].root_name().is_none())
+            .collect();
+        by_benefit(&mut any);
+        let mut by_root: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, pattern) in patterns.iter().enumerate() {
+            if let Some(root) = pattern.root_name() {
+                by_root.entry(root.to_string()).or_default().push(index);
+            }
+        }
+        for indices in by_root.values_mut() {
+            indices.extend(&any);
+            indices.sort();
+            by_benefit(indices);
+        }
+        Self { patterns, by_root, any }
+    }
+}
+
+impl fmt::Debug for FrozenRewritePatternSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.patterns.iter().map(|pattern| pattern.name()))
+            .finish()
+    }
+}
+
+/// Builder of the rewrites, recording the changed operations for the driver worklist.
+/// The patterns change the IR through the rewriter, the [Context] it dereferences to is for the types
+/// and the attributes.
+pub struct PatternRewriter<'c> {
+    builder: Builder<'c>,
+    changed: Vec<Operation>,
+}
+
+impl<'c> PatternRewriter<'c> {
+    pub fn new(ctx: &'c mut Context) -> Self {
+        Self {
+            builder: Builder::new(ctx),
+            changed: vec![],
+        }
+    }
+
+    /// Created, modified and possibly dead operations since the last call.
+    pub(crate) fn take_changed(&mut self) -> Vec<Operation> {
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn insertion_point(&self) -> Option<InsertionPoint> {
+        self.builder.insertion_point()
+    }
+
+    pub fn set_insertion_point(&mut self, insertion_point: InsertionPoint) {
+        self.builder.set_insertion_point(insertion_point);
+    }
+
+    pub fn set_insertion_point_before(&mut self, op: Operation) {
+        self.builder.set_insertion_point_before(op);
+    }
+
+    pub fn set_insertion_point_after(&mut self, op: Operation) {
+        self.builder.set_insertion_point_after(op);
+    }
+
+    /// Creates the operation and inserts it at the insertion point.
+    pub fn create(&mut self, state: OperationState) -> Operation {
+        let op = self.builder.create(state);
+        self.changed.push(op);
+        op
+    }
+
+    /// Inserts the detached operation at the insertion point.
+    pub fn insert(&mut self, op: Operation) -> Operation {
+        self.changed.push(op);
+        self.builder.insert(op)
+    }
+
+    /// Changes the operation through the closure, like the upstream `modifyOpInPlace`.
+    pub fn modify_op_in_place<R>(&mut self, op: Operation, modify: impl FnOnce(&mut Context) -> R) -> R {
+        let result = modify(&mut self.builder);
+        self.changed.push(op);
+        self.notify_users(op);
+        result
+    }
+
+    /// Replaces the uses of the value, their owners are revisited.
+    pub fn replace_all_uses_with(&mut self, from: Value, to: Value) {
+        self.changed.extend(from.users(&self.builder));
+        self.builder.replace_all_uses_with(from, to);
+    }
+
+    /// Replaces the uses of the results with the values, then erases the operation.
+    pub fn replace_op(&mut self, op: Operation, values: &[Value]) -> Result<(), IrError> {
+        self.notify_users(op);
+        self.notify_operands(op);
+        self.builder.replace_operation(op, values)
+    }
+
+    /// Creates the operation before the replaced one, replacing its results.
+    pub fn replace_op_with_new(&mut self, op: Operation, state: OperationState) -> Result<Operation, IrError> {
+        self.set_insertion_point_before(op);
+        let new = self.create(state);
+        let results = new.results(&self.builder).to_vec();
+        self.replace_op(op, &results)?;
+        Ok(new)
+    }
+
+    /// Erases the operation without uses, the operations defining its operands are revisited.
+    pub fn erase_op(&mut self, op: Operation) -> Result<(), IrError> {
+        self.notify_operands(op);
+        self.builder.erase_operation(op)
+    }
+
+    /// Creates the constant of the folded value through the dialect at the insertion point.
+    pub(crate) fn materialize_constant(
+        &mut self,
+        dialect: &dyn Dialect,
+        value: Attribute,
+        r#type: Type,
+        location: Attribute,
+    ) -> Option<Operation> {
+        let constant = dialect.materialize_constant(&mut self.builder, value, r#type, location)?;
+        self.changed.push(constant);
+        Some(constant)
+    }
+
+    /// Reason the pattern didn't match the operation.
+    pub fn notify_match_failure(&self, op: Operation, reason: impl fmt::Display) -> MatchFailure {
+        MatchFailure(format!("`{}`: {reason}", op.name(&self.builder)))
+    }
+
+    fn notify_users(&mut self, op: Operation) {
+        let ctx: &Context = &self.builder;
+        let users = op.results(ctx).iter().flat_map(|result| result.users(ctx));
+        self.changed.extend(users);
+    }
+
+    fn notify_operands(&mut self, op: Operation) {
+        let ctx: &Context = &self.builder;
+        let defining = op.operands(ctx).iter().filter_map(|operand| operand.defining_op(ctx));
+        self.changed.extend(defining);
+    }
+}
+
+impl Deref for PatternRewriter<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.builder
+    }
+}
+
+impl DerefMut for PatternRewriter<'_> {
+    fn deref_mut(&mut self) -> &mut Context {
+        &mut self.builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, print, PrinterOptions};
+
+    /// Matches nothing, named after its root and benefit.
+    struct Named(&'static str, Option<&'static str>, usize);
+
+    impl RewritePattern for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn root_name(&self) -> Option<&str> {
+            self.1
+        }
+
+        fn benefit(&self) -> usize {
+            self.2
+        }
+
+        fn match_and_rewrite(&self, op: Operation, rewriter: &mut PatternRewriter<'_>) -> Result<(), MatchFailure> {
+            Err(rewriter.notify_match_failure(op, "never matches"))
+        }
+    }
+
+    const SOURCE: &str = r#""builtin.module"() ({
+^bb0(%arg0: i32):
+  %0 = "test.def"(%arg0) : (i32) -> i32
+  %1 = "test.neg"(%0) : (i32) -> i32
+  "test.use"(%1) : (i32) -> ()
+}) : () -> ()
+"#;
+
+    #[test]
+    fn should_freeze_the_patterns_by_root_and_benefit() {
+        let mut set = RewritePatternSet::new();
+        set.add(Named("a", Some("test.neg"), 1))
+            .add(Named("any", None, 2))
+            .add(Named("b", Some("test.neg"), 3))
+            .add(Named("c", Some("test.neg"), 1))
+            .add(Named("dropped", Some("test.neg"), 5))
+            .add(Named("d", Some("test.def"), 1));
+        set.retain(|pattern| pattern.name() != "dropped");
+        assert_eq!(set.len(), 5);
+        let frozen = FrozenRewritePatternSet::from(set);
+        let names = |root: &str| frozen.matching(root).map(|pattern| pattern.name()).collect::<Vec<_>>();
+        assert_eq!(names("test.neg"), vec!["b", "any", "a", "c"]);
+        assert_eq!(names("test.def"), vec!["any", "d"]);
+        assert_eq!(names("test.unknown"), vec!["any"]);
+        assert_eq!(format!("{frozen:?}"), r#"["a", "any", "b", "c", "d"]"#);
+
+        let empty = FrozenRewritePatternSet::from(RewritePatternSet::new());
+        assert!(empty.is_empty() && empty.matching("test.neg").next().is_none());
+    }
+
+    #[test]
+    fn should_record_the_changed_operations() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let [_, def, neg, r#use] = module.walk(&ctx)[..] else {
+            panic!("expected the module with three operations");
+        };
+        let mut rewriter = PatternRewriter::new(&mut ctx);
+        let failure = rewriter.notify_match_failure(neg, "the operand isn't negated");
+        assert_eq!(failure.to_string(), "`test.neg`: the operand isn't negated");
+
+        let r#type = neg.result(&rewriter, 0).r#type(&rewriter);
+        let mut state = OperationState::new("test.abs");
+        state.add_operands([def.result(&rewriter, 0)]).add_types([r#type]);
+        let abs = rewriter.replace_op_with_new(neg, state).unwrap();
+        assert_eq!(rewriter.take_changed(), vec![abs, r#use, def]);
+        assert!(rewriter.take_changed().is_empty());
+
+        rewriter.modify_op_in_place(abs, |ctx| {
+            let unit = ctx.unit_attr();
+            ctx.set_attribute(abs, "test.modified", unit);
+        });
+        assert_eq!(rewriter.take_changed(), vec![abs, r#use]);
+
+        rewriter.erase_op(r#use).unwrap();
+        assert_eq!(rewriter.take_changed(), vec![abs]);
+        assert_eq!(
+            rewriter.erase_op(def),
+            Err(IrError::ResultInUse {
+                op: "test.def".to_string(),
+                index: 0
+            })
+        );
+        rewriter.take_changed();
+
+        let argument = def.operand(&rewriter, 0);
+        rewriter.replace_all_uses_with(def.result(&rewriter, 0), argument);
+        assert_eq!(rewriter.take_changed(), vec![abs]);
+        let generic = PrinterOptions {
+            generic_op_form: true,
+            ..PrinterOptions::default()
+        };
+        assert_eq!(
+            print(&ctx, module, generic),
+            r#""builtin.module"() ({
+^bb0(%arg0: i32):
+  %0 = "test.def"(%arg0) : (i32) -> i32
+  %1 = "test.abs"(%arg0) {test.modified} : (i32) -> i32
+}) : () -> ()
+"#
+        );
+    }
+}
@@ -0,0 +1,165 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Symbols named by their `sym_name` attributes within the symbol table operations, and the resolution of
+//! the `@root::@nested` references, like the upstream `SymbolTable` and `SymbolTableCollection`.
+//!
+
+use std::collections::HashMap;
+
+use crate::{Attribute, AttributeKind, Context, OpTrait, Operation};
+
+/// Symbols defined directly within the block of a symbol table operation, the first definition of a name wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTable {
+    op: Operation,
+    symbols: HashMap<String, Operation>,
+}
+
+impl SymbolTable {
+    /// Name of the attribute holding the symbol name.
+    pub const SYMBOL_NAME: &'static str = "sym_name";
+
+    pub fn new(ctx: &Context, op: Operation) -> Self {
+        let mut symbols = HashMap::new();
+        for region in op.regions(ctx) {
+            for block in region.blocks(ctx) {
+                for nested in block.operations(ctx) {
+                    if let Some(name) = Self::symbol_name(ctx, *nested) {
+                        symbols.entry(name.to_string()).or_insert(*nested);
+                    }
+                }
+            }
+        }
+        Self { op, symbols }
+    }
+
+    /// The symbol table operation.
+    pub fn op(&self) -> Operation {
+        self.op
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Operation> {
+        self.symbols.get(name).copied()
+    }
+
+    /// Name of the symbol operation.
+    pub fn symbol_name(ctx: &Context, op: Operation) -> Option<&str> {
+        op.attribute(ctx, Self::SYMBOL_NAME)?.as_string(ctx)
+    }
+
+    pub fn is_symbol_table(ctx: &Context, op: Operation) -> bool {
+        op.has_trait(ctx, OpTrait::SymbolTable)
+    }
+
+    /// The closest symbol table operation enclosing the operation, or the operation itself.
+    pub fn nearest_symbol_table(ctx: &Context, mut op: Operation) -> Option<Operation> {
+        loop {
+            if Self::is_symbol_table(ctx, op) {
+                return Some(op);
+            }
+            op = op.parent_op(ctx)?;
+        }
+    }
+}
+
+/// Cache of the symbol tables, to be cleared once the symbols change, like the upstream `SymbolTableCollection`.
+#[derive(Debug, Default)]
+pub struct SymbolTableCollection {
+    tables: HashMap<Operation, SymbolTable>,
+}
+
+impl SymbolTableCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn symbol_table(&mut self, ctx: &Context, op: Operation) -> &SymbolTable {
+        self.tables.entry(op).or_insert_with(|| SymbolTable::new(ctx, op))
+    }
+
+    /// Resolves the `@root::@nested` reference within the symbol table operation.
+    pub fn lookup_symbol_in(&mut self, ctx: &Context, table: Operation, symbol: Attribute) -> Option<Operation> {
+        let AttributeKind::SymbolRef { root, nested } = symbol.kind(ctx) else {
+            return None;
+        };
+        let mut op = self.symbol_table(ctx, table).lookup(root)?;
+        for name in nested {
+            if !SymbolTable::is_symbol_table(ctx, op) {
+                return None;
+            }
+            op = self.symbol_table(ctx, op).lookup(name)?;
+        }
+        Some(op)
+    }
+
+    /// Resolves the reference within the closest symbol table enclosing the operation.
+    pub fn lookup_nearest_symbol_from(
+        &mut self,
+        ctx: &Context,
+        from: Operation,
+        symbol: Attribute,
+    ) -> Option<Operation> {
+        let table = SymbolTable::nearest_symbol_table(ctx, from)?;
+        self.lookup_symbol_in(ctx, table, symbol)
+    }
+
+    /// Drops the cached tables, once the symbols are added, renamed or erased.
+    pub fn invalidate(&mut self) {
+        self.tables.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, parse_attribute};
+
+    #[test]
+    fn should_resolve_nested_references() {
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#""builtin.module"() ({
+  "builtin.module"() ({
+    "test.symbol"() {sym_name = "g"} : () -> ()
+  }) {sym_name = "inner"} : () -> ()
+  "test.symbol"() {sym_name = "f"} : () -> ()
+  "test.symbol"() {sym_name = "f"} : () -> ()
+}) : () -> ()
+"#,
+        )
+        .unwrap();
+        let body = module.region(&ctx, 0).blocks(&ctx)[0].operations(&ctx).to_vec();
+        let inner = body[0];
+        let g = inner.region(&ctx, 0).blocks(&ctx)[0].operations(&ctx)[0];
+
+        let table = SymbolTable::new(&ctx, module);
+        assert_eq!(table.lookup("f"), Some(body[1]));
+        assert_eq!(table.lookup("g"), None);
+        assert_eq!(SymbolTable::symbol_name(&ctx, g), Some("g"));
+        assert_eq!(SymbolTable::nearest_symbol_table(&ctx, g), Some(inner));
+
+        let mut symbols = SymbolTableCollection::new();
+        let nested = parse_attribute(&mut ctx, "@inner::@g").unwrap();
+        let flat = parse_attribute(&mut ctx, "@f").unwrap();
+        assert_eq!(symbols.lookup_symbol_in(&ctx, module, nested), Some(g));
+        assert_eq!(symbols.lookup_nearest_symbol_from(&ctx, g, flat), None);
+        assert_eq!(symbols.lookup_nearest_symbol_from(&ctx, inner, flat), None);
+        assert_eq!(symbols.lookup_nearest_symbol_from(&ctx, body[2], flat), Some(body[1]));
+    }
+}
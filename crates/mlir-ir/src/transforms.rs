@@ -0,0 +1,39 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Dialect-independent passes of the core IR, like the upstream `mlir/Transforms` ones.
+//!
+
+mod canonicalize;
+mod cse;
+mod sccp;
+mod symbol_dce;
+
+pub use canonicalize::{Canonicalizer, CanonicalizerOptions};
+pub use cse::Cse;
+pub use sccp::Sccp;
+pub use symbol_dce::SymbolDce;
+
+use crate::PassRegistry;
+
+/// Registers the dialect-independent passes by their pipeline arguments.
+pub fn register_transforms(registry: &mut PassRegistry) {
+    registry.register_with_options("Canonicalize operations", Canonicalizer::new);
+    registry.register::<Cse>("cse", "Eliminate common sub-expressions");
+    registry.register::<Sccp>("sccp", "Sparse Conditional Constant Propagation");
+    registry.register::<SymbolDce>("symbol-dce", "Eliminate dead symbols");
+}
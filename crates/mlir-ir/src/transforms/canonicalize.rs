@@ -0,0 +1,226 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `canonicalize` pass, applying the canonicalization patterns of the registered dialects
+//! and folding the operations greedily, like the upstream `Canonicalizer`.
+//!
+
+use std::sync::Arc;
+
+use crate::passes::{parse_option, PassOptionError, PassOptionValue, PassOptions};
+
+use crate::{
+    apply_patterns_and_fold_greedily, AnalysisManager, Context, FrozenRewritePatternSet, GreedyRewriteConfig,
+    Operation, Pass, PassFailure, PreservedAnalyses, RewriteError, RewritePatternSet,
+};
+
+/// Options of the `canonicalize` pass, the upstream ones but `region-simplify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalizerOptions {
+    pub top_down: bool,
+    pub max_iterations: i64,
+    /// Negative for no limit.
+    pub max_num_rewrites: i64,
+    /// Fails the pass if the rewriting doesn't converge.
+    pub test_convergence: bool,
+    /// Names of the patterns left out.
+    pub disable_patterns: Vec<String>,
+    /// Names of the only patterns applied, all of them if empty.
+    pub enable_patterns: Vec<String>,
+}
+
+impl Default for CanonicalizerOptions {
+    fn default() -> Self {
+        Self {
+            top_down: true,
+            max_iterations: 10,
+            max_num_rewrites: -1,
+            test_convergence: false,
+            disable_patterns: vec![],
+            enable_patterns: vec![],
+        }
+    }
+}
+
+impl PassOptions for CanonicalizerOptions {
+    const ARGUMENT: &'static str = "canonicalize";
+
+    fn set_option(&mut self, option: &str, value: &str) -> Result<(), PassOptionError> {
+        match option {
+            "top-down" => self.top_down = parse_option(Self::ARGUMENT, option, value)?,
+            "max-iterations" => self.max_iterations = parse_option(Self::ARGUMENT, option, value)?,
+            "max-num-rewrites" => self.max_num_rewrites = parse_option(Self::ARGUMENT, option, value)?,
+            "test-convergence" => self.test_convergence = parse_option(Self::ARGUMENT, option, value)?,
+            "disable-patterns" => self.disable_patterns = parse_option(Self::ARGUMENT, option, value)?,
+            "enable-patterns" => self.enable_patterns = parse_option(Self::ARGUMENT, option, value)?,
+            _ => {
+                return Err(PassOptionError::UnknownOption {
+                    pass: Self::ARGUMENT,
+                    option: option.to_string(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// The empty pattern lists are left out, like upstream.
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        if !self.disable_patterns.is_empty() {
+            options.push(("disable-patterns", self.disable_patterns.print_value()));
+        }
+        if !self.enable_patterns.is_empty() {
+            options.push(("enable-patterns", self.enable_patterns.print_value()));
+        }
+        options.extend([
+            ("max-iterations", self.max_iterations.print_value()),
+            ("max-num-rewrites", self.max_num_rewrites.print_value()),
+            ("test-convergence", self.test_convergence.print_value()),
+            ("top-down", self.top_down.print_value()),
+        ]);
+        options
+    }
+}
+
+/// Canonicalizes the operations with the patterns and the folders of the registered dialects.
+#[derive(Debug, Clone, Default)]
+pub struct Canonicalizer {
+    options: CanonicalizerOptions,
+    /// Patterns gathered on the first run.
+    patterns: Option<Arc<FrozenRewritePatternSet>>,
+}
+
+impl Canonicalizer {
+    pub fn new(options: CanonicalizerOptions) -> Self {
+        Self {
+            options,
+            patterns: None,
+        }
+    }
+
+    fn patterns(&mut self, ctx: &Context) -> Arc<FrozenRewritePatternSet> {
+        let options = &self.options;
+        self.patterns
+            .get_or_insert_with(|| {
+                let mut patterns = RewritePatternSet::new();
+                for dialect in ctx.dialects().iter() {
+                    dialect.canonicalization_patterns(&mut patterns);
+                }
+                patterns.retain(|pattern| {
+                    let name = pattern.name().to_string();
+                    !options.disable_patterns.contains(&name)
+                        && (options.enable_patterns.is_empty() || options.enable_patterns.contains(&name))
+                });
+                Arc::new(patterns.into())
+            })
+            .clone()
+    }
+}
+
+impl Pass for Canonicalizer {
+    fn argument(&self) -> &str {
+        CanonicalizerOptions::ARGUMENT
+    }
+
+    fn name(&self) -> &str {
+        "Canonicalizer"
+    }
+
+    fn print_pipeline(&self) -> String {
+        self.options.print_options()
+    }
+
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        _analyses: &mut AnalysisManager,
+    ) -> Result<PreservedAnalyses, PassFailure> {
+        let config = GreedyRewriteConfig {
+            top_down: self.options.top_down,
+            max_iterations: self.options.max_iterations.max(0) as usize,
+            max_rewrites: usize::try_from(self.options.max_num_rewrites).ok(),
+            fold: true,
+        };
+        let patterns = self.patterns(ctx);
+        match apply_patterns_and_fold_greedily(ctx, op, &patterns, &config) {
+            Ok(false) => Ok(PreservedAnalyses::all()),
+            Ok(true) => Ok(PreservedAnalyses::none()),
+            Err(RewriteError::Ir(error)) => Err(PassFailure::new(error.to_string())),
+            Err(error) if self.options.test_convergence => Err(PassFailure::new(error.to_string())),
+            Err(_) => Ok(PreservedAnalyses::none()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::tests::{TestDialect, CANONICAL, SOURCE};
+    use crate::{parse, print, register_transforms, PassError, PassManager, PassRegistry, PrinterOptions};
+
+    #[test]
+    fn should_canonicalize_with_registered_dialects() {
+        let mut registry = PassRegistry::new();
+        register_transforms(&mut registry);
+        let mut pm =
+            PassManager::parse("builtin.module(func.func(canonicalize{max-iterations=4}))", &registry).unwrap();
+        assert_eq!(
+            pm.to_string(),
+            "builtin.module(func.func(canonicalize{max-iterations=4 max-num-rewrites=-1 test-convergence=false top-down=true}))"
+        );
+
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        assert_eq!(print(&ctx, module, PrinterOptions::default()), CANONICAL);
+
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let mut pm = PassManager::parse("builtin.module(canonicalize{disable-patterns=PickHigh})", &registry).unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        assert!(print(&ctx, module, PrinterOptions::default()).contains(r#"{picked = "PickLow"}"#));
+    }
+
+    #[test]
+    fn should_fail_on_non_convergence_when_testing_it() {
+        let mut registry = PassRegistry::new();
+        register_transforms(&mut registry);
+        let source = r#"module {
+  "test.flip"() : () -> ()
+}
+"#;
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(&mut ctx, source).unwrap();
+        PassManager::parse("builtin.module(canonicalize)", &registry)
+            .unwrap()
+            .run(&mut ctx, module)
+            .unwrap();
+        let error = PassManager::parse("builtin.module(canonicalize{test-convergence=true})", &registry)
+            .unwrap()
+            .run(&mut ctx, module)
+            .unwrap_err();
+        assert!(
+            matches!(&error, PassError::Failed { pass, message, .. }
+                if pass == "canonicalize" && message.contains("didn't converge after 10 iterations")),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `cse` pass, replacing the results of the operations without side effects by the ones of the identical
+//! operations dominating them, like the upstream `CSE`.
+//!
+
+use std::collections::HashMap;
+
+use crate::{
+    AnalysisManager, Context, DominanceInfo, OpTrait, Operation, Pass, PassFailure, PostDominanceInfo,
+    PreservedAnalyses, Value,
+};
+
+/// Eliminates the common subexpressions of the pure operations without regions, within the closest
+/// operation isolated from above.
+#[derive(Debug, Clone, Default)]
+pub struct Cse;
+
+/// The closest operation isolated from above enclosing the operation, the scope of its equivalent ones.
+fn isolated_scope(ctx: &Context, op: Operation) -> Option<Operation> {
+    let mut parent = op.parent_op(ctx)?;
+    while !parent.has_trait(ctx, OpTrait::IsolatedFromAbove) {
+        parent = match parent.parent_op(ctx) {
+            Some(grandparent) => grandparent,
+            None => return Some(parent),
+        };
+    }
+    Some(parent)
+}
+
+/// Whether the operation can be replaced by an identical one: pure, with results and without regions.
+fn is_candidate(ctx: &Context, op: Operation) -> bool {
+    !op.results(ctx).is_empty()
+        && op.regions(ctx).is_empty()
+        && op.successors(ctx).is_empty()
+        && !op.has_trait(ctx, OpTrait::Terminator)
+        && ctx.dialect_of(op).is_some_and(|dialect| dialect.is_pure(ctx, op))
+}
+
+impl Pass for Cse {
+    fn argument(&self) -> &str {
+        "cse"
+    }
+
+    fn name(&self) -> &str {
+        "CSE"
+    }
+
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        analyses: &mut AnalysisManager,
+    ) -> Result<PreservedAnalyses, PassFailure> {
+        let dominance = analyses.get::<DominanceInfo>(ctx, op).clone();
+        let mut known: HashMap<(Option<Operation>, String, Vec<Value>), Vec<Operation>> = HashMap::new();
+        let mut changed = false;
+        for nested in op.walk(ctx) {
+            if nested == op || !is_candidate(ctx, nested) {
+                continue;
+            }
+            let key = (
+                isolated_scope(ctx, nested),
+                nested.name(ctx).to_string(),
+                nested.operands(ctx).to_vec(),
+            );
+            let equivalents = known.entry(key).or_default();
+            let existing = equivalents.iter().copied().find(|existing| {
+                existing.result_types(ctx) == nested.result_types(ctx)
+                    && existing.properties(ctx) == nested.properties(ctx)
+                    && existing.attributes(ctx) == nested.attributes(ctx)
+                    && dominance.properly_dominates(ctx, *existing, nested)
+            });
+            let Some(existing) = existing else {
+                equivalents.push(nested);
+                continue;
+            };
+            let replacements: Vec<(Value, Value)> = nested
+                .results(ctx)
+                .iter()
+                .copied()
+                .zip(existing.results(ctx).iter().copied())
+                .collect();
+            for (from, to) in replacements {
+                ctx.replace_all_uses_with(from, to);
+            }
+            ctx.erase_operation(nested)
+                .map_err(|error| PassFailure::on(ctx, nested, error.to_string()))?;
+            changed = true;
+        }
+        match changed {
+            true => Ok(PreservedAnalyses::none()
+                .preserve::<DominanceInfo>()
+                .preserve::<PostDominanceInfo>()),
+            false => Ok(PreservedAnalyses::all()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rewrite::tests::TestDialect;
+    use crate::{parse, print, register_transforms, Context, PassManager, PassRegistry, PrinterOptions};
+
+    #[test]
+    fn should_eliminate_the_dominated_common_subexpressions() {
+        let mut registry = PassRegistry::new();
+        register_transforms(&mut registry);
+        let mut pm = PassManager::parse("builtin.module(cse)", &registry).unwrap();
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    %1 = "test.constant"() {value = 1 : i32} : () -> i32
+    %2 = "test.constant"() {value = 2 : i32} : () -> i32
+    %3 = "test.addi"(%arg0, %0) : (i32, i32) -> i32
+    %4 = "test.addi"(%arg0, %1) : (i32, i32) -> i32
+    %5 = "test.effect"(%arg0) : (i32) -> i32
+    %6 = "test.effect"(%arg0) : (i32) -> i32
+    "cf.cond_br"(%arg1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:  // pred: ^bb0
+    %7 = "test.addi"(%3, %2) : (i32, i32) -> i32
+    "func.return"(%7) : (i32) -> ()
+  ^bb2:  // pred: ^bb0
+    %8 = "test.addi"(%3, %2) : (i32, i32) -> i32
+    "test.use"(%4, %5, %6, %8) : (i32, i32, i32, i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+  "func.func"() ({
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    "func.return"(%0) : (i32) -> ()
+  }) {sym_name = "g"} : () -> ()
+}
+"#,
+        )
+        .unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "func.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    %1 = "test.constant"() {value = 2 : i32} : () -> i32
+    %2 = "test.addi"(%arg0, %0) : (i32, i32) -> i32
+    %3 = "test.effect"(%arg0) : (i32) -> i32
+    %4 = "test.effect"(%arg0) : (i32) -> i32
+    "cf.cond_br"(%arg1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:  // pred: ^bb0
+    %5 = "test.addi"(%2, %1) : (i32, i32) -> i32
+    "func.return"(%5) : (i32) -> ()
+  ^bb2:  // pred: ^bb0
+    %6 = "test.addi"(%2, %1) : (i32, i32) -> i32
+    "test.use"(%2, %3, %4, %6) : (i32, i32, i32, i32) -> ()
+  }) {sym_name = "f"} : () -> ()
+  "func.func"() ({
+    %0 = "test.constant"() {value = 1 : i32} : () -> i32
+    "func.return"(%0) : (i32) -> ()
+  }) {sym_name = "g"} : () -> ()
+}
+"#
+        );
+    }
+}
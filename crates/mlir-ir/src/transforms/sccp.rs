@@ -0,0 +1,213 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `sccp` pass, replacing the values proven constant by the [SparseConstantPropagation] along the executable
+//! blocks found by the [DeadCodeAnalysis], like the upstream `SCCP`.
+//! The lattice doesn't keep the dialect the constants come from, they're materialized by the dialect of the
+//! operation defining the value or owning the block of the argument, and left in place if it can't.
+//!
+
+use crate::{
+    AnalysisManager, Attribute, Block, Builder, ConstantValue, Context, DataFlowSolver, DeadCodeAnalysis, Executable,
+    Operation, Pass, PassFailure, PreservedAnalyses, SparseConstantPropagation, SparseForward, Value,
+};
+
+/// Sparse conditional constant propagation.
+#[derive(Debug, Clone, Default)]
+pub struct Sccp;
+
+/// Where the constant of a value is materialized.
+enum Site {
+    Before(Operation),
+    Start(Block),
+}
+
+/// Creates the constant through the dialect of the operation and replaces the uses of the value by it.
+fn replace_with_constant(ctx: &mut Context, value: Value, constant: Attribute, owner: Operation, site: Site) -> bool {
+    let Some(dialect) = ctx.dialect_of(owner) else {
+        return false;
+    };
+    let (r#type, location) = (value.r#type(ctx), owner.location(ctx));
+    let mut builder = Builder::new(ctx);
+    match site {
+        Site::Before(op) => builder.set_insertion_point_before(op),
+        Site::Start(block) => builder.set_insertion_point_to_start(block),
+    }
+    let Some(materialized) = dialect.materialize_constant(&mut builder, constant, r#type, location) else {
+        return false;
+    };
+    let replacement = materialized.result(ctx, 0);
+    ctx.replace_all_uses_with(value, replacement);
+    true
+}
+
+impl Pass for Sccp {
+    fn argument(&self) -> &str {
+        "sccp"
+    }
+
+    fn name(&self) -> &str {
+        "SCCP"
+    }
+
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        _analyses: &mut AnalysisManager,
+    ) -> Result<PreservedAnalyses, PassFailure> {
+        let mut solver = DataFlowSolver::new();
+        solver
+            .load(DeadCodeAnalysis::new())
+            .load(SparseForward::new(SparseConstantPropagation));
+        solver.initialize_and_run(ctx, op);
+
+        let constant = |ctx: &Context, value: Value| match value.use_empty(ctx) {
+            true => None,
+            false => solver.lookup::<ConstantValue>(value).and_then(|value| value.constant()),
+        };
+        let mut blocks: Vec<(Operation, Block)> = vec![];
+        for owner in op.walk(ctx) {
+            for region in owner.regions(ctx) {
+                blocks.extend(region.blocks(ctx).iter().map(|block| (owner, *block)));
+            }
+        }
+        blocks.retain(|(_, block)| solver.lookup::<Executable>(*block).is_some_and(|state| state.is_live()));
+        let mut changed = false;
+        let mut dead = vec![];
+        for (owner, block) in blocks {
+            let arguments = block.arguments(ctx).to_vec();
+            for argument in arguments {
+                if let Some(value) = constant(ctx, argument) {
+                    changed |= replace_with_constant(ctx, argument, value, owner, Site::Start(block));
+                }
+            }
+            let operations = block.operations(ctx).to_vec();
+            for nested in operations {
+                let Some(dialect) = ctx.dialect_of(nested) else {
+                    continue;
+                };
+                if dialect.constant_value(ctx, nested).is_some() {
+                    continue;
+                }
+                let results = nested.results(ctx).to_vec();
+                for result in results {
+                    if let Some(value) = constant(ctx, result) {
+                        changed |= replace_with_constant(ctx, result, value, nested, Site::Before(nested));
+                    }
+                }
+                let unused = nested.results(ctx).iter().all(|result| result.use_empty(ctx));
+                if !nested.results(ctx).is_empty()
+                    && unused
+                    && nested.regions(ctx).is_empty()
+                    && dialect.is_pure(ctx, nested)
+                {
+                    dead.push(nested);
+                }
+            }
+        }
+        // The operations left unused are erased once the uses of their operands are replaced too.
+        for nested in dead.into_iter().rev() {
+            if nested.results(ctx).iter().all(|result| result.use_empty(ctx)) {
+                ctx.erase_operation(nested)
+                    .map_err(|error| PassFailure::on(ctx, nested, error.to_string()))?;
+                changed = true;
+            }
+        }
+        match changed {
+            true => Ok(PreservedAnalyses::none()),
+            false => Ok(PreservedAnalyses::all()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::tests::{ArithDialect, TestDialect};
+    use crate::{parse, print, register_transforms, Context, PassManager, PassRegistry, PrinterOptions};
+
+    #[test]
+    fn should_replace_the_constants_of_the_executable_blocks() {
+        let mut registry = PassRegistry::new();
+        register_transforms(&mut registry);
+        let mut pm = PassManager::parse("builtin.module(sccp)", &registry).unwrap();
+        let mut ctx = Context::new();
+        ctx.register_dialect(ArithDialect);
+        ctx.register_dialect(TestDialect);
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.addi"(%arg0, %0) : (i32, i32) -> i32
+    "test.return"(%1) : (i32) -> ()
+  }) {sym_name = "inc", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.constant"() {value = 1 : i1} : () -> i1
+    %2 = "arith.addi"(%0, %0) : (i32, i32) -> i32
+    "test.cond_br"(%1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:  // pred: ^bb0
+    "test.br"(%0)[^bb3] : (i32) -> ()
+  ^bb2:  // pred: ^bb0
+    %3 = "arith.addi"(%arg0, %0) : (i32, i32) -> i32
+    "test.br"(%3)[^bb3] : (i32) -> ()
+  ^bb3(%4: i32):  // 2 preds: ^bb1, ^bb2
+    %5 = "test.call"(%4) {callee = @inc} : (i32) -> i32
+    %6 = "arith.addi"(%5, %arg0) : (i32, i32) -> i32
+    "test.return"(%2, %6) : (i32, i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}
+"#,
+        )
+        .unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %2 = "arith.constant"() {value = 2 : i32} : () -> i32
+    "test.return"(%2) : (i32) -> ()
+  }) {sym_name = "inc", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+  ^bb0(%arg0: i32):
+    %0 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %1 = "arith.constant"() {value = true} : () -> i1
+    %2 = "arith.constant"() {value = 2 : i32} : () -> i32
+    "test.cond_br"(%1)[^bb1, ^bb2] : (i1) -> ()
+  ^bb1:  // pred: ^bb0
+    "test.br"(%0)[^bb3] : (i32) -> ()
+  ^bb2:  // pred: ^bb0
+    %3 = "arith.addi"(%arg0, %0) : (i32, i32) -> i32
+    "test.br"(%3)[^bb3] : (i32) -> ()
+  ^bb3(%4: i32):  // 2 preds: ^bb1, ^bb2
+    %5 = "arith.constant"() {value = 1 : i32} : () -> i32
+    %6 = "arith.constant"() {value = 2 : i32} : () -> i32
+    %7 = "test.call"(%5) {callee = @inc} : (i32) -> i32
+    %8 = "arith.addi"(%6, %arg0) : (i32, i32) -> i32
+    "test.return"(%2, %8) : (i32, i32) -> ()
+  }) {sym_name = "main"} : () -> ()
+}
+"#
+        );
+    }
+}
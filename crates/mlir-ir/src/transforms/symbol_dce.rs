@@ -0,0 +1,216 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `symbol-dce` pass, erasing the private symbols of a symbol table unreachable from its public symbols
+//! and the rest of its operations, like the upstream `SymbolDCE`.
+//! Only the symbols defined directly within the symbol table are erased, the nested symbol tables are kept
+//! whole and all of their references are live.
+//!
+
+use std::collections::HashSet;
+
+use crate::{
+    AnalysisManager, Attribute, AttributeKind, Context, Operation, Pass, PassFailure, PreservedAnalyses,
+    SymbolTable, SymbolTableCollection,
+};
+
+/// Symbol dead code elimination.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolDce;
+
+fn is_private(ctx: &Context, op: Operation) -> bool {
+    op.attribute(ctx, "sym_visibility")
+        .and_then(|visibility| visibility.as_string(ctx))
+        == Some("private")
+}
+
+/// The `@root::@nested` references within the attribute, nested within the arrays and the dictionaries.
+fn symbol_references(ctx: &Context, attribute: Attribute, references: &mut Vec<Attribute>) {
+    match attribute.kind(ctx) {
+        AttributeKind::SymbolRef { .. } => references.push(attribute),
+        AttributeKind::Array(elements) => {
+            for element in elements {
+                symbol_references(ctx, *element, references);
+            }
+        }
+        AttributeKind::Dictionary(attributes) => {
+            for attribute in attributes {
+                symbol_references(ctx, attribute.value, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The symbols named along the reference from the operation, the root one first.
+fn referenced_symbols(
+    ctx: &Context,
+    symbols: &mut SymbolTableCollection,
+    from: Operation,
+    reference: Attribute,
+) -> Vec<Operation> {
+    let AttributeKind::SymbolRef { root, nested } = reference.kind(ctx) else {
+        return vec![];
+    };
+    let Some(table) = SymbolTable::nearest_symbol_table(ctx, from) else {
+        return vec![];
+    };
+    let mut resolved = vec![];
+    let mut current = symbols.symbol_table(ctx, table).lookup(root);
+    for name in nested {
+        let Some(op) = current else {
+            break;
+        };
+        resolved.push(op);
+        current = match SymbolTable::is_symbol_table(ctx, op) {
+            true => symbols.symbol_table(ctx, op).lookup(name),
+            false => None,
+        };
+    }
+    resolved.extend(current);
+    resolved
+}
+
+impl Pass for SymbolDce {
+    fn argument(&self) -> &str {
+        "symbol-dce"
+    }
+
+    fn name(&self) -> &str {
+        "SymbolDCE"
+    }
+
+    fn run(
+        &mut self,
+        ctx: &mut Context,
+        op: Operation,
+        _analyses: &mut AnalysisManager,
+    ) -> Result<PreservedAnalyses, PassFailure> {
+        if !SymbolTable::is_symbol_table(ctx, op) {
+            return Err(PassFailure::on(
+                ctx,
+                op,
+                "was scheduled to run under SymbolDCE, but does not define a symbol table",
+            ));
+        }
+        let children: Vec<Operation> = op
+            .regions(ctx)
+            .iter()
+            .flat_map(|region| region.blocks(ctx).to_vec())
+            .flat_map(|block| block.operations(ctx).to_vec())
+            .collect();
+        let mut worklist: Vec<Operation> = children
+            .iter()
+            .copied()
+            .filter(|child| SymbolTable::symbol_name(ctx, *child).is_none() || !is_private(ctx, *child))
+            .collect();
+        let mut live: HashSet<Operation> = worklist.iter().copied().collect();
+        let mut symbols = SymbolTableCollection::new();
+        while let Some(user) = worklist.pop() {
+            for nested in user.walk(ctx) {
+                let mut references = vec![];
+                for attribute in nested.all_attributes(ctx) {
+                    symbol_references(ctx, attribute.value, &mut references);
+                }
+                for reference in references {
+                    for symbol in referenced_symbols(ctx, &mut symbols, nested, reference) {
+                        if live.insert(symbol) {
+                            worklist.push(symbol);
+                        }
+                    }
+                }
+            }
+        }
+        let dead: Vec<Operation> = children.into_iter().filter(|child| !live.contains(child)).collect();
+        for symbol in &dead {
+            ctx.erase_operation(*symbol)
+                .map_err(|error| PassFailure::on(ctx, *symbol, error.to_string()))?;
+        }
+        match dead.is_empty() {
+            true => Ok(PreservedAnalyses::all()),
+            false => Ok(PreservedAnalyses::none()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, print, register_transforms, Context, PassError, PassManager, PassRegistry, PrinterOptions};
+
+    #[test]
+    fn should_erase_the_unreachable_private_symbols() {
+        let mut registry = PassRegistry::new();
+        register_transforms(&mut registry);
+        let mut pm = PassManager::parse("builtin.module(symbol-dce)", &registry).unwrap();
+        let mut ctx = Context::new();
+        let module = parse(
+            &mut ctx,
+            r#"module {
+  "test.func"() ({
+    "test.call"() {callee = @live} : () -> ()
+  }) {sym_name = "public"} : () -> ()
+  "test.func"() ({
+    "test.call"() {callees = [{target = @inner::@leaf}]} : () -> ()
+  }) {sym_name = "live", sym_visibility = "private"} : () -> ()
+  "test.func"() ({
+    "test.call"() {callee = @dead} : () -> ()
+  }) {sym_name = "dead", sym_visibility = "private"} : () -> ()
+  module @inner attributes {sym_visibility = "private"} {
+    "test.func"() ({
+    }) {sym_name = "leaf"} : () -> ()
+  }
+  module @unused attributes {sym_visibility = "private"} {
+  }
+  "test.global"() {sym_name = "global", sym_visibility = "private"} : () -> ()
+  "test.use"() {global = @global} : () -> ()
+}
+"#,
+        )
+        .unwrap();
+        pm.run(&mut ctx, module).unwrap();
+        assert_eq!(
+            print(&ctx, module, PrinterOptions::default()),
+            r#"module {
+  "test.func"() ({
+    "test.call"() {callee = @live} : () -> ()
+  }) {sym_name = "public"} : () -> ()
+  "test.func"() ({
+    "test.call"() {callees = [{target = @inner::@leaf}]} : () -> ()
+  }) {sym_name = "live", sym_visibility = "private"} : () -> ()
+  module @inner attributes {sym_visibility = "private"} {
+    "test.func"() ({
+    }) {sym_name = "leaf"} : () -> ()
+  }
+  "test.global"() {sym_name = "global", sym_visibility = "private"} : () -> ()
+  "test.use"() {global = @global} : () -> ()
+}
+"#
+        );
+
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, r#""test.func"() ({}) {sym_name = "f"} : () -> ()"#).unwrap();
+        let error = PassManager::parse("builtin.module(test.func(symbol-dce))", &registry)
+            .unwrap()
+            .run(&mut ctx, module)
+            .unwrap_err();
+        assert!(
+            matches!(&error, PassError::Failed { pass, message, .. }
+                if pass == "symbol-dce" && message.contains("does not define a symbol table")),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,336 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Uniqued builtin types, the dialect types without a dedicated representation are kept opaque.
+//!
+
+use crate::{Attribute, Context};
+
+/// Uniqued type handle, the equal types of a [Context] share it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Type(pub(crate) u32);
+
+/// Dynamic dimension of the shaped types, the upstream `ShapedType::kDynamic`.
+pub const DYNAMIC: i64 = i64::MIN;
+
+/// Signedness semantics of the integer types, `i32`, `si32` and `ui32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signedness {
+    Signless,
+    Signed,
+    Unsigned,
+}
+
+/// Builtin floating point types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatKind {
+    BF16,
+    F16,
+    TF32,
+    F32,
+    F64,
+    F80,
+    F128,
+}
+
+impl FloatKind {
+    pub fn width(self) -> u32 {
+        match self {
+            FloatKind::BF16 | FloatKind::F16 => 16,
+            FloatKind::TF32 => 19,
+            FloatKind::F32 => 32,
+            FloatKind::F64 => 64,
+            FloatKind::F80 => 80,
+            FloatKind::F128 => 128,
+        }
+    }
+}
+
+/// Structure of the uniqued type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    Integer {
+        width: u32,
+        signedness: Signedness,
+    },
+    Index,
+    Float(FloatKind),
+    None,
+    Complex(Type),
+    Function {
+        inputs: Vec<Type>,
+        results: Vec<Type>,
+    },
+    Tuple(Vec<Type>),
+    /// Vector type, the `scalable` flags go with the dimensions of the `shape`.
+    Vector {
+        shape: Vec<i64>,
+        scalable: Vec<bool>,
+        element: Type,
+    },
+    RankedTensor {
+        shape: Vec<i64>,
+        element: Type,
+        encoding: Option<Attribute>,
+    },
+    UnrankedTensor {
+        element: Type,
+    },
+    /// Ranked memref, the identity `layout` is left out.
+    MemRef {
+        shape: Vec<i64>,
+        element: Type,
+        layout: Option<Attribute>,
+        memory_space: Option<Attribute>,
+    },
+    UnrankedMemRef {
+        element: Type,
+        memory_space: Option<Attribute>,
+    },
+    /// Dialect type without a dedicated representation, `data` is the text following the `!dialect.` prefix,
+    /// or the `<...>` body of the `!dialect<...>` ones.
+    Opaque {
+        dialect: String,
+        data: String,
+    },
+}
+
+impl Type {
+    pub fn kind(self, ctx: &Context) -> &TypeKind {
+        ctx.types.get(self.0)
+    }
+
+    pub fn is_integer(self, ctx: &Context) -> bool {
+        matches!(self.kind(ctx), TypeKind::Integer { .. })
+    }
+
+    /// Signless integer of the given width, like the `isSignlessInteger(width)`.
+    pub fn is_signless_integer(self, ctx: &Context, width: u32) -> bool {
+        *self.kind(ctx)
+            == TypeKind::Integer {
+                width,
+                signedness: Signedness::Signless,
+            }
+    }
+
+    pub fn is_index(self, ctx: &Context) -> bool {
+        matches!(self.kind(ctx), TypeKind::Index)
+    }
+
+    pub fn is_float(self, ctx: &Context) -> bool {
+        matches!(self.kind(ctx), TypeKind::Float(_))
+    }
+
+    /// Bit width of the integer and floating point types.
+    pub fn int_or_float_width(self, ctx: &Context) -> Option<u32> {
+        match self.kind(ctx) {
+            TypeKind::Integer { width, .. } => Some(*width),
+            TypeKind::Float(kind) => Some(kind.width()),
+            _ => None,
+        }
+    }
+
+    /// Element type of the shaped and complex types.
+    pub fn element_type(self, ctx: &Context) -> Option<Type> {
+        match self.kind(ctx) {
+            TypeKind::Complex(element)
+            | TypeKind::Vector { element, .. }
+            | TypeKind::RankedTensor { element, .. }
+            | TypeKind::UnrankedTensor { element }
+            | TypeKind::MemRef { element, .. }
+            | TypeKind::UnrankedMemRef { element, .. } => Some(*element),
+            _ => None,
+        }
+    }
+
+    /// Shape of the ranked shaped types, with the [DYNAMIC] dimensions.
+    pub fn shape(self, ctx: &Context) -> Option<&[i64]> {
+        match self.kind(ctx) {
+            TypeKind::Vector { shape, .. } | TypeKind::RankedTensor { shape, .. } | TypeKind::MemRef { shape, .. } => {
+                Some(shape)
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of the elements of the statically shaped types.
+    pub fn num_elements(self, ctx: &Context) -> Option<i64> {
+        let shape = self.shape(ctx)?;
+        shape.iter().all(|dim| *dim != DYNAMIC).then(|| shape.iter().product())
+    }
+}
+
+impl Context {
+    /// Uniques the type.
+    pub fn get_type(&mut self, kind: TypeKind) -> Type {
+        Type(self.types.intern(kind))
+    }
+
+    /// Signless integer type, like `i32`.
+    pub fn integer_type(&mut self, width: u32) -> Type {
+        self.get_type(TypeKind::Integer {
+            width,
+            signedness: Signedness::Signless,
+        })
+    }
+
+    pub fn index_type(&mut self) -> Type {
+        self.get_type(TypeKind::Index)
+    }
+
+    pub fn float_type(&mut self, kind: FloatKind) -> Type {
+        self.get_type(TypeKind::Float(kind))
+    }
+
+    pub fn none_type(&mut self) -> Type {
+        self.get_type(TypeKind::None)
+    }
+
+    pub fn function_type(&mut self, inputs: &[Type], results: &[Type]) -> Type {
+        self.get_type(TypeKind::Function {
+            inputs: inputs.to_vec(),
+            results: results.to_vec(),
+        })
+    }
+
+    pub fn tuple_type(&mut self, types: &[Type]) -> Type {
+        self.get_type(TypeKind::Tuple(types.to_vec()))
+    }
+
+    /// Fixed-length vector type.
+    pub fn vector_type(&mut self, shape: &[i64], element: Type) -> Type {
+        self.get_type(TypeKind::Vector {
+            shape: shape.to_vec(),
+            scalable: vec![false; shape.len()],
+            element,
+        })
+    }
+
+    /// Ranked tensor type without an encoding.
+    pub fn tensor_type(&mut self, shape: &[i64], element: Type) -> Type {
+        self.get_type(TypeKind::RankedTensor {
+            shape: shape.to_vec(),
+            element,
+            encoding: None,
+        })
+    }
+
+    /// Memref type with the identity layout in the default memory space.
+    pub fn memref_type(&mut self, shape: &[i64], element: Type) -> Type {
+        self.get_type(TypeKind::MemRef {
+            shape: shape.to_vec(),
+            element,
+            layout: None,
+            memory_space: None,
+        })
+    }
+
+    /// Dialect type kept as its text, like `!llvm.ptr` of the `llvm` dialect and `ptr` data.
+    pub fn opaque_type(&mut self, dialect: &str, data: &str) -> Type {
+        self.get_type(TypeKind::Opaque {
+            dialect: dialect.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    /// `i1` of the same shape as the vector or tensor type, `i1` for the scalars, like the comparison results.
+    pub fn i1_same_shape(&mut self, r#type: Type) -> Type {
+        let i1 = self.integer_type(1);
+        let kind = match r#type.kind(self).clone() {
+            TypeKind::Vector { shape, scalable, .. } => TypeKind::Vector {
+                shape,
+                scalable,
+                element: i1,
+            },
+            TypeKind::RankedTensor { shape, encoding, .. } => TypeKind::RankedTensor {
+                shape,
+                element: i1,
+                encoding,
+            },
+            TypeKind::UnrankedTensor { .. } => TypeKind::UnrankedTensor { element: i1 },
+            _ => return i1,
+        };
+        self.get_type(kind)
+    }
+
+    /// Type of the ODS `BuildableType` textual form, like `$_builder.getI1Type()`.
+    ///
+    /// # Panics
+    /// If the text isn't a type, the generated builders only refer to the builtin and the parameterless dialect
+    /// types.
+    pub fn buildable_type(&mut self, text: &str) -> Type {
+        crate::parse_type(self, text).unwrap_or_else(|error| panic!("`{text}` is not a buildable type: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_unique_types() {
+        let mut ctx = Context::new();
+        let i32 = ctx.integer_type(32);
+        assert_eq!(ctx.integer_type(32), i32);
+        let si32 = ctx.get_type(TypeKind::Integer {
+            width: 32,
+            signedness: Signedness::Signed,
+        });
+        assert_ne!(si32, i32);
+        assert!(i32.is_signless_integer(&ctx, 32));
+        assert!(!si32.is_signless_integer(&ctx, 32));
+
+        let vector = ctx.vector_type(&[4], i32);
+        assert_eq!(ctx.vector_type(&[4], i32), vector);
+        assert_eq!(vector.element_type(&ctx), Some(i32));
+        assert_eq!(vector.shape(&ctx), Some(&[4][..]));
+
+        let f32 = ctx.float_type(FloatKind::F32);
+        assert_eq!(f32.int_or_float_width(&ctx), Some(32));
+        let function = ctx.function_type(&[i32, f32], &[i32]);
+        assert_eq!(
+            function.kind(&ctx),
+            &TypeKind::Function {
+                inputs: vec![i32, f32],
+                results: vec![i32]
+            }
+        );
+        assert_ne!(ctx.function_type(&[f32, i32], &[i32]), function);
+    }
+
+    #[test]
+    fn should_build_the_ods_types() {
+        let mut ctx = Context::new();
+        let i1 = ctx.integer_type(1);
+        let f32 = ctx.float_type(FloatKind::F32);
+        assert_eq!(ctx.buildable_type("i1"), i1);
+        assert_eq!(ctx.i1_same_shape(f32), i1);
+        let vector = ctx.vector_type(&[4], f32);
+        let mask = ctx.i1_same_shape(vector);
+        assert_eq!(mask, ctx.vector_type(&[4], i1));
+        assert_eq!(ctx.buildable_type("!llvm.ptr"), ctx.opaque_type("llvm", "ptr"));
+    }
+
+    #[test]
+    fn should_keep_dynamic_dimensions() {
+        let mut ctx = Context::new();
+        let f32 = ctx.float_type(FloatKind::F32);
+        let memref = ctx.memref_type(&[DYNAMIC, 4], f32);
+        assert_eq!(memref.shape(&ctx), Some(&[DYNAMIC, 4][..]));
+        assert_ne!(ctx.tensor_type(&[DYNAMIC, 4], f32), memref);
+    }
+}
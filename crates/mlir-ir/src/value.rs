@@ -0,0 +1,190 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! SSA values, the operation results and block arguments, with their use-def chains.
+//!
+
+use crate::{Attribute, Block, Context, Operation, Type};
+
+/// SSA value handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Value(pub(crate) u32);
+
+/// Definition of the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueOwner {
+    Result { op: Operation, index: usize },
+    Argument { block: Block, index: usize },
+}
+
+/// Use of a value, the operand `index` of its `owner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OpOperand {
+    pub owner: Operation,
+    pub index: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct ValueData {
+    pub r#type: Type,
+    pub owner: ValueOwner,
+    pub uses: Vec<OpOperand>,
+}
+
+impl Value {
+    pub fn r#type(self, ctx: &Context) -> Type {
+        ctx.values.get(self.0).r#type
+    }
+
+    pub fn owner(self, ctx: &Context) -> ValueOwner {
+        ctx.values.get(self.0).owner
+    }
+
+    /// Operation of the result, `None` for the block arguments.
+    pub fn defining_op(self, ctx: &Context) -> Option<Operation> {
+        match self.owner(ctx) {
+            ValueOwner::Result { op, .. } => Some(op),
+            ValueOwner::Argument { .. } => None,
+        }
+    }
+
+    /// Value of the constant-like operation defining the value, like the upstream `m_Constant` matcher.
+    pub fn constant_value(self, ctx: &Context) -> Option<Attribute> {
+        let op = self.defining_op(ctx)?;
+        ctx.dialect_of(op)?.constant_value(ctx, op)
+    }
+
+    /// Block of the argument, or the block the defining operation is inserted into.
+    pub fn parent_block(self, ctx: &Context) -> Option<Block> {
+        match self.owner(ctx) {
+            ValueOwner::Result { op, .. } => op.parent_block(ctx),
+            ValueOwner::Argument { block, .. } => Some(block),
+        }
+    }
+
+    pub fn uses(self, ctx: &Context) -> &[OpOperand] {
+        &ctx.values.get(self.0).uses
+    }
+
+    pub fn use_empty(self, ctx: &Context) -> bool {
+        self.uses(ctx).is_empty()
+    }
+
+    pub fn has_one_use(self, ctx: &Context) -> bool {
+        self.uses(ctx).len() == 1
+    }
+
+    /// Distinct operations using the value, in the order of their first use.
+    pub fn users(self, ctx: &Context) -> Vec<Operation> {
+        let mut users: Vec<Operation> = vec![];
+        for operand in self.uses(ctx) {
+            if !users.contains(&operand.owner) {
+                users.push(operand.owner);
+            }
+        }
+        users
+    }
+}
+
+/// Collects the types of the values.
+pub fn types(ctx: &Context, values: &[Value]) -> Vec<Type> {
+    values.iter().map(|value| value.r#type(ctx)).collect()
+}
+
+impl Context {
+    pub(crate) fn new_value(&mut self, r#type: Type, owner: ValueOwner) -> Value {
+        Value(self.values.alloc(ValueData {
+            r#type,
+            owner,
+            uses: vec![],
+        }))
+    }
+
+    pub(crate) fn add_use(&mut self, value: Value, operand: OpOperand) {
+        self.values.get_mut(value.0).uses.push(operand);
+    }
+
+    pub(crate) fn remove_use(&mut self, value: Value, operand: OpOperand) {
+        let uses = &mut self.values.get_mut(value.0).uses;
+        if let Some(position) = uses.iter().position(|candidate| *candidate == operand) {
+            uses.remove(position);
+        }
+    }
+
+    /// Changes the type of the value in place, like the upstream `Value::setType`.
+    pub fn set_type(&mut self, value: Value, r#type: Type) {
+        self.values.get_mut(value.0).r#type = r#type;
+    }
+
+    /// Makes every use of `from` use `to` instead.
+    pub fn replace_all_uses_with(&mut self, from: Value, to: Value) {
+        if from == to {
+            return;
+        }
+        for operand in std::mem::take(&mut self.values.get_mut(from.0).uses) {
+            self.operations.get_mut(operand.owner.0).operands[operand.index] = to;
+            self.add_use(to, operand);
+        }
+    }
+
+    /// Makes the uses of `from` accepted by the predicate use `to` instead.
+    pub fn replace_uses_with_if(&mut self, from: Value, to: Value, mut predicate: impl FnMut(OpOperand) -> bool) {
+        if from == to {
+            return;
+        }
+        let uses = from.uses(self).to_vec();
+        for operand in uses.into_iter().filter(|operand| predicate(*operand)) {
+            self.set_operand(operand.owner, operand.index, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OperationState;
+
+    use super::*;
+
+    #[test]
+    fn should_replace_uses() {
+        let mut ctx = Context::new();
+        let i1 = ctx.integer_type(1);
+        let block = ctx.create_block(&[i1, i1]);
+        let (a, b) = (block.argument(&ctx, 0), block.argument(&ctx, 1));
+        let mut state = OperationState::new("arith.andi");
+        state.add_operands([a, a]).add_types([i1]);
+        let and = ctx.create_operation(state);
+        assert_eq!(a.users(&ctx), [and]);
+        assert_eq!(a.parent_block(&ctx), Some(block));
+
+        ctx.replace_uses_with_if(a, b, |operand| operand.index == 1);
+        assert_eq!(and.operands(&ctx), &[a, b]);
+        assert!(a.has_one_use(&ctx) && b.has_one_use(&ctx));
+
+        ctx.replace_all_uses_with(a, b);
+        assert_eq!(and.operands(&ctx), &[b, b]);
+        assert!(a.use_empty(&ctx));
+        assert_eq!(
+            b.uses(&ctx),
+            &[OpOperand { owner: and, index: 1 }, OpOperand { owner: and, index: 0 }]
+        );
+
+        ctx.set_operand(and, 0, a);
+        assert_eq!(a.uses(&ctx), &[OpOperand { owner: and, index: 0 }]);
+        assert_eq!(b.uses(&ctx), &[OpOperand { owner: and, index: 1 }]);
+    }
+}
@@ -0,0 +1,449 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Verifier of the structural invariants of the IR, like the upstream `mlir::verify`: the operation verifiers
+//! of the registered dialects, the terminators, the successors, the operations isolated from above,
+//! the symbol tables with the symbol uses of the nested operations, and the SSA dominance of the operands.
+//!
+
+use std::collections::HashSet;
+
+use crate::{
+    Context, DominanceInfo, Location, OpTrait, Operation, SymbolTable, SymbolTableCollection, SymbolUserOpInterface,
+    Value, ValueOwner, VerifyError,
+};
+
+/// Verifies the operation with the nested ones, reporting the first violated invariant.
+pub fn verify(ctx: &Context, op: Operation) -> Result<(), VerifyError> {
+    let dominance = DominanceInfo::new(ctx, op);
+    for nested in op.walk(ctx) {
+        verify_operation(ctx, nested)?;
+        verify_dominance(ctx, nested, &dominance)?;
+    }
+    Ok(())
+}
+
+fn verify_operation(ctx: &Context, op: Operation) -> Result<(), VerifyError> {
+    if let Some(dialect) = ctx.dialect_of(op) {
+        dialect.verify(ctx, op).map_err(|message| error(ctx, op, message))?;
+    }
+    let parent_region = op.parent_region(ctx);
+    if op
+        .successors(ctx)
+        .iter()
+        .any(|successor| successor.parent_region(ctx) != parent_region || parent_region.is_none())
+    {
+        return Err(error(ctx, op, "reference to block defined in another region"));
+    }
+    if op.has_trait(ctx, OpTrait::Terminator) && op.next(ctx).is_some() {
+        return Err(error(ctx, op, "must be the last operation in the parent block"));
+    }
+    // The blocks of the unregistered operations may be valid without the terminators.
+    let terminated = op.is_registered(ctx) && !op.has_trait(ctx, OpTrait::NoTerminator);
+    for region in op.regions(ctx) {
+        for block in region.blocks(ctx) {
+            if block.is_entry_block(ctx) && !block.uses(ctx).is_empty() {
+                return Err(error(ctx, op, "entry block of region may not have predecessors"));
+            }
+            if !terminated {
+                continue;
+            }
+            match block.operations(ctx).last() {
+                None => return Err(error(ctx, op, "empty block: expect at least a terminator")),
+                Some(last) if last.is_registered(ctx) && !last.has_trait(ctx, OpTrait::Terminator) => {
+                    return Err(error(ctx, *last, "block with no terminator"));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    if op.has_trait(ctx, OpTrait::IsolatedFromAbove) {
+        verify_isolation(ctx, op)?;
+    }
+    if op.has_trait(ctx, OpTrait::SymbolTable) {
+        verify_symbol_table(ctx, op)?;
+    }
+    Ok(())
+}
+
+/// The nested operations may use only the values defined within the operation.
+fn verify_isolation(ctx: &Context, op: Operation) -> Result<(), VerifyError> {
+    let defined_within = |value: &Value| {
+        let owner = match value.owner(ctx) {
+            ValueOwner::Result { op: owner, .. } => Some(owner),
+            ValueOwner::Argument { block, .. } => block.parent_op(ctx),
+        };
+        owner.is_some_and(|owner| op.is_ancestor_of(ctx, owner))
+    };
+    for nested in op.walk(ctx).into_iter().filter(|nested| *nested != op) {
+        if !nested.operands(ctx).iter().all(defined_within) {
+            return Err(error(ctx, nested, "using value defined outside the region"));
+        }
+    }
+    Ok(())
+}
+
+/// The symbol names are unique within the single block, and the nested symbol uses resolve.
+fn verify_symbol_table(ctx: &Context, op: Operation) -> Result<(), VerifyError> {
+    let [region] = op.regions(ctx) else {
+        return Err(error(
+            ctx,
+            op,
+            "Operations with a 'SymbolTable' must have exactly one region",
+        ));
+    };
+    let [block] = region.blocks(ctx) else {
+        return Err(error(
+            ctx,
+            op,
+            "Operations with a 'SymbolTable' must have exactly one block",
+        ));
+    };
+    let mut names = HashSet::new();
+    for nested in block.operations(ctx) {
+        if let Some(name) = SymbolTable::symbol_name(ctx, *nested) {
+            if !names.insert(name) {
+                return Err(error(ctx, *nested, format!("redefinition of symbol named '{name}'")));
+            }
+        }
+    }
+    let mut symbols = SymbolTableCollection::new();
+    let mut pending: Vec<Operation> = block.operations(ctx).to_vec();
+    while let Some(nested) = pending.pop() {
+        if let Some(symbol_user) = nested.as_interface::<dyn SymbolUserOpInterface>(ctx) {
+            symbol_user
+                .verify_symbol_uses(ctx, nested, &mut symbols)
+                .map_err(|failure| error(ctx, nested, failure.to_string()))?;
+        }
+        // The nested symbol tables verify the uses within them.
+        if !SymbolTable::is_symbol_table(ctx, nested) {
+            let regions = nested.regions(ctx).iter().flat_map(|region| region.blocks(ctx));
+            pending.extend(regions.flat_map(|block| block.operations(ctx)));
+        }
+    }
+    Ok(())
+}
+
+/// The operands of the reachable operations dominate them.
+fn verify_dominance(ctx: &Context, op: Operation, dominance: &DominanceInfo) -> Result<(), VerifyError> {
+    let Some(block) = op.parent_block(ctx) else {
+        return Ok(());
+    };
+    if !dominance.is_reachable_from_entry(ctx, block) {
+        return Ok(());
+    }
+    for (index, operand) in op.operands(ctx).iter().enumerate() {
+        if !dominance.value_dominates(ctx, *operand, op) {
+            return Err(error(ctx, op, format!("operand #{index} does not dominate this use")));
+        }
+    }
+    Ok(())
+}
+
+fn error(ctx: &Context, op: Operation, message: impl Into<String>) -> VerifyError {
+    VerifyError {
+        op: op.name(ctx).to_string(),
+        location: Location::new(ctx, op.location(ctx)),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse, AnalysisManager, Dialect, InterfaceError, InterfaceRegistry, LogicalResult, OpDefinition, Pass,
+        PassFailure, PassManager, PreservedAnalyses, RegionKind, Variadicity,
+    };
+
+    /// `test.func` symbols isolated from above, `test.call` referring to them, `test.graph` of a graph region,
+    /// the `test.add` and `test.select` declarations and the `test.br` and `test.return` terminators.
+    struct TestDialect;
+
+    const OPERATIONS: &[OpDefinition] = &[
+        OpDefinition {
+            name: "test.add",
+            traits: &[],
+            pure: true,
+            operands: &[Variadicity::Single, Variadicity::Single],
+            results: &[Variadicity::Single],
+            regions: &[],
+            successors: &[],
+        },
+        OpDefinition {
+            name: "test.select",
+            traits: &[],
+            pure: true,
+            operands: &[Variadicity::Single, Variadicity::Optional, Variadicity::Variadic],
+            results: &[],
+            regions: &[],
+            successors: &[],
+        },
+    ];
+
+    impl Dialect for TestDialect {
+        fn namespace(&self) -> &str {
+            "test"
+        }
+
+        fn has_trait(&self, ctx: &Context, op: Operation, r#trait: OpTrait) -> bool {
+            matches!(
+                (op.name(ctx), r#trait),
+                ("test.br" | "test.return", OpTrait::Terminator)
+                    | ("test.func", OpTrait::IsolatedFromAbove)
+                    | ("test.graph", OpTrait::NoTerminator)
+            )
+        }
+
+        fn region_kind(&self, ctx: &Context, op: Operation, _index: usize) -> RegionKind {
+            match op.name(ctx) {
+                "test.graph" => RegionKind::Graph,
+                _ => RegionKind::SsaCfg,
+            }
+        }
+
+        fn verify(&self, ctx: &Context, op: Operation) -> Result<(), String> {
+            match OpDefinition::lookup(OPERATIONS, op.name(ctx)) {
+                Some(definition) => definition.verify(ctx, op),
+                None => Ok(()),
+            }
+        }
+
+        fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+            registry.register::<dyn SymbolUserOpInterface>("test.call", &CallOp);
+        }
+    }
+
+    struct CallOp;
+
+    impl SymbolUserOpInterface for CallOp {
+        fn verify_symbol_uses(
+            &self,
+            ctx: &Context,
+            op: Operation,
+            symbol_table: &mut SymbolTableCollection,
+        ) -> LogicalResult {
+            let Some(callee) = op.attribute(ctx, "callee") else {
+                return Ok(());
+            };
+            match symbol_table.lookup_nearest_symbol_from(ctx, op, callee) {
+                Some(func) if func.name(ctx) == "test.func" => Ok(()),
+                _ => Err(InterfaceError::new("'callee' does not reference a valid function")),
+            }
+        }
+    }
+
+    fn check(source: &str) -> Result<(), String> {
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(&mut ctx, source).unwrap();
+        verify(&ctx, module).map_err(|error| error.to_string())
+    }
+
+    /// Module of the `test.func` with the body.
+    fn func(body: &str) -> String {
+        format!(
+            "\"builtin.module\"() ({{\n  \"test.func\"() ({{\n{body}  }}) {{sym_name = \"f\"}} : () -> ()\n}}) : () -> ()\n"
+        )
+    }
+
+    #[test]
+    fn should_verify_valid_operations() {
+        check(&func(
+            r#"  ^bb0(%arg0: i32):
+    %0 = "test.add"(%arg0, %arg0) : (i32, i32) -> i32
+    "test.graph"() ({
+      %1 = "test.add"(%2, %0) : (i32, i32) -> i32
+      %2 = "test.add"(%1, %0) : (i32, i32) -> i32
+    }) : () -> ()
+    "test.call"() {callee = @f} : () -> ()
+    "test.br"()[^bb1] : () -> ()
+  ^bb1:
+    "test.return"() : () -> ()
+  ^bb2:
+    %4 = "test.add"(%3, %3) : (i32, i32) -> i32
+    %3 = "test.add"(%0, %0) : (i32, i32) -> i32
+    "test.br"()[^bb1] : () -> ()
+"#,
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn should_report_violated_invariants() {
+        let cases = [
+            (
+                func(
+                    r#"    %0 = "test.add"(%1, %1) : (i32, i32) -> i32
+    %1 = "other.value"() : () -> i32
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.add' op operand #0 does not dominate this use",
+            ),
+            (
+                func(
+                    r#"  ^bb0(%arg0: i32):
+    %0 = "test.add"(%arg0, %arg0) : (i32, i32) -> i32
+"#,
+                ),
+                "loc(unknown): 'test.add' op block with no terminator",
+            ),
+            (
+                func(
+                    r#"    "test.return"() : () -> ()
+    "other.op"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.return' op must be the last operation in the parent block",
+            ),
+            (
+                func(
+                    r#"  ^bb0(%arg0: i32):
+    %0 = "test.add"(%arg0) : (i32) -> i32
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.add' op expected 2 operands, but found 1",
+            ),
+            (
+                func(
+                    r#"    "test.select"() : () -> ()
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.select' op expected 1 or more operands, but found 0",
+            ),
+            (
+                func(
+                    r#"  ^bb0(%arg0: i32):
+    "test.select"(%arg0, %arg0, %arg0) {operandSegmentSizes = array<i32: 1, 2, 0>} : (i32, i32, i32) -> ()
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.select' op operand group #1 requires 0 or 1 element, but found 2",
+            ),
+            (
+                func(
+                    r#"  ^bb0(%arg0: i32):
+    "test.select"(%arg0, %arg0) {operandSegmentSizes = array<i32: 1, 1>} : (i32, i32) -> ()
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.select' op 'operandSegmentSizes' attribute for specifying operand segments \
+                 must have 3 elements, but got 2",
+            ),
+            (
+                func(
+                    r#"  ^bb0(%arg0: i32):
+    "test.add"(%arg0, %arg0) : (i32, i32) -> ()
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.add' op expected 1 result, but found 0",
+            ),
+            (
+                func(
+                    r#"  ^bb0:
+    "test.br"()[^bb0] : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.func' op entry block of region may not have predecessors",
+            ),
+            (
+                func(
+                    r#"    "test.call"() {callee = @g} : () -> ()
+    "test.return"() : () -> ()
+"#,
+                ),
+                "loc(unknown): 'test.call' op 'callee' does not reference a valid function",
+            ),
+            (
+                r#""builtin.module"() ({
+  %0 = "other.value"() : () -> i32
+  "test.func"() ({
+    "other.use"(%0) : (i32) -> ()
+    "test.return"() : () -> ()
+  }) : () -> ()
+}) : () -> ()
+"#
+                .to_string(),
+                "loc(unknown): 'other.use' op using value defined outside the region",
+            ),
+            (
+                r#""builtin.module"() ({
+  "other.symbol"() {sym_name = "f"} : () -> ()
+  "other.symbol"() {sym_name = "f"} : () -> ()
+}) : () -> ()
+"#
+                .to_string(),
+                "loc(unknown): 'other.symbol' op redefinition of symbol named 'f'",
+            ),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(check(&source), Err(expected.to_string()), "{source}");
+        }
+    }
+
+    /// Moves the first operation of the `test.func` body to its end.
+    #[derive(Clone)]
+    struct Break;
+
+    impl Pass for Break {
+        fn argument(&self) -> &str {
+            "test-break"
+        }
+
+        fn run(
+            &mut self,
+            ctx: &mut Context,
+            op: Operation,
+            _analyses: &mut AnalysisManager,
+        ) -> Result<PreservedAnalyses, PassFailure> {
+            let block = op.region(ctx, 0).blocks(ctx)[0];
+            let first = block.operations(ctx)[0];
+            ctx.remove_operation(first);
+            ctx.insert_operation(block, None, first).unwrap();
+            Ok(PreservedAnalyses::none())
+        }
+    }
+
+    #[test]
+    fn should_verify_after_each_pass() {
+        let mut ctx = Context::new();
+        ctx.register_dialect(TestDialect);
+        let module = parse(
+            &mut ctx,
+            &func(
+                r#"    %0 = "other.value"() : () -> i32
+    "other.use"(%0) : (i32) -> ()
+"#,
+            ),
+        )
+        .unwrap();
+        let mut pm = PassManager::new("builtin.module");
+        pm.nest("test.func").add_pass(Box::new(Break));
+        pm.enable_verifier(true);
+        assert_eq!(
+            pm.run(&mut ctx, module).unwrap_err().to_string(),
+            "verification failed after the `test-break` pass: loc(unknown): 'other.use' op operand #0 \
+             does not dominate this use"
+        );
+        pm.enable_verifier(false);
+        pm.run(&mut ctx, module).unwrap();
+    }
+}
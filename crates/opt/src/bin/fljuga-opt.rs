@@ -0,0 +1,160 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `fljuga-opt`, the `mlir-opt` counterpart running the pass pipelines over the `.mlir` and `.mlirbc` files
+//! with the target dialects registered, for the regression tests of the lowerings outside of rustc.
+//!
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use fljuga_handahofi_mlir_ir::{register_transforms, PassRegistry};
+use fljuga_handahofi_opt::{Opt, OptConfig, OptError};
+
+const USAGE: &str = "USAGE: fljuga-opt [options] <input file>
+
+The input is read from the standard input when it's `-` or left out.
+
+OPTIONS:
+  -o <file>                       Output file, the standard output by default
+  --pass-pipeline=<pipeline>      Anchored textual pipeline, like `builtin.module(canonicalize)`
+  --<pass>[=<options>]            Runs the registered pass on the top level operation
+  --split-input-file              Processes the chunks separated by the `// -----` lines independently
+  --verify-diagnostics            Checks the diagnostics against the `// expected-*` annotations
+  --verify-each[=<bool>]          Verifies the IR after every pass, enabled by default
+  --mlir-print-ir-after-all       Dumps the IR after every pass to the standard error
+  --mlir-print-debuginfo          Prints the operation locations
+  --mlir-print-local-scope        Prints the attributes and the types without the aliases
+  --mlir-print-op-generic         Prints the operations in the generic form
+  --emit-bytecode                 Writes the `.mlirbc` bytecode instead of the textual form
+  --help                          Prints this help, along with the registered passes";
+
+/// Command line arguments, the options are accepted with either one or two leading dashes, like the LLVM ones.
+struct Args {
+    config: OptConfig,
+    input: String,
+    output: Option<String>,
+}
+
+fn parse_args(passes: &PassRegistry, args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut config = OptConfig::default();
+    let mut input = None;
+    let mut output = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg
+            .strip_prefix("--")
+            .or_else(|| arg.strip_prefix('-'))
+            .filter(|option| !option.is_empty())
+        else {
+            if input.replace(arg).is_some() {
+                return Err("only one input file is supported".to_string());
+            }
+            continue;
+        };
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (option, None),
+        };
+        let mut value_or_next =
+            |value: Option<String>| value.or_else(|| args.next()).ok_or(format!("`{arg}` expects a value"));
+        match name {
+            "help" | "h" => return Ok(None),
+            "o" => output = Some(value_or_next(value)?),
+            "pass-pipeline" => config.pipeline = Some(value_or_next(value)?),
+            "split-input-file" => config.split_input_file = true,
+            "verify-diagnostics" => config.verify_diagnostics = true,
+            "verify-each" => {
+                config.verify_each = match value.as_deref() {
+                    None | Some("true") => true,
+                    Some("false") => false,
+                    Some(value) => return Err(format!("invalid `verify-each` value `{value}`")),
+                }
+            }
+            "mlir-print-ir-after-all" => config.print_ir_after_all = true,
+            "mlir-print-debuginfo" => config.printer.debug_info = true,
+            "mlir-print-local-scope" => config.printer.local_scope = true,
+            "mlir-print-op-generic" => config.printer.generic_op_form = true,
+            "emit-bytecode" => config.emit_bytecode = true,
+            pass if passes.get(pass).is_some() => config.passes.push(match value {
+                Some(options) => format!("{pass}{{{options}}}"),
+                None => pass.to_string(),
+            }),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    if config.pipeline.is_some() && !config.passes.is_empty() {
+        return Err("`--pass-pipeline` can't be combined with the pass options".to_string());
+    }
+    Ok(Some(Args {
+        config,
+        input: input.unwrap_or_else(|| "-".to_string()),
+        output,
+    }))
+}
+
+fn help(passes: &PassRegistry) {
+    println!("{USAGE}\n\nPASSES:");
+    for pass in passes.passes() {
+        println!("  --{:<30} {}", pass.argument, pass.summary);
+    }
+}
+
+fn run(args: Args) -> Result<(), OptError> {
+    let opt = Opt::new(args.config)?;
+    let (filename, input) = match args.input.as_str() {
+        "-" => {
+            let mut input = vec![];
+            io::stdin().read_to_end(&mut input)?;
+            ("<stdin>".to_string(), input)
+        }
+        path => (path.to_string(), fs::read(path)?),
+    };
+    let mut output = vec![];
+    let result = opt.run(&filename, &input, &mut output, &mut io::stderr().lock());
+    match &args.output {
+        Some(path) => fs::write(path, &output)?,
+        None => io::stdout().lock().write_all(&output)?,
+    }
+    result
+}
+
+fn main() -> ExitCode {
+    let mut passes = PassRegistry::new();
+    register_transforms(&mut passes);
+    let args = match parse_args(&passes, std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            help(&passes);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The diagnostics of the failed chunks are already reported.
+        Err(OptError::Failed(_)) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
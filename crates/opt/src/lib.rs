@@ -0,0 +1,490 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi opt
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! The `fljuga-opt` driver, running the textual pass pipelines over the `.mlir` and `.mlirbc` inputs
+//! with the target dialects registered, like the upstream `MlirOptMain`.
+//! The diagnostics are either reported, or checked against the `// expected-error {{...}}` annotations
+//! of the input, like the upstream `-verify-diagnostics`.
+//!
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use fljuga_handahofi_mlir_ir::{
+    is_bytecode, print, register_transforms, verify, BytecodeError, BytecodeReader, BytecodeReaderConfig,
+    BytecodeWriter, BytecodeWriterConfig, Context, Diagnostic, DialectRegistry, IrPrinting, IrPrintingConfig, Location,
+    Operation, Parser, PassError, PassManager, PassRegistry, PrinterOptions, Severity,
+};
+use fljuga_handahofi_targets::register_dialects;
+
+/// Line separating the chunks of the `--split-input-file` inputs, and of their outputs.
+pub const SPLIT_MARKER: &str = "// -----";
+
+/// Options of the driver, like the upstream `MlirOptMainConfig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptConfig {
+    /// Anchored pipeline of `--pass-pipeline`, like `builtin.module(canonicalize)`.
+    pub pipeline: Option<String>,
+    /// Pipeline elements of the pass flags, like `canonicalize{max-iterations=1}`,
+    /// run on the top level operation after the `pipeline`.
+    pub passes: Vec<String>,
+    /// `--split-input-file`, the chunks separated by the [SPLIT_MARKER] lines are processed independently.
+    pub split_input_file: bool,
+    /// `--verify-diagnostics`
+    pub verify_diagnostics: bool,
+    /// `--verify-each`, verifies the IR after every pass.
+    pub verify_each: bool,
+    /// `--mlir-print-ir-after-all`, the dumps are written along with the diagnostics.
+    pub print_ir_after_all: bool,
+    /// `--emit-bytecode`
+    pub emit_bytecode: bool,
+    pub printer: PrinterOptions,
+}
+
+impl Default for OptConfig {
+    fn default() -> Self {
+        Self {
+            pipeline: None,
+            passes: vec![],
+            split_input_file: false,
+            verify_diagnostics: false,
+            verify_each: true,
+            print_ir_after_all: false,
+            emit_bytecode: false,
+            printer: PrinterOptions::default(),
+        }
+    }
+}
+
+/// Derived [thiserror::Error] for the driver failures, the diagnostics are written along the way
+#[derive(thiserror::Error, Debug)]
+pub enum OptError {
+    #[error(transparent)]
+    Pipeline(#[from] PassError),
+
+    #[error("{0} of the input chunks failed")]
+    Failed(usize),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Input of a chunk, the annotations are read out of the textual ones.
+#[derive(Clone, Copy)]
+enum Input<'a> {
+    Text { source: &'a str, first_line: usize },
+    Bytecode(&'a [u8]),
+}
+
+/// `// expected-error @+1 {{message}}` annotation, the message is a substring of the expected diagnostic one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expectation {
+    severity: Severity,
+    /// Line of the diagnostic, `None` for the `@unknown` locations.
+    line: Option<u32>,
+    /// Line and column of the annotation itself.
+    position: (u32, u32),
+    message: String,
+}
+
+/// The driver with the target dialects and the dialect-independent passes registered.
+pub struct Opt {
+    config: OptConfig,
+    dialects: DialectRegistry,
+    passes: PassRegistry,
+}
+
+impl Opt {
+    /// Driver of the configuration, the pipeline is parsed upfront.
+    pub fn new(config: OptConfig) -> Result<Self, OptError> {
+        let mut dialects = DialectRegistry::default();
+        register_dialects(&mut dialects);
+        let mut passes = PassRegistry::new();
+        register_transforms(&mut passes);
+        let opt = Self {
+            config,
+            dialects,
+            passes,
+        };
+        opt.pass_manager()?;
+        Ok(opt)
+    }
+
+    pub fn dialects(&self) -> &DialectRegistry {
+        &self.dialects
+    }
+
+    pub fn passes(&self) -> &PassRegistry {
+        &self.passes
+    }
+
+    fn pass_manager(&self) -> Result<PassManager, PassError> {
+        let mut pm = match &self.config.pipeline {
+            Some(pipeline) => PassManager::parse(pipeline, &self.passes)?,
+            None => PassManager::new("builtin.module"),
+        };
+        if !self.config.passes.is_empty() {
+            pm.add_pipeline(&self.config.passes.join(","), &self.passes)?;
+        }
+        pm.enable_verifier(self.config.verify_each);
+        Ok(pm)
+    }
+
+    /// Processes the input of the file, writing the resulting IR to the output,
+    /// and the diagnostics along with the IR dumps to the errors.
+    pub fn run(
+        &self,
+        filename: &str,
+        input: &[u8],
+        output: &mut dyn Write,
+        errors: &mut dyn Write,
+    ) -> Result<(), OptError> {
+        let inputs = match is_bytecode(input) {
+            true => vec![Input::Bytecode(input)],
+            false => {
+                let source =
+                    std::str::from_utf8(input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                match self.config.split_input_file {
+                    true => split_input(source)
+                        .into_iter()
+                        .map(|(first_line, source)| Input::Text { source, first_line })
+                        .collect(),
+                    false => vec![Input::Text { source, first_line: 1 }],
+                }
+            }
+        };
+        let mut failed = 0;
+        for (index, input) in inputs.into_iter().enumerate() {
+            if index > 0 && !self.config.emit_bytecode {
+                writeln!(output, "{SPLIT_MARKER}")?;
+            }
+            if !self.run_chunk(filename, input, output, errors)? {
+                failed += 1;
+            }
+        }
+        match failed {
+            0 => Ok(()),
+            failed => Err(OptError::Failed(failed)),
+        }
+    }
+
+    /// Processes the chunk in its own context, whether it succeeded or produced the expected diagnostics.
+    fn run_chunk(
+        &self,
+        filename: &str,
+        input: Input<'_>,
+        output: &mut dyn Write,
+        errors: &mut dyn Write,
+    ) -> io::Result<bool> {
+        let mut ctx = Context::with_dialects(self.dialects.clone());
+        let loaded = match input {
+            Input::Text { source, first_line } => Parser::new(&mut ctx, source)
+                .with_file_locations(filename, first_line)
+                .parse_top_level()
+                .map_err(|error| {
+                    let location = Location::FileLineCol {
+                        filename: filename.to_string(),
+                        line: (first_line + error.line - 1) as u32,
+                        column: error.column as u32,
+                    };
+                    Diagnostic::error(location, error.message)
+                }),
+            Input::Bytecode(data) => {
+                read_bytecode(&mut ctx, data).map_err(|error| Diagnostic::error(Location::Unknown, error.to_string()))
+            }
+        };
+        let mut dumps = String::new();
+        let result = loaded.and_then(|op| self.process(&mut ctx, op, &mut dumps));
+        errors.write_all(dumps.as_bytes())?;
+        let diagnostics = match result {
+            Ok(ir) => {
+                output.write_all(&ir)?;
+                vec![]
+            }
+            Err(diagnostic) => vec![diagnostic],
+        };
+        if !self.config.verify_diagnostics {
+            for diagnostic in &diagnostics {
+                writeln!(errors, "{diagnostic}")?;
+            }
+            return Ok(diagnostics.is_empty());
+        }
+        let expectations = match input {
+            Input::Text { source, first_line } => expectations(source, first_line),
+            Input::Bytecode(_) => vec![],
+        };
+        let mismatches = check_expectations(filename, expectations, &diagnostics);
+        for mismatch in &mismatches {
+            writeln!(errors, "{mismatch}")?;
+        }
+        Ok(mismatches.is_empty())
+    }
+
+    /// Verifies the IR and runs the pipeline on it, returning the printed or the written bytecode result.
+    fn process(&self, ctx: &mut Context, op: Operation, dumps: &mut String) -> Result<Vec<u8>, Diagnostic> {
+        verify(ctx, op).map_err(|error| Diagnostic::from(&error))?;
+        let mut pm = self.pass_manager().map_err(|error| Diagnostic::from(&error))?;
+        let printed = Arc::new(Mutex::new(String::new()));
+        if self.config.print_ir_after_all {
+            let config = IrPrintingConfig {
+                after_all: true,
+                printer: self.config.printer,
+                ..IrPrintingConfig::default()
+            };
+            let printed = printed.clone();
+            pm.add_instrumentation(IrPrinting::new(config, move |dump| {
+                printed.lock().unwrap().push_str(dump)
+            }));
+        }
+        let result = pm.run(ctx, op);
+        dumps.push_str(&printed.lock().unwrap());
+        result.map_err(|error| Diagnostic::from(&error))?;
+        match self.config.emit_bytecode {
+            true => BytecodeWriter::new(ctx, BytecodeWriterConfig::default())
+                .write(op)
+                .map_err(|error| Diagnostic::error(Location::Unknown, error.to_string())),
+            false => Ok(print(ctx, op, self.config.printer).into_bytes()),
+        }
+    }
+}
+
+fn read_bytecode(ctx: &mut Context, data: &[u8]) -> Result<Operation, BytecodeError> {
+    BytecodeReader::new(data, BytecodeReaderConfig::default())?.read(ctx)
+}
+
+/// Chunks of the source separated by the [SPLIT_MARKER] lines, with the line numbers they start at.
+fn split_input(source: &str) -> Vec<(usize, &str)> {
+    let mut chunks = vec![];
+    let (mut start, mut first_line, mut offset) = (0, 1, 0);
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if line.trim() == SPLIT_MARKER {
+            chunks.push((first_line, &source[start..offset]));
+            start = offset + line.len();
+            first_line = index + 2;
+        }
+        offset += line.len();
+    }
+    chunks.push((first_line, &source[start..]));
+    chunks
+}
+
+/// `// expected-<severity> [@+N | @-N | @above | @below | @unknown] {{message}}` annotations of the source.
+fn expectations(source: &str, first_line: usize) -> Vec<Expectation> {
+    let mut expectations = vec![];
+    for (index, text) in source.lines().enumerate() {
+        let line = (first_line + index) as u32;
+        let Some(start) = text.find("// expected-") else {
+            continue;
+        };
+        let annotation = &text[start + "// expected-".len()..];
+        let Some((severity, rest)) = [
+            ("error", Severity::Error),
+            ("warning", Severity::Warning),
+            ("note", Severity::Note),
+            ("remark", Severity::Remark),
+        ]
+        .into_iter()
+        .find_map(|(name, severity)| annotation.strip_prefix(name).map(|rest| (severity, rest.trim_start()))) else {
+            continue;
+        };
+        let (target, rest) = match rest.strip_prefix('@') {
+            Some(designator) => {
+                let end = designator
+                    .find(|c: char| c.is_whitespace() || c == '{')
+                    .unwrap_or(designator.len());
+                let target = match &designator[..end] {
+                    "above" => Some(line - 1),
+                    "below" => Some(line + 1),
+                    "unknown" => None,
+                    offset => match offset.parse::<i64>() {
+                        Ok(offset) => Some((line as i64 + offset) as u32),
+                        Err(_) => continue,
+                    },
+                };
+                (target, designator[end..].trim_start())
+            }
+            None => (Some(line), rest),
+        };
+        let Some(message) = rest
+            .strip_prefix("{{")
+            .and_then(|rest| rest.rfind("}}").map(|end| &rest[..end]))
+        else {
+            continue;
+        };
+        expectations.push(Expectation {
+            severity,
+            line: target,
+            position: (line, start as u32 + 4),
+            message: message.to_string(),
+        });
+    }
+    expectations
+}
+
+/// Errors of the unexpected diagnostics and of the expectations left unmatched, the notes are matched
+/// as the diagnostics on their own.
+fn check_expectations(
+    filename: &str,
+    mut expectations: Vec<Expectation>,
+    diagnostics: &[Diagnostic],
+) -> Vec<Diagnostic> {
+    let mut mismatches = vec![];
+    let reported = diagnostics
+        .iter()
+        .flat_map(|diagnostic| std::iter::once(diagnostic).chain(&diagnostic.notes));
+    for diagnostic in reported {
+        // The diagnostics of the other files match no expectation.
+        let line = match diagnostic.location.file_line_col() {
+            Some((file, line, _)) if file == filename => Some(Some(line)),
+            Some(_) => None,
+            None => Some(None),
+        };
+        let matching = expectations.iter().position(|expectation| {
+            expectation.severity == diagnostic.severity
+                && line == Some(expectation.line)
+                && diagnostic.message.contains(&expectation.message)
+        });
+        match matching {
+            Some(index) => {
+                expectations.remove(index);
+            }
+            None => mismatches.push(Diagnostic::error(
+                diagnostic.location.clone(),
+                format!("unexpected {}: {}", diagnostic.severity, diagnostic.message),
+            )),
+        }
+    }
+    for expectation in expectations {
+        let location = Location::FileLineCol {
+            filename: filename.to_string(),
+            line: expectation.position.0,
+            column: expectation.position.1,
+        };
+        mismatches.push(Diagnostic::error(
+            location,
+            format!(
+                "expected {} \"{}\" was not produced",
+                expectation.severity, expectation.message
+            ),
+        ));
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#""func.func"() <{function_type = () -> i32, sym_name = "f"}> ({
+  %0 = "arith.constant"() <{value = 1 : i32}> : () -> i32
+  %1 = "arith.constant"() <{value = 2 : i32}> : () -> i32
+  "func.return"(%0) : (i32) -> ()
+}) : () -> ()
+
+// -----
+
+"func.func"() <{function_type = () -> (), sym_name = "g"}> ({
+  // expected-error @+1 {{block with no terminator}}
+  %0 = "arith.constant"() <{value = 1 : i32}> : () -> i32
+}) : () -> ()
+"#;
+
+    fn run(config: OptConfig, source: &str) -> (Result<(), OptError>, String, String) {
+        let opt = Opt::new(config).unwrap();
+        let (mut output, mut errors) = (vec![], vec![]);
+        let result = opt.run("input.mlir", source.as_bytes(), &mut output, &mut errors);
+        let text = |bytes: Vec<u8>| String::from_utf8(bytes).unwrap();
+        (result, text(output), text(errors))
+    }
+
+    #[test]
+    fn should_run_the_pipeline_on_the_split_input() {
+        let config = OptConfig {
+            passes: vec!["canonicalize".to_string()],
+            split_input_file: true,
+            print_ir_after_all: true,
+            ..OptConfig::default()
+        };
+        let (result, output, errors) = run(config, SOURCE);
+        assert!(matches!(result, Err(OptError::Failed(1))));
+        let canonical = r#"module {
+  func.func @f() -> i32 {
+    %0 = arith.constant 1 : i32
+    return %0 : i32
+  }
+}
+"#;
+        assert_eq!(output, format!("{canonical}{SPLIT_MARKER}\n"));
+        assert_eq!(
+            errors,
+            format!(
+                "// -----// IR Dump After Canonicalizer (canonicalize) ('builtin.module' operation) //----- //\n\
+                 {canonical}\n\
+                 loc(\"input.mlir\":11:3): error: 'arith.constant' op block with no terminator\n"
+            )
+        );
+    }
+
+    #[test]
+    fn should_verify_the_expected_diagnostics() {
+        let config = OptConfig {
+            split_input_file: true,
+            verify_diagnostics: true,
+            ..OptConfig::default()
+        };
+        let (result, _, errors) = run(config.clone(), SOURCE);
+        assert!(result.is_ok(), "{errors}");
+
+        let source = r#""test.op"() : () -> ()
+// expected-error @+1 {{never reported}}
+"func.func"() <{function_type = () -> (), sym_name = "f"}> ({
+  %0 = "arith.constant"() <{value = 1 : i32}> : () -> i32
+}) : () -> ()
+"#;
+        let (result, _, errors) = run(config, source);
+        assert!(matches!(result, Err(OptError::Failed(1))));
+        assert_eq!(
+            errors,
+            "loc(\"input.mlir\":4:3): error: unexpected error: 'arith.constant' op block with no terminator\n\
+             loc(\"input.mlir\":2:4): error: expected error \"never reported\" was not produced\n"
+        );
+    }
+
+    #[test]
+    fn should_report_the_parse_errors_in_the_input_file_lines() {
+        let config = OptConfig {
+            split_input_file: true,
+            ..OptConfig::default()
+        };
+        let (result, output, errors) = run(config, "\"test.op\"() : () -> ()\n// -----\n\n%0 = test.op");
+        assert!(matches!(result, Err(OptError::Failed(1))));
+        assert!(output.starts_with("module {\n  \"test.op\"() : () -> ()\n"), "{output}");
+        assert_eq!(
+            errors,
+            "loc(\"input.mlir\":4:6): error: custom op 'test.op' is unknown\n"
+        );
+        assert!(Opt::new(OptConfig {
+            pipeline: Some("builtin.module(unknown-pass)".to_string()),
+            ..OptConfig::default()
+        })
+        .is_err());
+    }
+}
@@ -14,7 +14,19 @@
    limitations under the License.
 */
 
-//! ## fljúga handahófi MLIR control flow dialect support.
 //!
-//! Describes internal MLIR control flow dialect.
+//! lit-style regression tests, the `.mlir` files under `tests/` run by their `// RUN:` lines,
+//! like `// RUN: fljuga-opt %s --canonicalize | FileCheck %s`.
 //!
+
+use fljuga_handahofi_filecheck::LitSuite;
+
+#[test]
+fn should_pass_the_lit_tests() {
+    let suite = LitSuite::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests"))
+        .tool("fljuga-opt", env!("CARGO_BIN_EXE_fljuga-opt"))
+        .temporary_dir(env!("CARGO_TARGET_TMPDIR"));
+    if let Err(report) = suite.run() {
+        panic!("\n{report}");
+    }
+}
@@ -0,0 +1,32 @@
+// RUN: fljuga-opt %s --canonicalize | FileCheck %s
+// RUN: fljuga-opt %s --pass-pipeline='builtin.module(func.func(canonicalize))' | FileCheck %s
+
+// CHECK-LABEL: func.func @constants
+// CHECK-SAME:    (%[[ARG:.*]]: i32) -> (i32, i64) {
+// CHECK-NOT:     arith.constant 7
+// CHECK-NOT:     arith.constant 0
+// CHECK-NOT:     arith.addi
+// CHECK:         %[[ONE:.*]] = arith.constant 1 : i64
+// CHECK-NEXT:    return %[[ARG]], %[[ONE]] : i32, i64
+// CHECK-NEXT:  }
+func.func @constants(%arg0: i32) -> (i32, i64) {
+  %c7 = arith.constant 7 : i32
+  %c1 = arith.constant 1 : i64
+  %c0 = arith.constant 0 : i32
+  %0 = arith.addi %arg0, %c0 : i32
+  return %0, %c1 : i32, i64
+}
+
+// CHECK-LABEL: func.func @reassociate
+// CHECK-SAME:    (%[[ARG:.*]]: i32) -> i32 {
+// CHECK:         %[[THREE:.*]] = arith.constant 3 : i32
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %[[ARG]], %[[THREE]] : i32
+// CHECK-NEXT:    return %[[SUM]] : i32
+// CHECK-NEXT:  }
+func.func @reassociate(%arg0: i32) -> i32 {
+  %c1 = arith.constant 1 : i32
+  %c2 = arith.constant 2 : i32
+  %0 = arith.addi %arg0, %c1 : i32
+  %1 = arith.addi %0, %c2 : i32
+  return %1 : i32
+}
@@ -0,0 +1,46 @@
+// RUN: fljuga-opt %s --cse | FileCheck %s
+// RUN: fljuga-opt %s --pass-pipeline='builtin.module(func.func(cse))' | FileCheck %s
+
+// CHECK-LABEL: func.func @simple_constant
+// CHECK-NEXT:    %[[ONE:.*]] = arith.constant 1 : i32
+// CHECK-NEXT:    return %[[ONE]], %[[ONE]] : i32, i32
+func.func @simple_constant() -> (i32, i32) {
+  %c1 = arith.constant 1 : i32
+  %c1_0 = arith.constant 1 : i32
+  return %c1, %c1_0 : i32, i32
+}
+
+// CHECK-LABEL: func.func @basic
+// CHECK-SAME:    (%[[ARG:.*]]: i32) -> (i32, i32) {
+// CHECK-NEXT:    %[[ONE:.*]] = arith.constant 1 : i32
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %[[ARG]], %[[ONE]] : i32
+// CHECK-NEXT:    return %[[SUM]], %[[SUM]] : i32, i32
+func.func @basic(%arg0: i32) -> (i32, i32) {
+  %c1 = arith.constant 1 : i32
+  %c1_0 = arith.constant 1 : i32
+  %0 = arith.addi %arg0, %c1 : i32
+  %1 = arith.addi %arg0, %c1_0 : i32
+  return %0, %1 : i32, i32
+}
+
+// The operations of the dominating blocks replace the ones of their successors, the ones of the blocks not
+// dominating each other are kept.
+// CHECK-LABEL: func.func @down_propagate
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %arg0, %arg0 : i32
+// CHECK:       ^bb1:
+// CHECK-NEXT:    arith.muli %[[SUM]], %[[SUM]] : i32
+// CHECK:       ^bb2:
+// CHECK-NEXT:    %[[PRODUCT:.*]] = arith.muli %[[SUM]], %[[SUM]] : i32
+// CHECK-NEXT:    arith.addi %[[PRODUCT]], %[[SUM]] : i32
+func.func @down_propagate(%arg0: i32, %arg1: i1) -> i32 {
+  %0 = arith.addi %arg0, %arg0 : i32
+  "cf.cond_br"(%arg1)[^bb1, ^bb2] <{operandSegmentSizes = array<i32: 1, 0, 0>}> : (i1) -> ()
+^bb1:
+  %1 = arith.muli %0, %0 : i32
+  return %1 : i32
+^bb2:
+  %2 = arith.muli %0, %0 : i32
+  %3 = arith.addi %arg0, %arg0 : i32
+  %4 = arith.addi %2, %3 : i32
+  return %4 : i32
+}
@@ -0,0 +1,35 @@
+// RUN: fljuga-opt %s | FileCheck %s
+// RUN: fljuga-opt %s --mlir-print-op-generic | FileCheck %s --check-prefix=GENERIC
+
+// CHECK-LABEL: module @forms attributes {test.unit} {
+// CHECK-NEXT:    func.func private @external(i16, i16 {test.arg}) -> i16
+// CHECK-NEXT:    func.func @call(%[[A:.*]]: i16, %[[B:.*]]: i16 {test.arg}) -> i16 attributes {test.fn} {
+// CHECK-NEXT:      %[[SUM:.*]] = call @external(%[[A]], %[[B]]) : (i16, i16) -> i16
+// CHECK-NEXT:      %[[CMP:.*]] = arith.cmpi slt, %[[SUM]], %[[A]] : i16
+// CHECK-NEXT:      %[[MIN:.*]] = arith.select %[[CMP]], %[[SUM]], %[[A]] : i16
+// CHECK-NEXT:      return %[[MIN]] : i16
+// CHECK-NEXT:    }
+// CHECK-NEXT:    func.func @generic() {
+// CHECK-NEXT:      return
+// CHECK-NEXT:    }
+// CHECK-NEXT:  }
+
+// GENERIC:       "builtin.module"() ({
+// GENERIC-NEXT:    "func.func"() ({
+// GENERIC-NEXT:    }) {arg_attrs = [{}, {test.arg}], function_type = (i16, i16) -> i16, sym_name = "external", sym_visibility = "private"} : () -> ()
+// GENERIC:         "func.call"(%arg0, %arg1) {callee = @external} : (i16, i16) -> i16
+// GENERIC:         "arith.cmpi"
+// GENERIC:         "func.return"(%{{.*}}) : (i16) -> ()
+// GENERIC:       }) {sym_name = "forms", test.unit} : () -> ()
+module @forms attributes {test.unit} {
+  func.func private @external(i16, i16 {test.arg}) -> i16
+  func.func @call(%a: i16, %b: i16 {test.arg}) -> i16 attributes {test.fn} {
+    %sum = func.call @external(%a, %b) : (i16, i16) -> i16
+    %cmp = arith.cmpi slt, %sum, %a : i16
+    %min = arith.select %cmp, %sum, %a : i16
+    return %min : i16
+  }
+  "func.func"() <{function_type = () -> (), sym_name = "generic"}> ({
+    "func.return"() : () -> ()
+  }) : () -> ()
+}
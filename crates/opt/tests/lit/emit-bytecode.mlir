@@ -0,0 +1,23 @@
+// RUN: fljuga-opt %s --emit-bytecode | fljuga-opt | FileCheck %s
+// RUN: fljuga-opt %s --emit-bytecode | fljuga-opt - --mlir-print-op-generic | FileCheck %s --check-prefix=GENERIC
+// RUN: fljuga-opt %s --emit-bytecode | fljuga-opt --canonicalize | FileCheck %s --check-prefix=CANONICAL
+
+// CHECK-LABEL: func.func @round_trip
+// CHECK-SAME:    (%[[ARG:.*]]: i32) -> i32 {
+// CHECK-NEXT:    %[[ZERO:.*]] = arith.constant 0 : i32
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %[[ARG]], %[[ZERO]] : i32
+// CHECK-NEXT:    return %[[SUM]] : i32
+
+// GENERIC:       "func.func"() ({
+// GENERIC:         "arith.addi"(%{{.*}}, %{{.*}}) : (i32, i32) -> i32
+// GENERIC-NEXT:    "func.return"
+// GENERIC-NEXT:  }) {function_type = (i32) -> i32, sym_name = "round_trip"} : () -> ()
+
+// CANONICAL-LABEL: func.func @round_trip
+// CANONICAL-SAME:    (%[[ARG:.*]]: i32) -> i32 {
+// CANONICAL-NEXT:    return %[[ARG]] : i32
+func.func @round_trip(%arg0: i32) -> i32 {
+  %c0 = arith.constant 0 : i32
+  %0 = arith.addi %arg0, %c0 : i32
+  return %0 : i32
+}
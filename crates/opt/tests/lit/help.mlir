@@ -0,0 +1,31 @@
+// RUN: fljuga-opt --help | FileCheck %s
+// RUN: not fljuga-opt %s --unknown-option 2>&1 | FileCheck %s --check-prefix=UNKNOWN
+// RUN: not fljuga-opt %s --cse --pass-pipeline='builtin.module(cse)' 2>&1 | FileCheck %s --check-prefix=COMBINED
+// RUN: not fljuga-opt %s %s 2>&1 | FileCheck %s --check-prefix=INPUTS
+
+// CHECK:       USAGE: fljuga-opt [options] <input file>
+// CHECK:       OPTIONS:
+// CHECK-NEXT:    -o <file>
+// CHECK-NEXT:    --pass-pipeline=<pipeline>
+// CHECK-NEXT:    --<pass>[=<options>]
+// CHECK-NEXT:    --split-input-file
+// CHECK-NEXT:    --verify-diagnostics
+// CHECK-NEXT:    --verify-each[=<bool>]
+// CHECK-NEXT:    --mlir-print-ir-after-all
+// CHECK-NEXT:    --mlir-print-debuginfo
+// CHECK-NEXT:    --mlir-print-local-scope
+// CHECK-NEXT:    --mlir-print-op-generic
+// CHECK-NEXT:    --emit-bytecode
+// CHECK-NEXT:    --help
+// CHECK:       PASSES:
+// CHECK-DAG:     --canonicalize {{ *}}Canonicalize operations
+// CHECK-DAG:     --cse {{ *}}Eliminate common sub-expressions
+// CHECK-DAG:     --sccp {{ *}}Sparse Conditional Constant Propagation
+// CHECK-DAG:     --symbol-dce {{ *}}Eliminate dead symbols
+
+// UNKNOWN:     error: unknown option `--unknown-option`
+// UNKNOWN:     USAGE: fljuga-opt
+
+// COMBINED:    error: `--pass-pipeline` can't be combined with the pass options
+
+// INPUTS:      error: only one input file is supported
@@ -0,0 +1,13 @@
+// RUN: fljuga-opt %s --canonicalize -o %t
+// RUN: FileCheck %s --input-file=%t
+// RUN: fljuga-opt %s --canonicalize --emit-bytecode -o %t
+// RUN: fljuga-opt %t | FileCheck %s
+
+// CHECK-LABEL: func.func @output
+// CHECK-NEXT:    %[[TWO:.*]] = arith.constant 2 : i32
+// CHECK-NEXT:    return %[[TWO]] : i32
+func.func @output() -> i32 {
+  %c1 = arith.constant 1 : i32
+  %0 = arith.addi %c1, %c1 : i32
+  return %0 : i32
+}
@@ -0,0 +1,18 @@
+// RUN: fljuga-opt %s --canonicalize --mlir-print-ir-after-all 2>&1 | FileCheck %s
+// RUN: not fljuga-opt %s --unknown-pass 2>&1 | FileCheck %s --check-prefix=ERROR
+
+// CHECK-LABEL: // -----// IR Dump After Canonicalizer (canonicalize) ('builtin.module' operation) //----- //
+// CHECK-NEXT:  module {
+// CHECK-NEXT:    func.func @dead_constant() {
+// CHECK-NEXT:      return
+// CHECK-NEXT:    }
+// CHECK-NEXT:  }
+// CHECK-EMPTY:
+// CHECK-NEXT:  module {
+// CHECK-NOT:     arith.constant
+
+// ERROR: unknown option `--unknown-pass`
+func.func @dead_constant() {
+  %c7 = arith.constant 7 : i32
+  return
+}
@@ -0,0 +1,26 @@
+// RUN: fljuga-opt %s | FileCheck %s
+// RUN: fljuga-opt %s --mlir-print-debuginfo | FileCheck %s --check-prefix=DEBUGINFO
+// RUN: fljuga-opt %s --mlir-print-local-scope | FileCheck %s --check-prefix=LOCAL
+// RUN: fljuga-opt %s --mlir-print-debuginfo --mlir-print-local-scope | FileCheck %s --check-prefix=LOCAL-DEBUGINFO
+
+// CHECK:       #map = affine_map<(d0) -> (d0 + 1)>
+// CHECK:       func.func @flags() attributes {first = #map, second = #map} {
+// CHECK-NOT:     loc(
+// CHECK:         return
+// CHECK-NOT:     loc(
+
+// DEBUGINFO:       func.func @flags() attributes {first = #map, second = #map} {
+// DEBUGINFO-NEXT:    return loc(#[[RETURN:loc[0-9]*]])
+// DEBUGINFO-NEXT:  } loc(#[[FUNC:loc[0-9]*]])
+// DEBUGINFO-DAG:   #[[RETURN]] = loc("{{.*}}printer-flags.mlir":25:3)
+// DEBUGINFO-DAG:   #[[FUNC]] = loc("{{.*}}printer-flags.mlir":24:1)
+
+// LOCAL-NOT:   #map
+// LOCAL:       func.func @flags() attributes {first = affine_map<(d0) -> (d0 + 1)>, second = affine_map<(d0) -> (d0 + 1)>} {
+
+// LOCAL-DEBUGINFO:       return loc("{{.*}}printer-flags.mlir":25:3)
+// LOCAL-DEBUGINFO-NEXT:  } loc("{{.*}}printer-flags.mlir":24:1)
+#map = affine_map<(d0) -> (d0 + 1)>
+func.func @flags() attributes {first = #map, second = #map} {
+  return
+}
@@ -0,0 +1,29 @@
+// RUN: fljuga-opt %s --sccp | FileCheck %s
+// RUN: fljuga-opt %s --pass-pipeline='builtin.module(sccp)' | FileCheck %s
+
+// CHECK-LABEL: func.func @no_control_flow
+// CHECK-SAME:    (%[[ARG:.*]]: i32) -> i32 {
+// CHECK-NOT:     arith.addi %{{.*}}, %{{.*}} : i32
+// CHECK:         %[[THREE:.*]] = arith.constant 3 : i32
+// CHECK-NEXT:    %[[SUM:.*]] = arith.addi %[[THREE]], %[[ARG]] : i32
+// CHECK-NEXT:    return %[[SUM]] : i32
+func.func @no_control_flow(%arg0: i32) -> i32 {
+  %c1 = arith.constant 1 : i32
+  %c2 = arith.constant 2 : i32
+  %0 = arith.addi %c1, %c2 : i32
+  %1 = arith.addi %0, %arg0 : i32
+  return %1 : i32
+}
+
+// The constant reaches the block argument through the branch.
+// CHECK-LABEL: func.func @simple_control_flow
+// CHECK:       ^bb1(%{{.*}}: i32):
+// CHECK-NEXT:    %[[TWO:.*]] = arith.constant 2 : i32
+// CHECK-NEXT:    return %[[TWO]] : i32
+func.func @simple_control_flow() -> i32 {
+  %c1 = arith.constant 1 : i32
+  "cf.br"(%c1)[^bb1] : (i32) -> ()
+^bb1(%0: i32):
+  %1 = arith.addi %0, %c1 : i32
+  return %1 : i32
+}
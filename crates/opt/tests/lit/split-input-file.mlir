@@ -0,0 +1,30 @@
+// RUN: fljuga-opt %s --split-input-file --canonicalize | FileCheck %s
+// RUN: not fljuga-opt %s 2>&1 | FileCheck %s --check-prefix=WHOLE
+
+// The chunks are parsed in their own contexts and printed as separate modules, the symbols of the same name
+// don't clash.
+
+// CHECK-LABEL: func.func @chunk
+// CHECK-NEXT:    %[[ONE:.*]] = arith.constant 1 : i32
+// CHECK-NEXT:    return %[[ONE]] : i32
+// CHECK-NEXT:  }
+// CHECK-NEXT:  }
+// CHECK-NEXT:  // -----
+// CHECK-NEXT:  module {
+// CHECK-NEXT:    func.func @chunk() -> i64 {
+// CHECK-NEXT:      %[[TWO:.*]] = arith.constant 2 : i64
+// CHECK-NEXT:      return %[[TWO]] : i64
+
+// WHOLE: redefinition of symbol named 'chunk'
+func.func @chunk() -> i32 {
+  %c1 = arith.constant 1 : i32
+  return %c1 : i32
+}
+
+// -----
+
+func.func @chunk() -> i64 {
+  %c1 = arith.constant 1 : i64
+  %0 = arith.addi %c1, %c1 : i64
+  return %0 : i64
+}
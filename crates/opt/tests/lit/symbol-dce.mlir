@@ -0,0 +1,19 @@
+// RUN: fljuga-opt %s --symbol-dce | FileCheck %s
+// RUN: fljuga-opt %s --pass-pipeline='builtin.module(symbol-dce)' | FileCheck %s
+
+// CHECK-NOT:   func.func private @dead_private
+// CHECK-NOT:   func.func private @dead_from_dead
+// CHECK:       func.func private @live_private() -> i32
+// CHECK:       func.func @public
+// CHECK-NEXT:    call @live_private() : () -> i32
+func.func private @dead_private()
+func.func private @dead_from_dead() -> i32
+func.func private @dead_caller() -> i32 {
+  %0 = func.call @dead_from_dead() : () -> i32
+  return %0 : i32
+}
+func.func private @live_private() -> i32
+func.func @public() -> i32 {
+  %0 = func.call @live_private() : () -> i32
+  return %0 : i32
+}
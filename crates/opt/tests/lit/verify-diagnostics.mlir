@@ -0,0 +1,13 @@
+// RUN: fljuga-opt %s --split-input-file --verify-diagnostics
+
+func.func @early_return() {
+  "func.return"() : () -> ()
+  // expected-error @+1 {{block with no terminator}}
+  %c7 = arith.constant 7 : i32
+}
+
+// -----
+
+// expected-error @below {{expects the body to have one block}}
+"builtin.module"() ({
+}) : () -> ()
@@ -0,0 +1,14 @@
+// RUN: fljuga-opt %s --canonicalize --verify-each | FileCheck %s
+// RUN: fljuga-opt %s --canonicalize --verify-each=true | FileCheck %s
+// RUN: fljuga-opt %s --canonicalize --verify-each=false | FileCheck %s
+// RUN: not fljuga-opt %s --verify-each=maybe 2>&1 | FileCheck %s --check-prefix=INVALID
+
+// CHECK-LABEL: func.func @verified
+// CHECK-NEXT:    return %arg0 : i32
+
+// INVALID: error: invalid `verify-each` value `maybe`
+func.func @verified(%arg0: i32) -> i32 {
+  %c0 = arith.constant 0 : i32
+  %0 = arith.addi %arg0, %c0 : i32
+  return %0 : i32
+}
@@ -0,0 +1,410 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi tablegen
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Fetches the MLIR dialect `.td` files out of a [TdSource], following their `include` directives from the
+//! [ENTRY_POINTS], and pins them with the [TdLock] the `dialect!` macro verifies the vendored files against.
+//!
+
+pub mod client;
+pub mod lock;
+pub mod source;
+
+use crate::ast::Statement;
+use client::ClientError;
+pub use lock::{LockedFile, TdLock};
+pub use source::{LlvmCheckout, LlvmTarball, Mirror, TdSource, Vendored};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use tokio::io;
+
+/// Derived [thiserror::Error] for TableGen errors
+#[derive(thiserror::Error, Debug)]
+pub enum TableGenError {
+    #[error("{0}")]
+    HttpClientError(#[from] ClientError),
+
+    #[error("{0}")]
+    IoError(#[from] io::Error),
+
+    #[error("`{path}` is not in the {provider}")]
+    NotFound { path: String, provider: String },
+
+    #[error("`{path}` is not relative to the include directory")]
+    InvalidPath { path: String },
+
+    #[error("`{path}` included by `{included_by}` is not in the {provider}")]
+    MissingInclude {
+        path: String,
+        included_by: String,
+        provider: String,
+    },
+
+    #[error("`{path}` is not a valid TableGen file: {message}")]
+    ParseError { path: String, message: String },
+
+    #[error("{0}")]
+    LockError(#[from] serde_json::Error),
+
+    #[error("`{path}` differs from the tablegen.lock, remove it to update the files")]
+    LockMismatch { path: String },
+}
+
+/// Upstream llvm-project revision the vendored `.td` files are fetched at.
+pub const LLVM_REVISION: &str = "llvmorg-19.1.7";
+
+/// Dialect `.td` files of the dialects the upstream Python bindings generate, of the dialect crates,
+/// and the `PatternBase.td` of the arith canonicalization patterns, relative to the `mlir/include` directory.
+/// Their includes are resolved by [resolve_includes], adding a dialect takes a single entry point.
+pub const ENTRY_POINTS: [&str; 60] = [
+    "mlir/Dialect/AMDGPU/IR/AMDGPU.td",
+    "mlir/Dialect/AMX/AMX.td",
+    "mlir/Dialect/Affine/IR/AffineOps.td",
+    "mlir/Dialect/Arith/IR/ArithOps.td",
+    "mlir/Dialect/ArmNeon/ArmNeon.td",
+    "mlir/Dialect/ArmSME/IR/ArmSMEOps.td",
+    "mlir/Dialect/ArmSVE/IR/ArmSVE.td",
+    "mlir/Dialect/Async/IR/AsyncOps.td",
+    "mlir/Dialect/Bufferization/IR/BufferizationEnums.td",
+    "mlir/Dialect/Bufferization/IR/BufferizationOps.td",
+    "mlir/Dialect/Bufferization/TransformOps/BufferizationTransformOps.td",
+    "mlir/Dialect/Complex/IR/ComplexOps.td",
+    "mlir/Dialect/ControlFlow/IR/ControlFlowOps.td",
+    "mlir/Dialect/EmitC/IR/EmitC.td",
+    "mlir/Dialect/Func/IR/FuncOps.td",
+    "mlir/Dialect/GPU/IR/GPUOps.td",
+    "mlir/Dialect/GPU/TransformOps/GPUTransformOps.td",
+    "mlir/Dialect/Index/IR/IndexOps.td",
+    "mlir/Dialect/LLVMIR/LLVMIntrinsicOps.td",
+    "mlir/Dialect/LLVMIR/LLVMOps.td",
+    "mlir/Dialect/LLVMIR/NVVMOps.td",
+    "mlir/Dialect/LLVMIR/ROCDLOps.td",
+    "mlir/Dialect/LLVMIR/VCIXOps.td",
+    "mlir/Dialect/Linalg/IR/LinalgOps.td",
+    "mlir/Dialect/Linalg/IR/LinalgStructuredOps.td",
+    "mlir/Dialect/Linalg/TransformOps/LinalgTransformEnums.td",
+    "mlir/Dialect/Linalg/TransformOps/LinalgTransformOps.td",
+    "mlir/Dialect/MLProgram/IR/MLProgramOps.td",
+    "mlir/Dialect/MPI/IR/MPIOps.td",
+    "mlir/Dialect/Math/IR/MathOps.td",
+    "mlir/Dialect/MemRef/IR/MemRefOps.td",
+    "mlir/Dialect/MemRef/TransformOps/MemRefTransformOps.td",
+    "mlir/Dialect/NVGPU/IR/NVGPU.td",
+    "mlir/Dialect/NVGPU/TransformOps/NVGPUTransformOps.td",
+    "mlir/Dialect/OpenACC/OpenACCOps.td",
+    "mlir/Dialect/OpenMP/OpenMPOps.td",
+    "mlir/Dialect/PDL/IR/PDLOps.td",
+    "mlir/Dialect/SCF/IR/SCFOps.td",
+    "mlir/Dialect/SCF/TransformOps/SCFTransformOps.td",
+    "mlir/Dialect/SPIRV/IR/SPIRVOps.td",
+    "mlir/Dialect/Shape/IR/ShapeOps.td",
+    "mlir/Dialect/SparseTensor/IR/SparseTensorAttrDefs.td",
+    "mlir/Dialect/SparseTensor/IR/SparseTensorOps.td",
+    "mlir/Dialect/SparseTensor/TransformOps/SparseTensorTransformOps.td",
+    "mlir/Dialect/Tensor/IR/TensorOps.td",
+    "mlir/Dialect/Tensor/TransformOps/TensorTransformOps.td",
+    "mlir/Dialect/Tosa/IR/TosaOps.td",
+    "mlir/Dialect/Transform/IR/TransformAttrs.td",
+    "mlir/Dialect/Transform/IR/TransformOps.td",
+    "mlir/Dialect/Transform/LoopExtension/LoopExtensionOps.td",
+    "mlir/Dialect/Transform/PDLExtension/PDLExtensionOps.td",
+    "mlir/Dialect/Vector/IR/Vector.td",
+    "mlir/Dialect/Vector/IR/VectorAttributes.td",
+    "mlir/Dialect/Vector/IR/VectorOps.td",
+    "mlir/Dialect/Vector/TransformOps/VectorTransformOps.td",
+    "mlir/Dialect/Vector/Transforms/VectorTransformsBase.td",
+    "mlir/Dialect/X86Vector/X86Vector.td",
+    "mlir/Dialect/XeGPU/IR/XeGPUOps.td",
+    "mlir/IR/BuiltinOps.td",
+    "mlir/IR/PatternBase.td",
+];
+
+/// `include` directives of the statements, the nested blocks included.
+fn includes(statements: &[Statement], found: &mut Vec<String>) {
+    for statement in statements {
+        match statement {
+            Statement::Include(path) => found.push(path.clone()),
+            Statement::Foreach { statements, .. }
+            | Statement::Let { statements, .. }
+            | Statement::MultiClass { statements, .. } => includes(statements, found),
+            Statement::If {
+                then_statements,
+                else_statements,
+                ..
+            } => {
+                includes(then_statements, found);
+                includes(else_statements, found);
+            }
+            Statement::Defset(definition) => includes(&definition.statements, found),
+            _ => {}
+        }
+    }
+}
+
+/// Reads the include closure of the entry points, the includes resolve against the include directory like `-I`.
+pub async fn resolve_includes(
+    source: &impl TdSource,
+    entry_points: &[&str],
+) -> Result<(BTreeMap<String, String>, TdLock), TableGenError> {
+    let mut files = BTreeMap::new();
+    let mut lock = TdLock {
+        source: source.name(),
+        entry_points: entry_points.iter().map(ToString::to_string).collect(),
+        files: BTreeMap::new(),
+    };
+
+    let mut queue: VecDeque<_> = entry_points.iter().map(|path| (path.to_string(), None)).collect();
+    while let Some((path, included_by)) = queue.pop_front() {
+        if files.contains_key(&path) {
+            continue;
+        }
+        let contents = source.read(&path).await.map_err(|error| match (error, included_by) {
+            (TableGenError::NotFound { path, provider }, Some(included_by)) => TableGenError::MissingInclude {
+                path,
+                included_by,
+                provider,
+            },
+            (error, _) => error,
+        })?;
+        let tablegen = crate::parse(&contents).map_err(|error| TableGenError::ParseError {
+            path: path.clone(),
+            message: error.to_string(),
+        })?;
+
+        let mut found = vec![];
+        includes(&tablegen.statements, &mut found);
+        queue.extend(found.iter().map(|include| (include.clone(), Some(path.clone()))));
+        lock.files.insert(path.clone(), LockedFile::new(&contents, found));
+        files.insert(path, contents);
+    }
+    Ok((files, lock))
+}
+
+/// Copies the include closure of the entry points into the `dest` include directory, keeping their paths,
+/// and pins it with the `tablegen.lock` file, the files have to match the existing one.
+pub async fn fetch_tablegen_files(
+    source: &impl TdSource,
+    entry_points: &[&str],
+    dest: &Path,
+) -> Result<TdLock, TableGenError> {
+    let (files, lock) = resolve_includes(source, entry_points).await?;
+    let lock_path = dest.join(TdLock::FILE_NAME);
+    if let Some(locked) = TdLock::read(&lock_path)? {
+        locked.verify(&lock)?;
+    }
+
+    for (path, contents) in files {
+        let file = dest.join(path);
+        if let Some(dir) = file.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(file, contents).await?
+    }
+    lock.write(&lock_path)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Value;
+    use crate::records::{IncludeDirs, RecordKeeper};
+    use source::tests::corpus;
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    use once_cell::sync::Lazy;
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    use tokio::sync::Mutex;
+
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    static TEST_FIXTURES_DIR: Lazy<Mutex<&Path>> =
+        Lazy::new(|| Mutex::new(Path::new("./.fixtures")));
+
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    async fn tablegen_fixtures() -> Vec<String> {
+        let fixtures_path = TEST_FIXTURES_DIR.lock().await;
+
+        if !Path::exists(*fixtures_path) {
+            tokio::fs::create_dir_all(*fixtures_path).await.unwrap();
+            let source = source::Mirror::default();
+            assert!(fetch_tablegen_files(&source, &ENTRY_POINTS, *fixtures_path).await.is_ok());
+        }
+
+        // Read the files in order, keeping their include paths
+        let mut downloaded_files: Vec<String> = Vec::new();
+
+        for path in ENTRY_POINTS {
+            let file_path = fixtures_path.join(path);
+            // Ensure the file is non-empty
+            let metadata = tokio::fs::metadata(&file_path).await.unwrap();
+            assert!(metadata.is_file());
+            assert!(metadata.len() > 0, "File {:?} is empty", file_path);
+
+            downloaded_files.push(
+                String::from_utf8(tokio::fs::read(file_path).await.unwrap().to_vec()).unwrap(),
+            );
+        }
+
+        downloaded_files
+    }
+
+    /// Copy of the vendored files and the mlir-codegen test ones, modifiable by the tests.
+    fn vendored_copy(dir: &Path) -> source::Vendored {
+        let root = dir.join("vendored");
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let files = [
+            ("TestOps.td", manifest_dir.join("../mlir-codegen/src/testdata/ods")),
+            ("TestInterfaces.td", manifest_dir.join("../mlir-codegen/src/testdata/ods")),
+            ("ArithCanonicalization.td", manifest_dir.join("../targets/src/arith")),
+            ("mlir/Dialect/Arith/IR/ArithOps.td", corpus().root),
+            ("mlir/IR/OpBase.td", corpus().root),
+            ("mlir/IR/PatternBase.td", corpus().root),
+        ];
+        for (path, dir) in files {
+            std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            std::fs::copy(dir.join(path), root.join(path)).unwrap();
+        }
+        source::Vendored { root }
+    }
+
+    /// It should follow the includes of the entry points, each file is read once.
+    #[tokio::test]
+    async fn should_resolve_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = vendored_copy(dir.path());
+        let (files, lock) = resolve_includes(&source, &["ArithCanonicalization.td", "TestOps.td"])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            [
+                "ArithCanonicalization.td",
+                "TestInterfaces.td",
+                "TestOps.td",
+                "mlir/Dialect/Arith/IR/ArithOps.td",
+                "mlir/IR/OpBase.td",
+                "mlir/IR/PatternBase.td",
+            ]
+        );
+        assert_eq!(
+            lock.files["ArithCanonicalization.td"].includes,
+            ["mlir/Dialect/Arith/IR/ArithOps.td", "mlir/IR/PatternBase.td"]
+        );
+        assert_eq!(lock.files["TestOps.td"].includes, ["mlir/IR/OpBase.td", "TestInterfaces.td"]);
+        assert!(lock.files["mlir/IR/OpBase.td"].includes.is_empty());
+        assert_eq!(lock.entry_points, ["ArithCanonicalization.td", "TestOps.td"]);
+    }
+
+    #[tokio::test]
+    async fn should_report_missing_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = vendored_copy(dir.path());
+        std::fs::remove_file(source.root.join("TestInterfaces.td")).unwrap();
+
+        let error = resolve_includes(&source, &["TestOps.td"]).await.unwrap_err();
+        assert!(
+            matches!(&error, TableGenError::MissingInclude { path, included_by, .. } if path == "TestInterfaces.td" && included_by == "TestOps.td"),
+            "{error}"
+        );
+        assert!(matches!(
+            resolve_includes(&source, &["Missing.td"]).await,
+            Err(TableGenError::NotFound { .. })
+        ));
+    }
+
+    /// It should keep the include paths, so the includes resolve against the destination.
+    #[tokio::test]
+    async fn should_fetch_tablegen_files() {
+        let dest = tempfile::tempdir().unwrap();
+        let entry_point = "mlir/Dialect/Arith/IR/ArithOps.td";
+        let lock = fetch_tablegen_files(&corpus(), &[entry_point], dest.path())
+            .await
+            .unwrap();
+        assert_eq!(lock.files.len(), 2);
+        assert_eq!(TdLock::read(&dest.path().join(TdLock::FILE_NAME)).unwrap(), Some(lock));
+
+        let filename = dest.path().join(entry_point);
+        let contents = std::fs::read_to_string(&filename).unwrap();
+        let includes = IncludeDirs::new([dest.path().to_path_buf()]);
+        let keeper = RecordKeeper::from_source(&filename.to_string_lossy(), &contents, &includes).unwrap();
+        assert!(keeper.def("Arith_AddIOp").is_some());
+    }
+
+    /// It should refuse the files changed since they were locked.
+    #[tokio::test]
+    async fn should_verify_locked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = vendored_copy(dir.path());
+        let dest = dir.path().join("include");
+        std::fs::create_dir_all(&dest).unwrap();
+
+        fetch_tablegen_files(&source, &["TestOps.td"], &dest).await.unwrap();
+        fetch_tablegen_files(&source, &["TestOps.td"], &dest).await.unwrap();
+
+        std::fs::write(source.root.join("mlir/IR/OpBase.td"), "class Op;").unwrap();
+        assert!(matches!(
+            fetch_tablegen_files(&source, &["TestOps.td"], &dest).await,
+            Err(TableGenError::LockMismatch { path }) if path == "mlir/IR/OpBase.td"
+        ));
+    }
+
+    /// It should be able to download tablegen files to a tmp dir.
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    #[tokio::test]
+    async fn should_download_tablegen_files() {
+        let downloaded_files = tablegen_fixtures().await;
+        goldie::assert!(downloaded_files.join("\n\n"));
+    }
+
+    #[cfg(feature = "optional-tests-with-fixtures")]
+    #[tokio::test]
+    async fn should_parse_tablegen_files() {
+        let downloaded_files = tablegen_fixtures().await;
+        assert!(!downloaded_files.is_empty());
+
+        for (path, contents) in ENTRY_POINTS.iter().zip(downloaded_files) {
+            if let Err(error) = crate::parse(&contents) {
+                panic!("{path}: {error}");
+            }
+        }
+    }
+
+    /// It should parse the upstream `.td` files of the llvm-project `main` branch the fixture golden was
+    /// recorded from, joined into a single input.
+    #[test]
+    fn should_parse_upstream_tablegen_files() {
+        let upstream = include_str!("testdata/should_download_tablegen_files.golden");
+        let tablegen = crate::parse(upstream).unwrap();
+
+        let defs: Vec<_> = tablegen
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Def(def) => def.name.clone(),
+                _ => None,
+            })
+            .collect();
+        for name in ["Arith_AddIOp", "AMDGPU_LDSBarrierOp", "NVVM_Barrier0Op", "FuncOp", "ParallelOp"] {
+            assert!(defs.iter().any(|def| *def == Value::Identifier(name.to_string())), "{name}");
+        }
+        let includes = tablegen.statements.iter().filter(|statement| matches!(statement, Statement::Include(_)));
+        assert!(includes.count() > 200);
+    }
+}
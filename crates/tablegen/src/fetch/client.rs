@@ -0,0 +1,746 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi tablegen
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Http Client Module of the [Mirror](super::Mirror) `.td` sources.
+//!
+//! The client reuses the connections, retries the server errors and the connection failures with
+//! an exponential backoff, tunnels through the plain `http` `HTTPS_PROXY` unless the host is listed
+//! in `NO_PROXY`, and revalidates the cached responses with their `ETag`.
+//!
+
+use std::future::Future;
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http_body_util::*;
+use hyper::body::{Buf, Bytes};
+use hyper::header::{HeaderValue, ToStrError, ETAG, IF_NONE_MATCH, LOCATION};
+use hyper::http::uri::InvalidUri;
+use hyper::{Method, Request, StatusCode, Uri};
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client as HyperClient;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Derived [thiserror::Error] for hyper errors
+#[derive(thiserror::Error, Debug)]
+pub enum ClientError {
+    #[error("Invalid URI: {0}")]
+    InvalidUri(#[from] InvalidUri),
+
+    #[error("{0}")]
+    BackendError(#[from] hyper::Error),
+
+    #[error("{0}")]
+    HttpError(#[from] hyper::http::Error),
+
+    #[error("{0}")]
+    HyperError(#[from] hyper_util::client::legacy::Error),
+
+    #[error("{to:?}")]
+    Redirected { to: String },
+
+    #[error("{uri:?}")]
+    LocationMissing { uri: String },
+
+    #[error("{0}")]
+    StringConvertError(#[from] ToStrError),
+
+    #[error("Too many redirects")]
+    TooManyRedirects,
+
+    #[error("No content")]
+    NoContent { status_code: StatusCode },
+
+    #[error("{uri:?} timed out")]
+    Timeout { uri: String },
+
+    #[error("Invalid redirect from {uri:?} to {location:?}")]
+    InvalidLocation { uri: String, location: String },
+
+    #[error("{0}")]
+    CacheError(#[from] std::io::Error),
+}
+
+impl ClientError {
+    /// Server errors, connection failures and timeouts are worth another attempt.
+    fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::NoContent { status_code } => status_code.is_server_error(),
+            ClientError::HyperError(error) => error.is_connect(),
+            ClientError::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// `HTTPS_PROXY` the requests are tunneled through with `CONNECT`, except the `NO_PROXY` hosts.
+/// The proxy itself is connected over plain TCP, the `https` ones are rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proxy {
+    pub uri: Uri,
+    /// Hosts and domains connected directly, `*` bypasses the proxy for all of them.
+    pub no_proxy: Vec<String>,
+}
+
+impl Proxy {
+    /// Reads the `HTTPS_PROXY` and `NO_PROXY` environment variables, or their lowercase variants.
+    pub fn from_env() -> Option<Proxy> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .or_else(|_| std::env::var(name.to_ascii_lowercase()))
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+        let uri = var("HTTPS_PROXY")?;
+        let uri = match uri.contains("://") {
+            true => uri.parse().ok()?,
+            false => format!("http://{uri}").parse().ok()?,
+        };
+        let no_proxy = var("NO_PROXY")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().to_ascii_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+        Some(Proxy { uri, no_proxy })
+    }
+
+    /// Whether the host is connected through the proxy, the `NO_PROXY` domains include their subdomains.
+    pub fn intercepts(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        !self.no_proxy.iter().any(|no_proxy| {
+            let domain = no_proxy.trim_start_matches('.');
+            no_proxy == "*" || host == domain || host.ends_with(&format!(".{domain}"))
+        })
+    }
+}
+
+/// [Client] settings, the defaults read the proxy out of the environment.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub connect_timeout: Duration,
+    /// Time to receive the whole response, headers and body.
+    pub read_timeout: Duration,
+    /// Attempts after the first one failed with a server error, a connection failure or a timeout.
+    pub retries: usize,
+    /// Delay before the first retry, doubled by every next one.
+    pub backoff: Duration,
+    pub proxy: Option<Proxy>,
+    /// Directory of the responses cached by their URL, revalidated with their `ETag`.
+    pub cache_dir: Option<PathBuf>,
+    /// Rejects the plain `http` URLs.
+    pub https_only: bool,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            retries: 3,
+            backoff: Duration::from_millis(250),
+            proxy: Proxy::from_env(),
+            cache_dir: None,
+            https_only: true,
+        }
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// [HttpConnector] tunneling the connections through the [Proxy].
+#[derive(Clone)]
+struct ProxyConnector {
+    http: HttpConnector,
+    proxy: Option<Arc<Proxy>>,
+}
+
+/// Opens the `CONNECT` tunnel to the authority of the uri.
+async fn tunnel(stream: &mut tokio::net::TcpStream, uri: &Uri) -> Result<(), BoxError> {
+    let host = uri.host().ok_or("uri without a host")?;
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("http") => 80,
+        _ => 443,
+    });
+    let request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = vec![];
+    let mut buffer = [0; 1024];
+    while !response.ends_with(b"\r\n\r\n") {
+        let read = stream.read(&mut buffer[..1]).await?;
+        if read == 0 || response.len() > 8192 {
+            return Err("proxy closed the connection".into());
+        }
+        response.extend_from_slice(&buffer[..read]);
+    }
+    let status = String::from_utf8_lossy(&response);
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("proxy refused the tunnel: {}", status.lines().next().unwrap_or_default()).into()),
+    }
+}
+
+impl tower_service::Service<Uri> for ProxyConnector {
+    type Response = TokioIo<tokio::net::TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let mut http = self.http.clone();
+        let proxy = self
+            .proxy
+            .clone()
+            .filter(|proxy| proxy.intercepts(uri.host().unwrap_or_default()));
+        Box::pin(async move {
+            let Some(proxy) = proxy else {
+                return Ok(http.call(uri).await?);
+            };
+            if proxy.uri.scheme_str() != Some("http") {
+                return Err(format!("unsupported proxy {}, only the plain http proxies are", proxy.uri).into());
+            }
+            let mut stream = http.call(proxy.uri.clone()).await?.into_inner();
+            tunnel(&mut stream, &uri).await?;
+            Ok(TokioIo::new(stream))
+        })
+    }
+}
+
+/// FNV-1a hash of the URL, naming the cached files the same way across the builds.
+#[derive(Default)]
+struct CacheKey(u64);
+
+impl Hasher for CacheKey {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.0 == 0 {
+            self.0 = 0xcbf29ce484222325;
+        }
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Resolves the `Location` of the redirect against the URL of the request, like the browsers do.
+fn resolve(url: &str, location: &str) -> Result<String, ClientError> {
+    let invalid = || ClientError::InvalidLocation {
+        uri: url.to_string(),
+        location: location.to_string(),
+    };
+    if location.contains("://") {
+        return location.parse::<Uri>().map(|_| location.to_string()).map_err(|_| invalid());
+    }
+    let uri = url.parse::<Uri>()?;
+    let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+        return Err(invalid());
+    };
+    let resolved = if let Some(network_path) = location.strip_prefix("//") {
+        format!("{scheme}://{network_path}")
+    } else if location.starts_with('/') {
+        format!("{scheme}://{authority}{location}")
+    } else if location.starts_with('?') {
+        format!("{scheme}://{authority}{}{location}", uri.path())
+    } else {
+        let directory = &uri.path()[..uri.path().rfind('/').map_or(0, |slash| slash + 1)];
+        let directory = if directory.is_empty() { "/" } else { directory };
+        format!("{scheme}://{authority}{directory}{location}")
+    };
+    resolved.parse::<Uri>().map(|_| resolved).map_err(|_| invalid())
+}
+
+// Implements simple http1&2 GET http client wrapper.
+/// Wraps [hyper] [HttpsConnector] with [aws_lc_rs] [rustls] provider.
+pub struct Client {
+    client: HyperClient<HttpsConnector<ProxyConnector>, Empty<Bytes>>,
+    config: ClientConfig,
+}
+
+impl Client {
+    /// Creates a new [hyper] http client wrapper with the default [ClientConfig].
+    pub(crate) fn new() -> Client {
+        Self::with_config(ClientConfig::default())
+    }
+
+    /// Creates a new [hyper] http client wrapper, its connections are reused by the requests.
+    pub(crate) fn with_config(config: ClientConfig) -> Client {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let mut root_cert_store = rustls::RootCertStore::empty();
+        root_cert_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        let tls_config = rustls::ClientConfig::builder()
+            .with_root_certificates(root_cert_store)
+            .with_no_client_auth();
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(Some(config.connect_timeout));
+        let connector = ProxyConnector {
+            http,
+            proxy: config.proxy.clone().map(Arc::new),
+        };
+        let builder = hyper_rustls::HttpsConnectorBuilder::new().with_tls_config(tls_config);
+        let builder = match config.https_only {
+            true => builder.https_only(),
+            false => builder.https_or_http(),
+        };
+        let https_connector = builder.enable_all_versions().wrap_connector(connector);
+
+        Client {
+            client: HyperClient::builder(TokioExecutor::new()).build(https_connector),
+            config,
+        }
+    }
+
+    fn cache_path(&self, url: &str) -> Option<PathBuf> {
+        let mut key = CacheKey::default();
+        key.write(url.as_bytes());
+        let dir = self.config.cache_dir.as_ref()?;
+        Some(dir.join(format!("{:016x}.cache", key.finish())))
+    }
+
+    /// Cached `ETag` and body of the URL, the `ETag` line is followed by the body.
+    async fn cached(&self, url: &str) -> Option<(HeaderValue, Bytes)> {
+        let mut cached = Bytes::from(tokio::fs::read(self.cache_path(url)?).await.ok()?);
+        let line = cached.iter().position(|byte| *byte == b'\n')?;
+        let etag = cached.split_to(line);
+        cached.advance(1);
+        Some((HeaderValue::from_maybe_shared(etag).ok()?, cached))
+    }
+
+    /// Writes the `ETag` and the body into a temporary file renamed over the cached one,
+    /// so the concurrent builds never read them torn apart.
+    async fn cache(&self, url: &str, etag: &HeaderValue, body: &Bytes) -> Result<(), ClientError> {
+        let Some(path) = self.cache_path(url) else {
+            return Ok(());
+        };
+        let Some(dir) = path.parent() else {
+            return Ok(());
+        };
+        tokio::fs::create_dir_all(dir).await?;
+        let temporary = path.with_extension(format!("cache.{}.tmp", std::process::id()));
+        let mut cached = Vec::with_capacity(etag.len() + 1 + body.len());
+        cached.extend_from_slice(etag.as_bytes());
+        cached.push(b'\n');
+        cached.extend_from_slice(body);
+        tokio::fs::write(&temporary, cached).await?;
+        if let Err(error) = tokio::fs::rename(&temporary, &path).await {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(error.into());
+        }
+        Ok(())
+    }
+
+    async fn get_without_redirects(&self, url: &str) -> Result<Bytes, ClientError> {
+        let uri = url.parse::<Uri>()?;
+        let cached = self.cached(url).await;
+
+        let mut req = hyper::Request::builder()
+            .uri(uri)
+            .method(Method::GET);
+        // NOTE: Setting HOST causes PROTOCOL_ERROR on http2, [answered here](https://github.com/hyperium/hyper/discussions/3676#discussioncomment-9570313)
+        if let Some((etag, _)) = &cached {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        let req: Request<Empty<Bytes>> = req.body(Empty::new())?;
+
+        let response = async {
+            let res = self.client.request(req).await?;
+            match res.status() {
+                StatusCode::OK => {
+                    let etag = res.headers().get(ETAG).cloned();
+                    let body = res.into_body().collect().await?.to_bytes();
+                    if let Some(etag) = etag {
+                        self.cache(url, &etag, &body).await?;
+                    }
+                    Ok(body)
+                }
+                StatusCode::NOT_MODIFIED if cached.is_some() => {
+                    Ok(cached.map(|(_, body)| body).unwrap_or_default())
+                }
+                status if status.is_redirection() => {
+                    let loc = res.headers().get(LOCATION).ok_or_else(|| {
+                        ClientError::LocationMissing {
+                            uri: url.to_string(),
+                        }
+                    })?;
+                    Err(ClientError::Redirected {
+                        to: resolve(url, loc.to_str()?)?,
+                    })
+                }
+                _ => Err(ClientError::NoContent {
+                    status_code: res.status(),
+                }),
+            }
+        };
+        tokio::time::timeout(self.config.read_timeout, response)
+            .await
+            .unwrap_or_else(|_| Err(ClientError::Timeout { uri: url.to_string() }))
+    }
+
+    /// Retries the request with the exponential backoff.
+    async fn get_with_retries(&self, url: &str) -> Result<Bytes, ClientError> {
+        let mut backoff = self.config.backoff;
+        let mut res = self.get_without_redirects(url).await;
+        for _ in 0..self.config.retries {
+            match &res {
+                Err(error) if error.is_retryable() => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                _ => return res,
+            }
+            res = self.get_without_redirects(url).await;
+        }
+        res
+    }
+
+    const MAX_REDIRECTS: usize = 10;
+
+    /// Performs HTTP GET request, following redirects
+    pub async fn get(&self, url: &str) -> Result<Bytes, ClientError> {
+        let mut resolved_url = url.to_string();
+        let mut res = self.get_with_retries(&resolved_url).await;
+
+        for _ in 0..=Self::MAX_REDIRECTS {
+            match res.as_ref().err() {
+                Some(ClientError::Redirected { to }) => {
+                    resolved_url = to.clone();
+                }
+
+                _ => return res,
+            }
+
+            res = self.get_with_retries(&resolved_url).await;
+        }
+
+        Err(ClientError::TooManyRedirects)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use hyper::body::Incoming;
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper::Response;
+    use tokio::net::{TcpListener, TcpStream};
+
+    type Handler = dyn Fn(Request<Incoming>, usize) -> Response<Full<Bytes>> + Send + Sync;
+
+    /// Local http server counting the accepted connections and the served requests.
+    pub(crate) struct Server {
+        addr: SocketAddr,
+        connections: Arc<AtomicUsize>,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl Server {
+        pub(crate) async fn start(handler: impl Fn(Request<Incoming>, usize) -> Response<Full<Bytes>> + Send + Sync + 'static) -> Server {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let server = Server {
+                addr: listener.local_addr().unwrap(),
+                connections: Arc::default(),
+                requests: Arc::default(),
+            };
+            let handler: Arc<Handler> = Arc::new(handler);
+            let (connections, requests) = (server.connections.clone(), server.requests.clone());
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    let (handler, requests) = (handler.clone(), requests.clone());
+                    let service = service_fn(move |req| {
+                        let res = handler(req, requests.fetch_add(1, Ordering::SeqCst));
+                        async move { Ok::<_, hyper::Error>(res) }
+                    });
+                    tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+                }
+            });
+            server
+        }
+
+        pub(crate) fn url(&self, path: &str) -> String {
+            format!("http://{}{path}", self.addr)
+        }
+    }
+
+    pub(crate) fn response(status: StatusCode, body: &'static str) -> Response<Full<Bytes>> {
+        Response::builder().status(status).body(Full::new(Bytes::from(body))).unwrap()
+    }
+
+    pub(crate) fn client() -> Client {
+        Client::with_config(ClientConfig {
+            read_timeout: Duration::from_millis(500),
+            backoff: Duration::from_millis(1),
+            proxy: None,
+            https_only: false,
+            ..ClientConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn should_retry_server_errors() {
+        let server = Server::start(|_, request| match request {
+            0 | 1 => response(StatusCode::SERVICE_UNAVAILABLE, ""),
+            _ => response(StatusCode::OK, "content"),
+        })
+        .await;
+
+        assert_eq!(client().get(&server.url("/")).await.unwrap(), "content");
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn should_give_up_retrying() {
+        let server = Server::start(|_, _| response(StatusCode::BAD_GATEWAY, "")).await;
+
+        let res = client().get(&server.url("/")).await;
+        assert!(matches!(res, Err(ClientError::NoContent { status_code: StatusCode::BAD_GATEWAY })));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_client_errors() {
+        let server = Server::start(|_, _| response(StatusCode::NOT_FOUND, "")).await;
+
+        let res = client().get(&server.url("/")).await;
+        assert!(matches!(res, Err(ClientError::NoContent { status_code: StatusCode::NOT_FOUND })));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_follow_redirects() {
+        let server = Server::start(|req, _| match req.uri().path() {
+            "/target" => response(StatusCode::OK, "redirected"),
+            _ => Response::builder()
+                .status(StatusCode::FOUND)
+                .header(LOCATION, "/target".to_string())
+                .body(Full::default())
+                .unwrap(),
+        })
+        .await;
+        let target = server.url("/");
+        let server = Server::start(move |req, _| match req.uri().path() {
+            "/" => Response::builder()
+                .status(StatusCode::MOVED_PERMANENTLY)
+                .header(LOCATION, target.clone())
+                .body(Full::default())
+                .unwrap(),
+            _ => response(StatusCode::NOT_FOUND, ""),
+        })
+        .await;
+
+        assert_eq!(client().get(&server.url("/")).await.unwrap(), "redirected");
+    }
+
+    #[test]
+    fn should_resolve_locations() {
+        let cases = [
+            ("https://llvm.org/a/b.td", "https://mirror.org/c.td", "https://mirror.org/c.td"),
+            ("https://llvm.org/a/b.td", "/c.td", "https://llvm.org/c.td"),
+            ("https://llvm.org/a/b.td", "c.td", "https://llvm.org/a/c.td"),
+            ("https://llvm.org/a/b.td", "//mirror.org/c.td", "https://mirror.org/c.td"),
+            ("https://llvm.org/a/b.td", "?raw=1", "https://llvm.org/a/b.td?raw=1"),
+            ("http://127.0.0.1:8080", "target", "http://127.0.0.1:8080/target"),
+        ];
+        for (url, location, expected) in cases {
+            assert_eq!(resolve(url, location).unwrap(), expected, "{location}");
+        }
+        assert!(matches!(
+            resolve("https://llvm.org/", "https://in valid/"),
+            Err(ClientError::InvalidLocation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reuse_connections() {
+        let server = Server::start(|_, _| response(StatusCode::OK, "content")).await;
+
+        let client = client();
+        for _ in 0..3 {
+            assert_eq!(client.get(&server.url("/")).await.unwrap(), "content");
+        }
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+        assert_eq!(server.connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::default());
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            let mut streams = vec![];
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                streams.push(stream);
+            }
+        });
+
+        let client = Client::with_config(ClientConfig {
+            read_timeout: Duration::from_millis(50),
+            retries: 1,
+            ..client().config
+        });
+        let res = client.get(&format!("http://{addr}/")).await;
+        assert!(matches!(res, Err(ClientError::Timeout { .. })));
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_revalidate_cache() {
+        let server = Server::start(|req, _| match req.headers().get(IF_NONE_MATCH) {
+            Some(etag) if etag == "\"v1\"" => response(StatusCode::NOT_MODIFIED, ""),
+            _ => Response::builder()
+                .header(ETAG, "\"v1\"")
+                .body(Full::new(Bytes::from("cached")))
+                .unwrap(),
+        })
+        .await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let client = Client::with_config(ClientConfig {
+            cache_dir: Some(cache_dir.path().to_path_buf()),
+            ..client().config
+        });
+
+        assert_eq!(client.get(&server.url("/")).await.unwrap(), "cached");
+        assert_eq!(client.get(&server.url("/")).await.unwrap(), "cached");
+        assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+        let cached: Vec<_> = std::fs::read_dir(cache_dir.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(std::fs::read(&cached[0]).unwrap(), b"\"v1\"\ncached");
+    }
+
+    /// Proxy accepting the `CONNECT` tunnels and counting them.
+    async fn start_proxy() -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let tunnels = Arc::new(AtomicUsize::default());
+        let counter = tunnels.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut request = vec![];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        request.push(stream.read_u8().await.unwrap());
+                    }
+                    let request = String::from_utf8(request).unwrap();
+                    let target = request.strip_prefix("CONNECT ").unwrap().split(' ').next().unwrap();
+                    let mut upstream = TcpStream::connect(target).await.unwrap();
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n").await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+                });
+            }
+        });
+        (addr, tunnels)
+    }
+
+    #[tokio::test]
+    async fn should_tunnel_through_proxy() {
+        let server = Server::start(|_, _| response(StatusCode::OK, "proxied")).await;
+        let (proxy, tunnels) = start_proxy().await;
+        let client = Client::with_config(ClientConfig {
+            proxy: Some(Proxy {
+                uri: format!("http://{proxy}").parse().unwrap(),
+                no_proxy: vec![],
+            }),
+            ..client().config
+        });
+
+        assert_eq!(client.get(&server.url("/")).await.unwrap(), "proxied");
+        assert_eq!(tunnels.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_bypass_proxy() {
+        let server = Server::start(|_, _| response(StatusCode::OK, "direct")).await;
+        let (proxy, tunnels) = start_proxy().await;
+        let client = Client::with_config(ClientConfig {
+            proxy: Some(Proxy {
+                uri: format!("http://{proxy}").parse().unwrap(),
+                no_proxy: vec!["127.0.0.1".to_string()],
+            }),
+            ..client().config
+        });
+
+        assert_eq!(client.get(&server.url("/")).await.unwrap(), "direct");
+        assert_eq!(tunnels.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn should_reject_https_proxies() {
+        let server = Server::start(|_, _| response(StatusCode::OK, "direct")).await;
+        let (proxy, tunnels) = start_proxy().await;
+        let client = Client::with_config(ClientConfig {
+            proxy: Some(Proxy {
+                uri: format!("https://{proxy}").parse().unwrap(),
+                no_proxy: vec![],
+            }),
+            retries: 0,
+            ..client().config
+        });
+
+        let res = client.get(&server.url("/")).await;
+        assert!(format!("{:?}", res.unwrap_err()).contains("unsupported proxy https://"));
+        assert_eq!(tunnels.load(Ordering::SeqCst), 0);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn should_match_no_proxy_hosts() {
+        let proxy = Proxy {
+            uri: Uri::from_static("http://proxy:3128"),
+            no_proxy: vec!["localhost".to_string(), ".internal".to_string(), "llvm.org".to_string()],
+        };
+
+        assert!(proxy.intercepts("raw.githubusercontent.com"));
+        assert!(proxy.intercepts("notllvm.org"));
+        assert!(!proxy.intercepts("localhost"));
+        assert!(!proxy.intercepts("mirror.internal"));
+        assert!(!proxy.intercepts("LLVM.org"));
+        assert!(!proxy.intercepts("github.llvm.org"));
+
+        let proxy = Proxy { no_proxy: vec!["*".to_string()], ..proxy };
+        assert!(!proxy.intercepts("raw.githubusercontent.com"));
+    }
+}
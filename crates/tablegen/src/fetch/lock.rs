@@ -0,0 +1,156 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `tablegen.lock` file, pinning the include closure of the entry points by the content hashes.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fetch::TableGenError;
+
+/// Resolved `.td` file with its `include` directives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Hex SHA-256 of the file contents.
+    pub sha256: String,
+    pub includes: Vec<String>,
+}
+
+impl LockedFile {
+    pub fn new(contents: &str, includes: Vec<String>) -> Self {
+        let digest = aws_lc_rs::digest::digest(&aws_lc_rs::digest::SHA256, contents.as_bytes());
+        LockedFile {
+            sha256: digest.as_ref().iter().fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            }),
+            includes,
+        }
+    }
+}
+
+/// Include closure of the entry points, keyed by the include paths.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TdLock {
+    /// [TdSource](super::source::TdSource) the files were resolved from.
+    pub source: String,
+    pub entry_points: Vec<String>,
+    pub files: BTreeMap<String, LockedFile>,
+}
+
+impl TdLock {
+    pub const FILE_NAME: &'static str = "tablegen.lock";
+
+    pub fn read(path: &Path) -> Result<Option<TdLock>, TableGenError> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), TableGenError> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?)
+    }
+
+    /// Checks the files resolved again are the locked ones, ignoring the source they came from.
+    /// The entry points added or removed since may change the set of the files, but not their contents.
+    pub fn verify(&self, resolved: &TdLock) -> Result<(), TableGenError> {
+        let same_entry_points = self.entry_points == resolved.entry_points;
+        let mut paths = self.files.keys().chain(resolved.files.keys());
+        match paths.find(|path| match (self.files.get(*path), resolved.files.get(*path)) {
+            (Some(locked), Some(file)) => locked != file,
+            _ => same_entry_points,
+        }) {
+            Some(path) => Err(TableGenError::LockMismatch { path: path.clone() }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(files: &[(&str, &str, &[&str])]) -> TdLock {
+        TdLock {
+            source: "vendored corpus".to_string(),
+            entry_points: vec![files[0].0.to_string()],
+            files: files
+                .iter()
+                .map(|(path, contents, includes)| {
+                    let includes = includes.iter().map(ToString::to_string).collect();
+                    (path.to_string(), LockedFile::new(contents, includes))
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn should_hash_files() {
+        assert_eq!(
+            LockedFile::new("", vec![]).sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn should_write_and_read_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TdLock::FILE_NAME);
+        let locked = lock(&[("a.td", "include \"b.td\"", &["b.td"]), ("b.td", "class B;", &[])]);
+
+        assert_eq!(TdLock::read(&path).unwrap(), None);
+        locked.write(&path).unwrap();
+        assert_eq!(TdLock::read(&path).unwrap(), Some(locked));
+    }
+
+    #[test]
+    fn should_verify_lock() {
+        let locked = lock(&[("a.td", "include \"b.td\"", &["b.td"]), ("b.td", "class B;", &[])]);
+        let moved = TdLock {
+            source: "mirror".to_string(),
+            ..locked.clone()
+        };
+        assert!(locked.verify(&moved).is_ok());
+
+        let changed = lock(&[("a.td", "include \"b.td\"", &["b.td"]), ("b.td", "class B {}", &[])]);
+        assert!(matches!(locked.verify(&changed), Err(TableGenError::LockMismatch { path }) if path == "b.td"));
+
+        let added = lock(&[
+            ("a.td", "include \"b.td\"", &["b.td", "c.td"]),
+            ("b.td", "class B;", &[]),
+            ("c.td", "class C;", &[]),
+        ]);
+        assert!(matches!(locked.verify(&added), Err(TableGenError::LockMismatch { path }) if path == "a.td"));
+
+        let extended = TdLock {
+            entry_points: vec!["a.td".to_string(), "c.td".to_string()],
+            ..lock(&[
+                ("a.td", "include \"b.td\"", &["b.td"]),
+                ("b.td", "class B;", &[]),
+                ("c.td", "class C;", &[]),
+            ])
+        };
+        assert!(locked.verify(&extended).is_ok());
+        assert!(extended.verify(&locked).is_ok());
+    }
+}
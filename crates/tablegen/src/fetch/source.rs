@@ -0,0 +1,326 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Providers of the `.td` files, addressed the same way the `include` directives address them,
+//! relative to the `mlir/include` directory, like `mlir/IR/OpBase.td`.
+//!
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::OnceCell;
+
+use crate::fetch::client::Client;
+use crate::fetch::{TableGenError, LLVM_REVISION};
+
+/// `.td` files provider, preserving the `mlir/include/mlir/...` directory structure,
+/// so the includes resolve exactly as they do for `mlir-tblgen`.
+/// The reads are awaited in place, the futures aren't required to be `Send`.
+#[allow(async_fn_in_trait)]
+pub trait TdSource {
+    /// Describes the source in the errors, like `llvm-project checkout /src/llvm-project`.
+    fn name(&self) -> String;
+
+    /// Reads the `.td` file relative to the `mlir/include` directory.
+    async fn read(&self, path: &str) -> Result<String, TableGenError>;
+}
+
+/// Rejects the absolute paths and the `..` components escaping the include directory.
+fn checked(path: &str) -> Result<&Path, TableGenError> {
+    let relative = Path::new(path);
+    match relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        true => Ok(relative),
+        false => Err(TableGenError::InvalidPath { path: path.to_string() }),
+    }
+}
+
+async fn read_file(source: &impl TdSource, path: PathBuf, td: &str) -> Result<String, TableGenError> {
+    match tokio::fs::read_to_string(&path).await {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Err(TableGenError::NotFound {
+            path: td.to_string(),
+            provider: source.name(),
+        }),
+        res => Ok(res?),
+    }
+}
+
+/// Local llvm-project checkout directory.
+#[derive(Debug, Clone)]
+pub struct LlvmCheckout {
+    pub root: PathBuf,
+}
+
+impl TdSource for LlvmCheckout {
+    fn name(&self) -> String {
+        format!("llvm-project checkout {}", self.root.display())
+    }
+
+    async fn read(&self, path: &str) -> Result<String, TableGenError> {
+        let file = self.root.join("mlir/include").join(checked(path)?);
+        read_file(self, file, path).await
+    }
+}
+
+/// Downloaded llvm release tarball, like `llvm-project-19.1.7.src.tar.xz`, read without extracting.
+///
+/// The `.tar`, `.tar.gz` and `.tar.xz` archives are scanned once, keeping the `.td` files of the
+/// `mlir/include` directory in memory.
+#[derive(Debug)]
+pub struct LlvmTarball {
+    pub archive: PathBuf,
+    files: OnceCell<Arc<HashMap<String, String>>>,
+}
+
+impl LlvmTarball {
+    pub fn new(archive: impl Into<PathBuf>) -> Self {
+        LlvmTarball {
+            archive: archive.into(),
+            files: OnceCell::new(),
+        }
+    }
+
+    /// Collects the `<top>/mlir/include/**.td` entries by their include path.
+    fn scan(archive: &Path) -> Result<HashMap<String, String>, TableGenError> {
+        let file = std::fs::File::open(archive)?;
+        let name = archive.to_string_lossy();
+        let reader: Box<dyn Read> = if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Box::new(xz2::read::XzDecoder::new(file))
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Box::new(flate2::read::GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+
+        let mut files = HashMap::new();
+        for entry in tar::Archive::new(reader).entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().replace('\\', "/");
+            let Some((_, td)) = path.split_once("/mlir/include/") else {
+                continue;
+            };
+            if td.ends_with(".td") && entry.header().entry_type().is_file() {
+                let td = td.to_string();
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                files.insert(td, content);
+            }
+        }
+        Ok(files)
+    }
+}
+
+impl TdSource for LlvmTarball {
+    fn name(&self) -> String {
+        format!("llvm release tarball {}", self.archive.display())
+    }
+
+    async fn read(&self, path: &str) -> Result<String, TableGenError> {
+        let files = self
+            .files
+            .get_or_try_init(|| async {
+                let archive = self.archive.clone();
+                let files = tokio::task::spawn_blocking(move || Self::scan(&archive))
+                    .await
+                    .map_err(std::io::Error::other)??;
+                Ok::<_, TableGenError>(Arc::new(files))
+            })
+            .await?;
+        let path = checked(path)?.to_string_lossy();
+        files
+            .get(path.as_ref())
+            .cloned()
+            .ok_or_else(|| TableGenError::NotFound {
+                path: path.to_string(),
+                provider: self.name(),
+            })
+    }
+}
+
+/// HTTP mirror of the llvm-project tree, the files are fetched from `<prefix>mlir/include/<path>`.
+pub struct Mirror {
+    pub prefix: String,
+    client: Client,
+}
+
+impl Mirror {
+    /// The llvm-project repository on GitHub, followed by the revision.
+    pub const LLVM_PROJECT: &'static str = "https://raw.githubusercontent.com/llvm/llvm-project/";
+
+    pub fn new(prefix: &str) -> Self {
+        Self::with_client(prefix, Client::new())
+    }
+
+    pub fn with_client(prefix: &str, client: Client) -> Self {
+        Mirror {
+            prefix: format!("{}/", prefix.trim_end_matches('/')),
+            client,
+        }
+    }
+}
+
+/// The pinned [LLVM_REVISION] on GitHub.
+impl Default for Mirror {
+    fn default() -> Self {
+        Mirror::new(&format!("{}{LLVM_REVISION}", Mirror::LLVM_PROJECT))
+    }
+}
+
+impl TdSource for Mirror {
+    fn name(&self) -> String {
+        format!("mirror {}", self.prefix)
+    }
+
+    async fn read(&self, path: &str) -> Result<String, TableGenError> {
+        let url = format!("{}mlir/include/{}", self.prefix, checked(path)?.to_string_lossy());
+        let bytes = self.client.get(&url).await?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error).into())
+    }
+}
+
+/// Corpus vendored into the crate, its root is the include directory itself.
+#[derive(Debug, Clone)]
+pub struct Vendored {
+    pub root: PathBuf,
+}
+
+impl TdSource for Vendored {
+    fn name(&self) -> String {
+        format!("vendored corpus {}", self.root.display())
+    }
+
+    async fn read(&self, path: &str) -> Result<String, TableGenError> {
+        let file = self.root.join(checked(path)?);
+        read_file(self, file, path).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use hyper::{Response, StatusCode};
+
+    use crate::fetch::client::tests::{client, response, Server};
+
+    const OP_BASE: &str = "mlir/IR/OpBase.td";
+
+    /// Include directory the dialect crates are generated out of.
+    pub(crate) fn corpus() -> Vendored {
+        Vendored {
+            root: Path::new(env!("CARGO_MANIFEST_DIR")).join("../mlir-codegen/include"),
+        }
+    }
+
+    /// llvm-project tree with the vendored `OpBase.td` and a `.td` file outside of the include directory.
+    fn llvm_project(root: &Path) {
+        let include = root.join("mlir/include/mlir/IR");
+        std::fs::create_dir_all(&include).unwrap();
+        std::fs::write(include.join("OpBase.td"), "class Op;").unwrap();
+        std::fs::create_dir_all(root.join("mlir/test")).unwrap();
+        std::fs::write(root.join("mlir/test/Test.td"), "class Test;").unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_read_llvm_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        llvm_project(dir.path());
+        let source = LlvmCheckout {
+            root: dir.path().to_path_buf(),
+        };
+
+        assert_eq!(source.read(OP_BASE).await.unwrap(), "class Op;");
+        assert!(matches!(
+            source.read("mlir/IR/Missing.td").await,
+            Err(TableGenError::NotFound { .. })
+        ));
+        assert!(matches!(
+            source.read("../test/Test.td").await,
+            Err(TableGenError::InvalidPath { .. })
+        ));
+        assert!(matches!(
+            source.read("/etc/passwd").await,
+            Err(TableGenError::InvalidPath { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_read_llvm_tarballs() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("llvm-project-19.1.7.src");
+        llvm_project(&tree);
+
+        for name in ["llvm-project.tar", "llvm-project.tar.gz", "llvm-project.tar.xz"] {
+            let path = dir.path().join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            let writer: Box<dyn std::io::Write> = match name {
+                "llvm-project.tar.gz" => Box::new(flate2::write::GzEncoder::new(file, flate2::Compression::fast())),
+                "llvm-project.tar.xz" => Box::new(xz2::write::XzEncoder::new(file, 1)),
+                _ => Box::new(file),
+            };
+            let mut builder = tar::Builder::new(writer);
+            builder.append_dir_all("llvm-project-19.1.7.src", &tree).unwrap();
+            builder.into_inner().unwrap().flush().unwrap();
+
+            let source = LlvmTarball::new(&path);
+            assert_eq!(source.read(OP_BASE).await.unwrap(), "class Op;", "{name}");
+            assert!(matches!(
+                source.read("mlir/test/Test.td").await,
+                Err(TableGenError::NotFound { .. })
+            ));
+            assert_eq!(source.files.get().unwrap().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_read_mirrors() {
+        let server = Server::start(|req, _| match req.uri().path() {
+            "/llvm/mlir/include/mlir/IR/OpBase.td" => response(StatusCode::OK, "class Op;"),
+            _ => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Default::default())
+                .unwrap(),
+        })
+        .await;
+        let source = Mirror::with_client(&server.url("/llvm"), client());
+
+        assert_eq!(source.read(OP_BASE).await.unwrap(), "class Op;");
+        assert!(matches!(
+            source.read("mlir/IR/Missing.td").await,
+            Err(TableGenError::HttpClientError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_read_vendored_corpus() {
+        let source = corpus();
+
+        assert!(source.read(OP_BASE).await.unwrap().contains("class Op<"));
+        assert!(source
+            .read("mlir/Dialect/Arith/IR/ArithOps.td")
+            .await
+            .unwrap()
+            .contains("Arith_Op"));
+    }
+}
@@ -33,25 +33,54 @@
 pub(crate) mod expressions;
 pub(crate) mod tokens;
 
-use std::borrow::Cow;
+use winnow::combinator::*;
 use winnow::PResult;
+use winnow::*;
 
 use expressions::preprocessor;
+use expressions::statements::*;
+use tokens::helpers::space_or_newline0;
 
-#[derive(Debug)]
-pub struct TableGen<'a> {
-    pub(crate) name: &'a str,
+/// Parsed Tablegen file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableGen {
+    pub statements: Vec<Statement>,
 }
 
-impl<'a> TableGen<'a> {
-    pub fn parse(input: &mut &'a str) -> PResult<Cow<'a, str>> {
-        let preproc = preprocessor::preprocess(input);
+impl TableGen {
+    pub fn parse(input: &mut &str) -> PResult<TableGen> {
+        let preprocessed = preprocessor::preprocess(input)?;
 
-        preproc
+        let mut preprocessed = preprocessed.as_ref();
+        let statements =
+            terminated(statements, (space_or_newline0, eof)).parse_next(&mut preprocessed)?;
+
+        Ok(TableGen { statements })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grammar::expressions::record_def::RecordDefinition;
+    use crate::grammar::expressions::values::Value;
+
+    #[test]
+    fn should_parse_tablegen() {
+        let input = "// Header\ninclude \"a.td\"\n#ifndef A\ndef A;\n#endif\n";
+        let parsed = TableGen::parse(&mut &*input).unwrap();
+
+        assert_eq!(
+            parsed.statements,
+            vec![
+                Statement::Include("a.td".to_string()),
+                Statement::Def(RecordDefinition {
+                    name: Some(Value::Identifier("A".to_string())),
+                    parents: vec![],
+                    body: vec![],
+                }),
+            ]
+        );
+        assert!(TableGen::parse(&mut "def A; }").is_err());
+    }
 }
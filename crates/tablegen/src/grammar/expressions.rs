@@ -35,3 +35,5 @@
 pub(crate) mod values;
 
 pub(crate) mod preprocessor;
+
+pub(crate) mod statements;
@@ -18,53 +18,90 @@
 //! Tablegen class definitions parsing.
 //!
 
-mod body;
-mod template_arg;
+pub(crate) mod body;
+pub(crate) mod template_arg;
 
 use winnow::PResult;
-use winnow::ascii::*;
 use winnow::combinator::*;
-use winnow::error::*;
 use winnow::*;
-use crate::grammar::expressions::class_def::template_arg::ClassTemplateArg;
-use crate::grammar::tokens::*;
+use crate::grammar::expressions::class_def::body::*;
+use crate::grammar::expressions::class_def::template_arg::*;
 use crate::grammar::tokens::helpers::*;
 use crate::grammar::tokens::identifier::identifier;
-use crate::grammar::tokens::type_name;
-use crate::grammar::expressions::values::value;
+use crate::grammar::expressions::values::{arguments, value, Argument, Value};
 
+/// `class Name<template args> : Parents { body }`
 #[derive(Debug, Clone, PartialEq)]
-struct ClassDefinition<'a> {
-    name: &'a str,
-    template_arguments: Vec<ClassTemplateArg<'a>>,
+pub struct ClassDefinition {
+    pub name: String,
+    pub template_arguments: Vec<ClassTemplateArg>,
+    pub parents: Vec<ParentClassDefinition>,
+    pub body: Vec<Body>,
 }
 
-struct ParentClassDefinition<'a> {
-    name: &'a str,
-    arguments: Vec<ClassTemplateArg<'a>>,
+/// `Parent<arguments>` superclass reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParentClassDefinition {
+    pub name: String,
+    pub arguments: Vec<Argument>,
 }
 
-// pub(crate) fn parse_class<'a>(input: &mut &'a str) -> PResult<&'a str> {
-//     preceded(("class", space_or_newline1), (identifier, delimited(("<", space_or_newline1), literal, (space_or_newline1, ">")))).parse_next(input)
-// }
+/// Parses an optional `= value`, defaulting to [Value::Uninitialized].
+pub(crate) fn opt_value(input: &mut &str) -> PResult<Value> {
+    opt(preceded(spaced_literal("="), spaced_parser_of(value)))
+        .map(|value| value.unwrap_or(Value::Uninitialized))
+        .parse_next(input)
+}
 
-fn opt_value<'a>(input: &mut &'a str) -> PResult<&'a str> {
-    opt(preceded(opt(spaced_literal("=")), spaced_parser(identifier))).map(|opt| opt.unwrap_or_else(|| "")).parse_next(input)
+fn parent_class(input: &mut &str) -> PResult<ParentClassDefinition> {
+    (
+        spaced_parser(identifier),
+        opt(terminated(arguments, space_or_newline0)),
+    )
+        .map(|(name, arguments)| ParentClassDefinition {
+            name: name.to_string(),
+            arguments: arguments.unwrap_or_default(),
+        })
+        .parse_next(input)
 }
 
+/// Parses an optional `: Parent<args>, Parent` superclass list.
+pub(crate) fn parent_classes(input: &mut &str) -> PResult<Vec<ParentClassDefinition>> {
+    opt(preceded(
+        spaced_literal(":"),
+        separated(1.., parent_class, spaced_literal(",")),
+    ))
+    .map(Option::unwrap_or_default)
+    .parse_next(input)
+}
 
-// fn class_def<'a>(input: &mut &'a str) -> PResult<ClassDefinition<'a>> {
-//
-//     let body = delimited(spaced_literal("{"), (), spaced_literal("}"));
-//
-//     preceded(spaced_literal("class"), (spaced_parser(identifier), spaced_parser(parse_template_args), body)).parse_next(input)
-// }
+/// Parses `{ body }` or an empty `;` record body.
+pub(crate) fn record_body(input: &mut &str) -> PResult<Vec<Body>> {
+    alt((
+        delimited(spaced_literal("{"), parse_fields, spaced_literal("}")),
+        spaced_literal(";").value(vec![]),
+    ))
+    .parse_next(input)
+}
 
-// fn class_property<'a>(input: &mut &'a str) -> PResult<ClassProperty<'a>> {
-//     "x".map(|_| ClassProperty{name: "x", ty: "x"}).parse_next(input)
-// }
+pub(crate) fn class_def(input: &mut &str) -> PResult<ClassDefinition> {
+    preceded(
+        spaced_keyword("class"),
+        (
+            spaced_parser(identifier),
+            opt(parse_template_args),
+            parent_classes,
+            record_body,
+        ),
+    )
+    .map(|(name, template_arguments, parents, body)| ClassDefinition {
+        name: name.to_string(),
+        template_arguments: template_arguments.unwrap_or_default(),
+        parents,
+        body,
+    })
+    .parse_next(input)
+}
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-}
+mod tests {}
@@ -15,30 +15,34 @@
 */
 
 //!
-//! Tablegen class definitions parsing.
+//! Tablegen class template arguments parsing.
 //!
 
 use winnow::PResult;
-use winnow::ascii::*;
 use winnow::combinator::*;
-use winnow::error::*;
 use winnow::*;
 use crate::grammar::expressions::class_def::opt_value;
-use crate::grammar::tokens::*;
 use crate::grammar::tokens::helpers::*;
 use crate::grammar::tokens::identifier::identifier;
-use crate::grammar::tokens::type_name;
-use crate::grammar::expressions::values::value;
+use crate::grammar::tokens::type_name::*;
+use crate::grammar::expressions::values::Value;
 
+/// Class or multiclass template argument, [Value::Uninitialized] stands for the missing default value.
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct ClassTemplateArg<'a> {
-    typ: &'a str,
-    identifier: &'a str,
-    value: &'a str,
+pub struct ClassTemplateArg {
+    pub typ: Type,
+    pub identifier: String,
+    pub value: Value,
 }
 
-pub(crate) fn parse_template_args<'a>(input: &mut &'a str) -> PResult<Vec<ClassTemplateArg<'a>>> {
-    let pair = (spaced_parser(identifier), spaced_parser(identifier), opt_value).map(|(typ, id, value)| ClassTemplateArg { typ, identifier: id, value });
+pub(crate) fn parse_template_args(input: &mut &str) -> PResult<Vec<ClassTemplateArg>> {
+    let pair = (spaced_parser_of(type_ref), spaced_parser(identifier), opt_value).map(
+        |(typ, id, value)| ClassTemplateArg {
+            typ,
+            identifier: id.to_string(),
+            value,
+        },
+    );
 
     let pairs = separated(1.., pair, spaced_literal(","));
 
@@ -50,17 +54,27 @@ pub(crate) fn parse_template_args<'a>(input: &mut &'a str) -> PResult<Vec<ClassT
 mod tests {
     use super::*;
     use crate::grammar::tokens::helpers::tests::*;
+    use crate::grammar::expressions::class_def::*;
+    use crate::grammar::expressions::class_def::body::Body;
+
+    fn class(name: &str) -> Type {
+        Type::Class(name.to_string())
+    }
 
     #[test]
     fn should_parse_template_args() {
         test_parser(
             vec![
                 ("<A B, C \nD, \n E F =  \n val\n>", Some(vec![
-                    ClassTemplateArg { typ: "A", identifier: "B", value: "?" },
-                    ClassTemplateArg { typ: "C", identifier: "D", value: "?" },
-                    ClassTemplateArg { typ: "E", identifier: "F", value: "val" }]
+                    ClassTemplateArg { typ: class("A"), identifier: "B".to_string(), value: Value::Uninitialized },
+                    ClassTemplateArg { typ: class("C"), identifier: "D".to_string(), value: Value::Uninitialized },
+                    ClassTemplateArg { typ: class("E"), identifier: "F".to_string(), value: Value::Identifier("val".to_string()) }]
                 ), ""), // Valid class, fully consumed
-                // ("", None, ""),                     // Empty input should fail
+                ("<string mnemonic, list<Trait> traits = []>", Some(vec![
+                    ClassTemplateArg { typ: Type::String, identifier: "mnemonic".to_string(), value: Value::Uninitialized },
+                    ClassTemplateArg { typ: Type::List(Box::new(class("Trait"))), identifier: "traits".to_string(), value: Value::List(vec![], None) }]
+                ), ""), // Primitive and list types with defaults
+                ("<>", None, ">"),                  // Empty input should fail
             ],
             parse_template_args,
         );
@@ -68,12 +82,44 @@ fn should_parse_template_args() {
 
     #[test]
     fn should_parse_class_def() {
-        // test_parser(
-        //     vec![
-        //         ("class Name {", Some("Name"), ""), // Valid class, fully consumed
-        //         ("", None, ""),                     // Empty input should fail
-        //     ],
-        //     class_def,
-        // );
+        let empty = |name: &str| ClassDefinition {
+            name: name.to_string(),
+            template_arguments: vec![],
+            parents: vec![],
+            body: vec![],
+        };
+
+        test_parser(
+            vec![
+                ("class Name {}", Some(empty("Name")), ""), // Valid class, fully consumed
+                ("class Name;", Some(empty("Name")), ""),   // Valid class without a body, fully consumed
+                (
+                    "class Arith_Op<string mnemonic> :\n    Op<Arith_Dialect, mnemonic, [Pure]>, Base {\n  let x = 1;\n}\n",
+                    Some(ClassDefinition {
+                        name: "Arith_Op".to_string(),
+                        template_arguments: vec![ClassTemplateArg {
+                            typ: Type::String,
+                            identifier: "mnemonic".to_string(),
+                            value: Value::Uninitialized,
+                        }],
+                        parents: vec![
+                            ParentClassDefinition {
+                                name: "Op".to_string(),
+                                arguments: vec![
+                                    Argument::Positional(Value::Identifier("Arith_Dialect".to_string())),
+                                    Argument::Positional(Value::Identifier("mnemonic".to_string())),
+                                    Argument::Positional(Value::List(vec![Value::Identifier("Pure".to_string())], None)),
+                                ],
+                            },
+                            ParentClassDefinition { name: "Base".to_string(), arguments: vec![] },
+                        ],
+                        body: vec![Body::LetField { name: "x".to_string(), ranges: vec![], value: Value::Int(1) }],
+                    }),
+                    "",
+                ), // Class with template args, parents and body
+                ("", None, ""),                     // Empty input should fail
+            ],
+            class_def,
+        );
     }
 }
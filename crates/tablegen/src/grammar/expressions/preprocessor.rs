@@ -48,29 +48,52 @@ pub fn new() -> Self {
     }
 }
 
+/// Preprocessor directives, any other `#` is treated as a paste operator.
+const DIRECTIVES: [&str; 5] = ["#define", "#ifdef", "#ifndef", "#else", "#endif"];
+
+/// Returns the length of the directive at the input start, if any.
+fn directive_len(input: &str) -> Option<usize> {
+    DIRECTIVES.iter().find_map(|directive| {
+        input
+            .strip_prefix(directive)
+            .filter(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+            .map(|_| directive.len())
+    })
+}
+
+fn directive<'a>(name: &'static str) -> impl Fn(&mut &'a str) -> PResult<&'a str> {
+    move |input: &mut &'a str| {
+        match directive_len(input) {
+            Some(len) if input[..len] == *name => {}
+            _ => return Err(ErrMode::Backtrack(ContextError::new())),
+        }
+        literal(name).parse_next(input)
+    }
+}
+
 macro_rules! define_parse_cond {
     ($name:ident, $condType:ident, $condition:expr) => {
-        pub(crate) fn $name<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
-            let (name, mut content) = (delimited(
-                literal($condition),
+        fn $name<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
+            let (name, content, else_content) = delimited(
+                directive($condition),
                 (
                     delimited(
                         space_or_newline1,
                         take_till_space_or_newline,
                         space_or_newline0,
                     ),
-                    any_string_terminated_eager(["#endif"]),
+                    parse_chunks,
+                    opt(preceded(directive("#else"), parse_chunks)),
                 ),
-                "#endif",
-            ))
+                directive("#endif"),
+            )
             .parse_next(input)?;
 
-            let parsed_content = parse_chunks(&mut content)?;
-
             Ok(Chunk::Conditional {
                 name,
                 condition_type: ConditionType::$condType,
-                content: parsed_content,
+                content,
+                else_content: else_content.unwrap_or_default(),
             })
         }
     };
@@ -81,7 +104,7 @@ pub(crate) fn $name<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
 
 fn parse_define<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
     delimited(
-        ("#define", space_or_newline1),
+        (directive("#define"), space_or_newline1),
         take_till_space_or_newline,
         space_or_newline0,
     )
@@ -90,32 +113,30 @@ fn parse_define<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
 }
 
 fn parse_text<'a>(input: &mut &'a str) -> PResult<Chunk<'a>> {
-    let text = take_till(1.., |c| c == '#').parse_next(input)?;
-    if text.is_empty() {
-        Err(ErrMode::Incomplete(Needed::Unknown))
+    let len = input
+        .match_indices('#')
+        .find(|(index, _)| directive_len(&input[*index..]).is_some())
+        .map_or(input.len(), |(index, _)| index);
+
+    if len == 0 {
+        Err(ErrMode::Backtrack(ContextError::new()))
     } else {
+        let (text, remainder) = input.split_at(len);
+        *input = remainder;
         Ok(Chunk::Text(text.trim()))
     }
 }
 
+/// Parses chunks until the input end or a dangling `#else`/`#endif`.
 fn parse_chunks<'a>(input: &mut &'a str) -> PResult<Chunks<'a>> {
-    let mut chunks = Vec::new();
-
-    while !input.is_empty() {
-        let result = alt((parse_text, parse_define, parse_ifdef, parse_ifndef)).parse_next(input);
-
-        match result {
-            Ok(Chunk::Text(text)) if text.is_empty() => continue,
-            Ok(chunk) => chunks.push(chunk),
-            Err(e) => {
-                if !chunks.is_empty() {
-                    break;
-                }
-
-                return Err(e);
+    let chunks = repeat(0.., alt((parse_text, parse_define, parse_ifdef, parse_ifndef)))
+        .fold(Vec::new, |mut chunks: Vec<Chunk<'a>>, chunk| {
+            if chunk != Chunk::Text("") {
+                chunks.push(chunk);
             }
-        }
-    }
+            chunks
+        })
+        .parse_next(input)?;
 
     Ok(Chunks { chunks })
 }
@@ -130,10 +151,11 @@ enum Chunk<'a> {
         name: &'a str,
         condition_type: ConditionType,
         content: Chunks<'a>,
+        else_content: Chunks<'a>,
     },
 }
 
-impl<'a> Chunk<'a> {
+impl Chunk<'_> {
     fn eval_ctx(&self, ctx: &mut EvalContext) -> String {
         match self {
             Chunk::Text(text) => text.to_string(),
@@ -145,8 +167,9 @@ fn eval_ctx(&self, ctx: &mut EvalContext) -> String {
                 name,
                 condition_type,
                 content,
+                else_content,
             } => {
-                let is_defined = ctx.defines.contains(&name.to_string());
+                let is_defined = ctx.defines.contains(*name);
                 let should_include = match condition_type {
                     ConditionType::IfDef => is_defined,
                     ConditionType::IfNDef => !is_defined,
@@ -155,31 +178,34 @@ fn eval_ctx(&self, ctx: &mut EvalContext) -> String {
                 if should_include {
                     content.eval_ctx(ctx)
                 } else {
-                    String::new()
+                    else_content.eval_ctx(ctx)
                 }
             }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 struct Chunks<'a> {
     chunks: Vec<Chunk<'a>>,
 }
 
-impl<'a> Chunks<'a> {
+impl Chunks<'_> {
     fn eval_ctx(&self, ctx: &mut EvalContext) -> String {
         self.chunks
             .iter()
             .map(|chunk| chunk.eval_ctx(ctx))
-            .collect()
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
 pub(crate) fn preprocess<'a>(input: &mut &'a str) -> PResult<Cow<'a, str>> {
     let mut ctx = EvalContext::new();
     let filtered_comments = comments::filter(input)?.into_owned();
-    let chunks = parse_chunks(&mut filtered_comments.as_str())?;
+    let mut filtered = filtered_comments.as_str();
+    let chunks = terminated(parse_chunks, eof).parse_next(&mut filtered)?;
     Ok(Cow::Owned(chunks.eval_ctx(&mut ctx)))
 }
 
@@ -233,6 +259,7 @@ fn should_parse_ifdef() {
                         content: Chunks {
                             chunks: vec![Chunk::Text("xx")],
                         },
+                        else_content: Chunks::default(),
                     }),
                     "",
                 ),
@@ -244,6 +271,7 @@ fn should_parse_ifdef() {
                         content: Chunks {
                             chunks: vec![Chunk::Text("some content")],
                         },
+                        else_content: Chunks::default(),
                     }),
                     "",
                 ),
@@ -259,8 +287,10 @@ fn should_parse_ifdef() {
                                 content: Chunks {
                                     chunks: vec![Chunk::Text("some content")],
                                 },
+                                else_content: Chunks::default(),
                             }],
                         },
+                        else_content: Chunks::default(),
                     }),
                     "",
                 ),
@@ -299,6 +329,21 @@ fn should_preprocess() {
                     Some(Cow::from("content")),
                     "",
                 ), // Define and condition
+                (
+                    "#ifdef NAME\nthen\n#else\notherwise\n#endif\n#ifndef NAME\nnext\n#endif",
+                    Some(Cow::from("otherwise\nnext")),
+                    "",
+                ), // Else branch and sequential conditions
+                (
+                    "def A#i;\n#define NAME\ndef #NAME#B;",
+                    Some(Cow::from("def A#i;\ndef #NAME#B;")),
+                    "",
+                ), // Paste operators are kept as text
+                (
+                    "#ifndef NAME\n// «comment»\ndef A { string s = \"→\"; }\n#endif",
+                    Some(Cow::from("def A { string s = \"→\"; }")),
+                    "",
+                ), // Multibyte text
             ],
             preprocess,
         );
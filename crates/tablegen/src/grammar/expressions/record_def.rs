@@ -14,38 +14,175 @@
    limitations under the License.
 */
 
-// use winnow::PResult;
-// use winnow::ascii::*;
-// use winnow::combinator::*;
-// use winnow::combinator::*;
-// use winnow::error::*;
-// use winnow::error::*;
-// use winnow::stream::{AsChar, Stream};
-// use winnow::token::*;
-// use winnow::*;
+//!
+//! Tablegen record definitions parsing.
+//!
+
+use winnow::combinator::*;
+use winnow::PResult;
+use winnow::*;
+
+use crate::grammar::expressions::class_def::body::*;
+use crate::grammar::expressions::class_def::*;
+use crate::grammar::expressions::statements::*;
+use crate::grammar::expressions::values::{name_value, Value};
+use crate::grammar::tokens::helpers::*;
+use crate::grammar::tokens::identifier::identifier;
+use crate::grammar::tokens::type_name::*;
+
+/// `def Name : Parents { body }`, anonymous records have no name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordDefinition {
+    pub name: Option<Value>,
+    pub parents: Vec<ParentClassDefinition>,
+    pub body: Vec<Body>,
+}
+
+/// `defm Name : MultiClass<args>, Parents;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiRecordDefinition {
+    pub name: Option<Value>,
+    pub parents: Vec<ParentClassDefinition>,
+}
+
+/// `defset list<Type> name = { statements }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordSetDefinition {
+    pub r#type: Type,
+    pub name: String,
+    pub statements: Vec<Statement>,
+}
+
+pub(crate) fn record_def(input: &mut &str) -> PResult<RecordDefinition> {
+    preceded(
+        spaced_keyword("def"),
+        (opt(spaced_parser_of(name_value)), parent_classes, record_body),
+    )
+    .map(|(name, parents, body)| RecordDefinition { name, parents, body })
+    .parse_next(input)
+}
+
+pub(crate) fn multi_record_def(input: &mut &str) -> PResult<MultiRecordDefinition> {
+    delimited(
+        spaced_keyword("defm"),
+        (opt(spaced_parser_of(name_value)), parent_classes),
+        spaced_literal(";"),
+    )
+    .map(|(name, parents)| MultiRecordDefinition { name, parents })
+    .parse_next(input)
+}
+
+pub(crate) fn record_set_def(input: &mut &str) -> PResult<RecordSetDefinition> {
+    preceded(
+        spaced_keyword("defset"),
+        (
+            spaced_parser_of(type_ref),
+            spaced_parser(identifier),
+            preceded(
+                spaced_literal("="),
+                delimited(spaced_literal("{"), statements, spaced_literal("}")),
+            ),
+        ),
+    )
+    .map(|(r#type, name, statements)| RecordSetDefinition {
+        r#type,
+        name: name.to_string(),
+        statements,
+    })
+    .parse_next(input)
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grammar::expressions::values::{Argument, DagArg};
     use crate::grammar::tokens::helpers::tests::*;
 
     #[test]
     fn should_parse_record_def() {
-        // let output56 = Some((5, 6));
-        // test_parser(
-        //     vec![
-        //         ("5 ... 6", output56, ""),    // Valid range, fully consumed
-        //         ("5...6xx", output56, "xx"),  // Partially valid range input, stops before 'x'
-        //         ("5-6", output56, ""),    // Valid range, fully consumed
-        //         ("5 -6", Some((5, -6)), ""),  // Invalid range, fully consumed
-        //         ("5 6xx", output56, "xx"),  // Partially valid range input, stops before 'x'
-        //         ("5 6", output56, ""),    // Valid range, fully consumed
-        //         ("5   6xx", output56, "xx"),  // Partially valid range input, stops before 'x'
-        //         ("5   -6xx", Some((5, -6)), "xx"),  // Partially valid range input with neg exclusion, stops before 'x'\
-        //         ("5--6xx", Some((5,-6)), "xx"),  // Partially valid range input with neg exclusion, stops before 'x'
-        //         ("", None, ""),                   // Empty input should fail
-        //     ],
-        //     range_int_piece,
-        // );
+        test_parser(
+            vec![
+                (
+                    "def Arith_AddIOp : Arith_Op<\"addi\"> {\n  let summary = \"add\";\n}",
+                    Some(RecordDefinition {
+                        name: Some(Value::Identifier("Arith_AddIOp".to_string())),
+                        parents: vec![ParentClassDefinition {
+                            name: "Arith_Op".to_string(),
+                            arguments: vec![Argument::Positional(Value::String("addi".to_string()))],
+                        }],
+                        body: vec![Body::LetField {
+                            name: "summary".to_string(),
+                            ranges: vec![],
+                            value: Value::String("add".to_string()),
+                        }],
+                    }),
+                    "",
+                ), // Named record with a body, fully consumed
+                (
+                    "def : Pat<(A $x), (B $x)>;",
+                    Some(RecordDefinition {
+                        name: None,
+                        parents: vec![ParentClassDefinition {
+                            name: "Pat".to_string(),
+                            arguments: vec![
+                                Argument::Positional(Value::Dag(
+                                    Box::new(Value::Identifier("A".to_string())),
+                                    None,
+                                    vec![DagArg {
+                                        value: None,
+                                        name: Some("x".to_string()),
+                                    }],
+                                )),
+                                Argument::Positional(Value::Dag(
+                                    Box::new(Value::Identifier("B".to_string())),
+                                    None,
+                                    vec![DagArg {
+                                        value: None,
+                                        name: Some("x".to_string()),
+                                    }],
+                                )),
+                            ],
+                        }],
+                        body: vec![],
+                    }),
+                    "",
+                ), // Anonymous record, fully consumed
+                (
+                    "def X{}",
+                    Some(RecordDefinition {
+                        name: Some(Value::Identifier("X".to_string())),
+                        parents: vec![],
+                        body: vec![],
+                    }),
+                    "",
+                ), // Attached body brace
+                ("define X;", None, "define X;"), // Identifiers prefixed with def should fail
+            ],
+            record_def,
+        );
+    }
+
+    #[test]
+    fn should_parse_multi_record_def() {
+        test_parser(
+            vec![(
+                "defm NAME : Binary<\"add\">, Commutative;",
+                Some(MultiRecordDefinition {
+                    name: Some(Value::Identifier("NAME".to_string())),
+                    parents: vec![
+                        ParentClassDefinition {
+                            name: "Binary".to_string(),
+                            arguments: vec![Argument::Positional(Value::String("add".to_string()))],
+                        },
+                        ParentClassDefinition {
+                            name: "Commutative".to_string(),
+                            arguments: vec![],
+                        },
+                    ],
+                }),
+                "",
+            )],
+            multi_record_def,
+        );
     }
 }
@@ -0,0 +1,394 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Tablegen top-level statements parsing.
+//!
+
+use winnow::combinator::*;
+use winnow::PResult;
+use winnow::*;
+
+use crate::grammar::expressions::class_def::body::*;
+use crate::grammar::expressions::class_def::template_arg::*;
+use crate::grammar::expressions::class_def::*;
+use crate::grammar::expressions::record_def::*;
+use crate::grammar::expressions::values::{value, Value};
+use crate::grammar::tokens::helpers::*;
+use crate::grammar::tokens::identifier::identifier;
+use crate::grammar::tokens::ranges::range_list;
+use crate::grammar::tokens::strings::string;
+use crate::grammar::tokens::type_name::*;
+use crate::grammar::tokens::*;
+
+/// Tablegen statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `include "path"`
+    Include(String),
+    Class(ClassDefinition),
+    Def(RecordDefinition),
+    Defm(MultiRecordDefinition),
+    Defset(RecordSetDefinition),
+    /// `defvar name = value;`
+    Defvar { name: String, value: Value },
+    /// `deftype Name = Type;`
+    Deftype { name: String, r#type: Type },
+    /// `foreach iterator = range in { statements }`
+    Foreach {
+        iterator: String,
+        range: ForeachRange,
+        statements: Vec<Statement>,
+    },
+    /// `if condition then { statements } else { statements }`
+    If {
+        condition: Value,
+        then_statements: Vec<Statement>,
+        else_statements: Vec<Statement>,
+    },
+    /// `let name = value, ... in { statements }`
+    Let {
+        items: Vec<LetItem>,
+        statements: Vec<Statement>,
+    },
+    /// `multiclass Name<template args> : Parents { statements }`
+    MultiClass {
+        name: String,
+        template_arguments: Vec<ClassTemplateArg>,
+        parents: Vec<ParentClassDefinition>,
+        statements: Vec<Statement>,
+    },
+    /// `assert condition, message;`
+    Assert { condition: Value, message: Value },
+    /// `dump message;`
+    Dump { message: Value },
+}
+
+/// `foreach` iteration range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForeachRange {
+    /// `{0-3, 5}` or `0...3` integer ranges.
+    Ranges(Ranges),
+    /// Any list value, like `[a, b]` or `!range(4)`.
+    Value(Value),
+}
+
+/// Single `name{ranges} = value` item of the top-level let statements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetItem {
+    pub name: String,
+    pub ranges: Ranges,
+    pub value: Value,
+}
+
+fn include(input: &mut &str) -> PResult<Statement> {
+    preceded(spaced_keyword("include"), spaced_parser_of(string))
+        .map(Statement::Include)
+        .parse_next(input)
+}
+
+/// Parses either `{ statements }` or a single statement.
+fn statement_block(input: &mut &str) -> PResult<Vec<Statement>> {
+    alt((
+        delimited(spaced_literal("{"), statements, spaced_literal("}")),
+        statement.map(|statement| vec![statement]),
+    ))
+    .parse_next(input)
+}
+
+fn let_item(input: &mut &str) -> PResult<LetItem> {
+    (
+        spaced_parser(identifier),
+        opt_ranges,
+        preceded(spaced_literal("="), spaced_parser_of(value)),
+    )
+        .map(|(name, ranges, value)| LetItem {
+            name: name.to_string(),
+            ranges,
+            value,
+        })
+        .parse_next(input)
+}
+
+fn let_statement(input: &mut &str) -> PResult<Statement> {
+    preceded(
+        spaced_keyword("let"),
+        (
+            separated(1.., let_item, spaced_literal(",")),
+            preceded(spaced_keyword("in"), statement_block),
+        ),
+    )
+    .map(|(items, statements)| Statement::Let { items, statements })
+    .parse_next(input)
+}
+
+fn foreach_range(input: &mut &str) -> PResult<ForeachRange> {
+    alt((
+        delimited(spaced_literal("{"), range_list, spaced_literal("}")).map(ForeachRange::Ranges),
+        terminated(range_list, peek(spaced_keyword("in"))).map(ForeachRange::Ranges),
+        value.map(ForeachRange::Value),
+    ))
+    .parse_next(input)
+}
+
+fn foreach(input: &mut &str) -> PResult<Statement> {
+    preceded(
+        spaced_keyword("foreach"),
+        (
+            spaced_parser(identifier),
+            preceded(spaced_literal("="), spaced_parser_of(foreach_range)),
+            preceded(spaced_keyword("in"), statement_block),
+        ),
+    )
+    .map(|(iterator, range, statements)| Statement::Foreach {
+        iterator: iterator.to_string(),
+        range,
+        statements,
+    })
+    .parse_next(input)
+}
+
+fn if_statement(input: &mut &str) -> PResult<Statement> {
+    preceded(
+        spaced_keyword("if"),
+        (
+            spaced_parser_of(value),
+            preceded(spaced_keyword("then"), statement_block),
+            opt(preceded(spaced_keyword("else"), statement_block)),
+        ),
+    )
+    .map(|(condition, then_statements, else_statements)| Statement::If {
+        condition,
+        then_statements,
+        else_statements: else_statements.unwrap_or_default(),
+    })
+    .parse_next(input)
+}
+
+fn multiclass(input: &mut &str) -> PResult<Statement> {
+    preceded(
+        spaced_keyword("multiclass"),
+        (
+            spaced_parser(identifier),
+            opt(parse_template_args),
+            parent_classes,
+            alt((
+                delimited(spaced_literal("{"), statements, spaced_literal("}")),
+                spaced_literal(";").value(vec![]),
+            )),
+        ),
+    )
+    .map(
+        |(name, template_arguments, parents, statements)| Statement::MultiClass {
+            name: name.to_string(),
+            template_arguments: template_arguments.unwrap_or_default(),
+            parents,
+            statements,
+        },
+    )
+    .parse_next(input)
+}
+
+fn deftype(input: &mut &str) -> PResult<Statement> {
+    delimited(
+        spaced_keyword("deftype"),
+        separated_pair(
+            spaced_parser(identifier),
+            spaced_literal("="),
+            spaced_parser_of(type_ref),
+        ),
+        spaced_literal(";"),
+    )
+    .map(|(name, r#type)| Statement::Deftype {
+        name: name.to_string(),
+        r#type,
+    })
+    .parse_next(input)
+}
+
+/// Parses body-like `defvar`, `assert` and `dump` statements.
+fn body_statement(input: &mut &str) -> PResult<Statement> {
+    terminated(alt((parse_defvar, parse_assert, parse_dump)), spaced_literal(";"))
+        .map(|body| match body {
+            Body::DefVar { name, value } => Statement::Defvar { name, value },
+            Body::Assert { condition, message } => Statement::Assert { condition, message },
+            Body::Dump { message } => Statement::Dump { message },
+            _ => unreachable!("only defvar, assert and dump are parsed"),
+        })
+        .parse_next(input)
+}
+
+pub(crate) fn statement(input: &mut &str) -> PResult<Statement> {
+    alt((
+        include,
+        class_def.map(Statement::Class),
+        record_def.map(Statement::Def),
+        multi_record_def.map(Statement::Defm),
+        record_set_def.map(Statement::Defset),
+        body_statement,
+        deftype,
+        foreach,
+        if_statement,
+        let_statement,
+        multiclass,
+    ))
+    .parse_next(input)
+}
+
+/// Parses statements until the input end or a closing `}`.
+pub(crate) fn statements(input: &mut &str) -> PResult<Vec<Statement>> {
+    repeat(0.., spaced_parser_of(statement)).parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::tokens::helpers::tests::*;
+
+    fn id(name: &str) -> Value {
+        Value::Identifier(name.to_string())
+    }
+
+    fn def(name: &str) -> Statement {
+        Statement::Def(RecordDefinition {
+            name: Some(id(name)),
+            parents: vec![],
+            body: vec![],
+        })
+    }
+
+    #[test]
+    fn should_parse_includes() {
+        test_parser(
+            vec![
+                (
+                    "include \"mlir/IR/OpBase.td\"\n",
+                    Some(Statement::Include("mlir/IR/OpBase.td".to_string())),
+                    "",
+                ), // Valid include, fully consumed
+                ("include mlir", None, "mlir"), // Unquoted path should fail
+            ],
+            include,
+        );
+    }
+
+    #[test]
+    fn should_parse_let_statements() {
+        test_parser(
+            vec![
+                (
+                    "let a = 1, b = 2 in {\n def A;\n def B;\n}",
+                    Some(Statement::Let {
+                        items: vec![
+                            LetItem {
+                                name: "a".to_string(),
+                                ranges: vec![],
+                                value: Value::Int(1),
+                            },
+                            LetItem {
+                                name: "b".to_string(),
+                                ranges: vec![],
+                                value: Value::Int(2),
+                            },
+                        ],
+                        statements: vec![def("A"), def("B")],
+                    }),
+                    "",
+                ), // Block let, fully consumed
+                (
+                    "let a = 1 in\ndef A;",
+                    Some(Statement::Let {
+                        items: vec![LetItem {
+                            name: "a".to_string(),
+                            ranges: vec![],
+                            value: Value::Int(1),
+                        }],
+                        statements: vec![def("A")],
+                    }),
+                    "",
+                ), // Single statement let, fully consumed
+            ],
+            statement,
+        );
+    }
+
+    #[test]
+    fn should_parse_foreach_and_if() {
+        test_parser(
+            vec![
+                (
+                    "foreach i = 0...1 in def A#i;",
+                    Some(Statement::Foreach {
+                        iterator: "i".to_string(),
+                        range: ForeachRange::Ranges(vec![Range { start: 0, end: 1 }]),
+                        statements: vec![Statement::Def(RecordDefinition {
+                            name: Some(Value::Paste(Box::new(id("A")), Box::new(id("i")))),
+                            parents: vec![],
+                            body: vec![],
+                        })],
+                    }),
+                    "",
+                ), // Integer range foreach
+                (
+                    "foreach x = [a, b] in { def X; }",
+                    Some(Statement::Foreach {
+                        iterator: "x".to_string(),
+                        range: ForeachRange::Value(Value::List(vec![id("a"), id("b")], None)),
+                        statements: vec![def("X")],
+                    }),
+                    "",
+                ), // List foreach
+                (
+                    "if !eq(a, 1) then def A; else { def B; }",
+                    Some(Statement::If {
+                        condition: Value::Bang("eq".to_string(), None, vec![id("a"), Value::Int(1)]),
+                        then_statements: vec![def("A")],
+                        else_statements: vec![def("B")],
+                    }),
+                    "",
+                ), // If-else statement
+            ],
+            statement,
+        );
+    }
+
+    #[test]
+    fn should_parse_multiclasses() {
+        test_parser(
+            vec![(
+                "multiclass M<int n> {\n  def _a;\n  defvar x = n;\n}",
+                Some(Statement::MultiClass {
+                    name: "M".to_string(),
+                    template_arguments: vec![ClassTemplateArg {
+                        typ: Type::Int,
+                        identifier: "n".to_string(),
+                        value: Value::Uninitialized,
+                    }],
+                    parents: vec![],
+                    statements: vec![
+                        def("_a"),
+                        Statement::Defvar {
+                            name: "x".to_string(),
+                            value: id("n"),
+                        },
+                    ],
+                }),
+                "",
+            )],
+            statement,
+        );
+    }
+}
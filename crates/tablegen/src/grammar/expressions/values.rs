@@ -13,38 +13,350 @@
    See the License for the specific language governing permissions and
    limitations under the License.
 */
+
+//!
+//! Tablegen values parsing.
+//!
+
 use winnow::combinator::*;
+use winnow::token::literal;
 use winnow::PResult;
 use winnow::*;
 
-use crate::grammar::tokens::ranges::*;
+use crate::grammar::tokens::bang_operator::*;
+use crate::grammar::tokens::digits::*;
+use crate::grammar::tokens::helpers::*;
+use crate::grammar::tokens::identifier::*;
+use crate::grammar::tokens::strings::*;
+use crate::grammar::tokens::type_name::*;
+use crate::grammar::tokens::variable::*;
+
+/// Tablegen value, as written in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// `?`
+    Uninitialized,
+    /// `true` or `false`
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Code(String),
+    /// `{a, b, c}`
+    Bits(Vec<Value>),
+    /// `[a, b, c]<Type>`
+    List(Vec<Value>, Option<Type>),
+    /// `(operator:$name arg:$name, ...)`
+    Dag(Box<Value>, Option<String>, Vec<DagArg>),
+    Identifier(String),
+    /// `Class<args>` anonymous record instantiation.
+    ClassRef(String, Vec<Argument>),
+    /// `!operator<Type>(args)`
+    Bang(String, Option<Type>, Vec<Value>),
+    /// `!cond(condition : value, ...)`
+    Cond(Vec<(Value, Value)>),
+    /// `value.field`
+    Field(Box<Value>, String),
+    /// `value[slices]`
+    ListSlice(Box<Value>, Vec<Slice>),
+    /// `value{slices}`
+    BitSlice(Box<Value>, Vec<Slice>),
+    /// `value # value`
+    Paste(Box<Value>, Box<Value>),
+}
+
+/// Dag argument, either of the value and name are optional, but not both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DagArg {
+    pub value: Option<Value>,
+    pub name: Option<String>,
+}
+
+/// Class instantiation argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Positional(Value),
+    Named(String, Value),
+}
+
+/// List or bits slice element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slice {
+    Single(Value),
+    /// Inclusive range.
+    Range(Value, Value),
+}
+
+fn uninitialized(input: &mut &str) -> PResult<Value> {
+    '?'.value(Value::Uninitialized).parse_next(input)
+}
+
+fn bool_value(input: &mut &str) -> PResult<Value> {
+    alt((
+        spaced_keyword("true").value(Value::Bool(true)),
+        spaced_keyword("false").value(Value::Bool(false)),
+    ))
+    .parse_next(input)
+}
+
+fn hex_or_bin_value(input: &mut &str) -> PResult<Value> {
+    hex_or_bin_i64.map(Value::Int).parse_next(input)
+}
+
+fn int_value(input: &mut &str) -> PResult<Value> {
+    dec_i64.map(Value::Int).parse_next(input)
+}
+
+fn string_value(input: &mut &str) -> PResult<Value> {
+    strings.map(Value::String).parse_next(input)
+}
+
+fn code_value(input: &mut &str) -> PResult<Value> {
+    code.map(|code| Value::Code(code.to_string())).parse_next(input)
+}
+
+/// Parses comma separated values, allowing a trailing comma.
+pub(crate) fn value_list(input: &mut &str) -> PResult<Vec<Value>> {
+    terminated(
+        separated(0.., spaced_parser_of(value), spaced_literal(",")),
+        opt(spaced_literal(",")),
+    )
+    .parse_next(input)
+}
+
+fn bits_value(input: &mut &str) -> PResult<Value> {
+    delimited(spaced_literal("{"), value_list, literal("}"))
+        .map(Value::Bits)
+        .parse_next(input)
+}
+
+fn list_value(input: &mut &str) -> PResult<Value> {
+    (
+        delimited(spaced_literal("["), value_list, literal("]")),
+        opt(delimited(spaced_literal("<"), type_ref, spaced_literal(">"))),
+    )
+        .map(|(values, ty)| Value::List(values, ty))
+        .parse_next(input)
+}
+
+fn dag_arg(input: &mut &str) -> PResult<DagArg> {
+    alt((
+        variable_name.map(|name| DagArg {
+            value: None,
+            name: Some(name.to_string()),
+        }),
+        (value, opt(preceded(spaced_literal(":"), variable_name))).map(|(value, name)| DagArg {
+            value: Some(value),
+            name: name.map(String::from),
+        }),
+    ))
+    .parse_next(input)
+}
+
+fn dag_value(input: &mut &str) -> PResult<Value> {
+    delimited(
+        spaced_literal("("),
+        (
+            value,
+            opt(preceded(spaced_literal(":"), variable_name)),
+            opt(preceded(
+                space_or_newline0,
+                terminated(
+                    separated(0.., spaced_parser_of(dag_arg), spaced_literal(",")),
+                    opt(spaced_literal(",")),
+                ),
+            )),
+        ),
+        literal(")"),
+    )
+    .map(|(operator, name, args): (Value, Option<&str>, Option<Vec<DagArg>>)| {
+        Value::Dag(
+            Box::new(operator),
+            name.map(String::from),
+            args.unwrap_or_default(),
+        )
+    })
+    .parse_next(input)
+}
+
+fn argument(input: &mut &str) -> PResult<Argument> {
+    alt((
+        separated_pair(identifier, spaced_literal("="), spaced_parser_of(value))
+            .map(|(name, value)| Argument::Named(name.to_string(), value)),
+        value.map(Argument::Positional),
+    ))
+    .parse_next(input)
+}
+
+/// Parses `<arg, name = arg>` class instantiation arguments.
+pub(crate) fn arguments(input: &mut &str) -> PResult<Vec<Argument>> {
+    delimited(
+        spaced_literal("<"),
+        terminated(
+            separated(0.., spaced_parser_of(argument), spaced_literal(",")),
+            opt(spaced_literal(",")),
+        ),
+        literal(">"),
+    )
+    .parse_next(input)
+}
+
+fn identifier_value(input: &mut &str) -> PResult<Value> {
+    (identifier, opt(preceded(space_or_newline0, arguments)))
+        .map(|(name, args)| match args {
+            Some(args) => Value::ClassRef(name.to_string(), args),
+            None => Value::Identifier(name.to_string()),
+        })
+        .parse_next(input)
+}
+
+fn cond_value(input: &mut &str) -> PResult<Value> {
+    preceded(
+        ("!cond", space_or_newline0),
+        delimited(
+            spaced_literal("("),
+            terminated(
+                separated(
+                    1..,
+                    separated_pair(
+                        spaced_parser_of(value),
+                        spaced_literal(":"),
+                        spaced_parser_of(value),
+                    ),
+                    spaced_literal(","),
+                ),
+                opt(spaced_literal(",")),
+            ),
+            literal(")"),
+        ),
+    )
+    .map(Value::Cond)
+    .parse_next(input)
+}
+
+fn bang_value(input: &mut &str) -> PResult<Value> {
+    (
+        bang_operator,
+        opt(delimited(spaced_literal("<"), type_ref, spaced_literal(">"))),
+        delimited(spaced_literal("("), value_list, literal(")")),
+    )
+        .map(|(operator, ty, args)| Value::Bang(operator.to_string(), ty, args))
+        .parse_next(input)
+}
 
-pub fn value<'a>(input: &mut &'a str) -> PResult<&'a str> {
-    simple_value.parse_next(input)
-    // (
-    //     simple_value,
-    //     // repeat(0.., value_suffix),
-    // ).parse_next(input)
-    // // .map(|(a, b): (&str, Vec<&str>)| a)
+pub(crate) fn simple_value(input: &mut &str) -> PResult<Value> {
+    alt((
+        uninitialized,
+        bool_value,
+        hex_or_bin_value,
+        string_value,
+        code_value,
+        bits_value,
+        list_value,
+        dag_value,
+        cond_value,
+        bang_value,
+        identifier_value,
+        int_value,
+    ))
+    .parse_next(input)
 }
 
-pub(crate) fn simple_value<'a>(input: &mut &'a str) -> PResult<&'a str> {
-    "x".parse_next(input)
+/// Parses `#` pasted record names, like `NAME # "Op"`, without any suffixes,
+/// so the record body braces are not mistaken for the bit slices.
+pub(crate) fn name_value(input: &mut &str) -> PResult<Value> {
+    let piece = || {
+        alt((
+            string_value,
+            bang_value,
+            identifier.map(|name| Value::Identifier(name.to_string())),
+            int_value,
+        ))
+    };
+
+    (piece(), repeat(0.., preceded(spaced_literal("#"), piece())))
+        .map(|(value, pieces): (Value, Vec<Value>)| {
+            pieces.into_iter().fold(value, |value, piece| {
+                Value::Paste(Box::new(value), Box::new(piece))
+            })
+        })
+        .parse_next(input)
 }
 
+fn slice(input: &mut &str) -> PResult<Slice> {
+    alt((
+        separated_pair(
+            spaced_parser_of(simple_value),
+            alt((spaced_literal("..."), spaced_literal("-"))),
+            spaced_parser_of(simple_value),
+        )
+        .map(|(from, to)| Slice::Range(from, to)),
+        spaced_parser_of(value).map(Slice::Single),
+    ))
+    .parse_next(input)
+}
 
-// fn slice_elements<'a>(input: &mut &'a str) -> PResult<&'a str> {
-//     delimited("(", slice_element, ")").parse_next(input)
-// }
+fn slices(input: &mut &str) -> PResult<Vec<Slice>> {
+    separated(1.., slice, spaced_literal(",")).parse_next(input)
+}
 
-// fn slice_element<'a>(input: &mut &'a str) -> PResult<&'a str> {
-//     range_value_piece.parse_next(input)
-// }
+/// Value suffixes are attached to the value without any spaces in between,
+/// otherwise `{` and `[` would be mistaken for the record bodies.
+enum Suffix {
+    Bits(Vec<Slice>),
+    List(Vec<Slice>),
+    Field(String),
+}
+
+fn suffix(input: &mut &str) -> PResult<Suffix> {
+    alt((
+        delimited("{", slices, "}").map(Suffix::Bits),
+        delimited("[", slices, "]").map(Suffix::List),
+        preceded('.', identifier).map(|field| Suffix::Field(field.to_string())),
+    ))
+    .parse_next(input)
+}
+
+fn suffixed_value(input: &mut &str) -> PResult<Value> {
+    (simple_value, repeat(0.., suffix))
+        .map(|(value, suffixes): (Value, Vec<Suffix>)| {
+            suffixes
+                .into_iter()
+                .fold(value, |value, suffix| match suffix {
+                    Suffix::Bits(slices) => Value::BitSlice(Box::new(value), slices),
+                    Suffix::List(slices) => Value::ListSlice(Box::new(value), slices),
+                    Suffix::Field(field) => Value::Field(Box::new(value), field),
+                })
+        })
+        .parse_next(input)
+}
+
+/// Parses a value, pasting the `#` separated pieces together.
+pub fn value(input: &mut &str) -> PResult<Value> {
+    (
+        suffixed_value,
+        repeat(0.., preceded(spaced_literal("#"), suffixed_value)),
+    )
+        .map(|(value, pieces): (Value, Vec<Value>)| {
+            pieces.into_iter().fold(value, |value, piece| {
+                Value::Paste(Box::new(value), Box::new(piece))
+            })
+        })
+        .parse_next(input)
+}
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::grammar::tokens::helpers::tests::*;
 
+    fn id(name: &str) -> Value {
+        Value::Identifier(name.to_string())
+    }
+
+    fn string(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
     #[test]
     fn should_parse_ranges() {
         // let output56 = Some((5, 6));
@@ -64,4 +376,191 @@ fn should_parse_ranges() {
         //     range_int_piece,
         // );
     }
+
+    #[test]
+    fn should_parse_simple_values() {
+        test_parser(
+            vec![
+                ("?", Some(Value::Uninitialized), ""),         // Uninitialized value
+                ("true;", Some(Value::Bool(true)), ";"),        // Bool value, stops before ';'
+                ("trueish", Some(id("trueish")), ""),           // Identifier prefixed with a keyword
+                ("0x10", Some(Value::Int(16)), ""),             // Hex value
+                ("-3", Some(Value::Int(-3)), ""),               // Negative dec value
+                ("1x", Some(id("1x")), ""),                     // Digit prefixed identifier
+                (r#""a" "b""#, Some(string("ab")), ""),         // Concatenated strings
+                ("[{ code }]", Some(Value::Code(" code ".to_string())), ""), // Code block
+                (
+                    "[1, 2,]<int>",
+                    Some(Value::List(vec![Value::Int(1), Value::Int(2)], Some(Type::Int))),
+                    "",
+                ), // Typed list with a trailing comma
+                ("{0, 1}", Some(Value::Bits(vec![Value::Int(0), Value::Int(1)])), ""), // Bits
+                ("", None, ""),                                 // Empty input should fail
+            ],
+            simple_value,
+        );
+    }
+
+    #[test]
+    fn should_parse_class_refs() {
+        test_parser(
+            vec![
+                (
+                    "Variadic<AnyType>",
+                    Some(Value::ClassRef(
+                        "Variadic".to_string(),
+                        vec![Argument::Positional(id("AnyType"))],
+                    )),
+                    "",
+                ), // Class instantiation
+                (
+                    "Foo<1, name = \"x\">",
+                    Some(Value::ClassRef(
+                        "Foo".to_string(),
+                        vec![
+                            Argument::Positional(Value::Int(1)),
+                            Argument::Named("name".to_string(), string("x")),
+                        ],
+                    )),
+                    "",
+                ), // Named arguments
+            ],
+            value,
+        );
+    }
+
+    #[test]
+    fn should_parse_dags() {
+        test_parser(
+            vec![
+                (
+                    "(ins I32:$lhs, $rhs)",
+                    Some(Value::Dag(
+                        Box::new(id("ins")),
+                        None,
+                        vec![
+                            DagArg {
+                                value: Some(id("I32")),
+                                name: Some("lhs".to_string()),
+                            },
+                            DagArg {
+                                value: None,
+                                name: Some("rhs".to_string()),
+                            },
+                        ],
+                    )),
+                    "",
+                ), // Named dag arguments
+                (
+                    "(outs)",
+                    Some(Value::Dag(Box::new(id("outs")), None, vec![])),
+                    "",
+                ), // Empty dag
+                (
+                    "(ins\n  Variadic<AnyType>:$args\n)",
+                    Some(Value::Dag(
+                        Box::new(id("ins")),
+                        None,
+                        vec![DagArg {
+                            value: Some(Value::ClassRef(
+                                "Variadic".to_string(),
+                                vec![Argument::Positional(id("AnyType"))],
+                            )),
+                            name: Some("args".to_string()),
+                        }],
+                    )),
+                    "",
+                ), // Multi-line dag
+            ],
+            value,
+        );
+    }
+
+    #[test]
+    fn should_parse_bang_values() {
+        test_parser(
+            vec![
+                (
+                    "!listconcat(a, [b])",
+                    Some(Value::Bang(
+                        "listconcat".to_string(),
+                        None,
+                        vec![id("a"), Value::List(vec![id("b")], None)],
+                    )),
+                    "",
+                ), // Bang operator
+                (
+                    "!cast<string>(x)",
+                    Some(Value::Bang(
+                        "cast".to_string(),
+                        Some(Type::String),
+                        vec![id("x")],
+                    )),
+                    "",
+                ), // Typed bang operator
+                (
+                    "!cond(a : 1, true : 2)",
+                    Some(Value::Cond(vec![
+                        (id("a"), Value::Int(1)),
+                        (Value::Bool(true), Value::Int(2)),
+                    ])),
+                    "",
+                ), // Conditional operator
+            ],
+            value,
+        );
+    }
+
+    #[test]
+    fn should_parse_name_values() {
+        test_parser(
+            vec![
+                ("Foo{", Some(id("Foo")), "{"), // Attached body brace is not a slice
+                (
+                    "NAME # \"_Op\" :",
+                    Some(Value::Paste(Box::new(id("NAME")), Box::new(string("_Op")))),
+                    " :",
+                ), // Pasted name, stops before ':'
+                ("", None, ""), // Empty input should fail
+            ],
+            name_value,
+        );
+    }
+
+    #[test]
+    fn should_parse_suffixes_and_pastes() {
+        test_parser(
+            vec![
+                (
+                    "a.b[0]",
+                    Some(Value::ListSlice(
+                        Box::new(Value::Field(Box::new(id("a")), "b".to_string())),
+                        vec![Slice::Single(Value::Int(0))],
+                    )),
+                    "",
+                ), // Field access and list slice
+                (
+                    "x{3-0}",
+                    Some(Value::BitSlice(
+                        Box::new(id("x")),
+                        vec![Slice::Range(Value::Int(3), Value::Int(0))],
+                    )),
+                    "",
+                ), // Bit slice
+                (
+                    "traits # [Pure] # \"x\"",
+                    Some(Value::Paste(
+                        Box::new(Value::Paste(
+                            Box::new(id("traits")),
+                            Box::new(Value::List(vec![id("Pure")], None)),
+                        )),
+                        Box::new(string("x")),
+                    )),
+                    "",
+                ), // Left associative pastes
+                ("x {", Some(id("x")), " {"), // Detached braces are not a slice
+            ],
+            value,
+        );
+    }
 }
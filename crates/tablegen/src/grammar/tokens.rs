@@ -39,9 +39,9 @@
 pub(crate) mod variable;
 pub(crate) mod ranges;
 
-pub(crate) type Range = std::ops::Range<i64>;
+pub type Range = std::ops::Range<i64>;
 
-pub(crate) type Ranges = Vec<Range>;
+pub type Ranges = Vec<Range>;
 
 #[cfg(test)]
 mod tests {}
@@ -34,4 +34,16 @@ pub mod tests {
     use super::*;
 
     use crate::grammar::tokens::helpers::tests::*;
+
+    #[test]
+    fn should_parse_bang_operators() {
+        test_parser(
+            vec![
+                ("!foreach(", Some("foreach"), "("),  // Valid operator, stops before '('
+                ("!cast<Op>", Some("cast"), "<Op>"),  // Valid typed operator, stops before '<'
+                ("cast", None, "cast"),               // Operators should start with '!'
+            ],
+            bang_operator,
+        );
+    }
 }
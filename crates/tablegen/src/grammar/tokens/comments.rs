@@ -19,48 +19,111 @@
 //!
 
 use std::borrow::Cow;
-use winnow::combinator::*;
 use winnow::PResult;
-use winnow::*;
 
-fn filter_terminated<'a>(start: &str, end: &str, strip_ending: bool, input: &mut &'a str) -> PResult<Cow<'a, str>> {
-    let mut working_copy = Cow::Borrowed(*input);
+/// Comment delimiters, the ending is kept for single-line comments.
+struct CommentKind {
+    start: &'static str,
+    end: &'static str,
+    strip_ending: bool,
+}
+
+const SINGLE_LINE: CommentKind = CommentKind {
+    start: "//",
+    end: "\n",
+    strip_ending: false,
+};
+
+const MULTI_LINE: CommentKind = CommentKind {
+    start: "/*",
+    end: "*/",
+    strip_ending: true,
+};
+
+/// Returns the length of a string literal or a code block at the start of the input, if any.
+/// Comment delimiters are not recognized inside those.
+fn literal_len(input: &str) -> Option<usize> {
+    if input.starts_with("[{") {
+        return Some(input.find("}]").map_or(input.len(), |end| end + 2));
+    }
 
-    while let Some(start_pos) = working_copy.find(start) {
-        let end_pos = start_pos + working_copy[start_pos..].find(end).unwrap_or(working_copy.len() - start_pos);
-        let after = if strip_ending {
-            Cow::Borrowed(&working_copy[end_pos + end.len()..])
-        } else {
-            Cow::Borrowed(&working_copy[end_pos..])
-        };
+    if !input.starts_with('"') {
+        return None;
+    }
 
-        let mut new_working_copy = String::with_capacity(start_pos + after.len());
-        new_working_copy.push_str(&working_copy[..start_pos]);
-        new_working_copy.push_str(&after);
+    let mut escaped = false;
+    for (pos, c) in input.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(pos + 1),
+            '\n' => return Some(pos),
+            _ => escaped = false,
+        }
+    }
+
+    Some(input.len())
+}
 
-        working_copy = Cow::Owned(new_working_copy);
+fn filter_terminated<'a>(kinds: &[CommentKind], input: &mut &'a str) -> PResult<Cow<'a, str>> {
+    let source = *input;
+    let mut filtered: Option<String> = None;
+    let mut copied = 0;
+    let mut pos = 0;
+
+    while pos < source.len() {
+        let rest = &source[pos..];
+
+        if let Some(len) = literal_len(rest) {
+            pos += len;
+            continue;
+        }
+
+        if let Some(kind) = kinds.iter().find(|kind| rest.starts_with(kind.start)) {
+            let end_pos = rest[kind.start.len()..]
+                .find(kind.end)
+                .map_or(rest.len(), |end| {
+                    kind.start.len() + end + if kind.strip_ending { kind.end.len() } else { 0 }
+                });
+
+            filtered
+                .get_or_insert_with(|| String::with_capacity(source.len()))
+                .push_str(&source[copied..pos]);
+
+            pos += end_pos;
+            copied = pos;
+            continue;
+        }
+
+        pos += rest.chars().next().map_or(1, char::len_utf8);
     }
 
-    Ok(working_copy)
+    Ok(match filtered {
+        Some(mut filtered) => {
+            filtered.push_str(&source[copied..]);
+            Cow::Owned(filtered)
+        }
+        None => Cow::Borrowed(source),
+    })
 }
 
+#[cfg(test)]
 fn filter_single_line<'a>(input: &mut &'a str) -> PResult<Cow<'a, str>> {
-    let result = filter_terminated("//", "\n", false, input);
+    let result = filter_terminated(&[SINGLE_LINE], input);
     *input = "";
     result
 }
 
+#[cfg(test)]
 fn filter_multi_line<'a>(input: &mut &'a str) -> PResult<Cow<'a, str>> {
-    let result = filter_terminated("/*", "*/", true, input);
+    let result = filter_terminated(&[MULTI_LINE], input);
     *input = "";
     result
 }
 
+/// Filters both single-line and multi-line comments in a single pass,
+/// so comment delimiters nested into each other are not mistaken.
 pub(crate) fn filter<'a>(input: &mut &'a str) -> PResult<Cow<'a, str>> {
-    let result = match filter_multi_line(input)? {
-        Cow::Borrowed(input) => filter_single_line(&mut &*input),
-        Cow::Owned(input) => Ok(Cow::Owned(filter_single_line(&mut &*input)?.into_owned())),
-    };
+    let result = filter_terminated(&[SINGLE_LINE, MULTI_LINE], input);
     *input = "";
     result
 }
@@ -132,6 +195,16 @@ fn should_filter_comments() {
                     "",
                 ), // Mixed comments, fully consumed
                 ("code with no comments", Some(Cow::from("code with no comments")), ""), // No comments
+                (
+                    "s = \"http://a/*b\"; // c\n[{ x // y }]",
+                    Some(Cow::from("s = \"http://a/*b\"; \n[{ x // y }]")),
+                    "",
+                ), // Comment delimiters inside strings and code blocks
+                (
+                    "a // b /* c\nd */ e",
+                    Some(Cow::from("a \nd */ e")),
+                    "",
+                ), // Multi-line comment start inside a single-line comment
                 ("", Some(Cow::from("")), ""),                         // Empty input should not fail
             ],
             filter,
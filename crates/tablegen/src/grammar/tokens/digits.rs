@@ -20,7 +20,6 @@
 
 use winnow::ascii::*;
 use winnow::combinator::*;
-use winnow::stream::AsChar;
 use winnow::token::*;
 use winnow::PResult;
 use winnow::*;
@@ -71,8 +70,9 @@ pub(crate) fn dec_i64(input: &mut &str) -> PResult<i64> {
 }
 
 /// Parses dec hex bin numbers into i64.
+/// Hex and bin numbers go first, otherwise their `0` prefix is taken for a dec number.
 pub(crate) fn int(input: &mut &str) -> PResult<i64> {
-    alt((dec_i64, hex_or_bin_i64)).parse_next(input)
+    alt((hex_or_bin_i64, dec_i64)).parse_next(input)
 }
 
 #[cfg(test)]
@@ -123,4 +123,18 @@ fn should_parse_decimal_numbers() {
             dec_i64,
         );
     }
+
+    #[test]
+    fn should_parse_ints() {
+        test_parser(
+            vec![
+                ("0x1F", Some(31), ""),      // Valid hex string, fully consumed
+                ("0b11,", Some(3), ","),     // Valid bin string, stops before ','
+                ("-42>", Some(-42), ">"),    // Valid dec string, stops before '>'
+                ("0", Some(0), ""),          // Zero is not a hex prefix
+                ("", None, ""),              // Empty input should fail
+            ],
+            int,
+        );
+    }
 }
@@ -64,6 +64,24 @@ pub(crate) fn spaced_literal<'a>(literal_str: &'static str) -> impl Fn(&mut &'a
     }
 }
 
+/// Creates a parser for a reserved word surrounded by optional spaces or newlines,
+/// which is not a prefix of a longer identifier.
+pub(crate) fn spaced_keyword<'a>(keyword: &'static str) -> impl Fn(&mut &'a str) -> PResult<&'a str> {
+    move |input: &mut &'a str| {
+        let start = *input;
+        delimited(
+            space_or_newline0,
+            terminated(
+                literal(keyword),
+                not(one_of(|c: char| c.is_ascii_alphanumeric() || c == '_')),
+            ),
+            space_or_newline0,
+        )
+        .parse_next(input)
+        .inspect_err(|_| *input = start)
+    }
+}
+
 pub(crate) fn spaced_parser<'a, F>(parser: F) -> impl Fn(&mut &'a str) -> PResult<&'a str>
 where
     F: Fn(&mut &'a str) -> PResult<&'a str> + Clone
@@ -73,6 +91,17 @@ pub(crate) fn spaced_parser<'a, F>(parser: F) -> impl Fn(&mut &'a str) -> PResul
     }
 }
 
+/// Creates a parser of any output surrounded by optional spaces or newlines.
+pub(crate) fn spaced_parser_of<'a, O, P>(mut parser: P) -> impl FnMut(&mut &'a str) -> PResult<O>
+where
+    P: Parser<&'a str, O, ContextError>,
+{
+    move |input: &mut &'a str| {
+        delimited(space_or_newline0, parser.by_ref(), space_or_newline0).parse_next(input)
+    }
+}
+
+#[cfg(test)]
 #[cfg_attr(test, mutants::skip)]
 /// Parses a substring until the last specified terminator is encountered, then consumes it skipping the terminator.
 fn any_string_terminated<'a, const N: usize>(
@@ -116,6 +145,7 @@ fn any_string_terminated<'a, const N: usize>(
     }
 }
 
+#[cfg(test)]
 /// Parses a substring lazily until the last specified terminator is encountered, then consumes it optionally skipping the terminator.
 pub(crate) fn any_string_terminated_lazy<'a, const N: usize>(
     endings: [&'static str; N],
@@ -123,6 +153,7 @@ pub(crate) fn any_string_terminated_lazy<'a, const N: usize>(
     any_string_terminated(endings, false)
 }
 
+#[cfg(test)]
 /// Parses a substring eagerly until the last specified terminator is encountered, then consumes it optionally skipping the terminator.
 /// Eager parsing does not work for chained terminators.
 pub(crate) fn any_string_terminated_eager<'a, const N: usize>(
@@ -131,6 +162,7 @@ pub(crate) fn any_string_terminated_eager<'a, const N: usize>(
     any_string_terminated(endings, true)
 }
 
+#[cfg(test)]
 pub(crate) fn any_string<'a>(input: &mut &'a str) -> PResult<&'a str> {
     take_till(1.., |_| false).parse_next(input)
 }
@@ -228,6 +260,19 @@ fn should_parse_any_string_terminated_lazy() {
         );
     }
 
+    #[test]
+    fn should_parse_spaced_keywords() {
+        test_parser(
+            vec![
+                (" let x", Some("let"), "x"), // Valid keyword, spaces consumed
+                ("let\n", Some("let"), ""),   // Valid keyword, fully consumed
+                ("letter", None, "letter"),   // Identifiers prefixed with a keyword should fail
+                ("", None, ""),               // Empty input should fail
+            ],
+            spaced_keyword("let"),
+        );
+    }
+
     #[test]
     fn should_concatenate_parsers() {
         test_parser(
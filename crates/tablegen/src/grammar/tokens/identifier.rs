@@ -63,16 +63,34 @@ pub(crate) fn digit_identifier_chars0<'a>(input: &mut &'a str) -> PResult<&'a st
     take_while(0.., '0'..='9').parse_next(input)
 }
 
-pub(crate) fn identifier<'a>(input: &mut &'a str) -> PResult<&'a str> {
-    let id = concat([
+/// Parses identifier (alpha | digit)* chars including underscore.
+pub(crate) fn alnum_identifier_chars0<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    take_while(0.., ('a'..='z', 'A'..='Z', '0'..='9', '_')).parse_next(input)
+}
+
+/// Checks whether the char may continue an identifier.
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Parses any identifier-like word, including the reserved ones.
+pub(crate) fn word<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    concat([
         digit_identifier_chars0 as StrParser<'a>,
         alpha_identifier_chars1 as StrParser<'a>,
-        digit_identifier_chars0 as StrParser<'a>,
+        alnum_identifier_chars0 as StrParser<'a>,
     ])
-    .parse_next(input)?;
+    .parse_next(input)
+}
+
+/// Parses `[0-9]* ualpha (ualpha | [0-9])*` identifiers, rejecting the reserved words.
+pub(crate) fn identifier<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    let start = *input;
+    let id = word.parse_next(input)?;
 
     if RESERVED_WORDS.contains(&id) {
-        Err(ErrMode::from_error_kind(&id, ErrorKind::Fail))
+        *input = start;
+        Err(ErrMode::from_error_kind(input, ErrorKind::Fail))
     } else {
         Ok(id)
     }
@@ -89,9 +107,12 @@ fn should_parse_identifiers() {
             vec![
                 ("01id", Some("01id"), ""),   // Valid prefixed identifier, fully consumed
                 ("0id01", Some("0id01"), ""), // Valid suffixed identifier, fully consumed
-                ("id191x", Some("id191"), "x"), // Partially valid identifier input, stops before 'x'
-                ("1id191x", Some("1id191"), "x"), // Partially valid identifier input, stops before 'x'
-                ("", None, ""),                   // Empty input should fail
+                ("id191x", Some("id191x"), ""), // Valid alphanumeric identifier, fully consumed
+                ("1id191x", Some("1id191x"), ""), // Valid prefixed alphanumeric identifier, fully consumed
+                ("I32EnumAttrCase<", Some("I32EnumAttrCase"), "<"), // Stops before the template args
+                ("id-x", Some("id"), "-x"),     // Partially valid identifier input, stops before '-'
+                ("def", None, "def"),           // Reserved words should fail
+                ("", None, ""),                 // Empty input should fail
             ],
             identifier,
         );
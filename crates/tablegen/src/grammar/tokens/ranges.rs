@@ -18,7 +18,6 @@
 //! Tablegen ranges parsing.
 //!
 
-use std::ops::RangeFrom;
 use winnow::combinator::*;
 use winnow::stream::AsChar;
 use winnow::token::*;
@@ -45,7 +44,35 @@ fn ranged_parser<'a, F, T>(parser: F) -> impl Fn(&mut &'a str) -> PResult<(T, T)
     }
 }
 
-pub(crate) fn range_list(input: &mut &str) -> PResult<Vec<Range>> {
-    separated(1.., ranged_parser(int).map(|(from, to)| std::ops::Range { start: from, end: to}), ",")
-        .parse_next(input)
+/// Parses a comma separated list of inclusive ranges and single integers, like `0-3, 5, 7...9`.
+/// The `end` of each parsed [Range] is inclusive.
+pub(crate) fn range_list(input: &mut &str) -> PResult<Ranges> {
+    separated(
+        1..,
+        alt((ranged_parser(int), int.map(|n| (n, n))))
+            .map(|(from, to)| std::ops::Range { start: from, end: to }),
+        spaced_literal(","),
+    )
+    .parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::grammar::tokens::helpers::tests::*;
+
+    #[test]
+    fn should_parse_range_lists() {
+        test_parser(
+            vec![
+                ("0-3", Some(vec![Range { start: 0, end: 3 }]), ""),     // Dash range, fully consumed
+                ("0...3, 5", Some(vec![0..3, 5..5]), ""),                 // Ellipsis range and a single int
+                ("5 -6}", Some(vec![Range { start: 5, end: -6 }]), "}"), // Space separated range, stops before '}'
+                ("7,8x", Some(vec![7..7, 8..8]), "x"),                    // Single ints, stops before 'x'
+                ("", None, ""),                                           // Empty input should fail
+            ],
+            range_list,
+        );
+    }
 }
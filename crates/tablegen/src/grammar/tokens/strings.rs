@@ -23,21 +23,95 @@
 use winnow::PResult;
 use winnow::*;
 
-fn string<'a>(input: &mut &'a str) -> PResult<&'a str> {
-    preceded('"', terminated(take_while(1.., |c: char| c != '"'), '"')).parse_next(input)
-}
+use crate::grammar::tokens::helpers::*;
 
-fn code<'a>(input: &mut &'a str) -> PResult<&'a str> {
+/// Parses a single escaped string literal char, like `\n` or `\"`.
+fn escaped_char(input: &mut &str) -> PResult<char> {
     preceded(
-        "[{",
-        terminated(take_while(1.., |c: char| !"[{}]".contains(c)), "}]"),
+        '\\',
+        alt((
+            'n'.value('\n'),
+            't'.value('\t'),
+            '\\'.value('\\'),
+            '"'.value('"'),
+            '\''.value('\''),
+        )),
     )
     .parse_next(input)
 }
 
+/// Parses a double quoted string literal, unescaping its contents.
+pub(crate) fn string(input: &mut &str) -> PResult<String> {
+    delimited(
+        '"',
+        repeat(
+            0..,
+            alt((
+                take_while(1.., |c: char| c != '"' && c != '\\' && c != '\n').map(String::from),
+                escaped_char.map(String::from),
+            )),
+        )
+        .fold(String::new, |mut acc: String, chunk: String| {
+            acc.push_str(&chunk);
+            acc
+        }),
+        '"',
+    )
+    .parse_next(input)
+}
+
+/// Parses a series of adjacent string literals, which are concatenated just like in C.
+pub(crate) fn strings(input: &mut &str) -> PResult<String> {
+    let first = string.parse_next(input)?;
+
+    repeat(0.., preceded(space_or_newline0, string))
+        .fold(move || first.clone(), |acc: String, chunk: String| acc + &chunk)
+        .parse_next(input)
+}
+
+/// Parses a `[{ ... }]` code block, its contents are taken verbatim.
+pub(crate) fn code<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    delimited("[{", take_until(0.., "}]"), "}]").parse_next(input)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
     use crate::grammar::tokens::helpers::tests::*;
+
+    #[test]
+    fn should_parse_strings() {
+        test_parser(
+            vec![
+                (r#""abc""#, Some("abc".to_string()), ""), // Valid string, fully consumed
+                (r#""""#, Some("".to_string()), ""),       // Valid empty string, fully consumed
+                (r#""a\"b\n";"#, Some("a\"b\n".to_string()), ";"), // Escaped chars, stops before ';'
+                (r#""a" "b""#, Some("a".to_string()), r#" "b""#), // A single literal only
+                ("\"abc", None, ""),                      // Unterminated string should fail
+                ("", None, ""),                           // Empty input should fail
+            ],
+            string,
+        );
+
+        test_parser(
+            vec![
+                (r#""a" "b""#, Some("ab".to_string()), ""), // Adjacent literals are concatenated
+                ("\"a\"\n \"b\";", Some("ab".to_string()), ";"), // Literals split with a newline
+            ],
+            strings,
+        );
+    }
+
+    #[test]
+    fn should_parse_code() {
+        test_parser(
+            vec![
+                ("[{ a[0] = {1}; }]", Some(" a[0] = {1}; "), ""), // Brackets inside the code block
+                ("[{}]xx", Some(""), "xx"),                        // Empty code block, stops before 'xx'
+                ("[{ unterminated", None, " unterminated"),        // Unterminated code block should fail
+            ],
+            code,
+        );
+    }
 }
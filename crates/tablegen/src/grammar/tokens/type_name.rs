@@ -24,26 +24,66 @@
 use winnow::*;
 
 use crate::grammar::tokens::digits::*;
+use crate::grammar::tokens::helpers::*;
 use crate::grammar::tokens::identifier::*;
 
+/// Tablegen value types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Bit,
+    Int,
+    String,
+    Code,
+    Dag,
+    Bits(i64),
+    List(Box<Type>),
+    Class(String),
+}
+
 pub(crate) fn generic_bits_type_name(input: &mut &str) -> PResult<i64> {
-    delimited(literal("bits<"), int, literal(">")).parse_next(input)
+    delimited(literal("bits<"), spaced_parser_of(int), literal(">")).parse_next(input)
 }
 
+#[cfg(test)]
 pub(crate) fn generic_type_name<'a>(input: &mut &'a str) -> PResult<&'a str> {
     delimited(literal("type<"), identifier, literal(">")).parse_next(input)
 }
 
 pub(crate) fn type_name<'a>(input: &mut &'a str) -> PResult<&'a str> {
     alt((
-        "bit", "int", "string", "dag",
-        // generic_bits_type_name,
-        // generic_type_name,
+        terminated(alt(("bits", "bit", "int", "string", "dag", "code", "list")), not(one_of(is_identifier_char))),
         identifier,
     ))
     .parse_next(input)
 }
 
+fn list_type(input: &mut &str) -> PResult<Type> {
+    delimited(
+        (literal("list"), space_or_newline0, literal("<")),
+        spaced_parser_of(type_ref),
+        literal(">"),
+    )
+    .map(|element| Type::List(Box::new(element)))
+    .parse_next(input)
+}
+
+/// Parses a type reference, like `int`, `bits<4>`, `list<Trait>` or `Dialect`.
+pub(crate) fn type_ref(input: &mut &str) -> PResult<Type> {
+    alt((
+        generic_bits_type_name.map(Type::Bits),
+        list_type,
+        type_name.map(|name| match name {
+            "bit" => Type::Bit,
+            "int" => Type::Int,
+            "string" => Type::String,
+            "code" => Type::Code,
+            "dag" => Type::Dag,
+            name => Type::Class(name.to_string()),
+        }),
+    ))
+    .parse_next(input)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -70,4 +110,22 @@ fn should_parse_generic_types() {
             generic_type_name,
         );
     }
+
+    #[test]
+    fn should_parse_type_refs() {
+        test_parser(
+            vec![
+                ("int", Some(Type::Int), ""),                  // Primitive type, fully consumed
+                ("bits<8> x", Some(Type::Bits(8)), " x"),      // Sized bits, stops before ' x'
+                ("bitsx", Some(Type::Class("bitsx".to_string())), ""), // Class prefixed with a primitive
+                (
+                    "list<list<Trait>>",
+                    Some(Type::List(Box::new(Type::List(Box::new(Type::Class("Trait".to_string())))))),
+                    "",
+                ), // Nested lists, fully consumed
+                ("", None, ""),                               // Empty input should fail
+            ],
+            type_ref,
+        );
+    }
 }
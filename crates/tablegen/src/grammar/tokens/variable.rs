@@ -28,12 +28,13 @@
 pub(crate) fn variable_name_chars<'a>(input: &mut &'a str) -> PResult<&'a str> {
     concat([
         alpha_identifier_chars1 as StrParser<'a>,
-        digit_identifier_chars0 as StrParser<'a>,
+        alnum_identifier_chars0 as StrParser<'a>,
     ])
     .parse_next(input)
 }
 
-fn variable_name<'a>(input: &mut &'a str) -> PResult<&'a str> {
+/// Parses `$name` dag argument names.
+pub(crate) fn variable_name<'a>(input: &mut &'a str) -> PResult<&'a str> {
     preceded('$', variable_name_chars).parse_next(input)
 }
 
@@ -42,4 +43,17 @@ pub mod tests {
     use super::*;
 
     use crate::grammar::tokens::helpers::tests::*;
+
+    #[test]
+    fn should_parse_variable_names() {
+        test_parser(
+            vec![
+                ("$lhs", Some("lhs"), ""),           // Valid variable, fully consumed
+                ("$in_1, $b", Some("in_1"), ", $b"), // Valid variable, stops before ','
+                ("$1x", None, "1x"),                 // Variables can't start with digits
+                ("", None, ""),                      // Empty input should fail
+            ],
+            variable_name,
+        );
+    }
 }
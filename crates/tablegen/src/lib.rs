@@ -24,9 +24,30 @@
 
 extern crate core;
 
+#[cfg(feature = "fetch")]
+pub mod fetch;
 mod grammar;
+pub mod records;
+
+pub use grammar::TableGen;
+
+/// Tablegen syntax tree.
+pub mod ast {
+    pub use crate::grammar::expressions::class_def::body::Body;
+    pub use crate::grammar::expressions::class_def::template_arg::ClassTemplateArg;
+    pub use crate::grammar::expressions::class_def::{ClassDefinition, ParentClassDefinition};
+    pub use crate::grammar::expressions::record_def::{
+        MultiRecordDefinition, RecordDefinition, RecordSetDefinition,
+    };
+    pub use crate::grammar::expressions::statements::{ForeachRange, LetItem, Statement};
+    pub use crate::grammar::expressions::values::{Argument, DagArg, Slice, Value};
+    pub use crate::grammar::tokens::Ranges;
+    pub use crate::grammar::tokens::type_name::Type;
+}
 
 use winnow::PResult;
-pub fn parse<'a>(input: &'a str) -> PResult<grammar::TableGen<'a>> {
-    Ok(grammar::TableGen::<'a> { name: "r" })
+
+/// Preprocesses and parses Tablegen source.
+pub fn parse(input: &str) -> PResult<TableGen> {
+    TableGen::parse(&mut &*input)
 }
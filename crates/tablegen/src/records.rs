@@ -0,0 +1,813 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi tablegen
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Tablegen records evaluation, mirroring the LLVM `RecordKeeper`.
+//! Classes are instantiated with their template arguments, `let`, `foreach`, `if`, `defset`
+//! and `multiclass` statements are expanded, so only the concrete records are left.
+//!
+
+pub(crate) mod bang;
+pub(crate) mod eval;
+pub(crate) mod init;
+
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::grammar::expressions::class_def::template_arg::ClassTemplateArg;
+use crate::grammar::expressions::class_def::{ClassDefinition, ParentClassDefinition};
+use crate::grammar::expressions::record_def::{MultiRecordDefinition, RecordDefinition};
+use crate::grammar::expressions::statements::{ForeachRange, LetItem, Statement};
+use crate::grammar::expressions::values::Value;
+use crate::grammar::tokens::type_name::Type;
+use crate::grammar::TableGen;
+use eval::{Binding, Eval, RecordState, Scope};
+
+pub use init::{DagInit, Init};
+
+/// Derived [thiserror::Error] for Tablegen records evaluation errors
+#[derive(thiserror::Error, Debug)]
+pub enum RecordError {
+    #[error("Failed to parse {0:?}")]
+    ParseError(String),
+
+    #[error("Include {0:?} not found")]
+    IncludeNotFound(String),
+
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Unknown class {0:?}")]
+    UnknownClass(String),
+
+    #[error("Unknown multiclass {0:?}")]
+    UnknownMultiClass(String),
+
+    #[error("Undefined identifier {0:?}")]
+    Undefined(String),
+
+    #[error("Record {0:?} is already defined")]
+    Redefinition(String),
+
+    #[error("Record {record:?} has no field {field:?}")]
+    UnknownField { record: String, field: String },
+
+    #[error("{owner:?} has no template argument {arg:?}")]
+    UnknownTemplateArg { owner: String, arg: String },
+
+    #[error("Too many template arguments of {0:?}")]
+    TooManyTemplateArgs(String),
+
+    #[error("Circular reference to {record:?} field {field:?}")]
+    CircularReference { record: String, field: String },
+
+    #[error("Expected {expected}, got {found}")]
+    TypeMismatch { expected: String, found: String },
+
+    #[error("Unsupported bang operator !{0}")]
+    UnsupportedOperator(String),
+
+    #[error("Assertion failed in {record:?}: {message}")]
+    Assertion { record: String, message: String },
+
+    #[error("{0}")]
+    Evaluation(String),
+}
+
+impl RecordError {
+    pub(crate) fn type_mismatch(expected: &str, found: &Init) -> Self {
+        RecordError::TypeMismatch {
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+}
+
+/// Concrete Tablegen record with all the fields evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub name: String,
+    pub anonymous: bool,
+    /// File the record is defined in, as resolved by the [IncludeLoader].
+    pub filename: String,
+    /// All the direct and indirect superclasses, the most generic ones go first.
+    pub superclasses: Vec<String>,
+    /// Fields in their declaration order.
+    pub fields: Vec<(String, Init)>,
+}
+
+impl Record {
+    pub fn is_subclass_of(&self, class: &str) -> bool {
+        self.superclasses.iter().any(|superclass| superclass == class)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&Init> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, init)| init)
+    }
+
+    /// String or code field value.
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        self.value(name).and_then(Init::as_str)
+    }
+
+    pub fn get_bit(&self, name: &str) -> Option<bool> {
+        self.value(name).and_then(Init::as_bool)
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        self.value(name).and_then(Init::as_int)
+    }
+
+    pub fn get_list(&self, name: &str) -> Option<&[Init]> {
+        self.value(name).and_then(Init::as_list)
+    }
+
+    pub fn get_dag(&self, name: &str) -> Option<&DagInit> {
+        self.value(name).and_then(Init::as_dag)
+    }
+
+    pub fn get_def(&self, name: &str) -> Option<&Rc<Record>> {
+        self.value(name).and_then(Init::as_def)
+    }
+}
+
+/// Loads the `include "path"` files.
+pub trait IncludeLoader {
+    /// Returns the resolved file name and its contents.
+    fn load(&self, path: &str) -> Result<(String, String), RecordError>;
+}
+
+/// Looks up the included files in the list of directories, like `tblgen -I`.
+#[derive(Debug, Clone, Default)]
+pub struct IncludeDirs {
+    pub dirs: Vec<PathBuf>,
+}
+
+impl IncludeDirs {
+    pub fn new(dirs: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            dirs: dirs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl IncludeLoader for IncludeDirs {
+    fn load(&self, path: &str) -> Result<(String, String), RecordError> {
+        let file = self
+            .dirs
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|file| file.is_file())
+            .ok_or_else(|| RecordError::IncludeNotFound(path.to_string()))?;
+        let contents = std::fs::read_to_string(&file)?;
+        Ok((file.display().to_string(), contents))
+    }
+}
+
+/// In-memory sources, keyed by the include path.
+impl IncludeLoader for HashMap<String, String> {
+    fn load(&self, path: &str) -> Result<(String, String), RecordError> {
+        self.get(path)
+            .map(|contents| (path.to_string(), contents.clone()))
+            .ok_or_else(|| RecordError::IncludeNotFound(path.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ClassDef {
+    pub(crate) definition: ClassDefinition,
+    pub(crate) scope: Rc<Scope>,
+}
+
+#[derive(Debug)]
+struct MultiClassDef {
+    template_arguments: Vec<ClassTemplateArg>,
+    parents: Vec<ParentClassDefinition>,
+    statements: Vec<Statement>,
+    scope: Rc<Scope>,
+}
+
+#[derive(Debug, Clone)]
+struct LetBinding {
+    item: LetItem,
+    scope: Rc<Scope>,
+}
+
+/// `defm` instantiation context of the multiclass statements.
+#[derive(Debug)]
+struct DefmContext {
+    name: String,
+    parents: Vec<(ParentClassDefinition, Rc<Scope>)>,
+    lets: Vec<LetBinding>,
+}
+
+/// Statements processing frame.
+#[derive(Clone)]
+struct Frame<'a> {
+    filename: &'a str,
+    scope: Rc<Scope>,
+    lets: Vec<LetBinding>,
+    defm: Option<Rc<DefmContext>>,
+}
+
+impl Frame<'_> {
+    fn child(&self) -> Self {
+        Self {
+            scope: Scope::child(&self.scope),
+            ..self.clone()
+        }
+    }
+}
+
+/// Checks if the record name refers to the `NAME` of the multiclass instantiation.
+fn refers_to_name(value: &Value) -> bool {
+    match value {
+        Value::Identifier(name) => name == "NAME",
+        Value::Paste(lhs, rhs) => refers_to_name(lhs) || refers_to_name(rhs),
+        Value::Bang(_, _, args) => args.iter().any(refers_to_name),
+        _ => false,
+    }
+}
+
+/// Evaluated Tablegen records.
+#[derive(Debug, Default)]
+pub struct RecordKeeper {
+    defs: Vec<Rc<Record>>,
+    by_name: HashMap<String, Rc<Record>>,
+    pub(crate) classes: HashMap<String, Rc<ClassDef>>,
+    multiclasses: HashMap<String, Rc<MultiClassDef>>,
+    types: HashMap<String, Type>,
+    globals: Rc<Scope>,
+    included: HashSet<String>,
+    anonymous: Cell<usize>,
+}
+
+impl RecordKeeper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses and evaluates the Tablegen source, the includes are resolved by the loader.
+    pub fn from_source(
+        filename: &str,
+        source: &str,
+        loader: &dyn IncludeLoader,
+    ) -> Result<Self, RecordError> {
+        let mut keeper = Self::new();
+        keeper.add_source(filename, source, loader)?;
+        Ok(keeper)
+    }
+
+    /// Adds the records of another source file, each file is included only once.
+    pub fn add_source(
+        &mut self,
+        filename: &str,
+        source: &str,
+        loader: &dyn IncludeLoader,
+    ) -> Result<(), RecordError> {
+        if !self.included.insert(filename.to_string()) {
+            return Ok(());
+        }
+        let tablegen = TableGen::parse(&mut &*source)
+            .map_err(|_| RecordError::ParseError(filename.to_string()))?;
+        let frame = Frame {
+            filename,
+            scope: self.globals.clone(),
+            lets: vec![],
+            defm: None,
+        };
+        self.statements(&tablegen.statements, &frame, loader)
+    }
+
+    /// All the concrete records in their definition order.
+    pub fn defs(&self) -> &[Rc<Record>] {
+        &self.defs
+    }
+
+    pub fn def(&self, name: &str) -> Option<&Rc<Record>> {
+        self.by_name.get(name)
+    }
+
+    pub fn is_class(&self, name: &str) -> bool {
+        self.classes.contains_key(name)
+    }
+
+    /// Records derived from the class, like `getAllDerivedDefinitions`.
+    pub fn derived_definitions<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a Rc<Record>> {
+        self.defs.iter().filter(move |def| def.is_subclass_of(class))
+    }
+
+    /// Files included while evaluating the sources.
+    pub fn included_files(&self) -> impl Iterator<Item = &str> {
+        self.included.iter().map(String::as_str)
+    }
+
+    pub(crate) fn resolve_type(&self, r#type: &Type) -> Type {
+        match r#type {
+            Type::Class(name) => self.types.get(name).cloned().unwrap_or_else(|| r#type.clone()),
+            Type::List(element) => Type::List(Box::new(self.resolve_type(element))),
+            r#type => r#type.clone(),
+        }
+    }
+
+    pub(crate) fn anonymous_name(&self) -> String {
+        let index = self.anonymous.get();
+        self.anonymous.set(index + 1);
+        format!("anonymous_{index}")
+    }
+
+    fn statements(
+        &mut self,
+        statements: &[Statement],
+        frame: &Frame,
+        loader: &dyn IncludeLoader,
+    ) -> Result<(), RecordError> {
+        for statement in statements {
+            self.statement(statement, frame, loader)?;
+        }
+        Ok(())
+    }
+
+    fn statement(
+        &mut self,
+        statement: &Statement,
+        frame: &Frame,
+        loader: &dyn IncludeLoader,
+    ) -> Result<(), RecordError> {
+        match statement {
+            Statement::Include(path) => {
+                let (filename, source) = loader.load(path)?;
+                self.add_source(&filename, &source, loader)?;
+            }
+            Statement::Class(definition) => {
+                self.classes.insert(
+                    definition.name.clone(),
+                    Rc::new(ClassDef {
+                        definition: definition.clone(),
+                        scope: frame.scope.clone(),
+                    }),
+                );
+            }
+            Statement::Def(definition) => self.define(definition, frame)?,
+            Statement::Defm(definition) => self.defm(definition, frame, loader)?,
+            Statement::Defset(definition) => {
+                let first = self.defs.len();
+                self.statements(&definition.statements, &frame.child(), loader)?;
+                let defs = self.defs[first..]
+                    .iter()
+                    .filter(|def| {
+                        let element = Init::Def((*def).clone());
+                        let eval = Eval::new(self, frame.filename);
+                        match self.resolve_type(&definition.r#type) {
+                            Type::List(r#type) => eval.isa(&element, &r#type),
+                            _ => true,
+                        }
+                    })
+                    .map(|def| Init::Def(def.clone()))
+                    .collect();
+                self.globals
+                    .bind(&definition.name, Binding::Init(Init::List(defs)));
+            }
+            Statement::Defvar { name, value } => {
+                let init = Eval::new(self, frame.filename).value(value, &frame.scope)?;
+                frame.scope.bind(name, Binding::Init(init));
+            }
+            Statement::Deftype { name, r#type } => {
+                let r#type = self.resolve_type(r#type);
+                self.types.insert(name.clone(), r#type);
+            }
+            Statement::Foreach {
+                iterator,
+                range,
+                statements,
+            } => {
+                let values = match range {
+                    ForeachRange::Ranges(ranges) => ranges
+                        .iter()
+                        .flat_map(|range| {
+                            let values: Vec<i64> = if range.start <= range.end {
+                                (range.start..=range.end).collect()
+                            } else {
+                                (range.end..=range.start).rev().collect()
+                            };
+                            values
+                        })
+                        .map(Init::Int)
+                        .collect(),
+                    ForeachRange::Value(value) => {
+                        match Eval::new(self, frame.filename).value(value, &frame.scope)? {
+                            Init::List(values) => values,
+                            init => return Err(RecordError::type_mismatch("list", &init)),
+                        }
+                    }
+                };
+                for value in values {
+                    let frame = frame.child();
+                    frame.scope.bind(iterator, Binding::Init(value));
+                    self.statements(statements, &frame, loader)?;
+                }
+            }
+            Statement::If {
+                condition,
+                then_statements,
+                else_statements,
+            } => {
+                let condition =
+                    Eval::new(self, frame.filename).condition(condition, &frame.scope)?;
+                let statements = if condition {
+                    then_statements
+                } else {
+                    else_statements
+                };
+                self.statements(statements, &frame.child(), loader)?;
+            }
+            Statement::Let { items, statements } => {
+                let mut frame = frame.child();
+                frame.lets.extend(items.iter().map(|item| LetBinding {
+                    item: item.clone(),
+                    scope: frame.scope.clone(),
+                }));
+                self.statements(statements, &frame, loader)?;
+            }
+            Statement::MultiClass {
+                name,
+                template_arguments,
+                parents,
+                statements,
+            } => {
+                self.multiclasses.insert(
+                    name.clone(),
+                    Rc::new(MultiClassDef {
+                        template_arguments: template_arguments.clone(),
+                        parents: parents.clone(),
+                        statements: statements.clone(),
+                        scope: frame.scope.clone(),
+                    }),
+                );
+            }
+            Statement::Assert { condition, message } => {
+                let eval = Eval::new(self, frame.filename);
+                if !eval.condition(condition, &frame.scope)? {
+                    return Err(RecordError::Assertion {
+                        record: String::new(),
+                        message: eval.value(message, &frame.scope)?.to_name_string(),
+                    });
+                }
+            }
+            Statement::Dump { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn define(&mut self, definition: &RecordDefinition, frame: &Frame) -> Result<(), RecordError> {
+        let eval = Eval::new(self, frame.filename);
+        let name = match &definition.name {
+            Some(name) => {
+                let evaluated = eval.name(name, &frame.scope)?;
+                match &frame.defm {
+                    Some(defm) if !refers_to_name(name) => Some(defm.name.clone() + &evaluated),
+                    _ => Some(evaluated),
+                }
+            }
+            None => None,
+        };
+
+        let mut state = match name {
+            Some(name) => RecordState::new(name, false),
+            None => RecordState::new(self.anonymous_name(), true),
+        };
+        let scope = Scope::child(&frame.scope);
+        for parent in &definition.parents {
+            eval.add_class(&mut state, parent, &frame.scope)?;
+        }
+        for binding in &frame.lets {
+            let LetItem {
+                name,
+                ranges,
+                value,
+            } = &binding.item;
+            state.set_field(name, ranges, value, &binding.scope)?;
+        }
+        eval.apply_body(&mut state, &definition.body, &scope)?;
+
+        if let Some(defm) = &frame.defm {
+            for (parent, scope) in &defm.parents {
+                eval.add_class(&mut state, parent, scope)?;
+            }
+            for binding in &defm.lets {
+                let LetItem {
+                    name,
+                    ranges,
+                    value,
+                } = &binding.item;
+                state.set_field(name, ranges, value, &binding.scope)?;
+            }
+        }
+
+        let record = Rc::new(eval.finalize(state)?);
+        if !record.anonymous {
+            if self.by_name.contains_key(&record.name) {
+                return Err(RecordError::Redefinition(record.name.clone()));
+            }
+            self.by_name.insert(record.name.clone(), record.clone());
+        }
+        self.defs.push(record);
+        Ok(())
+    }
+
+    fn defm(
+        &mut self,
+        definition: &MultiRecordDefinition,
+        frame: &Frame,
+        loader: &dyn IncludeLoader,
+    ) -> Result<(), RecordError> {
+        let eval = Eval::new(self, frame.filename);
+        let mut name = match &definition.name {
+            Some(name) => eval.name(name, &frame.scope)?,
+            None => self.anonymous_name(),
+        };
+
+        let mut parents = vec![];
+        let mut lets = vec![];
+        if let Some(outer) = &frame.defm {
+            if !definition.name.as_ref().is_some_and(refers_to_name) {
+                name = outer.name.clone() + &name;
+            }
+            parents.extend(outer.parents.iter().cloned());
+            lets.extend(outer.lets.iter().cloned());
+        }
+        lets.extend(frame.lets.iter().cloned());
+
+        let (multiclasses, classes): (Vec<_>, Vec<_>) = definition
+            .parents
+            .iter()
+            .partition(|parent| self.multiclasses.contains_key(&parent.name));
+        if multiclasses.is_empty() {
+            return Err(RecordError::UnknownMultiClass(
+                definition
+                    .parents
+                    .first()
+                    .map_or_else(String::new, |parent| parent.name.clone()),
+            ));
+        }
+        parents.extend(
+            classes
+                .into_iter()
+                .map(|parent| (parent.clone(), frame.scope.clone())),
+        );
+
+        let context = Rc::new(DefmContext {
+            name,
+            parents,
+            lets,
+        });
+        for parent in multiclasses {
+            self.instantiate_multiclass(parent, &frame.scope, &context, frame.filename, loader)?;
+        }
+        Ok(())
+    }
+
+    fn instantiate_multiclass(
+        &mut self,
+        parent: &ParentClassDefinition,
+        caller_scope: &Rc<Scope>,
+        context: &Rc<DefmContext>,
+        filename: &str,
+        loader: &dyn IncludeLoader,
+    ) -> Result<(), RecordError> {
+        let multiclass = self
+            .multiclasses
+            .get(&parent.name)
+            .cloned()
+            .ok_or_else(|| RecordError::UnknownMultiClass(parent.name.clone()))?;
+
+        let scope = Scope::child(&multiclass.scope);
+        scope.bind("NAME", Binding::Init(Init::String(context.name.clone())));
+        Eval::new(self, filename).bind_template_args(
+            &parent.name,
+            &multiclass.template_arguments,
+            &parent.arguments,
+            caller_scope,
+            &scope,
+        )?;
+
+        for superclass in &multiclass.parents {
+            self.instantiate_multiclass(superclass, &scope, context, filename, loader)?;
+        }
+
+        let frame = Frame {
+            filename,
+            scope: Scope::child(&scope),
+            lets: vec![],
+            defm: Some(context.clone()),
+        };
+        self.statements(&multiclass.statements, &frame, loader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(source: &str) -> RecordKeeper {
+        RecordKeeper::from_source("test.td", source, &HashMap::new()).unwrap()
+    }
+
+    fn names(keeper: &RecordKeeper) -> Vec<&str> {
+        keeper.defs().iter().map(|def| def.name.as_str()).collect()
+    }
+
+    #[test]
+    fn should_instantiate_classes() {
+        let keeper = records(
+            r#"
+            class Base<string n, int w = 8> {
+              string name = n;
+              int width = w;
+              bits<4> mask = w;
+              string full = name # "_" # width;
+            }
+            class Derived<string n> : Base<n, 16> {
+              let name = "derived_" # n;
+              bit flag = true;
+            }
+            def A : Base<"a">;
+            def B : Derived<"b"> { let mask{1-0} = 0b11; }
+            "#,
+        );
+
+        let a = keeper.def("A").unwrap();
+        assert_eq!(a.get_string("full"), Some("a_8"));
+        assert_eq!(a.get_int("mask"), Some(8));
+        assert_eq!(a.superclasses, vec!["Base"]);
+
+        let b = keeper.def("B").unwrap();
+        assert_eq!(b.get_string("name"), Some("derived_b"));
+        assert_eq!(b.get_string("full"), Some("derived_b_16"));
+        assert_eq!(b.get_int("mask"), Some(3));
+        assert_eq!(b.get_bit("flag"), Some(true));
+        assert!(b.is_subclass_of("Base"));
+        assert_eq!(keeper.derived_definitions("Derived").count(), 1);
+    }
+
+    #[test]
+    fn should_evaluate_statements() {
+        let keeper = records(
+            r#"
+            class C<int v> { int value = v; list<int> values = []; }
+            defvar base = 10;
+            let values = [1, 2] in {
+              foreach i = 0...1 in
+                def C#i : C<!add(base, i)>;
+            }
+            if !eq(base, 10) then {
+              def Then : C<1>;
+            } else {
+              def Else : C<2>;
+            }
+            defset list<C> All = {
+              def D : C<3>;
+            }
+            def Sizes : C<!size(All)>;
+            "#,
+        );
+
+        assert_eq!(names(&keeper), vec!["C0", "C1", "Then", "D", "Sizes"]);
+        assert_eq!(keeper.def("C1").unwrap().get_int("value"), Some(11));
+        assert_eq!(
+            keeper.def("C0").unwrap().value("values"),
+            Some(&Init::List(vec![Init::Int(1), Init::Int(2)]))
+        );
+        assert_eq!(keeper.def("Sizes").unwrap().get_int("value"), Some(1));
+    }
+
+    #[test]
+    fn should_instantiate_multiclasses() {
+        let keeper = records(
+            r#"
+            class Inst<string m> { string mnemonic = m; string name = NAME; }
+            class Tag { bit tagged = true; }
+            multiclass Arith<string m> {
+              def _rr : Inst<m # ".rr">;
+              def NAME # _ri : Inst<m # ".ri">;
+            }
+            multiclass Both<string m> : Arith<m> {
+              def _x : Inst<m>;
+            }
+            defm ADD : Arith<"add">, Tag;
+            defm SUB : Both<"sub">;
+            "#,
+        );
+
+        assert_eq!(
+            names(&keeper),
+            vec!["ADD_rr", "ADD_ri", "SUB_rr", "SUB_ri", "SUB_x"]
+        );
+        let add = keeper.def("ADD_ri").unwrap();
+        assert_eq!(add.get_string("mnemonic"), Some("add.ri"));
+        assert_eq!(add.get_string("name"), Some("ADD_ri"));
+        assert_eq!(add.get_bit("tagged"), Some(true));
+        assert_eq!(keeper.def("SUB_x").unwrap().get_bit("tagged"), None);
+    }
+
+    #[test]
+    fn should_resolve_references() {
+        let keeper = records(
+            r#"
+            def ins;
+            class Type<string n> { string name = n; }
+            def I32 : Type<"i32">;
+            class Op<dag a> {
+              dag arguments = a;
+              Type first = !getdagarg<Type>(a, 0);
+              list<string> names = !foreach(t, [I32, Type<"f32">], t.name);
+            }
+            def AddOp : Op<(ins I32:$lhs, I32:$rhs)>;
+            def Anon : Op<(ins Type<"i1">:$flag)>;
+            "#,
+        );
+
+        let add = keeper.def("AddOp").unwrap();
+        let arguments = add.get_dag("arguments").unwrap();
+        assert_eq!(arguments.operator_name(), Some("ins"));
+        assert_eq!(arguments.args.len(), 2);
+        assert_eq!(arguments.args[1].1.as_deref(), Some("rhs"));
+        assert_eq!(add.get_def("first").unwrap().name, "I32");
+        assert_eq!(
+            add.value("names"),
+            Some(&Init::List(vec![
+                Init::String("i32".to_string()),
+                Init::String("f32".to_string())
+            ]))
+        );
+
+        let anon = keeper.def("Anon").unwrap();
+        let flag = anon.get_def("first").unwrap();
+        assert!(flag.anonymous);
+        assert_eq!(flag.get_string("name"), Some("i1"));
+    }
+
+    #[test]
+    fn should_report_errors() {
+        let error = |source: &str| {
+            RecordKeeper::from_source("test.td", source, &HashMap::new())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(error("def A : Missing;"), "Unknown class \"Missing\"");
+        assert_eq!(error("def A; def A;"), "Record \"A\" is already defined");
+        assert_eq!(
+            error("class C { int a = b; int b = a; } def A : C;"),
+            "Circular reference to \"A\" field \"a\""
+        );
+        assert_eq!(
+            error("class C<int v> { assert !gt(v, 0), \"positive\"; } def A : C<0>;"),
+            "Assertion failed in \"A\": positive"
+        );
+        assert_eq!(error("include \"missing.td\""), "Include \"missing.td\" not found");
+    }
+
+    #[test]
+    fn should_include_files_once() {
+        let loader = HashMap::from([
+            ("base.td".to_string(), "class Base { int x = 1; }".to_string()),
+            (
+                "a.td".to_string(),
+                "include \"base.td\"\ndef A : Base;".to_string(),
+            ),
+        ]);
+        let keeper = RecordKeeper::from_source(
+            "main.td",
+            "include \"base.td\"\ninclude \"a.td\"\ndef B : Base;",
+            &loader,
+        )
+        .unwrap();
+
+        assert_eq!(names(&keeper), vec!["A", "B"]);
+        assert_eq!(keeper.def("A").unwrap().filename, "a.td");
+        assert_eq!(keeper.included_files().count(), 3);
+    }
+}
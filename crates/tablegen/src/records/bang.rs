@@ -0,0 +1,485 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Tablegen bang operators evaluation.
+//!
+
+use std::rc::Rc;
+
+use crate::grammar::expressions::values::Value;
+use crate::grammar::tokens::type_name::Type;
+use crate::records::eval::{Binding, Eval, Scope};
+use crate::records::init::{DagInit, Init};
+use crate::records::RecordError;
+
+fn int(init: &Init) -> Result<i64, RecordError> {
+    init.as_int()
+        .ok_or_else(|| RecordError::type_mismatch("int", init))
+}
+
+fn string(init: &Init) -> Result<&str, RecordError> {
+    init.as_str()
+        .ok_or_else(|| RecordError::type_mismatch("string", init))
+}
+
+fn list(init: &Init) -> Result<&[Init], RecordError> {
+    init.as_list()
+        .ok_or_else(|| RecordError::type_mismatch("list", init))
+}
+
+fn dag(init: &Init) -> Result<&DagInit, RecordError> {
+    init.as_dag()
+        .ok_or_else(|| RecordError::type_mismatch("dag", init))
+}
+
+/// Bitwise and logical operators keep the bit type, when all the operands are bits.
+fn int_or_bit(value: i64, operands: &[Init]) -> Init {
+    if operands.iter().all(|operand| matches!(operand, Init::Bit(_))) {
+        Init::Bit(value != 0)
+    } else {
+        Init::Int(value)
+    }
+}
+
+/// Compares the values, records are compared by their names.
+fn equals(lhs: &Init, rhs: &Init) -> bool {
+    match (lhs, rhs) {
+        (Init::Def(lhs), Init::Def(rhs)) => lhs.name == rhs.name,
+        (Init::String(lhs) | Init::Code(lhs), Init::String(rhs) | Init::Code(rhs)) => lhs == rhs,
+        (Init::List(lhs), Init::List(rhs)) => {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| equals(lhs, rhs))
+        }
+        (lhs, rhs) => match (lhs.as_int(), rhs.as_int()) {
+            (Some(lhs), Some(rhs)) => lhs == rhs,
+            _ => lhs == rhs,
+        },
+    }
+}
+
+fn arity(operator: &str, args: &[Value], range: std::ops::RangeInclusive<usize>) -> Result<(), RecordError> {
+    if range.contains(&args.len()) {
+        Ok(())
+    } else {
+        Err(RecordError::Evaluation(format!(
+            "!{operator} expects {range:?} operands, got {}",
+            args.len()
+        )))
+    }
+}
+
+impl Eval<'_> {
+    /// Checks the value type, the classes are matched against the record superclasses.
+    pub(crate) fn isa(&self, init: &Init, r#type: &Type) -> bool {
+        match (self.keeper.resolve_type(r#type), init) {
+            (Type::Class(class), Init::Def(def)) => def.is_subclass_of(&class),
+            (Type::String, Init::String(_)) | (Type::Code, Init::Code(_) | Init::String(_)) => true,
+            (Type::Int, Init::Int(_)) | (Type::Bit, Init::Bit(_)) | (Type::Dag, Init::Dag(_)) => true,
+            (Type::Bits(width), Init::Bits(bits)) => bits.len() as i64 == width,
+            (Type::List(element), Init::List(values)) => {
+                values.iter().all(|value| self.isa(value, &element))
+            }
+            _ => false,
+        }
+    }
+
+    fn cast(&self, init: Init, r#type: Option<&Type>) -> Result<Init, RecordError> {
+        let Some(r#type) = r#type else {
+            return Ok(init);
+        };
+        Ok(match (self.keeper.resolve_type(r#type), init) {
+            (Type::String, Init::Def(def)) => Init::String(def.name.clone()),
+            (Type::String, Init::Int(value)) => Init::String(value.to_string()),
+            (Type::Class(class), Init::String(name)) => {
+                let def = self
+                    .keeper
+                    .def(&name)
+                    .ok_or_else(|| RecordError::Undefined(name.clone()))?;
+                if !def.is_subclass_of(&class) {
+                    return Err(RecordError::Evaluation(format!(
+                        "record {name:?} is not a subclass of {class:?}"
+                    )));
+                }
+                Init::Def(def.clone())
+            }
+            (r#type, init) => init.convert(&r#type),
+        })
+    }
+
+    /// Evaluates the body with the bound loop variables.
+    fn with_vars(
+        &self,
+        scope: &Rc<Scope>,
+        vars: &[(&Value, Init)],
+        body: &Value,
+    ) -> Result<Init, RecordError> {
+        let scope = Scope::child(scope);
+        for (var, init) in vars {
+            let Value::Identifier(name) = var else {
+                return Err(RecordError::Evaluation(format!(
+                    "expected a variable name, got {var:?}"
+                )));
+            };
+            scope.bind(name, Binding::Init(init.clone()));
+        }
+        self.value(body, &scope)
+    }
+
+    /// Evaluates `!operator<type>(args)`.
+    pub(crate) fn bang(
+        &self,
+        operator: &str,
+        r#type: Option<&Type>,
+        args: &[Value],
+        scope: &Rc<Scope>,
+    ) -> Result<Init, RecordError> {
+        // Lazily evaluated operators.
+        match operator {
+            "if" => {
+                arity(operator, args, 3..=3)?;
+                let branch = if self.condition(&args[0], scope)? { &args[1] } else { &args[2] };
+                return self.value(branch, scope);
+            }
+            "foldl" => {
+                arity(operator, args, 5..=5)?;
+                let mut acc = self.value(&args[0], scope)?;
+                for element in list(&self.value(&args[1], scope)?)? {
+                    acc = self.with_vars(
+                        scope,
+                        &[(&args[2], acc), (&args[3], element.clone())],
+                        &args[4],
+                    )?;
+                }
+                return Ok(acc);
+            }
+            "foreach" => {
+                arity(operator, args, 3..=3)?;
+                return match self.value(&args[1], scope)? {
+                    Init::List(values) => Ok(Init::List(
+                        values
+                            .into_iter()
+                            .map(|value| self.with_vars(scope, &[(&args[0], value)], &args[2]))
+                            .collect::<Result<_, _>>()?,
+                    )),
+                    Init::Dag(mut dag) => {
+                        for (value, _) in dag.args.iter_mut() {
+                            *value =
+                                self.with_vars(scope, &[(&args[0], value.clone())], &args[2])?;
+                        }
+                        Ok(Init::Dag(dag))
+                    }
+                    init => Err(RecordError::type_mismatch("list", &init)),
+                };
+            }
+            "filter" => {
+                arity(operator, args, 3..=3)?;
+                let mut filtered = vec![];
+                for value in list(&self.value(&args[1], scope)?)? {
+                    let keep = self.with_vars(scope, &[(&args[0], value.clone())], &args[2])?;
+                    if int(&keep)? != 0 {
+                        filtered.push(value.clone());
+                    }
+                }
+                return Ok(Init::List(filtered));
+            }
+            "exists" => {
+                arity(operator, args, 1..=1)?;
+                let name = self.value(&args[0], scope)?;
+                let exists = self.keeper.def(string(&name)?).is_some_and(|def| {
+                    r#type.is_none_or(|r#type| self.isa(&Init::Def(def.clone()), r#type))
+                });
+                return Ok(Init::Bit(exists));
+            }
+            _ => {}
+        }
+
+        let operands = args
+            .iter()
+            .map(|arg| self.value(arg, scope))
+            .collect::<Result<Vec<_>, _>>()?;
+        let unary = || arity(operator, args, 1..=1);
+        let binary = || arity(operator, args, 2..=2);
+
+        Ok(match operator {
+            "add" | "mul" | "and" | "or" | "xor" => {
+                let mut acc = int(operands.first().unwrap_or(&Init::Int(0)))?;
+                for operand in operands.iter().skip(1) {
+                    let value = int(operand)?;
+                    acc = match operator {
+                        "add" => acc.wrapping_add(value),
+                        "mul" => acc.wrapping_mul(value),
+                        "and" => acc & value,
+                        "or" => acc | value,
+                        _ => acc ^ value,
+                    };
+                }
+                int_or_bit(acc, &operands)
+            }
+            "sub" | "div" | "shl" | "sra" | "srl" => {
+                binary()?;
+                let (lhs, rhs) = (int(&operands[0])?, int(&operands[1])?);
+                Init::Int(match operator {
+                    "sub" => lhs.wrapping_sub(rhs),
+                    "div" if rhs == 0 => {
+                        return Err(RecordError::Evaluation("division by zero".to_string()))
+                    }
+                    "div" => lhs / rhs,
+                    "shl" => lhs << rhs,
+                    "sra" => lhs >> rhs,
+                    _ => ((lhs as u64) >> rhs) as i64,
+                })
+            }
+            "not" => {
+                unary()?;
+                Init::Bit(int(&operands[0])? == 0)
+            }
+            "logtwo" => {
+                unary()?;
+                let value = int(&operands[0])?;
+                if value <= 0 {
+                    return Err(RecordError::Evaluation(format!("!logtwo of {value}")));
+                }
+                Init::Int(63 - value.leading_zeros() as i64)
+            }
+            "eq" | "ne" => {
+                binary()?;
+                Init::Bit(equals(&operands[0], &operands[1]) == (operator == "eq"))
+            }
+            "lt" | "le" | "gt" | "ge" => {
+                binary()?;
+                let ordering = match (&operands[0], &operands[1]) {
+                    (Init::String(lhs), Init::String(rhs)) => lhs.cmp(rhs),
+                    (lhs, rhs) => int(lhs)?.cmp(&int(rhs)?),
+                };
+                Init::Bit(match operator {
+                    "lt" => ordering.is_lt(),
+                    "le" => ordering.is_le(),
+                    "gt" => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                })
+            }
+            "strconcat" => Init::String(
+                operands
+                    .iter()
+                    .map(|operand| string(operand).map(str::to_string))
+                    .collect::<Result<String, _>>()?,
+            ),
+            "listconcat" => Init::List(
+                operands
+                    .iter()
+                    .map(|operand| list(operand).map(<[Init]>::to_vec))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat(),
+            ),
+            "listsplat" => {
+                binary()?;
+                Init::List(vec![operands[0].clone(); int(&operands[1])?.max(0) as usize])
+            }
+            "listremove" => {
+                binary()?;
+                let remove = list(&operands[1])?;
+                Init::List(
+                    list(&operands[0])?
+                        .iter()
+                        .filter(|value| !remove.iter().any(|removed| equals(value, removed)))
+                        .cloned()
+                        .collect(),
+                )
+            }
+            "interleave" => {
+                binary()?;
+                let separator = string(&operands[1])?;
+                let values = list(&operands[0])?
+                    .iter()
+                    .map(Init::to_name_string)
+                    .collect::<Vec<_>>();
+                Init::String(values.join(separator))
+            }
+            "size" => {
+                unary()?;
+                Init::Int(match &operands[0] {
+                    Init::List(values) => values.len(),
+                    Init::Dag(dag) => dag.args.len(),
+                    init => string(init)?.len(),
+                } as i64)
+            }
+            "empty" => {
+                unary()?;
+                Init::Bit(match &operands[0] {
+                    Init::List(values) => values.is_empty(),
+                    Init::Dag(dag) => dag.args.is_empty(),
+                    init => string(init)?.is_empty(),
+                })
+            }
+            "head" => {
+                unary()?;
+                list(&operands[0])?
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| RecordError::Evaluation("!head of an empty list".to_string()))?
+            }
+            "tail" => {
+                unary()?;
+                let values = list(&operands[0])?;
+                if values.is_empty() {
+                    return Err(RecordError::Evaluation("!tail of an empty list".to_string()));
+                }
+                Init::List(values[1..].to_vec())
+            }
+            "range" => {
+                arity(operator, args, 1..=3)?;
+                let (from, to, step) = match operands.as_slice() {
+                    [Init::List(values)] => (0, values.len() as i64, 1),
+                    [to] => (0, int(to)?, 1),
+                    [from, to] => (int(from)?, int(to)?, 1),
+                    [from, to, step] => (int(from)?, int(to)?, int(step)?),
+                    _ => unreachable!("arity is checked"),
+                };
+                if step == 0 {
+                    return Err(RecordError::Evaluation("!range with zero step".to_string()));
+                }
+                let mut values = vec![];
+                let mut value = from;
+                while (step > 0 && value < to) || (step < 0 && value > to) {
+                    values.push(Init::Int(value));
+                    value += step;
+                }
+                Init::List(values)
+            }
+            "cast" => {
+                unary()?;
+                self.cast(operands[0].clone(), r#type)?
+            }
+            "isa" => {
+                unary()?;
+                let r#type = r#type.ok_or_else(|| {
+                    RecordError::Evaluation("!isa expects a type".to_string())
+                })?;
+                Init::Bit(self.isa(&operands[0], r#type))
+            }
+            "initialized" => {
+                unary()?;
+                Init::Bit(!operands[0].is_unset())
+            }
+            "subst" => {
+                arity(operator, args, 3..=3)?;
+                match (&operands[0], &operands[1], &operands[2]) {
+                    (Init::String(from), Init::String(to), Init::String(value)) => {
+                        Init::String(value.replace(from.as_str(), to))
+                    }
+                    (from, to, value) if equals(from, value) => to.clone(),
+                    (_, _, value) => value.clone(),
+                }
+            }
+            "tolower" => {
+                unary()?;
+                Init::String(string(&operands[0])?.to_lowercase())
+            }
+            "toupper" => {
+                unary()?;
+                Init::String(string(&operands[0])?.to_uppercase())
+            }
+            "find" => {
+                arity(operator, args, 2..=3)?;
+                let start = operands.get(2).map(int).transpose()?.unwrap_or(0).max(0) as usize;
+                let haystack = string(&operands[0])?;
+                let found = haystack
+                    .get(start..)
+                    .and_then(|rest| rest.find(string(&operands[1]).ok()?))
+                    .map_or(-1, |index| (index + start) as i64);
+                Init::Int(found)
+            }
+            "substr" => {
+                arity(operator, args, 2..=3)?;
+                let value = string(&operands[0])?;
+                let start = (int(&operands[1])?.max(0) as usize).min(value.len());
+                let len = operands
+                    .get(2)
+                    .map(int)
+                    .transpose()?
+                    .map_or(value.len(), |len| len.max(0) as usize);
+                Init::String(value[start..(start + len).min(value.len())].to_string())
+            }
+            "repr" => {
+                unary()?;
+                Init::String(operands[0].to_string())
+            }
+            "dag" => {
+                arity(operator, args, 3..=3)?;
+                let values = list(&operands[1])?;
+                let names = match &operands[2] {
+                    Init::Unset => vec![Init::Unset; values.len()],
+                    names => list(names)?.to_vec(),
+                };
+                Init::Dag(DagInit {
+                    operator: Box::new(operands[0].clone()),
+                    operator_name: None,
+                    args: values
+                        .iter()
+                        .cloned()
+                        .zip(names.iter().map(|name| name.as_str().map(str::to_string)))
+                        .collect(),
+                })
+            }
+            "con" => {
+                let mut operands = operands.into_iter();
+                let mut result = match operands.next() {
+                    Some(init) => dag(&init)?.clone(),
+                    None => return Err(RecordError::Evaluation("!con without operands".to_string())),
+                };
+                for operand in operands {
+                    let operand = dag(&operand)?;
+                    if !equals(&result.operator, &operand.operator) {
+                        return Err(RecordError::Evaluation(format!(
+                            "!con operators mismatch: {} and {}",
+                            result.operator, operand.operator
+                        )));
+                    }
+                    result.args.extend(operand.args.iter().cloned());
+                }
+                Init::Dag(result)
+            }
+            "getdagop" => {
+                unary()?;
+                self.cast(*dag(&operands[0])?.operator.clone(), r#type)?
+            }
+            "setdagop" => {
+                binary()?;
+                let mut result = dag(&operands[0])?.clone();
+                result.operator = Box::new(operands[1].clone());
+                Init::Dag(result)
+            }
+            "getdagarg" | "getdagname" => {
+                binary()?;
+                let result = dag(&operands[0])?;
+                let index = match &operands[1] {
+                    Init::String(name) => result
+                        .args
+                        .iter()
+                        .position(|(_, arg_name)| arg_name.as_ref() == Some(name)),
+                    index => Some(int(index)? as usize),
+                };
+                let arg = index.and_then(|index| result.args.get(index));
+                match (operator, arg) {
+                    ("getdagarg", Some((init, _))) => self.cast(init.clone(), r#type)?,
+                    ("getdagname", Some((_, Some(name)))) => Init::String(name.clone()),
+                    _ => Init::Unset,
+                }
+            }
+            operator => return Err(RecordError::UnsupportedOperator(operator.to_string())),
+        })
+    }
+}
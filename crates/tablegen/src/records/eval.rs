@@ -0,0 +1,614 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Tablegen values evaluation and class instantiation.
+//!
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::grammar::expressions::class_def::body::Body;
+use crate::grammar::expressions::class_def::template_arg::ClassTemplateArg;
+use crate::grammar::expressions::class_def::ParentClassDefinition;
+use crate::grammar::expressions::values::{Argument, Slice, Value};
+use crate::grammar::tokens::type_name::Type;
+use crate::grammar::tokens::Ranges;
+use crate::records::init::{DagInit, Init};
+use crate::records::{Record, RecordError, RecordKeeper};
+
+/// Variable binding of the [Scope].
+#[derive(Debug, Clone)]
+pub(crate) enum Binding {
+    Init(Init),
+    /// Class body `defvar`, evaluated on each reference within the record.
+    Lazy(Value, Rc<Scope>),
+}
+
+/// Lexical scope of the template arguments, `defvar` variables and `foreach` iterators.
+#[derive(Debug, Default)]
+pub(crate) struct Scope {
+    parent: Option<Rc<Scope>>,
+    vars: RefCell<HashMap<String, Binding>>,
+}
+
+impl Scope {
+    pub(crate) fn child(parent: &Rc<Scope>) -> Rc<Scope> {
+        Rc::new(Scope {
+            parent: Some(parent.clone()),
+            vars: RefCell::default(),
+        })
+    }
+
+    pub(crate) fn bind(&self, name: &str, binding: Binding) {
+        self.vars.borrow_mut().insert(name.to_string(), binding);
+    }
+
+    pub(crate) fn lookup(&self, name: &str) -> Option<Binding> {
+        match self.vars.borrow().get(name) {
+            Some(binding) => Some(binding.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.lookup(name)),
+        }
+    }
+}
+
+/// Record field, evaluated at the record finalization, so the fields are late bound.
+#[derive(Debug)]
+struct Field {
+    name: String,
+    r#type: Type,
+    value: Option<(Value, Rc<Scope>)>,
+    bit_lets: Vec<(Ranges, Value, Rc<Scope>)>,
+}
+
+/// Record under construction.
+#[derive(Debug)]
+pub(crate) struct RecordState {
+    name: String,
+    anonymous: bool,
+    superclasses: Vec<String>,
+    fields: Vec<Field>,
+    asserts: Vec<(Value, Value, Rc<Scope>)>,
+    resolved: RefCell<HashMap<String, Init>>,
+    resolving: RefCell<HashSet<String>>,
+}
+
+impl RecordState {
+    pub(crate) fn new(name: String, anonymous: bool) -> Self {
+        Self {
+            name,
+            anonymous,
+            superclasses: vec![],
+            fields: vec![],
+            asserts: vec![],
+            resolved: RefCell::default(),
+            resolving: RefCell::default(),
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> Result<&mut Field, RecordError> {
+        let record = self.name.clone();
+        self.fields
+            .iter_mut()
+            .find(|field| field.name == name)
+            .ok_or_else(|| RecordError::UnknownField {
+                record,
+                field: name.to_string(),
+            })
+    }
+
+    /// Applies `let name{ranges} = value` override.
+    pub(crate) fn set_field(
+        &mut self,
+        name: &str,
+        ranges: &Ranges,
+        value: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RecordError> {
+        let field = self.field_mut(name)?;
+        if ranges.is_empty() {
+            field.value = Some((value.clone(), scope.clone()));
+            field.bit_lets.clear();
+        } else {
+            field.bit_lets.push((ranges.clone(), value.clone(), scope.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Bit indices of the `{ranges}` in the assignment order, starting from the least significant bit.
+fn bit_indices(ranges: &Ranges) -> Vec<usize> {
+    let mut indices = ranges
+        .iter()
+        .flat_map(|range| {
+            let indices: Vec<i64> = if range.start <= range.end {
+                (range.start..=range.end).collect()
+            } else {
+                (range.end..=range.start).rev().collect()
+            };
+            indices
+        })
+        .map(|index| index as usize)
+        .collect::<Vec<_>>();
+    indices.reverse();
+    indices
+}
+
+/// Evaluation context, optionally bound to the record under finalization.
+pub(crate) struct Eval<'a> {
+    pub(crate) keeper: &'a RecordKeeper,
+    pub(crate) record: Option<&'a RecordState>,
+    pub(crate) filename: &'a str,
+}
+
+impl<'a> Eval<'a> {
+    pub(crate) fn new(keeper: &'a RecordKeeper, filename: &'a str) -> Self {
+        Self {
+            keeper,
+            record: None,
+            filename,
+        }
+    }
+
+    /// Evaluates the value in the given scope.
+    pub(crate) fn value(&self, value: &Value, scope: &Rc<Scope>) -> Result<Init, RecordError> {
+        Ok(match value {
+            Value::Uninitialized => Init::Unset,
+            Value::Bool(value) => Init::Bit(*value),
+            Value::Int(value) => Init::Int(*value),
+            Value::String(value) => Init::String(value.clone()),
+            Value::Code(value) => Init::Code(value.clone()),
+            Value::Bits(values) => {
+                let mut bits = vec![];
+                for value in values.iter().rev() {
+                    let init = self.value(value, scope)?;
+                    match init {
+                        Init::Int(value @ (0 | 1)) => bits.push(Init::Bit(value == 1)),
+                        Init::Bits(inner) => bits.extend(inner),
+                        init @ (Init::Bit(_) | Init::Unset) => bits.push(init),
+                        init => return Err(RecordError::type_mismatch("bit", &init)),
+                    }
+                }
+                Init::Bits(bits)
+            }
+            Value::List(values, r#type) => {
+                let values = values
+                    .iter()
+                    .map(|value| self.value(value, scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let list = Init::List(values);
+                match r#type {
+                    Some(r#type) => list.convert(&Type::List(Box::new(self.keeper.resolve_type(r#type)))),
+                    None => list,
+                }
+            }
+            Value::Dag(operator, operator_name, args) => Init::Dag(DagInit {
+                operator: Box::new(self.value(operator, scope)?),
+                operator_name: operator_name.clone(),
+                args: args
+                    .iter()
+                    .map(|arg| {
+                        let init = match &arg.value {
+                            Some(value) => self.value(value, scope)?,
+                            None => Init::Unset,
+                        };
+                        Ok((init, arg.name.clone()))
+                    })
+                    .collect::<Result<Vec<_>, RecordError>>()?,
+            }),
+            Value::Identifier(name) => self.identifier(name, scope)?,
+            Value::ClassRef(class, args) => Init::Def(self.instantiate(class, args, scope)?),
+            Value::Bang(operator, r#type, args) => self.bang(operator, r#type.as_ref(), args, scope)?,
+            Value::Cond(cases) => {
+                for (condition, value) in cases {
+                    if self.condition(condition, scope)? {
+                        return self.value(value, scope);
+                    }
+                }
+                return Err(RecordError::Evaluation(
+                    "!cond has no true condition".to_string(),
+                ));
+            }
+            Value::Field(value, field) => {
+                let init = self.value(value, scope)?;
+                let def = init
+                    .as_def()
+                    .ok_or_else(|| RecordError::type_mismatch("record", &init))?;
+                def.value(field)
+                    .cloned()
+                    .ok_or_else(|| RecordError::UnknownField {
+                        record: def.name.clone(),
+                        field: field.clone(),
+                    })?
+            }
+            Value::ListSlice(value, slices) => {
+                let init = self.value(value, scope)?;
+                let list = init
+                    .as_list()
+                    .ok_or_else(|| RecordError::type_mismatch("list", &init))?;
+                let indices = self.slice_indices(slices, scope)?;
+                let element = |index: i64| {
+                    list.get(index as usize).cloned().ok_or_else(|| {
+                        RecordError::Evaluation(format!("list index {index} is out of range"))
+                    })
+                };
+                match (slices.as_slice(), indices.as_slice()) {
+                    ([Slice::Single(_)], [index]) => element(*index)?,
+                    _ => Init::List(indices.into_iter().map(element).collect::<Result<_, _>>()?),
+                }
+            }
+            Value::BitSlice(value, slices) => {
+                let init = self.value(value, scope)?;
+                let bits = init
+                    .to_bits()
+                    .ok_or_else(|| RecordError::type_mismatch("bits", &init))?;
+                let mut indices = self.slice_indices(slices, scope)?;
+                indices.reverse();
+                Init::Bits(
+                    indices
+                        .into_iter()
+                        .map(|index| bits.get(index as usize).cloned().unwrap_or(Init::Bit(false)))
+                        .collect(),
+                )
+            }
+            Value::Paste(lhs, rhs) => {
+                match (self.value(lhs, scope)?, self.value(rhs, scope)?) {
+                    (Init::List(mut lhs), Init::List(rhs)) => {
+                        lhs.extend(rhs);
+                        Init::List(lhs)
+                    }
+                    (lhs, rhs) => Init::String(lhs.to_name_string() + &rhs.to_name_string()),
+                }
+            }
+        })
+    }
+
+    /// Evaluates the value as a boolean condition.
+    pub(crate) fn condition(&self, value: &Value, scope: &Rc<Scope>) -> Result<bool, RecordError> {
+        let init = self.value(value, scope)?;
+        init.as_bool()
+            .ok_or_else(|| RecordError::type_mismatch("bit", &init))
+    }
+
+    fn slice_indices(&self, slices: &[Slice], scope: &Rc<Scope>) -> Result<Vec<i64>, RecordError> {
+        let int = |value: &Value| {
+            let init = self.value(value, scope)?;
+            init.as_int()
+                .ok_or_else(|| RecordError::type_mismatch("int", &init))
+        };
+
+        let mut indices = vec![];
+        for slice in slices {
+            match slice {
+                Slice::Single(value) => match self.value(value, scope)? {
+                    Init::List(values) => {
+                        for value in values {
+                            indices.push(
+                                value
+                                    .as_int()
+                                    .ok_or_else(|| RecordError::type_mismatch("int", &value))?,
+                            );
+                        }
+                    }
+                    init => indices.push(
+                        init.as_int()
+                            .ok_or_else(|| RecordError::type_mismatch("int", &init))?,
+                    ),
+                },
+                Slice::Range(from, to) => {
+                    let (from, to) = (int(from)?, int(to)?);
+                    if from <= to {
+                        indices.extend(from..=to);
+                    } else {
+                        indices.extend((to..=from).rev());
+                    }
+                }
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Resolves the identifier: scope variables first, then the record fields and the defined records.
+    fn identifier(&self, name: &str, scope: &Rc<Scope>) -> Result<Init, RecordError> {
+        if let Some(binding) = scope.lookup(name) {
+            return match binding {
+                Binding::Init(init) => Ok(init),
+                Binding::Lazy(value, scope) => self.value(&value, &scope),
+            };
+        }
+
+        if let Some(record) = self.record {
+            if name == "NAME" {
+                return Ok(Init::String(record.name.clone()));
+            }
+            if let Some(init) = self.field(record, name)? {
+                return Ok(init);
+            }
+        }
+
+        self.keeper
+            .def(name)
+            .map(|def| Init::Def(def.clone()))
+            .ok_or_else(|| RecordError::Undefined(name.to_string()))
+    }
+
+    /// Evaluates the record name, undefined identifiers are taken literally.
+    pub(crate) fn name(&self, value: &Value, scope: &Rc<Scope>) -> Result<String, RecordError> {
+        Ok(match value {
+            Value::Identifier(name) => match scope.lookup(name) {
+                Some(Binding::Init(init)) => init.to_name_string(),
+                Some(Binding::Lazy(value, scope)) => self.value(&value, &scope)?.to_name_string(),
+                None => name.clone(),
+            },
+            Value::Paste(lhs, rhs) => self.name(lhs, scope)? + &self.name(rhs, scope)?,
+            value => self.value(value, scope)?.to_name_string(),
+        })
+    }
+
+    /// Evaluates the record field, resolving the field references recursively.
+    fn field(&self, record: &RecordState, name: &str) -> Result<Option<Init>, RecordError> {
+        if let Some(init) = record.resolved.borrow().get(name) {
+            return Ok(Some(init.clone()));
+        }
+        let Some(field) = record.fields.iter().find(|field| field.name == name) else {
+            return Ok(None);
+        };
+        if !record.resolving.borrow_mut().insert(name.to_string()) {
+            return Err(RecordError::CircularReference {
+                record: record.name.clone(),
+                field: name.to_string(),
+            });
+        }
+
+        let r#type = self.keeper.resolve_type(&field.r#type);
+        let mut init = match &field.value {
+            Some((value, scope)) => self.value(value, scope)?.convert(&r#type),
+            None => Init::Unset,
+        };
+
+        for (ranges, value, scope) in &field.bit_lets {
+            let mut bits = match init.to_bits() {
+                Some(bits) => bits,
+                None => return Err(RecordError::type_mismatch("bits", &init)),
+            };
+            let indices = bit_indices(ranges);
+            let assigned = self.value(value, scope)?;
+            let value = assigned
+                .clone()
+                .convert(&Type::Bits(indices.len() as i64))
+                .to_bits()
+                .ok_or_else(|| RecordError::type_mismatch("bits", &assigned))?;
+            for (index, bit) in indices.into_iter().zip(value) {
+                if index >= bits.len() {
+                    bits.resize(index + 1, Init::Unset);
+                }
+                bits[index] = bit;
+            }
+            init = Init::Bits(bits);
+        }
+
+        record.resolving.borrow_mut().remove(name);
+        record.resolved.borrow_mut().insert(name.to_string(), init.clone());
+        Ok(Some(init))
+    }
+
+    /// Binds the template arguments, the defaults may refer to the preceding arguments.
+    pub(crate) fn bind_template_args(
+        &self,
+        owner: &str,
+        template_args: &[ClassTemplateArg],
+        args: &[Argument],
+        caller_scope: &Rc<Scope>,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RecordError> {
+        let mut bound = vec![None; template_args.len()];
+        let mut positional = 0;
+        for arg in args {
+            let (index, value) = match arg {
+                Argument::Positional(value) => {
+                    positional += 1;
+                    (positional - 1, value)
+                }
+                Argument::Named(name, value) => {
+                    let index = template_args
+                        .iter()
+                        .position(|template_arg| &template_arg.identifier == name)
+                        .ok_or_else(|| RecordError::UnknownTemplateArg {
+                            owner: owner.to_string(),
+                            arg: name.clone(),
+                        })?;
+                    (index, value)
+                }
+            };
+            if index >= template_args.len() {
+                return Err(RecordError::TooManyTemplateArgs(owner.to_string()));
+            }
+            bound[index] = Some(self.value(value, caller_scope)?);
+        }
+
+        for (template_arg, init) in template_args.iter().zip(bound) {
+            let init = match init {
+                Some(init) => init,
+                None => self.value(&template_arg.value, scope)?,
+            };
+            let r#type = self.keeper.resolve_type(&template_arg.typ);
+            scope.bind(&template_arg.identifier, Binding::Init(init.convert(&r#type)));
+        }
+        Ok(())
+    }
+
+    /// Adds the class fields and its superclasses to the record.
+    pub(crate) fn add_class(
+        &self,
+        state: &mut RecordState,
+        parent: &ParentClassDefinition,
+        caller_scope: &Rc<Scope>,
+    ) -> Result<(), RecordError> {
+        let class = self
+            .keeper
+            .classes
+            .get(&parent.name)
+            .cloned()
+            .ok_or_else(|| RecordError::UnknownClass(parent.name.clone()))?;
+
+        let scope = Scope::child(&class.scope);
+        self.bind_template_args(
+            &parent.name,
+            &class.definition.template_arguments,
+            &parent.arguments,
+            caller_scope,
+            &scope,
+        )?;
+
+        for superclass in &class.definition.parents {
+            self.add_class(state, superclass, &scope)?;
+        }
+        if !state.superclasses.contains(&parent.name) {
+            state.superclasses.push(parent.name.clone());
+        }
+
+        self.apply_body(state, &class.definition.body, &scope)
+    }
+
+    /// Applies the class or record body items to the record.
+    pub(crate) fn apply_body(
+        &self,
+        state: &mut RecordState,
+        body: &[Body],
+        scope: &Rc<Scope>,
+    ) -> Result<(), RecordError> {
+        for item in body {
+            match item {
+                Body::Field {
+                    name,
+                    r#type,
+                    value,
+                } => {
+                    let value = match value {
+                        Value::Uninitialized => None,
+                        value => Some((value.clone(), scope.clone())),
+                    };
+                    match state.fields.iter_mut().find(|field| &field.name == name) {
+                        Some(field) => {
+                            field.r#type = r#type.clone();
+                            field.value = value;
+                            field.bit_lets.clear();
+                        }
+                        None => state.fields.push(Field {
+                            name: name.clone(),
+                            r#type: r#type.clone(),
+                            value,
+                            bit_lets: vec![],
+                        }),
+                    }
+                }
+                Body::LetField {
+                    name,
+                    ranges,
+                    value,
+                } => state.set_field(name, ranges, value, scope)?,
+                Body::DefVar { name, value } => {
+                    scope.bind(name, Binding::Lazy(value.clone(), scope.clone()))
+                }
+                Body::Assert { condition, message } => {
+                    state
+                        .asserts
+                        .push((condition.clone(), message.clone(), scope.clone()))
+                }
+                Body::Dump { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates all the fields and checks the assertions of the record.
+    pub(crate) fn finalize(&self, state: RecordState) -> Result<Record, RecordError> {
+        let eval = Eval {
+            keeper: self.keeper,
+            record: Some(&state),
+            filename: self.filename,
+        };
+
+        let mut fields = Vec::with_capacity(state.fields.len());
+        for field in &state.fields {
+            let init = eval.field(&state, &field.name)?.unwrap_or(Init::Unset);
+            fields.push((field.name.clone(), init));
+        }
+
+        for (condition, message, scope) in &state.asserts {
+            if !eval.condition(condition, scope)? {
+                let message = eval.value(message, scope)?.to_name_string();
+                return Err(RecordError::Assertion {
+                    record: state.name.clone(),
+                    message,
+                });
+            }
+        }
+
+        Ok(Record {
+            name: state.name.clone(),
+            anonymous: state.anonymous,
+            filename: self.filename.to_string(),
+            superclasses: state.superclasses.clone(),
+            fields,
+        })
+    }
+
+    /// Instantiates an anonymous record of `Class<args>`.
+    pub(crate) fn instantiate(
+        &self,
+        class: &str,
+        args: &[Argument],
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Record>, RecordError> {
+        let mut state = RecordState::new(self.keeper.anonymous_name(), true);
+        let parent = ParentClassDefinition {
+            name: class.to_string(),
+            arguments: args.to_vec(),
+        };
+        self.add_class(&mut state, &parent, scope)?;
+        Ok(Rc::new(self.finalize(state)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::tokens::Range;
+
+    #[test]
+    fn should_order_bit_indices() {
+        assert_eq!(bit_indices(&vec![Range { start: 3, end: 0 }]), vec![0, 1, 2, 3]);
+        assert_eq!(bit_indices(&vec![Range { start: 0, end: 1 }]), vec![1, 0]);
+        assert_eq!(
+            bit_indices(&vec![Range { start: 5, end: 5 }, Range { start: 1, end: 0 }]),
+            vec![0, 1, 5]
+        );
+    }
+
+    #[test]
+    fn should_lookup_scopes() {
+        let root = Rc::new(Scope::default());
+        root.bind("a", Binding::Init(Init::Int(1)));
+        let child = Scope::child(&root);
+        child.bind("b", Binding::Init(Init::Int(2)));
+
+        assert!(matches!(child.lookup("a"), Some(Binding::Init(Init::Int(1)))));
+        assert!(matches!(child.lookup("b"), Some(Binding::Init(Init::Int(2)))));
+        assert!(root.lookup("b").is_none());
+    }
+}
@@ -0,0 +1,228 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Tablegen evaluated values, mirroring the LLVM `Init` hierarchy.
+//!
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::grammar::tokens::type_name::Type;
+use crate::records::Record;
+
+/// Fully evaluated Tablegen value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Init {
+    /// `?`
+    Unset,
+    Bit(bool),
+    Int(i64),
+    String(String),
+    Code(String),
+    /// Bits are stored starting from the least significant one.
+    Bits(Vec<Init>),
+    List(Vec<Init>),
+    Dag(DagInit),
+    Def(Rc<Record>),
+}
+
+/// `(operator:$name arg:$name, ...)` evaluated dag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DagInit {
+    pub operator: Box<Init>,
+    pub operator_name: Option<String>,
+    pub args: Vec<(Init, Option<String>)>,
+}
+
+impl DagInit {
+    /// Operator record name, like `ins` or `outs`.
+    pub fn operator_name(&self) -> Option<&str> {
+        self.operator.as_def().map(|def| def.name.as_str())
+    }
+
+    /// Argument by its `$name`.
+    pub fn arg(&self, name: &str) -> Option<&Init> {
+        self.args
+            .iter()
+            .find(|(_, arg_name)| arg_name.as_deref() == Some(name))
+            .map(|(init, _)| init)
+    }
+}
+
+impl Init {
+    pub fn is_unset(&self) -> bool {
+        matches!(self, Init::Unset)
+    }
+
+    /// Integer value of the ints, bits and fully initialized bits.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Init::Int(value) => Some(*value),
+            Init::Bit(value) => Some(*value as i64),
+            Init::Bits(bits) => bits.iter().enumerate().try_fold(0i64, |acc, (index, bit)| {
+                bit.as_bool().map(|bit| acc | ((bit as i64) << index))
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_int().map(|value| value != 0)
+    }
+
+    /// String contents of the strings and code blocks.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Init::String(value) | Init::Code(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Init]> {
+        match self {
+            Init::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_dag(&self) -> Option<&DagInit> {
+        match self {
+            Init::Dag(dag) => Some(dag),
+            _ => None,
+        }
+    }
+
+    pub fn as_def(&self) -> Option<&Rc<Record>> {
+        match self {
+            Init::Def(def) => Some(def),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to the declared field type, like the ints to the bits.
+    pub(crate) fn convert(self, r#type: &Type) -> Init {
+        match (r#type, self) {
+            (_, Init::Unset) => Init::Unset,
+            (Type::Bit, Init::Bits(bits)) if bits.len() == 1 => bits.into_iter().next().unwrap(),
+            (Type::Bit, Init::Int(value @ (0 | 1))) => Init::Bit(value == 1),
+            (Type::Int, init @ (Init::Bit(_) | Init::Bits(_))) => match init.as_int() {
+                Some(value) => Init::Int(value),
+                None => init,
+            },
+            (Type::Bits(width), Init::Int(value)) => {
+                Init::Bits((0..*width).map(|index| Init::Bit(value >> index & 1 == 1)).collect())
+            }
+            (Type::Bits(1), Init::Bit(value)) => Init::Bits(vec![Init::Bit(value)]),
+            (Type::List(element), Init::List(values)) => {
+                Init::List(values.into_iter().map(|value| value.convert(element)).collect())
+            }
+            (_, init) => init,
+        }
+    }
+
+    /// Bits of the value, starting from the least significant one.
+    pub(crate) fn to_bits(&self) -> Option<Vec<Init>> {
+        match self {
+            Init::Bit(_) | Init::Unset => Some(vec![self.clone()]),
+            Init::Bits(bits) => Some(bits.clone()),
+            Init::Int(value) => {
+                let width = (64 - value.leading_zeros()).max(1);
+                Some((0..width).map(|index| Init::Bit(value >> index & 1 == 1)).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Value as a part of the pasted name, strings are not quoted.
+    pub fn to_name_string(&self) -> String {
+        match self {
+            Init::String(value) | Init::Code(value) => value.clone(),
+            Init::Def(def) => def.name.clone(),
+            init => init.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Init {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Init::Unset => write!(f, "?"),
+            Init::Bit(value) => write!(f, "{}", *value as u8),
+            Init::Int(value) => write!(f, "{value}"),
+            Init::String(value) => write!(f, "{value:?}"),
+            Init::Code(value) => write!(f, "[{{{value}}}]"),
+            Init::Bits(bits) => {
+                let bits = bits.iter().rev().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{{ {} }}", bits.join(", "))
+            }
+            Init::List(values) => {
+                let values = values.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Init::Dag(dag) => write!(f, "{dag}"),
+            Init::Def(def) => write!(f, "{}", def.name),
+        }
+    }
+}
+
+impl fmt::Display for DagInit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}", self.operator)?;
+        if let Some(name) = &self.operator_name {
+            write!(f, ":${name}")?;
+        }
+        for (index, (init, name)) in self.args.iter().enumerate() {
+            write!(f, "{}{init}", if index == 0 { " " } else { ", " })?;
+            if let Some(name) = name {
+                write!(f, ":${name}")?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_inits() {
+        assert_eq!(Init::Int(1).convert(&Type::Bit), Init::Bit(true));
+        assert_eq!(
+            Init::Int(2).convert(&Type::Bits(3)),
+            Init::Bits(vec![Init::Bit(false), Init::Bit(true), Init::Bit(false)])
+        );
+        assert_eq!(Init::Int(2).convert(&Type::Bits(3)).as_int(), Some(2));
+        assert_eq!(Init::Bit(true).convert(&Type::Int), Init::Int(1));
+        assert_eq!(Init::Unset.convert(&Type::Int), Init::Unset);
+        assert_eq!(
+            Init::List(vec![Init::Int(0)]).convert(&Type::List(Box::new(Type::Bit))),
+            Init::List(vec![Init::Bit(false)])
+        );
+    }
+
+    #[test]
+    fn should_display_inits() {
+        assert_eq!(Init::String("a\"b".to_string()).to_string(), "\"a\\\"b\"");
+        assert_eq!(Init::Int(2).convert(&Type::Bits(2)).to_string(), "{ 1, 0 }");
+        assert_eq!(
+            Init::List(vec![Init::Int(1), Init::Code("x".to_string())]).to_string(),
+            "[1, [{x}]]"
+        );
+        assert_eq!(Init::String("a".to_string()).to_name_string(), "a");
+    }
+}
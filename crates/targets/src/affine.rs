@@ -18,3 +18,18 @@
 //!
 //! [Affine](https://mlir.llvm.org/docs/Dialects/Affine) is a MLIR internal dialect responsible for representing affine transforms
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[("affine.yield", &[OpTrait::Terminator, OpTrait::ReturnLike])],
+    pure: &[
+        "affine.apply",
+        "affine.min",
+        "affine.max",
+        "affine.delinearize_index",
+        "affine.linearize_index",
+    ],
+    ..TargetDialect::new("affine")
+};
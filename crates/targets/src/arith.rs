@@ -0,0 +1,175 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi MLIR arithmetics dialect support.
+//!
+//! Arithmetics dialect hold basic mathematical operations.
+//! The integer operations fold like the upstream `ArithOps.cpp` ones, the canonicalization patterns are
+//! generated out of the `arith/ArithCanonicalization.td` copy of the upstream `mlir/lib` one,
+//! against the `ArithOps.td` vendored by `cargo xtask vendor`, the custom forms and the ODS declarations
+//! the operations are verified against are generated out of the latter.
+//!
+
+use crate::ir::{Attribute, Builder, Context, OpFoldResult, Operation, OperationState, Type};
+use crate::TargetDialect;
+
+fljuga_handahofi_mlir_codegen::dialect! {
+    file = "src/arith/ArithCanonicalization.td",
+    includes = ["../mlir-codegen/include"],
+    hooks = crate::arith::hooks,
+    custom = [
+        "AddIntegerAttrs",
+        "SubIntegerAttrs",
+        "MulIntegerAttrs",
+        "GetZeroAttr",
+        "IsScalarOrSplatNegativeOne",
+        "IsEqOrNe"
+    ]
+}
+
+/// ODS declarations and custom forms of the arith operations.
+mod ods {
+    fljuga_handahofi_mlir_codegen::dialect! {
+        file = "../mlir-codegen/include/mlir/Dialect/Arith/IR/ArithOps.td",
+        includes = ["../mlir-codegen/include"]
+    }
+}
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    operations: ods::arith::OPERATIONS,
+    assembly: Some(ods::register),
+    interfaces: Some(ods::register_interfaces),
+    pure: &["*"],
+    fold: Some(fold),
+    constant_value: Some(constant_value),
+    materialize_constant: Some(materialize_constant),
+    canonicalization_patterns: Some(patterns::populate_with_generated),
+    ..TargetDialect::new("arith")
+};
+
+/// Native code calls and constraints of the canonicalization patterns, like the upstream `ArithOps.cpp` ones.
+pub(crate) mod hooks {
+    use crate::ir::{Attribute, AttributeKind, Context, Value};
+
+    fn integer_attr(ctx: &mut Context, res: Value, value: i128) -> Attribute {
+        let r#type = res.r#type(ctx);
+        let value = super::wrap(ctx, value, r#type);
+        ctx.integer_attr(value, r#type)
+    }
+
+    fn integer(ctx: &Context, attr: Attribute) -> i128 {
+        attr.as_integer(ctx).unwrap_or_default()
+    }
+
+    pub fn add_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs).wrapping_add(integer(ctx, rhs));
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn sub_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs).wrapping_sub(integer(ctx, rhs));
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn mul_integer_attrs(ctx: &mut Context, res: Value, lhs: Attribute, rhs: Attribute) -> Attribute {
+        let value = integer(ctx, lhs).wrapping_mul(integer(ctx, rhs));
+        integer_attr(ctx, res, value)
+    }
+
+    pub fn get_zero_attr(ctx: &mut Context, value: Value) -> Attribute {
+        integer_attr(ctx, value, 0)
+    }
+
+    pub fn is_scalar_or_splat_negative_one(ctx: &Context, attr: Attribute) -> bool {
+        matches!(
+            attr.kind(ctx),
+            AttributeKind::Integer { value: -1, .. } | AttributeKind::Bool(true)
+        )
+    }
+
+    /// `eq` and `ne` cases of the `CmpIPredicate`.
+    pub fn is_eq_or_ne(ctx: &Context, attr: Attribute) -> bool {
+        matches!(attr.as_integer(ctx), Some(0 | 1))
+    }
+}
+
+/// Sign-extends the low bits of the value to the width of the integer type, `index` is 64 bits wide.
+fn wrap(ctx: &Context, value: i128, r#type: Type) -> i128 {
+    let width = match r#type.is_index(ctx) {
+        true => 64,
+        false => r#type.int_or_float_width(ctx).unwrap_or(128),
+    };
+    match width {
+        1..=127 => (value << (128 - width)) >> (128 - width),
+        _ => value,
+    }
+}
+
+fn constant_value(ctx: &Context, op: Operation) -> Option<Attribute> {
+    (op.name(ctx) == "arith.constant").then(|| op.attribute(ctx, "value"))?
+}
+
+fn materialize_constant(
+    builder: &mut Builder<'_>,
+    value: Attribute,
+    r#type: Type,
+    location: Attribute,
+) -> Option<Operation> {
+    if value.r#type(builder) != Some(r#type) {
+        return None;
+    }
+    let mut state = OperationState::new("arith.constant");
+    state.add_attribute("value", value).add_types([r#type]).set_location(location);
+    state.move_into_properties(builder, &["value"]);
+    Some(builder.create(state))
+}
+
+/// Folds the binary scalar integer operations of the constant operands, and the ones of the identity or the
+/// absorbing constant operand or of the same operands, like `addi(x, 0) -> x`, `muli(x, 0) -> 0` and
+/// `subi(x, x) -> 0`.
+fn fold(ctx: &mut Context, op: Operation, operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+    let &[lhs, rhs] = op.operands(ctx) else {
+        return None;
+    };
+    let r#type = op.result_types(ctx).first().copied()?;
+    if !r#type.is_integer(ctx) && !r#type.is_index(ctx) {
+        return None;
+    }
+    let constant = |index: usize| operands.get(index).copied().flatten()?.as_integer(ctx);
+    let ones = wrap(ctx, -1, r#type);
+    let folded = match (op.name(ctx), constant(0), constant(1)) {
+        ("arith.addi", Some(lhs), Some(rhs)) => lhs.wrapping_add(rhs),
+        ("arith.subi", Some(lhs), Some(rhs)) => lhs.wrapping_sub(rhs),
+        ("arith.muli", Some(lhs), Some(rhs)) => lhs.wrapping_mul(rhs),
+        ("arith.andi", Some(lhs), Some(rhs)) => lhs & rhs,
+        ("arith.ori", Some(lhs), Some(rhs)) => lhs | rhs,
+        ("arith.xori", Some(lhs), Some(rhs)) => lhs ^ rhs,
+        ("arith.addi" | "arith.ori" | "arith.xori", Some(0), _) | ("arith.muli", Some(1), _) => {
+            return Some(vec![OpFoldResult::Value(rhs)]);
+        }
+        ("arith.andi", Some(constant), _) if constant == ones => return Some(vec![OpFoldResult::Value(rhs)]),
+        ("arith.addi" | "arith.subi" | "arith.ori" | "arith.xori", _, Some(0)) | ("arith.muli", _, Some(1)) => {
+            return Some(vec![OpFoldResult::Value(lhs)]);
+        }
+        ("arith.andi", _, Some(constant)) if constant == ones => return Some(vec![OpFoldResult::Value(lhs)]),
+        ("arith.muli" | "arith.andi", Some(0), _) | ("arith.muli" | "arith.andi", _, Some(0)) => 0,
+        ("arith.subi" | "arith.xori", _, _) if lhs == rhs => 0,
+        ("arith.andi" | "arith.ori", _, _) if lhs == rhs => return Some(vec![OpFoldResult::Value(lhs)]),
+        _ => return None,
+    };
+    let folded = wrap(ctx, folded, r#type);
+    Some(vec![OpFoldResult::Attribute(ctx.integer_attr(folded, r#type))])
+}
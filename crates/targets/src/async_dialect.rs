@@ -18,3 +18,15 @@
 //!
 //! MLIR async represents asynchronous operations and respective transforms.
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("async.func", &[OpTrait::IsolatedFromAbove]),
+        ("async.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("async.yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    ..TargetDialect::new("async")
+};
@@ -18,3 +18,7 @@
 //!
 //! Describes tensor bufferization operations, converting `tensor` to `memref`.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect::new("bufferization");
@@ -0,0 +1,83 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi MLIR control flow dialect support.
+//!
+//! Describes internal MLIR control flow dialect.
+//!
+
+use crate::ir::{AttributeKind, BranchOpInterface, Context, InterfaceRegistry, OpTrait, Operation, Value};
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("cf.br", &[OpTrait::Terminator]),
+        ("cf.cond_br", &[OpTrait::Terminator]),
+        ("cf.switch", &[OpTrait::Terminator]),
+    ],
+    interfaces: Some(interfaces),
+    ..TargetDialect::new("cf")
+};
+
+fn interfaces(registry: &mut InterfaceRegistry) {
+    for name in ["cf.br", "cf.cond_br", "cf.switch"] {
+        registry.register::<dyn BranchOpInterface>(name, &BranchOp);
+    }
+}
+
+/// Branches of the dialect, forwarding the operands to their successors.
+struct BranchOp;
+
+impl BranchOpInterface for BranchOp {
+    fn get_successor_operands(&self, ctx: &Context, op: Operation, index: u32) -> Vec<Value> {
+        successor_operands(ctx, op, index as usize).unwrap_or_default()
+    }
+}
+
+/// Sizes of the `array<i32: ...>` attribute segments.
+fn segments(ctx: &Context, op: Operation, name: &str) -> Option<Vec<usize>> {
+    match op.attribute(ctx, name)?.kind(ctx) {
+        AttributeKind::DenseArray { elements, .. } => elements
+            .iter()
+            .map(|element| element.as_integer(ctx).and_then(|size| usize::try_from(size).ok()))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Operands of the `cf.br` successor, of the `cf.cond_br` true and false destinations, and of the `cf.switch`
+/// default destination followed by its cases, split by the `operandSegmentSizes` and the `case_operand_segments`.
+fn successor_operands(ctx: &Context, op: Operation, index: usize) -> Option<Vec<Value>> {
+    let operands = op.operands(ctx);
+    let name = op.name(ctx);
+    if name == "cf.br" {
+        return Some(operands.to_vec());
+    }
+    let [condition, first, second] = segments(ctx, op, "operandSegmentSizes")?[..] else {
+        return None;
+    };
+    let range = match (name, index) {
+        ("cf.cond_br" | "cf.switch", 0) => condition..condition + first,
+        ("cf.cond_br", 1) => condition + first..condition + first + second,
+        ("cf.switch", case) => {
+            let cases = segments(ctx, op, "case_operand_segments")?;
+            let start = condition + first + cases.get(..case - 1)?.iter().sum::<usize>();
+            start..start + cases.get(case - 1)?
+        }
+        _ => return None,
+    };
+    operands.get(range).map(<[Value]>::to_vec)
+}
@@ -18,3 +18,10 @@
 //!
 //! Describes internal MLIR complex numbers operations.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["*"],
+    ..TargetDialect::new("complex")
+};
@@ -18,3 +18,7 @@
 //!
 //! Describes internal MLIR data layouts targeting multiple devices: CPU, GPU, XPU.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect::new("dlti");
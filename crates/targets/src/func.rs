@@ -0,0 +1,173 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi MLIR high-order functions dialect support.
+//!
+//! Describes internal MLIR high-order functions handling dialect.
+//! The `func.func`, `func.call` and `func.return` custom forms are hand-written like the upstream `FuncOps.cpp`
+//! ones, the operations of the function bodies are named without the `func` prefix.
+//!
+
+use crate::ir::{
+    types, AttributeKind, NamedAttribute, OpAsmRegistry, OpTrait, Operation, OperationState, ParseError, Parser,
+    Printer, TypeKind,
+};
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("func.func", &[OpTrait::IsolatedFromAbove]),
+        ("func.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    pure: &["func.constant"],
+    assembly: Some(assembly),
+    ..TargetDialect::new("func")
+};
+
+/// Attributes of the `func.func` printed as a part of its signature.
+const SIGNATURE_ATTRIBUTES: [&str; 5] = ["sym_name", "sym_visibility", "function_type", "arg_attrs", "res_attrs"];
+
+fn assembly(registry: &mut OpAsmRegistry) {
+    registry.register("func.func", print_func, parse_func);
+    registry.register("func.call", print_call, parse_call);
+    registry.register("func.return", print_return, parse_return);
+    registry.set_default_dialect("func.func", "func");
+}
+
+/// `func.func private @name(%arg0: i32) -> i32 attributes {...} {...}`, the declarations have no body
+/// and their signature lists only the types.
+fn print_func(op: Operation, p: &mut Printer<'_>) {
+    let ctx = p.ctx();
+    p.write(" ");
+    if let Some(visibility) = op.attribute(ctx, "sym_visibility").and_then(|visibility| visibility.as_string(ctx)) {
+        p.write(&format!("{visibility} "));
+    }
+    if let Some(name) = op.attribute(ctx, "sym_name") {
+        p.print_symbol_name(name);
+    }
+    let (inputs, results) = match op.attribute(ctx, "function_type").and_then(|r#type| r#type.as_type(ctx)) {
+        Some(r#type) => match r#type.kind(ctx) {
+            TypeKind::Function { inputs, results } => (inputs.clone(), results.clone()),
+            _ => (vec![], vec![]),
+        },
+        None => (vec![], vec![]),
+    };
+    let argument_attributes: Vec<Vec<NamedAttribute>> = op
+        .attribute(ctx, "arg_attrs")
+        .and_then(|attributes| attributes.as_array(ctx))
+        .unwrap_or_default()
+        .iter()
+        .map(|attributes| match attributes.kind(ctx) {
+            AttributeKind::Dictionary(attributes) => attributes.clone(),
+            _ => vec![],
+        })
+        .collect();
+    let body = op.regions(ctx).first().copied();
+    let entry = body.and_then(|body| body.entry_block(ctx));
+    p.print_function_signature(entry, &inputs, &results, &argument_attributes);
+    p.print_optional_attr_dict_with_keyword(&op.all_attributes(ctx), &SIGNATURE_ATTRIBUTES);
+    if let (Some(body), Some(_)) = (body, entry) {
+        p.write(" ");
+        p.print_region(body, false, true);
+    }
+}
+
+fn parse_func(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+    for visibility in ["private", "public", "nested"] {
+        if parser.parse_optional_keyword(visibility) {
+            let visibility = parser.ctx().string_attr(visibility);
+            state.add_attribute("sym_visibility", visibility);
+            break;
+        }
+    }
+    let name = parser.parse_symbol_name()?;
+    state.add_attribute("sym_name", name);
+    let (arguments, results) = parser.parse_function_signature()?;
+    let inputs: Vec<_> = arguments.iter().map(|argument| argument.r#type).collect();
+    let function_type = parser.ctx().function_type(&inputs, &results);
+    let function_type = parser.ctx().type_attr(function_type);
+    state.add_attribute("function_type", function_type);
+    if arguments.iter().any(|argument| !argument.attributes.is_empty()) {
+        let attributes: Vec<_> = arguments
+            .iter()
+            .map(|argument| parser.ctx().dictionary_attr(argument.attributes.clone()))
+            .collect();
+        let attributes = parser.ctx().array_attr(&attributes);
+        state.add_attribute("arg_attrs", attributes);
+    }
+    state.attributes.extend(parser.parse_optional_attr_dict_with_keyword()?);
+    let body = match arguments.iter().all(|argument| !argument.name.is_empty()) {
+        true => parser.parse_optional_region_with_arguments(&arguments)?,
+        false => None,
+    };
+    state.add_region(body.unwrap_or_default());
+    Ok(())
+}
+
+/// `func.call @callee(%a, %b) : (i32, i32) -> i32`
+fn print_call(op: Operation, p: &mut Printer<'_>) {
+    let ctx = p.ctx();
+    p.write(" ");
+    if let Some(callee) = op.attribute(ctx, "callee") {
+        p.print_attribute(callee);
+    }
+    p.write("(");
+    p.print_operands(op.operands(ctx));
+    p.write(")");
+    p.print_optional_attr_dict(&op.all_attributes(ctx), &["callee"]);
+    p.write(" : ");
+    let inputs = types(ctx, op.operands(ctx));
+    p.print_function_type(&inputs, &op.result_types(ctx));
+}
+
+fn parse_call(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+    let callee = parser.parse_symbol_name()?;
+    let callee = callee.as_string(parser.ctx()).unwrap_or_default().to_string();
+    let callee = parser.ctx().symbol_ref_attr(&callee);
+    state.add_attribute("callee", callee);
+    parser.parse_punct("(")?;
+    let operands = parser.parse_operand_list()?;
+    parser.parse_punct(")")?;
+    state.attributes.extend(parser.parse_optional_attr_dict()?);
+    parser.parse_punct(":")?;
+    let (inputs, results) = parser.parse_functional_type()?;
+    parser.resolve_operands(&operands, &inputs, state)?;
+    state.add_types(results);
+    Ok(())
+}
+
+/// `func.return %a, %b : i32, i32`, or just `func.return`.
+fn print_return(op: Operation, p: &mut Printer<'_>) {
+    let ctx = p.ctx();
+    p.print_optional_attr_dict(&op.all_attributes(ctx), &[]);
+    if !op.operands(ctx).is_empty() {
+        p.write(" ");
+        p.print_operands(op.operands(ctx));
+        p.write(" : ");
+        p.print_value_types(op.operands(ctx));
+    }
+}
+
+fn parse_return(parser: &mut Parser<'_, '_>, state: &mut OperationState) -> Result<(), ParseError> {
+    state.attributes.extend(parser.parse_optional_attr_dict()?);
+    let operands = parser.parse_operand_list()?;
+    if !operands.is_empty() {
+        parser.parse_punct(":")?;
+        let types = parser.parse_type_list()?;
+        parser.resolve_operands(&operands, &types, state)?;
+    }
+    Ok(())
+}
@@ -22,3 +22,31 @@
 //! *fljúga handahófi* will backport all the relevant GPU transformations, whenever possible,
 //! so this should be safe to skip in the nearest future.
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        (
+            "gpu.module",
+            &[OpTrait::NoTerminator, OpTrait::IsolatedFromAbove, OpTrait::SymbolTable],
+        ),
+        ("gpu.func", &[OpTrait::IsolatedFromAbove]),
+        ("gpu.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("gpu.terminator", &[OpTrait::Terminator]),
+        ("gpu.yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    pure: &[
+        "gpu.block_dim",
+        "gpu.block_id",
+        "gpu.global_id",
+        "gpu.grid_dim",
+        "gpu.lane_id",
+        "gpu.num_subgroups",
+        "gpu.subgroup_id",
+        "gpu.subgroup_size",
+        "gpu.thread_id",
+    ],
+    ..TargetDialect::new("gpu")
+};
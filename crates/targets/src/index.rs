@@ -18,3 +18,10 @@
 //!
 //! Describes internal MLIR pointer indexing dialect.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["*"],
+    ..TargetDialect::new("index")
+};
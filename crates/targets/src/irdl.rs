@@ -18,3 +18,19 @@
 //!
 //! Describes abstract internal IR handling.
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        (
+            "irdl.dialect",
+            &[OpTrait::NoTerminator, OpTrait::IsolatedFromAbove, OpTrait::SymbolTable],
+        ),
+        ("irdl.operation", &[OpTrait::NoTerminator]),
+        ("irdl.type", &[OpTrait::NoTerminator]),
+        ("irdl.attribute", &[OpTrait::NoTerminator]),
+    ],
+    ..TargetDialect::new("irdl")
+};
@@ -0,0 +1,310 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi mlir targets
+//!
+//! *fljúga handahófi* is a reference implementation of *rustc_codegen_mlir*,
+//! a code generator targeting [LLVM MLIR](https://mlir.llvm.org/) Transformations and Dialects.
+//!
+//! Dialects of the MLIR targets registered in the core IR [Context](ir::Context), declaring the traits
+//! of their operations the verifier and the transformations rely on, like the upstream ODS definitions.
+//! The `builtin`, `func` and `arith` operations are printed and parsed in their custom forms, the rest of the
+//! target dialects keep the generic one, the dialect crates enabled by the features are registered
+//! out of their generated ODS declarations, custom forms and interfaces.
+//! Shared by the `rustc_codegen_mlir` backend and the `fljuga-opt` driver.
+//!
+
+use fljuga_handahofi_mlir_ir as ir;
+
+use crate::ir::{
+    Attribute, BranchOpInterface, Builder, Context, Dialect, DialectRegistry, InterfaceRegistry, OpAsmRegistry,
+    OpDefinition, OpFoldResult, OpTrait, Operation, RegionKind, RewritePatternSet, Type,
+};
+
+mod affine;
+mod arith;
+mod async_dialect;
+mod bufferization;
+mod cf;
+mod complex;
+mod dlti;
+mod func;
+mod gpu;
+mod index;
+mod irdl;
+mod linalg;
+mod llvm;
+mod math;
+mod memref;
+mod mesh;
+mod ml_program;
+mod pdl;
+mod polynomial;
+mod ptr;
+mod quant;
+mod scf;
+mod shape;
+mod tensor;
+mod tensor_operator_set;
+mod tensor_sparse;
+mod ub;
+
+/// Folds the operation given the constant operands, like the ODS `fold` methods.
+pub(crate) type Fold = fn(&mut Context, Operation, &[Option<Attribute>]) -> Option<Vec<OpFoldResult>>;
+
+/// Creates the constant operation of the value, the type and the location, like the upstream `materializeConstant`.
+pub(crate) type MaterializeConstant = fn(&mut Builder<'_>, Attribute, Type, Attribute) -> Option<Operation>;
+
+/// Traits of the dialect operations, like the ODS ones, the operations missing from the tables have none.
+#[derive(Clone, Copy)]
+pub(crate) struct TargetDialect {
+    pub namespace: &'static str,
+    pub traits: &'static [(&'static str, &'static [OpTrait])],
+    /// Operations without side effects, `*` stands for all the operations of the dialect.
+    pub pure: &'static [&'static str],
+    /// Operations of the graph regions, like `builtin.module`.
+    pub graph_regions: &'static [&'static str],
+    /// Registers the interface implementations of the operations, like the `BranchOpInterface` of the branches.
+    pub interfaces: Option<fn(&mut InterfaceRegistry)>,
+    pub fold: Option<Fold>,
+    /// Value of the constant-like operations, like `arith.constant`.
+    pub constant_value: Option<fn(&Context, Operation) -> Option<Attribute>>,
+    pub materialize_constant: Option<MaterializeConstant>,
+    /// Adds the canonicalization patterns of the operations, like the generated DRR ones.
+    pub canonicalization_patterns: Option<fn(&mut RewritePatternSet)>,
+    /// ODS declarations generated by the `dialect!` macro, verified and queried along with the tables.
+    pub operations: &'static [OpDefinition],
+    /// Registers the generated custom forms of the operations.
+    pub assembly: Option<fn(&mut OpAsmRegistry)>,
+}
+
+impl TargetDialect {
+    pub const fn new(namespace: &'static str) -> Self {
+        Self {
+            namespace,
+            traits: &[],
+            pure: &[],
+            graph_regions: &[],
+            interfaces: None,
+            fold: None,
+            constant_value: None,
+            materialize_constant: None,
+            canonicalization_patterns: None,
+            operations: &[],
+            assembly: None,
+        }
+    }
+
+    /// Dialect of the dialect crate module generated by the `dialect!` macro.
+    pub const fn generated(
+        namespace: &'static str,
+        operations: &'static [OpDefinition],
+        assembly: fn(&mut OpAsmRegistry),
+        interfaces: fn(&mut InterfaceRegistry),
+    ) -> Self {
+        Self {
+            operations,
+            assembly: Some(assembly),
+            interfaces: Some(interfaces),
+            ..Self::new(namespace)
+        }
+    }
+}
+
+/// [TargetDialect] of the dialect crate module, like `generated!(fljuga_handahofi_dialect_omp::omp)`.
+macro_rules! generated {
+    ($($module:ident)::+) => {
+        TargetDialect::generated(
+            $($module)::+::NAMESPACE,
+            $($module)::+::OPERATIONS,
+            $($module)::+::register,
+            $($module)::+::register_interfaces,
+        )
+    };
+}
+
+impl Dialect for TargetDialect {
+    fn namespace(&self) -> &str {
+        self.namespace
+    }
+
+    fn fold(&self, ctx: &mut Context, op: Operation, operands: &[Option<Attribute>]) -> Option<Vec<OpFoldResult>> {
+        self.fold.and_then(|fold| fold(ctx, op, operands))
+    }
+
+    fn constant_value(&self, ctx: &Context, op: Operation) -> Option<Attribute> {
+        self.constant_value
+            .and_then(|constant_value| constant_value(ctx, op))
+    }
+
+    fn materialize_constant(
+        &self,
+        builder: &mut Builder<'_>,
+        value: Attribute,
+        r#type: Type,
+        location: Attribute,
+    ) -> Option<Operation> {
+        self.materialize_constant
+            .and_then(|materialize_constant| materialize_constant(builder, value, r#type, location))
+    }
+
+    fn is_pure(&self, ctx: &Context, op: Operation) -> bool {
+        let name = op.name(ctx);
+        self.pure.iter().any(|pure| *pure == "*" || *pure == name)
+            || OpDefinition::lookup(self.operations, name)
+                .is_some_and(|definition| definition.pure && !definition.traits.contains(&OpTrait::Terminator))
+    }
+
+    fn canonicalization_patterns(&self, patterns: &mut RewritePatternSet) {
+        if let Some(canonicalization_patterns) = self.canonicalization_patterns {
+            canonicalization_patterns(patterns);
+        }
+    }
+
+    fn has_trait(&self, ctx: &Context, op: Operation, r#trait: OpTrait) -> bool {
+        let name = op.name(ctx);
+        self.traits
+            .iter()
+            .any(|(op, traits)| *op == name && traits.contains(&r#trait))
+            || OpDefinition::lookup(self.operations, name)
+                .is_some_and(|definition| definition.traits.contains(&r#trait))
+    }
+
+    fn region_kind(&self, ctx: &Context, op: Operation, _index: usize) -> RegionKind {
+        match self.graph_regions.contains(&op.name(ctx)) {
+            true => RegionKind::Graph,
+            false => RegionKind::SsaCfg,
+        }
+    }
+
+    /// Verifies the operation against its ODS declaration and the operands forwarded by the branches match
+    /// the arguments of their successors.
+    fn verify(&self, ctx: &Context, op: Operation) -> Result<(), String> {
+        if let Some(definition) = OpDefinition::lookup(self.operations, op.name(ctx)) {
+            definition.verify(ctx, op)?;
+        }
+        let Some(branch) = op.as_interface::<dyn BranchOpInterface>(ctx) else {
+            return Ok(());
+        };
+        for (index, successor) in op.successors(ctx).iter().enumerate() {
+            let operands = branch.get_successor_operands(ctx, op, index as u32);
+            let arguments = successor.arguments(ctx);
+            if operands.len() != arguments.len() {
+                return Err(format!(
+                    "branch has {} operands for successor #{index}, but target block has {}",
+                    operands.len(),
+                    arguments.len()
+                ));
+            }
+            let mismatch = operands
+                .iter()
+                .zip(arguments)
+                .position(|(operand, argument)| operand.r#type(ctx) != argument.r#type(ctx));
+            if let Some(argument) = mismatch {
+                return Err(format!(
+                    "type mismatch for bb argument #{argument} of successor #{index}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn register_interfaces(&self, registry: &mut InterfaceRegistry) {
+        if let Some(interfaces) = self.interfaces {
+            interfaces(registry);
+        }
+    }
+
+    fn register_assembly(&self, registry: &mut OpAsmRegistry) {
+        if let Some(assembly) = self.assembly {
+            assembly(registry);
+        }
+    }
+}
+
+/// Dialects of the dialect crates enabled by the features, out of their generated ODS declarations, the ones
+/// of the other architectures are only built on their targets.
+const EXTERNAL: &[TargetDialect] = &[
+    #[cfg(feature = "fljuga-handahofi-dialect-amdgpu")]
+    generated!(fljuga_handahofi_dialect_amdgpu::amdgpu),
+    #[cfg(all(feature = "fljuga-handahofi-dialect-arm-neon", target_arch = "arm"))]
+    generated!(fljuga_handahofi_dialect_arm_neon::arm_neon),
+    #[cfg(all(feature = "fljuga-handahofi-dialect-arm-sme", target_arch = "arm"))]
+    generated!(fljuga_handahofi_dialect_arm_sme::arm_sme),
+    #[cfg(all(feature = "fljuga-handahofi-dialect-arm-sve", target_arch = "arm"))]
+    generated!(fljuga_handahofi_dialect_arm_sve::arm_sve),
+    #[cfg(feature = "fljuga-handahofi-dialect-emit-c")]
+    generated!(fljuga_handahofi_dialect_emit_c::emitc),
+    #[cfg(feature = "fljuga-handahofi-dialect-intel-amx")]
+    generated!(fljuga_handahofi_dialect_intel_amx::amx),
+    #[cfg(feature = "fljuga-handahofi-dialect-intel-xe")]
+    generated!(fljuga_handahofi_dialect_intel_xe::xegpu),
+    #[cfg(feature = "fljuga-handahofi-dialect-mpi")]
+    generated!(fljuga_handahofi_dialect_mpi::mpi),
+    #[cfg(feature = "fljuga-handahofi-dialect-nvgpu")]
+    generated!(fljuga_handahofi_dialect_nvgpu::nvgpu),
+    #[cfg(feature = "fljuga-handahofi-dialect-nvvm")]
+    generated!(fljuga_handahofi_dialect_nvvm::nvvm),
+    #[cfg(feature = "fljuga-handahofi-dialect-omp")]
+    generated!(fljuga_handahofi_dialect_omp::omp),
+    #[cfg(feature = "fljuga-handahofi-dialect-openacc")]
+    generated!(fljuga_handahofi_dialect_openacc::acc),
+    #[cfg(all(feature = "fljuga-handahofi-dialect-riscv-vcix", target_arch = "riscv64"))]
+    generated!(fljuga_handahofi_dialect_riscv_vcix::vcix),
+    #[cfg(feature = "fljuga-handahofi-dialect-rocdl")]
+    generated!(fljuga_handahofi_dialect_rocdl::rocdl),
+    #[cfg(feature = "fljuga-handahofi-dialect-spirv")]
+    generated!(fljuga_handahofi_dialect_spirv::spirv),
+    #[cfg(all(feature = "fljuga-handahofi-dialect-x86-vector", target_arch = "x86_64"))]
+    generated!(fljuga_handahofi_dialect_x86_vector::x86vector),
+];
+
+/// Registers the target dialects and the ones of the enabled dialect crates, like the upstream
+/// `registerAllDialects`.
+pub fn register_dialects(registry: &mut DialectRegistry) {
+    let targets = [
+        affine::DIALECT,
+        arith::DIALECT,
+        async_dialect::DIALECT,
+        bufferization::DIALECT,
+        cf::DIALECT,
+        complex::DIALECT,
+        dlti::DIALECT,
+        func::DIALECT,
+        gpu::DIALECT,
+        index::DIALECT,
+        irdl::DIALECT,
+        linalg::DIALECT,
+        llvm::DIALECT,
+        math::DIALECT,
+        memref::DIALECT,
+        mesh::DIALECT,
+        ml_program::DIALECT,
+        pdl::DIALECT,
+        polynomial::DIALECT,
+        ptr::DIALECT,
+        quant::DIALECT,
+        scf::DIALECT,
+        shape::DIALECT,
+        tensor::DIALECT,
+        tensor_operator_set::DIALECT,
+        tensor_sparse::DIALECT,
+        ub::DIALECT,
+    ];
+    for dialect in targets.into_iter().chain(EXTERNAL.iter().copied()) {
+        registry.insert(dialect);
+    }
+}
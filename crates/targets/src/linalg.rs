@@ -20,3 +20,12 @@
 //!
 //! [Linalg Rationale](https://mlir.llvm.org/docs/Rationale/RationaleLinalgDialect/).
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[("linalg.yield", &[OpTrait::Terminator, OpTrait::ReturnLike])],
+    pure: &["linalg.index"],
+    ..TargetDialect::new("linalg")
+};
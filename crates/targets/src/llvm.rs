@@ -0,0 +1,65 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi MLIR internal LLVM dialect support.
+//!
+//! Integrates LLVM dialect support.
+//!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("llvm.func", &[OpTrait::IsolatedFromAbove]),
+        ("llvm.comdat", &[OpTrait::NoTerminator, OpTrait::SymbolTable]),
+        ("llvm.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("llvm.br", &[OpTrait::Terminator]),
+        ("llvm.cond_br", &[OpTrait::Terminator]),
+        ("llvm.switch", &[OpTrait::Terminator]),
+        ("llvm.invoke", &[OpTrait::Terminator]),
+        ("llvm.resume", &[OpTrait::Terminator]),
+        ("llvm.unreachable", &[OpTrait::Terminator]),
+    ],
+    pure: &[
+        "llvm.mlir.addressof",
+        "llvm.mlir.constant",
+        "llvm.mlir.poison",
+        "llvm.mlir.undef",
+        "llvm.mlir.zero",
+        "llvm.add",
+        "llvm.sub",
+        "llvm.mul",
+        "llvm.and",
+        "llvm.or",
+        "llvm.xor",
+        "llvm.shl",
+        "llvm.lshr",
+        "llvm.ashr",
+        "llvm.icmp",
+        "llvm.select",
+        "llvm.zext",
+        "llvm.sext",
+        "llvm.trunc",
+        "llvm.bitcast",
+        "llvm.ptrtoint",
+        "llvm.inttoptr",
+        "llvm.getelementptr",
+        "llvm.extractvalue",
+        "llvm.insertvalue",
+    ],
+    ..TargetDialect::new("llvm")
+};
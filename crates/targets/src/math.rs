@@ -18,3 +18,10 @@
 //!
 //! Internal mlir Math operations beyond simple arithmetics - trigonometry, polynomials etc.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["*"],
+    ..TargetDialect::new("math")
+};
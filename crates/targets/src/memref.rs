@@ -17,3 +17,29 @@
 //! ## fljúga handahófi MLIR internal memory references (memref) dialect support.
 //!
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        (
+            "memref.alloca_scope.return",
+            &[OpTrait::Terminator, OpTrait::ReturnLike],
+        ),
+        ("memref.atomic_yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    pure: &[
+        "memref.cast",
+        "memref.collapse_shape",
+        "memref.dim",
+        "memref.expand_shape",
+        "memref.extract_strided_metadata",
+        "memref.get_global",
+        "memref.rank",
+        "memref.reinterpret_cast",
+        "memref.subview",
+        "memref.view",
+    ],
+    ..TargetDialect::new("memref")
+};
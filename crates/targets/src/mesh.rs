@@ -18,3 +18,7 @@
 //!
 //! Implements HPC communications subset.
 //!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect::new("mesh");
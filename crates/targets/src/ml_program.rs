@@ -19,3 +19,17 @@
 //! High level ML program representation,
 //! describes ML programs implemented in various common machine learning frameworks (TensorFlow, PyTorch, JAX).
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("ml_program.func", &[OpTrait::IsolatedFromAbove]),
+        ("ml_program.subgraph", &[OpTrait::IsolatedFromAbove]),
+        ("ml_program.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("ml_program.output", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    graph_regions: &["ml_program.subgraph"],
+    ..TargetDialect::new("ml_program")
+};
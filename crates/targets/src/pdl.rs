@@ -17,3 +17,15 @@
 //! ## fljúga handahófi MLIR pattern definition PDL dialect support.
 //!
 //! Implements PDL MLIR-to-MLIR transforms.
+//!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("pdl.pattern", &[OpTrait::IsolatedFromAbove]),
+        ("pdl.rewrite", &[OpTrait::Terminator, OpTrait::NoTerminator]),
+    ],
+    ..TargetDialect::new("pdl")
+};
@@ -17,3 +17,11 @@
 //! ## fljúga handahófi MLIR single variable polynomials dialect support.
 //!
 //! Implements single variable polynomial operations.
+//!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["*"],
+    ..TargetDialect::new("polynomial")
+};
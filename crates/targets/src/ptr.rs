@@ -17,3 +17,8 @@
 //! ## fljúga handahófi MLIR pointer dialect support.
 //!
 //! Implements pointer operations.
+//!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect::new("ptr");
@@ -17,3 +17,11 @@
 //! ## fljúga handahófi MLIR quantization dialect support.
 //!
 //! Implements quantization operations.
+//!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["*"],
+    ..TargetDialect::new("quant")
+};
@@ -18,3 +18,18 @@
 //!
 //! Implements mlir structured control flow dialect - `if`/`for` loops and conds are structured.
 //! `goto`/`assert` are unstructured.
+//!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        ("scf.yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("scf.condition", &[OpTrait::Terminator]),
+        ("scf.reduce", &[OpTrait::Terminator]),
+        ("scf.reduce.return", &[OpTrait::Terminator]),
+        ("scf.forall.in_parallel", &[OpTrait::Terminator, OpTrait::NoTerminator]),
+    ],
+    ..TargetDialect::new("scf")
+};
@@ -17,3 +17,21 @@
 //! ## fljúga handahófi shape dialect support.
 //!
 //! Implements mlir shape operations.
+//!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[
+        (
+            "shape.function_library",
+            &[OpTrait::NoTerminator, OpTrait::IsolatedFromAbove, OpTrait::SymbolTable],
+        ),
+        ("shape.func", &[OpTrait::IsolatedFromAbove]),
+        ("shape.return", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("shape.yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+        ("shape.assuming_yield", &[OpTrait::Terminator, OpTrait::ReturnLike]),
+    ],
+    ..TargetDialect::new("shape")
+};
@@ -17,3 +17,27 @@
 //! ## fljúga handahófi MLIR tensor dialect support.
 //!
 //! Implements dialect-agnostic tensor operations, often backed up by the `shape` dialect.
+//!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[("tensor.yield", &[OpTrait::Terminator, OpTrait::ReturnLike])],
+    pure: &[
+        "tensor.cast",
+        "tensor.collapse_shape",
+        "tensor.dim",
+        "tensor.empty",
+        "tensor.expand_shape",
+        "tensor.extract",
+        "tensor.extract_slice",
+        "tensor.from_elements",
+        "tensor.insert",
+        "tensor.insert_slice",
+        "tensor.rank",
+        "tensor.reshape",
+        "tensor.splat",
+    ],
+    ..TargetDialect::new("tensor")
+};
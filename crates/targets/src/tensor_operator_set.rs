@@ -17,3 +17,11 @@
 //! ## fljúga handahófi MLIR tensor operator set architecture (TOSA) dialect support.
 //!
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[("tosa.yield", &[OpTrait::Terminator, OpTrait::ReturnLike])],
+    ..TargetDialect::new("tosa")
+};
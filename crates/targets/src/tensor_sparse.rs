@@ -17,3 +17,11 @@
 //! ## fljúga handahófi MLIR sparse tensor (TACO).
 //!
 //!
+
+use crate::ir::OpTrait;
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    traits: &[("sparse_tensor.yield", &[OpTrait::Terminator, OpTrait::ReturnLike])],
+    ..TargetDialect::new("sparse_tensor")
+};
@@ -17,3 +17,11 @@
 //! ## fljúga handahófi undefined behavior dialect support.
 //!
 //! Handles poisoned constants with undefined behavior.
+//!
+
+use crate::TargetDialect;
+
+pub(crate) const DIALECT: TargetDialect = TargetDialect {
+    pure: &["ub.poison"],
+    ..TargetDialect::new("ub")
+};
@@ -0,0 +1,55 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ## fljúga handahófi test support
+//!
+//! Helpers shared by the tests of the dialect crates, [print] builds a single generated operation
+//! and prints its custom form.
+//!
+
+use fljuga_handahofi_mlir_ir::{
+    parse_type, Builder, Context, Dialect, OperationState, PrinterOptions, Type, Value,
+};
+
+/// Custom form of the operation built out of the block arguments of the types, printed as `%argN`.
+///
+/// The operation is built by `build` into the block of an enclosing region operation,
+/// with the `dialect` registered so its custom assembly is used.
+pub fn print(
+    dialect: impl Dialect + 'static,
+    types: &[&str],
+    build: impl FnOnce(&mut Context, &[Value]) -> OperationState,
+) -> String {
+    let mut ctx = Context::new();
+    ctx.register_dialect(dialect);
+    let types: Vec<Type> = types
+        .iter()
+        .map(|text| parse_type(&mut ctx, text).unwrap())
+        .collect();
+    let block = ctx.create_block(&types);
+    let mut region = OperationState::new("test.region");
+    region.add_region(vec![block]);
+    let region = ctx.create_operation(region);
+    let arguments = block.arguments(&ctx).to_vec();
+    let state = build(&mut ctx, &arguments);
+    Builder::at_end(&mut ctx, block).create(state);
+    let printed = fljuga_handahofi_mlir_ir::print(&ctx, region, PrinterOptions::default());
+    let mut lines = printed.lines().map(str::trim_start);
+    lines
+        .find(|line| !line.starts_with(['"', '^']))
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,125 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! `cargo xtask`, the repository maintenance tasks.
+//!
+//! `cargo xtask vendor` copies the include closure of the [ENTRY_POINTS] into `crates/mlir-codegen/include`,
+//! the `.td` files the `dialect!` macro of the dialect crates reads, and pins them with the `tablegen.lock`.
+//! Re-vendoring refuses the files changed since they were locked, the lock has to be removed to update them.
+//!
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use fljuga_handahofi_tablegen::fetch::{
+    fetch_tablegen_files, LlvmCheckout, LlvmTarball, Mirror, TableGenError, TdSource, Vendored, ENTRY_POINTS,
+    LLVM_REVISION,
+};
+
+const USAGE: &str = "USAGE: cargo xtask vendor [options]
+
+Vendors the MLIR TableGen files of the dialect crates, fetched from the pinned llvm-project revision by default.
+
+OPTIONS:
+  --tarball <file>      llvm-project release tarball, like `llvm-project-19.1.7.src.tar.xz`
+  --checkout <dir>      llvm-project checkout at the pinned revision
+  --mirror <url>        HTTP mirror of the llvm-project tree, the pinned GitHub revision by default
+  --corpus <dir>        Directory laid out like `mlir/include`
+  --dest <dir>          Include directory to vendor into, `crates/mlir-codegen/include` by default
+  --help                Prints this help";
+
+/// Include directory the dialect crates read the `.td` files from, relative to the workspace root.
+const INCLUDE_DIR: &str = "crates/mlir-codegen/include";
+
+/// Provider of the vendored files.
+enum Source {
+    Tarball(PathBuf),
+    Checkout(PathBuf),
+    Mirror(String),
+    Corpus(PathBuf),
+}
+
+fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).ancestors().nth(2).unwrap_or(Path::new("."))
+}
+
+struct Args {
+    source: Source,
+    dest: PathBuf,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        Some("vendor") => {}
+        Some("--help" | "-h") | None => return Ok(None),
+        Some(task) => return Err(format!("unknown task `{task}`")),
+    }
+    let mut source = Source::Mirror(format!("{}{LLVM_REVISION}", Mirror::LLVM_PROJECT));
+    let mut dest = workspace_root().join(INCLUDE_DIR);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("`{arg}` expects a value"));
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(None),
+            "--tarball" => source = Source::Tarball(value()?.into()),
+            "--checkout" => source = Source::Checkout(value()?.into()),
+            "--mirror" => source = Source::Mirror(value()?),
+            "--corpus" => source = Source::Corpus(value()?.into()),
+            "--dest" => dest = value()?.into(),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    Ok(Some(Args { source, dest }))
+}
+
+async fn vendor(source: &impl TdSource, dest: &Path) -> Result<(), TableGenError> {
+    tokio::fs::create_dir_all(dest).await?;
+    let lock = fetch_tablegen_files(source, &ENTRY_POINTS, dest).await?;
+    println!("vendored {} files from the {} into {}", lock.files.len(), lock.source, dest.display());
+    Ok(())
+}
+
+async fn run(args: Args) -> Result<(), TableGenError> {
+    match args.source {
+        Source::Tarball(archive) => vendor(&LlvmTarball::new(archive), &args.dest).await,
+        Source::Checkout(root) => vendor(&LlvmCheckout { root }, &args.dest).await,
+        Source::Mirror(prefix) => vendor(&Mirror::new(&prefix), &args.dest).await,
+        Source::Corpus(root) => vendor(&Vendored { root }, &args.dest).await,
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build();
+    match runtime.map_err(TableGenError::from).and_then(|runtime| runtime.block_on(run(args))) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,340 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! The rustc [CodegenBackend] of `-Zcodegen-backend=librustc_codegen_mlir.so`: the monomorphized MIR of every
+//! codegen unit is lowered to the `llvm` dialect, verified, translated to the LLVM IR and compiled to an object
+//! file by the `opt` and `llc` tools of the `llvm-tools` component, the crates are linked by [rustc_codegen_ssa]
+//! like for the other backends.
+//!
+//! The first cut compiles the `no_std` `#![no_main]` crates of the integer-only functions: the scalar locals
+//! without the place projections, the direct calls, and the failed assertions trapping instead of panicking.
+//! With `-Cllvm-args=lowering=func` the functions are lowered into the `func`, `cf`, `arith` and `memref`
+//! dialects instead, and the verified modules are written to the `<crate>.mlir` file rather than compiled.
+//! The other `-Cllvm-args` are passed to the LLVM tools.
+//!
+
+mod emit;
+mod func;
+mod lower;
+mod translate;
+
+use std::any::Any;
+use std::fs;
+
+use rustc_codegen_ssa::back::link::ensure_removed;
+use rustc_codegen_ssa::back::metadata::create_compressed_metadata_file;
+use rustc_codegen_ssa::base::allocator_kind_for_codegen;
+use rustc_codegen_ssa::traits::CodegenBackend;
+use rustc_codegen_ssa::{errors as ssa_errors, CodegenResults, CompiledModule, CrateInfo, ModuleKind};
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_metadata::fs::copy_to_stdout;
+use rustc_metadata::EncodedMetadata;
+use rustc_middle::dep_graph::{WorkProduct, WorkProductId};
+use rustc_middle::middle::exported_symbols::metadata_symbol_name;
+use rustc_middle::mir::mono::{CodegenUnit, CodegenUnitNameBuilder};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::{OutFileName, OutputFilenames, OutputType};
+use rustc_session::Session;
+
+use crate::backend::emit::{EmitConfig, FileType};
+use crate::diagnostics::SourceLocations;
+use crate::ir::{print, verify, Context, Diagnostic, DialectRegistry, PrinterOptions};
+use crate::targets::register_dialects;
+
+/// Derived [thiserror::Error] for the code the backend can't compile
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    #[error("the MLIR backend doesn't support {0} yet")]
+    Unsupported(String),
+
+    #[error("`{op}` can't be translated to LLVM IR: {reason}")]
+    Translation { op: String, reason: String },
+
+    #[error("LLVM {0}")]
+    Llvm(String),
+}
+
+impl CodegenError {
+    pub(crate) fn unsupported(what: impl Into<String>) -> Self {
+        Self::Unsupported(what.into())
+    }
+}
+
+/// Dialects the MIR is lowered into, the `lowering` option of `-Cllvm-args`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Lowering {
+    /// `llvm` dialect compiled into the objects.
+    #[default]
+    Llvm,
+    /// `func` and `cf` dialects written to the `.mlir` file.
+    Func,
+}
+
+/// Options of the backend, passed as `-Cllvm-args=lowering=<value>`.
+#[derive(Debug, Clone, Default)]
+struct BackendConfig {
+    lowering: Lowering,
+    /// The rest of the `-Cllvm-args`, forwarded to the LLVM tools.
+    llvm_args: Vec<String>,
+}
+
+impl BackendConfig {
+    fn from_session(sess: &Session) -> Self {
+        let mut config = Self::default();
+        for arg in &sess.opts.cg.llvm_args {
+            match arg.strip_prefix("lowering=") {
+                Some("llvm") => config.lowering = Lowering::Llvm,
+                Some("func") => config.lowering = Lowering::Func,
+                Some(lowering) => sess.dcx().fatal(format!(
+                    "unknown MLIR backend lowering `{lowering}`, expected `lowering=llvm|func`"
+                )),
+                None => config.llvm_args.push(arg.clone()),
+            }
+        }
+        config
+    }
+}
+
+/// MLIR codegen backend, compiling the codegen units one after another.
+pub struct MlirCodegenBackend;
+
+/// Compiled modules of [CodegenBackend::codegen_crate] handed over to [CodegenBackend::join_codegen].
+struct OngoingCodegen {
+    modules: Vec<CompiledModule>,
+    metadata_module: Option<CompiledModule>,
+    metadata: EncodedMetadata,
+    crate_info: CrateInfo,
+}
+
+impl CodegenBackend for MlirCodegenBackend {
+    fn locale_resource(&self) -> &'static str {
+        ""
+    }
+
+    fn codegen_crate(&self, tcx: TyCtxt<'_>, metadata: EncodedMetadata, need_metadata_module: bool) -> Box<dyn Any> {
+        let target_cpu = match &tcx.sess.opts.cg.target_cpu {
+            Some(cpu) => cpu.clone(),
+            None => tcx.sess.target.cpu.to_string(),
+        };
+        let crate_info = CrateInfo::new(tcx, target_cpu.clone());
+        // Neither the `--emit metadata` only crates are compiled, nor their mono items are collected.
+        if !tcx.sess.opts.output_types.should_codegen() {
+            return Box::new(OngoingCodegen {
+                modules: vec![],
+                metadata_module: None,
+                metadata,
+                crate_info,
+            });
+        }
+        if allocator_kind_for_codegen(tcx).is_some() {
+            tcx.dcx()
+                .fatal(CodegenError::unsupported("the global allocator shim").to_string());
+        }
+        if tcx.entry_fn(()).is_some() {
+            let error = CodegenError::unsupported("the `main` entry point");
+            tcx.dcx()
+                .fatal(format!("{error}, the crate is expected to be `#![no_main]`"));
+        }
+
+        let cgus = tcx.collect_and_partition_mono_items(()).1;
+        let config = BackendConfig::from_session(tcx.sess);
+        let modules: Vec<CompiledModule> = match config.lowering {
+            Lowering::Llvm => {
+                let config = EmitConfig::new(tcx.sess, &target_cpu, &config.llvm_args);
+                cgus.iter().filter_map(|cgu| codegen_unit(tcx, cgu, &config)).collect()
+            }
+            Lowering::Func => {
+                emit_func_modules(tcx, cgus);
+                vec![]
+            }
+        };
+        tcx.dcx().abort_if_errors();
+        let metadata_module = need_metadata_module.then(|| metadata_module(tcx, &metadata));
+        Box::new(OngoingCodegen {
+            modules,
+            metadata_module,
+            metadata,
+            crate_info,
+        })
+    }
+
+    fn join_codegen(
+        &self,
+        ongoing_codegen: Box<dyn Any>,
+        sess: &Session,
+        outputs: &OutputFilenames,
+    ) -> (CodegenResults, FxIndexMap<WorkProductId, WorkProduct>) {
+        let codegen = ongoing_codegen
+            .downcast::<OngoingCodegen>()
+            .expect("the codegen is started by the MLIR backend");
+        let results = CodegenResults {
+            modules: codegen.modules,
+            allocator_module: None,
+            metadata_module: codegen.metadata_module,
+            metadata: codegen.metadata,
+            crate_info: codegen.crate_info,
+        };
+        produce_final_outputs(sess, &results, outputs);
+        (results, FxIndexMap::default())
+    }
+}
+
+/// Lowers the codegen unit and compiles it to the numbered temporary object, along with the LLVM IR and
+/// the assembly of `--emit llvm-ir,asm`, the errors are reported to the session.
+fn codegen_unit<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>, config: &EmitConfig) -> Option<CompiledModule> {
+    let name = cgu.name().as_str().to_string();
+    let mut dialects = DialectRegistry::default();
+    register_dialects(&mut dialects);
+    let mut ctx = Context::with_dialects(dialects);
+    let locations = SourceLocations::new(tcx.sess.source_map());
+    let module = lower::lower_codegen_unit(tcx, &mut ctx, &locations, cgu)?;
+    if let Err(error) = verify(&ctx, module) {
+        locations.emit(tcx.dcx(), &Diagnostic::from(&error));
+        return None;
+    }
+    let ir = translate::translate_module(&ctx, module, &config.data_layout, &config.triple)
+        .unwrap_or_else(|error| tcx.dcx().fatal(error.to_string()));
+
+    let outputs = tcx.output_filenames(());
+    let wants = |output_type| tcx.sess.opts.output_types.contains_key(&output_type);
+    let mut compiled = CompiledModule {
+        name: name.clone(),
+        kind: ModuleKind::Regular,
+        object: Some(outputs.temp_path(OutputType::Object, Some(&name))),
+        dwarf_object: None,
+        bytecode: None,
+        assembly: wants(OutputType::Assembly).then(|| outputs.temp_path(OutputType::Assembly, Some(&name))),
+        llvm_ir: wants(OutputType::LlvmAssembly).then(|| outputs.temp_path(OutputType::LlvmAssembly, Some(&name))),
+    };
+    if let Some(path) = &compiled.llvm_ir {
+        if let Err(error) = fs::write(path, &ir) {
+            tcx.dcx()
+                .fatal(format!("error writing LLVM IR file {}: {error}", path.display()));
+        }
+    }
+    let mut files = vec![];
+    if let Some(path) = &compiled.assembly {
+        files.push((FileType::Assembly, path.as_path()));
+    }
+    if let Some(path) = &compiled.object {
+        files.push((FileType::Object, path.as_path()));
+    }
+    if let Err(error) = emit::compile(&ir, &name, config, &files) {
+        tcx.dcx().err(error.to_string());
+        compiled.object = None;
+    }
+    Some(compiled)
+}
+
+/// Lowers the codegen units into the `func` dialect and writes their verified modules to the `<crate>.mlir`
+/// file, split by the `// -----` markers like the `fljuga-opt --split-input-file` input.
+fn emit_func_modules<'tcx>(tcx: TyCtxt<'tcx>, cgus: &[CodegenUnit<'tcx>]) {
+    let mut chunks = vec![];
+    for cgu in cgus {
+        let mut dialects = DialectRegistry::default();
+        register_dialects(&mut dialects);
+        let mut ctx = Context::with_dialects(dialects);
+        let locations = SourceLocations::new(tcx.sess.source_map());
+        let Some(module) = func::lower_codegen_unit(tcx, &mut ctx, &locations, cgu) else {
+            continue;
+        };
+        if let Err(error) = verify(&ctx, module) {
+            locations.emit(tcx.dcx(), &Diagnostic::from(&error));
+            continue;
+        }
+        chunks.push(print(&ctx, module, PrinterOptions::default()));
+    }
+    tcx.dcx().abort_if_errors();
+    let path = tcx.output_filenames(()).with_extension("mlir");
+    if let Err(error) = fs::write(&path, chunks.join("// -----\n")) {
+        tcx.dcx()
+            .fatal(format!("error writing MLIR file {}: {error}", path.display()));
+    }
+}
+
+/// Object of the compressed crate metadata, like the one of the LLVM backend.
+fn metadata_module(tcx: TyCtxt<'_>, metadata: &EncodedMetadata) -> CompiledModule {
+    let name = CodegenUnitNameBuilder::new(tcx)
+        .build_cgu_name(LOCAL_CRATE, ["crate"], Some("metadata"))
+        .as_str()
+        .to_string();
+    let path = tcx.output_filenames(()).temp_path(OutputType::Metadata, Some(&name));
+    let object = create_compressed_metadata_file(tcx.sess, metadata, &metadata_symbol_name(tcx));
+    if let Err(error) = fs::write(&path, object) {
+        tcx.dcx().fatal(format!("error writing metadata object file: {error}"));
+    }
+    CompiledModule {
+        name,
+        kind: ModuleKind::Metadata,
+        object: Some(path),
+        dwarf_object: None,
+        bytecode: None,
+        assembly: None,
+        llvm_ir: None,
+    }
+}
+
+/// Copies the numbered outputs of the single codegen unit to the requested ones and removes the temporary
+/// objects not needed for linking, like the `produce_final_output_artifacts` of the LLVM backend.
+fn produce_final_outputs(sess: &Session, results: &CodegenResults, outputs: &OutputFilenames) {
+    let copy = |output_type: OutputType| {
+        let [module] = results.modules.as_slice() else {
+            // The multiple codegen units leave their numbered outputs in place.
+            if outputs.outputs.contains_explicit_name(&output_type) || outputs.single_output_file.is_some() {
+                let extension = output_type.extension().to_string();
+                sess.dcx().emit_warn(ssa_errors::IgnoringOutput { extension });
+            }
+            return;
+        };
+        let from = outputs.temp_path(output_type, Some(&module.name));
+        match outputs.path(output_type) {
+            OutFileName::Stdout if !output_type.is_text_output() && OutFileName::Stdout.is_tty() => {
+                sess.dcx().emit_err(ssa_errors::BinaryOutputToTty {
+                    shorthand: output_type.shorthand(),
+                });
+            }
+            OutFileName::Stdout => {
+                if let Err(error) = copy_to_stdout(&from) {
+                    sess.dcx()
+                        .emit_err(ssa_errors::CopyPath::new(&from, OutFileName::Stdout.as_path(), error));
+                }
+            }
+            OutFileName::Real(path) => {
+                if let Err(error) = fs::copy(&from, &path) {
+                    sess.dcx().emit_err(ssa_errors::CopyPath::new(&from, &path, error));
+                }
+            }
+        }
+        if output_type != OutputType::Object && !sess.opts.cg.save_temps {
+            ensure_removed(sess.dcx(), &from);
+        }
+    };
+    for output_type in [OutputType::Object, OutputType::Assembly, OutputType::LlvmAssembly] {
+        if outputs.outputs.contains_key(&output_type) {
+            copy(output_type);
+        }
+    }
+
+    // The linker removes the objects it's given, the other ones are removed here.
+    let wants_objects = outputs.outputs.contains_key(&OutputType::Object) && results.modules.len() > 1;
+    if !sess.opts.cg.save_temps && !outputs.outputs.contains_key(&OutputType::Exe) && !wants_objects {
+        for path in results.modules.iter().filter_map(|module| module.object.as_ref()) {
+            ensure_removed(sess.dcx(), path);
+        }
+    }
+}
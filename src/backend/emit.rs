@@ -0,0 +1,178 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Compilation of the textual LLVM IR to the object and the assembly files by the `opt` and `llc` tools of the
+//! `llvm-tools` component, built from the same LLVM as the toolchain: the IR is verified and optimized by the
+//! new pass manager `default<On>` pipeline of `opt`, then emitted by `llc` for the session target.
+//!
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use rustc_session::config::OptLevel;
+use rustc_session::Session;
+use rustc_target::spec::{CodeModel, RelocModel};
+
+use crate::backend::CodegenError;
+
+/// Files `llc` emits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileType {
+    Assembly,
+    Object,
+}
+
+impl FileType {
+    fn name(self) -> &'static str {
+        match self {
+            FileType::Assembly => "asm",
+            FileType::Object => "obj",
+        }
+    }
+}
+
+/// Target machine options of the session.
+pub(crate) struct EmitConfig {
+    pub(crate) triple: String,
+    pub(crate) data_layout: String,
+    opt: PathBuf,
+    llc: PathBuf,
+    cpu: String,
+    features: String,
+    passes: &'static str,
+    opt_level: &'static str,
+    reloc: &'static str,
+    code_model: Option<&'static str>,
+    llvm_args: Vec<String>,
+}
+
+impl EmitConfig {
+    /// The `llvm_args` are the `-Cllvm-args` options the backend doesn't handle itself, they're passed to both
+    /// of the tools.
+    pub(crate) fn new(sess: &Session, target_cpu: &str, llvm_args: &[String]) -> Self {
+        let (passes, opt_level) = match sess.opts.optimize {
+            OptLevel::No => ("default<O0>", "-O0"),
+            OptLevel::Less => ("default<O1>", "-O1"),
+            OptLevel::Default => ("default<O2>", "-O2"),
+            OptLevel::Aggressive => ("default<O3>", "-O3"),
+            OptLevel::Size => ("default<Os>", "-O2"),
+            OptLevel::SizeMin => ("default<Oz>", "-O2"),
+        };
+        // LLVM has no PIE relocation model, the position independent code is the closest one.
+        let reloc = match sess.relocation_model() {
+            RelocModel::Static => "static",
+            RelocModel::Pic | RelocModel::Pie => "pic",
+            RelocModel::DynamicNoPic => "dynamic-no-pic",
+            RelocModel::Ropi => "ropi",
+            RelocModel::Rwpi => "rwpi",
+            RelocModel::RopiRwpi => "ropi-rwpi",
+        };
+        let code_model = sess.code_model().map(|code_model| match code_model {
+            CodeModel::Tiny => "tiny",
+            CodeModel::Small => "small",
+            CodeModel::Kernel => "kernel",
+            CodeModel::Medium => "medium",
+            CodeModel::Large => "large",
+        });
+        let mut features: Vec<&str> = sess.target.features.split(',').collect();
+        features.extend(sess.opts.cg.target_feature.split(','));
+        features.retain(|feature| !feature.is_empty());
+        Self {
+            triple: sess.target.llvm_target.to_string(),
+            data_layout: sess.target.data_layout.to_string(),
+            opt: llvm_tool(sess, "opt"),
+            llc: llvm_tool(sess, "llc"),
+            cpu: target_cpu.to_string(),
+            features: features.join(","),
+            passes,
+            opt_level,
+            reloc,
+            code_model,
+            llvm_args: llvm_args.to_vec(),
+        }
+    }
+
+    /// Target options shared by `opt` and `llc`, `-mcpu=native` is resolved by the tools themselves.
+    fn target_args(&self) -> Vec<String> {
+        let mut args = vec![
+            format!("-mtriple={}", self.triple),
+            format!("-mcpu={}", self.cpu),
+            format!("-mattr={}", self.features),
+            format!("-relocation-model={}", self.reloc),
+        ];
+        args.extend(self.code_model.map(|code_model| format!("-code-model={code_model}")));
+        args.extend(self.llvm_args.iter().cloned());
+        args
+    }
+}
+
+/// The `llvm-tools` binary of the sysroot, or the one on the `PATH` if the component isn't installed.
+fn llvm_tool(sess: &Session, name: &str) -> PathBuf {
+    let file = format!("{name}{}", std::env::consts::EXE_SUFFIX);
+    sess.get_tools_search_paths(false)
+        .into_iter()
+        .map(|dir| dir.join(&file))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(file))
+}
+
+/// Runs the tool with the input piped to its stdin, returning its stdout or its error message.
+fn run(tool: &Path, args: &[String], input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("failed to run `{}`: {error}", tool.display()))?;
+    let mut stdin = child.stdin.take().expect("the stdin is piped");
+    // The input is written by its own thread, the tool may block on the full stdout pipe before reading it all.
+    // A failed write means the tool has exited, its status tells why.
+    let output = thread::scope(|scope| {
+        scope.spawn(move || stdin.write_all(input));
+        child.wait_with_output()
+    })
+    .map_err(|error| format!("failed to run `{}`: {error}", tool.display()))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// Verifies and optimizes the LLVM IR of the module, then emits it to every output file.
+pub(crate) fn compile(
+    ir: &str,
+    name: &str,
+    config: &EmitConfig,
+    outputs: &[(FileType, &Path)],
+) -> Result<(), CodegenError> {
+    let mut args = config.target_args();
+    args.push(format!("-passes={}", config.passes));
+    let bitcode = run(&config.opt, &args, ir.as_bytes()).map_err(|error| CodegenError::Llvm(format!("failed to optimize the module `{name}`: {error}")))?;
+
+    for (file_type, path) in outputs {
+        let mut args = config.target_args();
+        args.push(config.opt_level.to_string());
+        args.push(format!("-filetype={}", file_type.name()));
+        args.push(format!("-o={}", path.display()));
+        run(&config.llc, &args, &bitcode)
+            .map_err(|error| CodegenError::Llvm(format!("failed to emit {}: {error}", path.display())))?;
+    }
+    Ok(())
+}
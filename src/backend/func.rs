@@ -0,0 +1,1060 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Lowering of the monomorphized MIR of the codegen unit functions into the `func.func`s of the `func`, `cf`,
+//! `arith` and `memref` dialects, one block per MIR basic block.
+//! The scalar locals are promoted to the SSA values, the ones live at the start of a block are passed along
+//! as its arguments, while the address-taken locals live in the rank-0 `memref.alloca`s of the entry block.
+//! The thin references and pointers to the integers are the rank-0 memrefs of them.
+//! The blocks the control never leaves end with the failing `cf.assert` and the `func.return` of a poison.
+//!
+
+use std::collections::{BTreeSet, HashMap};
+
+use rustc_middle::mir::mono::{CodegenUnit, Linkage, MonoItem, MonoItemData};
+use rustc_middle::mir::visit::{MutatingUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::{
+    AssertKind, BasicBlock, BinOp, Body, CastKind, Local, Location, Operand, Place, ProjectionElem, Rvalue, Statement,
+    StatementKind, Terminator, TerminatorKind, UnOp, RETURN_PLACE, START_BLOCK,
+};
+use rustc_middle::ty::{self, Instance, InstanceKind, Ty, TyCtxt, TypeFoldable, TypingEnv};
+use rustc_span::Span;
+
+use crate::backend::lower::{monomorphize, sign_extend, EQ, NE, SGE, SGT, SLE, SLT, UGE, UGT, ULE, ULT};
+use crate::backend::CodegenError;
+use crate::diagnostics::SourceLocations;
+use crate::ir::{Attribute, Block, Context, Operation, OperationState, Type, Value};
+
+/// Lowers the functions of the codegen unit into a new module, the unsupported constructs are reported
+/// as the errors at their spans, leaving no module.
+pub(crate) fn lower_codegen_unit<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ctx: &mut Context,
+    locations: &SourceLocations<'_>,
+    cgu: &CodegenUnit<'tcx>,
+) -> Option<Operation> {
+    let module = ctx.create_module();
+    let body = module.region(ctx, 0).entry_block(ctx).expect("the module has a body");
+    let mut lowering = ModuleLowering {
+        tcx,
+        ctx,
+        locations,
+        body,
+        functions: HashMap::new(),
+    };
+    let mut failed = false;
+    for (item, data) in cgu.items_in_deterministic_order(tcx) {
+        let result = match item {
+            MonoItem::Fn(instance) => lowering.define(instance, data),
+            MonoItem::Static(def_id) => Err((tcx.def_span(def_id), CodegenError::unsupported("the statics"))),
+            MonoItem::GlobalAsm(item) => Err((
+                tcx.def_span(item.owner_id.to_def_id()),
+                CodegenError::unsupported("the global assembly"),
+            )),
+        };
+        if let Err((span, error)) = result {
+            tcx.dcx().span_err(span, error.to_string());
+            failed = true;
+        }
+    }
+    (!failed).then_some(module)
+}
+
+/// Message of the `cf.assert`, like the one of the panic the failed assertion calls.
+fn assert_message<O>(kind: &AssertKind<O>) -> &'static str {
+    match kind {
+        AssertKind::BoundsCheck { .. } => "index out of bounds",
+        AssertKind::Overflow(BinOp::Add, ..) => "attempt to add with overflow",
+        AssertKind::Overflow(BinOp::Sub, ..) => "attempt to subtract with overflow",
+        AssertKind::Overflow(BinOp::Mul, ..) => "attempt to multiply with overflow",
+        AssertKind::Overflow(BinOp::Div, ..) => "attempt to divide with overflow",
+        AssertKind::Overflow(BinOp::Rem, ..) => "attempt to calculate the remainder with overflow",
+        AssertKind::Overflow(BinOp::Shl, ..) => "attempt to shift left with overflow",
+        AssertKind::Overflow(BinOp::Shr, ..) => "attempt to shift right with overflow",
+        AssertKind::OverflowNeg(_) => "attempt to negate with overflow",
+        AssertKind::DivisionByZero(_) => "attempt to divide by zero",
+        AssertKind::RemainderByZero(_) => "attempt to calculate the remainder with a divisor of zero",
+        AssertKind::MisalignedPointerDereference { .. } => "misaligned pointer dereference",
+        _ => "assertion failed",
+    }
+}
+
+/// Locals the statement or the terminator reads, and the ones it assigns as a whole.
+#[derive(Default)]
+struct Accesses {
+    uses: Vec<Local>,
+    defs: Vec<Local>,
+}
+
+impl Visitor<'_> for Accesses {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, _location: Location) {
+        match context {
+            PlaceContext::NonUse(_) | PlaceContext::MutatingUse(MutatingUseContext::Drop) => {}
+            PlaceContext::MutatingUse(MutatingUseContext::Store | MutatingUseContext::Call) => self.defs.push(local),
+            _ => self.uses.push(local),
+        }
+    }
+}
+
+/// Promoted locals live at the start of every block, the ones the block takes as its arguments.
+fn live_ins(mir: &Body<'_>, promoted: &[bool]) -> Vec<Vec<Local>> {
+    let blocks = mir.basic_blocks.len();
+    let mut uses = vec![BTreeSet::new(); blocks];
+    let mut defs = vec![BTreeSet::new(); blocks];
+    for (block, data) in mir.basic_blocks.iter_enumerated() {
+        let (uses, defs) = (&mut uses[block.as_usize()], &mut defs[block.as_usize()]);
+        let mut access = |accesses: Accesses| {
+            for local in accesses.uses {
+                if promoted[local.as_usize()] && !defs.contains(&local) {
+                    uses.insert(local);
+                }
+            }
+            defs.extend(accesses.defs);
+        };
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            let mut accesses = Accesses::default();
+            accesses.visit_statement(statement, Location { block, statement_index });
+            access(accesses);
+        }
+        let mut accesses = Accesses::default();
+        let location = Location {
+            block,
+            statement_index: data.statements.len(),
+        };
+        accesses.visit_terminator(data.terminator(), location);
+        access(accesses);
+    }
+
+    let mut live = uses.clone();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (block, data) in mir.basic_blocks.iter_enumerated().rev() {
+            let index = block.as_usize();
+            let mut live_in = uses[index].clone();
+            for successor in data.terminator().successors() {
+                live_in.extend(
+                    live[successor.as_usize()]
+                        .iter()
+                        .filter(|local| !defs[index].contains(*local)),
+                );
+            }
+            if live_in != live[index] {
+                live[index] = live_in;
+                changed = true;
+            }
+        }
+    }
+    live.into_iter().map(|live| live.into_iter().collect()).collect()
+}
+
+struct ModuleLowering<'l, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    ctx: &'l mut Context,
+    locations: &'l SourceLocations<'l>,
+    body: Block,
+    /// `func.func`s of the symbols, both the defined and the declared ones.
+    functions: HashMap<String, Operation>,
+}
+
+impl<'tcx> ModuleLowering<'_, 'tcx> {
+    /// Type of the values, `None` for the zero-sized types.
+    fn value_type(&mut self, ty: Ty<'tcx>) -> Result<Option<Type>, CodegenError> {
+        let layout = self
+            .tcx
+            .layout_of(TypingEnv::fully_monomorphized().as_query_input(ty))
+            .map_err(|_| CodegenError::unsupported(format!("the layout of `{ty}`")))?;
+        if layout.is_zst() {
+            return Ok(None);
+        }
+        let r#type = match ty.kind() {
+            ty::Bool => self.ctx.integer_type(1),
+            ty::Int(_) | ty::Uint(_) | ty::Char => self.ctx.integer_type(layout.size.bits() as u32),
+            ty::Ref(_, pointee, _) | ty::RawPtr(pointee, _) => match self.value_type(*pointee)? {
+                Some(element) if element.is_integer(self.ctx) => self.ctx.memref_type(&[], element),
+                _ => return Err(CodegenError::unsupported(format!("the `{ty}` values"))),
+            },
+            _ => return Err(CodegenError::unsupported(format!("the `{ty}` values"))),
+        };
+        Ok(Some(r#type))
+    }
+
+    /// `function_type` of the arguments and of the result, the zero-sized ones are left out.
+    fn function_type(&mut self, inputs: &[Ty<'tcx>], output: Ty<'tcx>) -> Result<Type, CodegenError> {
+        let mut types = vec![];
+        for input in inputs {
+            types.extend(self.value_type(*input)?);
+        }
+        let results: Vec<Type> = self.value_type(output)?.into_iter().collect();
+        Ok(self.ctx.function_type(&types, &results))
+    }
+
+    /// `func.func` of the symbol, declared as a private one on the first use.
+    fn declare(&mut self, symbol: &str, function_type: Type, location: Attribute) -> Operation {
+        if let Some(function) = self.functions.get(symbol) {
+            return *function;
+        }
+        let ctx = &mut *self.ctx;
+        let mut state = OperationState::new("func.func");
+        let sym_name = ctx.string_attr(symbol);
+        let function_type = ctx.type_attr(function_type);
+        let private = ctx.string_attr("private");
+        state
+            .add_attribute("sym_name", sym_name)
+            .add_attribute("function_type", function_type)
+            .add_attribute("sym_visibility", private)
+            .add_regions(1)
+            .set_location(location);
+        let function = ctx.create_operation(state);
+        ctx.insert_operation(self.body, None, function)
+            .expect("the function is a new operation");
+        self.functions.insert(symbol.to_string(), function);
+        function
+    }
+
+    /// Defines the function of the instance, the internal ones are private to the module.
+    fn define(&mut self, instance: Instance<'tcx>, data: MonoItemData) -> Result<(), (Span, CodegenError)> {
+        let tcx = self.tcx;
+        let span = tcx.def_span(instance.def_id());
+        let mir = tcx.instance_mir(instance.def);
+        let unsupported = |what: &str| Err((span, CodegenError::unsupported(what)));
+        if mir.spread_arg.is_some() {
+            return unsupported("the `rust-call` ABI");
+        }
+        if instance.def.requires_caller_location(tcx) {
+            return unsupported("the `#[track_caller]` functions");
+        }
+        let monomorphize = |ty| monomorphize(tcx, instance, ty);
+        let inputs: Vec<Ty<'tcx>> = mir
+            .args_iter()
+            .map(|local| monomorphize(mir.local_decls[local].ty))
+            .collect();
+        let function_type = self
+            .function_type(&inputs, monomorphize(mir.return_ty()))
+            .map_err(|error| (span, error))?;
+        let location = self.locations.location(self.ctx, span);
+        let function = self.declare(tcx.symbol_name(instance).name, function_type, location);
+        self.ctx.set_location(function, location);
+        if !matches!(data.linkage, Linkage::Internal | Linkage::Private) {
+            self.ctx.remove_attribute(function, "sym_visibility");
+        }
+        let mut lowering = FunctionLowering {
+            module: self,
+            instance,
+            mir,
+            function,
+            slots: vec![],
+            live_ins: vec![],
+            blocks: vec![],
+            values: vec![],
+            block: None,
+            span,
+            location,
+        };
+        lowering.lower().map_err(|error| (lowering.span, error))
+    }
+}
+
+/// Storage of the MIR local.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    /// Zero-sized local without a value.
+    None,
+    /// Local promoted to the SSA values.
+    Value(Type),
+    /// Address-taken local, the `memref.alloca` of its value.
+    Memory(Value),
+}
+
+struct FunctionLowering<'m, 'l, 'tcx> {
+    module: &'m mut ModuleLowering<'l, 'tcx>,
+    instance: Instance<'tcx>,
+    mir: &'tcx Body<'tcx>,
+    function: Operation,
+    slots: Vec<Slot>,
+    /// Promoted locals the blocks take as their arguments.
+    live_ins: Vec<Vec<Local>>,
+    blocks: Vec<Block>,
+    /// Values of the promoted locals in the lowered block.
+    values: Vec<Option<Value>>,
+    /// Block the operations are appended to.
+    block: Option<Block>,
+    /// Span and location of the lowered statement.
+    span: Span,
+    location: Attribute,
+}
+
+impl<'tcx> FunctionLowering<'_, '_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.module.tcx
+    }
+
+    fn ctx(&mut self) -> &mut Context {
+        self.module.ctx
+    }
+
+    fn monomorphize<T: TypeFoldable<TyCtxt<'tcx>>>(&self, value: T) -> T {
+        monomorphize(self.tcx(), self.instance, value)
+    }
+
+    fn locate(&mut self, span: Span) {
+        self.span = span;
+        self.location = self.module.locations.location(self.module.ctx, span);
+    }
+
+    /// Appends the operation at the lowered statement location.
+    fn insert(&mut self, mut state: OperationState) -> Operation {
+        state.set_location(self.location);
+        let block = self.block.expect("the operations are lowered into a block");
+        let op = self.ctx().create_operation(state);
+        self.ctx()
+            .insert_operation(block, None, op)
+            .expect("the operation is a new one");
+        op
+    }
+
+    fn value(&mut self, name: &str, operands: &[Value], r#type: Type) -> Value {
+        let mut state = OperationState::new(name);
+        state.add_operands(operands.iter().copied()).add_types([r#type]);
+        let op = self.insert(state);
+        op.result(self.ctx(), 0)
+    }
+
+    fn constant(&mut self, r#type: Type, value: i128) -> Value {
+        let mut state = OperationState::new("arith.constant");
+        let value = self.ctx().integer_attr(value, r#type);
+        state.add_attribute("value", value).add_types([r#type]);
+        let op = self.insert(state);
+        op.result(self.ctx(), 0)
+    }
+
+    /// `array<i32: ...>` attribute.
+    fn i32_array(&mut self, values: &[usize]) -> Attribute {
+        let i32 = self.ctx().integer_type(32);
+        let values: Vec<Attribute> = values
+            .iter()
+            .map(|value| self.ctx().integer_attr(*value as i128, i32))
+            .collect();
+        self.ctx().dense_array_attr(i32, &values)
+    }
+
+    fn lower(&mut self) -> Result<(), CodegenError> {
+        let mir = self.mir;
+        let mut address_taken = vec![false; mir.local_decls.len()];
+        for data in mir.basic_blocks.iter() {
+            for statement in &data.statements {
+                if let StatementKind::Assign(assign) = &statement.kind {
+                    if let Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) = &assign.1 {
+                        if place.projection.is_empty() {
+                            address_taken[place.local.as_usize()] = true;
+                        }
+                    }
+                }
+            }
+        }
+        let mut types = vec![];
+        for decl in mir.local_decls.iter() {
+            self.locate(decl.source_info.span);
+            types.push(self.module.value_type(self.monomorphize(decl.ty))?);
+        }
+        let promoted: Vec<bool> = types
+            .iter()
+            .zip(&address_taken)
+            .map(|(r#type, address_taken)| r#type.is_some() && !address_taken)
+            .collect();
+        self.live_ins = live_ins(mir, &promoted);
+        self.values = vec![None; types.len()];
+
+        // The MIR `bb0` is the entry block unless it's a branch target.
+        let region = self.function.region(self.module.ctx, 0);
+        let inputs: Vec<Type> = mir.args_iter().filter_map(|local| types[local.as_usize()]).collect();
+        let bb0_is_entry = mir.basic_blocks.predecessors()[START_BLOCK].is_empty();
+        let entry = match bb0_is_entry {
+            true => None,
+            false => {
+                let entry = self.ctx().create_block(&inputs);
+                self.ctx().append_block(region, entry);
+                Some(entry)
+            }
+        };
+        for block in mir.basic_blocks.indices() {
+            let arguments: Vec<Type> = match block == START_BLOCK && bb0_is_entry {
+                true => inputs.clone(),
+                false => self.live_ins[block.as_usize()]
+                    .iter()
+                    .filter_map(|local| types[local.as_usize()])
+                    .collect(),
+            };
+            let block = self.ctx().create_block(&arguments);
+            self.ctx().append_block(region, block);
+            self.blocks.push(block);
+        }
+
+        let entry = entry.unwrap_or(self.blocks[0]);
+        self.block = Some(entry);
+        self.locate(mir.span);
+        for (local, r#type) in types.iter().enumerate() {
+            let slot = match (r#type, address_taken[local]) {
+                (None, _) => Slot::None,
+                (Some(r#type), false) => Slot::Value(*r#type),
+                (Some(r#type), true) => {
+                    let memref = self.ctx().memref_type(&[], *r#type);
+                    let segments = self.i32_array(&[0, 0]);
+                    let mut state = OperationState::new("memref.alloca");
+                    state.add_attribute("operandSegmentSizes", segments).add_types([memref]);
+                    let op = self.insert(state);
+                    Slot::Memory(op.result(self.ctx(), 0))
+                }
+            };
+            self.slots.push(slot);
+        }
+        let arguments: Vec<Value> = entry.arguments(self.module.ctx).to_vec();
+        let mut arguments = arguments.into_iter();
+        for local in mir.args_iter() {
+            if types[local.as_usize()].is_some() {
+                let argument = arguments.next().expect("the arguments are the entry block ones");
+                self.assign_local(local, argument);
+            }
+        }
+        if !bb0_is_entry {
+            self.branch(START_BLOCK);
+        }
+
+        for (block, data) in mir.basic_blocks.iter_enumerated() {
+            if block != START_BLOCK || !bb0_is_entry {
+                self.enter(block);
+            }
+            for statement in &data.statements {
+                self.statement(statement)?;
+            }
+            self.terminator(data.terminator())?;
+        }
+        Ok(())
+    }
+
+    /// Starts lowering the block, its arguments are the values of the locals live in it.
+    fn enter(&mut self, block: BasicBlock) {
+        let lowered = self.blocks[block.as_usize()];
+        self.block = Some(lowered);
+        self.values.fill(None);
+        let arguments: Vec<Value> = lowered.arguments(self.module.ctx).to_vec();
+        for (local, argument) in self.live_ins[block.as_usize()].iter().zip(arguments) {
+            self.values[local.as_usize()] = Some(argument);
+        }
+    }
+
+    /// Values of the locals the target block takes, the ones not yet assigned are poisoned.
+    fn successor_operands(&mut self, target: BasicBlock) -> Vec<Value> {
+        let live_ins = self.live_ins[target.as_usize()].clone();
+        live_ins
+            .into_iter()
+            .map(
+                |local| match (self.values[local.as_usize()], self.slots[local.as_usize()]) {
+                    (Some(value), _) => value,
+                    (None, Slot::Value(r#type)) => self.value("ub.poison", &[], r#type),
+                    (None, slot) => unreachable!("only the promoted locals are live in, not the {slot:?} ones"),
+                },
+            )
+            .collect()
+    }
+
+    fn branch(&mut self, target: BasicBlock) {
+        let operands = self.successor_operands(target);
+        let mut state = OperationState::new("cf.br");
+        state
+            .add_operands(operands)
+            .add_successors([self.blocks[target.as_usize()]]);
+        self.insert(state);
+    }
+
+    fn cond_branch(&mut self, condition: Value, on_true: BasicBlock, on_false: BasicBlock) {
+        let true_operands = self.successor_operands(on_true);
+        let false_operands = self.successor_operands(on_false);
+        let segments = self.i32_array(&[1, true_operands.len(), false_operands.len()]);
+        let mut state = OperationState::new("cf.cond_br");
+        state
+            .add_operands([condition])
+            .add_operands(true_operands)
+            .add_operands(false_operands)
+            .add_successors([self.blocks[on_true.as_usize()], self.blocks[on_false.as_usize()]])
+            .add_attribute("operandSegmentSizes", segments);
+        self.insert(state);
+    }
+
+    fn assign_local(&mut self, local: Local, value: Value) {
+        match self.slots[local.as_usize()] {
+            Slot::None => {}
+            Slot::Value(_) => self.values[local.as_usize()] = Some(value),
+            Slot::Memory(memref) => {
+                let mut state = OperationState::new("memref.store");
+                state.add_operands([value, memref]);
+                self.insert(state);
+            }
+        }
+    }
+
+    fn read_local(&mut self, local: Local) -> Option<Value> {
+        match self.slots[local.as_usize()] {
+            Slot::None => None,
+            Slot::Value(r#type) => Some(match self.values[local.as_usize()] {
+                Some(value) => value,
+                None => self.value("ub.poison", &[], r#type),
+            }),
+            Slot::Memory(memref) => Some(self.load(memref)),
+        }
+    }
+
+    fn load(&mut self, memref: Value) -> Value {
+        let ctx = &*self.module.ctx;
+        let element = memref
+            .r#type(ctx)
+            .element_type(ctx)
+            .expect("the memref has an element type");
+        self.value("memref.load", &[memref], element)
+    }
+
+    /// Memref the reference to the `*pointer` place is, also the one of the `*pointer` place itself.
+    fn pointer(&mut self, place: &Place<'tcx>) -> Result<Value, CodegenError> {
+        self.read_local(place.local)
+            .ok_or_else(|| CodegenError::unsupported("the zero-sized pointers"))
+    }
+
+    /// Value of the place, `None` for the zero-sized places.
+    fn read(&mut self, place: &Place<'tcx>) -> Result<Option<Value>, CodegenError> {
+        match place.projection.as_slice() {
+            [] => Ok(self.read_local(place.local)),
+            [ProjectionElem::Deref] => {
+                let ty = self.monomorphize(place.ty(self.mir, self.tcx()).ty);
+                if self.module.value_type(ty)?.is_none() {
+                    return Ok(None);
+                }
+                let pointer = self.pointer(place)?;
+                Ok(Some(self.load(pointer)))
+            }
+            _ => Err(CodegenError::unsupported("the place projections")),
+        }
+    }
+
+    fn write(&mut self, place: &Place<'tcx>, value: Value) -> Result<(), CodegenError> {
+        match place.projection.as_slice() {
+            [] => self.assign_local(place.local, value),
+            [ProjectionElem::Deref] => {
+                let pointer = self.pointer(place)?;
+                let mut state = OperationState::new("memref.store");
+                state.add_operands([value, pointer]);
+                self.insert(state);
+            }
+            _ => return Err(CodegenError::unsupported("the place projections")),
+        }
+        Ok(())
+    }
+
+    fn operand(&mut self, operand: &Operand<'tcx>) -> Result<Option<Value>, CodegenError> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.read(place),
+            Operand::Constant(constant) => {
+                let tcx = self.tcx();
+                let value = self.monomorphize(constant.const_);
+                let ty = value.ty();
+                let Some(r#type) = self.module.value_type(ty)? else {
+                    return Ok(None);
+                };
+                let scalar = value
+                    .eval(tcx, TypingEnv::fully_monomorphized(), constant.span)
+                    .ok()
+                    .and_then(|value| value.try_to_scalar_int())
+                    .filter(|_| r#type.is_integer(self.module.ctx))
+                    .ok_or_else(|| CodegenError::unsupported(format!("the `{ty}` constants")))?;
+                let width = scalar.size().bits() as u32;
+                Ok(Some(
+                    self.constant(r#type, sign_extend(scalar.to_bits_unchecked(), width)),
+                ))
+            }
+        }
+    }
+
+    /// Value of the operand, the zero-sized ones aren't expected.
+    fn scalar_operand(&mut self, operand: &Operand<'tcx>) -> Result<Value, CodegenError> {
+        self.operand(operand)?
+            .ok_or_else(|| CodegenError::unsupported("the zero-sized operands"))
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        self.monomorphize(operand.ty(self.mir, self.tcx()))
+    }
+
+    fn statement(&mut self, statement: &Statement<'tcx>) -> Result<(), CodegenError> {
+        self.locate(statement.source_info.span);
+        match &statement.kind {
+            StatementKind::Assign(assign) => {
+                let (place, rvalue) = &**assign;
+                let ty = self.monomorphize(place.ty(self.mir, self.tcx()).ty);
+                let Some(r#type) = self.module.value_type(ty)? else {
+                    return Ok(());
+                };
+                let value = self.rvalue(rvalue, r#type)?;
+                self.write(place, value)
+            }
+            StatementKind::StorageLive(_)
+            | StatementKind::StorageDead(_)
+            | StatementKind::FakeRead(_)
+            | StatementKind::Retag(..)
+            | StatementKind::PlaceMention(_)
+            | StatementKind::AscribeUserType(..)
+            | StatementKind::Coverage(_)
+            | StatementKind::ConstEvalCounter
+            | StatementKind::BackwardIncompatibleDropHint { .. }
+            | StatementKind::Nop => Ok(()),
+            kind => Err(CodegenError::unsupported(format!("the `{kind:?}` statement"))),
+        }
+    }
+
+    fn rvalue(&mut self, rvalue: &Rvalue<'tcx>, r#type: Type) -> Result<Value, CodegenError> {
+        match rvalue {
+            Rvalue::Use(operand) => self.scalar_operand(operand),
+            Rvalue::CopyForDeref(place) => self
+                .read(place)?
+                .ok_or_else(|| CodegenError::unsupported("the zero-sized operands")),
+            Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => match place.projection.as_slice() {
+                [] => match self.slots[place.local.as_usize()] {
+                    Slot::Memory(memref) => Ok(memref),
+                    slot => unreachable!("the address-taken locals are in memory, not in the {slot:?} ones"),
+                },
+                // Reborrowing is taking the same memref.
+                [ProjectionElem::Deref] => self.pointer(place),
+                _ => Err(CodegenError::unsupported("the references to the place projections")),
+            },
+            Rvalue::BinaryOp(op, operands) => {
+                let (lhs, rhs) = &**operands;
+                self.binary(*op, lhs, rhs)
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let value = self.scalar_operand(operand)?;
+                match op {
+                    UnOp::Not => {
+                        let ones = self.constant(r#type, -1);
+                        Ok(self.value("arith.xori", &[value, ones], r#type))
+                    }
+                    UnOp::Neg => {
+                        let zero = self.constant(r#type, 0);
+                        Ok(self.value("arith.subi", &[zero, value], r#type))
+                    }
+                    UnOp::PtrMetadata => Err(CodegenError::unsupported("the pointer metadata")),
+                }
+            }
+            Rvalue::Cast(CastKind::IntToInt, operand, _) => {
+                let signed = self.operand_ty(operand).is_signed();
+                let value = self.scalar_operand(operand)?;
+                Ok(self.resize(value, signed, r#type))
+            }
+            Rvalue::Cast(CastKind::PtrToPtr, operand, _) => {
+                let value = self.scalar_operand(operand)?;
+                match value.r#type(self.module.ctx) == r#type {
+                    true => Ok(value),
+                    false => Err(CodegenError::unsupported("the pointer casts to other types")),
+                }
+            }
+            rvalue => Err(CodegenError::unsupported(format!("the `{rvalue:?}` rvalue"))),
+        }
+    }
+
+    /// Truncates or extends the integer value to the type.
+    fn resize(&mut self, value: Value, signed: bool, r#type: Type) -> Value {
+        let ctx = &*self.module.ctx;
+        let from = value.r#type(ctx).int_or_float_width(ctx);
+        let to = r#type.int_or_float_width(ctx);
+        let name = match (from, to) {
+            (Some(from), Some(to)) if from > to => "arith.trunci",
+            (Some(from), Some(to)) if from < to && signed => "arith.extsi",
+            (Some(from), Some(to)) if from < to => "arith.extui",
+            _ => return value,
+        };
+        self.value(name, &[value], r#type)
+    }
+
+    fn binary(&mut self, op: BinOp, lhs: &Operand<'tcx>, rhs: &Operand<'tcx>) -> Result<Value, CodegenError> {
+        let signed = self.operand_ty(lhs).is_signed();
+        let lhs = self.scalar_operand(lhs)?;
+        let mut rhs = self.scalar_operand(rhs)?;
+        let r#type = lhs.r#type(self.module.ctx);
+        let predicate = match (op, signed) {
+            (BinOp::Eq, _) => Some(EQ),
+            (BinOp::Ne, _) => Some(NE),
+            (BinOp::Lt, true) => Some(SLT),
+            (BinOp::Le, true) => Some(SLE),
+            (BinOp::Gt, true) => Some(SGT),
+            (BinOp::Ge, true) => Some(SGE),
+            (BinOp::Lt, false) => Some(ULT),
+            (BinOp::Le, false) => Some(ULE),
+            (BinOp::Gt, false) => Some(UGT),
+            (BinOp::Ge, false) => Some(UGE),
+            _ => None,
+        };
+        if let Some(predicate) = predicate {
+            if !r#type.is_integer(self.module.ctx) {
+                return Err(CodegenError::unsupported("the pointer comparisons"));
+            }
+            let i1 = self.ctx().integer_type(1);
+            let i64 = self.ctx().integer_type(64);
+            let mut state = OperationState::new("arith.cmpi");
+            let predicate = self.ctx().integer_attr(predicate, i64);
+            state
+                .add_operands([lhs, rhs])
+                .add_attribute("predicate", predicate)
+                .add_types([i1]);
+            let op = self.insert(state);
+            return Ok(op.result(self.ctx(), 0));
+        }
+        let name = match (op, signed) {
+            (BinOp::Add | BinOp::AddUnchecked, _) => "arith.addi",
+            (BinOp::Sub | BinOp::SubUnchecked, _) => "arith.subi",
+            (BinOp::Mul | BinOp::MulUnchecked, _) => "arith.muli",
+            (BinOp::Div, true) => "arith.divsi",
+            (BinOp::Div, false) => "arith.divui",
+            (BinOp::Rem, true) => "arith.remsi",
+            (BinOp::Rem, false) => "arith.remui",
+            (BinOp::BitAnd, _) => "arith.andi",
+            (BinOp::BitOr, _) => "arith.ori",
+            (BinOp::BitXor, _) => "arith.xori",
+            (BinOp::Shl | BinOp::ShlUnchecked, _) => "arith.shli",
+            (BinOp::Shr | BinOp::ShrUnchecked, true) => "arith.shrsi",
+            (BinOp::Shr | BinOp::ShrUnchecked, false) => "arith.shrui",
+            (op, _) => return Err(CodegenError::unsupported(format!("the `{op:?}` operator"))),
+        };
+        if matches!(op, BinOp::Shl | BinOp::ShlUnchecked | BinOp::Shr | BinOp::ShrUnchecked) {
+            // The shift amount of its own type is taken modulo the bit width of the shifted one.
+            rhs = self.resize(rhs, false, r#type);
+            if matches!(op, BinOp::Shl | BinOp::Shr) {
+                let width = r#type.int_or_float_width(self.module.ctx).unwrap_or_default();
+                let mask = self.constant(r#type, i128::from(width) - 1);
+                rhs = self.value("arith.andi", &[rhs, mask], r#type);
+            }
+        }
+        Ok(self.value(name, &[lhs, rhs], r#type))
+    }
+
+    fn terminator(&mut self, terminator: &Terminator<'tcx>) -> Result<(), CodegenError> {
+        self.locate(terminator.source_info.span);
+        match &terminator.kind {
+            TerminatorKind::Goto { target } => self.branch(*target),
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let flag = self.scalar_operand(discr)?;
+                let r#type = flag.r#type(self.module.ctx);
+                let width = r#type.int_or_float_width(self.module.ctx).unwrap_or_default();
+                // The `if`s switch on the `false` value of their condition.
+                match targets.iter().collect::<Vec<_>>()[..] {
+                    [(0, on_false)] if width == 1 => self.cond_branch(flag, targets.otherwise(), on_false),
+                    [(1, on_true)] if width == 1 => self.cond_branch(flag, on_true, targets.otherwise()),
+                    _ => self.switch(flag, r#type, width, targets.iter(), targets.otherwise()),
+                }
+            }
+            TerminatorKind::Return => {
+                let operands: Vec<Value> = self.read_local(RETURN_PLACE).into_iter().collect();
+                let mut state = OperationState::new("func.return");
+                state.add_operands(operands);
+                self.insert(state);
+            }
+            TerminatorKind::Unreachable => self.unreachable("entered unreachable code"),
+            TerminatorKind::UnwindResume | TerminatorKind::UnwindTerminate(_) => {
+                self.unreachable("unwinding is not supported")
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+                ..
+            } => {
+                self.call(func, args.iter().map(|arg| &arg.node), destination)?;
+                match target {
+                    Some(target) => self.branch(*target),
+                    None => self.unreachable("the diverging call returned"),
+                }
+            }
+            TerminatorKind::Assert {
+                cond,
+                expected,
+                msg,
+                target,
+                ..
+            } => {
+                let mut condition = self.scalar_operand(cond)?;
+                if !expected {
+                    let i1 = condition.r#type(self.module.ctx);
+                    let ones = self.constant(i1, 1);
+                    condition = self.value("arith.xori", &[condition, ones], i1);
+                }
+                let message = self.ctx().string_attr(assert_message(msg));
+                let mut state = OperationState::new("cf.assert");
+                state.add_operands([condition]).add_attribute("msg", message);
+                self.insert(state);
+                self.branch(*target);
+            }
+            TerminatorKind::Drop { place, target, .. } => {
+                let ty = self.monomorphize(place.ty(self.mir, self.tcx()).ty);
+                if ty.needs_drop(self.tcx(), TypingEnv::fully_monomorphized()) {
+                    return Err(CodegenError::unsupported(format!("the drop glue of `{ty}`")));
+                }
+                self.branch(*target);
+            }
+            kind => return Err(CodegenError::unsupported(format!("the `{}` terminator", kind.name()))),
+        }
+        Ok(())
+    }
+
+    /// Terminates the block the control never leaves: the always failing `cf.assert` aborts, and the `func.return`
+    /// of the poisoned result keeps the block terminated without the `llvm` dialect.
+    fn unreachable(&mut self, message: &str) {
+        let i1 = self.ctx().integer_type(1);
+        let never = self.constant(i1, 0);
+        let message = self.ctx().string_attr(message);
+        let mut state = OperationState::new("cf.assert");
+        state.add_operands([never]).add_attribute("msg", message);
+        self.insert(state);
+
+        let ctx = &*self.module.ctx;
+        let result = match self.slots[RETURN_PLACE.as_usize()] {
+            Slot::None => None,
+            Slot::Value(r#type) => Some(r#type),
+            Slot::Memory(memref) => memref.r#type(ctx).element_type(ctx),
+        };
+        let operands: Vec<Value> = result
+            .map(|r#type| self.value("ub.poison", &[], r#type))
+            .into_iter()
+            .collect();
+        let mut state = OperationState::new("func.return");
+        state.add_operands(operands);
+        self.insert(state);
+    }
+
+    fn switch(
+        &mut self,
+        flag: Value,
+        r#type: Type,
+        width: u32,
+        cases: impl Iterator<Item = (u128, BasicBlock)>,
+        otherwise: BasicBlock,
+    ) {
+        let default_operands = self.successor_operands(otherwise);
+        let mut successors = vec![self.blocks[otherwise.as_usize()]];
+        let mut values = vec![];
+        let mut case_operands = vec![];
+        let mut case_segments = vec![];
+        for (value, target) in cases {
+            values.push(self.ctx().integer_attr(sign_extend(value, width), r#type));
+            successors.push(self.blocks[target.as_usize()]);
+            let operands = self.successor_operands(target);
+            case_segments.push(operands.len());
+            case_operands.extend(operands);
+        }
+        let vector = self.ctx().vector_type(&[values.len() as i64], r#type);
+        let case_values = self.ctx().dense_elements_attr(vector, &values);
+        let case_segments = self.i32_array(&case_segments);
+        let segments = self.i32_array(&[1, default_operands.len(), case_operands.len()]);
+        let mut state = OperationState::new("cf.switch");
+        state
+            .add_operands([flag])
+            .add_operands(default_operands)
+            .add_operands(case_operands)
+            .add_successors(successors)
+            .add_attribute("case_values", case_values)
+            .add_attribute("case_operand_segments", case_segments)
+            .add_attribute("operandSegmentSizes", segments);
+        self.insert(state);
+    }
+
+    /// Calls the function resolved out of the `FnDef` operand, assigning the result to the destination.
+    fn call<'a>(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: impl Iterator<Item = &'a Operand<'tcx>>,
+        destination: &Place<'tcx>,
+    ) -> Result<(), CodegenError>
+    where
+        'tcx: 'a,
+    {
+        let tcx = self.tcx();
+        let func_ty = self.operand_ty(func);
+        let ty::FnDef(def_id, generic_args) = *func_ty.kind() else {
+            return Err(CodegenError::unsupported("the indirect calls"));
+        };
+        let instance = Instance::expect_resolve(tcx, TypingEnv::fully_monomorphized(), def_id, generic_args, self.span)
+            .polymorphize(tcx);
+        match instance.def {
+            InstanceKind::Intrinsic(_) => {
+                return Err(CodegenError::unsupported(format!(
+                    "the `{}` intrinsic",
+                    tcx.item_name(def_id)
+                )))
+            }
+            InstanceKind::Virtual(..) => return Err(CodegenError::unsupported("the virtual calls")),
+            _ if instance.def.requires_caller_location(tcx) => {
+                return Err(CodegenError::unsupported("the `#[track_caller]` functions"))
+            }
+            _ => {}
+        }
+        let sig = tcx.instantiate_bound_regions_with_erased(func_ty.fn_sig(tcx));
+        if sig.c_variadic {
+            return Err(CodegenError::unsupported("the variadic calls"));
+        }
+        let function_type = self.module.function_type(sig.inputs(), sig.output())?;
+        let symbol = tcx.symbol_name(instance).name;
+        self.module.declare(symbol, function_type, self.location);
+        let mut operands = vec![];
+        for arg in args {
+            operands.extend(self.operand(arg)?);
+        }
+        let results = self.module.value_type(sig.output())?;
+        let mut state = OperationState::new("func.call");
+        let callee = self.ctx().symbol_ref_attr(symbol);
+        state
+            .add_operands(operands)
+            .add_attribute("callee", callee)
+            .add_types(results);
+        let call = self.insert(state);
+        if let Some(result) = call.results(self.module.ctx).first().copied() {
+            self.write(destination, result)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rustc_interface;
+
+    use rustc_driver::{Callbacks, Compilation, RunCompiler};
+    use rustc_interface::interface::{Compiler, Config};
+    use rustc_interface::Queries;
+    use rustc_session::config::Input;
+    use rustc_span::FileName;
+
+    use super::*;
+    use crate::ir::{print, verify, DialectRegistry, PrinterOptions};
+    use crate::targets::register_dialects;
+
+    /// Compiles the `no_std` crate up to the analysis in process and prints its verified `func` module.
+    struct Lowering {
+        source: String,
+        module: Option<String>,
+    }
+
+    impl Callbacks for Lowering {
+        fn config(&mut self, config: &mut Config) {
+            config.input = Input::Str {
+                name: FileName::Custom("lib.rs".to_string()),
+                input: format!("#![no_std]\n{}", self.source),
+            };
+        }
+
+        fn after_analysis<'tcx>(&mut self, _compiler: &Compiler, queries: &'tcx Queries<'tcx>) -> Compilation {
+            queries.global_ctxt().unwrap().enter(|tcx| {
+                let cgus = tcx.collect_and_partition_mono_items(()).1;
+                let [cgu] = cgus else {
+                    panic!("a single codegen unit is expected, got {}", cgus.len());
+                };
+                let mut dialects = DialectRegistry::default();
+                register_dialects(&mut dialects);
+                let mut ctx = Context::with_dialects(dialects);
+                let locations = SourceLocations::new(tcx.sess.source_map());
+                let module = lower_codegen_unit(tcx, &mut ctx, &locations, cgu).expect("the crate is lowered");
+                verify(&ctx, module).unwrap();
+                self.module = Some(print(&ctx, module, PrinterOptions::default()));
+            });
+            Compilation::Stop
+        }
+    }
+
+    fn lower(source: &str) -> String {
+        let args: Vec<String> = [
+            "rustc",
+            "lib.rs",
+            "--crate-type=rlib",
+            "-Cpanic=abort",
+            "-Coverflow-checks=off",
+            "-Ccodegen-units=1",
+        ]
+        .map(String::from)
+        .to_vec();
+        let mut lowering = Lowering {
+            source: source.to_string(),
+            module: None,
+        };
+        RunCompiler::new(&args, &mut lowering).run().unwrap();
+        lowering.module.unwrap()
+    }
+
+    #[test]
+    fn should_lower_the_bool_switches_into_cond_br() {
+        let module = lower(
+            "#[no_mangle]\n\
+             pub fn pick(flag: bool, a: i32, b: i32) -> i32 { if flag { a } else { b } }\n",
+        );
+        assert!(module.contains("\"cf.cond_br\"(%arg0, "), "{module}");
+        assert!(!module.contains("cf.switch"), "{module}");
+    }
+
+    #[test]
+    fn should_lower_the_integer_switches_into_cf_switch() {
+        let module = lower(
+            "#[no_mangle]\n\
+             pub fn classify(value: i8) -> u8 { match value { -1 => 10, 0 => 20, 1 => 30, _ => 40 } }\n",
+        );
+        assert!(module.contains("\"cf.switch\"(%arg0)"), "{module}");
+        assert!(module.contains("case_values = dense<[-1, 0, 1]> : vector<3xi8>"), "{module}");
+        assert!(!module.contains("cf.cond_br"), "{module}");
+    }
+
+    #[test]
+    fn should_pass_the_live_locals_as_block_arguments() {
+        let module = lower(
+            "#[no_mangle]\n\
+             pub fn sum(mut n: u32) -> u32 { let mut acc = 0; while n > 0 { acc += n; n -= 1; } acc }\n",
+        );
+        // The loop header takes both the counter and the accumulator, and the back edge passes them along.
+        assert!(module.contains("\"cf.br\"(%arg0, %0)[^bb1] : (i32, i32) -> ()"), "{module}");
+        assert!(module.contains("^bb1(%1: i32, %2: i32):  // 2 preds: ^bb0, ^bb2"), "{module}");
+        assert!(module.contains("\"cf.br\"(%9, %7)[^bb1] : (i32, i32) -> ()"), "{module}");
+        // The exit block takes only the accumulator.
+        assert!(module.contains("^bb3(%10: i32):  // pred: ^bb1"), "{module}");
+    }
+
+    #[test]
+    fn should_mask_the_shift_amounts() {
+        let module = lower(
+            "#[no_mangle]\n\
+             pub fn shift(value: u64, amount: u8) -> u64 { value << amount }\n",
+        );
+        // The `u8` amount is extended to the shifted type and taken modulo its 64 bits.
+        assert!(module.contains("arith.extui %arg1 : i8 to i64"), "{module}");
+        assert!(module.contains("arith.constant 63 : i64"), "{module}");
+        assert!(module.contains("arith.andi"), "{module}");
+        assert!(module.contains("\"arith.shli\""), "{module}");
+    }
+
+    #[test]
+    fn should_lower_the_unreachable_code_into_a_failing_assert() {
+        let module = lower(
+            "extern \"C\" { fn abort() -> !; }\n\
+             #[no_mangle]\n\
+             pub fn stop(value: u16) -> u16 { if value == 0 { unsafe { abort() } } value }\n",
+        );
+        assert!(module.contains("{msg = \"the diverging call returned\"}"), "{module}");
+        assert!(module.contains("\"ub.poison\"() : () -> i16"), "{module}");
+        assert!(!module.contains("llvm."), "{module}");
+    }
+}
@@ -0,0 +1,768 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Lowering of the monomorphized MIR of the codegen unit functions into the `llvm.func`s of the `llvm` dialect.
+//! Every MIR local of a scalar type lives in its own `llvm.alloca` slot, loaded and stored around the statements
+//! like by the unoptimized LLVM backend, the LLVM `mem2reg` promotes them back to the SSA values.
+//! The zero-sized locals, arguments and operands have no values at all.
+//!
+
+use std::collections::HashMap;
+
+use rustc_middle::mir::mono::{CodegenUnit, Linkage, MonoItem, MonoItemData, Visibility};
+use rustc_middle::mir::{
+    BasicBlock, BinOp, Body, CastKind, Operand, Place, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+    UnOp,
+};
+use rustc_middle::ty::{self, EarlyBinder, Instance, InstanceKind, Ty, TyCtxt, TypeFoldable, TypingEnv};
+use rustc_span::Span;
+
+use crate::backend::CodegenError;
+use crate::diagnostics::SourceLocations;
+use crate::ir::{Attribute, AttributeKind, Block, Context, Operation, OperationState, Type, TypeKind, Value};
+
+/// `llvm.icmp` and `arith.cmpi` predicates, like the upstream `ICmpPredicate` enums.
+pub(super) const EQ: i128 = 0;
+pub(super) const NE: i128 = 1;
+pub(super) const SLT: i128 = 2;
+pub(super) const SLE: i128 = 3;
+pub(super) const SGT: i128 = 4;
+pub(super) const SGE: i128 = 5;
+pub(super) const ULT: i128 = 6;
+pub(super) const ULE: i128 = 7;
+pub(super) const UGT: i128 = 8;
+pub(super) const UGE: i128 = 9;
+
+/// Scalar types of the function arguments and of its result, the zero-sized ones are left out.
+struct Signature {
+    inputs: Vec<Type>,
+    result: Option<Type>,
+}
+
+/// Lowers the functions of the codegen unit into a new module, the unsupported constructs are reported
+/// as the errors at their spans, leaving no module.
+pub(crate) fn lower_codegen_unit<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ctx: &mut Context,
+    locations: &SourceLocations<'_>,
+    cgu: &CodegenUnit<'tcx>,
+) -> Option<Operation> {
+    let module = ctx.create_module();
+    let body = module.region(ctx, 0).entry_block(ctx).expect("the module has a body");
+    let mut lowering = ModuleLowering {
+        tcx,
+        ctx,
+        locations,
+        body,
+        functions: HashMap::new(),
+    };
+    let mut failed = false;
+    for (item, data) in cgu.items_in_deterministic_order(tcx) {
+        let result = match item {
+            MonoItem::Fn(instance) => lowering.define(instance, data),
+            MonoItem::Static(def_id) => Err((tcx.def_span(def_id), CodegenError::unsupported("the statics"))),
+            MonoItem::GlobalAsm(item) => Err((
+                tcx.def_span(item.owner_id.to_def_id()),
+                CodegenError::unsupported("the global assembly"),
+            )),
+        };
+        if let Err((span, error)) = result {
+            tcx.dcx().span_err(span, error.to_string());
+            failed = true;
+        }
+    }
+    (!failed).then_some(module)
+}
+
+struct ModuleLowering<'l, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    ctx: &'l mut Context,
+    locations: &'l SourceLocations<'l>,
+    body: Block,
+    /// `llvm.func`s of the symbols, both the defined and the declared ones.
+    functions: HashMap<String, Operation>,
+}
+
+impl<'tcx> ModuleLowering<'_, 'tcx> {
+    fn ptr_type(&mut self) -> Type {
+        self.ctx.opaque_type("llvm", "ptr")
+    }
+
+    /// Type of the scalar values, `None` for the zero-sized types.
+    fn scalar_type(&mut self, ty: Ty<'tcx>) -> Result<Option<Type>, CodegenError> {
+        let layout = self
+            .tcx
+            .layout_of(TypingEnv::fully_monomorphized().as_query_input(ty))
+            .map_err(|_| CodegenError::unsupported(format!("the layout of `{ty}`")))?;
+        if layout.is_zst() {
+            return Ok(None);
+        }
+        let scalar = match ty.kind() {
+            ty::Bool => self.ctx.integer_type(1),
+            ty::Int(_) | ty::Uint(_) | ty::Char => self.ctx.integer_type(layout.size.bits() as u32),
+            ty::Ref(..) | ty::RawPtr(..) | ty::FnPtr(..) if layout.size == self.tcx.data_layout.pointer_size => {
+                self.ptr_type()
+            }
+            _ => return Err(CodegenError::unsupported(format!("the `{ty}` values"))),
+        };
+        Ok(Some(scalar))
+    }
+
+    fn signature(&mut self, inputs: &[Ty<'tcx>], output: Ty<'tcx>) -> Result<Signature, CodegenError> {
+        let mut scalars = vec![];
+        for input in inputs {
+            scalars.extend(self.scalar_type(*input)?);
+        }
+        Ok(Signature {
+            inputs: scalars,
+            result: self.scalar_type(output)?,
+        })
+    }
+
+    /// `ptr`, `i32` like type within the `!llvm.func<...>` ones.
+    fn nested_type_text(&self, r#type: Option<Type>) -> String {
+        match r#type.map(|r#type| r#type.kind(self.ctx)) {
+            None => "void".to_string(),
+            Some(TypeKind::Integer { width, .. }) => format!("i{width}"),
+            Some(TypeKind::Opaque { data, .. }) => data.clone(),
+            Some(kind) => unreachable!("the scalar types are integers and pointers, not {kind:?}"),
+        }
+    }
+
+    /// `llvm.func` of the symbol, declared with the signature on the first use, the `i1` values are passed
+    /// zero extended like by the Rust and C ABIs.
+    fn declare(&mut self, symbol: &str, signature: &Signature, location: Attribute) -> Operation {
+        if let Some(function) = self.functions.get(symbol) {
+            return *function;
+        }
+        let inputs: Vec<String> = signature
+            .inputs
+            .iter()
+            .map(|input| self.nested_type_text(Some(*input)))
+            .collect();
+        let function_type = format!(
+            "func<{} ({})>",
+            self.nested_type_text(signature.result),
+            inputs.join(", ")
+        );
+        let ctx = &mut *self.ctx;
+        let mut state = OperationState::new("llvm.func");
+        let sym_name = ctx.string_attr(symbol);
+        let function_type = ctx.opaque_type("llvm", &function_type);
+        let function_type = ctx.type_attr(function_type);
+        let linkage = linkage_attr(ctx, "external");
+        state
+            .add_attribute("sym_name", sym_name)
+            .add_attribute("function_type", function_type)
+            .add_attribute("linkage", linkage)
+            .add_regions(1)
+            .set_location(location);
+        let zeroext = |ctx: &mut Context, r#type: &Type| {
+            let attributes = match r#type.is_signless_integer(ctx, 1) {
+                true => {
+                    let unit = ctx.unit_attr();
+                    vec![crate::ir::NamedAttribute::new("llvm.zeroext", unit)]
+                }
+                false => vec![],
+            };
+            ctx.dictionary_attr(attributes)
+        };
+        if signature.inputs.iter().any(|input| input.is_signless_integer(ctx, 1)) {
+            let arg_attrs: Vec<Attribute> = signature.inputs.iter().map(|input| zeroext(ctx, input)).collect();
+            let arg_attrs = ctx.array_attr(&arg_attrs);
+            state.add_attribute("arg_attrs", arg_attrs);
+        }
+        if let Some(result) = signature.result.filter(|result| result.is_signless_integer(ctx, 1)) {
+            let res_attrs = [zeroext(ctx, &result)];
+            let res_attrs = ctx.array_attr(&res_attrs);
+            state.add_attribute("res_attrs", res_attrs);
+        }
+        let function = ctx.create_operation(state);
+        ctx.insert_operation(self.body, None, function)
+            .expect("the function is a new operation");
+        self.functions.insert(symbol.to_string(), function);
+        function
+    }
+
+    /// Defines the function of the instance with the linkage and the visibility of the codegen unit item.
+    fn define(&mut self, instance: Instance<'tcx>, data: MonoItemData) -> Result<(), (Span, CodegenError)> {
+        let tcx = self.tcx;
+        let span = tcx.def_span(instance.def_id());
+        let mir = tcx.instance_mir(instance.def);
+        let unsupported = |what: &str| Err((span, CodegenError::unsupported(what)));
+        if mir.spread_arg.is_some() {
+            return unsupported("the `rust-call` ABI");
+        }
+        if instance.def.requires_caller_location(tcx) {
+            return unsupported("the `#[track_caller]` functions");
+        }
+        let monomorphize = |ty| monomorphize(tcx, instance, ty);
+        let inputs: Vec<Ty<'tcx>> = mir
+            .args_iter()
+            .map(|local| monomorphize(mir.local_decls[local].ty))
+            .collect();
+        let signature = self
+            .signature(&inputs, monomorphize(mir.return_ty()))
+            .map_err(|error| (span, error))?;
+        let location = self.locations.location(self.ctx, span);
+        let symbol = tcx.symbol_name(instance).name;
+        let function = self.declare(symbol, &signature, location);
+        self.ctx.set_location(function, location);
+        let linkage = linkage_attr(self.ctx, linkage_name(data.linkage));
+        self.ctx.set_attribute(function, "linkage", linkage);
+        let visibility = match data.visibility {
+            Visibility::Default => 0,
+            Visibility::Hidden => 1,
+            Visibility::Protected => 2,
+        };
+        if visibility != 0 {
+            let i64 = self.ctx.integer_type(64);
+            let visibility = self.ctx.integer_attr(visibility, i64);
+            self.ctx.set_attribute(function, "visibility_", visibility);
+        }
+        let mut lowering = FunctionLowering {
+            module: self,
+            instance,
+            mir,
+            function,
+            slots: vec![],
+            blocks: vec![],
+            block: None,
+            trap: None,
+            span,
+            location,
+        };
+        lowering.lower(&signature).map_err(|error| (lowering.span, error))
+    }
+}
+
+fn linkage_name(linkage: Linkage) -> &'static str {
+    match linkage {
+        Linkage::External => "external",
+        Linkage::AvailableExternally => "available_externally",
+        Linkage::LinkOnceAny => "linkonce",
+        Linkage::LinkOnceODR => "linkonce_odr",
+        Linkage::WeakAny => "weak",
+        Linkage::WeakODR => "weak_odr",
+        Linkage::Appending => "appending",
+        Linkage::Internal => "internal",
+        Linkage::Private => "private",
+        Linkage::ExternalWeak => "extern_weak",
+        Linkage::Common => "common",
+    }
+}
+
+/// `#llvm.linkage<internal>`
+fn linkage_attr(ctx: &mut Context, linkage: &str) -> Attribute {
+    ctx.get_attribute(AttributeKind::Opaque {
+        dialect: "llvm".to_string(),
+        data: format!("linkage<{linkage}>"),
+    })
+}
+
+pub(super) fn monomorphize<'tcx, T: TypeFoldable<TyCtxt<'tcx>>>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    value: T,
+) -> T {
+    instance.instantiate_mir_and_normalize_erasing_regions(
+        tcx,
+        TypingEnv::fully_monomorphized(),
+        EarlyBinder::bind(value),
+    )
+}
+
+/// Two's complement value of the `width` low bits, the `i1` ones are kept unsigned.
+pub(super) fn sign_extend(bits: u128, width: u32) -> i128 {
+    match width {
+        1 => (bits & 1) as i128,
+        _ => ((bits << (128 - width)) as i128) >> (128 - width),
+    }
+}
+
+struct FunctionLowering<'m, 'l, 'tcx> {
+    module: &'m mut ModuleLowering<'l, 'tcx>,
+    instance: Instance<'tcx>,
+    mir: &'tcx Body<'tcx>,
+    function: Operation,
+    /// `llvm.alloca` slots of the scalar locals, along with their types.
+    slots: Vec<Option<(Value, Type)>>,
+    blocks: Vec<Block>,
+    /// Block the operations are appended to.
+    block: Option<Block>,
+    /// Block the failed assertions branch to.
+    trap: Option<Block>,
+    /// Span and location of the lowered statement.
+    span: Span,
+    location: Attribute,
+}
+
+impl<'tcx> FunctionLowering<'_, '_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.module.tcx
+    }
+
+    fn ctx(&mut self) -> &mut Context {
+        self.module.ctx
+    }
+
+    fn monomorphize<T: TypeFoldable<TyCtxt<'tcx>>>(&self, value: T) -> T {
+        monomorphize(self.tcx(), self.instance, value)
+    }
+
+    fn locate(&mut self, span: Span) {
+        self.span = span;
+        self.location = self.module.locations.location(self.module.ctx, span);
+    }
+
+    /// Appends the operation at the lowered statement location.
+    fn insert(&mut self, mut state: OperationState) -> Operation {
+        state.set_location(self.location);
+        let block = self.block.expect("the operations are lowered into a block");
+        let op = self.ctx().create_operation(state);
+        self.ctx()
+            .insert_operation(block, None, op)
+            .expect("the operation is a new one");
+        op
+    }
+
+    fn value(&mut self, name: &str, operands: &[Value], r#type: Type) -> Value {
+        let mut state = OperationState::new(name);
+        state.add_operands(operands.iter().copied()).add_types([r#type]);
+        let op = self.insert(state);
+        op.result(self.ctx(), 0)
+    }
+
+    fn constant(&mut self, r#type: Type, value: i128) -> Value {
+        let mut state = OperationState::new("llvm.mlir.constant");
+        let value = self.ctx().integer_attr(value, r#type);
+        state.add_attribute("value", value).add_types([r#type]);
+        let op = self.insert(state);
+        op.result(self.ctx(), 0)
+    }
+
+    /// `array<i32: ...>` attribute.
+    fn i32_array(&mut self, values: &[i128]) -> Attribute {
+        let i32 = self.ctx().integer_type(32);
+        let values: Vec<Attribute> = values
+            .iter()
+            .map(|value| self.ctx().integer_attr(*value, i32))
+            .collect();
+        self.ctx().dense_array_attr(i32, &values)
+    }
+
+    fn branch(&mut self, name: &str, operands: &[Value], successors: &[Block]) {
+        let mut state = OperationState::new(name);
+        state
+            .add_operands(operands.iter().copied())
+            .add_successors(successors.iter().copied());
+        if name == "llvm.cond_br" {
+            // The successors take no operands, the values are passed through the slots.
+            let segments = self.i32_array(&[1, 0, 0]);
+            state.add_attribute("operandSegmentSizes", segments);
+        }
+        self.insert(state);
+    }
+
+    fn lower(&mut self, signature: &Signature) -> Result<(), CodegenError> {
+        let region = self.function.region(self.module.ctx, 0);
+        // The entry block allocates the slots, the MIR `bb0` may be a branch target.
+        let entry = self.ctx().create_block(&signature.inputs);
+        self.ctx().append_block(region, entry);
+        for _ in self.mir.basic_blocks.indices() {
+            let block = self.ctx().create_block(&[]);
+            self.ctx().append_block(region, block);
+            self.blocks.push(block);
+        }
+        self.block = Some(entry);
+        let i64 = self.ctx().integer_type(64);
+        let one = self.constant(i64, 1);
+        let ptr = self.module.ptr_type();
+        for decl in self.mir.local_decls.iter() {
+            self.locate(decl.source_info.span);
+            let slot = match self.module.scalar_type(self.monomorphize(decl.ty))? {
+                Some(r#type) => {
+                    let mut state = OperationState::new("llvm.alloca");
+                    let elem_type = self.ctx().type_attr(r#type);
+                    state
+                        .add_operands([one])
+                        .add_attribute("elem_type", elem_type)
+                        .add_types([ptr]);
+                    let op = self.insert(state);
+                    Some((op.result(self.ctx(), 0), r#type))
+                }
+                None => None,
+            };
+            self.slots.push(slot);
+        }
+        let arguments: Vec<Value> = entry.arguments(self.module.ctx).to_vec();
+        let mut arguments = arguments.into_iter();
+        for local in self.mir.args_iter() {
+            if let Some((slot, _)) = self.slots[local.as_usize()] {
+                let argument = arguments.next().expect("the scalar arguments are the entry block ones");
+                self.store(argument, slot);
+            }
+        }
+        let start = self.blocks[0];
+        self.branch("llvm.br", &[], &[start]);
+
+        for (index, data) in self.mir.basic_blocks.iter_enumerated() {
+            self.block = Some(self.blocks[index.as_usize()]);
+            for statement in &data.statements {
+                self.statement(statement)?;
+            }
+            self.terminator(data.terminator())?;
+        }
+        Ok(())
+    }
+
+    fn store(&mut self, value: Value, slot: Value) {
+        let mut state = OperationState::new("llvm.store");
+        state.add_operands([value, slot]);
+        self.insert(state);
+    }
+
+    /// Slot of the place and its type, `None` for the zero-sized places.
+    fn place(&self, place: &Place<'tcx>) -> Result<Option<(Value, Type)>, CodegenError> {
+        if !place.projection.is_empty() {
+            return Err(CodegenError::unsupported("the place projections"));
+        }
+        Ok(self.slots[place.local.as_usize()])
+    }
+
+    fn operand(&mut self, operand: &Operand<'tcx>) -> Result<Option<Value>, CodegenError> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => Ok(self
+                .place(place)?
+                .map(|(slot, r#type)| self.value("llvm.load", &[slot], r#type))),
+            Operand::Constant(constant) => {
+                let tcx = self.tcx();
+                let value = self.monomorphize(constant.const_);
+                let ty = value.ty();
+                let Some(r#type) = self.module.scalar_type(ty)? else {
+                    return Ok(None);
+                };
+                let scalar = value
+                    .eval(tcx, TypingEnv::fully_monomorphized(), constant.span)
+                    .ok()
+                    .and_then(|value| value.try_to_scalar_int())
+                    .filter(|_| r#type.is_integer(self.module.ctx))
+                    .ok_or_else(|| CodegenError::unsupported(format!("the `{ty}` constants")))?;
+                let width = scalar.size().bits() as u32;
+                Ok(Some(
+                    self.constant(r#type, sign_extend(scalar.to_bits_unchecked(), width)),
+                ))
+            }
+        }
+    }
+
+    /// Value of the operand, the zero-sized ones aren't expected.
+    fn scalar_operand(&mut self, operand: &Operand<'tcx>) -> Result<Value, CodegenError> {
+        self.operand(operand)?
+            .ok_or_else(|| CodegenError::unsupported("the zero-sized operands"))
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        self.monomorphize(operand.ty(self.mir, self.tcx()))
+    }
+
+    fn statement(&mut self, statement: &Statement<'tcx>) -> Result<(), CodegenError> {
+        self.locate(statement.source_info.span);
+        match &statement.kind {
+            StatementKind::Assign(assign) => {
+                let (place, rvalue) = &**assign;
+                let Some((slot, r#type)) = self.place(place)? else {
+                    return Ok(());
+                };
+                let value = self.rvalue(rvalue, r#type)?;
+                self.store(value, slot);
+                Ok(())
+            }
+            StatementKind::StorageLive(_)
+            | StatementKind::StorageDead(_)
+            | StatementKind::FakeRead(_)
+            | StatementKind::Retag(..)
+            | StatementKind::PlaceMention(_)
+            | StatementKind::AscribeUserType(..)
+            | StatementKind::Coverage(_)
+            | StatementKind::ConstEvalCounter
+            | StatementKind::BackwardIncompatibleDropHint { .. }
+            | StatementKind::Nop => Ok(()),
+            kind => Err(CodegenError::unsupported(format!("the `{kind:?}` statement"))),
+        }
+    }
+
+    fn rvalue(&mut self, rvalue: &Rvalue<'tcx>, r#type: Type) -> Result<Value, CodegenError> {
+        match rvalue {
+            Rvalue::Use(operand) => self.scalar_operand(operand),
+            Rvalue::BinaryOp(op, operands) => {
+                let (lhs, rhs) = &**operands;
+                self.binary(*op, lhs, rhs)
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let value = self.scalar_operand(operand)?;
+                match op {
+                    UnOp::Not => {
+                        let ones = self.constant(r#type, -1);
+                        Ok(self.value("llvm.xor", &[value, ones], r#type))
+                    }
+                    UnOp::Neg => {
+                        let zero = self.constant(r#type, 0);
+                        Ok(self.value("llvm.sub", &[zero, value], r#type))
+                    }
+                    UnOp::PtrMetadata => Err(CodegenError::unsupported("the pointer metadata")),
+                }
+            }
+            Rvalue::Cast(CastKind::IntToInt, operand, _) => {
+                let signed = self.operand_ty(operand).is_signed();
+                let value = self.scalar_operand(operand)?;
+                Ok(self.resize(value, signed, r#type))
+            }
+            rvalue => Err(CodegenError::unsupported(format!("the `{rvalue:?}` rvalue"))),
+        }
+    }
+
+    /// Truncates or extends the integer value to the type.
+    fn resize(&mut self, value: Value, signed: bool, r#type: Type) -> Value {
+        let ctx = &*self.module.ctx;
+        let from = value.r#type(ctx).int_or_float_width(ctx);
+        let to = r#type.int_or_float_width(ctx);
+        let name = match (from, to) {
+            (Some(from), Some(to)) if from > to => "llvm.trunc",
+            (Some(from), Some(to)) if from < to && signed => "llvm.sext",
+            (Some(from), Some(to)) if from < to => "llvm.zext",
+            _ => return value,
+        };
+        self.value(name, &[value], r#type)
+    }
+
+    fn binary(&mut self, op: BinOp, lhs: &Operand<'tcx>, rhs: &Operand<'tcx>) -> Result<Value, CodegenError> {
+        let signed = self.operand_ty(lhs).is_signed();
+        let lhs = self.scalar_operand(lhs)?;
+        let mut rhs = self.scalar_operand(rhs)?;
+        let r#type = lhs.r#type(self.module.ctx);
+        let predicate = match (op, signed) {
+            (BinOp::Eq, _) => Some(EQ),
+            (BinOp::Ne, _) => Some(NE),
+            (BinOp::Lt, true) => Some(SLT),
+            (BinOp::Le, true) => Some(SLE),
+            (BinOp::Gt, true) => Some(SGT),
+            (BinOp::Ge, true) => Some(SGE),
+            (BinOp::Lt, false) => Some(ULT),
+            (BinOp::Le, false) => Some(ULE),
+            (BinOp::Gt, false) => Some(UGT),
+            (BinOp::Ge, false) => Some(UGE),
+            _ => None,
+        };
+        if let Some(predicate) = predicate {
+            let i1 = self.ctx().integer_type(1);
+            let i64 = self.ctx().integer_type(64);
+            let mut state = OperationState::new("llvm.icmp");
+            let predicate = self.ctx().integer_attr(predicate, i64);
+            state
+                .add_operands([lhs, rhs])
+                .add_attribute("predicate", predicate)
+                .add_types([i1]);
+            let op = self.insert(state);
+            return Ok(op.result(self.ctx(), 0));
+        }
+        let name = match (op, signed) {
+            (BinOp::Add | BinOp::AddUnchecked, _) => "llvm.add",
+            (BinOp::Sub | BinOp::SubUnchecked, _) => "llvm.sub",
+            (BinOp::Mul | BinOp::MulUnchecked, _) => "llvm.mul",
+            (BinOp::Div, true) => "llvm.sdiv",
+            (BinOp::Div, false) => "llvm.udiv",
+            (BinOp::Rem, true) => "llvm.srem",
+            (BinOp::Rem, false) => "llvm.urem",
+            (BinOp::BitAnd, _) => "llvm.and",
+            (BinOp::BitOr, _) => "llvm.or",
+            (BinOp::BitXor, _) => "llvm.xor",
+            (BinOp::Shl | BinOp::ShlUnchecked, _) => "llvm.shl",
+            (BinOp::Shr | BinOp::ShrUnchecked, true) => "llvm.ashr",
+            (BinOp::Shr | BinOp::ShrUnchecked, false) => "llvm.lshr",
+            (op, _) => return Err(CodegenError::unsupported(format!("the `{op:?}` operator"))),
+        };
+        if matches!(op, BinOp::Shl | BinOp::ShlUnchecked | BinOp::Shr | BinOp::ShrUnchecked) {
+            // The shift amount of its own type is taken modulo the bit width of the shifted one.
+            rhs = self.resize(rhs, false, r#type);
+            if matches!(op, BinOp::Shl | BinOp::Shr) {
+                let width = r#type.int_or_float_width(self.module.ctx).unwrap_or_default();
+                let mask = self.constant(r#type, i128::from(width) - 1);
+                rhs = self.value("llvm.and", &[rhs, mask], r#type);
+            }
+        }
+        Ok(self.value(name, &[lhs, rhs], r#type))
+    }
+
+    /// Block calling the `llvm.trap`, the failed assertions branch to.
+    fn trap(&mut self) -> Block {
+        if let Some(trap) = self.trap {
+            return trap;
+        }
+        let region = self.function.region(self.module.ctx, 0);
+        let trap = self.ctx().create_block(&[]);
+        self.ctx().append_block(region, trap);
+        let block = self.block.replace(trap);
+        self.insert(OperationState::new("llvm.intr.trap"));
+        self.insert(OperationState::new("llvm.unreachable"));
+        self.block = block;
+        self.trap = Some(trap);
+        trap
+    }
+
+    fn terminator(&mut self, terminator: &Terminator<'tcx>) -> Result<(), CodegenError> {
+        self.locate(terminator.source_info.span);
+        let block = |this: &Self, block: BasicBlock| this.blocks[block.as_usize()];
+        match &terminator.kind {
+            TerminatorKind::Goto { target } => {
+                let target = block(self, *target);
+                self.branch("llvm.br", &[], &[target]);
+            }
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let value = self.scalar_operand(discr)?;
+                let r#type = value.r#type(self.module.ctx);
+                let width = r#type.int_or_float_width(self.module.ctx).unwrap_or_default();
+                let mut successors = vec![block(self, targets.otherwise())];
+                let mut cases = vec![];
+                for (case, target) in targets.iter() {
+                    cases.push(self.ctx().integer_attr(sign_extend(case, width), r#type));
+                    successors.push(block(self, target));
+                }
+                let vector = self.ctx().vector_type(&[cases.len() as i64], r#type);
+                let case_values = self.ctx().dense_elements_attr(vector, &cases);
+                let segments = self.i32_array(&[1, 0, 0]);
+                let case_segments = self.i32_array(&vec![0; cases.len()]);
+                let mut state = OperationState::new("llvm.switch");
+                state
+                    .add_operands([value])
+                    .add_successors(successors)
+                    .add_attribute("case_values", case_values)
+                    .add_attribute("case_operand_segments", case_segments)
+                    .add_attribute("operandSegmentSizes", segments);
+                self.insert(state);
+            }
+            TerminatorKind::Return => {
+                let result = self.slots[0];
+                let operands = match result {
+                    Some((slot, r#type)) => vec![self.value("llvm.load", &[slot], r#type)],
+                    None => vec![],
+                };
+                let mut state = OperationState::new("llvm.return");
+                state.add_operands(operands);
+                self.insert(state);
+            }
+            TerminatorKind::Unreachable | TerminatorKind::UnwindResume | TerminatorKind::UnwindTerminate(_) => {
+                self.insert(OperationState::new("llvm.unreachable"));
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+                ..
+            } => {
+                self.call(func, args.iter().map(|arg| &arg.node), destination)?;
+                match target {
+                    Some(target) => {
+                        let target = block(self, *target);
+                        self.branch("llvm.br", &[], &[target]);
+                    }
+                    None => {
+                        self.insert(OperationState::new("llvm.unreachable"));
+                    }
+                }
+            }
+            TerminatorKind::Assert {
+                cond, expected, target, ..
+            } => {
+                let condition = self.scalar_operand(cond)?;
+                let target = block(self, *target);
+                let trap = self.trap();
+                let successors = match expected {
+                    true => [target, trap],
+                    false => [trap, target],
+                };
+                self.branch("llvm.cond_br", &[condition], &successors);
+            }
+            TerminatorKind::Drop { place, target, .. } => {
+                let ty = self.monomorphize(place.ty(self.mir, self.tcx()).ty);
+                if ty.needs_drop(self.tcx(), TypingEnv::fully_monomorphized()) {
+                    return Err(CodegenError::unsupported(format!("the drop glue of `{ty}`")));
+                }
+                let target = block(self, *target);
+                self.branch("llvm.br", &[], &[target]);
+            }
+            kind => return Err(CodegenError::unsupported(format!("the `{}` terminator", kind.name()))),
+        }
+        Ok(())
+    }
+
+    /// Calls the function resolved out of the `FnDef` operand, storing the result to the destination.
+    fn call<'a>(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: impl Iterator<Item = &'a Operand<'tcx>>,
+        destination: &Place<'tcx>,
+    ) -> Result<(), CodegenError>
+    where
+        'tcx: 'a,
+    {
+        let tcx = self.tcx();
+        let func_ty = self.operand_ty(func);
+        let ty::FnDef(def_id, generic_args) = *func_ty.kind() else {
+            return Err(CodegenError::unsupported("the indirect calls"));
+        };
+        let instance = Instance::expect_resolve(tcx, TypingEnv::fully_monomorphized(), def_id, generic_args, self.span)
+            .polymorphize(tcx);
+        match instance.def {
+            InstanceKind::Intrinsic(_) => {
+                return Err(CodegenError::unsupported(format!(
+                    "the `{}` intrinsic",
+                    tcx.item_name(def_id)
+                )))
+            }
+            InstanceKind::Virtual(..) => return Err(CodegenError::unsupported("the virtual calls")),
+            _ if instance.def.requires_caller_location(tcx) => {
+                return Err(CodegenError::unsupported("the `#[track_caller]` functions"))
+            }
+            _ => {}
+        }
+        let sig = tcx.instantiate_bound_regions_with_erased(func_ty.fn_sig(tcx));
+        if sig.c_variadic {
+            return Err(CodegenError::unsupported("the variadic calls"));
+        }
+        let signature = self.module.signature(sig.inputs(), sig.output())?;
+        let symbol = tcx.symbol_name(instance).name;
+        self.module.declare(symbol, &signature, self.location);
+        let mut operands = vec![];
+        for arg in args {
+            operands.extend(self.operand(arg)?);
+        }
+        let mut state = OperationState::new("llvm.call");
+        let callee = self.ctx().symbol_ref_attr(symbol);
+        state
+            .add_operands(operands)
+            .add_attribute("callee", callee)
+            .add_types(signature.result);
+        let call = self.insert(state);
+        if let (Some((slot, _)), Some(result)) = (self.place(destination)?, call.results(self.module.ctx).first()) {
+            let result = *result;
+            self.store(result, slot);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,498 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Translation of the `llvm` dialect modules to the textual LLVM IR, parsed back by the LLVM of the toolchain.
+//! The constants are folded into their uses, the values and the blocks get the `%vN` and the `%bbN` names.
+//!
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::backend::CodegenError;
+use crate::ir::{Attribute, AttributeKind, Block, Context, Operation, Type, TypeKind, Value};
+
+/// Translates the module of the `llvm.func`s for the target of the data layout and the triple.
+pub(crate) fn translate_module(
+    ctx: &Context,
+    module: Operation,
+    data_layout: &str,
+    triple: &str,
+) -> Result<String, CodegenError> {
+    let mut translator = Translator {
+        ctx,
+        out: String::new(),
+        intrinsics: BTreeSet::new(),
+    };
+    writeln!(translator.out, "target datalayout = \"{data_layout}\"").unwrap();
+    writeln!(translator.out, "target triple = \"{triple}\"").unwrap();
+    let functions: Vec<Operation> = module
+        .region(ctx, 0)
+        .blocks(ctx)
+        .iter()
+        .flat_map(|block| block.operations(ctx).to_vec())
+        .collect();
+    let mut signatures = HashMap::new();
+    for function in &functions {
+        if function.name(ctx) != "llvm.func" {
+            return Err(translation_error(
+                ctx,
+                *function,
+                "only the `llvm.func`s are translated",
+            ));
+        }
+        let signature = FunctionSignature::of(ctx, *function)?;
+        signatures.insert(signature.symbol.clone(), signature);
+    }
+    for function in functions {
+        let symbol = function
+            .attribute(ctx, "sym_name")
+            .and_then(|symbol| symbol.as_string(ctx))
+            .unwrap_or_default();
+        translator.function(function, &signatures[symbol], &signatures)?;
+    }
+    for intrinsic in std::mem::take(&mut translator.intrinsics) {
+        writeln!(translator.out, "\n{intrinsic}").unwrap();
+    }
+    Ok(translator.out)
+}
+
+fn translation_error(ctx: &Context, op: Operation, reason: impl Into<String>) -> CodegenError {
+    CodegenError::Translation {
+        op: op.name(ctx).to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// `"symbol"` quoted LLVM IR name.
+fn quote(symbol: &str) -> String {
+    let mut quoted = String::from("\"");
+    for byte in symbol.bytes() {
+        match byte {
+            b'"' | b'\\' | 0..=0x1f | 0x7f.. => write!(quoted, "\\{byte:02X}").unwrap(),
+            _ => quoted.push(byte as char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// `ptr`, `i32` like types, the `!llvm.func<...>` nested ones are kept as they are printed.
+fn type_text(ctx: &Context, op: Operation, r#type: Type) -> Result<String, CodegenError> {
+    match r#type.kind(ctx) {
+        TypeKind::Integer { width, .. } => Ok(format!("i{width}")),
+        TypeKind::Opaque { dialect, data } if dialect == "llvm" && data == "ptr" => Ok(data.clone()),
+        kind => Err(translation_error(
+            ctx,
+            op,
+            format!("the {kind:?} type has no LLVM IR counterpart"),
+        )),
+    }
+}
+
+/// `zeroext` like parameter attributes of the `{llvm.zeroext}` dictionary ones.
+fn parameter_attributes(ctx: &Context, attributes: Option<Attribute>) -> String {
+    let Some(AttributeKind::Dictionary(attributes)) = attributes.map(|attributes| attributes.kind(ctx)) else {
+        return String::new();
+    };
+    attributes
+        .iter()
+        .filter_map(|attribute| attribute.name.strip_prefix("llvm."))
+        .fold(String::new(), |mut text, name| {
+            write!(text, "{name} ").unwrap();
+            text
+        })
+}
+
+struct FunctionSignature {
+    symbol: String,
+    result: String,
+    result_attributes: String,
+    /// Types of the parameters along with their attributes.
+    parameters: Vec<(String, String)>,
+}
+
+impl FunctionSignature {
+    fn of(ctx: &Context, function: Operation) -> Result<Self, CodegenError> {
+        let error = |reason: &str| translation_error(ctx, function, reason);
+        let symbol = function
+            .attribute(ctx, "sym_name")
+            .and_then(|symbol| symbol.as_string(ctx))
+            .ok_or_else(|| error("the function has no `sym_name`"))?;
+        let function_type = function
+            .attribute(ctx, "function_type")
+            .and_then(|function_type| function_type.as_type(ctx))
+            .map(|function_type| function_type.kind(ctx));
+        let Some(TypeKind::Opaque { dialect, data }) = function_type else {
+            return Err(error("the `function_type` isn't an `!llvm.func`"));
+        };
+        let (result, parameters) = data
+            .strip_prefix("func<")
+            .and_then(|data| data.strip_suffix(")>"))
+            .and_then(|data| data.split_once(" ("))
+            .filter(|_| dialect == "llvm")
+            .ok_or_else(|| error("the `function_type` isn't an `!llvm.func`"))?;
+        let parameters: Vec<&str> = parameters
+            .split(", ")
+            .filter(|parameter| !parameter.is_empty())
+            .collect();
+        if parameters.contains(&"...") {
+            return Err(error("the variadic functions aren't translated"));
+        }
+        let attributes = |name: &str, index: usize| {
+            let attributes = function.attribute(ctx, name).and_then(|attributes| {
+                attributes
+                    .as_array(ctx)
+                    .and_then(|attributes| attributes.get(index).copied())
+            });
+            parameter_attributes(ctx, attributes)
+        };
+        Ok(Self {
+            symbol: symbol.to_string(),
+            result: result.to_string(),
+            result_attributes: attributes("res_attrs", 0),
+            parameters: parameters
+                .iter()
+                .enumerate()
+                .map(|(index, parameter)| (parameter.to_string(), attributes("arg_attrs", index)))
+                .collect(),
+        })
+    }
+}
+
+struct Translator<'c> {
+    ctx: &'c Context,
+    out: String,
+    /// Declarations of the intrinsics called by the `llvm.intr.*` operations.
+    intrinsics: BTreeSet<String>,
+}
+
+/// Names of the values and of the blocks of the translated function.
+#[derive(Default)]
+struct Names {
+    values: HashMap<Value, String>,
+    blocks: HashMap<Block, String>,
+}
+
+impl Names {
+    fn value(&self, ctx: &Context, op: Operation, value: Value) -> Result<String, CodegenError> {
+        let name = self
+            .values
+            .get(&value)
+            .ok_or_else(|| translation_error(ctx, op, "the operand is defined after its use"))?;
+        Ok(name.clone())
+    }
+
+    /// `i32 %v1` like typed operand.
+    fn typed(&self, ctx: &Context, op: Operation, value: Value) -> Result<String, CodegenError> {
+        Ok(format!(
+            "{} {}",
+            type_text(ctx, op, value.r#type(ctx))?,
+            self.value(ctx, op, value)?
+        ))
+    }
+}
+
+impl Translator<'_> {
+    fn function(
+        &mut self,
+        function: Operation,
+        signature: &FunctionSignature,
+        signatures: &HashMap<String, FunctionSignature>,
+    ) -> Result<(), CodegenError> {
+        let ctx = self.ctx;
+        let blocks = function.region(ctx, 0).blocks(ctx);
+        let linkage = match function.attribute(ctx, "linkage").map(|linkage| linkage.kind(ctx)) {
+            Some(AttributeKind::Opaque { dialect, data }) if dialect == "llvm" => data
+                .strip_prefix("linkage<")
+                .and_then(|data| data.strip_suffix('>'))
+                .ok_or_else(|| translation_error(ctx, function, "the `linkage` isn't a `#llvm.linkage`"))?,
+            _ => "external",
+        };
+        let visibility = match function
+            .attribute(ctx, "visibility_")
+            .and_then(|visibility| visibility.as_integer(ctx))
+        {
+            Some(1) => "hidden ",
+            Some(2) => "protected ",
+            _ => "",
+        };
+        let linkage = match linkage {
+            "external" => String::new(),
+            linkage => format!("{linkage} "),
+        };
+        let mut names = Names::default();
+        let mut parameters = vec![];
+        let arguments = blocks
+            .first()
+            .map(|entry| entry.arguments(ctx).to_vec())
+            .unwrap_or_default();
+        for (index, (r#type, attributes)) in signature.parameters.iter().enumerate() {
+            match arguments.get(index) {
+                Some(argument) => {
+                    let name = format!("%a{index}");
+                    parameters.push(format!("{type} {attributes}{name}", type = r#type));
+                    names.values.insert(*argument, name);
+                }
+                None => parameters.push(format!("{type} {attributes}", type = r#type).trim_end().to_string()),
+            }
+        }
+        let header = format!(
+            "{linkage}{visibility}{}{} @{}({})",
+            signature.result_attributes,
+            signature.result,
+            quote(&signature.symbol),
+            parameters.join(", ")
+        );
+        if blocks.is_empty() {
+            writeln!(self.out, "\ndeclare {header}").unwrap();
+            return Ok(());
+        }
+        writeln!(self.out, "\ndefine {header} {{").unwrap();
+        for (index, block) in blocks.iter().enumerate() {
+            names.blocks.insert(*block, format!("%bb{index}"));
+        }
+        let mut next_value = 0;
+        for (index, block) in blocks.iter().enumerate() {
+            if index > 0 && !block.arguments(ctx).is_empty() {
+                return Err(translation_error(
+                    ctx,
+                    function,
+                    "the block arguments aren't translated",
+                ));
+            }
+            writeln!(self.out, "bb{index}:").unwrap();
+            for op in block.operations(ctx) {
+                if let Some(result) = op.results(ctx).first() {
+                    names.values.insert(*result, format!("%v{next_value}"));
+                    next_value += 1;
+                }
+                self.operation(*op, &mut names, signatures)?;
+            }
+        }
+        writeln!(self.out, "}}").unwrap();
+        Ok(())
+    }
+
+    fn operation(
+        &mut self,
+        op: Operation,
+        names: &mut Names,
+        signatures: &HashMap<String, FunctionSignature>,
+    ) -> Result<(), CodegenError> {
+        let ctx = self.ctx;
+        let name = op.name(ctx);
+        let operands = op.operands(ctx);
+        let result = op.results(ctx).first().copied();
+        let typed = |index: usize| names.typed(ctx, op, operands[index]);
+        let plain = |index: usize| names.value(ctx, op, operands[index]);
+        let block = |index: usize| names.blocks[&op.successors(ctx)[index]].clone();
+        let result_type = || type_text(ctx, op, result.expect("the operation has a result").r#type(ctx));
+        let attribute = |name: &str| {
+            op.attribute(ctx, name)
+                .ok_or_else(|| translation_error(ctx, op, format!("the `{name}` attribute is missing")))
+        };
+        let instruction = match name {
+            "llvm.mlir.constant" => {
+                let value = attribute("value")?
+                    .as_integer(ctx)
+                    .ok_or_else(|| translation_error(ctx, op, "only the integer constants are translated"))?;
+                let value = match result_type()?.as_str() {
+                    "i1" => (value & 1 == 1).to_string(),
+                    _ => value.to_string(),
+                };
+                // The constants are folded into their uses.
+                names.values.insert(result.expect("the constant has a result"), value);
+                return Ok(());
+            }
+            "llvm.alloca" => {
+                let elem_type = attribute("elem_type")?
+                    .as_type(ctx)
+                    .ok_or_else(|| translation_error(ctx, op, "the `elem_type` isn't a type"))?;
+                format!("alloca {}, {}", type_text(ctx, op, elem_type)?, typed(0)?)
+            }
+            "llvm.load" => format!("load {}, {}", result_type()?, typed(0)?),
+            "llvm.store" => format!("store {}, {}", typed(0)?, typed(1)?),
+            "llvm.add" | "llvm.sub" | "llvm.mul" | "llvm.sdiv" | "llvm.udiv" | "llvm.srem" | "llvm.urem"
+            | "llvm.and" | "llvm.or" | "llvm.xor" | "llvm.shl" | "llvm.lshr" | "llvm.ashr" => {
+                format!("{} {}, {}", &name["llvm.".len()..], typed(0)?, plain(1)?)
+            }
+            "llvm.icmp" => {
+                let predicate = match attribute("predicate")?.as_integer(ctx) {
+                    Some(0) => "eq",
+                    Some(1) => "ne",
+                    Some(2) => "slt",
+                    Some(3) => "sle",
+                    Some(4) => "sgt",
+                    Some(5) => "sge",
+                    Some(6) => "ult",
+                    Some(7) => "ule",
+                    Some(8) => "ugt",
+                    Some(9) => "uge",
+                    _ => return Err(translation_error(ctx, op, "the `predicate` isn't an `icmp` one")),
+                };
+                format!("icmp {predicate} {}, {}", typed(0)?, plain(1)?)
+            }
+            "llvm.zext" | "llvm.sext" | "llvm.trunc" => {
+                format!("{} {} to {}", &name["llvm.".len()..], typed(0)?, result_type()?)
+            }
+            "llvm.br" => format!("br label {}", block(0)),
+            "llvm.cond_br" => format!("br {}, label {}, label {}", typed(0)?, block(0), block(1)),
+            "llvm.switch" => {
+                let cases = attribute("case_values")?;
+                let AttributeKind::DenseElements { elements, .. } = cases.kind(ctx) else {
+                    return Err(translation_error(ctx, op, "the `case_values` aren't dense elements"));
+                };
+                let cases = op.successors(ctx).len() - 1;
+                let r#type = type_text(ctx, op, operands[0].r#type(ctx))?;
+                let mut instruction = format!("switch {}, label {} [", typed(0)?, block(0));
+                for index in 0..cases {
+                    // A single element stands for the splat of it.
+                    let value = elements[index.min(elements.len() - 1)]
+                        .as_integer(ctx)
+                        .ok_or_else(|| translation_error(ctx, op, "the `case_values` aren't integers"))?;
+                    let value = match r#type.as_str() {
+                        "i1" => (value & 1 == 1).to_string(),
+                        _ => value.to_string(),
+                    };
+                    write!(instruction, "\n    {type} {value}, label {}", block(index + 1), type = r#type).unwrap();
+                }
+                instruction + "\n  ]"
+            }
+            "llvm.return" => match operands.is_empty() {
+                true => "ret void".to_string(),
+                false => format!("ret {}", typed(0)?),
+            },
+            "llvm.unreachable" => "unreachable".to_string(),
+            "llvm.call" => {
+                let callee = attribute("callee")?;
+                let AttributeKind::SymbolRef { root, nested } = callee.kind(ctx) else {
+                    return Err(translation_error(ctx, op, "the indirect calls aren't translated"));
+                };
+                let signature = signatures
+                    .get(root)
+                    .filter(|_| nested.is_empty())
+                    .ok_or_else(|| translation_error(ctx, op, format!("the `@{root}` callee isn't defined")))?;
+                let mut arguments = vec![];
+                for (index, (r#type, attributes)) in signature.parameters.iter().enumerate() {
+                    arguments.push(format!("{type} {attributes}{}", plain(index)?, type = r#type));
+                }
+                format!(
+                    "call {}{} @{}({})",
+                    signature.result_attributes,
+                    signature.result,
+                    quote(root),
+                    arguments.join(", ")
+                )
+            }
+            name if name.starts_with("llvm.intr.") => {
+                let intrinsic = format!("@llvm.{}", &name["llvm.intr.".len()..]);
+                let result = match result {
+                    Some(result) => type_text(ctx, op, result.r#type(ctx))?,
+                    None => "void".to_string(),
+                };
+                let mut types = vec![];
+                let mut arguments = vec![];
+                for operand in operands {
+                    types.push(type_text(ctx, op, operand.r#type(ctx))?);
+                    arguments.push(names.typed(ctx, op, *operand)?);
+                }
+                self.intrinsics
+                    .insert(format!("declare {result} {intrinsic}({})", types.join(", ")));
+                format!("call {result} {intrinsic}({})", arguments.join(", "))
+            }
+            _ => return Err(translation_error(ctx, op, "the operation has no LLVM IR translation")),
+        };
+        match result {
+            Some(result) => writeln!(self.out, "  {} = {instruction}", names.value(ctx, op, result)?).unwrap(),
+            None => writeln!(self.out, "  {instruction}").unwrap(),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse;
+
+    const SOURCE: &str = r#""builtin.module"() ({
+  "llvm.func"() ({
+  ^bb0(%arg0: i32, %arg1: i1):
+    %0 = "llvm.mlir.constant"() {value = 1 : i64} : () -> i64
+    %1 = "llvm.alloca"(%0) {elem_type = i32} : (i64) -> !llvm.ptr
+    "llvm.store"(%arg0, %1) : (i32, !llvm.ptr) -> ()
+    "llvm.cond_br"(%arg1)[^bb1, ^bb2] {operandSegmentSizes = array<i32: 1, 0, 0>} : (i1) -> ()
+  ^bb1:
+    %2 = "llvm.load"(%1) : (!llvm.ptr) -> i32
+    %3 = "llvm.mlir.constant"() {value = -3 : i32} : () -> i32
+    %4 = "llvm.call"(%2) {callee = @"other"} : (i32) -> i32
+    %5 = "llvm.sdiv"(%4, %3) : (i32, i32) -> i32
+    "llvm.return"(%5) : (i32) -> ()
+  ^bb2:
+    "llvm.intr.trap"() : () -> ()
+    "llvm.unreachable"() : () -> ()
+  }) {sym_name = "checked", function_type = !llvm.func<i32 (i32, i1)>, linkage = #llvm.linkage<internal>,
+      arg_attrs = [{}, {llvm.zeroext}], visibility_ = 1 : i64} : () -> ()
+  "llvm.func"() ({
+  }) {sym_name = "other", function_type = !llvm.func<i32 (i32)>} : () -> ()
+}) : () -> ()
+"#;
+
+    #[test]
+    fn should_translate_the_llvm_functions() {
+        let mut ctx = Context::new();
+        let module = parse(&mut ctx, SOURCE).unwrap();
+        let ir = translate_module(&ctx, module, "e-m:e", "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(
+            ir,
+            r#"target datalayout = "e-m:e"
+target triple = "x86_64-unknown-linux-gnu"
+
+define internal hidden i32 @"checked"(i32 %a0, i1 zeroext %a1) {
+bb0:
+  %v1 = alloca i32, i64 1
+  store i32 %a0, ptr %v1
+  br i1 %a1, label %bb1, label %bb2
+bb1:
+  %v2 = load i32, ptr %v1
+  %v4 = call i32 @"other"(i32 %v2)
+  %v5 = sdiv i32 %v4, -3
+  ret i32 %v5
+bb2:
+  call void @llvm.trap()
+  unreachable
+}
+
+declare i32 @"other"(i32)
+
+declare void @llvm.trap()
+"#
+        );
+    }
+
+    #[test]
+    fn should_reject_the_operations_without_translation() {
+        let mut ctx = Context::new();
+        let source = SOURCE.replace("llvm.sdiv", "llvm.fdiv");
+        let module = parse(&mut ctx, &source).unwrap();
+        let error = translate_module(&ctx, module, "", "").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`llvm.fdiv` can't be translated to LLVM IR: the operation has no LLVM IR translation"
+        );
+    }
+}
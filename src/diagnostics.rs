@@ -0,0 +1,258 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Locations of the lowered operations derived from the rustc [Span]s, and the core IR [Diagnostic]s reported
+//! back through the rustc [DiagCtxtHandle], pointing at the Rust sources the failing operations come from.
+//!
+
+use rustc_errors::{Diag, DiagCtxtHandle, Level};
+use rustc_span::source_map::SourceMap;
+use rustc_span::{BytePos, Pos, Span};
+
+use crate::ir::{Attribute, Context, Diagnostic, Location, Severity};
+
+/// Maps the spans of the source map to the location attributes and back.
+pub struct SourceLocations<'s> {
+    source_map: &'s SourceMap,
+}
+
+impl<'s> SourceLocations<'s> {
+    pub fn new(source_map: &'s SourceMap) -> Self {
+        Self { source_map }
+    }
+
+    /// `loc("file":line:column)` of the span start, the macro expanded spans are fused with the expansion site
+    /// in the user code going first.
+    pub fn location(&self, ctx: &mut Context, span: Span) -> Attribute {
+        if span.is_dummy() {
+            return ctx.unknown_loc();
+        }
+        let location = self.file_line_col(ctx, span);
+        match span.from_expansion() {
+            true => {
+                let expansion = self.file_line_col(ctx, span.source_callsite());
+                ctx.fused_loc(&[expansion, location], None)
+            }
+            false => location,
+        }
+    }
+
+    /// `loc(callsite(callee at caller))` of the span inlined through the call sites, the innermost one first,
+    /// like the upstream inliner.
+    pub fn inlined_location(&self, ctx: &mut Context, span: Span, call_sites: &[Span]) -> Attribute {
+        let mut location = self.location(ctx, span);
+        for call_site in call_sites {
+            let caller = self.location(ctx, *call_site);
+            location = ctx.call_site_loc(location, caller);
+        }
+        location
+    }
+
+    /// `loc("file":line:column)` of the span start, the columns are 1-based like the rustc ones.
+    fn file_line_col(&self, ctx: &mut Context, span: Span) -> Attribute {
+        let position = self.source_map.lookup_char_pos(span.lo());
+        let filename = self.source_map.filename_for_diagnostics(&position.file.name).to_string();
+        ctx.file_line_col_loc(&filename, position.line as u32, position.col.0 as u32 + 1)
+    }
+
+    /// Span of the character at the file location, `None` for the locations out of the source map files.
+    pub fn span(&self, location: &Location) -> Option<Span> {
+        let (filename, line, column) = location.file_line_col()?;
+        let files = self.source_map.files();
+        let file = files
+            .iter()
+            .find(|file| self.source_map.filename_for_diagnostics(&file.name).to_string() == filename)?;
+        let line = usize::try_from(line).ok()?.checked_sub(1)?;
+        if line >= file.count_lines() {
+            return None;
+        }
+        let bounds = file.line_bounds(line);
+        let offset = match &file.src {
+            Some(src) => {
+                let start = (bounds.start - file.start_pos).to_usize();
+                let text = &src[start..(bounds.end - file.start_pos).to_usize()];
+                text.char_indices()
+                    .nth(column.saturating_sub(1) as usize)
+                    .map_or(text.len(), |(offset, _)| offset)
+            }
+            None => column.saturating_sub(1) as usize,
+        };
+        let start = BytePos(bounds.start.0 + offset as u32).min(bounds.end);
+        Some(self.source_map.next_point(Span::with_root_ctxt(start, start)))
+    }
+
+    /// Reports the diagnostic on the span of its location, noting the call sites it was inlined through.
+    pub fn emit(&self, dcx: DiagCtxtHandle<'_>, diagnostic: &Diagnostic) {
+        let mut diag = Diag::<()>::new(dcx, level(diagnostic.severity), diagnostic.message.clone());
+        if let Some(span) = self.span(&diagnostic.location) {
+            diag.span(span);
+        }
+        self.note_call_sites(&mut diag, &diagnostic.location);
+        for note in &diagnostic.notes {
+            match self.span(&note.location) {
+                Some(span) => diag.span_note(span, note.message.clone()),
+                None => diag.note(note.message.clone()),
+            };
+        }
+        diag.emit();
+    }
+
+    fn note_call_sites(&self, diag: &mut Diag<'_, ()>, location: &Location) {
+        match location {
+            Location::CallSite { callee, caller } => {
+                self.note_call_sites(diag, callee);
+                if let Some(span) = self.span(caller) {
+                    diag.span_note(span, "inlined from here");
+                }
+                self.note_call_sites(diag, caller);
+            }
+            Location::Name { child: Some(child), .. } => self.note_call_sites(diag, child),
+            Location::Fused { locations, .. } => {
+                if let Some(location) = locations.iter().find(|location| location.file_line_col().is_some()) {
+                    self.note_call_sites(diag, location);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn level(severity: Severity) -> Level {
+    match severity {
+        Severity::Error => Level::Error,
+        Severity::Warning => Level::Warning,
+        Severity::Note | Severity::Remark => Level::Note,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate termcolor;
+
+    use std::io;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use rustc_errors::emitter::HumanEmitter;
+    use rustc_errors::{fallback_fluent_bundle, DiagCtxt};
+    use rustc_span::source_map::FilePathMapping;
+    use rustc_span::{create_default_session_globals_then, FileName, RealFileName};
+
+    use super::*;
+    use crate::ir::{PassError, Printer, PrinterOptions};
+
+    const SOURCE: &str = "fn main() {\n    let x = add(1, 2);\n}\n\nfn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n";
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Span of the first occurrence of the text in the `SOURCE`.
+    fn span(source_map: &SourceMap, text: &str) -> Span {
+        let file = source_map.files()[0].clone();
+        let start = file.start_pos + BytePos(SOURCE.find(text).unwrap() as u32);
+        Span::with_root_ctxt(start, start + BytePos(text.len() as u32))
+    }
+
+    /// The rustc `Lrc` of the emitter, `SourceMap` isn't `Sync` outside of the parallel compiler.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn source_map() -> Arc<SourceMap> {
+        let source_map = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        let name = FileName::Real(RealFileName::LocalPath(PathBuf::from("src/main.rs")));
+        source_map.new_source_file(name, SOURCE.to_string());
+        source_map
+    }
+
+    fn loc(ctx: &Context, location: Attribute) -> String {
+        let mut printer = Printer::new(ctx, PrinterOptions::default());
+        printer.print_location(location);
+        printer.finish()
+    }
+
+    #[test]
+    fn should_map_spans_to_locations_and_back() {
+        create_default_session_globals_then(|| {
+            let source_map = source_map();
+            let locations = SourceLocations::new(&source_map);
+            let mut ctx = Context::new();
+            let addition = span(&source_map, "a + b");
+            let call = span(&source_map, "add(1, 2)");
+            let location = locations.location(&mut ctx, addition);
+            assert_eq!(loc(&ctx, location), r#"loc("src/main.rs":6:5)"#);
+            let inlined = locations.inlined_location(&mut ctx, addition, &[call]);
+            assert_eq!(
+                loc(&ctx, inlined),
+                r#"loc(callsite("src/main.rs":6:5 at "src/main.rs":2:13))"#
+            );
+            let span = locations.span(&Location::new(&ctx, inlined)).unwrap();
+            assert_eq!(source_map.span_to_snippet(span).unwrap(), "a");
+        });
+    }
+
+    #[test]
+    fn should_report_pass_failures_on_the_rust_sources() {
+        create_default_session_globals_then(|| {
+            let source_map = source_map();
+            let locations = SourceLocations::new(&source_map);
+            let mut ctx = Context::new();
+            let addition = span(&source_map, "a + b");
+            let call = span(&source_map, "add(1, 2)");
+            let location = locations.inlined_location(&mut ctx, addition, &[call]);
+            let error = PassError::Failed {
+                pass: "convert-vector-to-llvm".to_string(),
+                op: "builtin.module".to_string(),
+                location: Location::new(&ctx, location),
+                message: "failed to legalize operation 'vector.contract'".to_string(),
+            };
+            let output = Output::default();
+            let emitter = HumanEmitter::new(
+                Box::new(termcolor::NoColor::new(output.clone())),
+                fallback_fluent_bundle(vec![], false),
+            )
+            .sm(Some(source_map.clone()));
+            let dcx = DiagCtxt::new(Box::new(emitter));
+            locations.emit(dcx.handle(), &Diagnostic::from(&error));
+            let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+            assert_eq!(
+                output,
+                "error: failed to legalize operation 'vector.contract'
+ --> src/main.rs:6:5
+  |
+6 |     a + b
+  |     ^
+  |
+note: inlined from here
+ --> src/main.rs:2:13
+  |
+2 |     let x = add(1, 2);
+  |             ^
+  = note: while running the `convert-vector-to-llvm` pass on `builtin.module`
+
+"
+            );
+        });
+    }
+}
@@ -22,7 +22,33 @@
 //! It even targets hardware description languages with [circt](https://github.com/llvm/circt) project.
 //!
 
-#![feature(const_option)]
+#![feature(rustc_private)]
 
-mod targets;
+extern crate rustc_codegen_ssa;
+extern crate rustc_data_structures;
+extern crate rustc_driver;
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_metadata;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+extern crate rustc_target;
 
+pub mod backend;
+pub mod diagnostics;
+
+/// MLIR core IR the backend lowers to and the `targets` dialects are built on.
+pub use fljuga_handahofi_mlir_ir as ir;
+/// Dialects the backend lowers to, the dialect crates are enabled by the features.
+pub use fljuga_handahofi_targets as targets;
+
+use rustc_codegen_ssa::traits::CodegenBackend;
+
+/// Entry point of the backend loaded by `-Zcodegen-backend=librustc_codegen_mlir.so`.
+// The driver looks the backend up by the unmangled symbol, the only exception of the `unsafe_code` lint.
+#[allow(unsafe_code)]
+#[no_mangle]
+pub fn __rustc_codegen_backend() -> Box<dyn CodegenBackend> {
+    Box::new(backend::MlirCodegenBackend)
+}
@@ -0,0 +1,219 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Crates compiled by the pinned toolchain with `-Zcodegen-backend` pointed at this backend,
+//! the `tests/fixtures` ones are linked with their C drivers and run, the `tests/fixtures/func` ones are lowered
+//! into the `func` dialect and compared against their `tests/testdata` snapshots.
+//!
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::{env, fs};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// `librustc_codegen_mlir.so` the test executable is linked with.
+fn backend() -> PathBuf {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    deps.join(format!(
+        "{}rustc_codegen_mlir{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ))
+}
+
+/// Fresh output directory of the test.
+fn out_dir(test: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("backend").join(test);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn rustc(source: &Path, crate_type: &str, out_dir: &Path, args: &[&str]) -> Output {
+    Command::new("rustc")
+        .arg(format!("-Zcodegen-backend={}", backend().display()))
+        .args([
+            "--crate-type",
+            crate_type,
+            "-C",
+            "panic=abort",
+            "-C",
+            "overflow-checks=off",
+        ])
+        .args(args)
+        .arg("--out-dir")
+        .arg(out_dir)
+        .arg(source)
+        .output()
+        .unwrap()
+}
+
+/// `<crate>.mlir` of the `tests/fixtures/func` crate lowered with `-Cllvm-args=lowering=func`.
+fn lower_to_func(name: &str) -> String {
+    let out_dir = out_dir(&format!("func-{name}"));
+    let source = Path::new(FIXTURES).join("func").join(format!("{name}.rs"));
+    let output = rustc(
+        &source,
+        "rlib",
+        &out_dir,
+        &["-C", "codegen-units=1", "-C", "llvm-args=lowering=func"],
+    );
+    assert_success(&output);
+    fs::read_to_string(out_dir.join(format!("{name}.mlir"))).unwrap()
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "{}\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn should_link_the_integer_only_crate() {
+    let out_dir = out_dir("link");
+    let output = rustc(&Path::new(FIXTURES).join("integers.rs"), "staticlib", &out_dir, &[]);
+    assert_success(&output);
+
+    let executable = out_dir.join("integers");
+    let output = Command::new("cc")
+        .arg(Path::new(FIXTURES).join("integers.c"))
+        .arg(out_dir.join("libintegers.a"))
+        .arg("-o")
+        .arg(&executable)
+        .output()
+        .unwrap();
+    assert_success(&output);
+    let output = Command::new(&executable).output().unwrap();
+    assert_success(&output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "25\n12\n0 1 2 3\n1 0\n-28664\n"
+    );
+}
+
+#[test]
+fn should_emit_the_object_file() {
+    let out_dir = out_dir("object");
+    let output = rustc(
+        &Path::new(FIXTURES).join("integers.rs"),
+        "staticlib",
+        &out_dir,
+        &["-O", "--emit", "obj,llvm-ir"],
+    );
+    assert_success(&output);
+
+    let object = fs::read(out_dir.join("integers.o")).unwrap();
+    assert_eq!(&object[..4], b"\x7fELF");
+    for symbol in ["sum_of_squares", "gcd", "classify", "is_even", "mix"] {
+        assert!(object.windows(symbol.len()).any(|window| window == symbol.as_bytes()));
+    }
+    let ir = fs::read_to_string(out_dir.join("integers.ll")).unwrap();
+    assert!(ir.contains("define i32 @\"gcd\"(i32 %a0, i32 %a1) {"), "{ir}");
+    assert!(ir.contains("define zeroext i1 @\"is_even\"(i16 %a0) {"), "{ir}");
+    // Only the requested outputs are left.
+    let mut files: Vec<String> = fs::read_dir(&out_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["integers.ll", "integers.o"]);
+}
+
+#[test]
+fn should_report_the_unsupported_code_at_its_span() {
+    let out_dir = out_dir("unsupported");
+    let source = out_dir.join("tuples.rs");
+    fs::write(
+        &source,
+        "#![no_std]\n\
+         #![no_main]\n\
+         #[panic_handler]\n\
+         fn panic(_: &core::panic::PanicInfo) -> ! { loop {} }\n\
+         #[no_mangle]\n\
+         pub extern \"C\" fn swap(a: i32, b: i32) -> i32 { let pair = (b, a); pair.0 - pair.1 }\n",
+    )
+    .unwrap();
+    let output = rustc(&source, "staticlib", &out_dir, &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("error: the MLIR backend doesn't support the `(i32, i32)` values yet"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("tuples.rs:6:"), "{stderr}");
+}
+
+#[test]
+fn should_lower_the_arithmetic_into_func() {
+    goldie::assert!(lower_to_func("arithmetic"));
+}
+
+#[test]
+fn should_lower_the_branches_into_block_arguments() {
+    goldie::assert!(lower_to_func("branches"));
+}
+
+#[test]
+fn should_lower_the_calls_into_func_call() {
+    goldie::assert!(lower_to_func("calls"));
+}
+
+#[test]
+fn should_lower_the_address_taken_locals_into_memref_alloca() {
+    goldie::assert!(lower_to_func("references"));
+}
+
+#[test]
+fn should_reject_the_unknown_backend_option() {
+    let out_dir = out_dir("unknown-option");
+    let source = Path::new(FIXTURES).join("func").join("calls.rs");
+    let output = rustc(&source, "rlib", &out_dir, &["-C", "llvm-args=lowering=spirv"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("error: unknown MLIR backend lowering `spirv`, expected `lowering=llvm|func`"),
+        "{stderr}"
+    );
+
+    // The other options belong to LLVM.
+    let output = rustc(
+        &source,
+        "rlib",
+        &out_dir,
+        &["-C", "llvm-args=-x86-asm-syntax=intel", "-C", "llvm-args=lowering=func"],
+    );
+    assert_success(&output);
+}
+
+#[test]
+fn should_forward_the_llvm_options_to_llc() {
+    let out_dir = out_dir("llvm-options");
+    let output = rustc(
+        &Path::new(FIXTURES).join("integers.rs"),
+        "staticlib",
+        &out_dir,
+        &["--emit", "asm", "-C", "llvm-args=-x86-asm-syntax=intel"],
+    );
+    assert_success(&output);
+
+    let assembly = fs::read_to_string(out_dir.join("integers.s")).unwrap();
+    assert!(assembly.contains(".intel_syntax noprefix"), "{assembly}");
+}
@@ -13,8 +13,29 @@
    See the License for the specific language governing permissions and
    limitations under the License.
 */
-
-//! ## fljúga handahófi MLIR arithmetics dialect support.
 //!
-//! Arithmetics dialect hold basic mathematical operations.
+//! Integer arithmetic lowered into the `arith` dialect, the divisions are guarded by the `cf.assert`s.
 //!
+
+#![no_std]
+
+#[no_mangle]
+pub fn mix(a: i32, b: u8) -> i64 {
+    let wide = (a as i64) << (b & 7);
+    (wide ^ !(b as i64)) - -(a as i64) / 3
+}
+
+#[no_mangle]
+pub fn average(a: u32, b: u32) -> u32 {
+    (a & b) + ((a ^ b) >> 1)
+}
+
+#[no_mangle]
+pub fn remainder(a: i16, b: i16) -> i16 {
+    a % b
+}
+
+#[no_mangle]
+pub fn narrow(value: u64) -> bool {
+    value as u8 == 0
+}
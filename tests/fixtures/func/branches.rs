@@ -0,0 +1,51 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+//!
+//! Loops and conditions lowered into the `cf` blocks passing the mutated locals along as their arguments.
+//!
+
+#![no_std]
+
+#[no_mangle]
+pub fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
+#[no_mangle]
+pub fn classify(value: i8) -> u8 {
+    match value {
+        0 => 0,
+        1 | 2 => 1,
+        -1 => 2,
+        _ => 3,
+    }
+}
+
+#[no_mangle]
+pub fn clamp(value: i32, low: i32, high: i32) -> i32 {
+    if value < low {
+        low
+    } else if value > high {
+        high
+    } else {
+        value
+    }
+}
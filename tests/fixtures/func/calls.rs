@@ -0,0 +1,50 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+//!
+//! Direct calls lowered into the `func.call`s, of the private, the exported and the external functions.
+//!
+
+#![no_std]
+
+extern "C" {
+    fn external(value: u16) -> u16;
+}
+
+fn square(value: i64) -> i64 {
+    value * value
+}
+
+#[no_mangle]
+pub fn sum_of_squares(a: i64, b: i64) -> i64 {
+    square(a) + square(b)
+}
+
+#[no_mangle]
+pub fn twice(value: u16) -> u16 {
+    unsafe { external(external(value)) }
+}
+
+fn halt() -> ! {
+    loop {}
+}
+
+#[no_mangle]
+pub fn checked(value: u32) -> u32 {
+    if value > 100 {
+        halt()
+    }
+    value
+}
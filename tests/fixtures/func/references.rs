@@ -0,0 +1,48 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+//!
+//! References and pointers lowered into the `memref`s, the address-taken locals are the `memref.alloca`s.
+//!
+
+#![no_std]
+
+fn increment(counter: &mut u32) {
+    *counter += 1;
+}
+
+#[no_mangle]
+pub fn count(times: u32) -> u32 {
+    let mut counter = 0;
+    let mut index = 0;
+    while index < times {
+        increment(&mut counter);
+        index += 1;
+    }
+    counter
+}
+
+#[no_mangle]
+pub fn swap_sum(a: &mut i32, b: &mut i32) -> i32 {
+    let first = *a;
+    *a = *b;
+    *b = first;
+    *a + *b
+}
+
+#[no_mangle]
+pub fn read(pointer: *const u8) -> u8 {
+    unsafe { *pointer }
+}
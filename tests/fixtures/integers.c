@@ -0,0 +1,18 @@
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+
+int64_t sum_of_squares(int64_t a, int64_t b);
+uint32_t gcd(uint32_t a, uint32_t b);
+uint8_t classify(int32_t value);
+bool is_even(uint16_t value);
+int64_t mix(uint8_t value, uint32_t shift);
+
+int main(void) {
+    printf("%lld\n", (long long)sum_of_squares(3, -4));
+    printf("%u\n", gcd(84, 36));
+    printf("%u %u %u %u\n", classify(-7), classify(0), classify(5), classify(42));
+    printf("%d %d\n", is_even(10), is_even(7));
+    printf("%lld\n", (long long)mix(200, 9));
+    return 0;
+}
@@ -0,0 +1,68 @@
+/*
+   Copyright (C) 2022-2025 Yuriy Yarosh.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//!
+//! Integer-only `no_std` fixture crate compiled by the MLIR backend, called from `integers.c`.
+//!
+
+#![no_std]
+#![no_main]
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+fn square(value: i64) -> i64 {
+    value * value
+}
+
+#[no_mangle]
+pub extern "C" fn sum_of_squares(a: i64, b: i64) -> i64 {
+    square(a) + square(b)
+}
+
+#[no_mangle]
+pub extern "C" fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+#[no_mangle]
+pub extern "C" fn classify(value: i32) -> u8 {
+    match value {
+        i32::MIN..=-1 => 0,
+        0 => 1,
+        1..=9 => 2,
+        _ => 3,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn is_even(value: u16) -> bool {
+    value & 1 == 0
+}
+
+#[no_mangle]
+pub extern "C" fn mix(value: u8, shift: u32) -> i64 {
+    let widened = value as i64;
+    let narrowed = (widened << shift) as i16;
+    !(narrowed as i64) ^ -(shift as i64)
+}